@@ -1,4 +1,4 @@
-use std::{env, process::Command};
+use std::{env, fs, path::PathBuf, process::Command};
 use structopt::StructOpt;
 use strum::{EnumString, EnumVariantNames};
 
@@ -25,6 +25,17 @@ enum BuildCommand {
         #[structopt(long)]
         test_kind: TestKind,
     },
+
+    /// Generate an `elusiv_vkey.bin` from a snarkjs `verification_key.json`
+    Vkey {
+        /// The snarkjs verification key
+        #[structopt(long, parse(from_os_str))]
+        input: PathBuf,
+
+        /// The output file
+        #[structopt(long, parse(from_os_str))]
+        output: PathBuf,
+    },
 }
 
 #[derive(EnumString, EnumVariantNames, Debug)]
@@ -91,6 +102,10 @@ fn main() {
                 }
             }
         }
+        BuildCommand::Vkey { input, output } => {
+            generate_vkey(input, output);
+            return;
+        }
     }
 
     let current_dir = env::current_dir().expect("Unable to get current directory");
@@ -127,3 +142,25 @@ fn main() {
 
     std::process::exit(exit_code.code().unwrap_or(0));
 }
+
+fn generate_vkey(input: PathBuf, output: PathBuf) {
+    use elusiv::proof::{
+        convert::verifying_key_from_snarkjs_json,
+        vkey::{verifying_key_source, VerifyingKey},
+    };
+
+    let json = fs::read_to_string(&input).expect("Unable to read the verification key");
+    let vk = verifying_key_from_snarkjs_json(&json).expect("Invalid verification key");
+    let public_inputs_count = vk.gamma_abc_g1.len() - 1;
+
+    let source = verifying_key_source(&vk);
+    assert_eq!(source.len(), VerifyingKey::source_size(public_inputs_count));
+    VerifyingKey::new(&source, public_inputs_count).expect("Invalid verifying key source");
+
+    fs::write(&output, source).expect("Unable to write the verifying key");
+    println!(
+        "Generated verifying key with {} public inputs at {}",
+        public_inputs_count,
+        output.display()
+    );
+}
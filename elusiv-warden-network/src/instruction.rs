@@ -163,6 +163,14 @@ pub enum ElusivWardenNetworkInstruction {
     },
 
     // -------- Program state management --------
+    /// Grows the `overflow_members` of the `BasicWardenNetworkAccount`
+    #[acc(payer, { writable, signer })]
+    #[pda(basic_network, BasicWardenNetworkAccount, { writable, account_info })]
+    #[sys(system_program, key = system_program::ID)]
+    GrowBasicWardenNetwork {
+        len: u32,
+    },
+
     #[cfg(not(feature = "mainnet"))]
     #[acc(payer, { signer })]
     #[acc(recipient, { writable })]
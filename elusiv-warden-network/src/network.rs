@@ -1,7 +1,9 @@
 use crate::warden::{ElusivWardenID, Quote, WardenRegion};
 use crate::{error::ElusivWardenNetworkError, warden::BasicWardenFeatures};
+use borsh::{BorshDeserialize, BorshSerialize};
+use elusiv_derive::BorshSerDeSized;
 use elusiv_proc_macros::elusiv_account;
-use elusiv_types::{ElusivOption, PDAAccountData, TOKENS};
+use elusiv_types::{BorshSerDeSized, ElusivOption, PDAAccountData, TOKENS};
 use elusiv_utils::guard;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program_error::ProgramError;
@@ -37,6 +39,22 @@ macro_rules! warden_network {
 
 warden_network!(ElusivBasicWardenNetwork, NetworkSize::Dynamic(0, 512));
 
+/// A member of the [`BasicWardenNetworkAccount`] beyond [`ElusivBasicWardenNetwork`]'s initial size
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, Clone, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
+pub struct BasicWardenNetworkMember {
+    pub warden_id: ElusivWardenID,
+    pub features: BasicWardenFeatures,
+    pub tokens: [bool; TOKENS.len()],
+    pub region: WardenRegion,
+}
+
+/// Account storing the members of the basic warden network
+///
+/// # Notes
+///
+/// Members beyond [`ElusivBasicWardenNetwork`]'s initial size are stored in `overflow_members`,
+/// which can be grown with [`crate::instruction::ElusivWardenNetworkInstruction::GrowBasicWardenNetwork`].
 #[elusiv_account]
 pub struct BasicWardenNetworkAccount {
     #[no_getter]
@@ -48,9 +66,17 @@ pub struct BasicWardenNetworkAccount {
     features: [BasicWardenFeatures; ElusivBasicWardenNetwork::SIZE.max()],
     tokens: [[bool; TOKENS.len()]; ElusivBasicWardenNetwork::SIZE.max()],
     region: [WardenRegion; ElusivBasicWardenNetwork::SIZE.max()],
+
+    #[growable]
+    overflow_members: [BasicWardenNetworkMember; 0],
 }
 
 impl<'a> BasicWardenNetworkAccount<'a> {
+    /// The current capacity of the network
+    pub fn capacity(&self) -> usize {
+        ElusivBasicWardenNetwork::SIZE.max() + self.overflow_members_len()
+    }
+
     pub fn try_add_member(
         &mut self,
         warden_id: ElusivWardenID,
@@ -60,14 +86,27 @@ impl<'a> BasicWardenNetworkAccount<'a> {
     ) -> ProgramResult {
         let members_count = self.get_members_count();
         guard!(
-            (members_count as usize) < ElusivBasicWardenNetwork::SIZE.max(),
+            (members_count as usize) < self.capacity(),
             ElusivWardenNetworkError::WardenRegistrationError
         );
 
-        self.set_members(members_count as usize, &warden_id);
-        self.set_features(members_count as usize, features);
-        self.set_region(members_count as usize, region);
-        self.set_tokens(members_count as usize, supported_tokens);
+        let index = members_count as usize;
+        if index < ElusivBasicWardenNetwork::SIZE.max() {
+            self.set_members(index, &warden_id);
+            self.set_features(index, features);
+            self.set_region(index, region);
+            self.set_tokens(index, supported_tokens);
+        } else {
+            self.set_overflow_members(
+                index - ElusivBasicWardenNetwork::SIZE.max(),
+                &BasicWardenNetworkMember {
+                    warden_id,
+                    features: features.clone(),
+                    tokens: *supported_tokens,
+                    region: *region,
+                },
+            )?;
+        }
         self.set_members_count(&(members_count + 1));
 
         Ok(())
@@ -79,12 +118,26 @@ impl<'a> BasicWardenNetworkAccount<'a> {
         member_index: usize,
         region: &WardenRegion,
     ) -> ProgramResult {
-        guard!(
-            self.get_members(member_index) == warden_id,
-            ElusivWardenNetworkError::InvalidInstructionData
-        );
-
-        self.set_region(member_index, region);
+        if member_index < ElusivBasicWardenNetwork::SIZE.max() {
+            guard!(
+                self.get_members(member_index) == warden_id,
+                ElusivWardenNetworkError::InvalidInstructionData
+            );
+
+            self.set_region(member_index, region);
+        } else {
+            let index = member_index - ElusivBasicWardenNetwork::SIZE.max();
+            let mut member = self
+                .get_overflow_members(index)
+                .ok_or(ElusivWardenNetworkError::InvalidInstructionData)?;
+            guard!(
+                member.warden_id == warden_id,
+                ElusivWardenNetworkError::InvalidInstructionData
+            );
+
+            member.region = *region;
+            self.set_overflow_members(index, &member)?;
+        }
 
         Ok(())
     }
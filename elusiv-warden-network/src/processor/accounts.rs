@@ -1,10 +1,11 @@
+use crate::error::ElusivWardenNetworkError;
 use crate::{
     apa::ApaProposalsAccount,
     network::{ApaWardenNetworkAccount, BasicWardenNetworkAccount},
     warden::WardensAccount,
 };
 use elusiv_types::UnverifiedAccountInfo;
-use elusiv_utils::open_pda_account_without_offset;
+use elusiv_utils::{guard, open_pda_account_without_offset, pda_account, realloc_pda_account};
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult};
 
 pub fn init<'a, 'b>(
@@ -42,6 +43,42 @@ pub fn init<'a, 'b>(
     Ok(())
 }
 
+/// Grows the `overflow_members` of the [`BasicWardenNetworkAccount`] to `len` members
+///
+/// # Notes
+///
+/// - Growing the account by more than [`solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE`] bytes requires multiple calls.
+/// - The additional rent is funded by the `payer`.
+pub fn grow_basic_warden_network<'a>(
+    payer: &AccountInfo<'a>,
+    basic_network_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+
+    len: u32,
+) -> ProgramResult {
+    let previous_len = {
+        pda_account!(
+            basic_network,
+            BasicWardenNetworkAccount,
+            basic_network_account
+        );
+        basic_network.overflow_members_len()
+    };
+    guard!(
+        len as usize > previous_len,
+        ElusivWardenNetworkError::InvalidInstructionData
+    );
+
+    realloc_pda_account::<BasicWardenNetworkAccount>(
+        payer,
+        basic_network_account,
+        system_program,
+        len as usize,
+    )?;
+
+    Ok(())
+}
+
 /// Closes a program owned account in devnet and localhost
 ///
 /// # Notes
@@ -1,9 +1,13 @@
 mod common;
 
 use common::*;
-use elusiv_types::{ProgramAccount, SignerAccount, UserAccount, WritableSignerAccount, TOKENS};
+use elusiv_types::{
+    PDAAccount, ProgramAccount, ReallocAccount, SignerAccount, SizedAccount, UserAccount,
+    WritableSignerAccount, TOKENS,
+};
 use elusiv_warden_network::{
     instruction::ElusivWardenNetworkInstruction,
+    network::{BasicWardenNetworkAccount, ElusivBasicWardenNetwork, WardenNetwork},
     processor::{unix_timestamp_to_day_and_year, TRACKABLE_ELUSIV_INSTRUCTIONS},
     warden::{
         BasicWardenAccount, BasicWardenFeatures, BasicWardenMapAccount, BasicWardenStatsAccount,
@@ -121,6 +125,41 @@ async fn test_register_warden_id() {
     }
 }
 
+#[tokio::test]
+async fn test_grow_basic_warden_network() {
+    let mut test = start_test_with_setup().await;
+    let network = BasicWardenNetworkAccount::find(None).0;
+    assert_eq!(
+        test.data(&network).await.len(),
+        BasicWardenNetworkAccount::SIZE
+    );
+
+    test.ix_should_succeed_simple(
+        ElusivWardenNetworkInstruction::grow_basic_warden_network_instruction(
+            2,
+            WritableSignerAccount(test.payer()),
+        ),
+    )
+    .await;
+
+    let mut data = test.data(&network).await;
+    assert_eq!(data.len(), BasicWardenNetworkAccount::size_with_len(2));
+    let network_account = BasicWardenNetworkAccount::new(&mut data).unwrap();
+    assert_eq!(
+        network_account.capacity(),
+        ElusivBasicWardenNetwork::SIZE.max() + 2
+    );
+
+    // The network cannot shrink
+    test.ix_should_fail_simple(
+        ElusivWardenNetworkInstruction::grow_basic_warden_network_instruction(
+            1,
+            WritableSignerAccount(test.payer()),
+        ),
+    )
+    .await;
+}
+
 #[ignore]
 #[tokio::test]
 async fn test_register_warden_account_fuzzing() {
@@ -3,13 +3,16 @@ mod poseidon_constants;
 pub mod poseidon_hash;
 
 use elusiv_computation::PartialComputation;
+#[cfg(not(feature = "poseidon-syscall"))]
 use elusiv_proc_macros::elusiv_hash_compute_units;
+#[cfg(feature = "poseidon-syscall")]
+use elusiv_proc_macros::elusiv_hash_syscall_compute_units;
 use elusiv_utils::{guard, two_pow};
 use solana_program::program_error::ProgramError;
 
 use crate::{
     bytes::usize_as_u32_safe,
-    commitment::poseidon_hash::{binary_poseidon_hash_partial, TOTAL_POSEIDON_ROUNDS},
+    commitment::poseidon_hash::{poseidon_hash_round, ROUNDS_PER_HASH},
     error::ElusivError,
     state::commitment::{BaseCommitmentHashingAccount, CommitmentHashingAccount},
 };
@@ -17,30 +20,106 @@ use crate::{
 /// Partial computation resulting in `commitment = h(base_commitment, amount)`
 pub struct BaseCommitmentHashComputation;
 
+#[cfg(not(feature = "poseidon-syscall"))]
 elusiv_hash_compute_units!(BaseCommitmentHashComputation, 1, 100_000);
-#[cfg(test)]
+#[cfg(all(test, not(feature = "poseidon-syscall")))]
 const_assert_eq!(BaseCommitmentHashComputation::TX_COUNT, 2);
 
+#[cfg(feature = "poseidon-syscall")]
+elusiv_hash_syscall_compute_units!(BaseCommitmentHashComputation, 1);
+#[cfg(all(test, feature = "poseidon-syscall"))]
+const_assert_eq!(BaseCommitmentHashComputation::TX_COUNT, 1);
+
+/// Partial computation resulting in the commitments of a batch of `BATCH_SIZE` base commitments
+///
+/// # Note
+///
+/// The rounds of all base commitments are distributed over the instructions, so the batch shares the compute budget of each instruction.
+struct BaseCommitmentBatchHashComputation<const BATCH_SIZE: usize>;
+
+/// Generates a [`BaseCommitmentBatchHashComputation`] with a specific `BATCH_SIZE`
+macro_rules! base_commitment_batch_hashing {
+    ($batch_size: literal) => {
+        #[cfg(not(feature = "poseidon-syscall"))]
+        elusiv_hash_compute_units!(
+            BaseCommitmentBatchHashComputation<$batch_size>,
+            $batch_size,
+            100_000
+        );
+
+        #[cfg(feature = "poseidon-syscall")]
+        elusiv_hash_syscall_compute_units!(
+            BaseCommitmentBatchHashComputation<$batch_size>,
+            $batch_size
+        );
+    };
+}
+
+base_commitment_batch_hashing!(2);
+base_commitment_batch_hashing!(3);
+base_commitment_batch_hashing!(4);
+base_commitment_batch_hashing!(5);
+base_commitment_batch_hashing!(6);
+base_commitment_batch_hashing!(7);
+base_commitment_batch_hashing!(8);
+
+/// The rounds per instruction required for hashing a batch of `batch_len` base commitments
+///
+/// # Note
+///
+/// A single base commitment is computed with [`BaseCommitmentHashComputation`].
+pub fn base_commitment_hash_computation_instructions<'a>(batch_len: usize) -> &'a [u8] {
+    match batch_len {
+        1 => &BaseCommitmentHashComputation::INSTRUCTION_ROUNDS,
+        2 => &BaseCommitmentBatchHashComputation::<2>::INSTRUCTION_ROUNDS,
+        3 => &BaseCommitmentBatchHashComputation::<3>::INSTRUCTION_ROUNDS,
+        4 => &BaseCommitmentBatchHashComputation::<4>::INSTRUCTION_ROUNDS,
+        5 => &BaseCommitmentBatchHashComputation::<5>::INSTRUCTION_ROUNDS,
+        6 => &BaseCommitmentBatchHashComputation::<6>::INSTRUCTION_ROUNDS,
+        7 => &BaseCommitmentBatchHashComputation::<7>::INSTRUCTION_ROUNDS,
+        8 => &BaseCommitmentBatchHashComputation::<8>::INSTRUCTION_ROUNDS,
+        _ => {
+            panic!()
+        }
+    }
+}
+
 pub fn compute_base_commitment_hash_partial(
     hashing_account: &mut BaseCommitmentHashingAccount,
 ) -> Result<(), ProgramError> {
+    let batch_len = hashing_account.batch_len();
+    let instructions = base_commitment_hash_computation_instructions(batch_len);
     let instruction = hashing_account.get_instruction();
     guard!(
-        (instruction as usize) < BaseCommitmentHashComputation::IX_COUNT,
+        (instruction as usize) < instructions.len(),
         ElusivError::ComputationIsAlreadyFinished
     );
 
     let start_round = hashing_account.get_round();
-    let rounds = BaseCommitmentHashComputation::INSTRUCTION_ROUNDS[instruction as usize] as u32;
+    let rounds = instructions[instruction as usize] as u32;
+    let rounds_per_hash = BaseCommitmentHashComputation::TOTAL_ROUNDS;
 
     let mut state = hashing_account.get_state();
 
     for round in start_round..start_round + rounds {
         guard!(
-            round < BaseCommitmentHashComputation::TOTAL_ROUNDS,
+            round < rounds_per_hash * usize_as_u32_safe(batch_len),
             ElusivError::ComputationIsAlreadyFinished
         );
-        binary_poseidon_hash_partial(round, &mut state);
+        let index = (round / rounds_per_hash) as usize;
+
+        // The first base commitment is hashed starting with the setup `state`
+        if round % rounds_per_hash == 0 && index > 0 {
+            state = hashing_account
+                .get_batch(index)
+                .ok_or(ElusivError::InvalidAccount)?;
+        }
+
+        poseidon_hash_round(round % rounds_per_hash, &mut state)?;
+
+        if round % rounds_per_hash == rounds_per_hash - 1 {
+            hashing_account.set_batch(index, &state)?;
+        }
     }
 
     hashing_account.set_state(&state);
@@ -69,14 +148,22 @@ struct CommitmentHashComputation<const BATCHING_RATE: usize>;
 /// # Note
 ///
 /// The macro also verifies that `$hash_count` is valid.
+/// With the `poseidon-syscall` feature all hashes of a batch are computed in a single instruction.
 macro_rules! commitment_batch_hashing {
     ($batching_rate: literal, $hash_count: literal, $instruction_count: literal) => {
+        #[cfg(not(feature = "poseidon-syscall"))]
         elusiv_hash_compute_units!(CommitmentHashComputation<$batching_rate>, $hash_count);
 
+        #[cfg(feature = "poseidon-syscall")]
+        elusiv_hash_syscall_compute_units!(CommitmentHashComputation<$batching_rate>, $hash_count);
+
         #[cfg(test)]
         const_assert_eq!($hash_count, hash_count_per_batch($batching_rate));
 
-        #[cfg(test)]
+        #[cfg(all(test, feature = "poseidon-syscall"))]
+        const_assert_eq!(1, <CommitmentHashComputation<$batching_rate>>::IX_COUNT);
+
+        #[cfg(all(test, not(feature = "poseidon-syscall")))]
         const_assert_eq!(
             $instruction_count,
             <CommitmentHashComputation<$batching_rate>>::IX_COUNT
@@ -147,6 +234,8 @@ const_assert_eq!(MAX_HT_SIZE, 31);
 #[cfg(test)]
 const_assert_eq!(MAX_HT_COMMITMENTS, 16);
 
+// `ROUNDS_PER_HASH` is one with the `poseidon-syscall` feature
+#[allow(clippy::modulo_one)]
 pub fn compute_commitment_hash_partial(
     hashing_account: &mut CommitmentHashingAccount,
 ) -> Result<(), ProgramError> {
@@ -169,19 +258,23 @@ pub fn compute_commitment_hash_partial(
     let mut state = hashing_account.get_state();
 
     for round in start_round..start_round + rounds {
-        binary_poseidon_hash_partial(round % TOTAL_POSEIDON_ROUNDS, &mut state);
-
-        // A single hash is finished
-        if round % TOTAL_POSEIDON_ROUNDS == 64 {
-            let hash_index = round / TOTAL_POSEIDON_ROUNDS;
+        let hash_index = (round / ROUNDS_PER_HASH) as usize;
+
+        // Reset state for the next hash
+        if round % ROUNDS_PER_HASH == 0 {
+            // Pipelined batches wait for the siblings of the previous batch
+            guard!(
+                !hashing_account.is_sibling_pending(hash_index),
+                ElusivError::ComputationIsNotYetFinished
+            );
+            state = hashing_account.next_hashing_state(hash_index);
+        }
 
-            // Save hash
-            hashing_account.save_finished_hash(hash_index as usize, &state);
+        poseidon_hash_round(round % ROUNDS_PER_HASH, &mut state)?;
 
-            // Reset state for next hash
-            if (hash_index as usize) < hash_count_per_batch(batching_rate) - 1 {
-                state = hashing_account.next_hashing_state(hash_index as usize + 1);
-            }
+        // A single hash is finished
+        if round % ROUNDS_PER_HASH == ROUNDS_PER_HASH - 1 {
+            hashing_account.save_finished_hash(hash_index, &state);
         }
     }
 
@@ -197,11 +290,18 @@ mod tests {
     use assert_matches::assert_matches;
     use solana_program::native_token::LAMPORTS_PER_SOL;
 
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger256, PrimeField};
+
     use crate::{
-        fields::{u256_from_str, u256_to_fr_skip_mr},
+        commitment::poseidon_hash::full_poseidon2_hash,
+        fields::{fr_to_u256_le, u256_from_str, u256_to_fr_skip_mr, u64_to_u256_skip_mr},
         macros::zero_program_account,
-        state::{commitment::base_commitment_request, storage::EMPTY_TREE},
-        types::U256,
+        processor::BaseCommitmentHashRequest,
+        state::commitment::{base_commitment_request, MAX_BASE_COMMITMENT_BATCH_SIZE},
+        state::program_account::{ProgramAccount, ReallocAccount},
+        state::storage::EMPTY_TREE,
+        types::{RawU256, U256},
     };
 
     use super::*;
@@ -257,6 +357,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_base_commitment_batch_hash_computation() {
+        for batch_len in 1..=MAX_BASE_COMMITMENT_BATCH_SIZE {
+            let mut data = vec![0; BaseCommitmentHashingAccount::size_with_len(batch_len)];
+            let mut account = BaseCommitmentHashingAccount::new(&mut data).unwrap();
+
+            let requests: Vec<BaseCommitmentHashRequest> = (0..batch_len as u64)
+                .map(|i| BaseCommitmentHashRequest {
+                    base_commitment: RawU256::new(u64_to_u256_skip_mr(i + 1)),
+                    commitment: RawU256::new([0; 32]),
+                    commitment_index: i as u32,
+                    amount: LAMPORTS_PER_SOL * i,
+                    token_id: 0,
+                    fee_version: 0,
+                    min_batching_rate: 0,
+                })
+                .collect();
+
+            account.setup_batch(&requests, [0; 32]).unwrap();
+
+            let mut computations = 0;
+            while !account.is_batch_hashed() {
+                compute_base_commitment_hash_partial(&mut account).unwrap();
+                computations += 1;
+            }
+
+            // The batch is hashed within the compute budget of the instructions
+            assert_eq!(
+                computations,
+                base_commitment_hash_computation_instructions(batch_len).len()
+            );
+            assert!(computations <= BaseCommitmentHashComputation::IX_COUNT * batch_len);
+            assert_matches!(compute_base_commitment_hash_partial(&mut account), Err(_));
+
+            for (i, request) in requests.iter().enumerate() {
+                let commitment = full_poseidon2_hash(
+                    u256_to_fr_skip_mr(&request.base_commitment.reduce()),
+                    Fr::from_repr(BigInteger256([
+                        request.amount,
+                        (request.commitment_index as u64) << 16,
+                        0,
+                        0,
+                    ]))
+                    .unwrap(),
+                );
+                assert_eq!(
+                    account.get_commitment(i).unwrap(),
+                    fr_to_u256_le(&commitment)
+                );
+            }
+        }
+    }
+
     struct CommitmentBatchHashRequest<'a> {
         batching_rate: u32,
         commitments: &'a [U256],
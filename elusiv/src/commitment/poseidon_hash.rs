@@ -1,15 +1,33 @@
 use super::poseidon_constants::*;
 use crate::{
     bytes::BorshSerDeSized,
-    fields::{fr_to_u256_le, u256_to_fr_skip_mr},
+    error::ElusivError::{self, NonScalarValue, PartialComputationError},
+    fields::{fr_to_u256_le, fr_to_u256_le_repr, u256_to_big_uint, u256_to_fr_skip_mr},
     types::U256,
 };
 use ark_bn254::Fr;
-use ark_ff::{Field, Zero};
+use ark_ff::{Field, PrimeField, Zero};
 use borsh::{BorshDeserialize, BorshSerialize};
 
 pub const TOTAL_POSEIDON_ROUNDS: u32 = 65;
 
+/// Rounds required by the active hashing backend to compute a single hash
+///
+/// # Notes
+///
+/// With the `poseidon-syscall` feature a single round computes the full hash.
+pub const ROUNDS_PER_HASH: u32 = if cfg!(feature = "poseidon-syscall") {
+    1
+} else {
+    TOTAL_POSEIDON_ROUNDS
+};
+
+/// `Bn254X5` parameters of the `sol_poseidon` syscall (circom compatible)
+pub const POSEIDON_PARAMETERS_BN254_X5: u64 = 0;
+
+/// Little-endian (non-montgomery) inputs and output of the `sol_poseidon` syscall
+pub const POSEIDON_ENDIANNESS_LITTLE_ENDIAN: u64 = 1;
+
 macro_rules! matrix_mix {
     ($new_state: ident, $s: literal, $i: literal, $state: ident) => {
         $new_state[$s] += MATRIX[$i] * $state[0];
@@ -27,7 +45,7 @@ macro_rules! round {
     }};
 }
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Copy, Default)]
 #[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
 pub struct BinarySpongeHashingState(pub [Fr; 3]);
 
@@ -105,6 +123,87 @@ pub fn binary_poseidon_hash_partial(round: u32, hashing_state: &mut BinarySponge
     hashing_state.0 = new_state;
 }
 
+/// Performs a single round of the active hashing backend
+pub fn poseidon_hash_round(
+    round: u32,
+    hashing_state: &mut BinarySpongeHashingState,
+) -> Result<(), ElusivError> {
+    if cfg!(feature = "poseidon-syscall") {
+        binary_poseidon_hash_syscall(hashing_state)
+    } else {
+        binary_poseidon_hash_partial(round, hashing_state);
+        Ok(())
+    }
+}
+
+/// Computes the Poseidon Hash for two input values in a single call using the `sol_poseidon` syscall
+///
+/// # Notes
+///
+/// Only the result (`hashing_state.0[0]`) matches the state after all rounds of [`binary_poseidon_hash_partial`].
+pub fn binary_poseidon_hash_syscall(
+    hashing_state: &mut BinarySpongeHashingState,
+) -> Result<(), ElusivError> {
+    let hash = poseidon2_hash_syscall(hashing_state.0[1], hashing_state.0[2])?;
+    hashing_state.0 = [hash, Fr::zero(), Fr::zero()];
+    Ok(())
+}
+
+pub fn poseidon2_hash_syscall(a: Fr, b: Fr) -> Result<Fr, ElusivError> {
+    let a = fr_to_u256_le_repr(&a);
+    let b = fr_to_u256_le_repr(&b);
+    let mut result = [0; 32];
+    poseidon_syscall(&[&a, &b], &mut result)?;
+
+    Fr::from_repr(u256_to_big_uint(&result)).ok_or(NonScalarValue)
+}
+
+#[cfg(target_arch = "bpf")]
+fn poseidon_syscall(inputs: &[&[u8]], result: &mut U256) -> Result<(), ElusivError> {
+    extern "C" {
+        fn sol_poseidon(
+            parameters: u64,
+            endianness: u64,
+            vals: *const u8,
+            val_len: u64,
+            hash_result: *mut u8,
+        ) -> u64;
+    }
+
+    let status = unsafe {
+        sol_poseidon(
+            POSEIDON_PARAMETERS_BN254_X5,
+            POSEIDON_ENDIANNESS_LITTLE_ENDIAN,
+            inputs as *const _ as *const u8,
+            inputs.len() as u64,
+            result.as_mut_ptr(),
+        )
+    };
+
+    match status {
+        0 => Ok(()),
+        _ => Err(PartialComputationError),
+    }
+}
+
+/// Native shim for the `sol_poseidon` syscall
+#[cfg(not(target_arch = "bpf"))]
+fn poseidon_syscall(inputs: &[&[u8]], result: &mut U256) -> Result<(), ElusivError> {
+    if inputs.len() != 2 || inputs.iter().any(|input| input.len() != 32) {
+        return Err(PartialComputationError);
+    }
+
+    let mut values = [Fr::zero(); 2];
+    for (value, input) in values.iter_mut().zip(inputs) {
+        let mut bytes = [0; 32];
+        bytes.copy_from_slice(input);
+        *value = Fr::from_repr(u256_to_big_uint(&bytes)).ok_or(NonScalarValue)?;
+    }
+
+    *result = fr_to_u256_le_repr(&full_poseidon2_hash(values[0], values[1]));
+    Ok(())
+}
+
 pub fn full_poseidon2_hash(a: Fr, b: Fr) -> Fr {
     let mut state = BinarySpongeHashingState::new(a, b, false);
     for round in 0..TOTAL_POSEIDON_ROUNDS {
@@ -195,6 +294,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_poseidon2_hash_syscall() {
+        let values = [
+            ("0", "0"),
+            ("1", "2"),
+            ("4631032765893457899344", "3453623782378239237823937"),
+            ("2727127217219281927655748957", "78758278433947439"),
+            (
+                "21888242871839275222246405745257275088548364400416034343698204186575808495616",
+                "1",
+            ),
+        ];
+
+        for (a, b) in values {
+            let a = Fr::from_str(a).unwrap();
+            let b = Fr::from_str(b).unwrap();
+
+            assert_eq!(
+                poseidon2_hash_syscall(a, b).unwrap(),
+                full_poseidon2_hash(a, b)
+            );
+
+            let mut state = BinarySpongeHashingState::new(a, b, false);
+            binary_poseidon_hash_syscall(&mut state).unwrap();
+            assert_eq!(state.result(), full_poseidon2_hash(a, b));
+        }
+    }
+
+    #[test]
+    fn test_poseidon_syscall_invalid_inputs() {
+        let mut result = [0; 32];
+        assert!(poseidon_syscall(&[&[0; 32]], &mut result).is_err());
+        assert!(poseidon_syscall(&[&[0; 32], &[0; 31]], &mut result).is_err());
+        assert!(poseidon_syscall(&[&[0; 32], &[255; 32]], &mut result).is_err());
+    }
+
+    #[test]
+    fn test_poseidon_hash_round() {
+        let a = Fr::from_str("78758278433947439").unwrap();
+        let b = Fr::from_str("2727127217219281927655748957").unwrap();
+        let mut state = BinarySpongeHashingState::new(a, b, false);
+        for round in 0..ROUNDS_PER_HASH {
+            poseidon_hash_round(round, &mut state).unwrap();
+        }
+        assert_eq!(state.result(), full_poseidon2_hash(a, b));
+    }
+
     #[test]
     fn test_mt_default_values() {
         let mut a = full_poseidon2_hash(Fr::zero(), Fr::zero());
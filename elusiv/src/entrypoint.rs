@@ -7,6 +7,9 @@ use solana_program::{
 
 crate::macros::declare_program_id!();
 
+/// The layout version of all program accounts (accounts are upgraded using [`crate::instruction::ElusivInstruction::MigrateAccount`])
+pub const PROGRAM_VERSION: u8 = 1;
+
 #[cfg(not(feature = "no-entrypoint"))]
 solana_program::entrypoint!(process_instruction);
 
@@ -58,6 +58,21 @@ pub enum ElusivError {
     // Accounts
     SubAccountAlreadyExists,
     SubAccouttDoesNotExists,
+
+    // Proof
+    InvalidProofPoint,
+
+    // Migration
+    InvalidAccountVersion,
+
+    // Limits
+    VolumeLimitExceeded,
+
+    // APA
+    ApaRestricted,
+
+    // Pool
+    PoolInsolvent,
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -3,8 +3,13 @@ use crate::{
     bytes::BorshSerDeSized,
     types::{u256_to_le_limbs, U256},
 };
-use ark_bn254::{Fq, Fq12, Fq2, Fq6, Fr, G1Affine, G1Projective, G2Affine};
-use ark_ff::{BigInteger256, One, PrimeField};
+use ark_bn254::{
+    Fq, Fq12, Fq2, Fq6, FqParameters, Fr, G1Affine, G1Projective, G2Affine, G2Projective,
+    Parameters,
+};
+use ark_ec::models::bn::BnParameters;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger256, Field, FpParameters, One, PrimeField, Zero};
 use borsh::{BorshDeserialize, BorshSerialize};
 
 /// From [`&[u8]`] to [`[u8; 8]`]
@@ -278,6 +283,30 @@ impl BorshDeserialize for Wrap<G1A> {
     }
 }
 
+// G1Projective
+impl BorshSerDeSized for Wrap<G1Projective> {
+    const SIZE: usize = 96;
+}
+impl BorshSerialize for Wrap<G1Projective> {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_base_montgomery(self.0.x, writer)?;
+        write_base_montgomery(self.0.y, writer)?;
+        write_base_montgomery(self.0.z, writer)
+    }
+}
+impl BorshDeserialize for Wrap<G1Projective> {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        assert!(buf.len() >= 96);
+        let res = Wrap(G1Projective::new(
+            fq_skip_mr!(buf),
+            fq_skip_mr!(&buf[32..]),
+            fq_skip_mr!(&buf[64..]),
+        ));
+        *buf = &buf[96..];
+        Ok(res)
+    }
+}
+
 // G2A
 impl BorshSerDeSized for G2A {
     const SIZE: usize = 129;
@@ -407,6 +436,70 @@ pub fn affine_into_projective(a: &G1Affine) -> G1Projective {
     G1Projective::new(a.x, a.y, Fq::one())
 }
 
+/// Checks whether a (mr-form) base field element is in canonical form
+/// - since points are deserialized without a Montgomery reduction, coordinates can exceed the modulus
+fn is_canonical_base(e: &Fq) -> bool {
+    e.0 < <FqParameters as FpParameters>::MODULUS
+}
+
+/// Checks whether `p` is a canonical element of G1
+/// - the point at infinity is only valid with the coordinates `(0, 0)` or `(0, 1)` (arkworks representation)
+/// - no subgroup check is required, since G1 has a cofactor of one
+pub fn is_valid_g1_point(p: &G1Affine) -> bool {
+    if p.infinity {
+        return p.x.is_zero() && (p.y.is_zero() || p.y.is_one());
+    }
+
+    is_canonical_base(&p.x) && is_canonical_base(&p.y) && p.is_on_curve()
+}
+
+/// Checks whether `p` is a canonical point on the G2 twist
+/// - the point at infinity is only valid with the coordinates `(0, 0)` or `(0, 1)` (arkworks representation)
+/// - does not check the subgroup membership (see [`is_in_g2_subgroup`]), which exceeds the compute budget of a single instruction
+pub fn is_valid_g2_point(p: &G2Affine) -> bool {
+    if p.infinity {
+        return p.x.is_zero() && (p.y.is_zero() || p.y.is_one());
+    }
+
+    if ![p.x.c0, p.x.c1, p.y.c0, p.y.c1]
+        .iter()
+        .all(is_canonical_base)
+    {
+        return false;
+    }
+
+    p.is_on_curve()
+}
+
+/// Checks whether a point `p` on the G2 twist is part of the prime-order subgroup
+/// - https://eprint.iacr.org/2022/348.pdf (only requires a 64 bit scalar multiplication)
+/// - on-chain the check is performed by the `G2SubgroupCheck` partial computation in [`crate::proof::verifier`]
+pub fn is_in_g2_subgroup(p: &G2Affine) -> bool {
+    if p.infinity {
+        return true;
+    }
+
+    // `[x + 1]P + ψ([x]P) + ψ²([x]P) == ψ³([2x]P)`
+    let xp = p.mul(BigInteger256::from(Parameters::X[0]));
+    let psi_xp = psi(&xp);
+    let psi2_xp = psi(&psi_xp);
+    let lhs = xp.add_mixed(p) + psi_xp + psi2_xp;
+    let rhs = psi(&psi2_xp).double();
+
+    lhs == rhs
+}
+
+/// The untwist-Frobenius-twist endomorphism `ψ` (on jacobian coordinates)
+pub(crate) fn psi(p: &G2Projective) -> G2Projective {
+    let mut p = *p;
+    p.x.frobenius_map(1);
+    p.x *= Parameters::TWIST_MUL_BY_Q_X;
+    p.y.frobenius_map(1);
+    p.y *= Parameters::TWIST_MUL_BY_Q_Y;
+    p.z.frobenius_map(1);
+    p
+}
+
 #[cfg(test)]
 use std::str::FromStr;
 
@@ -479,6 +572,68 @@ mod tests {
         assert!(is_element_scalar_field(BigInteger256::from(0)));
     }
 
+    #[test]
+    fn test_is_valid_g1_point() {
+        let g = G1Affine::prime_subgroup_generator();
+        let p = g.mul(Fr::from(123456789u64).into_repr()).into_affine();
+        assert!(is_valid_g1_point(&g));
+        assert!(is_valid_g1_point(&p));
+        assert!(is_valid_g1_point(&G1Affine::zero()));
+
+        // Point at infinity with non-zero coordinates
+        assert!(!is_valid_g1_point(&G1Affine::new(p.x, p.y, true)));
+
+        // Not on curve
+        assert!(!is_valid_g1_point(&G1Affine::new(p.x, p.x, false)));
+
+        // Non-canonical coordinate
+        let mut y = p.y.0;
+        y.add_nocarry(&<FqParameters as FpParameters>::MODULUS);
+        assert!(!is_valid_g1_point(&G1Affine::new(p.x, Fq::new(y), false)));
+    }
+
+    #[test]
+    fn test_is_valid_g2_point() {
+        let g = G2Affine::prime_subgroup_generator();
+        let p = g.mul(Fr::from(123456789u64).into_repr()).into_affine();
+        assert!(is_valid_g2_point(&g));
+        assert!(is_valid_g2_point(&p));
+        assert!(is_valid_g2_point(&G2Affine::zero()));
+        assert!(is_in_g2_subgroup(&g));
+        assert!(is_in_g2_subgroup(&p));
+
+        // Point at infinity with non-zero coordinates
+        assert!(!is_valid_g2_point(&G2Affine::new(p.x, p.y, true)));
+
+        // Not on curve
+        assert!(!is_valid_g2_point(&G2Affine::new(p.x, p.x, false)));
+
+        // Non-canonical coordinate
+        let mut c1 = p.y.c1.0;
+        c1.add_nocarry(&<FqParameters as FpParameters>::MODULUS);
+        let y = Fq2::new(p.y.c0, Fq::new(c1));
+        assert!(!is_valid_g2_point(&G2Affine::new(p.x, y, false)));
+
+        // Points on the twist (mostly) outside of the subgroup
+        let mut outside_count = 0;
+        for i in 0..20u64 {
+            let x = Fq2::new(Fq::from(i), Fq::one());
+            if let Some(p) = G2Affine::get_point_from_x(x, false) {
+                assert!(p.is_on_curve());
+                assert!(is_valid_g2_point(&p));
+                assert_eq!(
+                    is_in_g2_subgroup(&p),
+                    p.is_in_correct_subgroup_assuming_on_curve()
+                );
+
+                if !is_in_g2_subgroup(&p) {
+                    outside_count += 1;
+                }
+            }
+        }
+        assert!(outside_count > 0);
+    }
+
     #[test]
     fn test_ser_de_big_integer_256() {
         test_ser_de!(Wrap<BigInteger256>, Wrap(BigInteger256::from(123456789)));
@@ -603,6 +758,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ser_de_g1_projective() {
+        test_ser_de!(
+            Wrap<G1Projective>,
+            Wrap(G1Projective::new(
+                Fq::from_str(
+                    "10026859857882131638516328056627849627085232677511724829502598764489185541935"
+                )
+                .unwrap(),
+                Fq::from_str(
+                    "19685960310506634721912121951341598678325833230508240750559904196809564625591"
+                )
+                .unwrap(),
+                Fq::from_str(
+                    "8337064132573119120838379738103457054645361649757131991036638108422638197362"
+                )
+                .unwrap(),
+            ))
+        );
+    }
+
     #[test]
     fn test_ser_de_g2a() {
         test_ser_de!(
@@ -3,20 +3,27 @@
 use super::processor;
 use super::processor::BaseCommitmentHashRequest;
 use crate::macros::*;
-use crate::processor::{FinalizeSendData, ProofRequest, VKeyAccountDataPacket, MAX_MT_COUNT};
+use crate::processor::{
+    program_data_address, FinalizeSendData, MigratableAccount, ProofRequest, VKeyAccountDataPacket,
+    MAX_MT_COUNT,
+};
 use crate::state::{
     commitment::{
-        BaseCommitmentBufferAccount, BaseCommitmentHashingAccount, CommitmentHashingAccount,
+        hashing_account_pda_offset, BaseCommitmentBufferAccount, BaseCommitmentHashingAccount,
+        CommitmentHashingAccount,
     },
     fee::{FeeAccount, ProgramFee},
-    governor::{FeeCollectorAccount, GovernorAccount, PoolAccount},
+    governor::{
+        FeeCollectorAccount, GovernanceProposal, GovernanceProposalsAccount, GovernorAccount,
+        PoolAccount, PoolLedgerAccount, TokenVolumeAccount, MAX_GOVERNANCE_SIGNERS,
+    },
     nullifier::NullifierAccount,
     proof::VerificationAccount,
     queue::CommitmentQueueAccount,
-    storage::StorageAccount,
-    vkey::VKeyAccount,
+    storage::{mt_shard, shard_pda_offset, StorageAccount},
+    vkey::{ProofSystem, VKeyAccount, VKeyAction, MAX_VKEY_SIGNERS},
 };
-use crate::types::Proof;
+use crate::types::{PlonkProof, Proof};
 use borsh::{BorshDeserialize, BorshSerialize};
 use elusiv_types::{AccountRepr, ElusivOption};
 use solana_program::{pubkey::Pubkey, system_program, sysvar::instructions};
@@ -26,12 +33,21 @@ pub use elusiv_types::accounts::{
     SignerAccount, UserAccount, WritableSignerAccount, WritableUserAccount,
 };
 
+/// All program instructions
+///
+/// # Notes
+///
+/// Instructions without a `governor` account (and without `#[skip_program_version]`) require the [`GovernorAccount`] as their last (read-only) account.
 #[repr(u8)]
 #[derive(BorshDeserialize, BorshSerialize, ElusivInstruction)]
 #[allow(clippy::large_enum_variant)]
+#[program_version_account(GovernorAccount)]
 pub enum ElusivInstruction {
     // -------- Base commitment hashing --------
     /// Client sends `base_commitment` and `amount` to be stored in the Elusiv program
+    /// - `sender` can also be a PDA of an invoking program (see the `cpi` module)
+    /// - for `token_id = 0`, `sender_account` can also be a wrapped-SOL token account of `sender`, which is closed (unwrapped) before the transfers
+    /// - `apa_target_map` is the warden-network `ApaTargetMapAccount` of `sender`, `apa_proposal` the `ApaProposalAccount` referenced by it (or any account)
     #[acc(sender, { signer })]
     #[acc(sender_account, { writable })]
     #[acc(fee_payer, { writable, signer })]
@@ -43,10 +59,15 @@ pub enum ElusivInstruction {
     #[acc(sol_price_account)]
     #[acc(token_price_account)]
     #[pda(governor, GovernorAccount)]
+    #[pda(token_volume, TokenVolumeAccount, { writable })]
+    #[pda(pool_ledger, PoolLedgerAccount, { writable })]
     #[acc(hashing_account, { writable })]
     #[pda(buffer, BaseCommitmentBufferAccount, { writable })]
-    #[acc(token_program)] // if `token_id = 0` { `system_program` } else { `token_program` }
+    #[acc(token_program)]
+    // if `token_id = 0` { `system_program` (or `token_program` for a wrapped-SOL `sender_account`) } else { `token_program` }
     #[sys(system_program, key = system_program::ID)]
+    #[acc(apa_target_map)]
+    #[acc(apa_proposal)]
     StoreBaseCommitment {
         hash_account_index: u32,
         hash_account_bump: u8,
@@ -58,43 +79,72 @@ pub enum ElusivInstruction {
 
     #[acc(original_fee_payer, { writable })]
     #[pda(pool, PoolAccount, { writable, account_info })]
+    #[pda(pool_ledger, PoolLedgerAccount, { writable })]
     #[pda(fee, FeeAccount, pda_offset = Some(fee_version))]
     #[pda(hashing_account, BaseCommitmentHashingAccount, pda_offset = Some(hash_account_index), { writable, account_info })]
-    #[pda(commitment_hash_queue, CommitmentQueueAccount, { writable })]
+    #[pda(commitment_hash_queue, CommitmentQueueAccount, pda_offset = shard_pda_offset(shard), { writable, include_child_accounts })]
+    #[pda(governor, GovernorAccount)]
     FinalizeBaseCommitmentHash {
         hash_account_index: u32,
         fee_version: u32,
+        shard: u32,
     },
 
     // -------- Commitment hashing --------
     /// Hashes commitments in a new MT-root
-    #[pda(commitment_hashing_account, CommitmentHashingAccount, { writable })]
-    #[pda(storage_account, StorageAccount, { include_child_accounts })]
-    InitCommitmentHashSetup { insertion_can_fail: bool },
+    #[pda(previous_hashing_account, CommitmentHashingAccount, pda_offset = hashing_account_pda_offset(shard, previous_hashing_account_index), { account_info })]
+    #[pda(commitment_hashing_account, CommitmentHashingAccount, pda_offset = hashing_account_pda_offset(shard, hashing_account_index), { writable })]
+    #[pda(storage_account, StorageAccount, pda_offset = shard_pda_offset(shard), { include_child_accounts, writable })]
+    InitCommitmentHashSetup {
+        insertion_can_fail: bool,
+        #[ignore_field]
+        shard: u32,
+        hashing_account_index: u32,
+        previous_hashing_account_index: u32,
+    },
 
-    #[pda(commitment_hash_queue, CommitmentQueueAccount, { writable })]
-    #[pda(commitment_hashing_account, CommitmentHashingAccount, { writable })]
-    InitCommitmentHash { insertion_can_fail: bool },
+    #[pda(commitment_hash_queue, CommitmentQueueAccount, pda_offset = shard_pda_offset(shard), { writable, include_child_accounts })]
+    #[pda(commitment_hashing_account, CommitmentHashingAccount, pda_offset = hashing_account_pda_offset(shard, hashing_account_index), { writable })]
+    InitCommitmentHash {
+        insertion_can_fail: bool,
+        #[ignore_field]
+        shard: u32,
+        hashing_account_index: u32,
+    },
 
     #[acc(fee_payer, { writable, signer })]
     #[pda(fee, FeeAccount, pda_offset = Some(fee_version))]
     #[pda(pool, PoolAccount, { writable, account_info })]
-    #[pda(commitment_hashing_account, CommitmentHashingAccount, { writable })]
-    ComputeCommitmentHash { fee_version: u32, nonce: u32 },
+    #[pda(pool_ledger, PoolLedgerAccount, { writable })]
+    #[pda(commitment_hashing_account, CommitmentHashingAccount, pda_offset = hashing_account_pda_offset(shard, hashing_account_index), { writable })]
+    ComputeCommitmentHash {
+        fee_version: u32,
+        nonce: u32,
+        #[ignore_field]
+        shard: u32,
+        hashing_account_index: u32,
+    },
 
-    #[pda(commitment_hashing_account, CommitmentHashingAccount, { writable })]
-    #[pda(storage_account, StorageAccount, { include_child_accounts, writable })]
-    FinalizeCommitmentHash,
+    #[pda(commitment_hashing_account, CommitmentHashingAccount, pda_offset = hashing_account_pda_offset(shard, hashing_account_index), { writable })]
+    #[pda(next_hashing_account, CommitmentHashingAccount, pda_offset = hashing_account_pda_offset(shard, next_hashing_account_index), { writable, account_info })]
+    #[pda(storage_account, StorageAccount, pda_offset = shard_pda_offset(shard), { include_child_accounts, writable })]
+    FinalizeCommitmentHash {
+        #[ignore_field]
+        shard: u32,
+        hashing_account_index: u32,
+        next_hashing_account_index: u32,
+    },
 
     // -------- Proof Verification --------
     /// Proof verification initialization
     #[acc(fee_payer, { writable, signer })]
     #[pda(verification_account, VerificationAccount, pda_pubkey = fee_payer.pubkey(), pda_offset = Some(verification_account_index.into()), { writable, account_info, find_pda })]
-    #[pda(vkey_account, VKeyAccount, pda_offset = Some(vkey_id))]
+    #[pda(vkey_account, VKeyAccount, pda_offset = Some(vkey_id), { writable })]
     #[acc(nullifier_duplicate_account, { writable })]
     #[sys(system_program, key = system_program::ID, { ignore })]
     #[acc(identifier_account)]
-    #[pda(storage_account, StorageAccount)]
+    #[pda(storage_account0, StorageAccount, pda_offset = shard_pda_offset(mt_shard(tree_indices[0])))]
+    #[pda(storage_account1, StorageAccount, pda_offset = shard_pda_offset(mt_shard(tree_indices[1])))]
     #[pda(nullifier_account0, NullifierAccount, pda_offset = Some(tree_indices[0]), { include_child_accounts })]
     #[pda(nullifier_account1, NullifierAccount, pda_offset = Some(tree_indices[1]), { include_child_accounts })]
     InitVerification {
@@ -105,18 +155,26 @@ pub enum ElusivInstruction {
         skip_nullifier_pda: bool,
     },
 
+    /// - `recipient` is the recipient (wallet) later revealed by [`ElusivInstruction::FinalizeVerificationSend`]
+    /// - `apa_target_map` is the warden-network `ApaTargetMapAccount` of the recipient (or of the owner of a recipient token account), `apa_proposal` the `ApaProposalAccount` referenced by it (or any account)
+    /// - the withdrawal volume is reserved in `token_volume` once the fee has been transferred
     #[acc(fee_payer, { writable, signer })]
     #[acc(fee_payer_account, { writable })]
     #[pda(pool, PoolAccount, { writable, account_info })]
     #[acc(pool_account, { writable })]
+    #[pda(pool_ledger, PoolLedgerAccount, { writable })]
     #[pda(fee_collector, FeeCollectorAccount, { writable, account_info })]
     #[acc(fee_collector_account, { writable })]
     #[acc(sol_price_account)]
     #[acc(token_price_account)]
     #[pda(governor, GovernorAccount)]
+    #[pda(token_volume, TokenVolumeAccount, { writable })]
     #[pda(verification_account, VerificationAccount, pda_pubkey = fee_payer.pubkey(), pda_offset = Some(verification_account_index.into()), { writable })]
     #[acc(token_program)] // if `token_id = 0` { `system_program` } else { `token_program` }
     #[sys(system_program, key = system_program::ID)]
+    #[acc(recipient)]
+    #[acc(apa_target_map)]
+    #[acc(apa_proposal)]
     InitVerificationTransferFee { verification_account_index: u8 },
 
     #[acc(fee_payer, { signer })]
@@ -141,9 +199,10 @@ pub enum ElusivInstruction {
     #[acc(identifier_account)]
     #[acc(transaction_reference_account)]
     #[acc(original_fee_payer, { ignore })]
-    #[pda(commitment_hash_queue, CommitmentQueueAccount, { writable })]
+    #[pda(commitment_hash_queue, CommitmentQueueAccount, pda_offset = shard_pda_offset(mt_shard(data.mt_index)), { writable })]
     #[pda(verification_account, VerificationAccount, pda_pubkey = original_fee_payer.pubkey(), pda_offset = Some(verification_account_index.into()), { writable })]
-    #[pda(storage_account, StorageAccount)]
+    #[pda(storage_account, StorageAccount, pda_offset = shard_pda_offset(mt_shard(data.mt_index)))]
+    #[pda(governor, GovernorAccount)]
     #[sys(instructions_account, key = instructions::ID)]
     FinalizeVerificationSend {
         verification_account_index: u8,
@@ -156,16 +215,27 @@ pub enum ElusivInstruction {
     #[pda(nullifier_account, NullifierAccount, pda_offset = Some(verification_account.get_tree_indices(0)), { writable, include_child_accounts, skip_abi })]
     FinalizeVerificationInsertNullifier { verification_account_index: u8 },
 
+    /// - if `recipient_is_associated_token_account`, the lamports are delivered as wrapped-SOL into the `recipient` ATA of `recipient_wallet`
     #[acc(original_fee_payer, { signer, writable })]
     #[acc(recipient, { writable })]
+    #[acc(recipient_wallet)]
     #[pda(pool, PoolAccount, { account_info, writable })]
     #[pda(fee_collector, FeeCollectorAccount, { account_info, writable })]
-    #[pda(commitment_hash_queue, CommitmentQueueAccount, { writable })]
+    #[pda(commitment_hash_queue, CommitmentQueueAccount, pda_offset = shard_pda_offset(commitment_shard), { writable, include_child_accounts })]
     #[pda(verification_account, VerificationAccount, pda_pubkey = original_fee_payer.pubkey(), pda_offset = Some(verification_account_index.into()), { writable, account_info })]
+    #[acc(vkey_account, { writable, owned })]
     #[acc(nullifier_duplicate_account, { writable, owned })]
+    #[pda(token_volume, TokenVolumeAccount, { writable })]
+    #[pda(pool_ledger, PoolLedgerAccount, { writable })]
+    #[sys(a_token_program, key = spl_associated_token_account::ID, { ignore })]
+    #[sys(token_program, key = spl_token::ID)]
     #[sys(system_program, key = system_program::ID, { ignore })]
+    #[sys(mint_account, key = spl_token::native_mint::ID)]
     #[sys(instructions_account, key = instructions::ID)]
-    FinalizeVerificationTransferLamports { verification_account_index: u8 },
+    FinalizeVerificationTransferLamports {
+        verification_account_index: u8,
+        commitment_shard: u32,
+    },
 
     #[acc(original_fee_payer, { signer, writable })]
     #[acc(original_fee_payer_account, { writable })]
@@ -175,15 +245,21 @@ pub enum ElusivInstruction {
     #[acc(pool_account, { writable })]
     #[pda(fee_collector, FeeCollectorAccount, { account_info, writable })]
     #[acc(fee_collector_account, { writable })]
-    #[pda(commitment_hash_queue, CommitmentQueueAccount, { writable })]
+    #[pda(commitment_hash_queue, CommitmentQueueAccount, pda_offset = shard_pda_offset(commitment_shard), { writable, include_child_accounts })]
     #[pda(verification_account, VerificationAccount, pda_pubkey = original_fee_payer.pubkey(), pda_offset = Some(verification_account_index.into()), { writable, account_info })]
+    #[acc(vkey_account, { writable, owned })]
     #[acc(nullifier_duplicate_account, { writable, owned })]
+    #[pda(token_volume, TokenVolumeAccount, { writable })]
+    #[pda(pool_ledger, PoolLedgerAccount, { writable })]
     #[sys(a_token_program, key = spl_associated_token_account::ID, { ignore })]
     #[sys(token_program, key = spl_token::ID)]
     #[sys(system_program, key = system_program::ID, { ignore })]
     #[acc(mint_account)]
     #[sys(instructions_account, key = instructions::ID)]
-    FinalizeVerificationTransferToken { verification_account_index: u8 },
+    FinalizeVerificationTransferToken {
+        verification_account_index: u8,
+        commitment_shard: u32,
+    },
 
     // -------- Verifying key management --------
     #[acc(signer, { writable, signer })]
@@ -192,6 +268,7 @@ pub enum ElusivInstruction {
     CreateVkeyAccount {
         vkey_id: u32,
         public_inputs_count: u32,
+        proof_system: ProofSystem,
         deploy_authority: ElusivOption<Pubkey>,
     },
 
@@ -203,7 +280,6 @@ pub enum ElusivInstruction {
     #[acc(signer, { signer, writable })]
     #[pda(vkey_account, VKeyAccount, pda_offset = Some(vkey_id), { writable })]
     #[acc(old_vkey_binary_data_account, { writable })]
-    #[sys(system_program, key = system_program::ID)]
     UpdateVkeyVersion { vkey_id: u32 },
 
     #[acc(signer, { signer })]
@@ -223,20 +299,27 @@ pub enum ElusivInstruction {
     ChangeVkeyAuthority { vkey_id: u32, authority: Pubkey },
 
     // -------- MT management --------
-    /// Set the next MT as the active MT
-    #[pda(storage_account, StorageAccount, { writable, include_child_accounts })]
-    #[pda(commitment_hash_queue, CommitmentQueueAccount, { writable })]
+    /// Set the next MT of a shard as the active MT
+    #[pda(storage_account, StorageAccount, pda_offset = shard_pda_offset(mt_shard(active_mt_index)), { writable, include_child_accounts })]
+    #[pda(commitment_hash_queue, CommitmentQueueAccount, pda_offset = shard_pda_offset(mt_shard(active_mt_index)), { writable, include_child_accounts })]
     #[pda(active_nullifier_account, NullifierAccount, pda_offset = Some(active_mt_index), { writable })]
     ResetActiveMerkleTree { active_mt_index: u32 },
 
     /// Archives a `NullifierAccount` into a N-SMT
     #[acc(payer, { writable, signer })]
-    #[pda(storage_account, StorageAccount, { writable, include_child_accounts })]
+    #[pda(storage_account, StorageAccount, pda_offset = shard_pda_offset(mt_shard(closed_mt_index)), { writable, include_child_accounts })]
     #[pda(nullifier_account, NullifierAccount, pda_offset = Some(closed_mt_index), { writable, include_child_accounts })]
     #[acc(archived_tree_account, { writable })]
     #[sys(system_program, key = system_program::ID, { ignore })]
     ArchiveClosedMerkleTree { closed_mt_index: u32 },
 
+    /// Grows the root history of the `StorageAccount`
+    #[acc(authority, { writable, signer })]
+    #[pda(governor, GovernorAccount)]
+    #[pda(storage_account, StorageAccount, { writable, account_info })]
+    #[sys(system_program, key = system_program::ID)]
+    GrowRootHistory { len: u32 },
+
     // -------- Program state management --------
     #[acc(payer, { writable, signer })]
     #[pda(pool_account, PoolAccount, { writable, skip_pda_verification, account_info })]
@@ -246,29 +329,77 @@ pub enum ElusivInstruction {
     #[pda(storage_account, StorageAccount, { writable, skip_pda_verification, account_info })]
     #[pda(base_commitment_buffer_account, BaseCommitmentBufferAccount, { writable, skip_pda_verification, account_info })]
     #[sys(system_program, key = system_program::ID, { ignore })]
+    #[pda(governor, GovernorAccount)]
     OpenSingleInstanceAccounts,
 
+    #[acc(payer, { writable, signer })]
+    #[pda(token_volume_account, TokenVolumeAccount, { writable, skip_pda_verification, account_info })]
+    #[sys(system_program, key = system_program::ID, { ignore })]
+    #[pda(governor, GovernorAccount)]
+    OpenTokenVolumeAccount,
+
+    #[acc(payer, { writable, signer })]
+    #[pda(pool_ledger_account, PoolLedgerAccount, { writable, skip_pda_verification, account_info })]
+    #[sys(system_program, key = system_program::ID, { ignore })]
+    #[pda(governor, GovernorAccount)]
+    OpenPoolLedgerAccount,
+
+    #[acc(payer, { writable, signer })]
+    #[pda(governor, GovernorAccount, { writable })]
+    #[pda(commitment_hashing_account, CommitmentHashingAccount, pda_offset = Some(shard), { writable, skip_pda_verification, account_info })]
+    #[pda(commitment_queue_account, CommitmentQueueAccount, pda_offset = Some(shard), { writable, skip_pda_verification, account_info })]
+    #[pda(storage_account, StorageAccount, pda_offset = Some(shard), { writable, skip_pda_verification, account_info })]
+    #[sys(system_program, key = system_program::ID, { ignore })]
+    OpenCommitmentShardAccounts { shard: u32 },
+
+    #[acc(payer, { writable, signer })]
+    #[pda(governor, GovernorAccount)]
+    #[pda(storage_account, StorageAccount, pda_offset = shard_pda_offset(shard), { writable })]
+    #[pda(commitment_hashing_account, CommitmentHashingAccount, pda_offset = hashing_account_pda_offset(shard, hashing_account_index), { writable, skip_pda_verification, account_info })]
+    #[sys(system_program, key = system_program::ID, { ignore })]
+    OpenCommitmentHashingAccount {
+        shard: u32,
+        hashing_account_index: u32,
+    },
+
     #[acc(payer, { writable, signer })]
     #[pda(nullifier_account, NullifierAccount, pda_offset = Some(mt_index), { writable, skip_pda_verification, account_info })]
     #[sys(system_program, key = system_program::ID, { ignore })]
     OpenNullifierAccount { mt_index: u32 },
 
-    #[pda(storage_account, StorageAccount, { writable })]
+    #[pda(storage_account, StorageAccount, pda_offset = shard_pda_offset(shard), { writable })]
     #[acc(sub_account, { owned, writable })]
-    EnableStorageChildAccount { child_index: u32 },
+    EnableStorageChildAccount {
+        #[ignore_field]
+        shard: u32,
+        child_index: u32,
+    },
 
     #[pda(nullifier_account, NullifierAccount, pda_offset = Some(mt_index), { writable })]
     #[acc(sub_account, { owned, writable })]
     EnableNullifierChildAccount { mt_index: u32, child_index: u32 },
 
+    /// - the already enabled child-accounts of `commitment_queue` are required, since the elements of a wrapped-around queue are moved into the new slots
+    #[pda(commitment_queue, CommitmentQueueAccount, pda_offset = shard_pda_offset(shard), { writable, include_child_accounts })]
+    #[acc(sub_account, { owned, writable })]
+    EnableCommitmentQueueChildAccount {
+        #[ignore_field]
+        shard: u32,
+        child_index: u32,
+    },
+
+    #[skip_program_version]
     #[acc(payer, { writable, signer })]
     #[pda(governor, GovernorAccount, { writable, skip_pda_verification, account_info })]
+    #[pda(proposals, GovernanceProposalsAccount, { writable, skip_pda_verification, account_info })]
+    #[sys(program_data, key = program_data_address())]
     #[sys(system_program, key = system_program::ID, { ignore })]
     SetupGovernorAccount,
 
     #[acc(authority, { signer })]
     #[pda(governor, GovernorAccount, { writable })]
     #[pda(commitment_hash_queue, CommitmentQueueAccount)]
+    #[pda(proposals, GovernanceProposalsAccount, { writable })]
     UpgradeGovernorState {
         fee_version: u32,
         batching_rate: u32,
@@ -278,12 +409,138 @@ pub enum ElusivInstruction {
     #[pda(governor, GovernorAccount, { writable })]
     #[pda(fee, FeeAccount, pda_offset = Some(fee_version), { writable, skip_pda_verification, account_info })]
     #[sys(system_program, key = system_program::ID, { ignore })]
+    #[pda(proposals, GovernanceProposalsAccount, { writable })]
     InitNewFeeVersion {
         fee_version: u32,
         program_fee: ProgramFee,
     },
 
+    #[acc(fee_payer, { signer })]
+    #[pda(verification_account, VerificationAccount, pda_pubkey = fee_payer.pubkey(), pda_offset = Some(verification_account_index.into()), { writable })]
+    InitVerificationPlonkProof {
+        verification_account_index: u8,
+        proof: PlonkProof,
+    },
+
+    #[acc(signer, { signer })]
+    #[pda(vkey_account, VKeyAccount, pda_offset = Some(vkey_id), { writable })]
+    SetVkeySigners {
+        vkey_id: u32,
+        signers: [ElusivOption<Pubkey>; MAX_VKEY_SIGNERS],
+        threshold: u8,
+    },
+
+    #[acc(signer, { signer })]
+    #[pda(vkey_account, VKeyAccount, pda_offset = Some(vkey_id), { writable })]
+    ApproveVkeyAction { vkey_id: u32, action: VKeyAction },
+
+    #[acc(signer, { signer, writable })]
+    #[pda(vkey_account, VKeyAccount, pda_offset = Some(vkey_id), { writable })]
+    #[acc(previous_vkey_binary_data_account, { writable })]
+    ClosePreviousVkeyVersion { vkey_id: u32 },
+
+    // -------- Governance --------
+    #[acc(authority, { signer })]
+    #[pda(governor, GovernorAccount)]
+    #[pda(proposals, GovernanceProposalsAccount, { writable })]
+    ProposeGovernanceChange { proposal: GovernanceProposal },
+
+    #[acc(signer, { signer })]
+    #[pda(governor, GovernorAccount)]
+    #[pda(proposals, GovernanceProposalsAccount, { writable })]
+    ApproveGovernanceProposal { proposal_index: u8 },
+
+    #[acc(authority, { signer })]
+    #[pda(governor, GovernorAccount)]
+    #[pda(proposals, GovernanceProposalsAccount, { writable })]
+    CancelGovernanceProposal { proposal_index: u8 },
+
+    #[pda(governor, GovernorAccount, { writable })]
+    #[pda(proposals, GovernanceProposalsAccount, { writable })]
+    ChangeGovernanceAuthority {
+        authority: ElusivOption<Pubkey>,
+        signers: [ElusivOption<Pubkey>; MAX_GOVERNANCE_SIGNERS],
+        threshold: u8,
+    },
+
+    // -------- Account migration --------
+    #[skip_program_version]
+    #[acc(payer, { signer, writable })]
+    #[acc(account, { writable, owned })]
+    #[sys(program_data, key = program_data_address())]
+    #[sys(system_program, key = system_program::ID)]
+    MigrateAccount { kind: MigratableAccount },
+
+    /// Verification accounts are migrated separately, since they are registered with their vkey
+    #[skip_program_version]
+    #[acc(payer, { signer, writable })]
+    #[acc(original_fee_payer)]
+    #[acc(verification_account, { writable, owned })]
+    #[acc(vkey_account, { writable, owned })]
+    #[sys(system_program, key = system_program::ID)]
+    MigrateVerificationAccount { verification_account_index: u8 },
+
+    // -------- Batched base commitment hashing --------
+    /// Client sends up to `MAX_BASE_COMMITMENT_BATCH_SIZE` base commitments to be hashed in a single hashing account
+    /// - `apa_target_map` and `apa_proposal` as for `StoreBaseCommitment`
+    #[acc(sender, { signer })]
+    #[acc(sender_account, { writable })]
+    #[acc(fee_payer, { writable, signer })]
+    #[acc(fee_payer_account, { writable })]
+    #[pda(pool, PoolAccount, { writable, account_info })]
+    #[acc(pool_account, { writable })]
+    #[pda(fee_collector, FeeCollectorAccount, { writable, account_info })]
+    #[acc(fee_collector_account, { writable })]
+    #[acc(sol_price_account)]
+    #[acc(token_price_account)]
+    #[pda(governor, GovernorAccount)]
+    #[pda(token_volume, TokenVolumeAccount, { writable })]
+    #[pda(pool_ledger, PoolLedgerAccount, { writable })]
+    #[acc(hashing_account, { writable })]
+    #[pda(buffer, BaseCommitmentBufferAccount, { writable })]
+    #[acc(token_program)]
+    // if `token_id = 0` { `system_program` (or `token_program` for a wrapped-SOL `sender_account`) } else { `token_program` }
+    #[sys(system_program, key = system_program::ID)]
+    #[acc(apa_target_map)]
+    #[acc(apa_proposal)]
+    StoreBaseCommitmentBatch {
+        hash_account_index: u32,
+        hash_account_bump: u8,
+        requests: Vec<BaseCommitmentHashRequest>,
+    },
+
+    // -------- Token volume limits --------
+    #[pda(token_volume, TokenVolumeAccount, { writable })]
+    #[pda(proposals, GovernanceProposalsAccount, { writable })]
+    SetTokenVolumeLimits {
+        token_id: u16,
+        deposit_limit: ElusivOption<u64>,
+        withdrawal_limit: ElusivOption<u64>,
+    },
+
+    // -------- Token delisting --------
+    #[acc(signer, { signer })]
+    #[pda(governor, GovernorAccount)]
+    #[pda(token_volume, TokenVolumeAccount, { writable })]
+    DelistToken { token_id: u16 },
+
+    #[pda(token_volume, TokenVolumeAccount, { writable })]
+    #[pda(proposals, GovernanceProposalsAccount, { writable })]
+    RelistToken { token_id: u16 },
+
+    // -------- Pool solvency --------
+    /// Verifies (permissionless) that the pool holds at least the outstanding liabilities of `token_id` and logs the ledger
+    #[pda(pool, PoolAccount, { account_info })]
+    #[acc(pool_account)]
+    #[pda(pool_ledger, PoolLedgerAccount)]
+    AuditPool { token_id: u16 },
+
+    #[pda(pool_ledger, PoolLedgerAccount, { writable })]
+    #[pda(proposals, GovernanceProposalsAccount, { writable })]
+    SetPoolOpeningBalance { token_id: u16, opening_balance: u64 },
+
     #[cfg(not(feature = "mainnet"))]
+    #[skip_program_version]
     #[acc(payer, { signer })]
     #[acc(recipient, { writable })]
     #[acc(program_account, { writable })]
@@ -292,12 +549,19 @@ pub enum ElusivInstruction {
 
     // -------- NOP --------
     /// NOP-instruction
+    #[skip_program_version]
     Nop,
 }
 
 #[cfg(feature = "elusiv-client")]
 use elusiv_types::accounts::PDAAccount;
 
+#[cfg(feature = "elusiv-client")]
+use spl_associated_token_account::get_associated_token_address;
+
+#[cfg(feature = "elusiv-client")]
+use crate::state::apa::apa_target_map_account;
+
 #[cfg(feature = "elusiv-client")]
 impl ElusivInstruction {
     pub fn store_base_commitment_sol_instruction(
@@ -323,12 +587,79 @@ impl ElusivInstruction {
             UserAccount(system_program::id()),
             WritableUserAccount(hash_account_pubkey),
             UserAccount(system_program::id()),
+            UserAccount(apa_target_map_account(&client)),
+            UserAccount(system_program::id()),
+        )
+    }
+
+    /// Deposits lamports from the wrapped-SOL associated token account of `client`
+    pub fn store_base_commitment_wrapped_sol_instruction(
+        hash_account_index: u32,
+        request: BaseCommitmentHashRequest,
+        client: Pubkey,
+        warden: Pubkey,
+    ) -> solana_program::instruction::Instruction {
+        let (hash_account_pubkey, hash_account_bump) =
+            BaseCommitmentHashingAccount::find(Some(hash_account_index));
+
+        let mut ix = ElusivInstruction::store_base_commitment_instruction(
+            hash_account_index,
+            hash_account_bump,
+            request,
+            SignerAccount(client),
+            WritableUserAccount(get_associated_token_address(
+                &client,
+                &spl_token::native_mint::ID,
+            )),
+            WritableSignerAccount(warden),
+            WritableUserAccount(warden),
+            WritableUserAccount(PoolAccount::find(None).0),
+            WritableUserAccount(FeeCollectorAccount::find(None).0),
+            UserAccount(system_program::id()),
+            UserAccount(system_program::id()),
+            WritableUserAccount(hash_account_pubkey),
+            UserAccount(spl_token::id()),
+            UserAccount(apa_target_map_account(&client)),
+            UserAccount(system_program::id()),
+        );
+
+        // `client` receives the unwrapped lamports
+        ix.accounts[0].is_writable = true;
+        ix
+    }
+
+    pub fn store_base_commitment_batch_sol_instruction(
+        hash_account_index: u32,
+        requests: Vec<BaseCommitmentHashRequest>,
+        client: Pubkey,
+        warden: Pubkey,
+    ) -> solana_program::instruction::Instruction {
+        let (hash_account_pubkey, hash_account_bump) =
+            BaseCommitmentHashingAccount::find(Some(hash_account_index));
+
+        ElusivInstruction::store_base_commitment_batch_instruction(
+            hash_account_index,
+            hash_account_bump,
+            requests,
+            SignerAccount(client),
+            WritableUserAccount(client),
+            WritableSignerAccount(warden),
+            WritableUserAccount(warden),
+            WritableUserAccount(PoolAccount::find(None).0),
+            WritableUserAccount(FeeCollectorAccount::find(None).0),
+            UserAccount(system_program::id()),
+            UserAccount(system_program::id()),
+            WritableUserAccount(hash_account_pubkey),
+            UserAccount(system_program::id()),
+            UserAccount(apa_target_map_account(&client)),
+            UserAccount(system_program::id()),
         )
     }
 
     pub fn init_verification_transfer_fee_sol_instruction(
         verification_account_index: u8,
         warden: Pubkey,
+        recipient: Pubkey,
     ) -> solana_program::instruction::Instruction {
         ElusivInstruction::init_verification_transfer_fee_instruction(
             verification_account_index,
@@ -339,6 +670,9 @@ impl ElusivInstruction {
             UserAccount(spl_token::id()),
             UserAccount(spl_token::id()),
             UserAccount(spl_token::id()),
+            UserAccount(recipient),
+            UserAccount(apa_target_map_account(&recipient)),
+            UserAccount(system_program::id()),
         )
     }
 
@@ -349,6 +683,8 @@ impl ElusivInstruction {
         warden_account: Pubkey,
         pool_account: Pubkey,
         fee_collector_account: Pubkey,
+        recipient: Pubkey,
+        apa_target: Pubkey,
     ) -> solana_program::instruction::Instruction {
         use crate::token::elusiv_token;
 
@@ -361,6 +697,9 @@ impl ElusivInstruction {
             UserAccount(elusiv_token(0).unwrap().pyth_usd_price_key),
             UserAccount(elusiv_token(token_id).unwrap().pyth_usd_price_key),
             UserAccount(spl_token::id()),
+            UserAccount(recipient),
+            UserAccount(apa_target_map_account(&apa_target)),
+            UserAccount(system_program::id()),
         )
     }
 }
@@ -3,25 +3,39 @@ use crate::bytes::{is_zero, BorshSerDeSized, ElusivOption};
 use crate::commitment::DEFAULT_COMMITMENT_BATCHING_RATE;
 use crate::error::ElusivError;
 use crate::macros::*;
-use crate::state::commitment::{BaseCommitmentBufferAccount, CommitmentHashingAccount};
+use crate::processor::governance::{execute_governance_proposal_at, verify_governance_authority};
+use crate::state::commitment::{
+    hashing_account_pda_offset, BaseCommitmentBufferAccount, CommitmentHashingAccount,
+    MAX_COMMITMENT_HASHING_ACCOUNTS_COUNT,
+};
 use crate::state::{
     fee::{FeeAccount, ProgramFee},
-    governor::{FeeCollectorAccount, GovernorAccount, PoolAccount},
+    governor::{
+        FeeCollectorAccount, GovernanceProposal, GovernanceProposalsAccount, GovernorAccount,
+        PoolAccount, PoolLedgerAccount, TokenVolumeAccount,
+    },
     nullifier::{NullifierAccount, NullifierChildAccount},
-    queue::{CommitmentQueue, CommitmentQueueAccount, Queue},
-    storage::{StorageAccount, MT_COMMITMENT_COUNT},
+    queue::{
+        CommitmentQueue, CommitmentQueueAccount, Queue, COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT,
+    },
+    storage::{
+        mt_shard, StorageAccount, MAX_COMMITMENT_SHARDS_COUNT, MAX_HISTORY_ARRAY_SIZE,
+        MT_COMMITMENT_COUNT,
+    },
 };
+use crate::token::Lamports;
 use crate::{bytes::usize_as_u32_safe, map::ElusivMap};
 use elusiv_types::{
     split_child_account_data_mut, ChildAccount, ChildAccountConfig, ParentAccount, SizedAccount,
     UnverifiedAccountInfo,
 };
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError, rent::Rent,
-    sysvar::Sysvar,
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    program_pack::Pack, rent::Rent, sysvar::Sysvar,
 };
 
 /// Opens one single instance [`elusiv_types::PDAAccount`], as long this PDA does not already exist
+#[allow(clippy::too_many_arguments)]
 pub fn open_single_instance_accounts<'a, 'b>(
     payer: &AccountInfo<'b>,
     pool_account: UnverifiedAccountInfo<'a, 'b>,
@@ -30,7 +44,10 @@ pub fn open_single_instance_accounts<'a, 'b>(
     commitment_queue_account: UnverifiedAccountInfo<'a, 'b>,
     storage_account: UnverifiedAccountInfo<'a, 'b>,
     base_commitment_buffer_account: UnverifiedAccountInfo<'a, 'b>,
+    governor: &GovernorAccount,
 ) -> ProgramResult {
+    verify_governance_authority(payer, governor)?;
+
     open_pda_account_without_offset::<PoolAccount>(
         &crate::id(),
         payer,
@@ -71,6 +88,138 @@ pub fn open_single_instance_accounts<'a, 'b>(
     Ok(())
 }
 
+/// Opens the [`TokenVolumeAccount`] (initially no token volume limits are set)
+///
+/// # Notes
+///
+/// Independent of [`open_single_instance_accounts`], so that the account can also be opened for an existing deployment.
+pub fn open_token_volume_account<'a, 'b>(
+    payer: &AccountInfo<'b>,
+    token_volume_account: UnverifiedAccountInfo<'a, 'b>,
+    governor: &GovernorAccount,
+) -> ProgramResult {
+    verify_governance_authority(payer, governor)?;
+
+    open_pda_account_without_offset::<TokenVolumeAccount>(
+        &crate::id(),
+        payer,
+        token_volume_account.get_unsafe(),
+        None,
+    )
+}
+
+/// Opens the [`PoolLedgerAccount`] (the liabilities of an existing pool are seeded with [`crate::processor::set_pool_opening_balance`])
+///
+/// # Notes
+///
+/// Independent of [`open_single_instance_accounts`], so that the account can also be opened for an existing deployment.
+pub fn open_pool_ledger_account<'a, 'b>(
+    payer: &AccountInfo<'b>,
+    pool_ledger_account: UnverifiedAccountInfo<'a, 'b>,
+    governor: &GovernorAccount,
+) -> ProgramResult {
+    verify_governance_authority(payer, governor)?;
+
+    open_pda_account_without_offset::<PoolLedgerAccount>(
+        &crate::id(),
+        payer,
+        pool_ledger_account.get_unsafe(),
+        None,
+    )
+}
+
+/// Opens the [`StorageAccount`], [`CommitmentQueueAccount`] and [`CommitmentHashingAccount`] of the next commitment shard
+///
+/// # Notes
+///
+/// - The first shard uses the accounts opened by [`open_single_instance_accounts`].
+/// - New commitments are distributed to the new shard as soon as it's opened.
+pub fn open_commitment_shard_accounts<'a, 'b>(
+    payer: &AccountInfo<'b>,
+    governor: &mut GovernorAccount,
+    commitment_hashing_account: UnverifiedAccountInfo<'a, 'b>,
+    commitment_queue_account: UnverifiedAccountInfo<'a, 'b>,
+    storage_account: UnverifiedAccountInfo<'a, 'b>,
+
+    shard: u32,
+) -> ProgramResult {
+    verify_governance_authority(payer, governor)?;
+
+    let shards_count = governor.get_commitment_shards_count().max(1);
+    guard!(
+        shard == shards_count && shard < MAX_COMMITMENT_SHARDS_COUNT,
+        ElusivError::InvalidInstructionData
+    );
+
+    open_pda_account_with_offset::<CommitmentHashingAccount>(
+        &crate::id(),
+        payer,
+        commitment_hashing_account.get_unsafe(),
+        shard,
+        None,
+    )?;
+    open_pda_account_with_offset::<CommitmentQueueAccount>(
+        &crate::id(),
+        payer,
+        commitment_queue_account.get_unsafe(),
+        shard,
+        None,
+    )?;
+    open_pda_account_with_offset::<StorageAccount>(
+        &crate::id(),
+        payer,
+        storage_account.get_unsafe(),
+        shard,
+        None,
+    )?;
+
+    pda_account!(mut storage, StorageAccount, storage_account.get_unsafe());
+    storage.set_shard(&shard);
+
+    governor.set_commitment_shards_count(&(shard + 1));
+
+    Ok(())
+}
+
+/// Opens an additional [`CommitmentHashingAccount`] for a shard, allowing for the concurrent hashing of consecutive batches
+pub fn open_commitment_hashing_account<'a, 'b>(
+    payer: &AccountInfo<'b>,
+    governor: &GovernorAccount,
+    storage_account: &mut StorageAccount,
+    commitment_hashing_account: UnverifiedAccountInfo<'a, 'b>,
+
+    shard: u32,
+    hashing_account_index: u32,
+) -> ProgramResult {
+    verify_governance_authority(payer, governor)?;
+
+    let hashing_accounts_count = storage_account.get_hashing_accounts_count().max(1);
+    guard!(
+        hashing_account_index == hashing_accounts_count
+            && hashing_account_index < MAX_COMMITMENT_HASHING_ACCOUNTS_COUNT,
+        ElusivError::InvalidInstructionData
+    );
+
+    // The new account is inserted after the last account of the ring, so it has to be the next one used
+    guard!(
+        storage_account.get_next_hashing_account_index() % hashing_accounts_count == 0,
+        ElusivError::InvalidAccountState
+    );
+
+    open_pda_account_with_offset::<CommitmentHashingAccount>(
+        &crate::id(),
+        payer,
+        commitment_hashing_account.get_unsafe(),
+        hashing_account_pda_offset(shard, hashing_account_index).unwrap(),
+        None,
+    )?;
+
+    storage_account.set_hashing_accounts_count(&(hashing_account_index + 1));
+    storage_account.set_next_hashing_account_index(&hashing_account_index);
+
+    Ok(())
+}
+
 pub fn open_nullifier_account<'b>(
     payer: &AccountInfo<'b>,
     nullifier_account: UnverifiedAccountInfo<'_, 'b>,
@@ -132,6 +281,38 @@ pub fn enable_nullifier_child_account(
     Ok(())
 }
 
+/// Enables the supplied overflow child-account for a [`CommitmentQueueAccount`], extending the capacity of the queue
+///
+/// # Notes
+///
+/// - The child-accounts have to be enabled in order.
+/// - All enabled child-accounts have to be supplied, since the elements of a wrapped-around queue are moved into the new slots.
+pub fn enable_commitment_queue_child_account<'a, 'b, 't>(
+    commitment_queue: &mut CommitmentQueueAccount<'a, 'b, 't>,
+    child_account: &'b AccountInfo<'t>,
+
+    child_index: u32,
+) -> ProgramResult {
+    guard!(
+        child_index == commitment_queue.get_overflow_accounts_count()
+            && (child_index as usize) < COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT,
+        ElusivError::InvalidInstructionData
+    );
+
+    // Note: we don't zero-check these accounts, since we will never access data that has not been set by the program
+    setup_child_account(
+        commitment_queue,
+        child_account,
+        child_index as usize,
+        false,
+        None,
+    )?;
+
+    commitment_queue.set_child_account(child_index as usize, child_account);
+
+    commitment_queue.enable_overflow_account()
+}
+
 /// Closes the active MT and activates the next one
 ///
 /// # Notes
@@ -147,7 +328,7 @@ pub fn reset_active_merkle_tree(
     active_merkle_tree_index: u32,
 ) -> ProgramResult {
     guard!(
-        storage_account.get_trees_count() == active_merkle_tree_index,
+        storage_account.active_mt_index()? == active_merkle_tree_index,
         ElusivError::InvalidInstructionData
     );
 
@@ -173,12 +354,10 @@ fn is_mt_full(
     }
 
     let commitments_count = storage_account.get_next_commitment_ptr() as usize;
-    let queue_len = queue.next_batch()?.0.len();
-    if commitments_count + queue_len >= MT_COMMITMENT_COUNT {
-        return Ok(true);
+    match queue.next_batch(storage_account.get_next_commitment_ptr()) {
+        Ok((batch, _)) => Ok(commitments_count + batch.len() >= MT_COMMITMENT_COUNT),
+        Err(_) => Ok(false),
     }
-
-    Ok(false)
 }
 
 /// Archives a closed MT by creating creating a N-SMT in an [`ArchivedTreeAccount`]
@@ -191,68 +370,202 @@ pub fn archive_closed_merkle_tree<'a>(
     closed_merkle_tree_index: u32,
 ) -> ProgramResult {
     guard!(
-        storage_account.get_trees_count() > closed_merkle_tree_index,
+        mt_shard(closed_merkle_tree_index) == storage_account.get_shard()
+            && storage_account.active_mt_index()? > closed_merkle_tree_index,
         ElusivError::InvalidInstructionData
     );
     panic!("N-SMT not implemented yet");
 }
 
-/// Setup the [`GovernorAccount`] with the default values
+/// Grows the root history of the [`StorageAccount`] to `len` roots
 ///
-/// # Note
+/// # Notes
 ///
-/// There is no way of upgrading it atm.
+/// - Growing the account by more than [`solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE`] bytes requires multiple calls.
+/// - The additional rent is funded by the `authority`.
+pub fn grow_root_history<'a>(
+    authority: &AccountInfo<'a>,
+    governor: &GovernorAccount,
+    storage_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+
+    len: u32,
+) -> ProgramResult {
+    verify_governance_authority(authority, governor)?;
+
+    let previous_len = {
+        pda_account!(storage, StorageAccount, storage_account);
+        storage.root_history_len()
+    };
+    guard!(
+        len as usize > previous_len && len as usize <= MAX_HISTORY_ARRAY_SIZE,
+        ElusivError::InvalidInstructionData
+    );
+
+    let new_len = realloc_pda_account::<StorageAccount>(
+        authority,
+        storage_account,
+        system_program,
+        len as usize,
+    )?;
+
+    pda_account!(mut storage, StorageAccount, storage_account);
+    guard!(
+        storage.root_history_len() == new_len,
+        ElusivError::InvalidAccountState
+    );
+    storage.reorder_root_history(previous_len)
+}
+
+/// Setup the [`GovernorAccount`] with the default values and the [`GovernanceProposalsAccount`]
+///
+/// # Notes
+///
+/// Only the upgrade authority of the program can setup the governor (to prevent front-running the deployment).
+/// The `payer` becomes the governance authority.
 pub fn setup_governor_account<'b>(
     payer: &AccountInfo<'b>,
     governor_account: UnverifiedAccountInfo<'_, 'b>,
+    proposals_account: UnverifiedAccountInfo<'_, 'b>,
+    program_data: &AccountInfo,
 ) -> ProgramResult {
+    verify_upgrade_authority(payer, program_data)?;
+
     open_pda_account_without_offset::<GovernorAccount>(
         &crate::id(),
         payer,
         governor_account.get_unsafe(),
         None,
     )?;
+    open_pda_account_without_offset::<GovernanceProposalsAccount>(
+        &crate::id(),
+        payer,
+        proposals_account.get_unsafe(),
+        None,
+    )?;
 
     pda_account!(mut governor, GovernorAccount, governor_account.get_unsafe());
     governor.set_commitment_batching_rate(&usize_as_u32_safe(DEFAULT_COMMITMENT_BATCHING_RATE));
+    governor.set_authority(&Some(*payer.key).into());
+    governor.set_program_version(&u32::from(crate::PROGRAM_VERSION));
+    governor.set_commitment_shards_count(&1);
 
     Ok(())
 }
 
 /// Changes the state of the [`GovernorAccount`]
+///
+/// # Notes
+///
+/// Requires an unlocked [`GovernanceProposal::GovernorState`] proposal.
 pub fn upgrade_governor_state(
     _authority: &AccountInfo,
-    _governor_account: &mut GovernorAccount,
+    governor_account: &mut GovernorAccount,
     _commitment_queue: &CommitmentQueueAccount,
+    proposals: &mut GovernanceProposalsAccount,
+
+    fee_version: u32,
+    batching_rate: u32,
+) -> ProgramResult {
+    upgrade_governor_state_at(
+        governor_account,
+        proposals,
+        fee_version,
+        batching_rate,
+        current_timestamp()?,
+    )
+}
 
-    _fee_version: u32,
-    _batching_rate: u32,
+fn upgrade_governor_state_at(
+    governor_account: &mut GovernorAccount,
+    proposals: &mut GovernanceProposalsAccount,
+    fee_version: u32,
+    batching_rate: u32,
+    timestamp: u64,
 ) -> ProgramResult {
-    todo!("Not implemented yet");
+    // Fee versions are changed using `init_new_fee_version`
+    guard!(
+        fee_version == governor_account.get_fee_version(),
+        ElusivError::InvalidFeeVersion
+    );
+
     // TODO: changes in the batching rate are only possible when checking the commitment queue
-    // TODO: fee changes require empty queues
+    execute_governance_proposal_at(
+        proposals,
+        &GovernanceProposal::GovernorState {
+            fee_version,
+            batching_rate,
+        },
+        timestamp,
+    )?;
+
+    governor_account.set_commitment_batching_rate(&batching_rate);
+
+    Ok(())
 }
 
-/// Setup a new [`FeeAccount`]
+/// Setup a new [`FeeAccount`] and activates its fee version
 ///
-/// # Note
+/// # Notes
 ///
-/// There is no way of upgrading the program fees atm.
+/// - The genesis fee of the current fee version is set by the governance authority.
+/// - The next fee version (`fee_version + 1`) requires an unlocked [`GovernanceProposal::NewFeeVersion`] proposal.
+/// - Requests using a previous fee version are still computed using the previous [`FeeAccount`].
 pub fn init_new_fee_version<'b>(
     payer: &AccountInfo<'b>,
     governor: &mut GovernorAccount,
-    mut new_fee_account: UnverifiedAccountInfo<'_, 'b>,
+    new_fee_account: UnverifiedAccountInfo<'_, 'b>,
+    proposals: &mut GovernanceProposalsAccount,
 
     fee_version: u32,
     program_fee: ProgramFee,
 ) -> ProgramResult {
-    // Note: we have no upgrade-authroity check here since with the current setup it's impossible to have a fee version higher than zero, so will be added once that changes
-    guard!(
-        fee_version == governor.get_fee_version(),
-        ElusivError::InvalidFeeVersion
-    );
+    let timestamp = if fee_version == governor.get_fee_version() {
+        None
+    } else {
+        Some(current_timestamp()?)
+    };
+
+    init_new_fee_version_at(
+        payer,
+        governor,
+        new_fee_account,
+        proposals,
+        fee_version,
+        program_fee,
+        timestamp,
+    )
+}
+
+fn init_new_fee_version_at<'b>(
+    payer: &AccountInfo<'b>,
+    governor: &mut GovernorAccount,
+    mut new_fee_account: UnverifiedAccountInfo<'_, 'b>,
+    proposals: &mut GovernanceProposalsAccount,
+    fee_version: u32,
+    program_fee: ProgramFee,
+    timestamp: Option<u64>,
+) -> ProgramResult {
     guard!(program_fee.is_valid(), ElusivError::InvalidInstructionData);
 
+    if fee_version == governor.get_fee_version() {
+        verify_governance_authority(payer, governor)?;
+    } else {
+        guard!(
+            Some(fee_version) == governor.get_fee_version().checked_add(1),
+            ElusivError::InvalidFeeVersion
+        );
+
+        execute_governance_proposal_at(
+            proposals,
+            &GovernanceProposal::NewFeeVersion {
+                fee_version,
+                program_fee,
+            },
+            timestamp.ok_or(ElusivError::InvalidAccountState)?,
+        )?;
+    }
+
     open_pda_account_with_offset::<FeeAccount>(
         &crate::id(),
         payer,
@@ -263,11 +576,89 @@ pub fn init_new_fee_version<'b>(
 
     pda_account!(mut fee_account, FeeAccount, new_fee_account.get_safe()?);
     fee_account.set_program_fee(&program_fee);
+    governor.set_fee_version(&fee_version);
     governor.set_program_fee(&program_fee);
 
     Ok(())
 }
 
+/// Verifies that the [`PoolAccount`] holds at least the outstanding liabilities of `token_id` (tracked in the [`PoolLedgerAccount`])
+///
+/// # Notes
+///
+/// Can be called by anyone, the ledger and the pool balance are logged.
+/// The rent-exemption minimum of the [`PoolAccount`] is not part of its Lamports balance.
+pub fn audit_pool(
+    pool: &AccountInfo,
+    pool_account: &AccountInfo,
+    pool_ledger: &PoolLedgerAccount,
+
+    token_id: u16,
+) -> ProgramResult {
+    audit_pool_at(
+        pool,
+        pool_account,
+        pool_ledger,
+        token_id,
+        rent_exemption_minimum(pool.data_len())?,
+    )
+}
+
+fn audit_pool_at(
+    pool: &AccountInfo,
+    pool_account: &AccountInfo,
+    pool_ledger: &PoolLedgerAccount,
+    token_id: u16,
+    pool_rent: Lamports,
+) -> ProgramResult {
+    verify_program_token_account(pool, pool_account, token_id)?;
+
+    let balance = pool_balance(pool, pool_account, token_id, pool_rent)?;
+    let ledger = pool_ledger.get_balances(token_id as usize);
+    let liabilities = ledger.liabilities();
+
+    solana_program::msg!(
+        "Pool {}: balance {}, liabilities {} (opening balance {}, deposited {}, withdrawn {}, fees reserved {}, fees paid {})",
+        token_id,
+        balance,
+        liabilities,
+        ledger.opening_balance,
+        ledger.deposited,
+        ledger.withdrawn,
+        ledger.fees_reserved,
+        ledger.fees_paid
+    );
+
+    // More has flowed out of the pool than into it
+    guard!(liabilities >= 0, ElusivError::PoolInsolvent);
+
+    guard!(
+        i128::from(balance) >= liabilities,
+        ElusivError::PoolInsolvent
+    );
+
+    Ok(())
+}
+
+fn pool_balance(
+    pool: &AccountInfo,
+    pool_account: &AccountInfo,
+    token_id: u16,
+    pool_rent: Lamports,
+) -> Result<u64, ProgramError> {
+    if token_id == 0 {
+        return Ok(pool.lamports().saturating_sub(pool_rent.0));
+    }
+
+    guard!(
+        *pool_account.owner == spl_token::ID,
+        ElusivError::InvalidAccount
+    );
+    let account = spl_token::state::Account::unpack(&pool_account.data.borrow())?;
+
+    Ok(account.amount)
+}
+
 /// Closes a program owned account in devnet and localhost
 ///
 /// # Note
@@ -371,14 +762,182 @@ fn verify_extern_data_account(
 mod tests {
     use super::*;
     use crate::{
-        macros::account_info,
+        macros::{account_info, signing_test_account_info},
         processor::CommitmentHashRequest,
-        state::{program_account::SizedAccount, queue::RingQueue, storage::StorageChildAccount},
+        state::{
+            governor::{PendingGovernanceProposal, PoolFlow, MAX_GOVERNANCE_SIGNERS},
+            program_account::{PDAAccount, SizedAccount},
+            queue::{CommitmentQueueChildAccount, RingQueue},
+            storage::{shard_mt_index, StorageChildAccount},
+        },
+        token::elusiv_token,
         types::U256,
     };
     use assert_matches::assert_matches;
     use elusiv_types::ProgramAccount;
     use solana_program::pubkey::Pubkey;
+    use spl_associated_token_account::get_associated_token_address;
+
+    #[test]
+    fn test_open_commitment_shard_accounts() {
+        signing_test_account_info!(authority);
+        signing_test_account_info!(invalid_authority);
+        zero_program_account!(mut governor, GovernorAccount);
+        governor.set_authority(&Some(*authority.key).into());
+        governor.set_commitment_shards_count(&1);
+
+        account_info!(hashing, CommitmentHashingAccount::find(Some(1)).0, vec![]);
+        account_info!(queue, CommitmentQueueAccount::find(Some(1)).0, vec![]);
+        account_info!(
+            storage,
+            StorageAccount::find(Some(1)).0,
+            vec![0; StorageAccount::SIZE]
+        );
+        let accounts = || {
+            (
+                UnverifiedAccountInfo::new(&hashing),
+                UnverifiedAccountInfo::new(&queue),
+                UnverifiedAccountInfo::new(&storage),
+            )
+        };
+
+        // Invalid authority
+        let (h, q, s) = accounts();
+        assert_matches!(
+            open_commitment_shard_accounts(&invalid_authority, &mut governor, h, q, s, 1),
+            Err(_)
+        );
+
+        // Shards are opened in order
+        for shard in [0, 2] {
+            let (h, q, s) = accounts();
+            assert_matches!(
+                open_commitment_shard_accounts(&authority, &mut governor, h, q, s, shard),
+                Err(_)
+            );
+        }
+
+        let (h, q, s) = accounts();
+        assert_matches!(
+            open_commitment_shard_accounts(&authority, &mut governor, h, q, s, 1),
+            Ok(())
+        );
+        assert_eq!(governor.get_commitment_shards_count(), 2);
+
+        {
+            let mut data = storage.data.borrow_mut();
+            let storage_account = StorageAccount::new(&mut data).unwrap();
+            assert_eq!(storage_account.get_shard(), 1);
+            assert_eq!(
+                storage_account.active_mt_index().unwrap(),
+                shard_mt_index(1, 0).unwrap()
+            );
+        }
+
+        // Max. number of shards
+        governor.set_commitment_shards_count(&MAX_COMMITMENT_SHARDS_COUNT);
+        let (h, q, s) = accounts();
+        assert_matches!(
+            open_commitment_shard_accounts(
+                &authority,
+                &mut governor,
+                h,
+                q,
+                s,
+                MAX_COMMITMENT_SHARDS_COUNT
+            ),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_open_commitment_hashing_account() {
+        signing_test_account_info!(authority);
+        signing_test_account_info!(invalid_authority);
+        zero_program_account!(mut governor, GovernorAccount);
+        governor.set_authority(&Some(*authority.key).into());
+        parent_account!(mut storage_account, StorageAccount);
+
+        account_info!(
+            hashing,
+            CommitmentHashingAccount::find(hashing_account_pda_offset(0, 1)).0,
+            vec![]
+        );
+
+        // Invalid authority
+        assert_matches!(
+            open_commitment_hashing_account(
+                &invalid_authority,
+                &governor,
+                &mut storage_account,
+                UnverifiedAccountInfo::new(&hashing),
+                0,
+                1
+            ),
+            Err(_)
+        );
+
+        // Hashing accounts are opened in order
+        for hashing_account_index in [0, 2] {
+            assert_matches!(
+                open_commitment_hashing_account(
+                    &authority,
+                    &governor,
+                    &mut storage_account,
+                    UnverifiedAccountInfo::new(&hashing),
+                    0,
+                    hashing_account_index
+                ),
+                Err(_)
+            );
+        }
+
+        // The next batch has to be hashed by the last account of the ring
+        storage_account.set_next_hashing_account_index(&1);
+        storage_account.set_hashing_accounts_count(&2);
+        assert_matches!(
+            open_commitment_hashing_account(
+                &authority,
+                &governor,
+                &mut storage_account,
+                UnverifiedAccountInfo::new(&hashing),
+                0,
+                2
+            ),
+            Err(_)
+        );
+
+        storage_account.set_next_hashing_account_index(&0);
+        storage_account.set_hashing_accounts_count(&0);
+        assert_matches!(
+            open_commitment_hashing_account(
+                &authority,
+                &governor,
+                &mut storage_account,
+                UnverifiedAccountInfo::new(&hashing),
+                0,
+                1
+            ),
+            Ok(())
+        );
+        assert_eq!(storage_account.get_hashing_accounts_count(), 2);
+        assert_eq!(storage_account.get_next_hashing_account_index(), 1);
+
+        // Max. number of hashing accounts
+        storage_account.set_hashing_accounts_count(&MAX_COMMITMENT_HASHING_ACCOUNTS_COUNT);
+        storage_account.set_next_hashing_account_index(&0);
+        assert_matches!(
+            open_commitment_hashing_account(
+                &authority,
+                &governor,
+                &mut storage_account,
+                UnverifiedAccountInfo::new(&hashing),
+                0,
+                MAX_COMMITMENT_HASHING_ACCOUNTS_COUNT
+            ),
+            Err(_)
+        );
+    }
 
     #[test]
     fn test_enable_storage_child_account() {
@@ -464,6 +1023,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_enable_commitment_queue_child_account() {
+        let mut data = vec![0; CommitmentQueueAccount::SIZE];
+        let mut queue = CommitmentQueueAccount::new(&mut data).unwrap();
+
+        account_info!(
+            child_account,
+            Pubkey::new_unique(),
+            vec![0; CommitmentQueueChildAccount::SIZE]
+        );
+
+        // Child-accounts have to be enabled in order
+        assert_matches!(
+            enable_commitment_queue_child_account(&mut queue, &child_account, 1),
+            Err(_)
+        );
+
+        // Queue wraps around (the element in front of `tail` is moved into the child-account)
+        let mut q = CommitmentQueue::new(&mut queue);
+        for _ in 0..CommitmentQueue::CAPACITY {
+            q.enqueue(CommitmentHashRequest::default()).unwrap();
+        }
+        q.remove(CommitmentQueue::CAPACITY - 1).unwrap();
+        let last = CommitmentHashRequest {
+            min_batching_rate: 1,
+            ..Default::default()
+        };
+        q.enqueue(last).unwrap();
+
+        enable_commitment_queue_child_account(&mut queue, &child_account, 0).unwrap();
+        assert_eq!(queue.get_overflow_accounts_count(), 1);
+        assert_eq!(child_account.data.borrow()[0], 1);
+
+        let q = CommitmentQueue::new(&mut queue);
+        assert_eq!(q.len(), 2);
+        assert_eq!(q.view(1).unwrap(), last);
+
+        // Account already is use
+        assert_matches!(
+            enable_commitment_queue_child_account(&mut queue, &child_account, 1),
+            Err(_)
+        );
+
+        // Max. number of child-accounts
+        queue.set_overflow_accounts_count(&(COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT as u32));
+        account_info!(
+            child_account,
+            Pubkey::new_unique(),
+            vec![0; CommitmentQueueChildAccount::SIZE]
+        );
+        assert_matches!(
+            enable_commitment_queue_child_account(
+                &mut queue,
+                &child_account,
+                COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT as u32
+            ),
+            Err(_)
+        );
+    }
+
     #[test]
     fn test_is_mt_full() {
         let mut data = vec![0; StorageAccount::SIZE];
@@ -495,6 +1114,10 @@ mod tests {
 
         storage_account.set_next_commitment_ptr(&(MT_COMMITMENT_COUNT as u32 - 2));
         assert!(is_mt_full(&storage_account, &queue).unwrap());
+
+        // The remaining place is filled by a batch below the min_batching_rate of the next commitment
+        storage_account.set_next_commitment_ptr(&(MT_COMMITMENT_COUNT as u32 - 1));
+        assert!(is_mt_full(&storage_account, &queue).unwrap());
     }
 
     #[test]
@@ -518,13 +1141,139 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn test_upgrade_governor_state() {
-        test_account_info!(authority, 0);
         zero_program_account!(mut governor_account, GovernorAccount);
-        zero_program_account!(commitment_queue, CommitmentQueueAccount);
+        zero_program_account!(mut proposals, GovernanceProposalsAccount);
+
+        let proposal = GovernanceProposal::GovernorState {
+            fee_version: 0,
+            batching_rate: 1,
+        };
+        proposals.set_proposals(
+            0,
+            &Some(PendingGovernanceProposal {
+                proposal,
+                approvals: [false; MAX_GOVERNANCE_SIGNERS],
+                unlock_timestamp: Some(100).into(),
+            })
+            .into(),
+        );
+
+        // Invalid fee version
+        assert_matches!(
+            upgrade_governor_state_at(&mut governor_account, &mut proposals, 1, 1, 100),
+            Err(_)
+        );
+
+        // Timelock
+        assert_matches!(
+            upgrade_governor_state_at(&mut governor_account, &mut proposals, 0, 1, 99),
+            Err(_)
+        );
+
+        // Mismatching proposal
+        assert_matches!(
+            upgrade_governor_state_at(&mut governor_account, &mut proposals, 0, 2, 100),
+            Err(_)
+        );
 
-        upgrade_governor_state(&authority, &mut governor_account, &commitment_queue, 1, 1).unwrap();
+        assert_matches!(
+            upgrade_governor_state_at(&mut governor_account, &mut proposals, 0, 1, 100),
+            Ok(())
+        );
+        assert_eq!(governor_account.get_commitment_batching_rate(), 1);
+
+        // Proposals are only executed once
+        assert_matches!(
+            upgrade_governor_state_at(&mut governor_account, &mut proposals, 0, 1, 100),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_audit_pool_lamports() {
+        test_account_info!(pool, 0);
+        test_account_info!(any, 0);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
+        let rent = Lamports(1_000);
+        let balance = pool.lamports() - rent.0;
+
+        pool_ledger.track(0, PoolFlow::Deposit, balance).unwrap();
+        assert_matches!(audit_pool_at(&pool, &pool, &pool_ledger, 0, rent), Ok(()));
+
+        // Invalid pool_account
+        assert_matches!(audit_pool_at(&pool, &any, &pool_ledger, 0, rent), Err(_));
+
+        // The rent-exemption minimum is not part of the balance
+        pool_ledger.track(0, PoolFlow::FeeReservation, 1).unwrap();
+        assert_matches!(
+            audit_pool_at(&pool, &pool, &pool_ledger, 0, rent),
+            Err(e) if e == ElusivError::PoolInsolvent.into()
+        );
+        assert_matches!(
+            audit_pool_at(&pool, &pool, &pool_ledger, 0, Lamports(rent.0 - 1)),
+            Ok(())
+        );
+
+        pool_ledger.track(0, PoolFlow::FeePayment, 1).unwrap();
+        assert_matches!(audit_pool_at(&pool, &pool, &pool_ledger, 0, rent), Ok(()));
+
+        // Outflows exceeding the inflows
+        pool_ledger
+            .track(0, PoolFlow::Withdrawal, balance + 1)
+            .unwrap();
+        assert_matches!(
+            audit_pool_at(&pool, &pool, &pool_ledger, 0, rent),
+            Err(e) if e == ElusivError::PoolInsolvent.into()
+        );
+
+        // Opening balance
+        let mut ledger = pool_ledger.get_balances(0);
+        ledger.opening_balance = 1;
+        pool_ledger.set_balances(0, &ledger);
+        assert_matches!(audit_pool_at(&pool, &pool, &pool_ledger, 0, rent), Ok(()));
+    }
+
+    #[test]
+    fn test_audit_pool_token() {
+        test_account_info!(pool, 0);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
+
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: elusiv_token(1).unwrap().mint,
+            owner: *pool.key,
+            amount: 1_000,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        account_info!(
+            pool_account,
+            get_associated_token_address(pool.key, &elusiv_token(1).unwrap().mint),
+            data,
+            spl_token::ID,
+            false
+        );
+
+        pool_ledger.track(1, PoolFlow::Deposit, 1_500).unwrap();
+        pool_ledger.track(1, PoolFlow::Withdrawal, 500).unwrap();
+        assert_matches!(
+            audit_pool_at(&pool, &pool_account, &pool_ledger, 1, Lamports(0)),
+            Ok(())
+        );
+
+        pool_ledger.track(1, PoolFlow::FeeReservation, 1).unwrap();
+        assert_matches!(
+            audit_pool_at(&pool, &pool_account, &pool_ledger, 1, Lamports(0)),
+            Err(e) if e == ElusivError::PoolInsolvent.into()
+        );
+
+        // Lamports are audited separately
+        assert_matches!(
+            audit_pool_at(&pool, &pool, &pool_ledger, 0, Lamports(0)),
+            Ok(())
+        );
     }
 
     #[test]
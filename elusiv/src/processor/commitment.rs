@@ -1,33 +1,36 @@
-use super::utils::{close_account, open_pda_account_with_offset};
+use super::utils::{close_account, open_pda_account, MATH_ERR};
 use crate::bytes::usize_as_u32_safe;
 use crate::commitment::{
-    commitment_hash_computation_instructions, commitments_per_batch,
-    compute_base_commitment_hash_partial, compute_commitment_hash_partial,
-    BaseCommitmentHashComputation, MAX_HT_COMMITMENTS,
+    commitments_per_batch, compute_base_commitment_hash_partial, compute_commitment_hash_partial,
+    MAX_COMMITMENT_BATCHING_RATE, MAX_HT_COMMITMENTS,
 };
 use crate::error::ElusivError;
-use crate::fields::{fr_to_u256_le, is_element_scalar_field, u256_to_big_uint, u256_to_fr_skip_mr};
+use crate::fields::{is_element_scalar_field, u256_to_big_uint, u256_to_fr_skip_mr};
 use crate::macros::{guard, pda_account, BorshSerDeSized};
 use crate::processor::utils::{
-    transfer_lamports_from_pda_checked, transfer_token, transfer_token_from_pda,
-    transfer_with_system_program, verify_program_token_account,
+    current_timestamp, transfer_lamports_from_pda_checked, transfer_token, transfer_token_from_pda,
+    transfer_with_system_program, unwrap_lamports, verify_program_token_account,
 };
+use crate::state::apa::{enforce_apa_policy, ApaAction};
 use crate::state::commitment::{
     BaseCommitmentBufferAccount, BaseCommitmentHashingAccount, CommitmentHashingAccount,
+    MAX_BASE_COMMITMENT_BATCH_SIZE,
 };
-use crate::state::governor::FeeCollectorAccount;
-use crate::state::storage::{StorageAccount, MT_COMMITMENT_COUNT};
+use crate::state::governor::{
+    FeeCollectorAccount, PoolFlow, PoolLedgerAccount, TokenVolumeAccount,
+};
+use crate::state::program_account::ReallocAccount;
+use crate::state::storage::{commitment_shard, StorageAccount, MT_COMMITMENT_COUNT};
 use crate::state::{
     fee::FeeAccount,
     governor::GovernorAccount,
     queue::{CommitmentQueue, CommitmentQueueAccount, Queue, RingQueue},
 };
-use crate::token::{Token, TokenPrice};
+use crate::token::{Lamports, Token, TokenPrice};
 use crate::types::{RawU256, U256};
 use ark_bn254::Fr;
 use ark_ff::BigInteger256;
 use borsh::{BorshDeserialize, BorshSerialize};
-use elusiv_computation::PartialComputation;
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult};
 
 #[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Clone, Debug)]
@@ -88,7 +91,7 @@ pub const ZERO_COMMITMENT_RAW: U256 = [
 ///     - performs the hash computation,
 ///     - swaps fee from token into lamports (for tx compensation of the commitment hash).
 #[allow(clippy::too_many_arguments)]
-pub fn store_base_commitment<'a>(
+pub fn store_base_commitment<'a, 'b>(
     sender: &AccountInfo<'a>,
     sender_account: &AccountInfo<'a>,
     fee_payer: &AccountInfo<'a>,
@@ -98,64 +101,264 @@ pub fn store_base_commitment<'a>(
     fee_collector: &AccountInfo<'a>,
     fee_collector_account: &AccountInfo<'a>,
 
-    sol_usd_price_account: &AccountInfo,
-    token_usd_price_account: &AccountInfo,
+    sol_usd_price_account: &AccountInfo<'a>,
+    token_usd_price_account: &AccountInfo<'a>,
 
-    governor: &GovernorAccount,
+    governor: &GovernorAccount<'b>,
+    token_volume: &mut TokenVolumeAccount<'b>,
+    pool_ledger: &mut PoolLedgerAccount<'b>,
     hashing_account: &AccountInfo<'a>,
-    base_commitment_buffer: &mut BaseCommitmentBufferAccount,
+    base_commitment_buffer: &mut BaseCommitmentBufferAccount<'b>,
     token_program: &AccountInfo<'a>,
     system_program: &AccountInfo<'a>,
+    apa_target_map: &AccountInfo<'a>,
+    apa_proposal: &AccountInfo<'a>,
 
     hash_account_index: u32,
     hash_account_bump: u8,
     request: BaseCommitmentHashRequest,
 ) -> ProgramResult {
-    let token_id = request.token_id;
-    let amount = Token::new_checked(token_id, request.amount)?;
-    let price = TokenPrice::new(sol_usd_price_account, token_usd_price_account, token_id)?;
+    store_base_commitments(
+        StoreBaseCommitmentAccounts {
+            sender,
+            sender_account,
+            fee_payer,
+            fee_payer_account,
+            pool,
+            pool_account,
+            fee_collector,
+            fee_collector_account,
+            sol_usd_price_account,
+            token_usd_price_account,
+            governor,
+            token_volume,
+            pool_ledger,
+            hashing_account,
+            base_commitment_buffer,
+            token_program,
+            system_program,
+            apa_target_map,
+            apa_proposal,
+        },
+        hash_account_index,
+        hash_account_bump,
+        &[request],
+        current_timestamp()?,
+    )
+}
 
-    guard!(
-        is_element_scalar_field(u256_to_big_uint(&request.base_commitment.skip_mr())),
-        ElusivError::NonScalarValue
-    );
-    guard!(
-        is_element_scalar_field(u256_to_big_uint(&request.commitment.skip_mr())),
-        ElusivError::NonScalarValue
-    );
+/// Stores up to [`MAX_BASE_COMMITMENT_BATCH_SIZE`] base commitments (of the same token) in a single [`BaseCommitmentHashingAccount`]
+///
+/// # Notes
+///
+/// The base commitments are hashed sequentially and the fee-payer's signature overhead is shared by all base commitments (see [`crate::state::fee::ProgramFee::batched_base_commitment_hash_computation_fee`]).
+#[allow(clippy::too_many_arguments)]
+pub fn store_base_commitment_batch<'a, 'b>(
+    sender: &AccountInfo<'a>,
+    sender_account: &AccountInfo<'a>,
+    fee_payer: &AccountInfo<'a>,
+    fee_payer_account: &AccountInfo<'a>,
+    pool: &AccountInfo<'a>,
+    pool_account: &AccountInfo<'a>,
+    fee_collector: &AccountInfo<'a>,
+    fee_collector_account: &AccountInfo<'a>,
+
+    sol_usd_price_account: &AccountInfo<'a>,
+    token_usd_price_account: &AccountInfo<'a>,
+
+    governor: &GovernorAccount<'b>,
+    token_volume: &mut TokenVolumeAccount<'b>,
+    pool_ledger: &mut PoolLedgerAccount<'b>,
+    hashing_account: &AccountInfo<'a>,
+    base_commitment_buffer: &mut BaseCommitmentBufferAccount<'b>,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    apa_target_map: &AccountInfo<'a>,
+    apa_proposal: &AccountInfo<'a>,
+
+    hash_account_index: u32,
+    hash_account_bump: u8,
+    requests: Vec<BaseCommitmentHashRequest>,
+) -> ProgramResult {
+    store_base_commitments(
+        StoreBaseCommitmentAccounts {
+            sender,
+            sender_account,
+            fee_payer,
+            fee_payer_account,
+            pool,
+            pool_account,
+            fee_collector,
+            fee_collector_account,
+            sol_usd_price_account,
+            token_usd_price_account,
+            governor,
+            token_volume,
+            pool_ledger,
+            hashing_account,
+            base_commitment_buffer,
+            token_program,
+            system_program,
+            apa_target_map,
+            apa_proposal,
+        },
+        hash_account_index,
+        hash_account_bump,
+        &requests,
+        current_timestamp()?,
+    )
+}
 
-    // TODO: verify commitment-index in the next SDK version
+/// The accounts used by [`store_base_commitment`] and [`store_base_commitment_batch`]
+struct StoreBaseCommitmentAccounts<'a, 'b, 'c> {
+    sender: &'b AccountInfo<'a>,
+    sender_account: &'b AccountInfo<'a>,
+    fee_payer: &'b AccountInfo<'a>,
+    fee_payer_account: &'b AccountInfo<'a>,
+    pool: &'b AccountInfo<'a>,
+    pool_account: &'b AccountInfo<'a>,
+    fee_collector: &'b AccountInfo<'a>,
+    fee_collector_account: &'b AccountInfo<'a>,
+
+    sol_usd_price_account: &'b AccountInfo<'a>,
+    token_usd_price_account: &'b AccountInfo<'a>,
+
+    governor: &'b GovernorAccount<'c>,
+    token_volume: &'b mut TokenVolumeAccount<'c>,
+    pool_ledger: &'b mut PoolLedgerAccount<'c>,
+    hashing_account: &'b AccountInfo<'a>,
+    base_commitment_buffer: &'b mut BaseCommitmentBufferAccount<'c>,
+    token_program: &'b AccountInfo<'a>,
+    system_program: &'b AccountInfo<'a>,
+    apa_target_map: &'b AccountInfo<'a>,
+    apa_proposal: &'b AccountInfo<'a>,
+}
+
+fn store_base_commitments(
+    accounts: StoreBaseCommitmentAccounts,
+    hash_account_index: u32,
+    hash_account_bump: u8,
+    requests: &[BaseCommitmentHashRequest],
+    timestamp: u64,
+) -> ProgramResult {
+    let StoreBaseCommitmentAccounts {
+        sender,
+        sender_account,
+        fee_payer,
+        fee_payer_account,
+        pool,
+        pool_account,
+        fee_collector,
+        fee_collector_account,
+        sol_usd_price_account,
+        token_usd_price_account,
+        governor,
+        token_volume,
+        pool_ledger,
+        hashing_account,
+        base_commitment_buffer,
+        token_program,
+        system_program,
+        apa_target_map,
+        apa_proposal,
+    } = accounts;
 
-    // Zero-commitment cannot be inserted by user
     guard!(
-        u256_to_fr_skip_mr(&request.base_commitment.reduce()) != ZERO_BASE_COMMITMENT,
+        !requests.is_empty() && requests.len() <= MAX_BASE_COMMITMENT_BATCH_SIZE,
         ElusivError::InvalidInstructionData
     );
 
+    let token_id = requests[0].token_id;
+
+    // Inactive and delisted tokens can only be withdrawn
     guard!(
-        request.fee_version == governor.get_fee_version(),
-        ElusivError::InvalidFeeVersion
-    );
-    guard!(
-        request.min_batching_rate == governor.get_commitment_batching_rate(),
-        ElusivError::InvalidBatchingRate
+        token_volume.accepts_deposits(token_id)?,
+        ElusivError::UnsupportedToken
     );
 
+    // Deposits of APA-targets are restricted by the warden network
+    enforce_apa_policy(
+        sender.key,
+        apa_target_map,
+        apa_proposal,
+        ApaAction::Deposit,
+        token_id,
+    )?;
+
+    let price = TokenPrice::new(sol_usd_price_account, token_usd_price_account, token_id)?;
     let fee = governor.get_program_fee();
-    let subvention = fee
-        .base_commitment_subvention
-        .into_token(&price, token_id)?;
-    let computation_fee = (fee.base_commitment_hash_computation_fee()
-        + fee.commitment_hash_computation_fee(request.min_batching_rate))?;
+
+    let mut amount = Token::new(token_id, 0);
+    let mut subvention = Token::new(token_id, 0);
+    let mut computation_fee = Lamports(0);
+    let mut network_fee = Token::new(token_id, 0);
+
+    for request in requests {
+        guard!(
+            request.token_id == token_id,
+            ElusivError::InvalidInstructionData
+        );
+        let request_amount = Token::new_checked(token_id, request.amount)?;
+
+        guard!(
+            is_element_scalar_field(u256_to_big_uint(&request.base_commitment.skip_mr())),
+            ElusivError::NonScalarValue
+        );
+        guard!(
+            is_element_scalar_field(u256_to_big_uint(&request.commitment.skip_mr())),
+            ElusivError::NonScalarValue
+        );
+
+        // TODO: verify commitment-index in the next SDK version
+
+        // Zero-commitment cannot be inserted by user
+        guard!(
+            u256_to_fr_skip_mr(&request.base_commitment.reduce()) != ZERO_BASE_COMMITMENT,
+            ElusivError::InvalidInstructionData
+        );
+
+        guard!(
+            request.fee_version == governor.get_fee_version(),
+            ElusivError::InvalidFeeVersion
+        );
+        guard!(
+            request.min_batching_rate >= governor.get_commitment_batching_rate()
+                && request.min_batching_rate as usize <= MAX_COMMITMENT_BATCHING_RATE,
+            ElusivError::InvalidBatchingRate
+        );
+
+        amount = (amount + request_amount)?;
+        subvention = (subvention
+            + fee
+                .base_commitment_subvention
+                .into_token(&price, token_id)?)?;
+        computation_fee = ((computation_fee
+            + fee.batched_base_commitment_hash_computation_fee(requests.len()))?
+            + fee.commitment_hash_computation_fee(request.min_batching_rate))?;
+        network_fee = (network_fee
+            + Token::new(
+                token_id,
+                fee.base_commitment_network_fee
+                    .calc(request_amount.amount()),
+            ))?;
+    }
     let computation_fee_token = computation_fee.into_token(&price, token_id)?;
-    let network_fee = Token::new(
-        token_id,
-        fee.base_commitment_network_fee.calc(amount.amount()),
-    );
+
+    token_volume.track_deposit(token_id, amount.amount(), timestamp)?;
+    pool_ledger.track(token_id, PoolFlow::Deposit, amount.amount())?;
+    pool_ledger.track(0, PoolFlow::FeeReservation, computation_fee.0)?;
 
     verify_program_token_account(pool, pool_account, token_id)?;
     verify_program_token_account(fee_collector, fee_collector_account, token_id)?;
 
+    // Lamports can also be deposited from a wrapped-SOL `sender_account`, which is unwrapped into `sender` first
+    let token_program = if token_id == 0 && *token_program.key == spl_token::ID {
+        unwrap_lamports(sender, sender_account, token_program)?;
+        system_program
+    } else {
+        token_program
+    };
+
     // `sender` transfers `computation_fee_token` - `subvention` to `fee_payer` (token)
     transfer_token(
         sender,
@@ -180,13 +383,15 @@ pub fn store_base_commitment<'a>(
     // `sender` transfers `amount` to `pool` (token)
     transfer_token(sender, sender_account, pool_account, token_program, amount)?;
 
-    // `fee_payer` rents `hashing_account`
-    open_pda_account_with_offset::<BaseCommitmentHashingAccount>(
+    // `fee_payer` rents `hashing_account` (with a hashing state per base commitment)
+    open_pda_account::<BaseCommitmentHashingAccount>(
         &crate::id(),
         fee_payer,
         hashing_account,
-        hash_account_index,
+        None,
+        Some(hash_account_index),
         Some(hash_account_bump),
+        BaseCommitmentHashingAccount::size_with_len(requests.len()),
     )?;
 
     // `fee_collector` transfers `subvention` to `fee_payer` (token)
@@ -201,7 +406,9 @@ pub fn store_base_commitment<'a>(
     )?;
 
     // Buffer duplicate check and insertion
-    base_commitment_buffer.try_insert(&request.base_commitment.skip_mr())?;
+    for request in requests {
+        base_commitment_buffer.try_insert(&request.base_commitment.skip_mr())?;
+    }
 
     // `hashing_account` setup
     pda_account!(
@@ -209,7 +416,7 @@ pub fn store_base_commitment<'a>(
         BaseCommitmentHashingAccount,
         hashing_account
     );
-    hashing_account.setup(request, fee_payer.key.to_bytes())
+    hashing_account.setup_batch(requests, fee_payer.key.to_bytes())
 }
 
 // TODO: add functionality for a Warden to compute other uncomputed base-commitments (initiated by other Wardens)
@@ -229,12 +436,15 @@ pub fn compute_base_commitment_hash(
 pub fn finalize_base_commitment_hash<'a>(
     original_fee_payer: &AccountInfo<'a>,
     pool: &AccountInfo<'a>,
+    pool_ledger: &mut PoolLedgerAccount,
     fee: &FeeAccount,
     hashing_account_info: &AccountInfo<'a>,
     commitment_hash_queue: &mut CommitmentQueueAccount,
+    governor: &GovernorAccount,
 
     _hash_account_index: u32,
     fee_version: u32,
+    shard: u32,
 ) -> ProgramResult {
     pda_account!(
         mut hashing_account,
@@ -254,26 +464,48 @@ pub fn finalize_base_commitment_hash<'a>(
         ElusivError::InvalidAccount
     );
     guard!(
-        (hashing_account.get_instruction() as usize) == BaseCommitmentHashComputation::IX_COUNT,
+        hashing_account.is_batch_hashed(),
         ElusivError::ComputationIsNotYetFinished
     );
 
-    // `pool` transfers `base_commitment_hash_fee` to `original_fee_payer` (lamports)
-    transfer_lamports_from_pda_checked(
-        pool,
-        original_fee_payer,
-        fee.get_program_fee()
-            .base_commitment_hash_computation_fee()
-            .0,
-    )?;
-
-    let commitment = hashing_account.get_state().result();
+    // Each commitment of a batch is enqueued into the queue of its own shard
+    let batch_len = usize_as_u32_safe(hashing_account.batch_len());
+    let shards_count = governor.get_commitment_shards_count();
+    let mut finalized_mask = hashing_account.get_finalized_mask();
     let mut commitment_queue = CommitmentQueue::new(commitment_hash_queue);
-    commitment_queue.enqueue(CommitmentHashRequest {
-        commitment: fr_to_u256_le(&commitment),
-        fee_version,
-        min_batching_rate: hashing_account.get_min_batching_rate(),
-    })?;
+    let mut is_shard_used = false;
+    for i in 0..batch_len {
+        let commitment = hashing_account
+            .get_commitment(i as usize)
+            .ok_or(ElusivError::InvalidAccount)?;
+        if finalized_mask & (1 << i) != 0 || commitment_shard(&commitment, shards_count) != shard {
+            continue;
+        }
+
+        commitment_queue.enqueue(CommitmentHashRequest {
+            commitment,
+            fee_version,
+            min_batching_rate: hashing_account.get_min_batching_rate(),
+        })?;
+        finalized_mask |= 1 << i;
+        is_shard_used = true;
+    }
+    guard!(is_shard_used, ElusivError::InvalidAccount);
+
+    hashing_account.set_finalized_mask(&finalized_mask);
+    if finalized_mask != (1 << batch_len) - 1 {
+        return Ok(());
+    }
+
+    // `pool` transfers `base_commitment_hash_fee` to `original_fee_payer` (lamports)
+    let base_commitment_hash_fee = fee
+        .get_program_fee()
+        .batched_base_commitment_hash_computation_fee(batch_len as usize)
+        .0
+        .checked_mul(batch_len as u64)
+        .ok_or(MATH_ERR)?;
+    pool_ledger.track(0, PoolFlow::FeePayment, base_commitment_hash_fee)?;
+    transfer_lamports_from_pda_checked(pool, original_fee_payer, base_commitment_hash_fee)?;
 
     // Close hashing account
     hashing_account.set_is_active(&false);
@@ -281,13 +513,27 @@ pub fn finalize_base_commitment_hash<'a>(
 }
 
 /// Places the hash siblings into the hashing account
+///
+/// # Notes
+///
+/// - The hashing accounts of a shard are used in order, so `previous_hashing_account` is the account used for the previous batch.
+/// - If the batch of `previous_hashing_account` has not yet been finalized, the batches are hashed in a pipeline (see [`CommitmentHashingAccount::setup_pipelined`]).
 pub fn init_commitment_hash_setup(
+    previous_hashing_account: &AccountInfo,
     hashing_account: &mut CommitmentHashingAccount,
-    storage_account: &StorageAccount,
+    storage_account: &mut StorageAccount,
 
     insertion_can_fail: bool,
+    hashing_account_index: u32,
+    previous_hashing_account_index: u32,
 ) -> ProgramResult {
-    match init_commitment_hash_setup_inner(hashing_account, storage_account) {
+    match init_commitment_hash_setup_inner(
+        previous_hashing_account,
+        hashing_account,
+        storage_account,
+        hashing_account_index,
+        previous_hashing_account_index,
+    ) {
         Ok(()) => Ok(()),
         Err(e) => {
             if insertion_can_fail {
@@ -301,18 +547,62 @@ pub fn init_commitment_hash_setup(
 }
 
 fn init_commitment_hash_setup_inner(
+    previous_hashing_account: &AccountInfo,
     hashing_account: &mut CommitmentHashingAccount,
-    storage_account: &StorageAccount,
+    storage_account: &mut StorageAccount,
+    hashing_account_index: u32,
+    previous_hashing_account_index: u32,
 ) -> ProgramResult {
     guard!(
         !hashing_account.get_is_active(),
         ElusivError::ComputationIsNotYetFinished
     );
 
+    let hashing_accounts_count = storage_account.get_hashing_accounts_count().max(1);
+    let next_index = storage_account.get_next_hashing_account_index() % hashing_accounts_count;
+    let last_index = (next_index + hashing_accounts_count - 1) % hashing_accounts_count;
+    guard!(
+        hashing_account_index < hashing_accounts_count,
+        ElusivError::InvalidInstructionData
+    );
+    guard!(
+        previous_hashing_account_index
+            == (hashing_account_index + hashing_accounts_count - 1) % hashing_accounts_count,
+        ElusivError::InvalidInstructionData
+    );
+
+    // The last setup can be repeated as long as no batch has been placed in the account
+    guard!(
+        hashing_account_index == next_index
+            || (hashing_account_index == last_index && hashing_account.get_setup()),
+        ElusivError::InvalidInstructionData
+    );
+
+    if previous_hashing_account_index != hashing_account_index {
+        pda_account!(previous, CommitmentHashingAccount, previous_hashing_account);
+
+        if previous.get_is_active() {
+            hashing_account.setup_pipelined(&previous)?;
+            storage_account.set_next_hashing_account_index(
+                &((hashing_account_index + 1) % hashing_accounts_count),
+            );
+            return Ok(());
+        }
+
+        guard!(
+            !previous.get_setup(),
+            ElusivError::ComputationIsNotYetStarted
+        );
+    }
+
     let ordering = storage_account.get_next_commitment_ptr();
     let siblings = storage_account.get_mt_opening(ordering as usize)?;
 
-    hashing_account.setup(ordering, &siblings)
+    hashing_account.setup(ordering, &siblings)?;
+    storage_account
+        .set_next_hashing_account_index(&((hashing_account_index + 1) % hashing_accounts_count));
+
+    Ok(())
 }
 
 /// Places the next batch from the commitment queue in the [`CommitmentHashingAccount`]
@@ -321,6 +611,7 @@ pub fn init_commitment_hash(
     hashing_account: &mut CommitmentHashingAccount,
 
     insertion_can_fail: bool,
+    _hashing_account_index: u32,
 ) -> ProgramResult {
     match init_commitment_hash_inner(queue, hashing_account) {
         Ok(()) => Ok(()),
@@ -349,7 +640,7 @@ fn init_commitment_hash_inner(
     );
 
     let mut queue = CommitmentQueue::new(queue);
-    let (batch, batching_rate) = queue.next_batch()?;
+    let (batch, batching_rate) = queue.next_batch(hashing_account.get_ordering())?;
     queue.remove(usize_as_u32_safe(batch.len()))?;
 
     // The fee/batch-upgrader logic has to guarantee that there are no lower fees in a batch
@@ -369,14 +660,17 @@ fn init_commitment_hash_inner(
     hashing_account.reset(batching_rate, fee_version, &commitments)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn compute_commitment_hash<'a>(
     fee_payer: &AccountInfo<'a>,
     fee: &FeeAccount,
     pool: &AccountInfo<'a>,
+    pool_ledger: &mut PoolLedgerAccount,
     hashing_account: &mut CommitmentHashingAccount,
 
     fee_version: u32,
     _nonce: u32,
+    _hashing_account_index: u32,
 ) -> ProgramResult {
     guard!(
         hashing_account.get_is_active(),
@@ -389,22 +683,39 @@ pub fn compute_commitment_hash<'a>(
 
     compute_commitment_hash_partial(hashing_account)?;
 
-    transfer_lamports_from_pda_checked(
-        pool,
-        fee_payer,
-        fee.get_program_fee().hash_tx_compensation().0,
-    )
+    let hash_tx_compensation = fee.get_program_fee().hash_tx_compensation().0;
+    pool_ledger.track(0, PoolFlow::FeePayment, hash_tx_compensation)?;
+    transfer_lamports_from_pda_checked(pool, fee_payer, hash_tx_compensation)
 }
 
 /// Requires `batching_rate + 1` calls
+///
+/// # Notes
+///
+/// - Batches are finalized in the order of their insertion into the MT, enforced through the hashing account's `ordering`.
+/// - The last call resolves the pending siblings of the batch pipelined in `next_hashing_account`.
 pub fn finalize_commitment_hash(
     hashing_account: &mut CommitmentHashingAccount,
+    next_hashing_account: &AccountInfo,
     storage_account: &mut StorageAccount,
+
+    hashing_account_index: u32,
+    next_hashing_account_index: u32,
 ) -> ProgramResult {
+    let hashing_accounts_count = storage_account.get_hashing_accounts_count().max(1);
+    guard!(
+        next_hashing_account_index == (hashing_account_index + 1) % hashing_accounts_count,
+        ElusivError::InvalidInstructionData
+    );
+
     guard!(
         hashing_account.get_is_active(),
         ElusivError::ComputationIsNotYetStarted
     );
+    guard!(
+        hashing_account.get_ordering() == storage_account.get_next_commitment_ptr(),
+        ElusivError::ComputationIsNotYetStarted
+    );
 
     let finalization_ix = hashing_account.get_finalization_ix();
     let batching_rate = hashing_account.get_batching_rate();
@@ -413,11 +724,8 @@ pub fn finalize_commitment_hash(
         ElusivError::ComputationIsAlreadyFinished
     );
 
-    let instruction = hashing_account.get_instruction();
-    let instructions =
-        commitment_hash_computation_instructions(hashing_account.get_batching_rate());
     guard!(
-        (instruction as usize) >= instructions.len(),
+        hashing_account.is_hashing_finished(),
         ElusivError::ComputationIsAlreadyFinished
     );
 
@@ -432,6 +740,17 @@ pub fn finalize_commitment_hash(
     if finalization_ix == batching_rate {
         hashing_account.set_is_active(&false);
         hashing_account.set_setup(&false);
+
+        if next_hashing_account_index != hashing_account_index {
+            pda_account!(mut next, CommitmentHashingAccount, next_hashing_account);
+
+            if next.get_setup()
+                && next.get_pending_siblings() != 0
+                && next.get_ordering() == storage_account.get_next_commitment_ptr()
+            {
+                next.resolve_pending_siblings(storage_account)?;
+            }
+        }
     }
     Ok(())
 }
@@ -439,20 +758,30 @@ pub fn finalize_commitment_hash(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::commitment::poseidon_hash::full_poseidon2_hash;
+    use crate::bytes::ElusivOption;
+    use crate::commitment::poseidon_hash::{full_poseidon2_hash, BinarySpongeHashingState};
+    use crate::commitment::{
+        base_commitment_hash_computation_instructions, commitment_hash_computation_instructions,
+        BaseCommitmentHashComputation,
+    };
     use crate::fields::{
-        big_uint_to_u256, fr_to_u256_le_repr, u256_from_str_skip_mr, SCALAR_MODULUS_RAW,
+        big_uint_to_u256, fr_to_u256_le, fr_to_u256_le_repr, u256_from_str_skip_mr,
+        SCALAR_MODULUS_RAW,
     };
     use crate::macros::{
         account_info, parent_account, program_token_account_info, pyth_price_account_info,
         test_account_info, test_pda_account_info, zero_program_account,
     };
-    use crate::state::governor::PoolAccount;
+    use crate::state::apa::{
+        apa_accounts_data, apa_target_map_account, ApaLevel, WARDEN_NETWORK_PROGRAM_ID,
+    };
+    use crate::state::governor::{PoolAccount, PoolBalance, TokenVolume};
     use crate::state::program_account::{PDAAccount, SizedAccount};
     use crate::state::storage::{EMPTY_TREE, MT_HEIGHT};
     use crate::token::{lamports_token, usdc_token, LAMPORTS_TOKEN_ID, USDC_TOKEN_ID};
     use ark_ff::Zero;
     use assert_matches::assert_matches;
+    use elusiv_computation::PartialComputation;
     use elusiv_types::tokens::Price;
     use solana_program::native_token::LAMPORTS_PER_SOL;
     use solana_program::pubkey::Pubkey;
@@ -487,6 +816,8 @@ mod tests {
     #[test]
     fn test_store_base_commitment_lamports() {
         zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
         zero_program_account!(mut buffer, BaseCommitmentBufferAccount);
         test_account_info!(sender, 0);
         test_account_info!(fee_payer, 0);
@@ -494,6 +825,7 @@ mod tests {
         test_account_info!(fee_collector, 0);
         test_account_info!(any, 0);
         account_info!(sys, system_program::id(), vec![]);
+        account_info!(apa_target_map, apa_target_map_account(sender.key));
         account_info!(spl, spl_token::id(), vec![]);
         let (hasing_account_pubkey, bump) = BaseCommitmentHashingAccount::find(Some(0));
         account_info!(
@@ -546,27 +878,38 @@ mod tests {
         requests.push(request.clone());
         requests.last_mut().unwrap().min_batching_rate = 0;
 
+        // min_batching_rate too high
+        requests.push(request.clone());
+        requests.last_mut().unwrap().min_batching_rate = MAX_COMMITMENT_BATCHING_RATE as u32 + 1;
+
         for request in requests {
             assert_matches!(
-                store_base_commitment(
-                    &sender,
-                    &sender,
-                    &fee_payer,
-                    &fee_payer,
-                    &pool,
-                    &pool,
-                    &fee_collector,
-                    &fee_collector,
-                    &any,
-                    &any,
-                    &governor,
-                    &hashing_acc,
-                    &mut buffer,
-                    &sys,
-                    &sys,
+                store_base_commitments(
+                    StoreBaseCommitmentAccounts {
+                        sender: &sender,
+                        sender_account: &sender,
+                        fee_payer: &fee_payer,
+                        fee_payer_account: &fee_payer,
+                        pool: &pool,
+                        pool_account: &pool,
+                        fee_collector: &fee_collector,
+                        fee_collector_account: &fee_collector,
+                        sol_usd_price_account: &any,
+                        token_usd_price_account: &any,
+                        governor: &governor,
+                        token_volume: &mut token_volume,
+                        pool_ledger: &mut pool_ledger,
+                        hashing_account: &hashing_acc,
+                        base_commitment_buffer: &mut buffer,
+                        token_program: &sys,
+                        system_program: &sys,
+                        apa_target_map: &apa_target_map,
+                        apa_proposal: &any
+                    },
                     0,
                     bump,
-                    request
+                    &[request],
+                    0
                 ),
                 Err(_)
             );
@@ -574,182 +917,486 @@ mod tests {
 
         // Invalid pool_account
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender,
-                &fee_payer,
-                &fee_payer,
-                &pool,
-                &any,
-                &fee_collector,
-                &fee_collector,
-                &any,
-                &any,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &sys,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &any,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &fee_collector,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &any
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Invalid fee_collector_account
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender,
-                &fee_payer,
-                &fee_payer,
-                &pool,
-                &pool,
-                &fee_collector,
-                &any,
-                &any,
-                &any,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &sys,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &any,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &any
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Invalid token_program
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender,
-                &fee_payer,
-                &fee_payer,
-                &pool,
-                &pool,
-                &fee_collector,
-                &pool,
-                &any,
-                &any,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &spl,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &pool,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &spl,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &any
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Mismatch between PDA and offset
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender,
-                &fee_payer,
-                &fee_payer,
-                &pool,
-                &pool,
-                &fee_collector,
-                &pool,
-                &any,
-                &any,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &sys,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &pool,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &any
+                },
                 1,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Invalid bump
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender,
-                &fee_payer,
-                &fee_payer,
-                &pool,
-                &pool,
-                &fee_collector,
-                &fee_collector,
-                &any,
-                &any,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &sys,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &fee_collector,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &any
+                },
                 0,
                 0,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
+        // Deposit limit exceeded
+        token_volume.set_volumes(
+            0,
+            &TokenVolume {
+                deposit_limit: ElusivOption::Some(request.amount - 1),
+                ..Default::default()
+            },
+        );
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender,
-                &fee_payer,
-                &fee_payer,
-                &pool,
-                &pool,
-                &fee_collector,
-                &fee_collector,
-                &any,
-                &any,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &sys,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &fee_collector,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &any
+                },
+                0,
+                bump,
+                std::slice::from_ref(&request),
+                0
+            ),
+            Err(_)
+        );
+
+        // Outcast sender
+        let ((map_pubkey, map_data), (proposal_pubkey, proposal_data)) =
+            apa_accounts_data(sender.key, 0, ApaLevel::Outcast, None);
+        account_info!(
+            outcast_map,
+            map_pubkey,
+            map_data,
+            WARDEN_NETWORK_PROGRAM_ID,
+            false
+        );
+        account_info!(
+            outcast_proposal,
+            proposal_pubkey,
+            proposal_data,
+            WARDEN_NETWORK_PROGRAM_ID,
+            false
+        );
+        assert_matches!(
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &fee_collector,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &outcast_map,
+                    apa_proposal: &outcast_proposal
+                },
+                0,
+                bump,
+                std::slice::from_ref(&request),
+                0
+            ),
+            Err(_)
+        );
+
+        // Delisted token
+        token_volume.set_delisted(0, &true);
+        assert_matches!(
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &fee_collector,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &any
+                },
+                0,
+                bump,
+                std::slice::from_ref(&request),
+                0
+            ),
+            Err(_)
+        );
+        token_volume.set_volumes(0, &TokenVolume::default());
+        token_volume.set_delisted(0, &false);
+        pool_ledger.set_balances(0, &PoolBalance::default());
+
+        assert_matches!(
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &fee_collector,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &any
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Ok(())
         );
+        assert_eq!(token_volume.get_volumes(0).deposit_volume, request.amount);
+        assert_eq!(pool_ledger.get_balances(0).deposited, request.amount);
 
         // Immediate uplicate insertion will fail
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender,
-                &fee_payer,
-                &fee_payer,
-                &pool,
-                &pool,
-                &fee_collector,
-                &fee_collector,
-                &any,
-                &any,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &sys,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &fee_collector,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &any
+                },
                 0,
                 bump,
-                request
+                &[request],
+                0
             ),
             Err(_)
         );
     }
 
+    #[test]
+    fn test_store_base_commitment_batch() -> ProgramResult {
+        zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
+        zero_program_account!(mut buffer, BaseCommitmentBufferAccount);
+        test_account_info!(sender, 0);
+        test_account_info!(fee_payer, 0);
+        test_account_info!(pool, 0);
+        test_account_info!(fee_collector, 0);
+        test_account_info!(any, 0);
+        account_info!(sys, system_program::id(), vec![]);
+        account_info!(apa_target_map, apa_target_map_account(sender.key));
+        let (hasing_account_pubkey, bump) = BaseCommitmentHashingAccount::find(Some(0));
+        account_info!(
+            hashing_acc,
+            hasing_account_pubkey,
+            vec![0; BaseCommitmentHashingAccount::size_with_len(MAX_BASE_COMMITMENT_BATCH_SIZE)]
+        );
+
+        governor.set_commitment_batching_rate(&4);
+        governor.set_fee_version(&1);
+
+        let request = |base_commitment: &str| BaseCommitmentHashRequest {
+            base_commitment: RawU256::new(u256_from_str_skip_mr(base_commitment)),
+            commitment_index: 123,
+            amount: LAMPORTS_PER_SOL,
+            token_id: LAMPORTS_TOKEN_ID,
+            commitment: RawU256::new(u256_from_str_skip_mr("1")),
+            fee_version: 1,
+            min_batching_rate: 4,
+        };
+
+        let mut invalid_batches = vec![
+            // Empty batch
+            vec![],
+            // Batch too large
+            (1..=MAX_BASE_COMMITMENT_BATCH_SIZE + 1)
+                .map(|i| request(&i.to_string()))
+                .collect(),
+            // Duplicate base commitment
+            vec![request("1"), request("1")],
+        ];
+
+        // Mismatched token
+        invalid_batches.push(vec![request("1"), request("2")]);
+        invalid_batches.last_mut().unwrap()[1].token_id = USDC_TOKEN_ID;
+
+        // Mismatched min_batching_rate
+        invalid_batches.push(vec![request("1"), request("2")]);
+        invalid_batches.last_mut().unwrap()[1].min_batching_rate = 5;
+
+        for requests in invalid_batches {
+            assert_matches!(
+                store_base_commitments(
+                    StoreBaseCommitmentAccounts {
+                        sender: &sender,
+                        sender_account: &sender,
+                        fee_payer: &fee_payer,
+                        fee_payer_account: &fee_payer,
+                        pool: &pool,
+                        pool_account: &pool,
+                        fee_collector: &fee_collector,
+                        fee_collector_account: &fee_collector,
+                        sol_usd_price_account: &any,
+                        token_usd_price_account: &any,
+                        governor: &governor,
+                        token_volume: &mut token_volume,
+                        pool_ledger: &mut pool_ledger,
+                        hashing_account: &hashing_acc,
+                        base_commitment_buffer: &mut buffer,
+                        token_program: &sys,
+                        system_program: &sys,
+                        apa_target_map: &apa_target_map,
+                        apa_proposal: &any
+                    },
+                    0,
+                    bump,
+                    &requests,
+                    0
+                ),
+                Err(_)
+            );
+        }
+
+        zero_program_account!(mut buffer, BaseCommitmentBufferAccount);
+        let requests: Vec<BaseCommitmentHashRequest> = (1..=MAX_BASE_COMMITMENT_BATCH_SIZE)
+            .map(|i| request(&i.to_string()))
+            .collect();
+        assert_matches!(
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool,
+                    fee_collector: &fee_collector,
+                    fee_collector_account: &fee_collector,
+                    sol_usd_price_account: &any,
+                    token_usd_price_account: &any,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &any
+                },
+                0,
+                bump,
+                &requests,
+                0
+            ),
+            Ok(())
+        );
+
+        for request in requests {
+            assert!(buffer.contains(&request.base_commitment.skip_mr()));
+        }
+        pda_account!(h, BaseCommitmentHashingAccount, hashing_acc);
+        assert_eq!(h.batch_len(), MAX_BASE_COMMITMENT_BATCH_SIZE);
+        Ok(())
+    }
+
     #[test]
     fn test_store_base_commitment_token() {
         zero_program_account!(governor, GovernorAccount);
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
         zero_program_account!(mut buffer, BaseCommitmentBufferAccount);
         test_account_info!(sender);
         test_account_info!(fee_payer);
@@ -760,6 +1407,7 @@ mod tests {
         program_token_account_info!(pool_token, PoolAccount, USDC_TOKEN_ID);
         program_token_account_info!(fee_c_token, FeeCollectorAccount, USDC_TOKEN_ID);
         account_info!(sys, system_program::id(), vec![]);
+        account_info!(apa_target_map, apa_target_map_account(sender.key));
         account_info!(spl, spl_token::id(), vec![]);
         let (hasing_account_pubkey, bump) = BaseCommitmentHashingAccount::find(Some(0));
         account_info!(
@@ -801,25 +1449,32 @@ mod tests {
 
         for request in requests {
             assert_matches!(
-                store_base_commitment(
-                    &sender,
-                    &sender_token,
-                    &fee_payer,
-                    &fee_payer_token,
-                    &pool,
-                    &pool_token,
-                    &fee_c,
-                    &fee_c_token,
-                    &sol,
-                    &usdc,
-                    &governor,
-                    &hashing_acc,
-                    &mut buffer,
-                    &spl,
-                    &sys,
+                store_base_commitments(
+                    StoreBaseCommitmentAccounts {
+                        sender: &sender,
+                        sender_account: &sender_token,
+                        fee_payer: &fee_payer,
+                        fee_payer_account: &fee_payer_token,
+                        pool: &pool,
+                        pool_account: &pool_token,
+                        fee_collector: &fee_c,
+                        fee_collector_account: &fee_c_token,
+                        sol_usd_price_account: &sol,
+                        token_usd_price_account: &usdc,
+                        governor: &governor,
+                        token_volume: &mut token_volume,
+                        pool_ledger: &mut pool_ledger,
+                        hashing_account: &hashing_acc,
+                        base_commitment_buffer: &mut buffer,
+                        token_program: &spl,
+                        system_program: &sys,
+                        apa_target_map: &apa_target_map,
+                        apa_proposal: &sys
+                    },
                     0,
                     bump,
-                    request
+                    &[request],
+                    0
                 ),
                 Err(_)
             );
@@ -827,249 +1482,319 @@ mod tests {
 
         // Invalid pool_account
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender_token,
-                &fee_payer,
-                &fee_payer_token,
-                &pool,
-                &fee_c_token,
-                &fee_c,
-                &fee_c_token,
-                &sol,
-                &usdc,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &spl,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender_token,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer_token,
+                    pool: &pool,
+                    pool_account: &fee_c_token,
+                    fee_collector: &fee_c,
+                    fee_collector_account: &fee_c_token,
+                    sol_usd_price_account: &sol,
+                    token_usd_price_account: &usdc,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &spl,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &sys
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Invalid fee_collector_account
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender_token,
-                &fee_payer,
-                &fee_payer_token,
-                &pool,
-                &pool_token,
-                &fee_c,
-                &pool_token,
-                &sol,
-                &usdc,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &spl,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender_token,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer_token,
+                    pool: &pool,
+                    pool_account: &pool_token,
+                    fee_collector: &fee_c,
+                    fee_collector_account: &pool_token,
+                    sol_usd_price_account: &sol,
+                    token_usd_price_account: &usdc,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &spl,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &sys
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Invalid token_program
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender_token,
-                &fee_payer,
-                &fee_payer_token,
-                &pool,
-                &pool_token,
-                &fee_c,
-                &fee_c_token,
-                &sol,
-                &usdc,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &sys,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender_token,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer_token,
+                    pool: &pool,
+                    pool_account: &pool_token,
+                    fee_collector: &fee_c,
+                    fee_collector_account: &fee_c_token,
+                    sol_usd_price_account: &sol,
+                    token_usd_price_account: &usdc,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &sys,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &sys
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Mismatch between PDA and offset
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender_token,
-                &fee_payer,
-                &fee_payer_token,
-                &pool,
-                &pool_token,
-                &fee_c,
-                &fee_c_token,
-                &sol,
-                &usdc,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &spl,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender_token,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer_token,
+                    pool: &pool,
+                    pool_account: &pool_token,
+                    fee_collector: &fee_c,
+                    fee_collector_account: &fee_c_token,
+                    sol_usd_price_account: &sol,
+                    token_usd_price_account: &usdc,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &spl,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &sys
+                },
                 1,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Invalid sender_account
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender,
-                &fee_payer,
-                &fee_payer_token,
-                &pool,
-                &pool_token,
-                &fee_c,
-                &fee_c_token,
-                &sol,
-                &usdc,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &spl,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer_token,
+                    pool: &pool,
+                    pool_account: &pool_token,
+                    fee_collector: &fee_c,
+                    fee_collector_account: &fee_c_token,
+                    sol_usd_price_account: &sol,
+                    token_usd_price_account: &usdc,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &spl,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &sys
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Invalid fee_collector_account
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender_token,
-                &fee_payer,
-                &fee_payer,
-                &pool,
-                &pool_token,
-                &fee_c,
-                &fee_c_token,
-                &sol,
-                &usdc,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &spl,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender_token,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer,
+                    pool: &pool,
+                    pool_account: &pool_token,
+                    fee_collector: &fee_c,
+                    fee_collector_account: &fee_c_token,
+                    sol_usd_price_account: &sol,
+                    token_usd_price_account: &usdc,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &spl,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &sys
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Invalid sol_usd_price_account
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender_token,
-                &fee_payer,
-                &fee_payer_token,
-                &pool,
-                &pool_token,
-                &fee_c,
-                &fee_c_token,
-                &usdc,
-                &usdc,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &spl,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender_token,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer_token,
+                    pool: &pool,
+                    pool_account: &pool_token,
+                    fee_collector: &fee_c,
+                    fee_collector_account: &fee_c_token,
+                    sol_usd_price_account: &usdc,
+                    token_usd_price_account: &usdc,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &spl,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &sys
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         // Invalid token_usd_price_account
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender_token,
-                &fee_payer,
-                &fee_payer_token,
-                &pool,
-                &pool_token,
-                &fee_c,
-                &fee_c_token,
-                &sol,
-                &sol,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &spl,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender_token,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer_token,
+                    pool: &pool,
+                    pool_account: &pool_token,
+                    fee_collector: &fee_c,
+                    fee_collector_account: &fee_c_token,
+                    sol_usd_price_account: &sol,
+                    token_usd_price_account: &sol,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &spl,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &sys
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Err(_)
         );
 
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender_token,
-                &fee_payer,
-                &fee_payer_token,
-                &pool,
-                &pool_token,
-                &fee_c,
-                &fee_c_token,
-                &sol,
-                &usdc,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &spl,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender_token,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer_token,
+                    pool: &pool,
+                    pool_account: &pool_token,
+                    fee_collector: &fee_c,
+                    fee_collector_account: &fee_c_token,
+                    sol_usd_price_account: &sol,
+                    token_usd_price_account: &usdc,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &spl,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &sys
+                },
                 0,
                 bump,
-                request.clone()
+                std::slice::from_ref(&request),
+                0
             ),
             Ok(())
         );
 
         // Immediate uplicate insertion will fail
         assert_matches!(
-            store_base_commitment(
-                &sender,
-                &sender_token,
-                &fee_payer,
-                &fee_payer_token,
-                &pool,
-                &pool_token,
-                &fee_c,
-                &fee_c_token,
-                &sol,
-                &usdc,
-                &governor,
-                &hashing_acc,
-                &mut buffer,
-                &spl,
-                &sys,
+            store_base_commitments(
+                StoreBaseCommitmentAccounts {
+                    sender: &sender,
+                    sender_account: &sender_token,
+                    fee_payer: &fee_payer,
+                    fee_payer_account: &fee_payer_token,
+                    pool: &pool,
+                    pool_account: &pool_token,
+                    fee_collector: &fee_c,
+                    fee_collector_account: &fee_c_token,
+                    sol_usd_price_account: &sol,
+                    token_usd_price_account: &usdc,
+                    governor: &governor,
+                    token_volume: &mut token_volume,
+                    pool_ledger: &mut pool_ledger,
+                    hashing_account: &hashing_acc,
+                    base_commitment_buffer: &mut buffer,
+                    token_program: &spl,
+                    system_program: &sys,
+                    apa_target_map: &apa_target_map,
+                    apa_proposal: &sys
+                },
                 0,
                 bump,
-                request
+                &[request],
+                0
             ),
             Err(_)
         );
@@ -1118,6 +1843,8 @@ mod tests {
         );
         zero_program_account!(mut q, CommitmentQueueAccount);
         zero_program_account!(fee, FeeAccount);
+        zero_program_account!(governor, GovernorAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
         test_account_info!(pool, 0);
 
         // Inactive hashing account
@@ -1127,7 +1854,18 @@ mod tests {
             h.set_fee_payer(&fee_payer.key.to_bytes());
         }
         assert_matches!(
-            finalize_base_commitment_hash(&fee_payer, &pool, &fee, &h_account, &mut q, 0, 0),
+            finalize_base_commitment_hash(
+                &fee_payer,
+                &pool,
+                &mut pool_ledger,
+                &fee,
+                &h_account,
+                &mut q,
+                &governor,
+                0,
+                0,
+                0
+            ),
             Err(_)
         );
 
@@ -1138,7 +1876,18 @@ mod tests {
             h.set_fee_payer(&[0; 32]);
         }
         assert_matches!(
-            finalize_base_commitment_hash(&fee_payer, &pool, &fee, &h_account, &mut q, 0, 0),
+            finalize_base_commitment_hash(
+                &fee_payer,
+                &pool,
+                &mut pool_ledger,
+                &fee,
+                &h_account,
+                &mut q,
+                &governor,
+                0,
+                0,
+                0
+            ),
             Err(_)
         );
 
@@ -1149,13 +1898,35 @@ mod tests {
             h.set_fee_payer(&fee_payer.key.to_bytes());
         }
         assert_matches!(
-            finalize_base_commitment_hash(&fee_payer, &pool, &fee, &h_account, &mut q, 0, 0),
+            finalize_base_commitment_hash(
+                &fee_payer,
+                &pool,
+                &mut pool_ledger,
+                &fee,
+                &h_account,
+                &mut q,
+                &governor,
+                0,
+                0,
+                0
+            ),
             Err(_)
         );
 
         // Invalid fee version
         assert_matches!(
-            finalize_base_commitment_hash(&fee_payer, &pool, &fee, &h_account, &mut q, 0, 1),
+            finalize_base_commitment_hash(
+                &fee_payer,
+                &pool,
+                &mut pool_ledger,
+                &fee,
+                &h_account,
+                &mut q,
+                &governor,
+                0,
+                1,
+                0
+            ),
             Err(_)
         );
 
@@ -1175,27 +1946,166 @@ mod tests {
             }
         }
         assert_matches!(
-            finalize_base_commitment_hash(&fee_payer, &pool, &fee, &h_account, &mut q, 0, 0),
+            finalize_base_commitment_hash(
+                &fee_payer,
+                &pool,
+                &mut pool_ledger,
+                &fee,
+                &h_account,
+                &mut q,
+                &governor,
+                0,
+                0,
+                0
+            ),
             Err(_)
         );
 
         zero_program_account!(mut q, CommitmentQueueAccount);
+
+        // Invalid shard
         assert_matches!(
-            finalize_base_commitment_hash(&fee_payer, &pool, &fee, &h_account, &mut q, 0, 0),
+            finalize_base_commitment_hash(
+                &fee_payer,
+                &pool,
+                &mut pool_ledger,
+                &fee,
+                &h_account,
+                &mut q,
+                &governor,
+                0,
+                0,
+                1
+            ),
+            Err(_)
+        );
+
+        assert_matches!(
+            finalize_base_commitment_hash(
+                &fee_payer,
+                &pool,
+                &mut pool_ledger,
+                &fee,
+                &h_account,
+                &mut q,
+                &governor,
+                0,
+                0,
+                0
+            ),
             Ok(())
         );
         Ok(())
     }
 
+    #[test]
+    fn test_finalize_base_commitment_hash_batch() -> ProgramResult {
+        account_info!(fee_payer, Pubkey::new_unique(), vec![0]);
+        account_info!(
+            h_account,
+            BaseCommitmentHashingAccount::find(Some(0)).0,
+            vec![0; BaseCommitmentHashingAccount::size_with_len(3)]
+        );
+        zero_program_account!(mut q0, CommitmentQueueAccount);
+        zero_program_account!(mut q1, CommitmentQueueAccount);
+        zero_program_account!(fee, FeeAccount);
+        zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
+        test_account_info!(pool, 0);
+
+        governor.set_commitment_shards_count(&2);
+
+        // Commitments 0 and 2 are in shard 0, commitment 1 is in shard 1
+        {
+            pda_account!(mut h, BaseCommitmentHashingAccount, h_account);
+            h.set_is_active(&true);
+            h.set_fee_payer(&fee_payer.key.to_bytes());
+            h.set_instruction(&(base_commitment_hash_computation_instructions(3).len() as u32));
+            for (i, v) in [2, 1, 5].into_iter().enumerate() {
+                h.set_batch(
+                    i,
+                    &BinarySpongeHashingState([Fr::from(v), Fr::zero(), Fr::zero()]),
+                )?;
+            }
+        }
+
+        finalize_base_commitment_hash(
+            &fee_payer,
+            &pool,
+            &mut pool_ledger,
+            &fee,
+            &h_account,
+            &mut q0,
+            &governor,
+            0,
+            0,
+            0,
+        )?;
+        assert_eq!(CommitmentQueue::new(&mut q0).len(), 2);
+        {
+            pda_account!(h, BaseCommitmentHashingAccount, h_account);
+            assert!(h.get_is_active());
+            assert_eq!(h.get_finalized_mask(), 0b101);
+        }
+
+        // Shard 0 has already been finalized
+        assert_matches!(
+            finalize_base_commitment_hash(
+                &fee_payer,
+                &pool,
+                &mut pool_ledger,
+                &fee,
+                &h_account,
+                &mut q0,
+                &governor,
+                0,
+                0,
+                0
+            ),
+            Err(_)
+        );
+
+        finalize_base_commitment_hash(
+            &fee_payer,
+            &pool,
+            &mut pool_ledger,
+            &fee,
+            &h_account,
+            &mut q1,
+            &governor,
+            0,
+            0,
+            1,
+        )?;
+        assert_eq!(CommitmentQueue::new(&mut q1).len(), 1);
+        assert_eq!(
+            CommitmentQueue::new(&mut q1)
+                .view_first()
+                .unwrap()
+                .commitment,
+            fr_to_u256_le(&Fr::from(1))
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_init_commitment_hash_empty_queue() {
-        parent_account!(storage_account, StorageAccount);
+        parent_account!(mut storage_account, StorageAccount);
         zero_program_account!(mut queue, CommitmentQueueAccount);
         zero_program_account!(mut hashing_account, CommitmentHashingAccount);
-
-        init_commitment_hash_setup(&mut hashing_account, &storage_account, false).unwrap();
+        test_account_info!(previous_hashing_account, 0);
+
+        init_commitment_hash_setup(
+            &previous_hashing_account,
+            &mut hashing_account,
+            &mut storage_account,
+            false,
+            0,
+            0,
+        )
+        .unwrap();
         assert_matches!(
-            init_commitment_hash(&mut queue, &mut hashing_account, false),
+            init_commitment_hash(&mut queue, &mut hashing_account, false, 0),
             Err(_)
         );
     }
@@ -1216,7 +2126,7 @@ mod tests {
         hashing_account.set_is_active(&true);
         hashing_account.set_setup(&true);
         assert_matches!(
-            init_commitment_hash(&mut queue, &mut hashing_account, false),
+            init_commitment_hash(&mut queue, &mut hashing_account, false, 0),
             Err(_)
         );
     }
@@ -1226,6 +2136,7 @@ mod tests {
         parent_account!(mut storage_account, StorageAccount);
         zero_program_account!(mut queue, CommitmentQueueAccount);
         zero_program_account!(mut hashing_account, CommitmentHashingAccount);
+        test_account_info!(previous_hashing_account, 0);
 
         let mut q = CommitmentQueue::new(&mut queue);
         q.enqueue(CommitmentHashRequest {
@@ -1236,18 +2147,27 @@ mod tests {
         .unwrap();
 
         storage_account.set_next_commitment_ptr(&(MT_COMMITMENT_COUNT as u32));
-        init_commitment_hash_setup(&mut hashing_account, &storage_account, false).unwrap();
+        init_commitment_hash_setup(
+            &previous_hashing_account,
+            &mut hashing_account,
+            &mut storage_account,
+            false,
+            0,
+            0,
+        )
+        .unwrap();
         assert_matches!(
-            init_commitment_hash(&mut queue, &mut hashing_account, false),
+            init_commitment_hash(&mut queue, &mut hashing_account, false, 0),
             Err(_)
         );
     }
 
     #[test]
     fn test_init_commitment_hash_incomplete_batch() {
-        parent_account!(storage_account, StorageAccount);
+        parent_account!(mut storage_account, StorageAccount);
         zero_program_account!(mut queue, CommitmentQueueAccount);
         zero_program_account!(mut hashing_account, CommitmentHashingAccount);
+        test_account_info!(previous_hashing_account, 0);
 
         let mut q = CommitmentQueue::new(&mut queue);
         q.enqueue(CommitmentHashRequest {
@@ -1257,18 +2177,27 @@ mod tests {
         })
         .unwrap();
 
-        init_commitment_hash_setup(&mut hashing_account, &storage_account, false).unwrap();
+        init_commitment_hash_setup(
+            &previous_hashing_account,
+            &mut hashing_account,
+            &mut storage_account,
+            false,
+            0,
+            0,
+        )
+        .unwrap();
         assert_matches!(
-            init_commitment_hash(&mut queue, &mut hashing_account, false),
+            init_commitment_hash(&mut queue, &mut hashing_account, false, 0),
             Err(_)
         );
     }
 
     #[test]
-    fn test_init_commitment_hash_batch_too_big() {
+    fn test_init_commitment_hash_batch_at_tree_end() {
         parent_account!(mut storage_account, StorageAccount);
         zero_program_account!(mut queue, CommitmentQueueAccount);
         zero_program_account!(mut hashing_account, CommitmentHashingAccount);
+        test_account_info!(previous_hashing_account, 0);
 
         let mut q = CommitmentQueue::new(&mut queue);
         q.enqueue(CommitmentHashRequest {
@@ -1279,19 +2208,32 @@ mod tests {
         .unwrap();
 
         storage_account.set_next_commitment_ptr(&(MT_COMMITMENT_COUNT as u32 - 1));
-        init_commitment_hash_setup(&mut hashing_account, &storage_account, false).unwrap();
+        init_commitment_hash_setup(
+            &previous_hashing_account,
+            &mut hashing_account,
+            &mut storage_account,
+            false,
+            0,
+            0,
+        )
+        .unwrap();
+
+        // The last place of the MT is unaligned, so the commitment is batched below its min_batching_rate
         assert_matches!(
-            init_commitment_hash(&mut queue, &mut hashing_account, false),
-            Err(_)
+            init_commitment_hash(&mut queue, &mut hashing_account, false, 0),
+            Ok(())
         );
+        assert!(hashing_account.get_is_active());
+        assert_eq!(hashing_account.get_batching_rate(), 0);
     }
 
     #[test]
     #[allow(clippy::needless_range_loop)]
     fn test_init_commitment_hash_valid() {
-        parent_account!(storage_account, StorageAccount);
+        parent_account!(mut storage_account, StorageAccount);
         zero_program_account!(mut queue, CommitmentQueueAccount);
         zero_program_account!(mut hashing_account, CommitmentHashingAccount);
+        test_account_info!(previous_hashing_account, 0);
 
         let mut q = CommitmentQueue::new(&mut queue);
         q.enqueue(CommitmentHashRequest {
@@ -1319,8 +2261,16 @@ mod tests {
         })
         .unwrap();
 
-        init_commitment_hash_setup(&mut hashing_account, &storage_account, false).unwrap();
-        init_commitment_hash(&mut queue, &mut hashing_account, false).unwrap();
+        init_commitment_hash_setup(
+            &previous_hashing_account,
+            &mut hashing_account,
+            &mut storage_account,
+            false,
+            0,
+            0,
+        )
+        .unwrap();
+        init_commitment_hash(&mut queue, &mut hashing_account, false, 0).unwrap();
 
         assert_eq!(hashing_account.get_batching_rate(), 2);
 
@@ -1337,29 +2287,143 @@ mod tests {
 
     #[test]
     fn test_init_commitment_hash_setup_insertion_can_fail() {
-        parent_account!(storage_account, StorageAccount);
+        parent_account!(mut storage_account, StorageAccount);
         zero_program_account!(mut hashing_account, CommitmentHashingAccount);
+        test_account_info!(previous_hashing_account, 0);
         hashing_account.set_is_active(&true);
         assert_matches!(
-            init_commitment_hash_setup(&mut hashing_account, &storage_account, false),
+            init_commitment_hash_setup(
+                &previous_hashing_account,
+                &mut hashing_account,
+                &mut storage_account,
+                false,
+                0,
+                0,
+            ),
             Err(_)
         );
         assert_matches!(
-            init_commitment_hash_setup(&mut hashing_account, &storage_account, true),
+            init_commitment_hash_setup(
+                &previous_hashing_account,
+                &mut hashing_account,
+                &mut storage_account,
+                true,
+                0,
+                0,
+            ),
             Ok(())
         );
     }
 
+    #[test]
+    fn test_init_commitment_hash_setup_pipelined() -> ProgramResult {
+        parent_account!(mut storage_account, StorageAccount);
+        zero_program_account!(mut hashing_account, CommitmentHashingAccount);
+        test_account_info!(previous_hashing_account, CommitmentHashingAccount::SIZE);
+        storage_account.set_hashing_accounts_count(&2);
+
+        // Invalid hashing account index
+        assert_matches!(
+            init_commitment_hash_setup_inner(
+                &previous_hashing_account,
+                &mut hashing_account,
+                &mut storage_account,
+                1,
+                0
+            ),
+            Err(_)
+        );
+
+        // Invalid previous hashing account index
+        assert_matches!(
+            init_commitment_hash_setup_inner(
+                &previous_hashing_account,
+                &mut hashing_account,
+                &mut storage_account,
+                0,
+                0
+            ),
+            Err(_)
+        );
+
+        // Previous hashing account is inactive
+        init_commitment_hash_setup_inner(
+            &previous_hashing_account,
+            &mut hashing_account,
+            &mut storage_account,
+            0,
+            1,
+        )
+        .unwrap();
+        assert_eq!(storage_account.get_next_hashing_account_index(), 1);
+
+        // The setup can be repeated until a batch is placed in the account
+        init_commitment_hash_setup_inner(
+            &previous_hashing_account,
+            &mut hashing_account,
+            &mut storage_account,
+            0,
+            1,
+        )
+        .unwrap();
+
+        // Previous hashing account is setup, but has no batch
+        {
+            pda_account!(
+                mut previous,
+                CommitmentHashingAccount,
+                previous_hashing_account
+            );
+            previous.setup(0, &[]).unwrap();
+        }
+        hashing_account.set_setup(&false);
+        storage_account.set_next_hashing_account_index(&0);
+        assert_matches!(
+            init_commitment_hash_setup_inner(
+                &previous_hashing_account,
+                &mut hashing_account,
+                &mut storage_account,
+                0,
+                1
+            ),
+            Err(_)
+        );
+
+        // Pipelined on the finished hashing of the previous batch
+        {
+            pda_account!(
+                mut previous,
+                CommitmentHashingAccount,
+                previous_hashing_account
+            );
+            previous.set_is_active(&true);
+            previous.set_ordering(&2);
+            previous.set_batching_rate(&1);
+            previous.set_instruction(&(commitment_hash_computation_instructions(1).len() as u32));
+        }
+        init_commitment_hash_setup_inner(
+            &previous_hashing_account,
+            &mut hashing_account,
+            &mut storage_account,
+            0,
+            1,
+        )
+        .unwrap();
+        assert_eq!(hashing_account.get_ordering(), 4);
+        assert_eq!(storage_account.get_next_hashing_account_index(), 1);
+        Ok(())
+    }
+
     #[test]
     fn test_init_commitment_hash_insertion_can_fail() {
         zero_program_account!(mut queue, CommitmentQueueAccount);
         zero_program_account!(mut hashing_account, CommitmentHashingAccount);
         assert_matches!(
-            init_commitment_hash(&mut queue, &mut hashing_account, false),
+            init_commitment_hash(&mut queue, &mut hashing_account, false, 0),
             Err(_)
         );
         assert_matches!(
-            init_commitment_hash(&mut queue, &mut hashing_account, true),
+            init_commitment_hash(&mut queue, &mut hashing_account, true, 0),
             Ok(())
         );
     }
@@ -1368,35 +2432,71 @@ mod tests {
     fn test_compute_commitment_hash() {
         zero_program_account!(mut hashing_account, CommitmentHashingAccount);
         zero_program_account!(fee, FeeAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
         test_account_info!(pool, 0);
         test_account_info!(fee_payer, 0);
 
         // Inactive account
         assert_matches!(
-            compute_commitment_hash(&fee_payer, &fee, &pool, &mut hashing_account, 0, 0),
+            compute_commitment_hash(
+                &fee_payer,
+                &fee,
+                &pool,
+                &mut pool_ledger,
+                &mut hashing_account,
+                0,
+                0,
+                0
+            ),
             Err(_)
         );
 
         // Invalid fee_version
         hashing_account.set_is_active(&true);
         assert_matches!(
-            compute_commitment_hash(&fee_payer, &fee, &pool, &mut hashing_account, 1, 0),
+            compute_commitment_hash(
+                &fee_payer,
+                &fee,
+                &pool,
+                &mut pool_ledger,
+                &mut hashing_account,
+                1,
+                0,
+                0
+            ),
             Err(_)
         );
 
-        compute_commitment_hash(&fee_payer, &fee, &pool, &mut hashing_account, 0, 0).unwrap();
+        compute_commitment_hash(
+            &fee_payer,
+            &fee,
+            &pool,
+            &mut pool_ledger,
+            &mut hashing_account,
+            0,
+            0,
+            0,
+        )
+        .unwrap();
     }
 
     #[test]
     fn test_finalize_commitment_hash() {
         parent_account!(mut storage_account, StorageAccount);
         zero_program_account!(mut hashing_account, CommitmentHashingAccount);
+        test_account_info!(next_hashing_account, CommitmentHashingAccount::SIZE);
 
         // Computation not finished
         hashing_account.set_is_active(&true);
         hashing_account.set_instruction(&0);
         assert_matches!(
-            finalize_commitment_hash(&mut hashing_account, &mut storage_account),
+            finalize_commitment_hash(
+                &mut hashing_account,
+                &next_hashing_account,
+                &mut storage_account,
+                0,
+                0
+            ),
             Err(_)
         );
 
@@ -1405,26 +2505,120 @@ mod tests {
         hashing_account
             .set_instruction(&(commitment_hash_computation_instructions(0).len() as u32));
         assert_matches!(
-            finalize_commitment_hash(&mut hashing_account, &mut storage_account),
+            finalize_commitment_hash(
+                &mut hashing_account,
+                &next_hashing_account,
+                &mut storage_account,
+                0,
+                0
+            ),
             Err(_)
         );
 
-        // Storage account is full
+        // Previous batch is not yet finalized
         hashing_account.set_is_active(&true);
+        hashing_account.set_ordering(&1);
+        assert_matches!(
+            finalize_commitment_hash(
+                &mut hashing_account,
+                &next_hashing_account,
+                &mut storage_account,
+                0,
+                0
+            ),
+            Err(_)
+        );
+
+        // Storage account is full
+        hashing_account.set_ordering(&(MT_COMMITMENT_COUNT as u32));
         storage_account.set_next_commitment_ptr(&(MT_COMMITMENT_COUNT as u32));
         assert_matches!(
-            finalize_commitment_hash(&mut hashing_account, &mut storage_account),
+            finalize_commitment_hash(
+                &mut hashing_account,
+                &next_hashing_account,
+                &mut storage_account,
+                0,
+                0
+            ),
             Err(_)
         );
 
+        hashing_account.set_ordering(&0);
         storage_account.set_next_commitment_ptr(&0);
-        finalize_commitment_hash(&mut hashing_account, &mut storage_account).unwrap();
+
+        // Invalid next hashing account index
+        assert_matches!(
+            finalize_commitment_hash(
+                &mut hashing_account,
+                &next_hashing_account,
+                &mut storage_account,
+                0,
+                1
+            ),
+            Err(_)
+        );
+
+        finalize_commitment_hash(
+            &mut hashing_account,
+            &next_hashing_account,
+            &mut storage_account,
+            0,
+            0,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_finalize_commitment_hash_pipelined() -> ProgramResult {
+        parent_account!(mut storage_account, StorageAccount);
+        zero_program_account!(mut hashing_account, CommitmentHashingAccount);
+        test_account_info!(next_hashing_account, CommitmentHashingAccount::SIZE);
+        storage_account.set_hashing_accounts_count(&2);
+
+        hashing_account.setup(0, &storage_account.get_mt_opening(0)?)?;
+        hashing_account.reset(1, 0, &[[1; 32], [2; 32]])?;
+
+        // The sibling on level 1 is the HT-root of the previous batch, which is not yet computed
+        {
+            pda_account!(mut next, CommitmentHashingAccount, next_hashing_account);
+            next.setup_pipelined(&hashing_account)?;
+            assert_eq!(next.get_ordering(), 2);
+            assert_eq!(next.get_pending_siblings(), 0b10);
+
+            next.reset(0, 0, &[[3; 32]])?;
+            assert!(!next.is_sibling_pending(0));
+            assert!(next.is_sibling_pending(1));
+        }
+
+        while !hashing_account.is_hashing_finished() {
+            compute_commitment_hash_partial(&mut hashing_account)?;
+        }
+        for _ in 0..=1 {
+            finalize_commitment_hash(
+                &mut hashing_account,
+                &next_hashing_account,
+                &mut storage_account,
+                0,
+                1,
+            )?;
+        }
+
+        // Pending siblings are resolved from the MT
+        pda_account!(next, CommitmentHashingAccount, next_hashing_account);
+        assert_eq!(next.get_pending_siblings(), 0);
+        let opening = storage_account.get_mt_opening(2)?;
+        for (level, sibling) in opening.iter().enumerate() {
+            assert_eq!(next.get_siblings(level), *sibling);
+        }
+
+        Ok(())
     }
 
     #[test]
     fn test_finalize_commitment_hash_valid() {
         parent_account!(mut storage_account, StorageAccount);
         zero_program_account!(mut hashing_account, CommitmentHashingAccount);
+        test_account_info!(next_hashing_account, CommitmentHashingAccount::SIZE);
 
         let batching_rate = 4;
         let commitment_count = commitments_per_batch(batching_rate);
@@ -1446,7 +2640,14 @@ mod tests {
         }
 
         for _ in 0..=batching_rate {
-            finalize_commitment_hash(&mut hashing_account, &mut storage_account).unwrap();
+            finalize_commitment_hash(
+                &mut hashing_account,
+                &next_hashing_account,
+                &mut storage_account,
+                0,
+                0,
+            )
+            .unwrap();
         }
 
         assert!(!hashing_account.get_is_active());
@@ -0,0 +1,994 @@
+use super::utils::current_timestamp;
+use crate::commitment::MAX_COMMITMENT_BATCHING_RATE;
+use crate::error::ElusivError;
+use crate::macros::guard;
+use crate::state::governor::{
+    GovernanceProposal, GovernanceProposalsAccount, GovernorAccount, PendingGovernanceProposal,
+    PoolLedgerAccount, TokenVolumeAccount, GOVERNANCE_TIMELOCK, MAX_GOVERNANCE_SIGNERS,
+    MAX_PENDING_GOVERNANCE_PROPOSALS,
+};
+use crate::token::{TokenID, SPL_TOKEN_COUNT};
+use elusiv_types::ElusivOption;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Proposes a timelocked [`GovernanceProposal`]
+///
+/// # Notes
+///
+/// Without registered signers, a proposal is approved by the governance authority right away.
+pub fn propose_governance_change(
+    authority: &AccountInfo,
+    governor: &GovernorAccount,
+    proposals: &mut GovernanceProposalsAccount,
+
+    proposal: GovernanceProposal,
+) -> ProgramResult {
+    propose_governance_change_at(
+        authority,
+        governor,
+        proposals,
+        proposal,
+        current_timestamp()?,
+    )
+}
+
+fn propose_governance_change_at(
+    authority: &AccountInfo,
+    governor: &GovernorAccount,
+    proposals: &mut GovernanceProposalsAccount,
+    proposal: GovernanceProposal,
+    timestamp: u64,
+) -> ProgramResult {
+    verify_governance_authority(authority, governor)?;
+    guard!(
+        is_valid_governance_proposal(&proposal),
+        ElusivError::InvalidInstructionData
+    );
+
+    let index = (0..MAX_PENDING_GOVERNANCE_PROPOSALS)
+        .find(|&i| proposals.get_proposals(i).option().is_none())
+        .ok_or(ElusivError::InvalidAccountState)?;
+
+    let unlock_timestamp = if governor.get_threshold() == 0 {
+        Some(unlock_timestamp(timestamp)?)
+    } else {
+        None
+    };
+
+    proposals.set_proposals(
+        index,
+        &ElusivOption::Some(PendingGovernanceProposal {
+            proposal,
+            approvals: [false; MAX_GOVERNANCE_SIGNERS],
+            unlock_timestamp: unlock_timestamp.into(),
+        }),
+    );
+
+    Ok(())
+}
+
+/// Approves a pending [`GovernanceProposal`]
+///
+/// # Notes
+///
+/// Once `threshold` signers have approved, the proposal can be executed after [`GOVERNANCE_TIMELOCK`].
+pub fn approve_governance_proposal(
+    signer: &AccountInfo,
+    governor: &GovernorAccount,
+    proposals: &mut GovernanceProposalsAccount,
+
+    proposal_index: u8,
+) -> ProgramResult {
+    approve_governance_proposal_at(
+        signer,
+        governor,
+        proposals,
+        proposal_index,
+        current_timestamp()?,
+    )
+}
+
+fn approve_governance_proposal_at(
+    signer: &AccountInfo,
+    governor: &GovernorAccount,
+    proposals: &mut GovernanceProposalsAccount,
+    proposal_index: u8,
+    timestamp: u64,
+) -> ProgramResult {
+    let threshold = governor.get_threshold() as usize;
+    guard!(threshold > 0, ElusivError::InvalidAccountState);
+
+    let index = proposal_index as usize;
+    guard!(
+        index < MAX_PENDING_GOVERNANCE_PROPOSALS,
+        ElusivError::InvalidInstructionData
+    );
+    let mut pending = proposals
+        .get_proposals(index)
+        .option()
+        .ok_or(ElusivError::InvalidAccountState)?;
+
+    let key = signer
+        .signer_key()
+        .ok_or(ProgramError::MissingRequiredSignature)?;
+    let signer_index = (0..MAX_GOVERNANCE_SIGNERS)
+        .find(|&i| governor.get_signers(i).option() == Some(*key))
+        .ok_or(ElusivError::InvalidAccount)?;
+
+    pending.approvals[signer_index] = true;
+
+    let approvals = pending.approvals.iter().filter(|&&a| a).count();
+    if approvals >= threshold && pending.unlock_timestamp.option().is_none() {
+        pending.unlock_timestamp = Some(unlock_timestamp(timestamp)?).into();
+    }
+
+    proposals.set_proposals(index, &ElusivOption::Some(pending));
+
+    Ok(())
+}
+
+/// Removes a pending [`GovernanceProposal`]
+pub fn cancel_governance_proposal(
+    authority: &AccountInfo,
+    governor: &GovernorAccount,
+    proposals: &mut GovernanceProposalsAccount,
+
+    proposal_index: u8,
+) -> ProgramResult {
+    verify_governance_authority(authority, governor)?;
+
+    let index = proposal_index as usize;
+    guard!(
+        index < MAX_PENDING_GOVERNANCE_PROPOSALS,
+        ElusivError::InvalidInstructionData
+    );
+    guard!(
+        proposals.get_proposals(index).option().is_some(),
+        ElusivError::InvalidAccountState
+    );
+
+    proposals.set_proposals(index, &ElusivOption::None);
+
+    Ok(())
+}
+
+/// Changes the governance authority and signers of the [`GovernorAccount`]
+///
+/// # Notes
+///
+/// Requires an unlocked [`GovernanceProposal::Authority`] proposal.
+/// All other pending proposals are removed, since their approvals refer to the previous signers.
+pub fn change_governance_authority(
+    governor: &mut GovernorAccount,
+    proposals: &mut GovernanceProposalsAccount,
+
+    authority: ElusivOption<Pubkey>,
+    signers: [ElusivOption<Pubkey>; MAX_GOVERNANCE_SIGNERS],
+    threshold: u8,
+) -> ProgramResult {
+    change_governance_authority_at(
+        governor,
+        proposals,
+        authority,
+        signers,
+        threshold,
+        current_timestamp()?,
+    )
+}
+
+fn change_governance_authority_at(
+    governor: &mut GovernorAccount,
+    proposals: &mut GovernanceProposalsAccount,
+    authority: ElusivOption<Pubkey>,
+    signers: [ElusivOption<Pubkey>; MAX_GOVERNANCE_SIGNERS],
+    threshold: u8,
+    timestamp: u64,
+) -> ProgramResult {
+    execute_governance_proposal_at(
+        proposals,
+        &GovernanceProposal::Authority {
+            authority,
+            signers,
+            threshold,
+        },
+        timestamp,
+    )?;
+
+    governor.set_authority(&authority);
+    for (i, signer) in signers.iter().enumerate() {
+        governor.set_signers(i, signer);
+    }
+    governor.set_threshold(&threshold);
+
+    for i in 0..MAX_PENDING_GOVERNANCE_PROPOSALS {
+        proposals.set_proposals(i, &ElusivOption::None);
+    }
+
+    Ok(())
+}
+
+/// Sets the deposit and withdrawal limits of a token in the [`TokenVolumeAccount`]
+///
+/// # Notes
+///
+/// Requires an unlocked [`GovernanceProposal::TokenVolumeLimits`] proposal.
+/// The volumes already tracked in the current window are kept.
+pub fn set_token_volume_limits(
+    token_volume: &mut TokenVolumeAccount,
+    proposals: &mut GovernanceProposalsAccount,
+
+    token_id: TokenID,
+    deposit_limit: ElusivOption<u64>,
+    withdrawal_limit: ElusivOption<u64>,
+) -> ProgramResult {
+    set_token_volume_limits_at(
+        token_volume,
+        proposals,
+        token_id,
+        deposit_limit,
+        withdrawal_limit,
+        current_timestamp()?,
+    )
+}
+
+fn set_token_volume_limits_at(
+    token_volume: &mut TokenVolumeAccount,
+    proposals: &mut GovernanceProposalsAccount,
+    token_id: TokenID,
+    deposit_limit: ElusivOption<u64>,
+    withdrawal_limit: ElusivOption<u64>,
+    timestamp: u64,
+) -> ProgramResult {
+    execute_governance_proposal_at(
+        proposals,
+        &GovernanceProposal::TokenVolumeLimits {
+            token_id,
+            deposit_limit,
+            withdrawal_limit,
+        },
+        timestamp,
+    )?;
+
+    let mut volume = token_volume.get_volumes(token_id as usize);
+    volume.deposit_limit = deposit_limit;
+    volume.withdrawal_limit = withdrawal_limit;
+    token_volume.set_volumes(token_id as usize, &volume);
+
+    Ok(())
+}
+
+/// Delists a token, so that no new deposits are accepted (withdrawals are still possible)
+///
+/// # Notes
+///
+/// In contrast to all other governance changes, delisting is not timelocked and can be performed by the governance authority or any registered signer.
+pub fn delist_token(
+    signer: &AccountInfo,
+    governor: &GovernorAccount,
+    token_volume: &mut TokenVolumeAccount,
+
+    token_id: TokenID,
+) -> ProgramResult {
+    let key = signer
+        .signer_key()
+        .ok_or(ProgramError::MissingRequiredSignature)?;
+    guard!(
+        governor.get_authority().option() == Some(*key)
+            || (0..MAX_GOVERNANCE_SIGNERS).any(|i| governor.get_signers(i).option() == Some(*key)),
+        ElusivError::InvalidAccount
+    );
+    guard!(
+        token_id as usize <= SPL_TOKEN_COUNT,
+        ElusivError::UnsupportedToken
+    );
+
+    token_volume.set_delisted(token_id as usize, &true);
+
+    Ok(())
+}
+
+/// Relists a token delisted by [`delist_token`]
+///
+/// # Notes
+///
+/// Requires an unlocked [`GovernanceProposal::RelistToken`] proposal.
+pub fn relist_token(
+    token_volume: &mut TokenVolumeAccount,
+    proposals: &mut GovernanceProposalsAccount,
+
+    token_id: TokenID,
+) -> ProgramResult {
+    relist_token_at(token_volume, proposals, token_id, current_timestamp()?)
+}
+
+fn relist_token_at(
+    token_volume: &mut TokenVolumeAccount,
+    proposals: &mut GovernanceProposalsAccount,
+    token_id: TokenID,
+    timestamp: u64,
+) -> ProgramResult {
+    execute_governance_proposal_at(
+        proposals,
+        &GovernanceProposal::RelistToken { token_id },
+        timestamp,
+    )?;
+
+    token_volume.set_delisted(token_id as usize, &false);
+
+    Ok(())
+}
+
+/// Sets the liabilities of the [`crate::state::governor::PoolAccount`] from before the [`PoolLedgerAccount`] has been opened
+///
+/// # Notes
+///
+/// Requires an unlocked [`GovernanceProposal::PoolOpeningBalance`] proposal.
+/// The flows already tracked by the ledger are kept.
+pub fn set_pool_opening_balance(
+    pool_ledger: &mut PoolLedgerAccount,
+    proposals: &mut GovernanceProposalsAccount,
+
+    token_id: TokenID,
+    opening_balance: u64,
+) -> ProgramResult {
+    set_pool_opening_balance_at(
+        pool_ledger,
+        proposals,
+        token_id,
+        opening_balance,
+        current_timestamp()?,
+    )
+}
+
+fn set_pool_opening_balance_at(
+    pool_ledger: &mut PoolLedgerAccount,
+    proposals: &mut GovernanceProposalsAccount,
+    token_id: TokenID,
+    opening_balance: u64,
+    timestamp: u64,
+) -> ProgramResult {
+    execute_governance_proposal_at(
+        proposals,
+        &GovernanceProposal::PoolOpeningBalance {
+            token_id,
+            opening_balance,
+        },
+        timestamp,
+    )?;
+
+    let mut balance = pool_ledger.get_balances(token_id as usize);
+    balance.opening_balance = opening_balance;
+    pool_ledger.set_balances(token_id as usize, &balance);
+
+    Ok(())
+}
+
+/// Removes the unlocked pending `proposal`, which is executed by the caller
+pub(crate) fn execute_governance_proposal_at(
+    proposals: &mut GovernanceProposalsAccount,
+    proposal: &GovernanceProposal,
+    timestamp: u64,
+) -> ProgramResult {
+    let index = (0..MAX_PENDING_GOVERNANCE_PROPOSALS)
+        .find(|&i| match proposals.get_proposals(i).option() {
+            Some(pending) => {
+                pending.proposal == *proposal
+                    && pending
+                        .unlock_timestamp
+                        .option()
+                        .map_or(false, |unlock_timestamp| timestamp >= unlock_timestamp)
+            }
+            None => false,
+        })
+        .ok_or(ElusivError::InvalidAccountState)?;
+
+    proposals.set_proposals(index, &ElusivOption::None);
+
+    Ok(())
+}
+
+pub(crate) fn verify_governance_authority(
+    authority: &AccountInfo,
+    governor: &GovernorAccount,
+) -> ProgramResult {
+    let key = authority
+        .signer_key()
+        .ok_or(ProgramError::MissingRequiredSignature)?;
+
+    guard!(
+        governor.get_authority().option() == Some(*key),
+        ElusivError::InvalidAccount
+    );
+
+    Ok(())
+}
+
+fn is_valid_governance_proposal(proposal: &GovernanceProposal) -> bool {
+    match proposal {
+        GovernanceProposal::NewFeeVersion { program_fee, .. } => program_fee.is_valid(),
+        GovernanceProposal::GovernorState { batching_rate, .. } => {
+            *batching_rate as usize <= MAX_COMMITMENT_BATCHING_RATE
+        }
+        GovernanceProposal::Authority {
+            signers, threshold, ..
+        } => {
+            let keys: Vec<Pubkey> = signers.iter().filter_map(|s| s.option()).collect();
+            let unique = keys
+                .iter()
+                .enumerate()
+                .all(|(i, key)| !keys[..i].contains(key));
+
+            unique && *threshold as usize <= keys.len()
+        }
+        GovernanceProposal::TokenVolumeLimits { token_id, .. }
+        | GovernanceProposal::RelistToken { token_id }
+        | GovernanceProposal::PoolOpeningBalance { token_id, .. } => {
+            *token_id as usize <= SPL_TOKEN_COUNT
+        }
+    }
+}
+
+fn unlock_timestamp(timestamp: u64) -> Result<u64, ProgramError> {
+    timestamp
+        .checked_add(GOVERNANCE_TIMELOCK)
+        .ok_or_else(|| ElusivError::InvalidAccountState.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::macros::{signing_test_account_info, test_account_info, zero_program_account};
+    use crate::state::fee::ProgramFee;
+    use crate::state::governor::PoolFlow;
+    use assert_matches::assert_matches;
+
+    fn fee_proposal(fee_version: u32) -> GovernanceProposal {
+        GovernanceProposal::NewFeeVersion {
+            fee_version,
+            program_fee: ProgramFee::new(5000, 11, 100, 33, 44, 300, 555).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_propose_governance_change() {
+        zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut proposals, GovernanceProposalsAccount);
+        signing_test_account_info!(authority);
+        signing_test_account_info!(invalid_authority);
+        test_account_info!(non_signer);
+
+        // No authority set
+        assert_matches!(
+            propose_governance_change_at(&authority, &governor, &mut proposals, fee_proposal(1), 0),
+            Err(_)
+        );
+
+        governor.set_authority(&Some(*authority.key).into());
+
+        assert_matches!(
+            propose_governance_change_at(
+                &invalid_authority,
+                &governor,
+                &mut proposals,
+                fee_proposal(1),
+                0
+            ),
+            Err(_)
+        );
+
+        governor.set_authority(&Some(*non_signer.key).into());
+        assert_matches!(
+            propose_governance_change_at(
+                &non_signer,
+                &governor,
+                &mut proposals,
+                fee_proposal(1),
+                0
+            ),
+            Err(_)
+        );
+        governor.set_authority(&Some(*authority.key).into());
+
+        // Invalid batching rate
+        assert_matches!(
+            propose_governance_change_at(
+                &authority,
+                &governor,
+                &mut proposals,
+                GovernanceProposal::GovernorState {
+                    fee_version: 0,
+                    batching_rate: MAX_COMMITMENT_BATCHING_RATE as u32 + 1
+                },
+                0
+            ),
+            Err(_)
+        );
+
+        // Invalid threshold
+        assert_matches!(
+            propose_governance_change_at(
+                &authority,
+                &governor,
+                &mut proposals,
+                GovernanceProposal::Authority {
+                    authority: ElusivOption::None,
+                    signers: [ElusivOption::None; MAX_GOVERNANCE_SIGNERS],
+                    threshold: 1
+                },
+                0
+            ),
+            Err(_)
+        );
+
+        // Without signers proposals are approved right away
+        for i in 0..MAX_PENDING_GOVERNANCE_PROPOSALS {
+            assert_matches!(
+                propose_governance_change_at(
+                    &authority,
+                    &governor,
+                    &mut proposals,
+                    fee_proposal(1),
+                    100
+                ),
+                Ok(())
+            );
+
+            let pending = proposals.get_proposals(i).option().unwrap();
+            assert_eq!(pending.proposal, fee_proposal(1));
+            assert_eq!(
+                pending.unlock_timestamp.option(),
+                Some(100 + GOVERNANCE_TIMELOCK)
+            );
+        }
+
+        // Too many pending proposals
+        assert_matches!(
+            propose_governance_change_at(&authority, &governor, &mut proposals, fee_proposal(1), 0),
+            Err(_)
+        );
+
+        cancel_governance_proposal(&authority, &governor, &mut proposals, 3).unwrap();
+        assert!(proposals.get_proposals(3).option().is_none());
+        assert_matches!(
+            cancel_governance_proposal(&authority, &governor, &mut proposals, 3),
+            Err(_)
+        );
+        assert_matches!(
+            cancel_governance_proposal(&invalid_authority, &governor, &mut proposals, 4),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_approve_governance_proposal() {
+        zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut proposals, GovernanceProposalsAccount);
+        signing_test_account_info!(authority);
+        signing_test_account_info!(a);
+        signing_test_account_info!(b);
+        signing_test_account_info!(invalid_signer);
+
+        governor.set_authority(&Some(*authority.key).into());
+        propose_governance_change_at(&authority, &governor, &mut proposals, fee_proposal(1), 0)
+            .unwrap();
+
+        // No signers registered
+        assert_matches!(
+            approve_governance_proposal_at(&a, &governor, &mut proposals, 0, 0),
+            Err(_)
+        );
+
+        governor.set_signers(0, &Some(*a.key).into());
+        governor.set_signers(1, &Some(*b.key).into());
+        governor.set_threshold(&2);
+        proposals.set_proposals(0, &ElusivOption::None);
+        propose_governance_change_at(&authority, &governor, &mut proposals, fee_proposal(1), 0)
+            .unwrap();
+        assert!(proposals
+            .get_proposals(0)
+            .option()
+            .unwrap()
+            .unlock_timestamp
+            .option()
+            .is_none());
+
+        assert_matches!(
+            approve_governance_proposal_at(&invalid_signer, &governor, &mut proposals, 0, 0),
+            Err(_)
+        );
+        assert_matches!(
+            approve_governance_proposal_at(&a, &governor, &mut proposals, 1, 0),
+            Err(_)
+        );
+        assert_matches!(
+            approve_governance_proposal_at(
+                &a,
+                &governor,
+                &mut proposals,
+                MAX_PENDING_GOVERNANCE_PROPOSALS as u8,
+                0
+            ),
+            Err(_)
+        );
+
+        // Threshold not reached
+        approve_governance_proposal_at(&a, &governor, &mut proposals, 0, 10).unwrap();
+        approve_governance_proposal_at(&a, &governor, &mut proposals, 0, 10).unwrap();
+        assert_matches!(
+            execute_governance_proposal_at(&mut proposals, &fee_proposal(1), u64::MAX),
+            Err(_)
+        );
+
+        // Threshold reached
+        approve_governance_proposal_at(&b, &governor, &mut proposals, 0, 20).unwrap();
+        let unlock_timestamp = 20 + GOVERNANCE_TIMELOCK;
+        assert_eq!(
+            proposals
+                .get_proposals(0)
+                .option()
+                .unwrap()
+                .unlock_timestamp
+                .option(),
+            Some(unlock_timestamp)
+        );
+
+        // Timelock
+        assert_matches!(
+            execute_governance_proposal_at(&mut proposals, &fee_proposal(1), unlock_timestamp - 1),
+            Err(_)
+        );
+
+        // Mismatching proposal
+        assert_matches!(
+            execute_governance_proposal_at(&mut proposals, &fee_proposal(2), unlock_timestamp),
+            Err(_)
+        );
+
+        assert_matches!(
+            execute_governance_proposal_at(&mut proposals, &fee_proposal(1), unlock_timestamp),
+            Ok(())
+        );
+        assert!(proposals.get_proposals(0).option().is_none());
+
+        // Executed only once
+        assert_matches!(
+            execute_governance_proposal_at(&mut proposals, &fee_proposal(1), unlock_timestamp),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_change_governance_authority() {
+        zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut proposals, GovernanceProposalsAccount);
+        signing_test_account_info!(authority);
+
+        let new_authority = Pubkey::new_unique();
+        let mut signers = [ElusivOption::None; MAX_GOVERNANCE_SIGNERS];
+        signers[1] = Some(Pubkey::new_unique()).into();
+        signers[3] = Some(Pubkey::new_unique()).into();
+
+        // Duplicate signers
+        governor.set_authority(&Some(*authority.key).into());
+        let mut duplicate_signers = signers;
+        duplicate_signers[0] = signers[1];
+        assert_matches!(
+            propose_governance_change_at(
+                &authority,
+                &governor,
+                &mut proposals,
+                GovernanceProposal::Authority {
+                    authority: Some(new_authority).into(),
+                    signers: duplicate_signers,
+                    threshold: 1,
+                },
+                0
+            ),
+            Err(_)
+        );
+
+        propose_governance_change_at(
+            &authority,
+            &governor,
+            &mut proposals,
+            GovernanceProposal::Authority {
+                authority: Some(new_authority).into(),
+                signers,
+                threshold: 2,
+            },
+            0,
+        )
+        .unwrap();
+        propose_governance_change_at(&authority, &governor, &mut proposals, fee_proposal(1), 0)
+            .unwrap();
+
+        // Timelock
+        assert_matches!(
+            change_governance_authority_at(
+                &mut governor,
+                &mut proposals,
+                Some(new_authority).into(),
+                signers,
+                2,
+                GOVERNANCE_TIMELOCK - 1
+            ),
+            Err(_)
+        );
+
+        // Mismatching threshold
+        assert_matches!(
+            change_governance_authority_at(
+                &mut governor,
+                &mut proposals,
+                Some(new_authority).into(),
+                signers,
+                1,
+                GOVERNANCE_TIMELOCK
+            ),
+            Err(_)
+        );
+
+        assert_matches!(
+            change_governance_authority_at(
+                &mut governor,
+                &mut proposals,
+                Some(new_authority).into(),
+                signers,
+                2,
+                GOVERNANCE_TIMELOCK
+            ),
+            Ok(())
+        );
+
+        assert_eq!(governor.get_authority().option(), Some(new_authority));
+        assert_eq!(governor.get_signers(3), signers[3]);
+        assert_eq!(governor.get_threshold(), 2);
+
+        // All pending proposals are removed
+        for i in 0..MAX_PENDING_GOVERNANCE_PROPOSALS {
+            assert!(proposals.get_proposals(i).option().is_none());
+        }
+    }
+
+    #[test]
+    fn test_set_token_volume_limits() {
+        zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut proposals, GovernanceProposalsAccount);
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
+        signing_test_account_info!(authority);
+        governor.set_authority(&Some(*authority.key).into());
+
+        // Invalid token-id
+        assert_matches!(
+            propose_governance_change_at(
+                &authority,
+                &governor,
+                &mut proposals,
+                GovernanceProposal::TokenVolumeLimits {
+                    token_id: SPL_TOKEN_COUNT as u16 + 1,
+                    deposit_limit: ElusivOption::None,
+                    withdrawal_limit: ElusivOption::None,
+                },
+                0
+            ),
+            Err(_)
+        );
+
+        propose_governance_change_at(
+            &authority,
+            &governor,
+            &mut proposals,
+            GovernanceProposal::TokenVolumeLimits {
+                token_id: 1,
+                deposit_limit: ElusivOption::Some(100),
+                withdrawal_limit: ElusivOption::None,
+            },
+            0,
+        )
+        .unwrap();
+        token_volume.track_deposit(1, 10, 0).unwrap();
+
+        // Timelock
+        assert_matches!(
+            set_token_volume_limits_at(
+                &mut token_volume,
+                &mut proposals,
+                1,
+                ElusivOption::Some(100),
+                ElusivOption::None,
+                GOVERNANCE_TIMELOCK - 1
+            ),
+            Err(_)
+        );
+
+        // Mismatching limits
+        assert_matches!(
+            set_token_volume_limits_at(
+                &mut token_volume,
+                &mut proposals,
+                1,
+                ElusivOption::Some(101),
+                ElusivOption::None,
+                GOVERNANCE_TIMELOCK
+            ),
+            Err(_)
+        );
+
+        assert_matches!(
+            set_token_volume_limits_at(
+                &mut token_volume,
+                &mut proposals,
+                1,
+                ElusivOption::Some(100),
+                ElusivOption::None,
+                GOVERNANCE_TIMELOCK
+            ),
+            Ok(())
+        );
+
+        let volume = token_volume.get_volumes(1);
+        assert_eq!(volume.deposit_limit.option(), Some(100));
+        assert_eq!(volume.withdrawal_limit.option(), None);
+        assert_eq!(volume.deposit_volume, 10);
+
+        // The proposal is consumed
+        assert!(proposals.get_proposals(0).option().is_none());
+    }
+
+    #[test]
+    fn test_set_pool_opening_balance() {
+        zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut proposals, GovernanceProposalsAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
+        signing_test_account_info!(authority);
+        governor.set_authority(&Some(*authority.key).into());
+
+        // Invalid token-id
+        assert_matches!(
+            propose_governance_change_at(
+                &authority,
+                &governor,
+                &mut proposals,
+                GovernanceProposal::PoolOpeningBalance {
+                    token_id: SPL_TOKEN_COUNT as u16 + 1,
+                    opening_balance: 1,
+                },
+                0
+            ),
+            Err(_)
+        );
+
+        propose_governance_change_at(
+            &authority,
+            &governor,
+            &mut proposals,
+            GovernanceProposal::PoolOpeningBalance {
+                token_id: 1,
+                opening_balance: 1_000,
+            },
+            0,
+        )
+        .unwrap();
+        pool_ledger.track(1, PoolFlow::Deposit, 10).unwrap();
+
+        // Timelock
+        assert_matches!(
+            set_pool_opening_balance_at(
+                &mut pool_ledger,
+                &mut proposals,
+                1,
+                1_000,
+                GOVERNANCE_TIMELOCK - 1
+            ),
+            Err(_)
+        );
+
+        // Mismatching balance
+        assert_matches!(
+            set_pool_opening_balance_at(
+                &mut pool_ledger,
+                &mut proposals,
+                1,
+                1_001,
+                GOVERNANCE_TIMELOCK
+            ),
+            Err(_)
+        );
+
+        assert_matches!(
+            set_pool_opening_balance_at(
+                &mut pool_ledger,
+                &mut proposals,
+                1,
+                1_000,
+                GOVERNANCE_TIMELOCK
+            ),
+            Ok(())
+        );
+
+        let balance = pool_ledger.get_balances(1);
+        assert_eq!(balance.opening_balance, 1_000);
+        assert_eq!(balance.deposited, 10);
+        assert_eq!(balance.liabilities(), 1_010);
+
+        // The proposal is consumed
+        assert!(proposals.get_proposals(0).option().is_none());
+    }
+
+    #[test]
+    fn test_delist_token() {
+        zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut proposals, GovernanceProposalsAccount);
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
+        signing_test_account_info!(authority);
+        signing_test_account_info!(signer);
+        signing_test_account_info!(invalid_signer);
+        test_account_info!(non_signer);
+
+        governor.set_authority(&Some(*authority.key).into());
+        governor.set_signers(2, &Some(*signer.key).into());
+
+        assert_matches!(
+            delist_token(&invalid_signer, &governor, &mut token_volume, 1),
+            Err(_)
+        );
+        assert_matches!(
+            delist_token(&non_signer, &governor, &mut token_volume, 1),
+            Err(_)
+        );
+
+        // Invalid token-id
+        assert_matches!(
+            delist_token(
+                &authority,
+                &governor,
+                &mut token_volume,
+                SPL_TOKEN_COUNT as u16 + 1
+            ),
+            Err(_)
+        );
+
+        assert_matches!(
+            delist_token(&signer, &governor, &mut token_volume, 1),
+            Ok(())
+        );
+        assert!(token_volume.get_delisted(1));
+        assert_matches!(
+            delist_token(&authority, &governor, &mut token_volume, 2),
+            Ok(())
+        );
+        assert!(token_volume.get_delisted(2));
+
+        // Relisting is timelocked
+        propose_governance_change_at(
+            &authority,
+            &governor,
+            &mut proposals,
+            GovernanceProposal::RelistToken { token_id: 1 },
+            0,
+        )
+        .unwrap();
+        assert_matches!(
+            relist_token_at(
+                &mut token_volume,
+                &mut proposals,
+                1,
+                GOVERNANCE_TIMELOCK - 1
+            ),
+            Err(_)
+        );
+        assert_matches!(
+            relist_token_at(&mut token_volume, &mut proposals, 2, GOVERNANCE_TIMELOCK),
+            Err(_)
+        );
+        assert_matches!(
+            relist_token_at(&mut token_volume, &mut proposals, 1, GOVERNANCE_TIMELOCK),
+            Ok(())
+        );
+        assert!(!token_volume.get_delisted(1));
+        assert!(token_volume.get_delisted(2));
+    }
+}
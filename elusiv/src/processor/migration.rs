@@ -0,0 +1,941 @@
+use super::utils::{realloc_account, verify_upgrade_authority};
+use super::vkey::acquire_vkey_version;
+use crate::bytes::{usize_as_u32_safe, SizedType};
+use crate::commitment::poseidon_hash::BinarySpongeHashingState;
+use crate::error::ElusivError;
+use crate::fields::{G2HomProjective, Wrap, G1A, G2A};
+use crate::macros::{guard, pda_account};
+use crate::processor::{CommitmentHashRequest, ProofRequest, MAX_MT_COUNT};
+use crate::proof::plonk::verifier::PLONK_MSM_TERMS_COUNT;
+use crate::proof::verifier::VerificationStep;
+use crate::state::{
+    commitment::{
+        hashing_account_pda_offset, BaseCommitmentBufferAccount, BaseCommitmentHashingAccount,
+        CommitmentHashingAccount,
+    },
+    fee::{FeeAccount, ProgramFee},
+    governor::{FeeCollectorAccount, GovernanceProposalsAccount, GovernorAccount, PoolAccount},
+    nullifier::NullifierAccount,
+    program_account::{PDAAccount, PDAAccountData, PDAOffset, SizedAccount},
+    proof::{
+        RAMFq, RAMFq12, RAMFq2, RAMFq6, RAMG1Projective, VerificationAccount,
+        VerificationAccountData, VerificationState, MAX_PREPARE_INPUTS_INSTRUCTIONS,
+        MAX_PUBLIC_INPUTS_COUNT,
+    },
+    queue::{
+        CommitmentQueue, CommitmentQueueAccount, RingQueue,
+        COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT,
+    },
+    storage::{shard_pda_offset, StorageAccount, HISTORY_ARRAY_SIZE},
+    vkey::VKeyAccount,
+};
+use crate::types::{PlonkProof, ProgramCall, RawU256, U256};
+use ark_bn254::Fq12;
+use borsh::{BorshDeserialize, BorshSerialize};
+use elusiv_types::{BorshSerDeSized, ElusivOption};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// The [`PDAAccount`]s that can be migrated to the current [`crate::PROGRAM_VERSION`]
+#[derive(BorshDeserialize, BorshSerialize, PartialEq, Clone, Copy)]
+#[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
+pub enum MigratableAccount {
+    Governor,
+    GovernanceProposals,
+    Pool,
+    FeeCollector,
+    CommitmentHashing {
+        shard: u32,
+        hashing_account_index: u32,
+    },
+    CommitmentQueue {
+        shard: u32,
+    },
+    BaseCommitmentBuffer,
+    Storage {
+        shard: u32,
+    },
+    Fee {
+        fee_version: u32,
+    },
+    Nullifier {
+        mt_index: u32,
+    },
+    VKey {
+        vkey_id: u32,
+    },
+    BaseCommitmentHashing {
+        hash_account_index: u32,
+    },
+}
+
+/// A layout change of a [`PDAAccount`] from `version` to `version + 1`
+pub struct AccountMigration {
+    /// The [`PDAAccount::SEED`] of the account
+    pub seed: &'static [u8],
+
+    /// The version the migration is applied to
+    pub version: u8,
+
+    /// The account size after the migration
+    pub size: usize,
+
+    /// Migrates the (already resized) account data in place
+    pub migrate: fn(&mut [u8]) -> ProgramResult,
+}
+
+/// All layout changes of the program accounts
+///
+/// # Notes
+///
+/// Accounts without a layout change between two versions are only tagged with the next version.
+/// Since the [`PDAAccount::VERSION`] of every account is the [`crate::PROGRAM_VERSION`], this plain version bump is still required
+/// for each of them (e.g. all [`NullifierAccount`]s, [`FeeAccount`]s, the [`PoolAccount`] and the [`FeeCollectorAccount`]).
+const ACCOUNT_MIGRATIONS: &[AccountMigration] = &[
+    AccountMigration {
+        seed: CommitmentQueueAccount::SEED,
+        version: 0,
+        size: CommitmentQueueAccount::SIZE,
+        migrate: migrate_commitment_queue_account_v0,
+    },
+    AccountMigration {
+        seed: CommitmentHashingAccount::SEED,
+        version: 0,
+        size: CommitmentHashingAccount::SIZE,
+        migrate: migrate_commitment_hashing_account_v0,
+    },
+    AccountMigration {
+        seed: GovernorAccount::SEED,
+        version: 0,
+        size: GovernorAccount::SIZE,
+        migrate: migrate_governor_account_v0,
+    },
+    AccountMigration {
+        seed: VKeyAccount::SEED,
+        version: 0,
+        size: VKeyAccount::SIZE,
+        migrate: migrate_vkey_account_v0,
+    },
+    AccountMigration {
+        seed: VerificationAccount::SEED,
+        version: 0,
+        size: VerificationAccount::SIZE,
+        migrate: migrate_verification_account_v0,
+    },
+    AccountMigration {
+        seed: StorageAccount::SEED,
+        version: 0,
+        size: StorageAccount::SIZE,
+        migrate: migrate_storage_account_v0,
+    },
+    AccountMigration {
+        seed: BaseCommitmentHashingAccount::SEED,
+        version: 0,
+        size: BaseCommitmentHashingAccount::SIZE,
+        migrate: migrate_base_commitment_hashing_account_v0,
+    },
+];
+
+/// A part of an account layout during a migration
+#[derive(Clone, Copy)]
+enum LayoutSegment {
+    /// Bytes of the previous layout, that are kept
+    Keep(usize),
+
+    /// Zero-initialized bytes of a new field
+    Insert(usize),
+}
+
+use LayoutSegment::{Insert, Keep};
+
+/// Moves the kept bytes of the previous layout to their offsets in the new layout
+///
+/// # Notes
+///
+/// - `data` already has the size of the new layout (with the previous layout at the start).
+/// - Since fields are only inserted, bytes are only moved towards the end (so the segments are processed in reverse order).
+fn insert_fields(data: &mut [u8], segments: &[LayoutSegment]) -> ProgramResult {
+    let size = |keep_only: bool| {
+        segments
+            .iter()
+            .map(|segment| match segment {
+                Keep(len) => *len,
+                Insert(len) => usize::from(!keep_only) * len,
+            })
+            .sum::<usize>()
+    };
+    let (mut previous_end, mut end) = (size(true), size(false));
+    guard!(end == data.len(), ProgramError::InvalidAccountData);
+
+    for segment in segments.iter().rev() {
+        match *segment {
+            Keep(len) => {
+                data.copy_within(previous_end - len..previous_end, end - len);
+                previous_end -= len;
+                end -= len;
+            }
+            Insert(len) => {
+                data[end - len..end].fill(0);
+                end -= len;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Inserts the overflow child-accounts before and the `overflow_accounts_count` after `head` and `tail`
+fn migrate_commitment_queue_account_v0(data: &mut [u8]) -> ProgramResult {
+    insert_fields(
+        data,
+        &[
+            Keep(PDAAccountData::SIZE),
+            Insert(<ElusivOption<Pubkey>>::SIZE * COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT),
+            Keep(4 + 4),
+            Insert(4),
+            Keep(CommitmentHashRequest::SIZE * CommitmentQueue::SIZE as usize),
+        ],
+    )
+}
+
+/// Appends the `pending_siblings` (no siblings are pending)
+fn migrate_commitment_hashing_account_v0(data: &mut [u8]) -> ProgramResult {
+    insert_fields(data, &[Keep(CommitmentHashingAccount::SIZE - 4), Insert(4)])
+}
+
+/// The size of the [`GovernorAccount`] (`pda_data`, `fee_version`, `program_fee`, `commitment_batching_rate`, `program_version`) in version 0
+const GOVERNOR_ACCOUNT_V0_SIZE: usize = PDAAccountData::SIZE + 4 + ProgramFee::SIZE + 4 + 4;
+
+/// Appends the governance authority, the signers, the `threshold` and the `commitment_shards_count`
+fn migrate_governor_account_v0(data: &mut [u8]) -> ProgramResult {
+    insert_fields(
+        data,
+        &[
+            Keep(GOVERNOR_ACCOUNT_V0_SIZE),
+            Insert(GovernorAccount::SIZE - GOVERNOR_ACCOUNT_V0_SIZE),
+        ],
+    )
+}
+
+/// The size of the [`VKeyAccount`] (`pda_data`, two child-accounts, `public_inputs_count`, `authority`, `is_frozen`, `version`) in version 0
+const VKEY_ACCOUNT_V0_SIZE: usize =
+    PDAAccountData::SIZE + 3 * <ElusivOption<Pubkey>>::SIZE + 4 + 1 + 4;
+
+/// Inserts the (closed) previous-version child-account and appends the `proof_system` (Groth16), the verification counters and the signers
+fn migrate_vkey_account_v0(data: &mut [u8]) -> ProgramResult {
+    let child_accounts_size = PDAAccountData::SIZE + 2 * <ElusivOption<Pubkey>>::SIZE;
+
+    insert_fields(
+        data,
+        &[
+            Keep(child_accounts_size),
+            Insert(<ElusivOption<Pubkey>>::SIZE),
+            Keep(VKEY_ACCOUNT_V0_SIZE - child_accounts_size),
+            Insert(VKeyAccount::SIZE - VKEY_ACCOUNT_V0_SIZE - <ElusivOption<Pubkey>>::SIZE),
+        ],
+    )
+}
+
+/// The size of the [`VerificationAccountData`] in version 0 (without `commitment_shard`, `program_call` and `withdrawal_window_start`)
+const VERIFICATION_ACCOUNT_DATA_V0_SIZE: usize =
+    VerificationAccountData::SIZE - 4 - <ElusivOption<ProgramCall>>::SIZE - 8;
+
+/// Inserts the `vkey_version` and the PLONK fields and appends the new fields of the `other_data`
+///
+/// # Notes
+///
+/// No withdrawal has been reserved by a verification of version 0, so `withdrawal_window_start` is zero-initialized.
+fn migrate_verification_account_v0(data: &mut [u8]) -> ProgramResult {
+    insert_fields(
+        data,
+        &[
+            // pda_data, instruction, round, prepare_inputs_instructions, vkey_id
+            Keep(PDAAccountData::SIZE + 4 + 4 + 4 + 2 * MAX_PREPARE_INPUTS_INSTRUCTIONS + 4),
+            Insert(4),
+            // step, state, public_input, a, b, c
+            Keep(
+                VerificationStep::SIZE
+                    + VerificationState::SIZE
+                    + RawU256::SIZE * MAX_PUBLIC_INPUTS_COUNT
+                    + 2 * G1A::SIZE
+                    + G2A::SIZE,
+            ),
+            Insert(PlonkProof::SIZE),
+            // prepared_inputs, r, f, alt_b, coeff_index
+            Keep(G1A::SIZE + G2HomProjective::SIZE + <Wrap<Fq12>>::SIZE + G2A::SIZE + 1),
+            Insert(U256::SIZE * PLONK_MSM_TERMS_COUNT + 2 * G1A::SIZE),
+            Keep(RAMFq::SIZE + RAMFq2::SIZE + RAMFq6::SIZE + RAMFq12::SIZE),
+            Insert(RAMG1Projective::SIZE),
+            // is_verified, other_data
+            Keep(<ElusivOption<bool>>::SIZE + VERIFICATION_ACCOUNT_DATA_V0_SIZE),
+            Insert(VerificationAccountData::SIZE - VERIFICATION_ACCOUNT_DATA_V0_SIZE),
+            // request, tree_indices
+            Keep(ProofRequest::SIZE + 4 * MAX_MT_COUNT),
+        ],
+    )
+}
+
+/// The size of the [`StorageAccount`] fields kept in place (`pda_data`, `pubkeys`, `next_commitment_ptr`, `trees_count`, `archived_count`)
+const STORAGE_ACCOUNT_HEADER_SIZE: usize =
+    StorageAccount::SIZE - 6 * 4 - U256::SIZE * HISTORY_ARRAY_SIZE;
+
+/// Moves the roots of the `active_mt_root_history` into the `root_history` ring buffer and inserts the root counters, the `shard` and the hashing account indices
+///
+/// # Notes
+///
+/// - In version 0 the root of the batch starting at `ordering` was stored at `ordering % HISTORY_ARRAY_SIZE` (unused entries are zero).
+/// - The roots are inserted in their insertion order (the slot following `next_commitment_ptr` is the oldest) and all belong to the active MT.
+/// - Only the account of shard 0 exists in version 0, with a single hashing account.
+fn migrate_storage_account_v0(data: &mut [u8]) -> ProgramResult {
+    guard!(
+        data.len() == StorageAccount::SIZE,
+        ProgramError::InvalidAccountData
+    );
+
+    let header = STORAGE_ACCOUNT_HEADER_SIZE;
+    let next_commitment_ptr =
+        u32::from_le_bytes(data[header - 12..header - 8].try_into().unwrap()) as usize;
+
+    let roots: Vec<U256> = (0..HISTORY_ARRAY_SIZE)
+        .map(|i| {
+            let offset = header + (next_commitment_ptr + i) % HISTORY_ARRAY_SIZE * U256::SIZE;
+            data[offset..offset + U256::SIZE].try_into().unwrap()
+        })
+        .filter(|root| *root != [0; 32])
+        .collect();
+    let roots_count = usize_as_u32_safe(roots.len());
+
+    // mt_roots_count, closed_mt_roots_count, root_history_count, shard, hashing_accounts_count, next_hashing_account_index
+    let (fields, root_history) = data[header..].split_at_mut(6 * 4);
+    for (i, value) in [roots_count, 0, roots_count, 0, 0, 0].iter().enumerate() {
+        fields[i * 4..(i + 1) * 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    root_history.fill(0);
+    for (i, root) in roots.iter().enumerate() {
+        root_history[i * U256::SIZE..(i + 1) * U256::SIZE].copy_from_slice(root);
+    }
+
+    Ok(())
+}
+
+/// The size of the [`BaseCommitmentHashingAccount`] in version 0 (without `finalized_mask` and `batch`)
+const BASE_COMMITMENT_HASHING_ACCOUNT_V0_SIZE: usize =
+    BaseCommitmentHashingAccount::SIZE - 4 - BinarySpongeHashingState::SIZE;
+
+/// Appends the `finalized_mask` and a `batch` containing the single base commitment
+///
+/// # Notes
+///
+/// The `state` of a hashed base commitment is its final state, which is read from `batch` in version 1.
+fn migrate_base_commitment_hashing_account_v0(data: &mut [u8]) -> ProgramResult {
+    insert_fields(
+        data,
+        &[
+            Keep(BASE_COMMITMENT_HASHING_ACCOUNT_V0_SIZE),
+            Insert(4 + BinarySpongeHashingState::SIZE),
+        ],
+    )?;
+
+    // pda_data, instruction, round, fee_version, fee_payer, is_active, token_id
+    let state = PDAAccountData::SIZE + 4 + 4 + 4 + U256::SIZE + 1 + 2;
+    let batch = BASE_COMMITMENT_HASHING_ACCOUNT_V0_SIZE + 4;
+    data.copy_within(state..state + BinarySpongeHashingState::SIZE, batch);
+
+    Ok(())
+}
+
+/// Migrates a [`PDAAccount`] from its version `N` to `N + 1`
+///
+/// # Notes
+///
+/// - All instructions (except for the migration) are rejected until the [`GovernorAccount`] is migrated to [`crate::PROGRAM_VERSION`], so it is migrated last.
+/// - Instructions also reject each PDA that has not been migrated to its [`PDAAccount::VERSION`] (so an account that is left behind cannot be used with its previous layout).
+/// - Growing an account by more than [`solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE`] bytes requires multiple calls.
+/// - The additional rent is funded by the `payer`.
+/// - The [`GovernorAccount`] of version 0 has no governance authority, so it can only be migrated by the upgrade authority of the program (which becomes the governance authority, as in [`super::setup_governor_account`]).
+pub fn migrate_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    program_data: &AccountInfo,
+    system_program: &AccountInfo<'a>,
+
+    kind: MigratableAccount,
+) -> ProgramResult {
+    match kind {
+        MigratableAccount::Governor => {
+            let previous_version = GovernorAccount::get_version(account);
+            if previous_version == 0 {
+                verify_upgrade_authority(payer, program_data)?;
+            }
+
+            migrate::<GovernorAccount>(payer, account, system_program, None)?;
+
+            let version = GovernorAccount::get_version(account);
+            if previous_version == 0 && version == 1 {
+                pda_account!(mut governor, GovernorAccount, account);
+                governor.set_authority(&Some(*payer.key).into());
+                governor.set_commitment_shards_count(&1);
+            }
+
+            if version == GovernorAccount::VERSION {
+                pda_account!(mut governor, GovernorAccount, account);
+                governor.set_program_version(&u32::from(crate::PROGRAM_VERSION));
+            }
+
+            Ok(())
+        }
+        MigratableAccount::GovernanceProposals => {
+            migrate::<GovernanceProposalsAccount>(payer, account, system_program, None)
+        }
+        MigratableAccount::Pool => migrate::<PoolAccount>(payer, account, system_program, None),
+        MigratableAccount::FeeCollector => {
+            migrate::<FeeCollectorAccount>(payer, account, system_program, None)
+        }
+        MigratableAccount::CommitmentHashing {
+            shard,
+            hashing_account_index,
+        } => migrate::<CommitmentHashingAccount>(
+            payer,
+            account,
+            system_program,
+            hashing_account_pda_offset(shard, hashing_account_index),
+        ),
+        MigratableAccount::CommitmentQueue { shard } => migrate::<CommitmentQueueAccount>(
+            payer,
+            account,
+            system_program,
+            shard_pda_offset(shard),
+        ),
+        MigratableAccount::BaseCommitmentBuffer => {
+            migrate::<BaseCommitmentBufferAccount>(payer, account, system_program, None)
+        }
+        MigratableAccount::Storage { shard } => {
+            migrate::<StorageAccount>(payer, account, system_program, shard_pda_offset(shard))
+        }
+        MigratableAccount::Fee { fee_version } => {
+            migrate::<FeeAccount>(payer, account, system_program, Some(fee_version))
+        }
+        MigratableAccount::Nullifier { mt_index } => {
+            migrate::<NullifierAccount>(payer, account, system_program, Some(mt_index))
+        }
+        MigratableAccount::VKey { vkey_id } => {
+            migrate::<VKeyAccount>(payer, account, system_program, Some(vkey_id))
+        }
+        MigratableAccount::BaseCommitmentHashing { hash_account_index } => {
+            migrate::<BaseCommitmentHashingAccount>(
+                payer,
+                account,
+                system_program,
+                Some(hash_account_index),
+            )
+        }
+    }
+}
+
+/// Migrates a [`VerificationAccount`] from its version `N` to `N + 1`
+///
+/// # Notes
+///
+/// - The [`VKeyAccount`] has to be migrated first.
+/// - A verification of version 0 is registered with the active version of the [`VKeyAccount`] (so it needs to be migrated before a new vkey version is activated).
+pub fn migrate_verification_account<'a>(
+    payer: &AccountInfo<'a>,
+    original_fee_payer: &AccountInfo,
+    verification_account: &AccountInfo<'a>,
+    vkey_account: &AccountInfo,
+    system_program: &AccountInfo<'a>,
+
+    verification_account_index: u8,
+) -> ProgramResult {
+    VerificationAccount::verify_account_with_pubkey(
+        verification_account,
+        *original_fee_payer.key,
+        Some(verification_account_index.into()),
+    )?;
+
+    let previous_version = VerificationAccount::get_version(verification_account);
+    migrate_account_data(
+        payer,
+        verification_account,
+        system_program,
+        VerificationAccount::SEED,
+        VerificationAccount::VERSION,
+        ACCOUNT_MIGRATIONS,
+    )?;
+
+    if previous_version == 0 && VerificationAccount::get_version(verification_account) == 1 {
+        pda_account!(
+            mut verification_account,
+            VerificationAccount,
+            verification_account
+        );
+
+        VKeyAccount::verify_account(vkey_account, Some(verification_account.get_vkey_id()))?;
+        VKeyAccount::verify_account_version(vkey_account)?;
+        pda_account!(mut vkey_account, VKeyAccount, vkey_account);
+
+        verification_account.set_vkey_version(&acquire_vkey_version(&mut vkey_account)?);
+    }
+
+    Ok(())
+}
+
+fn migrate<'a, T: PDAAccount>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    offset: PDAOffset,
+) -> ProgramResult {
+    T::verify_account(account, offset)?;
+
+    migrate_account_data(
+        payer,
+        account,
+        system_program,
+        T::SEED,
+        T::VERSION,
+        ACCOUNT_MIGRATIONS,
+    )
+}
+
+fn migrate_account_data<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    seed: &[u8],
+    target_version: u8,
+    migrations: &[AccountMigration],
+) -> ProgramResult {
+    let version = account.data.borrow()[1];
+    guard!(version < target_version, ElusivError::InvalidAccountVersion);
+
+    if let Some(migration) = migrations
+        .iter()
+        .find(|m| m.seed == seed && m.version == version)
+    {
+        // Large accounts are grown over multiple instructions before being migrated
+        let size = realloc_account(payer, account, system_program, migration.size, 1)?;
+        if size < migration.size {
+            return Ok(());
+        }
+
+        (migration.migrate)(&mut account.data.borrow_mut())?;
+    }
+
+    account.data.borrow_mut()[1] = version + 1;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::macros::{account_info, test_account_info};
+    use crate::state::queue::Queue;
+    use crate::state::vkey::ProofSystem;
+    use ark_bn254::Fr;
+    use assert_matches::assert_matches;
+    use elusiv_types::{ParentAccount, ProgramAccount, SizedAccount};
+    use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+    fn migrate_test_data(data: &mut [u8]) -> ProgramResult {
+        guard!(data[2] == 1, ProgramError::InvalidAccountData);
+        data[2] = 2;
+        Ok(())
+    }
+
+    const TEST_MIGRATIONS: &[AccountMigration] = &[
+        AccountMigration {
+            seed: b"A",
+            version: 1,
+            size: 3,
+            migrate: migrate_test_data,
+        },
+        AccountMigration {
+            seed: b"B",
+            version: 0,
+            size: 3,
+            migrate: migrate_test_data,
+        },
+    ];
+
+    #[test]
+    fn test_migrate_account_data() {
+        test_account_info!(payer, 0);
+        test_account_info!(system_program, 0);
+        let pk = Pubkey::new_unique();
+        account_info!(account, pk, vec![0, 0, 1]);
+
+        // No layout change from 0 to 1
+        migrate_account_data(&payer, &account, &system_program, b"A", 3, TEST_MIGRATIONS).unwrap();
+        assert_eq!(*account.data.borrow(), [0, 1, 1]);
+
+        // Layout change from 1 to 2
+        migrate_account_data(&payer, &account, &system_program, b"A", 3, TEST_MIGRATIONS).unwrap();
+        assert_eq!(*account.data.borrow(), [0, 2, 2]);
+
+        migrate_account_data(&payer, &account, &system_program, b"A", 3, TEST_MIGRATIONS).unwrap();
+        assert_eq!(*account.data.borrow(), [0, 3, 2]);
+
+        // Account is already migrated to the target version
+        assert_matches!(
+            migrate_account_data(&payer, &account, &system_program, b"A", 3, TEST_MIGRATIONS),
+            Err(_)
+        );
+
+        // Failing migration
+        account_info!(account, pk, vec![0, 0, 0]);
+        assert_matches!(
+            migrate_account_data(&payer, &account, &system_program, b"B", 1, TEST_MIGRATIONS),
+            Err(_)
+        );
+        assert_eq!(*account.data.borrow(), [0, 0, 0]);
+
+        // Shrinking accounts is not supported
+        account_info!(account, pk, vec![0, 0, 1, 0]);
+        assert_matches!(
+            migrate_account_data(&payer, &account, &system_program, b"B", 1, TEST_MIGRATIONS),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_insert_fields() {
+        let mut data = vec![1, 2, 3, 4, 5, 0, 0, 0];
+        insert_fields(
+            &mut data,
+            &[Keep(1), Insert(2), Keep(3), Insert(1), Keep(1)],
+        )
+        .unwrap();
+        assert_eq!(data, [1, 0, 0, 2, 3, 4, 0, 5]);
+
+        // Size mismatch
+        let mut data = vec![1, 2, 3];
+        assert_matches!(insert_fields(&mut data, &[Keep(1), Insert(1)]), Err(_));
+    }
+
+    #[test]
+    fn test_migrate_commitment_queue_account_v0() {
+        let requests: Vec<CommitmentHashRequest> = (0..3)
+            .map(|i| CommitmentHashRequest {
+                commitment: [i as u8 + 1; 32],
+                fee_version: i,
+                min_batching_rate: 0,
+            })
+            .collect();
+
+        // Previous layout: pda_data, head, tail, raw_data
+        let mut data = vec![255, 0];
+        data.extend(1u32.to_le_bytes());
+        data.extend(4u32.to_le_bytes());
+        let mut raw_data = vec![CommitmentHashRequest::default(); CommitmentQueue::SIZE as usize];
+        raw_data[1..4].copy_from_slice(&requests);
+        for request in raw_data {
+            data.extend(request.try_to_vec().unwrap());
+        }
+        data.resize(CommitmentQueueAccount::SIZE, 0);
+
+        migrate_commitment_queue_account_v0(&mut data).unwrap();
+        assert_eq!(data[..2], [255, 0]);
+
+        let mut queue = CommitmentQueueAccount::new_with_child_accounts(
+            &mut data,
+            vec![None; COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT],
+        )
+        .unwrap();
+        assert_eq!(queue.get_overflow_accounts_count(), 0);
+
+        let mut queue = CommitmentQueue::new(&mut queue);
+        assert_eq!(queue.len(), 3);
+        for request in requests {
+            assert_eq!(queue.dequeue_first().unwrap(), request);
+        }
+    }
+
+    #[test]
+    fn test_migrate_governor_account_v0() {
+        let program_fee = ProgramFee::new(5000, 11, 22, 0, 0, 100, 200).unwrap();
+
+        // Previous layout: pda_data, fee_version, program_fee, commitment_batching_rate, program_version
+        let mut data = vec![255, 0];
+        data.extend(3u32.to_le_bytes());
+        data.extend(program_fee.try_to_vec().unwrap());
+        data.extend(4u32.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        assert_eq!(data.len(), GOVERNOR_ACCOUNT_V0_SIZE);
+        data.resize(GovernorAccount::SIZE, 1);
+
+        migrate_governor_account_v0(&mut data).unwrap();
+
+        let governor = GovernorAccount::new(&mut data).unwrap();
+        assert_eq!(governor.get_fee_version(), 3);
+        assert_eq!(governor.get_program_fee(), program_fee);
+        assert_eq!(governor.get_commitment_batching_rate(), 4);
+        assert_eq!(governor.get_authority().option(), None);
+        assert_eq!(governor.get_threshold(), 0);
+        assert_eq!(governor.get_commitment_shards_count(), 0);
+    }
+
+    #[test]
+    fn test_migrate_vkey_account_v0() {
+        let pubkeys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+
+        // Previous layout: pda_data, pubkeys (two child-accounts), public_inputs_count, authority, is_frozen, version
+        let mut data = vec![255, 0];
+        for pubkey in &pubkeys {
+            if *pubkey == pubkeys[2] {
+                data.extend(14u32.to_le_bytes());
+            }
+            data.extend(ElusivOption::Some(*pubkey).try_to_vec().unwrap());
+        }
+        data.push(1);
+        data.extend(3u32.to_le_bytes());
+        assert_eq!(data.len(), VKEY_ACCOUNT_V0_SIZE);
+        data.resize(VKeyAccount::SIZE, 1);
+
+        migrate_vkey_account_v0(&mut data).unwrap();
+
+        let vkey_account = VKeyAccount::new_with_child_accounts(&mut data, vec![None; 3]).unwrap();
+        assert_eq!(vkey_account.get_child_pubkey(0), Some(pubkeys[0]));
+        assert_eq!(vkey_account.get_child_pubkey(1), Some(pubkeys[1]));
+        assert_eq!(vkey_account.get_child_pubkey(2), None);
+        assert_eq!(vkey_account.get_public_inputs_count(), 14);
+        assert_eq!(vkey_account.get_authority().option(), Some(pubkeys[2]));
+        assert!(vkey_account.get_is_frozen());
+        assert_eq!(vkey_account.get_version(), 3);
+        assert_eq!(vkey_account.get_proof_system(), ProofSystem::Groth16);
+        assert_eq!(vkey_account.get_active_verifications(), 0);
+        assert_eq!(vkey_account.get_previous_verifications(), 0);
+        assert_eq!(vkey_account.get_threshold(), 0);
+        assert_eq!(vkey_account.get_unlock_timestamp().option(), None);
+    }
+
+    #[test]
+    fn test_migrate_verification_account_v0() {
+        let other_data = VerificationAccountData {
+            fee_payer: RawU256::new([1; 32]),
+            skip_nullifier_pda: true,
+            token_id: 2,
+            associated_token_account_rent: 3,
+            ..Default::default()
+        };
+
+        // Previous layout: pda_data, instruction, round, prepare_inputs_instructions, vkey_id
+        let mut data = vec![255, 0];
+        data.extend(7u32.to_le_bytes());
+        data.extend(9u32.to_le_bytes());
+        data.extend(3u32.to_le_bytes());
+        for i in 0..MAX_PREPARE_INPUTS_INSTRUCTIONS as u16 {
+            data.extend(i.to_le_bytes());
+        }
+        data.extend(5u32.to_le_bytes());
+
+        // step, state, public_input, a, b, c, prepared_inputs, r, f, alt_b, coeff_index
+        data.extend(vec![0; VerificationStep::SIZE]);
+        data.extend(VerificationState::ProofSetup.try_to_vec().unwrap());
+        data.extend([8; 32]);
+        data.extend(vec![0; RawU256::SIZE * (MAX_PUBLIC_INPUTS_COUNT - 1)]);
+        data.extend(vec![0; 3 * G1A::SIZE + 2 * G2A::SIZE]);
+        data.extend(vec![0; G2HomProjective::SIZE + <Wrap<Fq12>>::SIZE]);
+        data.push(11);
+
+        // RAMs, is_verified, other_data, request, tree_indices
+        data.extend(vec![
+            0;
+            RAMFq::SIZE
+                + RAMFq2::SIZE
+                + RAMFq6::SIZE
+                + RAMFq12::SIZE
+        ]);
+        data.extend(ElusivOption::Some(true).try_to_vec().unwrap());
+        data.extend(&other_data.try_to_vec().unwrap()[..VERIFICATION_ACCOUNT_DATA_V0_SIZE]);
+        data.extend(vec![0; ProofRequest::SIZE]);
+        data.extend(123u32.to_le_bytes());
+        data.extend(456u32.to_le_bytes());
+        data.resize(VerificationAccount::SIZE, 1);
+
+        migrate_verification_account_v0(&mut data).unwrap();
+
+        let verification_account = VerificationAccount::new(&mut data).unwrap();
+        assert_eq!(verification_account.get_instruction(), 7);
+        assert_eq!(verification_account.get_round(), 9);
+        assert_eq!(
+            verification_account.get_prepare_inputs_instructions_count(),
+            3
+        );
+        assert_eq!(
+            verification_account
+                .get_prepare_inputs_instructions(MAX_PREPARE_INPUTS_INSTRUCTIONS - 1),
+            MAX_PREPARE_INPUTS_INSTRUCTIONS as u16 - 1
+        );
+        assert_eq!(verification_account.get_vkey_id(), 5);
+        assert_eq!(verification_account.get_vkey_version(), 0);
+        assert_matches!(
+            verification_account.get_state(),
+            VerificationState::ProofSetup
+        );
+        assert_eq!(verification_account.get_public_input(0).skip_mr(), [8; 32]);
+        assert_eq!(verification_account.get_coeff_index(), 11);
+        assert_eq!(
+            verification_account.get_plonk_scalars(PLONK_MSM_TERMS_COUNT - 1),
+            [0; 32]
+        );
+        assert_eq!(verification_account.get_is_verified().option(), Some(true));
+        assert_eq!(verification_account.get_other_data(), other_data);
+        assert_eq!(verification_account.all_tree_indices(), [123, 456]);
+    }
+
+    #[test]
+    fn test_migrate_storage_account_v0() {
+        // Previous layout: header, active_mt_root_history, mt_roots_count
+        let mut data = vec![0; STORAGE_ACCOUNT_HEADER_SIZE];
+        data[..2].copy_from_slice(&[255, 0]);
+        data[STORAGE_ACCOUNT_HEADER_SIZE - 12..STORAGE_ACCOUNT_HEADER_SIZE - 8]
+            .copy_from_slice(&(2 * HISTORY_ARRAY_SIZE as u32 + 5).to_le_bytes());
+        data[STORAGE_ACCOUNT_HEADER_SIZE - 8..STORAGE_ACCOUNT_HEADER_SIZE - 4]
+            .copy_from_slice(&7u32.to_le_bytes());
+
+        let mut history = vec![[0; 32]; HISTORY_ARRAY_SIZE];
+        history[1] = [1; 32];
+        history[4] = [4; 32];
+        history[3] = [3; 32];
+        history[8] = [8; 32];
+        for root in history {
+            data.extend(root);
+        }
+        data.extend(4u32.to_le_bytes());
+        data.resize(StorageAccount::SIZE, 1);
+
+        migrate_storage_account_v0(&mut data).unwrap();
+        assert_eq!(data[..2], [255, 0]);
+
+        let storage_account =
+            StorageAccount::new_with_child_accounts(&mut data, vec![None; StorageAccount::COUNT])
+                .unwrap();
+        assert_eq!(
+            storage_account.get_next_commitment_ptr(),
+            2 * HISTORY_ARRAY_SIZE as u32 + 5
+        );
+        assert_eq!(storage_account.get_trees_count(), 7);
+        assert_eq!(storage_account.get_mt_roots_count(), 4);
+        assert_eq!(storage_account.get_closed_mt_roots_count(), 0);
+        assert_eq!(storage_account.get_root_history_count(), 4);
+        assert_eq!(storage_account.get_shard(), 0);
+        assert_eq!(storage_account.get_hashing_accounts_count(), 0);
+        assert_eq!(storage_account.get_next_hashing_account_index(), 0);
+        assert_eq!(storage_account.root_history_len(), HISTORY_ARRAY_SIZE);
+
+        // Roots are ordered from the oldest to the most recent one
+        for (i, root) in [[8; 32], [1; 32], [3; 32], [4; 32]].iter().enumerate() {
+            assert_eq!(storage_account.get_root_history(i), Some(*root));
+        }
+        assert_eq!(storage_account.get_root_history(4), Some([0; 32]));
+        assert!(storage_account.is_root_valid(&[1; 32]));
+
+        // Invalid size
+        let mut data = vec![0; StorageAccount::SIZE - 1];
+        assert_matches!(migrate_storage_account_v0(&mut data), Err(_));
+    }
+
+    #[test]
+    fn test_migrate_base_commitment_hashing_account_v0() {
+        let state = BinarySpongeHashingState([Fr::from(1), Fr::from(2), Fr::from(3)]);
+
+        // Previous layout: pda_data, instruction, round, fee_version, fee_payer, is_active, token_id, state, min_batching_rate
+        let mut data = vec![255, 0];
+        data.extend(4u32.to_le_bytes());
+        data.extend(5u32.to_le_bytes());
+        data.extend(6u32.to_le_bytes());
+        data.extend([7; 32]);
+        data.push(1);
+        data.extend(2u16.to_le_bytes());
+        data.extend(state.try_to_vec().unwrap());
+        data.extend(3u32.to_le_bytes());
+        assert_eq!(data.len(), BASE_COMMITMENT_HASHING_ACCOUNT_V0_SIZE);
+        data.resize(BaseCommitmentHashingAccount::SIZE, 1);
+
+        migrate_base_commitment_hashing_account_v0(&mut data).unwrap();
+
+        let hashing_account = BaseCommitmentHashingAccount::new(&mut data).unwrap();
+        assert_eq!(hashing_account.get_instruction(), 4);
+        assert_eq!(hashing_account.get_round(), 5);
+        assert_eq!(hashing_account.get_fee_version(), 6);
+        assert_eq!(hashing_account.get_fee_payer(), [7; 32]);
+        assert!(hashing_account.get_is_active());
+        assert_eq!(hashing_account.get_state(), state);
+        assert_eq!(hashing_account.get_min_batching_rate(), 3);
+        assert_eq!(hashing_account.get_finalized_mask(), 0);
+        assert_eq!(hashing_account.batch_len(), 1);
+        assert_eq!(hashing_account.get_batch(0), Some(state));
+    }
+
+    #[test]
+    fn test_migrate_account() {
+        test_account_info!(payer, 0);
+        test_account_info!(program_data, 0);
+        test_account_info!(system_program, 0);
+
+        // Accounts are created with the current version
+        let (pk, bump) = GovernorAccount::find(None);
+        let mut data = vec![0; GovernorAccount::SIZE];
+        data[0] = bump;
+        data[1] = crate::PROGRAM_VERSION;
+        account_info!(account, pk, data);
+
+        assert_matches!(
+            migrate_account(
+                &payer,
+                &account,
+                &program_data,
+                &system_program,
+                MigratableAccount::Governor
+            ),
+            Err(_)
+        );
+
+        // Invalid PDA
+        assert_matches!(
+            migrate_account(
+                &payer,
+                &account,
+                &program_data,
+                &system_program,
+                MigratableAccount::Pool
+            ),
+            Err(_)
+        );
+        // The governor of version 0 can only be migrated by the upgrade authority
+        let mut data = vec![0; GovernorAccount::SIZE];
+        data[0] = bump;
+        account_info!(account, pk, data);
+        assert_matches!(
+            migrate_account(
+                &payer,
+                &account,
+                &program_data,
+                &system_program,
+                MigratableAccount::Governor
+            ),
+            Err(_)
+        );
+        assert_eq!(GovernorAccount::get_version(&account), 0);
+
+        // Account without a layout change of version 0
+        let (pk, bump) = PoolAccount::find(None);
+        let mut data = vec![0; PoolAccount::SIZE];
+        data[0] = bump;
+        account_info!(account, pk, data);
+        migrate_account(
+            &payer,
+            &account,
+            &program_data,
+            &system_program,
+            MigratableAccount::Pool,
+        )
+        .unwrap();
+        assert_eq!(PoolAccount::get_version(&account), crate::PROGRAM_VERSION);
+    }
+}
@@ -1,11 +1,15 @@
 mod accounts;
 mod commitment;
+mod governance;
+mod migration;
 mod proof;
 mod utils;
 mod vkey;
 
 pub use accounts::*;
 pub use commitment::*;
+pub use governance::*;
+pub use migration::*;
 pub use proof::*;
-pub use utils::{nop, program_token_account_address};
+pub use utils::{nop, program_data_address, program_token_account_address};
 pub use vkey::*;
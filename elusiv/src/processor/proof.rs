@@ -2,41 +2,58 @@ use super::utils::{DefaultInstructionsSysvar, InstructionsSysvar};
 use super::CommitmentHashRequest;
 use crate::bytes::{usize_as_u32_safe, BorshSerDeSized, ElusivOption};
 use crate::error::ElusivError;
+use crate::fields::{is_valid_g1_point, is_valid_g2_point};
 use crate::instruction::ElusivInstruction;
 use crate::macros::{guard, pda_account, BorshSerDeSized, EnumVariantIndex};
 use crate::processor::utils::{
-    close_account, create_associated_token_account, spl_token_account_rent,
+    close_account, create_associated_token_account, current_timestamp, spl_token_account_rent,
     system_program_account_rent, transfer_lamports_from_pda_checked, transfer_token,
-    transfer_token_from_pda, verify_program_token_account,
+    transfer_token_from_pda, verify_program_token_account, wrap_lamports_from_pda,
 };
-use crate::processor::ZERO_COMMITMENT_RAW;
-use crate::proof::verifier::{prepare_public_inputs_instructions, verify_partial};
+use crate::processor::{
+    acquire_vkey_version, release_vkey_version, vkey_version_child_index, ZERO_COMMITMENT_RAW,
+};
+#[cfg(feature = "alt-bn128")]
+use crate::proof::alt_bn128::verify_alt_bn128;
+use crate::proof::plonk::verifier::{plonk_preparation_instructions, verify_plonk_partial};
+use crate::proof::plonk::vkey::PlonkVerifyingKey;
+use crate::proof::verifier::prepare_public_inputs_instructions;
+#[cfg(not(feature = "alt-bn128"))]
+use crate::proof::verifier::verify_partial;
 use crate::proof::vkey::{MigrateUnaryVKey, SendQuadraVKey, VerifyingKey, VerifyingKeyInfo};
-use crate::state::governor::{FeeCollectorAccount, GovernorAccount, PoolAccount};
+use crate::state::apa::{enforce_apa_policy, ApaAction};
+use crate::state::governor::{
+    token_volume_window_start, FeeCollectorAccount, GovernorAccount, PoolAccount, PoolFlow,
+    PoolLedgerAccount, TokenVolumeAccount,
+};
 use crate::state::nullifier::NullifierAccount;
 use crate::state::proof::{
     NullifierDuplicateAccount, VerificationAccount, VerificationAccountData, VerificationState,
 };
 use crate::state::queue::{CommitmentQueue, CommitmentQueueAccount, Queue, RingQueue};
-use crate::state::storage::{StorageAccount, MT_COMMITMENT_COUNT};
-use crate::state::vkey::VKeyAccount;
+use crate::state::storage::{commitment_shard, mt_shard, StorageAccount, MT_COMMITMENT_COUNT};
+use crate::state::vkey::{ProofSystem, VKeyAccount};
 use crate::token::{
-    elusiv_token, verify_associated_token_account, verify_token_account, Lamports, Token,
+    elusiv_token, verify_associated_token_account, verify_token_account, Lamports, Token, TokenID,
     TokenPrice,
 };
 use crate::types::{
-    generate_hashed_inputs, InputCommitment, JoinSplitPublicInputs, MigratePublicInputs, Proof,
-    PublicInputs, RawU256, SendPublicInputs, JOIN_SPLIT_MAX_N_ARITY, U256,
+    generate_hashed_inputs, InputCommitment, JoinSplitPublicInputs, MigratePublicInputs,
+    PlonkProof, ProgramCall, Proof, PublicInputs, RawU256, SendPublicInputs,
+    JOIN_SPLIT_MAX_N_ARITY, U256,
 };
+use ark_ff::Zero;
 use borsh::{BorshDeserialize, BorshSerialize};
-use elusiv_types::ParentAccount;
+use elusiv_types::{PDAAccount, ParentAccount};
 use elusiv_utils::open_pda_account_with_associated_pubkey;
 use solana_program::instruction::Instruction;
 use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::system_instruction;
 use solana_program::sysvar::instructions;
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult};
+use spl_associated_token_account::get_associated_token_address;
 use std::collections::HashSet;
 
 #[derive(
@@ -46,6 +63,9 @@ use std::collections::HashSet;
 pub enum ProofRequest {
     Send(SendPublicInputs),
     Migrate(MigratePublicInputs),
+
+    /// A [`ProofRequest::Send`] verified with a PLONK proof
+    PlonkSend(SendPublicInputs),
 }
 
 macro_rules! proof_request {
@@ -53,6 +73,7 @@ macro_rules! proof_request {
         match $request {
             ProofRequest::Send($public_inputs) => $e,
             ProofRequest::Migrate($public_inputs) => $e,
+            ProofRequest::PlonkSend($public_inputs) => $e,
         }
     };
 }
@@ -69,17 +90,27 @@ impl ProofRequest {
     /// The amount used to compute the fee
     pub fn proof_fee_amount(&self) -> u64 {
         match self {
-            ProofRequest::Send(request) => request.join_split.amount,
+            ProofRequest::Send(request) | ProofRequest::PlonkSend(request) => {
+                request.join_split.amount
+            }
             _ => 0,
         }
     }
 
+    /// The proof system used by a circuit is determined by the [`ProofSystem`] of its [`VKeyAccount`]
     pub fn vkey_id(&self) -> u32 {
         match self {
-            ProofRequest::Send(_) => SendQuadraVKey::VKEY_ID,
+            ProofRequest::Send(_) | ProofRequest::PlonkSend(_) => SendQuadraVKey::VKEY_ID,
             ProofRequest::Migrate(_) => MigrateUnaryVKey::VKEY_ID,
         }
     }
+
+    pub fn proof_system(&self) -> ProofSystem {
+        match self {
+            ProofRequest::PlonkSend(_) => ProofSystem::Plonk,
+            _ => ProofSystem::Groth16,
+        }
+    }
 }
 
 /// We only allow two distinct MTs in a join-split (merges can be used to reduce the amount of MTs)
@@ -92,13 +123,14 @@ pub const RESERVED_VERIFICATION_ACCOUNT_IDS: u8 = 128;
 /// - subsequent calls of [`init_verification_transfer_fee`] and [`init_verification_proof`] required to start the computation
 /// - both need to be called by the same signer (-> the fee structure "enforces" [`init_verification_transfer_fee`] to be called in the same transaction)
 #[allow(clippy::too_many_arguments)]
-pub fn init_verification<'a, 'b, 'c, 'd>(
+pub fn init_verification<'a, 'b, 'c, 'd, 'e, 'f, 'g>(
     fee_payer: &AccountInfo<'a>,
     verification_account: &AccountInfo<'a>,
-    vkey_account: &VKeyAccount,
+    vkey_account: &mut VKeyAccount,
     nullifier_duplicate_account: &AccountInfo<'a>,
     _identifier_account: &AccountInfo,
-    storage_account: &StorageAccount,
+    storage_account0: &StorageAccount<'e, 'f, 'g>,
+    storage_account1: &StorageAccount<'e, 'f, 'g>,
     nullifier_account0: &NullifierAccount<'b, 'c, 'd>,
     nullifier_account1: &NullifierAccount<'b, 'c, 'd>,
 
@@ -114,26 +146,33 @@ pub fn init_verification<'a, 'b, 'c, 'd>(
     guard!(vkey_account.get_version() != 0, ElusivError::InvalidAccount);
 
     guard!(vkey_id == request.vkey_id(), ElusivError::InvalidAccount);
+    guard!(
+        vkey_account.get_proof_system() == request.proof_system(),
+        ElusivError::InvalidAccount
+    );
     guard!(
         verification_account_index <= RESERVED_VERIFICATION_ACCOUNT_IDS,
         ElusivError::InvalidAccount
     );
 
-    let instructions = prepare_public_inputs_instructions(
-        &proof_request!(
-            &request,
-            public_inputs,
-            public_inputs.public_signals_skip_mr()
+    let instructions = match request.proof_system() {
+        ProofSystem::Groth16 => prepare_public_inputs_instructions(
+            &proof_request!(
+                &request,
+                public_inputs,
+                public_inputs.public_signals_skip_mr()
+            ),
+            vkey_account.get_public_inputs_count() as usize,
         ),
-        vkey_account.get_public_inputs_count() as usize,
-    );
+        ProofSystem::Plonk => plonk_preparation_instructions(),
+    };
 
     // TODO: reject zero-commitment nullifier
     // TODO: add identifier_account verification
 
     // Verify public inputs
     let join_split = match &request {
-        ProofRequest::Send(public_inputs) => {
+        ProofRequest::Send(public_inputs) | ProofRequest::PlonkSend(public_inputs) => {
             guard!(
                 public_inputs.verify_additional_constraints(),
                 ElusivError::InvalidPublicInputs
@@ -149,7 +188,7 @@ pub fn init_verification<'a, 'b, 'c, 'd>(
 
     check_join_split_public_inputs(
         join_split,
-        storage_account,
+        [storage_account0, storage_account1],
         [nullifier_account0, nullifier_account1],
         &tree_indices,
     )?;
@@ -194,17 +233,24 @@ pub fn init_verification<'a, 'b, 'c, 'd>(
         VerificationAccount,
         verification_account
     );
+    // The verification keeps using the active version, even if a new version is activated in the meantime
+    let vkey_version = acquire_vkey_version(vkey_account)?;
+
     verification_account.setup(
         RawU256::new(fee_payer.key.to_bytes()),
         skip_nullifier_pda,
         &raw_public_inputs,
         &instructions,
         vkey_id,
+        vkey_version,
         request,
         tree_indices,
-    )
+    )?;
+
+    Ok(())
 }
 
+/// Transfers the verification fee and reserves the withdrawal volume
 #[allow(clippy::too_many_arguments)]
 pub fn init_verification_transfer_fee<'a>(
     fee_payer: &AccountInfo<'a>,
@@ -212,6 +258,7 @@ pub fn init_verification_transfer_fee<'a>(
 
     pool: &AccountInfo<'a>,
     pool_account: &AccountInfo<'a>,
+    pool_ledger: &mut PoolLedgerAccount,
 
     fee_collector: &AccountInfo<'a>,
     fee_collector_account: &AccountInfo<'a>,
@@ -220,11 +267,60 @@ pub fn init_verification_transfer_fee<'a>(
     token_usd_price_account: &AccountInfo,
 
     governor: &GovernorAccount,
+    token_volume: &mut TokenVolumeAccount,
     verification_account: &mut VerificationAccount,
     token_program: &AccountInfo<'a>,
     system_program: &AccountInfo<'a>,
+    recipient: &AccountInfo,
+    apa_target_map: &AccountInfo,
+    apa_proposal: &AccountInfo,
+
+    verification_account_index: u8,
+) -> ProgramResult {
+    init_verification_transfer_fee_at(
+        fee_payer,
+        fee_payer_token_account,
+        pool,
+        pool_account,
+        pool_ledger,
+        fee_collector,
+        fee_collector_account,
+        sol_usd_price_account,
+        token_usd_price_account,
+        governor,
+        token_volume,
+        verification_account,
+        token_program,
+        system_program,
+        recipient,
+        apa_target_map,
+        apa_proposal,
+        verification_account_index,
+        current_timestamp()?,
+    )
+}
 
+#[allow(clippy::too_many_arguments)]
+fn init_verification_transfer_fee_at<'a>(
+    fee_payer: &AccountInfo<'a>,
+    fee_payer_token_account: &AccountInfo<'a>,
+    pool: &AccountInfo<'a>,
+    pool_account: &AccountInfo<'a>,
+    pool_ledger: &mut PoolLedgerAccount,
+    fee_collector: &AccountInfo<'a>,
+    fee_collector_account: &AccountInfo<'a>,
+    sol_usd_price_account: &AccountInfo,
+    token_usd_price_account: &AccountInfo,
+    governor: &GovernorAccount,
+    token_volume: &mut TokenVolumeAccount,
+    verification_account: &mut VerificationAccount,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    recipient: &AccountInfo,
+    apa_target_map: &AccountInfo,
+    apa_proposal: &AccountInfo,
     _verification_account_index: u8,
+    timestamp: u64,
 ) -> ProgramResult {
     guard!(
         matches!(verification_account.get_state(), VerificationState::None),
@@ -245,18 +341,23 @@ pub fn init_verification_transfer_fee<'a>(
         ElusivError::InvalidFeeVersion
     );
     let token_id = join_split.token_id;
+    let amount = join_split.amount;
     let price = TokenPrice::new(sol_usd_price_account, token_usd_price_account, token_id)?;
     let min_batching_rate = governor.get_commitment_batching_rate();
     let fee = governor.get_program_fee();
     let subvention = fee.proof_subvention.into_token(&price, token_id)?;
     let input_preparation_tx_count =
         verification_account.get_prepare_inputs_instructions_count() as usize;
-    let proof_verification_fee = fee
-        .proof_verification_computation_fee(input_preparation_tx_count)
-        .into_token(&price, token_id)?;
+    let proof_verification_fee = match request.proof_system() {
+        ProofSystem::Groth16 => fee.proof_verification_computation_fee(input_preparation_tx_count),
+        ProofSystem::Plonk => {
+            fee.plonk_proof_verification_computation_fee(input_preparation_tx_count)
+        }
+    }
+    .into_token(&price, token_id)?;
     let commitment_hash_fee = fee.commitment_hash_computation_fee(min_batching_rate);
     let commitment_hash_fee_token = commitment_hash_fee.into_token(&price, token_id)?;
-    let network_fee = Token::new(token_id, fee.proof_network_fee.calc(join_split.amount));
+    let network_fee = Token::new(token_id, fee.proof_network_fee.calc(amount));
 
     let fee =
         (((commitment_hash_fee_token + proof_verification_fee)? + network_fee)? - subvention)?;
@@ -268,9 +369,28 @@ pub fn init_verification_transfer_fee<'a>(
     let mut associated_token_account_rent = Lamports(0);
     let mut associated_token_account_rent_token = 0;
 
-    if let ProofRequest::Send(public_inputs) = request {
+    if let ProofRequest::Send(public_inputs) | ProofRequest::PlonkSend(public_inputs) = request {
+        // Lamports sent to an associated token account are delivered as wrapped-SOL, which is not supported by Solana Pay transfers
         if public_inputs.recipient_is_associated_token_account && token_id == 0 {
-            return Err(ElusivError::InvalidRecipient.into());
+            guard!(
+                !public_inputs.solana_pay_transfer,
+                ElusivError::InvalidRecipient
+            );
+        }
+
+        // Withdrawals to APA-targets are restricted by the warden network (before any computation is performed)
+        if public_inputs.join_split.amount > 0 {
+            enforce_apa_policy(
+                &withdrawal_apa_target(
+                    recipient,
+                    token_id,
+                    public_inputs.recipient_is_associated_token_account,
+                )?,
+                apa_target_map,
+                apa_proposal,
+                ApaAction::Withdrawal,
+                token_id,
+            )?;
         }
 
         // Enforce minimum-send-amount for Lamports (zero-amount is always allowed for merges)
@@ -295,13 +415,17 @@ pub fn init_verification_transfer_fee<'a>(
         }
     }
 
+    let reserved_lamports = (commitment_hash_fee + associated_token_account_rent)?;
+    pool_ledger.track(0, PoolFlow::FeeReservation, reserved_lamports.0)?;
+    pool_ledger.track(token_id, PoolFlow::FeeReservation, subvention.amount())?;
+
     // `fee_payer` transfers `commitment_hash_fee` (+ `associated_token_account_rent`)? to `pool` (lamports)
     transfer_token(
         fee_payer,
         fee_payer,
         pool,
         system_program,
-        (commitment_hash_fee + associated_token_account_rent)?.into_token_strict(),
+        reserved_lamports.into_token_strict(),
     )?;
 
     // `fee_collector` transfers `subvention` to `pool` (token)
@@ -321,10 +445,16 @@ pub fn init_verification_transfer_fee<'a>(
         ElusivError::InvalidAccount
     );
 
+    // The withdrawal volume is only reserved once the fee has been paid (and released for an invalid proof)
+    // - an initialized verification without a transferred fee can therefore not block other withdrawals
+    if amount > 0 {
+        token_volume.track_withdrawal(token_id, amount, timestamp)?;
+    }
+
     verification_account.set_other_data(&VerificationAccountData {
         fee_payer: RawU256::new(fee_payer.key.to_bytes()),
         fee_payer_account: RawU256::new(fee_payer_token_account.key.to_bytes()),
-        recipient_wallet: ElusivOption::None,
+        recipient_wallet: ElusivOption::Some(RawU256::new(recipient.key.to_bytes())),
         skip_nullifier_pda: other_data.skip_nullifier_pda,
         min_batching_rate,
         token_id,
@@ -334,6 +464,9 @@ pub fn init_verification_transfer_fee<'a>(
         commitment_hash_fee_token: commitment_hash_fee_token.amount(),
         proof_verification_fee: proof_verification_fee.amount(),
         associated_token_account_rent: associated_token_account_rent_token,
+        commitment_shard: other_data.commitment_shard,
+        program_call: ElusivOption::None,
+        withdrawal_window_start: token_volume_window_start(timestamp),
     });
 
     verification_account.set_state(&VerificationState::FeeTransferred);
@@ -341,6 +474,29 @@ pub fn init_verification_transfer_fee<'a>(
     Ok(())
 }
 
+/// The warden-network APA-target of a withdrawal to `recipient`
+///
+/// # Notes
+///
+/// The target of a token account (that is not an associated-token-account) is its owner.
+fn withdrawal_apa_target(
+    recipient: &AccountInfo,
+    token_id: TokenID,
+    recipient_is_associated_token_account: bool,
+) -> Result<Pubkey, ProgramError> {
+    if token_id == 0 || recipient_is_associated_token_account {
+        return Ok(*recipient.key);
+    }
+
+    guard!(
+        matches!(verify_token_account(recipient, token_id), Ok(true)),
+        ElusivError::InvalidRecipient
+    );
+    let account = spl_token::state::Account::unpack(&recipient.data.borrow()[..])?;
+
+    Ok(account.owner)
+}
+
 /// Called once after [`init_verification`] to initialize the proof's public inputs
 ///
 /// # Notes
@@ -354,6 +510,69 @@ pub fn init_verification_proof(
 
     _verification_account_index: u8,
     proof: Proof,
+) -> ProgramResult {
+    check_proof_setup(fee_payer, verification_account, ProofSystem::Groth16)?;
+
+    // Points at infinity are rejected, since they are never part of an honestly generated proof
+    guard!(
+        !proof.a.0.is_zero() && !proof.b.0.is_zero() && !proof.c.0.is_zero(),
+        ElusivError::InvalidProofPoint
+    );
+    // The G2 subgroup membership of `b` is checked during the verification (`VerificationStep::ProofSubgroupCheck`)
+    guard!(
+        is_valid_g1_point(&proof.a.0)
+            && is_valid_g2_point(&proof.b.0)
+            && is_valid_g1_point(&proof.c.0),
+        ElusivError::InvalidProofPoint
+    );
+
+    verification_account.a.set(proof.a);
+    verification_account.b.set(proof.b);
+    verification_account.c.set(proof.c);
+
+    verification_account.set_state(&VerificationState::ProofSetup);
+
+    Ok(())
+}
+
+/// The PLONK equivalent of [`init_verification_proof`]
+pub fn init_verification_plonk_proof(
+    fee_payer: &AccountInfo,
+    verification_account: &mut VerificationAccount,
+
+    _verification_account_index: u8,
+    proof: PlonkProof,
+) -> ProgramResult {
+    check_proof_setup(fee_payer, verification_account, ProofSystem::Plonk)?;
+
+    guard!(
+        [
+            proof.a,
+            proof.b,
+            proof.c,
+            proof.z,
+            proof.t1,
+            proof.t2,
+            proof.t3,
+            proof.w_xi,
+            proof.w_xiw,
+        ]
+        .iter()
+        .all(|p| is_valid_g1_point(&p.0)),
+        ElusivError::InvalidProofPoint
+    );
+
+    verification_account.plonk_proof.set(proof);
+
+    verification_account.set_state(&VerificationState::ProofSetup);
+
+    Ok(())
+}
+
+fn check_proof_setup(
+    fee_payer: &AccountInfo,
+    verification_account: &VerificationAccount,
+    proof_system: ProofSystem,
 ) -> ProgramResult {
     guard!(
         matches!(
@@ -370,12 +589,10 @@ pub fn init_verification_proof(
         verification_account.get_other_data().fee_payer.skip_mr() == fee_payer.key.to_bytes(),
         ElusivError::InvalidAccount
     );
-
-    verification_account.a.set(proof.a);
-    verification_account.b.set(proof.b);
-    verification_account.c.set(proof.c);
-
-    verification_account.set_state(&VerificationState::ProofSetup);
+    guard!(
+        verification_account.get_request().proof_system() == proof_system,
+        ElusivError::InvalidInstructionData
+    );
 
     Ok(())
 }
@@ -417,11 +634,33 @@ pub fn compute_verification(
         instructions::load_current_index_checked(instructions_account)?
     };
 
-    let result = vkey_account.execute_on_child_account_mut(0, |data| {
-        let vkey = VerifyingKey::new(data, vkey_account.get_public_inputs_count() as usize)
-            .ok_or(ElusivError::InvalidAccountState)?;
+    let child_index =
+        vkey_version_child_index(vkey_account, verification_account.get_vkey_version())?;
+    let public_inputs_count = vkey_account.get_public_inputs_count() as usize;
+    let result = vkey_account.execute_on_child_account_mut(child_index, |data| {
+        match vkey_account.get_proof_system() {
+            ProofSystem::Groth16 => {
+                let vkey = VerifyingKey::new(data, public_inputs_count)
+                    .ok_or(ElusivError::InvalidAccountState)?;
+
+                // The syscall backend does not depend on the instruction index
+                #[cfg(not(feature = "alt-bn128"))]
+                {
+                    verify_partial(verification_account, &vkey, instruction_index)
+                }
+
+                #[cfg(feature = "alt-bn128")]
+                {
+                    verify_alt_bn128(verification_account, &vkey)
+                }
+            }
+            ProofSystem::Plonk => {
+                let vkey = PlonkVerifyingKey::new(data, public_inputs_count)
+                    .ok_or(ElusivError::InvalidAccountState)?;
 
-        verify_partial(verification_account, &vkey, instruction_index)
+                verify_plonk_partial(verification_account, &vkey, instruction_index)
+            }
+        }
     })?;
 
     match result {
@@ -459,6 +698,9 @@ pub struct FinalizeSendData {
 
     pub iv: U256,
     pub encrypted_owner: U256,
+
+    /// Instruction that has to follow the transfer to the recipient (committed to by `hashed_inputs`)
+    pub program_call: ElusivOption<ProgramCall>,
 }
 
 const SPL_MEMO_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
@@ -479,6 +721,8 @@ const SPL_MEMO_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
 /// - for an invalid proof:
 ///     [`finalize_verification_send`],
 ///     [`finalize_verification_transfer_lamports`] or [`finalize_verification_transfer_token`].
+///
+/// If `data.program_call` is set, the funds are transferred into the temporary account of the invoked program and the instruction directly following the transfer has to match it (see [`ProgramCall`]).
 #[allow(clippy::too_many_arguments)]
 pub fn finalize_verification_send(
     recipient: &AccountInfo,
@@ -487,6 +731,7 @@ pub fn finalize_verification_send(
     commitment_hash_queue: &mut CommitmentQueueAccount,
     verification_account: &mut VerificationAccount,
     storage_account: &StorageAccount,
+    governor: &GovernorAccount,
     instructions_account: &AccountInfo,
 
     verification_account_index: u8,
@@ -503,10 +748,17 @@ pub fn finalize_verification_send(
 
     let request = verification_account.get_request();
     let public_inputs = match request {
-        ProofRequest::Send(public_inputs) => public_inputs,
+        ProofRequest::Send(public_inputs) | ProofRequest::PlonkSend(public_inputs) => public_inputs,
         _ => return Err(ElusivError::FeatureNotAvailable.into()),
     };
 
+    // A program-call cannot be combined with a Solana-Pay transfer (which requires the transfer to be the last instruction)
+    let program_call = data.program_call.option();
+    guard!(
+        program_call.is_none() || !public_inputs.solana_pay_transfer,
+        ElusivError::InvalidInstructionData
+    );
+
     // Check spl-memo-instruction
     let memo = if uses_memo {
         Some(get_memo_from_instructions(
@@ -530,15 +782,41 @@ pub fn finalize_verification_send(
         },
         public_inputs.recipient_is_associated_token_account,
         &memo,
+        &program_call,
     );
     guard!(
         hash == public_inputs.hashed_inputs,
         ElusivError::InputsMismatch
     );
 
-    // Set `recipient_wallet`
+    // The funds of a program-call are transferred into the temporary account of the invoked program (or its associated-token-account)
+    if let Some(program_call) = &program_call {
+        guard!(
+            *recipient.key
+                == program_call
+                    .temporary_account(&public_inputs.join_split.output_commitment.skip_mr()),
+            ElusivError::InvalidRecipient
+        );
+        guard!(
+            public_inputs.join_split.token_id == 0
+                || public_inputs.recipient_is_associated_token_account,
+            ElusivError::InvalidRecipient
+        );
+    }
+
+    // The recipient has been checked against the APA-policies in `init_verification_transfer_fee`
+    guard!(
+        verification_account
+            .get_other_data()
+            .recipient_wallet
+            .option()
+            == Some(RawU256::new(recipient.key.to_bytes())),
+        ElusivError::InvalidRecipient
+    );
+
+    // Set `program_call`
     verification_account.set_other_data(&mutate(&verification_account.get_other_data(), |data| {
-        data.recipient_wallet = ElusivOption::Some(RawU256::new(recipient.key.to_bytes()))
+        data.program_call = program_call.into();
     }));
 
     match verification_account.get_is_verified() {
@@ -556,8 +834,19 @@ pub fn finalize_verification_send(
         verification_account_index,
     )?;
 
+    // The next-commitment is inserted into the shard of the supplied `storage_account` and `commitment_hash_queue`
+    let shard = storage_account.get_shard();
+    guard!(
+        shard
+            == commitment_shard(
+                &public_inputs.join_split.output_commitment.reduce(),
+                governor.get_commitment_shards_count()
+            ),
+        ElusivError::InvalidAccount
+    );
+
     let (commitment_index, mt_index) = minimum_commitment_mt_index(
-        storage_account.get_trees_count(),
+        storage_account.active_mt_index()?,
         storage_account.get_next_commitment_ptr(),
         CommitmentQueue::new(commitment_hash_queue).len(),
     );
@@ -575,6 +864,9 @@ pub fn finalize_verification_send(
     );
     guard!(data.mt_index == mt_index, ElusivError::InputsMismatch);
 
+    verification_account.set_other_data(&mutate(&verification_account.get_other_data(), |data| {
+        data.commitment_shard = shard
+    }));
     verification_account.set_state(&VerificationState::InsertNullifiers);
     verification_account.set_instruction(&0);
 
@@ -599,7 +891,7 @@ pub fn finalize_verification_insert_nullifier(
 
     let request = verification_account.get_request();
     let public_inputs = match request {
-        ProofRequest::Send(public_inputs) => public_inputs,
+        ProofRequest::Send(public_inputs) | ProofRequest::PlonkSend(public_inputs) => public_inputs,
         _ => return Err(ElusivError::FeatureNotAvailable.into()),
     };
 
@@ -653,14 +945,21 @@ pub fn finalize_verification_insert_nullifier(
 pub fn finalize_verification_transfer_lamports<'a>(
     original_fee_payer: &AccountInfo<'a>,
     recipient: &AccountInfo<'a>, // can be any account for merge/migrate
+    recipient_wallet: &AccountInfo<'a>,
     pool: &AccountInfo<'a>,
     fee_collector: &AccountInfo<'a>,
     commitment_hash_queue: &mut CommitmentQueueAccount,
     verification_account_info: &AccountInfo<'a>,
+    vkey_account: &AccountInfo,
     nullifier_duplicate_account: &AccountInfo<'a>,
+    token_volume: &mut TokenVolumeAccount,
+    pool_ledger: &mut PoolLedgerAccount,
+    token_program: &AccountInfo<'a>,
+    mint_account: &AccountInfo<'a>,
     instructions_account: &AccountInfo,
 
     _verification_account_index: u8,
+    commitment_shard: u32,
 ) -> ProgramResult {
     pda_account!(
         mut verification_account,
@@ -690,6 +989,8 @@ pub fn finalize_verification_transfer_lamports<'a>(
         ElusivError::InvalidAccount
     );
 
+    release_verification_vkey_version(&verification_account, vkey_account)?;
+
     // Invalid proof
     if let ElusivOption::Some(false) = verification_account.get_is_verified() {
         // `rent` and `commitment_hash_fee` flow to `fee_collector`
@@ -699,24 +1000,90 @@ pub fn finalize_verification_transfer_lamports<'a>(
         }
 
         verification_account.set_state(&VerificationState::Closed);
+        token_volume.release_withdrawal(0, join_split.amount, data.withdrawal_window_start)?;
+
+        let reserved_lamports =
+            (data.commitment_hash_fee + Lamports(data.associated_token_account_rent))?;
+        pool_ledger.track(
+            0,
+            PoolFlow::FeePayment,
+            (Lamports(data.subvention) + reserved_lamports)?.0,
+        )?;
 
         // `pool` transfers `subvention` to `fee_collector` (lamports)
         transfer_lamports_from_pda_checked(pool, fee_collector, data.subvention)?;
 
-        // `pool` transfers `commitment_hash_fee` to `fee_collector` (lamports)
-        transfer_lamports_from_pda_checked(pool, fee_collector, data.commitment_hash_fee.0)?;
+        // `pool` transfers `commitment_hash_fee` and `associated_token_account_rent` to `fee_collector` (lamports)
+        transfer_lamports_from_pda_checked(pool, fee_collector, reserved_lamports.0)?;
 
         return Ok(());
     }
 
-    if let ProofRequest::Send(public_inputs) = &request {
+    // The next-commitment is inserted into the shard selected in `finalize_verification_send`
+    guard!(
+        commitment_shard == data.commitment_shard,
+        ElusivError::InvalidAccount
+    );
+
+    let mut associated_token_account_rent = None;
+    if let ProofRequest::Send(public_inputs) | ProofRequest::PlonkSend(public_inputs) = &request {
         if public_inputs.join_split.amount > 0 {
-            guard!(
-                recipient.key.to_bytes() == data.recipient_wallet.option().unwrap().skip_mr(),
-                ElusivError::InvalidRecipient
-            );
+            pool_ledger.track(0, PoolFlow::Withdrawal, public_inputs.join_split.amount)?;
+
+            let recipient_address = data.recipient_wallet.option().unwrap().skip_mr();
+
+            if public_inputs.recipient_is_associated_token_account {
+                // Wrapped-SOL associated-token-account
+                guard!(
+                    recipient_wallet.key.to_bytes() == recipient_address,
+                    ElusivError::InvalidRecipient
+                );
+                guard!(
+                    *recipient.key
+                        == get_associated_token_address(
+                            recipient_wallet.key,
+                            &spl_token::native_mint::ID
+                        ),
+                    ElusivError::InvalidRecipient
+                );
+
+                if recipient.lamports() == 0 {
+                    // `original_fee_payer` rents the associated token account (refunded at the end)
+                    create_associated_token_account(
+                        original_fee_payer,
+                        recipient_wallet,
+                        recipient,
+                        mint_account,
+                        0,
+                    )?;
+
+                    // `pool` transfers `associated_token_account_rent` to `fee_payer` (lamports)
+                    associated_token_account_rent = Some(data.associated_token_account_rent);
+                } else {
+                    associated_token_account_rent = Some(0);
+                }
+
+                // `pool` transfers `amount` to `recipient` (wrapped-SOL)
+                wrap_lamports_from_pda(
+                    pool,
+                    recipient,
+                    token_program,
+                    public_inputs.join_split.amount - associated_token_account_rent.unwrap(),
+                )?;
+
+                if let Some(program_call) = data.program_call.option() {
+                    enforce_program_call(
+                        &DefaultInstructionsSysvar(instructions_account),
+                        &program_call,
+                        recipient.key,
+                    )?;
+                }
+            } else if public_inputs.solana_pay_transfer {
+                guard!(
+                    recipient.key.to_bytes() == recipient_address,
+                    ElusivError::InvalidRecipient
+                );
 
-            if public_inputs.solana_pay_transfer {
                 // `pool` transfers `amount` to `original_fee_payer` (lamports)
                 transfer_lamports_from_pda_checked(
                     pool,
@@ -737,21 +1104,42 @@ pub fn finalize_verification_transfer_lamports<'a>(
                     false,
                 )?;
             } else {
+                guard!(
+                    recipient.key.to_bytes() == recipient_address,
+                    ElusivError::InvalidRecipient
+                );
+
                 // `pool` transfers `amount` to `recipient` (lamports)
                 transfer_lamports_from_pda_checked(
                     pool,
                     recipient,
                     public_inputs.join_split.amount,
                 )?;
+
+                if let Some(program_call) = data.program_call.option() {
+                    enforce_program_call(
+                        &DefaultInstructionsSysvar(instructions_account),
+                        &program_call,
+                        recipient.key,
+                    )?;
+                }
             }
         }
     }
 
-    // `pool` transfers `commitment_hash_fee_token (incl. subvention) + proof_verification_fee` to `fee_payer` (lamports)
+    // The `associated_token_account_rent` is part of the withdrawn `amount`
+    let fees = ((Lamports(data.commitment_hash_fee_token)
+        + Lamports(data.proof_verification_fee))?
+        + Lamports(data.network_fee))?;
+    pool_ledger.track(0, PoolFlow::FeePayment, fees.0)?;
+
+    // `pool` transfers `commitment_hash_fee_token (incl. subvention) + proof_verification_fee + associated_token_account_rent?` to `fee_payer` (lamports)
     transfer_lamports_from_pda_checked(
         pool,
         original_fee_payer,
-        (Lamports(data.commitment_hash_fee_token) + Lamports(data.proof_verification_fee))?.0,
+        ((Lamports(data.commitment_hash_fee_token) + Lamports(data.proof_verification_fee))?
+            + Lamports(associated_token_account_rent.unwrap_or(0)))?
+        .0,
     )?;
 
     // `pool` transfers `network_fee` to `fee_collector` (lamports)
@@ -765,6 +1153,16 @@ pub fn finalize_verification_transfer_lamports<'a>(
         data.skip_nullifier_pda,
     )?;
 
+    // `pool` transfers the `associated_token_account_rent` reserved in `init_verification_transfer_fee` back to `original_fee_payer` (lamports)
+    if associated_token_account_rent.is_some() {
+        pool_ledger.track(0, PoolFlow::FeePayment, data.associated_token_account_rent)?;
+        transfer_lamports_from_pda_checked(
+            pool,
+            original_fee_payer,
+            data.associated_token_account_rent,
+        )?;
+    }
+
     let mut commitment_queue = CommitmentQueue::new(commitment_hash_queue);
     commitment_queue.enqueue(CommitmentHashRequest {
         commitment: join_split.output_commitment.reduce(),
@@ -789,12 +1187,16 @@ pub fn finalize_verification_transfer_token<'a>(
     fee_collector_account: &AccountInfo<'a>,
     commitment_hash_queue: &mut CommitmentQueueAccount,
     verification_account_info: &AccountInfo<'a>,
+    vkey_account: &AccountInfo,
     nullifier_duplicate_account: &AccountInfo<'a>,
+    token_volume: &mut TokenVolumeAccount,
+    pool_ledger: &mut PoolLedgerAccount,
     token_program: &AccountInfo<'a>,
     mint_account: &AccountInfo<'a>,
     instructions_account: &AccountInfo,
 
     _verification_account_index: u8,
+    commitment_shard: u32,
 ) -> ProgramResult {
     pda_account!(
         mut verification_account,
@@ -833,6 +1235,8 @@ pub fn finalize_verification_transfer_token<'a>(
     verify_program_token_account(pool, pool_account, token_id)?;
     verify_program_token_account(fee_collector, fee_collector_account, token_id)?;
 
+    release_verification_vkey_version(&verification_account, vkey_account)?;
+
     // Invalid proof
     if let ElusivOption::Some(false) = verification_account.get_is_verified() {
         // rent flows to `fee_collector`
@@ -844,6 +1248,15 @@ pub fn finalize_verification_transfer_token<'a>(
         )?;
 
         verification_account.set_state(&VerificationState::Closed);
+        token_volume.release_withdrawal(
+            token_id,
+            join_split.amount,
+            data.withdrawal_window_start,
+        )?;
+
+        let reserved_lamports = (data.commitment_hash_fee + spl_token_account_rent()?)?;
+        pool_ledger.track(token_id, PoolFlow::FeePayment, data.subvention)?;
+        pool_ledger.track(0, PoolFlow::FeePayment, reserved_lamports.0)?;
 
         // `pool` transfers `subvention` to `fee_collector` (token)
         transfer_token_from_pda::<PoolAccount>(
@@ -857,18 +1270,26 @@ pub fn finalize_verification_transfer_token<'a>(
         )?;
 
         // `pool` transfers `commitment_hash_fee` and `associated_token_account_rent` to `fee_collector` (lamports)
-        transfer_lamports_from_pda_checked(
-            pool,
-            fee_collector,
-            (data.commitment_hash_fee + spl_token_account_rent()?)?.0,
-        )?;
+        transfer_lamports_from_pda_checked(pool, fee_collector, reserved_lamports.0)?;
 
         return Ok(());
     }
 
+    // The next-commitment is inserted into the shard selected in `finalize_verification_send`
+    guard!(
+        commitment_shard == data.commitment_shard,
+        ElusivError::InvalidAccount
+    );
+
     let mut associated_token_account_rent_token = None;
-    if let ProofRequest::Send(public_inputs) = &request {
+    if let ProofRequest::Send(public_inputs) | ProofRequest::PlonkSend(public_inputs) = &request {
         if public_inputs.join_split.amount > 0 {
+            pool_ledger.track(
+                token_id,
+                PoolFlow::Withdrawal,
+                public_inputs.join_split.amount,
+            )?;
+
             let mut actual_recipient = recipient;
 
             if !public_inputs.recipient_is_associated_token_account {
@@ -960,10 +1381,24 @@ pub fn finalize_verification_transfer_token<'a>(
                     None,
                     None,
                 )?;
+
+                if let Some(program_call) = data.program_call.option() {
+                    enforce_program_call(
+                        &DefaultInstructionsSysvar(instructions_account),
+                        &program_call,
+                        recipient.key,
+                    )?;
+                }
             }
         }
     }
 
+    // The `associated_token_account_rent_token` is part of the withdrawn `amount`
+    let fees = ((Token::new(token_id, data.commitment_hash_fee_token)
+        + Token::new(token_id, data.proof_verification_fee))?
+        + Token::new(token_id, data.network_fee))?;
+    pool_ledger.track(token_id, PoolFlow::FeePayment, fees.amount())?;
+
     // `pool` transfers `commitment_hash_fee_token (incl. subvention) + proof_verification_fee + associated_token_account_rent_token?` to `fee_payer` (token)
     transfer_token_from_pda::<PoolAccount>(
         pool,
@@ -997,7 +1432,13 @@ pub fn finalize_verification_transfer_token<'a>(
     )?;
 
     if associated_token_account_rent_token.is_some() {
-        transfer_lamports_from_pda_checked(pool, original_fee_payer, spl_token_account_rent()?.0)?;
+        let associated_token_account_rent = spl_token_account_rent()?.0;
+        pool_ledger.track(0, PoolFlow::FeePayment, associated_token_account_rent)?;
+        transfer_lamports_from_pda_checked(
+            pool,
+            original_fee_payer,
+            associated_token_account_rent,
+        )?;
     }
 
     let mut commitment_queue = CommitmentQueue::new(commitment_hash_queue);
@@ -1012,6 +1453,18 @@ pub fn finalize_verification_transfer_token<'a>(
     Ok(())
 }
 
+/// Releases the [`VKeyAccount`] version used by a [`VerificationAccount`] that is being closed
+fn release_verification_vkey_version(
+    verification_account: &VerificationAccount,
+    vkey_account: &AccountInfo,
+) -> ProgramResult {
+    VKeyAccount::verify_account(vkey_account, Some(verification_account.get_vkey_id()))?;
+    VKeyAccount::verify_account_version(vkey_account)?;
+
+    pda_account!(mut vkey_account, VKeyAccount, vkey_account);
+    release_vkey_version(&mut vkey_account, verification_account.get_vkey_version())
+}
+
 fn close_verification_pdas<'a>(
     beneficiary: &AccountInfo<'a>,
     verification_account: &AccountInfo<'a>,
@@ -1037,7 +1490,7 @@ fn is_vec_duplicate_free<T: std::cmp::Eq + std::hash::Hash + std::clone::Clone>(
     (*v).clone().drain(..).collect::<HashSet<T>>().len() == v.len()
 }
 
-/// Computes the minimum index of a commitment and it's corresponding MT-index
+/// Computes the minimum index of a commitment and it's corresponding MT-index (in the shard of the active MT)
 fn minimum_commitment_mt_index(
     mt_index: u32,
     commitment_count: u32,
@@ -1051,7 +1504,7 @@ fn minimum_commitment_mt_index(
 
 fn check_join_split_public_inputs(
     public_inputs: &JoinSplitPublicInputs,
-    storage_account: &StorageAccount,
+    storage_accounts: [&StorageAccount; MAX_MT_COUNT],
     nullifier_accounts: [&NullifierAccount; MAX_MT_COUNT],
     tree_indices: &[u32; MAX_MT_COUNT],
 ) -> ProgramResult {
@@ -1071,8 +1524,6 @@ fn check_join_split_public_inputs(
 
     // TODO: add user supplied commitment-index verification in next version
 
-    let active_tree_index = storage_account.get_trees_count();
-
     let mut roots = Vec::new();
     let mut tree_index = Vec::with_capacity(public_inputs.input_commitments.len());
     let mut nullifier_hashes = Vec::new();
@@ -1088,8 +1539,15 @@ fn check_join_split_public_inputs(
                 roots.push(root);
                 nullifier_hashes.push(vec![nullifier_hash]);
 
-                // Verify that root is valid
+                // Verify that root is valid (using the `StorageAccount` of the MT's shard)
                 // Note: roots are stored in mr-form
+                let storage_account = storage_accounts[index];
+                guard!(
+                    storage_account.get_shard() == mt_shard(tree_indices[index]),
+                    ElusivError::InvalidAccount
+                );
+                let active_tree_index = storage_account.active_mt_index()?;
+
                 if tree_indices[index] == active_tree_index {
                     // Active tree
                     guard!(
@@ -1097,9 +1555,12 @@ fn check_join_split_public_inputs(
                         ElusivError::InvalidMerkleRoot
                     );
                 } else {
-                    // Closed tree
+                    // Closed tree (recent roots of the most recently closed tree remain valid)
+                    let root = root.reduce();
                     guard!(
-                        root.reduce() == nullifier_accounts[index].get_root(),
+                        root == nullifier_accounts[index].get_root()
+                            || (tree_indices[index].checked_add(1) == Some(active_tree_index)
+                                && storage_account.is_closed_root_valid(&root)),
                         ElusivError::InvalidMerkleRoot
                     );
                 }
@@ -1285,20 +1746,50 @@ fn enforce_instruction<I: InstructionsSysvar>(
     Ok(instruction)
 }
 
-fn memo_instruction(memo: &[u8]) -> Instruction {
-    Instruction {
-        program_id: SPL_MEMO_PROGRAM_ID,
-        accounts: Vec::new(),
-        data: memo.to_vec(),
-    }
-}
-
-fn get_memo_from_instructions<I: InstructionsSysvar>(
+/// Enforces that the next instruction is `program_call` and uses the `recipient` of the transferred funds
+///
+/// # Notes
+///
+/// `recipient` is the temporary account of the invoked program (see [`ProgramCall::temporary_account`]), which is consumed by `program_call`.
+fn enforce_program_call<I: InstructionsSysvar>(
     instruction_sysvar: &I,
-    solana_pay_transfer: bool,
-) -> Result<Vec<u8>, ProgramError> {
-    let instruction_count = instruction_sysvar.find_instruction_count()?;
-    let memo_index = if solana_pay_transfer {
+    program_call: &ProgramCall,
+    recipient: &Pubkey,
+) -> ProgramResult {
+    let index = instruction_sysvar.current_index()? as usize + 1;
+    let instruction = instruction_sysvar
+        .instruction_at_index(index)
+        .or(Err(ElusivError::InvalidOtherInstruction))?;
+
+    guard!(
+        ProgramCall::new(&instruction.program_id, &instruction.data) == *program_call,
+        ElusivError::InvalidOtherInstruction
+    );
+    guard!(
+        instruction
+            .accounts
+            .iter()
+            .any(|account| account.pubkey == *recipient),
+        ElusivError::InvalidOtherInstruction
+    );
+
+    Ok(())
+}
+
+fn memo_instruction(memo: &[u8]) -> Instruction {
+    Instruction {
+        program_id: SPL_MEMO_PROGRAM_ID,
+        accounts: Vec::new(),
+        data: memo.to_vec(),
+    }
+}
+
+fn get_memo_from_instructions<I: InstructionsSysvar>(
+    instruction_sysvar: &I,
+    solana_pay_transfer: bool,
+) -> Result<Vec<u8>, ProgramError> {
+    let instruction_count = instruction_sysvar.find_instruction_count()?;
+    let memo_index = if solana_pay_transfer {
         instruction_count - 2
     } else {
         instruction_count - 1
@@ -1340,11 +1831,24 @@ macro_rules! vkey_account {
             source
         );
 
+        let mut source = <$vkey as crate::proof::vkey::VerifyingKeyInfo>::verifying_key_source();
+        source.insert(0, 0);
+
+        crate::macros::account_info!(
+            vkey_account2,
+            solana_program::pubkey::Pubkey::new_unique(),
+            source
+        );
+
         let mut data = vec![0; <VKeyAccount as elusiv_types::accounts::SizedAccount>::SIZE];
         let mut $id =
             <VKeyAccount as elusiv_types::accounts::ParentAccount>::new_with_child_accounts(
                 &mut data,
-                vec![Some(&vkey_account), Some(&vkey_account1)],
+                vec![
+                    Some(&vkey_account),
+                    Some(&vkey_account1),
+                    Some(&vkey_account2),
+                ],
             )
             .unwrap();
 
@@ -1362,24 +1866,30 @@ pub(crate) use vkey_account;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::fields::{u256_from_str, u256_from_str_skip_mr};
+    use crate::fields::{u256_from_str, u256_from_str_skip_mr, G1A, G2A};
     use crate::macros::{
         account_info, parent_account, program_token_account_info, pyth_price_account_info,
         test_account_info, test_pda_account_info, two_pow, zero_program_account,
     };
     use crate::processor::ZERO_COMMITMENT_RAW;
     use crate::proof::verifier::{
-        proof_from_str, COMBINED_MILLER_LOOP_IXS, FINAL_EXPONENTIATION_IXS,
+        COMBINED_MILLER_LOOP_IXS, FINAL_EXPONENTIATION_IXS, G2_SUBGROUP_CHECK_IXS,
+    };
+    use crate::state::apa::{
+        apa_accounts_data, apa_target_map_account, ApaLevel, WARDEN_NETWORK_PROGRAM_ID,
     };
     use crate::state::fee::ProgramFee;
-    use crate::state::governor::PoolAccount;
+    use crate::state::governor::{PoolAccount, TokenVolume, TOKEN_VOLUME_WINDOW};
     use crate::state::nullifier::NullifierChildAccount;
     use crate::state::program_account::{PDAAccount, SizedAccount};
-    use crate::state::storage::empty_root_raw;
+    use crate::state::storage::{empty_root_raw, shard_mt_index, MAX_COMMITMENT_SHARDS_COUNT};
     use crate::token::{spl_token_account_data, LAMPORTS_TOKEN_ID, USDC_TOKEN_ID, USDT_TOKEN_ID};
     use crate::types::{
         compute_fee_rec, compute_fee_rec_lamports, Proof, RawU256, JOIN_SPLIT_MAX_N_ARITY,
     };
+    use ark_bn254::{Fq, Fq2, Fr, G1Affine, G2Affine};
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{One, PrimeField};
     use assert_matches::assert_matches;
     use elusiv_types::tokens::Price;
     use elusiv_types::ProgramAccount;
@@ -1396,6 +1906,8 @@ mod tests {
         use ProofRequest::*;
 
         parent_account!(storage, StorageAccount);
+        parent_account!(mut storage1, StorageAccount);
+        storage1.set_shard(&1);
         parent_account!(mut nullifier, NullifierAccount);
         test_account_info!(fee_payer, 0);
         test_account_info!(identifier, 0);
@@ -1440,15 +1952,37 @@ mod tests {
         // TODO: wrong vkey-id
         // TODO: vkey not checked
 
+        // Proof-system mismatch
+        assert_matches!(
+            init_verification(
+                &fee_payer,
+                &v_acc,
+                &mut vkey,
+                &n_duplicate_acc,
+                &identifier,
+                &storage,
+                &storage1,
+                &nullifier,
+                &nullifier,
+                0,
+                vkey_id,
+                [0, 1],
+                PlonkSend(inputs.clone()),
+                false
+            ),
+            Err(_)
+        );
+
         // vkey-id exceeds `RESERVED_VERIFICATION_ACCOUNT_IDS`
         assert_matches!(
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 RESERVED_VERIFICATION_ACCOUNT_IDS,
@@ -1465,10 +1999,11 @@ mod tests {
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 0,
@@ -1487,10 +2022,11 @@ mod tests {
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 0,
@@ -1510,10 +2046,11 @@ mod tests {
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 0,
@@ -1532,10 +2069,11 @@ mod tests {
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 0,
@@ -1552,10 +2090,11 @@ mod tests {
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 0,
@@ -1581,10 +2120,11 @@ mod tests {
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 0,
@@ -1607,10 +2147,11 @@ mod tests {
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &invalid_n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 0,
@@ -1627,10 +2168,11 @@ mod tests {
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &invalid_n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 0,
@@ -1647,10 +2189,11 @@ mod tests {
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 0,
@@ -1670,26 +2213,35 @@ mod tests {
             init_verification(
                 &fee_payer,
                 &v_acc,
-                &vkey,
+                &mut vkey,
                 &n_duplicate_acc,
                 &identifier,
                 &storage,
+                &storage1,
                 &nullifier,
                 &nullifier,
                 0,
                 vkey_id,
                 [0, 1],
-                Send(inputs),
+                Send(inputs.clone()),
                 false
             ),
             Ok(())
         );
+
+        // The active version is used for the whole verification
+        assert_eq!(vkey.get_active_verifications(), 1);
+        let mut data = v_acc.data.borrow_mut();
+        let v_acc = VerificationAccount::new(&mut data).unwrap();
+        assert_eq!(v_acc.get_vkey_version(), 1);
     }
 
     #[test]
     #[should_panic]
     fn test_init_verification_commitment_count_too_high() {
         parent_account!(storage, StorageAccount);
+        parent_account!(mut storage1, StorageAccount);
+        storage1.set_shard(&1);
         parent_account!(nullifier, NullifierAccount);
         test_account_info!(fee_payer, 0);
         test_account_info!(identifier, 0);
@@ -1739,10 +2291,11 @@ mod tests {
         let _ = init_verification(
             &fee_payer,
             &v_acc,
-            &vkey,
+            &mut vkey,
             &n_duplicate_acc,
             &identifier,
             &storage,
+            &storage1,
             &nullifier,
             &nullifier,
             0,
@@ -1755,13 +2308,17 @@ mod tests {
 
     #[test]
     fn test_init_verification_transfer_fee_lamports() {
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
         test_account_info!(fee_payer, 0);
         test_account_info!(pool, 0);
         test_account_info!(fee_collector, 0);
         test_account_info!(any, 0);
+        test_account_info!(recipient, 0);
+        account_info!(apa_target_map, apa_target_map_account(recipient.key));
         account_info!(sys, system_program::id());
         account_info!(spl, spl_token::id());
         zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
         governor.set_program_fee(&fee());
 
         let mut inputs = SendPublicInputs {
@@ -1800,19 +2357,25 @@ mod tests {
         // Invalid fee_payer
         test_account_info!(fee_payer2, 0);
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer2,
                 &fee_payer,
                 &pool,
                 &pool,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector,
                 &any,
                 &any,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &sys,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -1821,19 +2384,25 @@ mod tests {
         // Invalid verification account state
         verification_acc.set_state(&VerificationState::FeeTransferred);
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &fee_payer,
                 &pool,
                 &pool,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector,
                 &any,
                 &any,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &sys,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -1843,19 +2412,25 @@ mod tests {
         verification_acc.set_state(&VerificationState::None);
         governor.set_fee_version(&1);
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &fee_payer,
                 &pool,
                 &pool,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector,
                 &any,
                 &any,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &sys,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -1866,19 +2441,25 @@ mod tests {
         inputs.join_split.fee -= 1;
         verification_acc.set_request(&ProofRequest::Send(inputs.clone()));
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &fee_payer,
                 &pool,
                 &pool,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector,
                 &any,
                 &any,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &sys,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -1887,21 +2468,27 @@ mod tests {
         // Invalid system_program
         inputs.join_split.fee = 0;
         compute_fee_rec_lamports::<SendQuadraVKey, _>(&mut inputs, &fee());
-        verification_acc.set_request(&ProofRequest::Send(inputs));
+        verification_acc.set_request(&ProofRequest::Send(inputs.clone()));
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &fee_payer,
                 &pool,
                 &pool,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector,
                 &any,
                 &any,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &sys,
                 &spl,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -1909,19 +2496,25 @@ mod tests {
 
         // Invalid pool_account
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &fee_payer,
                 &pool,
                 &any,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector,
                 &any,
                 &any,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &sys,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -1929,55 +2522,235 @@ mod tests {
 
         // Invalid fee_collector_account
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &fee_payer,
                 &pool,
                 &pool,
+                &mut pool_ledger,
                 &fee_collector,
                 &any,
                 &any,
                 &any,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &sys,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
         );
 
+        // Withdrawal limit exceeded
+        token_volume.set_volumes(
+            0,
+            &TokenVolume {
+                withdrawal_limit: ElusivOption::Some(inputs.join_split.amount - 1),
+                ..Default::default()
+            },
+        );
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &fee_payer,
                 &pool,
                 &pool,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector,
                 &any,
                 &any,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &sys,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
+            Err(_)
+        );
+        token_volume.set_volumes(0, &TokenVolume::default());
+
+        assert_matches!(
+            init_verification_transfer_fee_at(
+                &fee_payer,
+                &fee_payer,
+                &pool,
+                &pool,
+                &mut pool_ledger,
+                &fee_collector,
+                &fee_collector,
+                &any,
+                &any,
+                &governor,
+                &mut token_volume,
+                &mut verification_acc,
+                &sys,
+                &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
+                TOKEN_VOLUME_WINDOW + 1
+            ),
             Ok(())
         );
 
+        // The withdrawal volume is reserved once the fee has been transferred
+        assert_eq!(
+            token_volume.get_volumes(0).withdrawal_volume,
+            inputs.join_split.amount
+        );
+        assert_eq!(
+            verification_acc.get_other_data().withdrawal_window_start,
+            TOKEN_VOLUME_WINDOW
+        );
+
         assert_matches!(
             verification_acc.get_state(),
             VerificationState::FeeTransferred
         );
+        assert_eq!(
+            verification_acc.get_other_data().recipient_wallet.option(),
+            Some(RawU256::new(recipient.key.to_bytes()))
+        );
+    }
+
+    #[test]
+    fn test_unfinished_verification_does_not_block_withdrawals() {
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
+        test_account_info!(fee_payer, 0);
+        test_account_info!(pool, 0);
+        test_account_info!(fee_collector, 0);
+        test_account_info!(any, 0);
+        test_account_info!(recipient, 0);
+        account_info!(apa_target_map, apa_target_map_account(recipient.key));
+        account_info!(sys, system_program::id());
+        zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
+        governor.set_program_fee(&fee());
+
+        let mut inputs = SendPublicInputs {
+            join_split: JoinSplitPublicInputs {
+                input_commitments: vec![InputCommitment {
+                    root: Some(empty_root_raw()),
+                    nullifier_hash: RawU256::new(u256_from_str_skip_mr("1")),
+                }],
+                output_commitment: RawU256::new(u256_from_str_skip_mr("1")),
+                output_commitment_index: 123,
+                fee_version: 0,
+                amount: LAMPORTS_PER_SOL,
+                fee: 0,
+                token_id: 0,
+            },
+            recipient_is_associated_token_account: false,
+            hashed_inputs: u256_from_str_skip_mr("1"),
+            solana_pay_transfer: false,
+        };
+        compute_fee_rec_lamports::<SendQuadraVKey, _>(&mut inputs, &fee());
+        let instructions = prepare_public_inputs_instructions(
+            &inputs.public_signals_skip_mr(),
+            SendQuadraVKey::public_inputs_count(),
+        );
+
+        // The whole withdrawal limit is requested by two verifications
+        token_volume.set_volumes(
+            0,
+            &TokenVolume {
+                withdrawal_limit: ElusivOption::Some(inputs.join_split.amount),
+                ..Default::default()
+            },
+        );
+
+        zero_program_account!(mut unfinished_acc, VerificationAccount);
+        zero_program_account!(mut verification_acc, VerificationAccount);
+        for acc in [&mut unfinished_acc, &mut verification_acc] {
+            acc.set_request(&ProofRequest::Send(inputs.clone()));
+            acc.set_prepare_inputs_instructions_count(&(instructions.len() as u32));
+            acc.set_other_data(&VerificationAccountData {
+                fee_payer: RawU256::new(fee_payer.key.to_bytes()),
+                ..Default::default()
+            });
+        }
+
+        // The initialized verification (without a transferred fee) reserves nothing
+        assert_matches!(unfinished_acc.get_state(), VerificationState::None);
+        assert_eq!(token_volume.get_volumes(0).withdrawal_volume, 0);
+
+        assert_matches!(
+            init_verification_transfer_fee_at(
+                &fee_payer,
+                &fee_payer,
+                &pool,
+                &pool,
+                &mut pool_ledger,
+                &fee_collector,
+                &fee_collector,
+                &any,
+                &any,
+                &governor,
+                &mut token_volume,
+                &mut verification_acc,
+                &sys,
+                &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
+                0
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            token_volume.get_volumes(0).withdrawal_volume,
+            inputs.join_split.amount
+        );
+
+        // The limit is exhausted for the unfinished verification
+        assert_matches!(
+            init_verification_transfer_fee_at(
+                &fee_payer,
+                &fee_payer,
+                &pool,
+                &pool,
+                &mut pool_ledger,
+                &fee_collector,
+                &fee_collector,
+                &any,
+                &any,
+                &governor,
+                &mut token_volume,
+                &mut unfinished_acc,
+                &sys,
+                &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
+                0
+            ),
+            Err(_)
+        );
+        assert_matches!(unfinished_acc.get_state(), VerificationState::None);
     }
 
     #[test]
     fn test_init_verification_transfer_fee_token() {
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
         test_account_info!(fee_payer, 0);
         account_info!(sys, system_program::id());
         account_info!(spl, spl_token::id());
         zero_program_account!(mut governor, GovernorAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
         governor.set_program_fee(&fee());
 
         account_info!(
@@ -1995,6 +2768,29 @@ mod tests {
             false
         );
 
+        // Recipient token account (not an associated-token-account)
+        let recipient_owner = Pubkey::new_unique();
+        let mut recipient_data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(
+            spl_token::state::Account {
+                mint: elusiv_token(USDC_TOKEN_ID).unwrap().mint,
+                owner: recipient_owner,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            },
+            &mut recipient_data[..],
+        )
+        .unwrap();
+        account_info!(
+            recipient,
+            Pubkey::new_unique(),
+            recipient_data,
+            spl_token::id(),
+            false
+        );
+        account_info!(apa_target_map, apa_target_map_account(&recipient_owner));
+        test_account_info!(any, 0);
+
         test_pda_account_info!(pool, PoolAccount, None);
         test_pda_account_info!(fee_collector, FeeCollectorAccount, None);
         program_token_account_info!(pool_token, PoolAccount, USDC_TOKEN_ID);
@@ -2049,19 +2845,25 @@ mod tests {
         inputs.join_split.fee -= 1;
         verification_acc.set_request(&ProofRequest::Send(inputs.clone()));
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &token_acc,
                 &pool,
                 &pool_token,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector_token,
                 &sol,
                 &usdc,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &spl,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2073,19 +2875,25 @@ mod tests {
 
         // Invalid system_program
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &token_acc,
                 &pool,
                 &pool_token,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector_token,
                 &sol,
                 &usdc,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &spl,
                 &spl,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2093,19 +2901,25 @@ mod tests {
 
         // Invalid token_program
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &token_acc,
                 &pool,
                 &pool_token,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector_token,
                 &sol,
                 &usdc,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &sys,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2113,19 +2927,25 @@ mod tests {
 
         // Invalid fee_payer_account
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &wrong_token_acc,
                 &pool,
                 &pool_token,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector_token,
                 &sol,
                 &usdc,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &spl,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2133,19 +2953,25 @@ mod tests {
 
         // Invalid pool_account
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &token_acc,
                 &pool,
                 &fee_collector_token,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector_token,
                 &sol,
                 &usdc,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &spl,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2153,19 +2979,25 @@ mod tests {
 
         // Invalid fee_collector_account
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &token_acc,
                 &pool,
                 &pool_token,
+                &mut pool_ledger,
                 &fee_collector,
                 &pool_token,
                 &sol,
                 &usdc,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &spl,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2173,19 +3005,25 @@ mod tests {
 
         // Invalid sol_usd_price_account
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &token_acc,
                 &pool,
                 &pool_token,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector_token,
                 &usdc,
                 &usdc,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &spl,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2193,38 +3031,148 @@ mod tests {
 
         // Invalid token_usd_price_account
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
+                &fee_payer,
+                &token_acc,
+                &pool,
+                &pool_token,
+                &mut pool_ledger,
+                &fee_collector,
+                &fee_collector_token,
+                &sol,
+                &sol,
+                &governor,
+                &mut token_volume,
+                &mut verification_acc,
+                &spl,
+                &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
+                0
+            ),
+            Err(_)
+        );
+
+        // Invalid recipient token account
+        assert_matches!(
+            init_verification_transfer_fee_at(
+                &fee_payer,
+                &token_acc,
+                &pool,
+                &pool_token,
+                &mut pool_ledger,
+                &fee_collector,
+                &fee_collector_token,
+                &sol,
+                &usdc,
+                &governor,
+                &mut token_volume,
+                &mut verification_acc,
+                &spl,
+                &sys,
+                &wrong_token_acc,
+                &apa_target_map,
+                &any,
+                0,
+                0
+            ),
+            Err(_)
+        );
+
+        // The APA-target of a token account is its owner
+        account_info!(
+            token_account_apa_target_map,
+            apa_target_map_account(recipient.key)
+        );
+        assert_matches!(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &token_acc,
                 &pool,
                 &pool_token,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector_token,
                 &sol,
+                &usdc,
+                &governor,
+                &mut token_volume,
+                &mut verification_acc,
+                &spl,
+                &sys,
+                &recipient,
+                &token_account_apa_target_map,
+                &any,
+                0,
+                0
+            ),
+            Err(_)
+        );
+
+        // Withdrawals to outcasts are rejected
+        let ((map_pubkey, map_data), (proposal_pubkey, proposal_data)) =
+            apa_accounts_data(&recipient_owner, 0, ApaLevel::Outcast, None);
+        account_info!(
+            outcast_map,
+            map_pubkey,
+            map_data,
+            WARDEN_NETWORK_PROGRAM_ID,
+            false
+        );
+        account_info!(
+            outcast_proposal,
+            proposal_pubkey,
+            proposal_data,
+            WARDEN_NETWORK_PROGRAM_ID,
+            false
+        );
+        assert_matches!(
+            init_verification_transfer_fee_at(
+                &fee_payer,
+                &token_acc,
+                &pool,
+                &pool_token,
+                &mut pool_ledger,
+                &fee_collector,
+                &fee_collector_token,
                 &sol,
+                &usdc,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &spl,
                 &sys,
+                &recipient,
+                &outcast_map,
+                &outcast_proposal,
+                0,
                 0
             ),
             Err(_)
         );
 
         assert_matches!(
-            init_verification_transfer_fee(
+            init_verification_transfer_fee_at(
                 &fee_payer,
                 &token_acc,
                 &pool,
                 &pool_token,
+                &mut pool_ledger,
                 &fee_collector,
                 &fee_collector_token,
                 &sol,
                 &usdc,
                 &governor,
+                &mut token_volume,
                 &mut verification_acc,
                 &spl,
                 &sys,
+                &recipient,
+                &apa_target_map,
+                &any,
+                0,
                 0
             ),
             Ok(())
@@ -2272,6 +3220,44 @@ mod tests {
             Err(_)
         );
 
+        // Point at infinity
+        let mut invalid_proof = proof;
+        invalid_proof.c = G1A(G1Affine::zero());
+        assert_matches!(
+            init_verification_proof(&fee_payer, &mut verification_account, 0, invalid_proof),
+            Err(ProgramError::Custom(e)) if e == ElusivError::InvalidProofPoint as u32
+        );
+
+        // A not on curve
+        let mut invalid_proof = proof;
+        invalid_proof.a = G1A(G1Affine::new(proof.a.0.x, proof.a.0.x, false));
+        assert_matches!(
+            init_verification_proof(&fee_payer, &mut verification_account, 0, invalid_proof),
+            Err(ProgramError::Custom(e)) if e == ElusivError::InvalidProofPoint as u32
+        );
+
+        // B not on curve
+        let mut invalid_proof = proof;
+        invalid_proof.b = G2A(G2Affine::new(proof.b.0.x, proof.b.0.x, false));
+        assert_matches!(
+            init_verification_proof(&fee_payer, &mut verification_account, 0, invalid_proof),
+            Err(ProgramError::Custom(e)) if e == ElusivError::InvalidProofPoint as u32
+        );
+
+        // B outside of the G2 subgroup is only rejected during the verification (`G2SubgroupCheck`)
+        let mut invalid_proof = proof;
+        invalid_proof.b = G2A((0..)
+            .filter_map(|i| {
+                G2Affine::get_point_from_x(Fq2::new(Fq::from(i as u64), Fq::one()), false)
+            })
+            .find(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap());
+        assert_matches!(
+            init_verification_proof(&fee_payer, &mut verification_account, 0, invalid_proof),
+            Ok(())
+        );
+        verification_account.set_state(&VerificationState::FeeTransferred);
+
         // Success
         assert_matches!(
             init_verification_proof(&fee_payer, &mut verification_account, 0, proof),
@@ -2292,6 +3278,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_init_verification_plonk_proof() {
+        let proof = test_proof();
+        let plonk_proof = PlonkProof {
+            a: proof.a,
+            b: proof.c,
+            c: proof.a,
+            z: proof.c,
+            t1: proof.a,
+            t2: proof.c,
+            t3: proof.a,
+            w_xi: proof.c,
+            w_xiw: proof.a,
+            eval_a: RawU256::new(u256_from_str_skip_mr("1")),
+            eval_b: RawU256::new(u256_from_str_skip_mr("2")),
+            eval_c: RawU256::new(u256_from_str_skip_mr("3")),
+            eval_s1: RawU256::new(u256_from_str_skip_mr("4")),
+            eval_s2: RawU256::new(u256_from_str_skip_mr("5")),
+            eval_zw: RawU256::new(u256_from_str_skip_mr("6")),
+        };
+        let valid_pk = Pubkey::new(&[0; 32]);
+        account_info!(fee_payer, valid_pk, vec![0; 0]);
+        zero_program_account!(mut verification_account, VerificationAccount);
+        verification_account.set_state(&VerificationState::FeeTransferred);
+
+        // Groth16 request
+        assert_matches!(
+            init_verification_plonk_proof(&fee_payer, &mut verification_account, 0, plonk_proof),
+            Err(_)
+        );
+
+        let inputs = verification_account.get_request();
+        let inputs = match inputs {
+            ProofRequest::Send(inputs) => inputs,
+            _ => panic!(),
+        };
+        verification_account.set_request(&ProofRequest::PlonkSend(inputs));
+
+        // PLONK request with a Groth16 proof
+        assert_matches!(
+            init_verification_proof(&fee_payer, &mut verification_account, 0, proof),
+            Err(_)
+        );
+
+        // Invalid commitment
+        let mut invalid_proof = plonk_proof;
+        invalid_proof.t3 = G1A(G1Affine::new(proof.a.0.x, proof.a.0.x, false));
+        assert_matches!(
+            init_verification_plonk_proof(&fee_payer, &mut verification_account, 0, invalid_proof),
+            Err(ProgramError::Custom(e)) if e == ElusivError::InvalidProofPoint as u32
+        );
+
+        // Success
+        assert_matches!(
+            init_verification_plonk_proof(&fee_payer, &mut verification_account, 0, plonk_proof),
+            Ok(())
+        );
+        assert_matches!(
+            verification_account.get_state(),
+            VerificationState::ProofSetup
+        );
+        assert_eq!(verification_account.plonk_proof.get(), plonk_proof);
+    }
+
     #[test]
     fn test_compute_verification() {
         zero_program_account!(mut verification_account, VerificationAccount);
@@ -2299,6 +3349,20 @@ mod tests {
         vkey.set_version(&1);
         test_account_info!(any, 0);
 
+        // Unknown vkey version
+        verification_account.set_vkey_version(&2);
+        assert_matches!(
+            compute_verification(
+                &mut verification_account,
+                &vkey,
+                &any,
+                0,
+                SendQuadraVKey::VKEY_ID
+            ),
+            Err(_)
+        );
+        verification_account.set_vkey_version(&1);
+
         // Setup
         let public_inputs = test_public_inputs();
         for (i, &public_input) in public_inputs.iter().enumerate() {
@@ -2360,7 +3424,12 @@ mod tests {
         verification_account.set_state(&VerificationState::ProofSetup);
 
         // Success
-        for _ in 0..COMBINED_MILLER_LOOP_IXS + FINAL_EXPONENTIATION_IXS {
+        let compute_ix_count = if cfg!(feature = "alt-bn128") {
+            G2_SUBGROUP_CHECK_IXS + 1
+        } else {
+            G2_SUBGROUP_CHECK_IXS + COMBINED_MILLER_LOOP_IXS + FINAL_EXPONENTIATION_IXS
+        };
+        for _ in 0..compute_ix_count {
             assert_matches!(
                 compute_verification(
                     &mut verification_account,
@@ -2387,6 +3456,16 @@ mod tests {
         assert_matches!(verification_account.get_is_verified().option(), Some(false));
     }
 
+    macro_rules! vkey_pda_account {
+        ($id: ident, $vkey_id: expr) => {
+            let (pk, bump) = VKeyAccount::find(Some($vkey_id));
+            let mut data = vec![0; VKeyAccount::SIZE];
+            data[0] = bump;
+            data[1] = crate::PROGRAM_VERSION;
+            account_info!($id, pk, data);
+        };
+    }
+
     macro_rules! finalize_send_test {
         (
             $token_id: expr,
@@ -2426,6 +3505,7 @@ mod tests {
                     $reference,
                     false,
                     &None,
+                    &None,
                 ),
                 solana_pay_transfer: false,
             };
@@ -2440,6 +3520,7 @@ mod tests {
                     &[],
                     &vec![0],
                     0,
+                    0,
                     ProofRequest::Send($public_inputs.clone()),
                     [0, 1],
                 )
@@ -2460,6 +3541,7 @@ mod tests {
                 commitment_index: 0,
                 encrypted_owner,
                 iv,
+                program_call: ElusivOption::None,
             };
         };
     }
@@ -2470,6 +3552,100 @@ mod tests {
             let $id =
                 <StorageAccount as elusiv_types::accounts::ProgramAccount>::new(&mut data).unwrap();
         };
+        (mut $id: ident) => {
+            let mut data = vec![0; StorageAccount::SIZE];
+            let mut $id =
+                <StorageAccount as elusiv_types::accounts::ProgramAccount>::new(&mut data).unwrap();
+        };
+    }
+
+    #[test]
+    fn test_finalize_verification_send_program_call() {
+        finalize_send_test!(
+            0,
+            LAMPORTS_PER_SOL,
+            public_inputs,
+            verification_acc_data,
+            recipient_bytes,
+            identifier_bytes,
+            reference_bytes,
+            finalize_data
+        );
+
+        let mut data = vec![0; CommitmentQueueAccount::SIZE];
+        let mut queue = CommitmentQueueAccount::new(&mut data).unwrap();
+        storage_account!(storage);
+        zero_program_account!(governor, GovernorAccount);
+        account_info!(identifier, Pubkey::new_from_array(identifier_bytes));
+        account_info!(reference, Pubkey::new_from_array(reference_bytes));
+        test_account_info!(any, 0);
+
+        let program_call = ProgramCall::new(&Pubkey::new_unique(), &[1, 2, 3]);
+        let temporary_account =
+            program_call.temporary_account(&public_inputs.join_split.output_commitment.skip_mr());
+        let finalize_data = mutate(&finalize_data, |d| {
+            d.program_call = ElusivOption::Some(program_call)
+        });
+
+        for (recipient, token_id, recipient_is_associated_token_account, success) in [
+            // The funds are not transferred into the temporary account
+            (Pubkey::new_from_array(recipient_bytes), 0, false, false),
+            // Token program-calls require the associated-token-account of the temporary account
+            (temporary_account, USDC_TOKEN_ID, false, false),
+            (temporary_account, 0, false, true),
+            (temporary_account, USDC_TOKEN_ID, true, true),
+        ] {
+            let mut public_inputs = public_inputs.clone();
+            public_inputs.join_split.token_id = token_id;
+            public_inputs.recipient_is_associated_token_account =
+                recipient_is_associated_token_account;
+            public_inputs.hashed_inputs = generate_hashed_inputs(
+                recipient.to_bytes(),
+                identifier_bytes,
+                finalize_data.iv,
+                finalize_data.encrypted_owner,
+                reference_bytes,
+                recipient_is_associated_token_account,
+                &None,
+                &Some(program_call),
+            );
+            let finalize_data = mutate(&finalize_data, |d| {
+                d.token_id = token_id;
+                d.total_amount = public_inputs.join_split.total_amount();
+            });
+
+            let mut data = verification_acc_data.clone();
+            let mut verification_acc = VerificationAccount::new(&mut data).unwrap();
+            verification_acc.set_request(&ProofRequest::Send(public_inputs));
+            verification_acc.set_other_data(&mutate(&verification_acc.get_other_data(), |d| {
+                d.recipient_wallet = ElusivOption::Some(RawU256::new(recipient.to_bytes()));
+            }));
+
+            account_info!(recipient, recipient);
+            let result = finalize_verification_send(
+                &recipient,
+                &identifier,
+                &reference,
+                &mut queue,
+                &mut verification_acc,
+                &storage,
+                &governor,
+                &any,
+                0,
+                finalize_data,
+                false,
+            );
+
+            if success {
+                assert_matches!(result, Ok(()));
+                assert_eq!(
+                    verification_acc.get_other_data().program_call.option(),
+                    Some(program_call)
+                );
+            } else {
+                assert_matches!(result, Err(_));
+            }
+        }
     }
 
     #[test]
@@ -2489,6 +3665,7 @@ mod tests {
         let mut data = vec![0; CommitmentQueueAccount::SIZE];
         let mut queue = CommitmentQueueAccount::new(&mut data).unwrap();
         storage_account!(storage);
+        zero_program_account!(governor, GovernorAccount);
 
         account_info!(recipient, Pubkey::new_from_array(recipient_bytes));
         account_info!(identifier, Pubkey::new_from_array(identifier_bytes));
@@ -2505,6 +3682,7 @@ mod tests {
                 &mut queue,
                 &mut verification_acc,
                 &storage,
+                &governor,
                 &any,
                 0,
                 finalize_data.clone(),
@@ -2515,9 +3693,33 @@ mod tests {
 
         verification_acc.set_is_verified(&ElusivOption::Some(true));
 
-        // Invalid recipient
+        // Invalid recipient
+        {
+            account_info!(recipient, Pubkey::new_from_array(identifier_bytes));
+            assert_matches!(
+                finalize_verification_send(
+                    &recipient,
+                    &identifier,
+                    &reference,
+                    &mut queue,
+                    &mut verification_acc,
+                    &storage,
+                    &governor,
+                    &any,
+                    0,
+                    finalize_data.clone(),
+                    false
+                ),
+                Err(_)
+            );
+        }
+
+        // Recipient differs from the recipient checked in `init_verification_transfer_fee`
         {
-            account_info!(recipient, Pubkey::new_from_array(identifier_bytes));
+            let other_data = verification_acc.get_other_data();
+            verification_acc.set_other_data(&mutate(&other_data, |d| {
+                d.recipient_wallet = ElusivOption::Some(RawU256::new(identifier_bytes));
+            }));
             assert_matches!(
                 finalize_verification_send(
                     &recipient,
@@ -2526,6 +3728,7 @@ mod tests {
                     &mut queue,
                     &mut verification_acc,
                     &storage,
+                    &governor,
                     &any,
                     0,
                     finalize_data.clone(),
@@ -2533,6 +3736,7 @@ mod tests {
                 ),
                 Err(_)
             );
+            verification_acc.set_other_data(&other_data);
         }
 
         // Invalid identifier
@@ -2546,6 +3750,7 @@ mod tests {
                     &mut queue,
                     &mut verification_acc,
                     &storage,
+                    &governor,
                     &any,
                     0,
                     finalize_data.clone(),
@@ -2566,6 +3771,7 @@ mod tests {
                     &mut queue,
                     &mut verification_acc,
                     &storage,
+                    &governor,
                     &any,
                     0,
                     finalize_data.clone(),
@@ -2585,6 +3791,9 @@ mod tests {
             mutate(&finalize_data, |d| d.mt_index = 1),
             mutate(&finalize_data, |d| d.encrypted_owner = d.iv),
             mutate(&finalize_data, |d| d.iv = d.encrypted_owner),
+            mutate(&finalize_data, |d| {
+                d.program_call = ElusivOption::Some(ProgramCall::new(&Pubkey::new_unique(), &[]))
+            }),
         ] {
             assert_matches!(
                 finalize_verification_send(
@@ -2594,6 +3803,7 @@ mod tests {
                     &mut queue,
                     &mut verification_acc,
                     &storage,
+                    &governor,
                     &any,
                     0,
                     invalid_data,
@@ -2603,6 +3813,35 @@ mod tests {
             );
         }
 
+        // Next-commitment belongs to a different shard
+        {
+            zero_program_account!(mut governor, GovernorAccount);
+            governor.set_commitment_shards_count(&MAX_COMMITMENT_SHARDS_COUNT);
+            let shard = commitment_shard(
+                &public_inputs.join_split.output_commitment.reduce(),
+                MAX_COMMITMENT_SHARDS_COUNT,
+            );
+            storage_account!(mut storage);
+            storage.set_shard(&((shard + 1) % MAX_COMMITMENT_SHARDS_COUNT));
+
+            assert_matches!(
+                finalize_verification_send(
+                    &recipient,
+                    &identifier,
+                    &reference,
+                    &mut queue,
+                    &mut verification_acc,
+                    &storage,
+                    &governor,
+                    &any,
+                    0,
+                    finalize_data.clone(),
+                    false
+                ),
+                Err(_)
+            );
+        }
+
         // Success
         assert_matches!(
             finalize_verification_send(
@@ -2612,6 +3851,7 @@ mod tests {
                 &mut queue,
                 &mut verification_acc,
                 &storage,
+                &governor,
                 &any,
                 0,
                 finalize_data.clone(),
@@ -2624,6 +3864,7 @@ mod tests {
             verification_acc.get_state(),
             VerificationState::InsertNullifiers
         );
+        assert_eq!(verification_acc.get_other_data().commitment_shard, 0);
 
         // Called twice
         assert_matches!(
@@ -2634,6 +3875,7 @@ mod tests {
                 &mut queue,
                 &mut verification_acc,
                 &storage,
+                &governor,
                 &any,
                 0,
                 finalize_data,
@@ -2660,6 +3902,7 @@ mod tests {
         let mut data = vec![0; CommitmentQueueAccount::SIZE];
         let mut queue = CommitmentQueueAccount::new(&mut data).unwrap();
         storage_account!(storage);
+        zero_program_account!(governor, GovernorAccount);
         test_account_info!(any, 0);
 
         account_info!(recipient, Pubkey::new_from_array(recipient_bytes));
@@ -2676,6 +3919,7 @@ mod tests {
                 &mut queue,
                 &mut verification_acc,
                 &storage,
+                &governor,
                 &any,
                 0,
                 finalize_data,
@@ -2719,6 +3963,7 @@ mod tests {
 
         let finalize_data = FinalizeSendData::default();
         storage_account!(storage);
+        zero_program_account!(governor, GovernorAccount);
         test_account_info!(any, 0);
 
         assert_matches!(
@@ -2729,6 +3974,7 @@ mod tests {
                 &mut queue,
                 &mut v_account,
                 &storage,
+                &governor,
                 &any,
                 0,
                 finalize_data,
@@ -2796,6 +4042,8 @@ mod tests {
 
     #[test]
     fn test_finalize_verification_transfer_lamports() -> ProgramResult {
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
         finalize_send_test!(
             LAMPORTS_TOKEN_ID,
             LAMPORTS_PER_SOL,
@@ -2828,6 +4076,7 @@ mod tests {
             None
         );
         account_info!(v_acc, Pubkey::new_unique(), verification_acc_data);
+        vkey_pda_account!(vkey_acc, 0);
         let mut data = vec![0; CommitmentQueueAccount::SIZE];
         let mut queue = CommitmentQueueAccount::new(&mut data).unwrap();
 
@@ -2842,12 +4091,19 @@ mod tests {
             finalize_verification_transfer_lamports(
                 &f,
                 &recipient,
+                &any,
                 &pool,
                 &fee_collector,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
+                &any,
                 &any,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2868,12 +4124,19 @@ mod tests {
             finalize_verification_transfer_lamports(
                 &f,
                 &recipient,
+                &any,
                 &pool,
                 &fee_collector,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &invalid_n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
+                &any,
                 &any,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2884,12 +4147,19 @@ mod tests {
             finalize_verification_transfer_lamports(
                 &any,
                 &recipient,
+                &any,
                 &pool,
                 &fee_collector,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
+                &any,
                 &any,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2900,12 +4170,19 @@ mod tests {
             finalize_verification_transfer_lamports(
                 &f,
                 &any,
+                &any,
                 &pool,
                 &fee_collector,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
+                &any,
                 &any,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2928,12 +4205,19 @@ mod tests {
             finalize_verification_transfer_lamports(
                 &f,
                 &recipient,
+                &any,
                 &pool,
                 &fee_collector,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
+                &any,
                 &any,
+                &any,
+                0,
                 0
             ),
             Err(_)
@@ -2942,31 +4226,98 @@ mod tests {
         let mut data = vec![0; CommitmentQueueAccount::SIZE];
         let mut queue = CommitmentQueueAccount::new(&mut data).unwrap();
 
+        // Invalid vkey_account
+        assert_matches!(
+            finalize_verification_transfer_lamports(
+                &f,
+                &recipient,
+                &any,
+                &pool,
+                &fee_collector,
+                &mut queue,
+                &v_acc,
+                &invalid_n_pda,
+                &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
+                &any,
+                &any,
+                &any,
+                0,
+                0
+            ),
+            Err(_)
+        );
+
+        // Invalid commitment shard
+        assert_matches!(
+            finalize_verification_transfer_lamports(
+                &f,
+                &recipient,
+                &any,
+                &pool,
+                &fee_collector,
+                &mut queue,
+                &v_acc,
+                &vkey_acc,
+                &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
+                &any,
+                &any,
+                &any,
+                0,
+                1
+            ),
+            Err(_)
+        );
+
+        {
+            pda_account!(mut vkey, VKeyAccount, vkey_acc);
+            vkey.set_active_verifications(&1);
+        }
+
         assert_matches!(
             finalize_verification_transfer_lamports(
                 &f,
                 &recipient,
+                &any,
                 &pool,
                 &fee_collector,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
+                &any,
+                &any,
                 &any,
+                0,
                 0
             ),
             Ok(())
         );
 
+        // The withdrawal volume has already been reserved in `init_verification_transfer_fee`
+        assert_eq!(token_volume.get_volumes(0).withdrawal_volume, 0);
+
         assert_eq!(n_pda.lamports(), 0);
         assert_eq!(v_acc.lamports(), 0);
         pda_account!(v_acc, VerificationAccount, v_acc);
         assert_matches!(v_acc.get_state(), VerificationState::Closed);
 
+        // The vkey version is released
+        pda_account!(vkey, VKeyAccount, vkey_acc);
+        assert_eq!(vkey.get_active_verifications(), 0);
+
         Ok(())
     }
 
     #[test]
     fn test_finalize_verification_transfer_lamports_merge() -> ProgramResult {
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
         finalize_send_test!(
             LAMPORTS_TOKEN_ID,
             0,
@@ -2999,6 +4350,7 @@ mod tests {
         );
 
         account_info!(v_acc, Pubkey::new_unique(), verification_acc_data);
+        vkey_pda_account!(vkey_acc, 0);
         let mut data = vec![0; CommitmentQueueAccount::SIZE];
         let mut queue = CommitmentQueueAccount::new(&mut data).unwrap();
 
@@ -3014,12 +4366,19 @@ mod tests {
             finalize_verification_transfer_lamports(
                 &f,
                 &recipient,
+                &any,
                 &pool,
                 &fee_collector,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
                 &any,
+                &any,
+                &any,
+                0,
                 0
             ),
             Ok(())
@@ -3028,8 +4387,131 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_finalize_verification_transfer_lamports_associated_token_account() -> ProgramResult {
+        const ASSOCIATED_TOKEN_ACCOUNT_RENT: u64 = 2_039_280;
+
+        for associated_token_account_exists in [false, true] {
+            zero_program_account!(mut token_volume, TokenVolumeAccount);
+            zero_program_account!(mut pool_ledger, PoolLedgerAccount);
+            finalize_send_test!(
+                LAMPORTS_TOKEN_ID,
+                LAMPORTS_PER_SOL,
+                public_inputs,
+                verification_acc_data,
+                recipient_bytes,
+                _i,
+                _r,
+                _f
+            );
+
+            let mut public_inputs = public_inputs;
+            public_inputs.recipient_is_associated_token_account = true;
+            {
+                let mut v_acc = VerificationAccount::new(&mut verification_acc_data).unwrap();
+                v_acc.set_request(&ProofRequest::Send(public_inputs.clone()));
+                v_acc.set_state(&VerificationState::Finalized);
+                v_acc.set_other_data(&mutate(&v_acc.get_other_data(), |data| {
+                    data.associated_token_account_rent = ASSOCIATED_TOKEN_ACCOUNT_RENT;
+                }));
+            }
+
+            let wallet = Pubkey::new_from_array(recipient_bytes);
+            account_info!(recipient_wallet, wallet);
+            account_info!(
+                recipient,
+                get_associated_token_address(&wallet, &spl_token::native_mint::ID),
+                vec![],
+                spl_token::id(),
+                false
+            );
+            if !associated_token_account_exists {
+                **recipient.lamports.borrow_mut() = 0;
+            }
+
+            let fee_payer_pk = Pubkey::new(
+                &VerificationAccount::new(&mut verification_acc_data)
+                    .unwrap()
+                    .get_other_data()
+                    .fee_payer
+                    .skip_mr(),
+            );
+            account_info!(f, fee_payer_pk); // fee_payer
+            test_account_info!(pool, 0);
+            test_account_info!(fee_collector, 0);
+            test_account_info!(any, 0);
+            account_info!(spl, spl_token::id());
+            test_pda_account_info!(
+                n_pda,
+                NullifierDuplicateAccount,
+                public_inputs
+                    .join_split
+                    .associated_nullifier_duplicate_pda_pubkey(),
+                None
+            );
+            account_info!(v_acc, Pubkey::new_unique(), verification_acc_data);
+            vkey_pda_account!(vkey_acc, 0);
+            {
+                pda_account!(mut vkey, VKeyAccount, vkey_acc);
+                vkey.set_active_verifications(&1);
+            }
+            let mut data = vec![0; CommitmentQueueAccount::SIZE];
+            let mut queue = CommitmentQueueAccount::new(&mut data).unwrap();
+
+            let fee_payer_lamports = f.lamports() + v_acc.lamports() + n_pda.lamports();
+            let recipient_lamports = recipient.lamports();
+
+            assert_matches!(
+                finalize_verification_transfer_lamports(
+                    &f,
+                    &recipient,
+                    &recipient_wallet,
+                    &pool,
+                    &fee_collector,
+                    &mut queue,
+                    &v_acc,
+                    &vkey_acc,
+                    &n_pda,
+                    &mut token_volume,
+                    &mut pool_ledger,
+                    &spl,
+                    &any,
+                    &any,
+                    0,
+                    0
+                ),
+                Ok(())
+            );
+
+            // A created associated token account is rented by `original_fee_payer` out of the `amount`
+            let rent = if associated_token_account_exists {
+                0
+            } else {
+                ASSOCIATED_TOKEN_ACCOUNT_RENT
+            };
+            assert_eq!(
+                recipient.lamports(),
+                recipient_lamports + LAMPORTS_PER_SOL - rent
+            );
+
+            // The reserved `associated_token_account_rent` is paid back to `original_fee_payer`
+            assert_eq!(
+                f.lamports(),
+                fee_payer_lamports + rent + ASSOCIATED_TOKEN_ACCOUNT_RENT
+            );
+            assert_eq!(
+                pool_ledger.get_balances(0).fees_paid,
+                ASSOCIATED_TOKEN_ACCOUNT_RENT
+            );
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_finalize_verification_transfer_token() -> ProgramResult {
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
         finalize_send_test!(
             USDC_TOKEN_ID,
             LAMPORTS_PER_SOL,
@@ -3074,6 +4556,7 @@ mod tests {
             None
         );
         account_info!(v_acc, Pubkey::new_unique(), verification_acc_data);
+        vkey_pda_account!(vkey_acc, 0);
         let mut data = vec![0; CommitmentQueueAccount::SIZE];
         let mut queue = CommitmentQueueAccount::new(&mut data).unwrap();
 
@@ -3096,10 +4579,14 @@ mod tests {
                 &fee_collector_token,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
                 &spl,
                 &any,
                 &any,
+                0,
                 0
             ),
             Err(_)
@@ -3118,10 +4605,14 @@ mod tests {
                 &pool_token,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
                 &spl,
                 &any,
                 &any,
+                0,
                 0
             ),
             Err(_)
@@ -3140,10 +4631,14 @@ mod tests {
                 &fee_collector_token,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
                 &any,
                 &any,
                 &any,
+                0,
                 0
             ),
             Err(_)
@@ -3162,10 +4657,14 @@ mod tests {
                 &fee_collector_token,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
                 &spl,
                 &any,
                 &any,
+                0,
                 0
             ),
             Err(_)
@@ -3184,10 +4683,14 @@ mod tests {
                 &fee_collector_token,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
                 &spl,
                 &any,
                 &any,
+                0,
                 0
             ),
             Err(_)
@@ -3205,10 +4708,14 @@ mod tests {
                 &fee_collector_token,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
                 &spl,
                 &any,
                 &any,
+                0,
                 0
             ),
             Ok(())
@@ -3224,6 +4731,8 @@ mod tests {
 
     #[test]
     fn test_finalize_verification_transfer_token_merge() -> ProgramResult {
+        zero_program_account!(mut token_volume, TokenVolumeAccount);
+        zero_program_account!(mut pool_ledger, PoolLedgerAccount);
         finalize_send_test!(
             USDC_TOKEN_ID,
             0,
@@ -3267,6 +4776,7 @@ mod tests {
             None
         );
         account_info!(v_acc, Pubkey::new_unique(), verification_acc_data);
+        vkey_pda_account!(vkey_acc, 0);
         let mut data = vec![0; CommitmentQueueAccount::SIZE];
         let mut queue = CommitmentQueueAccount::new(&mut data).unwrap();
 
@@ -3290,10 +4800,14 @@ mod tests {
                 &fee_collector_token,
                 &mut queue,
                 &v_acc,
+                &vkey_acc,
                 &n_pda,
+                &mut token_volume,
+                &mut pool_ledger,
                 &spl,
                 &any,
                 &any,
+                0,
                 0
             ),
             Ok(())
@@ -3328,6 +4842,13 @@ mod tests {
             ),
             (1, 2)
         );
+
+        // The next MT of a shard
+        let mt_index = shard_mt_index(3, 4).unwrap();
+        assert_eq!(
+            minimum_commitment_mt_index(mt_index, MT_COMMITMENT_COUNT as u32, 0),
+            (0, shard_mt_index(3, 5).unwrap())
+        );
     }
 
     #[test]
@@ -3346,6 +4867,12 @@ mod tests {
         storage_account!(storage);
         parent_account!(n_account, NullifierAccount);
 
+        // The closed MT of the second shard
+        parent_account!(mut storage1, StorageAccount);
+        storage1.set_shard(&1);
+        storage1.set_trees_count(&1);
+        let mt1 = shard_mt_index(1, 0).unwrap();
+
         let valid_inputs = JoinSplitPublicInputs {
             input_commitments: vec![InputCommitment {
                 root: Some(empty_root_raw()),
@@ -3404,9 +4931,9 @@ mod tests {
             assert_matches!(
                 check_join_split_public_inputs(
                     &public_inputs,
-                    &storage,
+                    [&storage, &storage1],
                     [&n_account, &n_account],
-                    &[0, 1]
+                    &[0, mt1]
                 ),
                 Err(_)
             );
@@ -3427,7 +4954,7 @@ mod tests {
                         },
                     ];
                 }),
-                &storage,
+                [&storage, &storage],
                 [&n_account, &n_account],
                 &[0, 0]
             ),
@@ -3438,9 +4965,9 @@ mod tests {
         assert_matches!(
             check_join_split_public_inputs(
                 &valid_inputs,
-                &storage,
+                [&storage, &storage1],
                 [&n_account, &n_account],
-                &[0, 1]
+                &[0, mt1]
             ),
             Ok(())
         );
@@ -3465,9 +4992,9 @@ mod tests {
             assert_matches!(
                 check_join_split_public_inputs(
                     &public_inputs,
-                    &storage,
+                    [&storage, &storage1],
                     [&n_account, &n_account],
-                    &[0, 1]
+                    &[0, mt1]
                 ),
                 Ok(())
             );
@@ -3495,12 +5022,123 @@ mod tests {
                     inputs.input_commitments[0].nullifier_hash =
                         RawU256::new(u256_from_str_skip_mr("1"));
                 }),
-                &storage,
+                [&storage, &storage1],
+                [&n_account, &n_account],
+                &[0, mt1]
+            ),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_check_join_split_public_inputs_root_history() {
+        let mut data = vec![0; StorageAccount::SIZE];
+        let mut storage = StorageAccount::new(&mut data).unwrap();
+        parent_account!(mut storage1, StorageAccount);
+        storage1.set_shard(&1);
+        parent_account!(n_account, NullifierAccount);
+
+        let root = |v: &str| RawU256::new(u256_from_str_skip_mr(v));
+        let inputs = |roots: [Option<RawU256>; 2]| JoinSplitPublicInputs {
+            input_commitments: roots
+                .iter()
+                .enumerate()
+                .filter(|(i, r)| *i == 0 || r.is_some())
+                .map(|(i, r)| InputCommitment {
+                    root: *r,
+                    nullifier_hash: RawU256::new(u256_from_str_skip_mr(&i.to_string())),
+                })
+                .collect(),
+            output_commitment: RawU256::new(u256_from_str_skip_mr("1")),
+            output_commitment_index: 123,
+            fee_version: 0,
+            amount: 0,
+            fee: 456,
+            token_id: 0,
+        };
+        let check = |storage: &StorageAccount, roots, tree_indices: [u32; MAX_MT_COUNT]| {
+            check_join_split_public_inputs(
+                &inputs(roots),
+                [storage, storage],
                 [&n_account, &n_account],
-                &[0, 1]
+                &tree_indices,
+            )
+        };
+
+        // Subsequent MTs of the same shard
+        let mt0 = 0;
+        let mt1 = 1;
+        let mt2 = 2;
+
+        // Roots of MT 0
+        for v in ["1", "2", "3"] {
+            storage.push_root(&root(v).reduce()).unwrap();
+        }
+
+        // Stale but recent roots of the active MT
+        for v in ["1", "2", "3"] {
+            assert_matches!(check(&storage, [Some(root(v)), None], [mt0, 1]), Ok(()));
+        }
+        assert_matches!(check(&storage, [Some(root("4")), None], [mt0, 1]), Err(_));
+
+        // MT 0 is closed, MT 1 is active
+        storage.set_trees_count(&1);
+        storage.reset();
+        storage.push_root(&root("4").reduce()).unwrap();
+
+        assert_matches!(check(&storage, [Some(root("4")), None], [mt1, mt0]), Ok(()));
+        assert_matches!(check(&storage, [Some(root("1")), None], [mt1, mt0]), Err(_));
+
+        // Recent roots of the most recently closed MT
+        for v in ["1", "2", "3"] {
+            assert_matches!(
+                check(&storage, [Some(root("4")), Some(root(v))], [mt1, mt0]),
+                Ok(())
+            );
+        }
+        assert_matches!(
+            check(&storage, [Some(root("4")), Some(root("4"))], [mt1, mt0]),
+            Err(_)
+        );
+
+        // Older closed MTs only accept their final root
+        storage.set_trees_count(&2);
+        storage.reset();
+        assert_matches!(
+            check(
+                &storage,
+                [Some(empty_root_raw()), Some(root("3"))],
+                [mt2, mt0]
+            ),
+            Err(_)
+        );
+        assert_matches!(
+            check(
+                &storage,
+                [Some(empty_root_raw()), Some(root("4"))],
+                [mt2, mt1]
+            ),
+            Ok(())
+        );
+
+        // The active MT of another shard requires the `StorageAccount` of that shard
+        assert_matches!(
+            check(
+                &storage,
+                [Some(empty_root_raw()), Some(empty_root_raw())],
+                [mt2, shard_mt_index(1, 0).unwrap()]
             ),
             Err(_)
         );
+        assert_matches!(
+            check_join_split_public_inputs(
+                &inputs([Some(empty_root_raw()), Some(empty_root_raw())]),
+                [&storage, &storage1],
+                [&n_account, &n_account],
+                &[mt2, shard_mt_index(1, 0).unwrap()],
+            ),
+            Ok(())
+        );
     }
 
     struct StubInstruction(u8, Option<Vec<u8>>, Pubkey);
@@ -3928,6 +5566,81 @@ mod tests {
         assert_eq!(SPL_MEMO_PROGRAM_ID, spl_memo::ID);
     }
 
+    #[test]
+    fn test_enforce_program_call() {
+        let recipient = Pubkey::new_unique();
+        let instruction =
+            system_instruction::transfer(&recipient, &Pubkey::new_unique(), LAMPORTS_PER_SOL);
+        let program_call = ProgramCall::new(&instruction.program_id, &instruction.data);
+        let transfer_instruction: Instruction = StubInstruction(
+            ElusivInstruction::FINALIZE_VERIFICATION_TRANSFER_LAMPORTS_INDEX,
+            None,
+            crate::id(),
+        )
+        .into();
+
+        let sysvar = |instructions: Vec<Instruction>| TestInstructionsSysvar {
+            current_index: Some(0),
+            instructions,
+        };
+
+        // Missing program call
+        assert_matches!(
+            enforce_program_call(
+                &sysvar(vec![transfer_instruction.clone()]),
+                &program_call,
+                &recipient
+            ),
+            Err(_)
+        );
+
+        // Program call not following the transfer
+        assert_matches!(
+            enforce_program_call(
+                &sysvar(vec![
+                    transfer_instruction.clone(),
+                    memo_instruction(&[]),
+                    instruction.clone()
+                ]),
+                &program_call,
+                &recipient
+            ),
+            Err(_)
+        );
+
+        // Mismatched instruction data
+        assert_matches!(
+            enforce_program_call(
+                &sysvar(vec![
+                    transfer_instruction.clone(),
+                    system_instruction::transfer(&recipient, &Pubkey::new_unique(), 1)
+                ]),
+                &program_call,
+                &recipient
+            ),
+            Err(_)
+        );
+
+        // Recipient not used
+        assert_matches!(
+            enforce_program_call(
+                &sysvar(vec![transfer_instruction.clone(), instruction.clone()]),
+                &program_call,
+                &Pubkey::new_unique()
+            ),
+            Err(_)
+        );
+
+        assert_matches!(
+            enforce_program_call(
+                &sysvar(vec![transfer_instruction, instruction]),
+                &program_call,
+                &recipient
+            ),
+            Ok(())
+        );
+    }
+
     #[test]
     fn test_memo_instruction() {
         let memo = String::from("Thanks%20for%20all%20the%20fish");
@@ -3989,29 +5702,14 @@ mod tests {
     }
 
     fn test_proof() -> Proof {
-        proof_from_str(
-            (
-                "10026859857882131638516328056627849627085232677511724829502598764489185541935",
-                "19685960310506634721912121951341598678325833230508240750559904196809564625591",
-                false,
-            ),
-            (
-                (
-                    "857882131638516328056627849627085232677511724829502598764489185541935",
-                    "685960310506634721912121951341598678325833230508240750559904196809564625591",
-                ),
-                (
-                    "837064132573119120838379738103457054645361649757131991036638108422638197362",
-                    "86803555845400161937398579081414146527572885637089779856221229551142844794",
-                ),
-                false,
-            ),
-            (
-                "21186803555845400161937398579081414146527572885637089779856221229551142844794",
-                "85960310506634721912121951341598678325833230508240750559904196809564625591",
-                false,
-            ),
-        )
+        let g1 = G1Affine::prime_subgroup_generator();
+        let g2 = G2Affine::prime_subgroup_generator();
+
+        Proof {
+            a: G1A(g1.mul(Fr::from(123u64).into_repr()).into_affine()),
+            b: G2A(g2.mul(Fr::from(456u64).into_repr()).into_affine()),
+            c: G1A(g1.mul(Fr::from(789u64).into_repr()).into_affine()),
+        }
     }
 
     fn test_public_inputs() -> Vec<U256> {
@@ -2,14 +2,15 @@ use crate::error::ElusivError;
 use crate::macros::guard;
 use crate::state::program_account::{PDAAccount, PDAOffset};
 use crate::token::{elusiv_token, Lamports, SPLToken, Token};
+use solana_program::bpf_loader_upgradeable;
 use solana_program::instruction::Instruction;
 use solana_program::program::invoke;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::sysvar::instructions;
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError, rent::Rent,
-    sysvar::Sysvar,
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+    program_error::ProgramError, rent::Rent, sysvar::Sysvar,
 };
 use spl_associated_token_account::get_associated_token_address;
 
@@ -154,6 +155,7 @@ fn transfer_with_token_program<'a>(
     }
 }
 
+/// Creates an associated token account for `token_id` (for `token_id = 0` a wrapped-SOL account)
 pub fn create_associated_token_account<'a>(
     payer: &AccountInfo<'a>,
     wallet_account: &AccountInfo<'a>,
@@ -166,7 +168,7 @@ pub fn create_associated_token_account<'a>(
         &spl_associated_token_account::instruction::create_associated_token_account(
             payer.key,
             wallet_account.key,
-            &elusiv_token(token_id)?.mint,
+            &associated_token_account_mint(token_id)?,
             &spl_token::ID,
         ),
         &[
@@ -178,6 +180,72 @@ pub fn create_associated_token_account<'a>(
     )
 }
 
+/// The mint of associated token accounts for `token_id` (wrapped-SOL for `token_id = 0`)
+pub fn associated_token_account_mint(token_id: u16) -> Result<Pubkey, ProgramError> {
+    if token_id == 0 {
+        Ok(spl_token::native_mint::ID)
+    } else {
+        Ok(elusiv_token(token_id)?.mint)
+    }
+}
+
+/// Closes the wrapped-SOL `token_account` of `owner`, unwrapping all of its lamports into `owner`
+pub fn unwrap_lamports<'a>(
+    owner: &AccountInfo<'a>,
+    token_account: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    guard!(
+        *token_program.key == spl_token::ID,
+        ElusivError::InvalidAccount
+    );
+    guard!(
+        *token_account.owner == spl_token::ID,
+        ElusivError::InvalidAccount
+    );
+
+    let account = spl_token::state::Account::unpack(&token_account.data.borrow())?;
+    guard!(
+        account.mint == spl_token::native_mint::ID && account.owner == *owner.key,
+        ElusivError::InvalidAccount
+    );
+
+    invoke(
+        &spl_token::instruction::close_account(
+            &spl_token::ID,
+            token_account.key,
+            owner.key,
+            owner.key,
+            &[],
+        )?,
+        &[token_account.clone(), owner.clone(), token_program.clone()],
+    )
+}
+
+/// Transfers `lamports` from `pda` into the wrapped-SOL `token_account` and syncs its token amount
+pub fn wrap_lamports_from_pda<'a>(
+    pda: &AccountInfo<'a>,
+    token_account: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    lamports: u64,
+) -> ProgramResult {
+    guard!(
+        *token_program.key == spl_token::ID,
+        ElusivError::InvalidAccount
+    );
+    guard!(
+        *token_account.owner == spl_token::ID,
+        ElusivError::InvalidAccount
+    );
+
+    transfer_lamports_from_pda_checked(pda, token_account, lamports)?;
+
+    invoke(
+        &spl_token::instruction::sync_native(&spl_token::ID, token_account.key)?,
+        &[token_account.clone(), token_program.clone()],
+    )
+}
+
 pub fn program_token_account_address<A: PDAAccount>(
     token_id: u16,
     offset: PDAOffset,
@@ -224,6 +292,53 @@ pub fn spl_token_account_rent() -> Result<Lamports, ProgramError> {
     ))
 }
 
+/// The rent-exemption minimum of an account with `data_len` bytes
+pub fn rent_exemption_minimum(data_len: usize) -> Result<Lamports, ProgramError> {
+    Ok(Lamports(Rent::get()?.minimum_balance(data_len)))
+}
+
+pub fn current_timestamp() -> Result<u64, ProgramError> {
+    Ok(Clock::get()?.unix_timestamp as u64)
+}
+
+/// The `ProgramData` account of the program (owned by the upgradeable BPF loader)
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[crate::id().as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/// Verifies that `signer` is the upgrade authority stored in the `ProgramData` account
+///
+/// # Notes
+///
+/// The `ProgramData` account is bincode-serialized as `(u32 variant = 3, u64 slot, Option<Pubkey> upgrade_authority)`.
+pub fn verify_upgrade_authority(signer: &AccountInfo, program_data: &AccountInfo) -> ProgramResult {
+    const PROGRAM_DATA_VARIANT: u32 = 3;
+    const AUTHORITY_OFFSET: usize = 4 + 8;
+
+    guard!(signer.is_signer, ProgramError::MissingRequiredSignature);
+    guard!(
+        *program_data.owner == bpf_loader_upgradeable::id(),
+        ElusivError::InvalidAccount
+    );
+
+    let data = program_data.data.borrow();
+    guard!(
+        data.len() >= AUTHORITY_OFFSET + 1 + 32,
+        ElusivError::InvalidAccount
+    );
+    guard!(
+        data[..4] == PROGRAM_DATA_VARIANT.to_le_bytes(),
+        ElusivError::InvalidAccount
+    );
+    guard!(data[AUTHORITY_OFFSET] == 1, ElusivError::InvalidAccount);
+    guard!(
+        data[AUTHORITY_OFFSET + 1..AUTHORITY_OFFSET + 1 + 32] == signer.key.to_bytes(),
+        ElusivError::InvalidAccount
+    );
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -350,6 +465,114 @@ mod tests {
         );
     }
 
+    fn wrapped_sol_account_data(owner: Pubkey, mint: Pubkey) -> Vec<u8> {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint,
+            owner,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: solana_program::program_option::COption::Some(0),
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        data
+    }
+
+    #[test]
+    fn test_unwrap_lamports() {
+        let owner_pk = Pubkey::new_unique();
+        test_account_info!(owner, 0, owner_pk);
+        account_info!(token_program, spl_token::id(), vec![]);
+        account_info!(invalid_token_program, system_program::id(), vec![]);
+
+        account_info!(
+            token_account,
+            Pubkey::new_unique(),
+            wrapped_sol_account_data(*owner.key, spl_token::native_mint::ID),
+            spl_token::id(),
+            false
+        );
+        account_info!(
+            invalid_owner_token_account,
+            Pubkey::new_unique(),
+            wrapped_sol_account_data(Pubkey::new_unique(), spl_token::native_mint::ID),
+            spl_token::id(),
+            false
+        );
+        account_info!(
+            invalid_mint_token_account,
+            Pubkey::new_unique(),
+            wrapped_sol_account_data(*owner.key, TOKENS[1].mint),
+            spl_token::id(),
+            false
+        );
+        account_info!(
+            invalid_program_token_account,
+            Pubkey::new_unique(),
+            wrapped_sol_account_data(*owner.key, spl_token::native_mint::ID),
+            system_program::id(),
+            false
+        );
+
+        assert_matches!(
+            unwrap_lamports(&owner, &token_account, &invalid_token_program),
+            Err(_)
+        );
+        assert_matches!(
+            unwrap_lamports(&owner, &invalid_owner_token_account, &token_program),
+            Err(_)
+        );
+        assert_matches!(
+            unwrap_lamports(&owner, &invalid_mint_token_account, &token_program),
+            Err(_)
+        );
+        assert_matches!(
+            unwrap_lamports(&owner, &invalid_program_token_account, &token_program),
+            Err(_)
+        );
+
+        assert_matches!(
+            unwrap_lamports(&owner, &token_account, &token_program),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_wrap_lamports_from_pda() {
+        test_account_info!(pda, 0);
+        test_account_info!(token_account, 0, spl_token::id());
+        test_account_info!(invalid_token_account, 0);
+        account_info!(token_program, spl_token::id(), vec![]);
+        account_info!(invalid_token_program, system_program::id(), vec![]);
+
+        assert_matches!(
+            wrap_lamports_from_pda(&pda, &token_account, &invalid_token_program, 100),
+            Err(_)
+        );
+        assert_matches!(
+            wrap_lamports_from_pda(&pda, &invalid_token_account, &token_program, 100),
+            Err(_)
+        );
+
+        let pda_lamports = pda.lamports();
+        let token_account_lamports = token_account.lamports();
+        assert_matches!(
+            wrap_lamports_from_pda(&pda, &token_account, &token_program, 100),
+            Ok(())
+        );
+        assert_eq!(pda.lamports(), pda_lamports - 100);
+        assert_eq!(token_account.lamports(), token_account_lamports + 100);
+    }
+
+    #[test]
+    fn test_associated_token_account_mint() {
+        assert_eq!(
+            associated_token_account_mint(0).unwrap(),
+            spl_token::native_mint::ID
+        );
+        assert_eq!(associated_token_account_mint(1).unwrap(), TOKENS[1].mint);
+    }
+
     #[test]
     fn test_open_pda_account_with_offset() {
         test_account_info!(payer, 0);
@@ -557,4 +780,64 @@ mod tests {
             Err(_)
         );
     }
+
+    fn program_data(upgrade_authority: Option<Pubkey>) -> Vec<u8> {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend(0u64.to_le_bytes());
+        match upgrade_authority {
+            Some(authority) => {
+                data.push(1);
+                data.extend(authority.to_bytes());
+            }
+            None => data.extend([0; 33]),
+        }
+        data
+    }
+
+    #[test]
+    fn test_verify_upgrade_authority() {
+        let authority = Pubkey::new_unique();
+        let loader = bpf_loader_upgradeable::id();
+
+        account_info!(signer, authority, vec![], system_program::id(), true);
+        account_info!(non_signer, authority, vec![], system_program::id(), false);
+        account_info!(
+            other_signer,
+            Pubkey::new_unique(),
+            vec![],
+            system_program::id(),
+            true
+        );
+
+        account_info!(
+            p,
+            program_data_address(),
+            program_data(Some(authority)),
+            loader,
+            false
+        );
+        assert_matches!(verify_upgrade_authority(&signer, &p), Ok(()));
+        assert_matches!(verify_upgrade_authority(&non_signer, &p), Err(_));
+        assert_matches!(verify_upgrade_authority(&other_signer, &p), Err(_));
+
+        // Invalid owner
+        account_info!(
+            p,
+            program_data_address(),
+            program_data(Some(authority)),
+            crate::id(),
+            false
+        );
+        assert_matches!(verify_upgrade_authority(&signer, &p), Err(_));
+
+        // Immutable program
+        account_info!(p, program_data_address(), program_data(None), loader, false);
+        assert_matches!(verify_upgrade_authority(&signer, &p), Err(_));
+
+        // Invalid variant
+        let mut data = program_data(Some(authority));
+        data[0] = 2;
+        account_info!(p, program_data_address(), data, loader, false);
+        assert_matches!(verify_upgrade_authority(&signer, &p), Err(_));
+    }
 }
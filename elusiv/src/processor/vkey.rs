@@ -1,19 +1,20 @@
 use crate::{
-    error::ElusivError, processor::setup_child_account, proof::vkey::VerifyingKey,
-    state::vkey::VKeyAccount,
+    error::ElusivError,
+    processor::{setup_child_account, utils::current_timestamp},
+    proof::plonk::vkey::PlonkVerifyingKey,
+    proof::vkey::VerifyingKey,
+    state::vkey::{ProofSystem, VKeyAccount, VKeyAction, MAX_VKEY_SIGNERS, VKEY_UPDATE_TIMELOCK},
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use elusiv_types::{BorshSerDeSized, ChildAccountConfig, ElusivOption, ParentAccount};
-use elusiv_utils::{
-    guard, open_pda_account_with_offset, pda_account, transfer_with_system_program,
-};
+use elusiv_utils::{close_account, guard, open_pda_account_with_offset, pda_account};
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
     pubkey::Pubkey,
 };
 
 pub const VKEY_ACCOUNT_DATA_PACKET_SIZE: usize = 964;
-const MAX_NUMBER_OF_VKEYS: u32 = 1;
+pub const MAX_NUMBER_OF_VKEYS: u32 = 256;
 
 /// A binary data packet containing [`VKEY_ACCOUNT_DATA_PACKET_SIZE`] bytes
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -30,6 +31,7 @@ pub fn create_vkey_account<'a>(
 
     vkey_id: u32,
     public_inputs_count: u32,
+    proof_system: ProofSystem,
     authority: ElusivOption<Pubkey>,
 ) -> ProgramResult {
     guard!(
@@ -42,6 +44,7 @@ pub fn create_vkey_account<'a>(
     pda_account!(mut vkey_account, VKeyAccount, vkey_account);
     vkey_account.set_authority(&authority);
     vkey_account.set_public_inputs_count(&public_inputs_count);
+    vkey_account.set_proof_system(&proof_system);
 
     Ok(())
 }
@@ -60,9 +63,7 @@ pub fn create_new_vkey_version(
         ElusivError::InvalidAccountState
     );
 
-    let public_inputs_count = vkey_account.get_public_inputs_count() as usize;
-    let binary_data_account_size =
-        VerifyingKey::source_size(public_inputs_count) + ChildAccountConfig::SIZE;
+    let binary_data_account_size = vkey_source_size(vkey_account) + ChildAccountConfig::SIZE;
 
     setup_child_account(
         vkey_account,
@@ -72,6 +73,8 @@ pub fn create_new_vkey_version(
         Some(binary_data_account_size),
     )?;
 
+    reset_vkey_version_approvals(vkey_account);
+
     Ok(())
 }
 
@@ -85,8 +88,7 @@ pub fn set_vkey_data(
 ) -> ProgramResult {
     verify_vkey_modification(signer, vkey_account)?;
 
-    let public_inputs_count = vkey_account.get_public_inputs_count();
-    let len = VerifyingKey::source_size(public_inputs_count as usize);
+    let len = vkey_source_size(vkey_account);
     let start = data_position as usize * VKEY_ACCOUNT_DATA_PACKET_SIZE;
     let end = start + VKEY_ACCOUNT_DATA_PACKET_SIZE;
     let cutoff = if end > len { end - len } else { 0 };
@@ -98,6 +100,8 @@ pub fn set_vkey_data(
             .copy_from_slice(&packet.0[..VKEY_ACCOUNT_DATA_PACKET_SIZE - cutoff])
     })?;
 
+    reset_vkey_version_approvals(vkey_account);
+
     Ok(())
 }
 
@@ -106,9 +110,22 @@ pub fn update_vkey_version<'a>(
     signer: &AccountInfo<'a>,
     vkey_account: &mut VKeyAccount,
     old_vkey_binary_data_account: &AccountInfo<'a>,
-    system_program: &AccountInfo<'a>,
 
     _vkey_id: u32,
+) -> ProgramResult {
+    update_vkey_version_at(
+        signer,
+        vkey_account,
+        old_vkey_binary_data_account,
+        current_timestamp()?,
+    )
+}
+
+fn update_vkey_version_at<'a>(
+    signer: &AccountInfo<'a>,
+    vkey_account: &mut VKeyAccount,
+    old_vkey_binary_data_account: &AccountInfo<'a>,
+    timestamp: u64,
 ) -> ProgramResult {
     verify_vkey_modification(signer, vkey_account)?;
 
@@ -117,24 +134,21 @@ pub fn update_vkey_version<'a>(
         ElusivError::InvalidAccountState
     );
 
-    // Close old vkey account
-    if let Some(old_vkey_account) = vkey_account.get_child_pubkey(0) {
-        guard!(
-            old_vkey_account == *old_vkey_binary_data_account.key,
-            ElusivError::InvalidAccount
-        );
+    verify_vkey_action(vkey_account, &VKeyAction::ActivateVersion, timestamp)?;
 
-        transfer_with_system_program(
-            old_vkey_binary_data_account,
-            signer,
-            system_program,
-            old_vkey_binary_data_account.lamports(),
-        )?;
+    // Close the previous version (only possible once it is not used by any verification anymore)
+    if vkey_account.get_child_pubkey(2).is_some() {
+        close_previous_version(signer, vkey_account, old_vkey_binary_data_account)?;
     }
 
+    // The active version stays readable as the previous version
+    vkey_account.set_child_pubkey(2, vkey_account.get_child_pubkey(0).into());
+    vkey_account.set_previous_verifications(&vkey_account.get_active_verifications());
+
     // Swap child accounts
     vkey_account.set_child_pubkey(0, vkey_account.get_child_pubkey(1).into());
     vkey_account.set_child_pubkey(1, None.into());
+    vkey_account.set_active_verifications(&0);
 
     // Inc version
     let version = vkey_account.get_version();
@@ -144,6 +158,126 @@ pub fn update_vkey_version<'a>(
             .ok_or(ElusivError::InvalidAccountState)?,
     );
 
+    reset_vkey_approvals(vkey_account);
+
+    Ok(())
+}
+
+/// Closes the previous version of a [`VKeyAccount`]
+///
+/// # Notes
+///
+/// Requires all verifications using the previous version to be closed.
+/// Also possible for a frozen [`VKeyAccount`].
+pub fn close_previous_vkey_version<'a>(
+    signer: &AccountInfo<'a>,
+    vkey_account: &mut VKeyAccount,
+    previous_vkey_binary_data_account: &AccountInfo<'a>,
+
+    _vkey_id: u32,
+) -> ProgramResult {
+    verify_vkey_authority(signer, vkey_account)?;
+    close_previous_version(signer, vkey_account, previous_vkey_binary_data_account)
+}
+
+/// Registers the signers and threshold of the M-of-N approval scheme for new versions of a [`VKeyAccount`]
+///
+/// # Notes
+///
+/// Once registered, the signers cannot be changed anymore.
+pub fn set_vkey_signers(
+    signer: &AccountInfo,
+    vkey_account: &mut VKeyAccount,
+
+    _vkey_id: u32,
+    signers: [ElusivOption<Pubkey>; MAX_VKEY_SIGNERS],
+    threshold: u8,
+) -> ProgramResult {
+    verify_vkey_modification(signer, vkey_account)?;
+
+    guard!(
+        vkey_account.get_threshold() == 0,
+        ElusivError::InvalidAccountState
+    );
+
+    let keys: Vec<Pubkey> = signers.iter().filter_map(|s| s.option()).collect();
+    guard!(
+        threshold > 0 && threshold as usize <= keys.len(),
+        ElusivError::InvalidInstructionData
+    );
+    for (i, key) in keys.iter().enumerate() {
+        guard!(!keys[..i].contains(key), ElusivError::DuplicateValue);
+    }
+
+    for (i, s) in signers.iter().enumerate() {
+        vkey_account.set_signers(i, s);
+    }
+    vkey_account.set_threshold(&threshold);
+    reset_vkey_approvals(vkey_account);
+
+    Ok(())
+}
+
+/// Approves a [`VKeyAction`] on a [`VKeyAccount`]
+///
+/// # Notes
+///
+/// - Once `threshold` signers have approved, the action can be executed after [`VKEY_UPDATE_TIMELOCK`].
+/// - Approving a different action than the pending one resets all approvals.
+pub fn approve_vkey_action(
+    signer: &AccountInfo,
+    vkey_account: &mut VKeyAccount,
+
+    _vkey_id: u32,
+    action: VKeyAction,
+) -> ProgramResult {
+    approve_vkey_action_at(signer, vkey_account, action, current_timestamp()?)
+}
+
+fn approve_vkey_action_at(
+    signer: &AccountInfo,
+    vkey_account: &mut VKeyAccount,
+    action: VKeyAction,
+    timestamp: u64,
+) -> ProgramResult {
+    guard!(
+        !vkey_account.get_is_frozen(),
+        ElusivError::InvalidAccountState
+    );
+    guard!(
+        vkey_account.get_threshold() > 0,
+        ElusivError::InvalidAccountState
+    );
+    if let VKeyAction::ActivateVersion = action {
+        guard!(
+            vkey_account.get_child_pubkey(1).is_some(),
+            ElusivError::InvalidAccountState
+        );
+    }
+
+    let key = signer
+        .signer_key()
+        .ok_or(ProgramError::MissingRequiredSignature)?;
+    let index = (0..MAX_VKEY_SIGNERS)
+        .find(|&i| vkey_account.get_signers(i).option() == Some(*key))
+        .ok_or(ElusivError::InvalidAccount)?;
+
+    if vkey_account.get_pending_action() != action {
+        reset_vkey_approvals(vkey_account);
+        vkey_account.set_pending_action(&action);
+    }
+
+    vkey_account.set_approvals(index, &true);
+
+    if vkey_approvals_count(vkey_account) >= vkey_account.get_threshold() as usize
+        && vkey_account.get_unlock_timestamp().option().is_none()
+    {
+        let unlock_timestamp = timestamp
+            .checked_add(VKEY_UPDATE_TIMELOCK)
+            .ok_or(ElusivError::InvalidAccountState)?;
+        vkey_account.set_unlock_timestamp(&Some(unlock_timestamp).into());
+    }
+
     Ok(())
 }
 
@@ -153,9 +287,20 @@ pub fn freeze_vkey(
     vkey_account: &mut VKeyAccount,
 
     _vkey_id: u32,
+) -> ProgramResult {
+    freeze_vkey_at(signer, vkey_account, current_timestamp()?)
+}
+
+fn freeze_vkey_at(
+    signer: &AccountInfo,
+    vkey_account: &mut VKeyAccount,
+    timestamp: u64,
 ) -> ProgramResult {
     verify_vkey_modification(signer, vkey_account)?;
+    verify_vkey_action(vkey_account, &VKeyAction::Freeze, timestamp)?;
+
     vkey_account.set_is_frozen(&true);
+    reset_vkey_approvals(vkey_account);
 
     Ok(())
 }
@@ -167,19 +312,171 @@ pub fn change_vkey_authority(
 
     _vkey_id: u32,
     authority: Pubkey,
+) -> ProgramResult {
+    change_vkey_authority_at(signer, vkey_account, authority, current_timestamp()?)
+}
+
+fn change_vkey_authority_at(
+    signer: &AccountInfo,
+    vkey_account: &mut VKeyAccount,
+    authority: Pubkey,
+    timestamp: u64,
 ) -> ProgramResult {
     verify_vkey_modification(signer, vkey_account)?;
+    verify_vkey_action(
+        vkey_account,
+        &VKeyAction::ChangeAuthority { authority },
+        timestamp,
+    )?;
+
     vkey_account.set_authority(&Some(authority).into());
+    reset_vkey_approvals(vkey_account);
+
+    Ok(())
+}
+
+/// The child-account index of the binary data of a specific version of a [`VKeyAccount`]
+pub fn vkey_version_child_index(
+    vkey_account: &VKeyAccount,
+    version: u32,
+) -> Result<usize, ProgramError> {
+    if version == vkey_account.get_version() {
+        return Ok(0);
+    }
+
+    guard!(
+        version.checked_add(1) == Some(vkey_account.get_version())
+            && vkey_account.get_child_pubkey(2).is_some(),
+        ElusivError::InvalidAccountState
+    );
+
+    Ok(2)
+}
+
+/// Registers a new verification using the active version of a [`VKeyAccount`]
+///
+/// # Notes
+///
+/// Returns the active version.
+pub fn acquire_vkey_version(vkey_account: &mut VKeyAccount) -> Result<u32, ProgramError> {
+    let verifications = vkey_account
+        .get_active_verifications()
+        .checked_add(1)
+        .ok_or(ElusivError::InvalidAccountState)?;
+    vkey_account.set_active_verifications(&verifications);
+
+    Ok(vkey_account.get_version())
+}
+
+/// Releases a verification using the `version` of a [`VKeyAccount`]
+pub fn release_vkey_version(vkey_account: &mut VKeyAccount, version: u32) -> ProgramResult {
+    match vkey_version_child_index(vkey_account, version)? {
+        0 => vkey_account
+            .set_active_verifications(&vkey_account.get_active_verifications().saturating_sub(1)),
+        _ => vkey_account.set_previous_verifications(
+            &vkey_account.get_previous_verifications().saturating_sub(1),
+        ),
+    }
+
+    Ok(())
+}
+
+fn close_previous_version<'a>(
+    beneficiary: &AccountInfo<'a>,
+    vkey_account: &mut VKeyAccount,
+    previous_vkey_binary_data_account: &AccountInfo<'a>,
+) -> ProgramResult {
+    let previous = vkey_account
+        .get_child_pubkey(2)
+        .ok_or(ElusivError::InvalidAccountState)?;
+    guard!(
+        previous == *previous_vkey_binary_data_account.key,
+        ElusivError::InvalidAccount
+    );
+    guard!(
+        vkey_account.get_previous_verifications() == 0,
+        ElusivError::InvalidAccountState
+    );
+
+    close_account(beneficiary, previous_vkey_binary_data_account)?;
+    vkey_account.set_child_pubkey(2, None.into());
+
+    Ok(())
+}
+
+fn vkey_approvals_count(vkey_account: &VKeyAccount) -> usize {
+    (0..MAX_VKEY_SIGNERS)
+        .filter(|&i| vkey_account.get_approvals(i))
+        .count()
+}
+
+/// A [`VKeyAction`] is unlocked once the threshold is reached and the timelock has passed
+fn is_vkey_action_unlocked(
+    vkey_account: &VKeyAccount,
+    action: &VKeyAction,
+    timestamp: u64,
+) -> bool {
+    if vkey_account.get_pending_action() != *action
+        || vkey_approvals_count(vkey_account) < vkey_account.get_threshold() as usize
+    {
+        return false;
+    }
+
+    match vkey_account.get_unlock_timestamp().option() {
+        Some(unlock_timestamp) => timestamp >= unlock_timestamp,
+        None => false,
+    }
+}
+
+/// Actions require the approval of the signers once a threshold is registered
+fn verify_vkey_action(
+    vkey_account: &VKeyAccount,
+    action: &VKeyAction,
+    timestamp: u64,
+) -> ProgramResult {
+    if vkey_account.get_threshold() > 0 {
+        guard!(
+            is_vkey_action_unlocked(vkey_account, action, timestamp),
+            ElusivError::InvalidAccountState
+        );
+    }
 
     Ok(())
 }
 
+fn reset_vkey_approvals(vkey_account: &mut VKeyAccount) {
+    for i in 0..MAX_VKEY_SIGNERS {
+        vkey_account.set_approvals(i, &false);
+    }
+    vkey_account.set_unlock_timestamp(&None.into());
+}
+
+/// Approvals of the pending version only apply to the data present at the time of approval
+fn reset_vkey_version_approvals(vkey_account: &mut VKeyAccount) {
+    if vkey_account.get_pending_action() == VKeyAction::ActivateVersion {
+        reset_vkey_approvals(vkey_account);
+    }
+}
+
+/// The size of the vkey source depends on the [`ProofSystem`] of the [`VKeyAccount`]
+fn vkey_source_size(vkey_account: &VKeyAccount) -> usize {
+    let public_inputs_count = vkey_account.get_public_inputs_count() as usize;
+    match vkey_account.get_proof_system() {
+        ProofSystem::Groth16 => VerifyingKey::source_size(public_inputs_count),
+        ProofSystem::Plonk => PlonkVerifyingKey::source_size(public_inputs_count),
+    }
+}
+
 fn verify_vkey_modification(signer: &AccountInfo, vkey_account: &VKeyAccount) -> ProgramResult {
     guard!(
         !vkey_account.get_is_frozen(),
         ElusivError::InvalidAccountState
     );
 
+    verify_vkey_authority(signer, vkey_account)
+}
+
+fn verify_vkey_authority(signer: &AccountInfo, vkey_account: &VKeyAccount) -> ProgramResult {
     if let Some(authority) = vkey_account.get_authority().option() {
         guard!(
             *signer
@@ -320,7 +617,7 @@ mod test {
         vkey_account.set_authority(&Some(*signer.key).into());
 
         assert_matches!(
-            update_vkey_version(&signer, &mut vkey_account, &acc, &acc, 0),
+            update_vkey_version_at(&signer, &mut vkey_account, &acc, 0),
             Err(_)
         );
 
@@ -328,7 +625,7 @@ mod test {
         vkey_account.set_child_pubkey(1, Some(*vkey_binary_data_account.key).into());
 
         assert_matches!(
-            update_vkey_version(&signer, &mut vkey_account, &acc, &acc, 0),
+            update_vkey_version_at(&signer, &mut vkey_account, &acc, 0),
             Ok(())
         );
 
@@ -338,6 +635,294 @@ mod test {
             *vkey_binary_data_account.key
         );
         assert!(vkey_account.get_child_pubkey(1).is_none());
+        assert!(vkey_account.get_child_pubkey(2).is_none());
+
+        // The active version becomes the previous version
+        test_account_info!(new_vkey_binary_data_account);
+        vkey_account.set_child_pubkey(1, Some(*new_vkey_binary_data_account.key).into());
+        vkey_account.set_active_verifications(&2);
+
+        assert_matches!(
+            update_vkey_version_at(&signer, &mut vkey_account, &acc, 0),
+            Ok(())
+        );
+
+        assert_eq!(vkey_account.get_version(), 2);
+        assert_eq!(
+            vkey_account.get_child_pubkey(2).unwrap(),
+            *vkey_binary_data_account.key
+        );
+        assert_eq!(vkey_account.get_previous_verifications(), 2);
+        assert_eq!(vkey_account.get_active_verifications(), 0);
+
+        // Previous version still in use
+        test_account_info!(next_vkey_binary_data_account);
+        vkey_account.set_child_pubkey(1, Some(*next_vkey_binary_data_account.key).into());
+        assert_matches!(
+            update_vkey_version_at(&signer, &mut vkey_account, &vkey_binary_data_account, 0),
+            Err(_)
+        );
+
+        // Invalid previous binary data account
+        vkey_account.set_previous_verifications(&0);
+        assert_matches!(
+            update_vkey_version_at(&signer, &mut vkey_account, &acc, 0),
+            Err(_)
+        );
+
+        assert_matches!(
+            update_vkey_version_at(&signer, &mut vkey_account, &vkey_binary_data_account, 0),
+            Ok(())
+        );
+        assert_eq!(vkey_binary_data_account.lamports(), 0);
+        assert_eq!(
+            vkey_account.get_child_pubkey(2).unwrap(),
+            *new_vkey_binary_data_account.key
+        );
+    }
+
+    #[test]
+    fn test_close_previous_vkey_version() {
+        vkey_account!(vkey_account, TestVKey);
+        signing_test_account_info!(signer);
+        signing_test_account_info!(invalid_signer);
+        test_account_info!(acc);
+        test_account_info!(previous_vkey_binary_data_account);
+
+        vkey_account.set_authority(&Some(*signer.key).into());
+
+        // No previous version
+        assert_matches!(
+            close_previous_vkey_version(
+                &signer,
+                &mut vkey_account,
+                &previous_vkey_binary_data_account,
+                0
+            ),
+            Err(_)
+        );
+
+        vkey_account.set_child_pubkey(2, Some(*previous_vkey_binary_data_account.key).into());
+        vkey_account.set_previous_verifications(&1);
+
+        // Previous version still in use
+        assert_matches!(
+            close_previous_vkey_version(
+                &signer,
+                &mut vkey_account,
+                &previous_vkey_binary_data_account,
+                0
+            ),
+            Err(_)
+        );
+
+        vkey_account.set_previous_verifications(&0);
+
+        // Invalid signer
+        assert_matches!(
+            close_previous_vkey_version(
+                &invalid_signer,
+                &mut vkey_account,
+                &previous_vkey_binary_data_account,
+                0
+            ),
+            Err(_)
+        );
+
+        // Invalid binary data account
+        assert_matches!(
+            close_previous_vkey_version(&signer, &mut vkey_account, &acc, 0),
+            Err(_)
+        );
+
+        // Frozen accounts can still be cleaned up
+        vkey_account.set_is_frozen(&true);
+        assert_matches!(
+            close_previous_vkey_version(
+                &signer,
+                &mut vkey_account,
+                &previous_vkey_binary_data_account,
+                0
+            ),
+            Ok(())
+        );
+        assert!(vkey_account.get_child_pubkey(2).is_none());
+        assert_eq!(previous_vkey_binary_data_account.lamports(), 0);
+    }
+
+    #[test]
+    fn test_vkey_version_references() {
+        vkey_account!(vkey_account, TestVKey);
+        vkey_account.set_version(&3);
+
+        assert_eq!(vkey_version_child_index(&vkey_account, 3), Ok(0));
+        assert_matches!(vkey_version_child_index(&vkey_account, 2), Err(_));
+        assert_matches!(vkey_version_child_index(&vkey_account, 4), Err(_));
+
+        assert_eq!(acquire_vkey_version(&mut vkey_account), Ok(3));
+        assert_eq!(acquire_vkey_version(&mut vkey_account), Ok(3));
+        assert_eq!(vkey_account.get_active_verifications(), 2);
+
+        // Previous version
+        vkey_account.set_child_pubkey(2, Some(Pubkey::new_unique()).into());
+        vkey_account.set_previous_verifications(&1);
+        assert_eq!(vkey_version_child_index(&vkey_account, 2), Ok(2));
+        assert_matches!(vkey_version_child_index(&vkey_account, 1), Err(_));
+
+        release_vkey_version(&mut vkey_account, 2).unwrap();
+        assert_eq!(vkey_account.get_previous_verifications(), 0);
+        assert_eq!(vkey_account.get_active_verifications(), 2);
+
+        release_vkey_version(&mut vkey_account, 3).unwrap();
+        assert_eq!(vkey_account.get_active_verifications(), 1);
+
+        assert_matches!(release_vkey_version(&mut vkey_account, 1), Err(_));
+    }
+
+    #[test]
+    fn test_set_vkey_signers() {
+        vkey_account!(vkey_account, TestVKey);
+        signing_test_account_info!(signer);
+
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let mut signers = [ElusivOption::None; MAX_VKEY_SIGNERS];
+        signers[0] = Some(a).into();
+        signers[2] = Some(b).into();
+
+        // Invalid threshold
+        for threshold in [0, 3] {
+            assert_matches!(
+                set_vkey_signers(&signer, &mut vkey_account, 0, signers, threshold),
+                Err(_)
+            );
+        }
+
+        // Duplicate signer
+        let mut duplicate_signers = signers;
+        duplicate_signers[1] = Some(a).into();
+        assert_matches!(
+            set_vkey_signers(&signer, &mut vkey_account, 0, duplicate_signers, 2),
+            Err(_)
+        );
+
+        assert_matches!(
+            set_vkey_signers(&signer, &mut vkey_account, 0, signers, 2),
+            Ok(())
+        );
+        assert_eq!(vkey_account.get_threshold(), 2);
+        assert_eq!(vkey_account.get_signers(2).option(), Some(b));
+
+        // Signers cannot be changed
+        assert_matches!(
+            set_vkey_signers(&signer, &mut vkey_account, 0, signers, 1),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_approve_vkey_action() {
+        vkey_account!(vkey_account, TestVKey);
+        signing_test_account_info!(authority);
+        signing_test_account_info!(a);
+        signing_test_account_info!(b);
+        signing_test_account_info!(c);
+        signing_test_account_info!(invalid_signer);
+        test_account_info!(non_signer);
+
+        vkey_account.set_child_pubkey(1, Some(Pubkey::new_unique()).into());
+
+        // No signers registered
+        assert_matches!(
+            approve_vkey_action_at(&a, &mut vkey_account, VKeyAction::ActivateVersion, 0),
+            Err(_)
+        );
+
+        let mut signers = [ElusivOption::None; MAX_VKEY_SIGNERS];
+        for (i, s) in [&a, &b, &c].iter().enumerate() {
+            signers[i] = Some(*s.key).into();
+        }
+        set_vkey_signers(&authority, &mut vkey_account, 0, signers, 2).unwrap();
+
+        assert_matches!(
+            approve_vkey_action_at(
+                &invalid_signer,
+                &mut vkey_account,
+                VKeyAction::ActivateVersion,
+                0
+            ),
+            Err(_)
+        );
+        vkey_account.set_signers(3, &Some(*non_signer.key).into());
+        assert_matches!(
+            approve_vkey_action_at(
+                &non_signer,
+                &mut vkey_account,
+                VKeyAction::ActivateVersion,
+                0
+            ),
+            Err(_)
+        );
+
+        // Threshold not reached
+        approve_vkey_action_at(&a, &mut vkey_account, VKeyAction::ActivateVersion, 10).unwrap();
+        approve_vkey_action_at(&a, &mut vkey_account, VKeyAction::ActivateVersion, 10).unwrap();
+        assert!(vkey_account.get_unlock_timestamp().option().is_none());
+        assert!(!is_vkey_action_unlocked(
+            &vkey_account,
+            &VKeyAction::ActivateVersion,
+            u64::MAX
+        ));
+
+        // Threshold reached
+        approve_vkey_action_at(&c, &mut vkey_account, VKeyAction::ActivateVersion, 100).unwrap();
+        let unlock_timestamp = 100 + VKEY_UPDATE_TIMELOCK;
+        assert_eq!(
+            vkey_account.get_unlock_timestamp().option(),
+            Some(unlock_timestamp)
+        );
+
+        // Additional approvals do not extend the timelock
+        approve_vkey_action_at(&b, &mut vkey_account, VKeyAction::ActivateVersion, 200).unwrap();
+        assert_eq!(
+            vkey_account.get_unlock_timestamp().option(),
+            Some(unlock_timestamp)
+        );
+
+        assert!(!is_vkey_action_unlocked(
+            &vkey_account,
+            &VKeyAction::ActivateVersion,
+            unlock_timestamp - 1
+        ));
+        assert!(is_vkey_action_unlocked(
+            &vkey_account,
+            &VKeyAction::ActivateVersion,
+            unlock_timestamp
+        ));
+
+        // Modifying the data resets all approvals
+        set_vkey_data(
+            &authority,
+            &mut vkey_account,
+            0,
+            0,
+            VKeyAccountDataPacket(vec![0; VKEY_ACCOUNT_DATA_PACKET_SIZE]),
+        )
+        .unwrap();
+        assert!(!vkey_account.get_approvals(0));
+        assert!(vkey_account.get_unlock_timestamp().option().is_none());
+        assert!(!is_vkey_action_unlocked(
+            &vkey_account,
+            &VKeyAction::ActivateVersion,
+            u64::MAX
+        ));
+
+        // No pending version
+        vkey_account.set_child_pubkey(1, None.into());
+        assert_matches!(
+            approve_vkey_action_at(&a, &mut vkey_account, VKeyAction::ActivateVersion, 0),
+            Err(_)
+        );
     }
 
     #[test]
@@ -352,10 +937,10 @@ mod test {
             })
             .unwrap();
 
-        freeze_vkey(&signer, &mut vkey_account, 0).unwrap();
+        freeze_vkey_at(&signer, &mut vkey_account, 0).unwrap();
 
         assert!(vkey_account.get_is_frozen());
-        assert_matches!(freeze_vkey(&signer, &mut vkey_account, 0), Err(_));
+        assert_matches!(freeze_vkey_at(&signer, &mut vkey_account, 0), Err(_));
     }
 
     #[test]
@@ -365,24 +950,110 @@ mod test {
         signing_test_account_info!(signer2);
 
         assert_matches!(
-            change_vkey_authority(&signer, &mut vkey_account, 0, *signer.key),
+            change_vkey_authority_at(&signer, &mut vkey_account, *signer.key, 0),
             Ok(())
         );
 
         assert_matches!(
-            change_vkey_authority(&signer2, &mut vkey_account, 0, *signer.key),
+            change_vkey_authority_at(&signer2, &mut vkey_account, *signer.key, 0),
             Err(_)
         );
 
         assert_matches!(
-            change_vkey_authority(&signer, &mut vkey_account, 0, *signer2.key),
+            change_vkey_authority_at(&signer, &mut vkey_account, *signer2.key, 0),
             Ok(())
         );
 
         assert_matches!(
-            change_vkey_authority(&signer, &mut vkey_account, 0, *signer.key),
+            change_vkey_authority_at(&signer, &mut vkey_account, *signer.key, 0),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_vkey_actions_require_approval() {
+        vkey_account!(vkey_account, TestVKey);
+        signing_test_account_info!(authority);
+        signing_test_account_info!(a);
+        signing_test_account_info!(b);
+
+        vkey_account.set_authority(&Some(*authority.key).into());
+
+        let mut signers = [ElusivOption::None; MAX_VKEY_SIGNERS];
+        signers[0] = Some(*a.key).into();
+        signers[1] = Some(*b.key).into();
+        set_vkey_signers(&authority, &mut vkey_account, 0, signers, 2).unwrap();
+
+        let new_authority = Pubkey::new_unique();
+        let change_authority = VKeyAction::ChangeAuthority {
+            authority: new_authority,
+        };
+
+        // The authority alone cannot execute actions
+        assert_matches!(
+            change_vkey_authority_at(&authority, &mut vkey_account, new_authority, u64::MAX),
+            Err(_)
+        );
+        assert_matches!(
+            freeze_vkey_at(&authority, &mut vkey_account, u64::MAX),
+            Err(_)
+        );
+
+        approve_vkey_action_at(&a, &mut vkey_account, change_authority, 0).unwrap();
+        approve_vkey_action_at(&b, &mut vkey_account, change_authority, 0).unwrap();
+
+        // Approvals only apply to the approved action
+        assert_matches!(
+            freeze_vkey_at(&authority, &mut vkey_account, u64::MAX),
+            Err(_)
+        );
+        assert_matches!(
+            change_vkey_authority_at(&authority, &mut vkey_account, *a.key, u64::MAX),
+            Err(_)
+        );
+
+        // Timelock
+        assert_matches!(
+            change_vkey_authority_at(
+                &authority,
+                &mut vkey_account,
+                new_authority,
+                VKEY_UPDATE_TIMELOCK - 1
+            ),
+            Err(_)
+        );
+
+        // Approving a different action resets all approvals
+        approve_vkey_action_at(&a, &mut vkey_account, VKeyAction::Freeze, 0).unwrap();
+        assert_eq!(vkey_account.get_pending_action(), VKeyAction::Freeze);
+        assert!(!vkey_account.get_approvals(1));
+        assert_matches!(
+            freeze_vkey_at(&authority, &mut vkey_account, u64::MAX),
             Err(_)
         );
+
+        approve_vkey_action_at(&b, &mut vkey_account, VKeyAction::Freeze, 10).unwrap();
+        assert_matches!(
+            freeze_vkey_at(&authority, &mut vkey_account, 10 + VKEY_UPDATE_TIMELOCK),
+            Ok(())
+        );
+        assert!(vkey_account.get_is_frozen());
+        assert!(!vkey_account.get_approvals(0));
+
+        // Change of authority
+        vkey_account.set_is_frozen(&false);
+        approve_vkey_action_at(&a, &mut vkey_account, change_authority, 0).unwrap();
+        approve_vkey_action_at(&b, &mut vkey_account, change_authority, 0).unwrap();
+        assert_matches!(
+            change_vkey_authority_at(
+                &authority,
+                &mut vkey_account,
+                new_authority,
+                VKEY_UPDATE_TIMELOCK
+            ),
+            Ok(())
+        );
+        assert_eq!(vkey_account.get_authority().option(), Some(new_authority));
     }
 
     #[test]
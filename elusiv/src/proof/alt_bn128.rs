@@ -0,0 +1,584 @@
+//! Groth16 proof verification using the alt_bn128 syscalls (https://eips.ethereum.org/EIPS/eip-197)
+//! Instead of the partial computations in `verifier`, the input preparation and the pairing check are each performed in a single instruction.
+//! Only the G2 subgroup check of the proof still uses the partial computation of `verifier`.
+//! On non-BPF targets the syscalls are replaced by native shims, which allows for testing both backends.
+
+use super::verifier::{g2_subgroup_check, G2SubgroupCheck, VerificationStep};
+use super::vkey::VerifyingKey;
+use crate::error::ElusivError::{
+    self, ComputationIsAlreadyFinished, CouldNotProcessProof, InvalidAccountState,
+};
+use crate::fields::{u64_limb, G1A};
+use crate::state::proof::{VerificationAccount, VerificationState};
+use crate::types::U256;
+use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::AffineCurve;
+use ark_ff::{BigInteger, BigInteger256, PrimeField, Zero};
+use elusiv_computation::PartialComputation;
+use elusiv_utils::guard;
+
+pub const ALT_BN128_ADDITION_OPERATION: u64 = 0;
+pub const ALT_BN128_MULTIPLICATION_OPERATION: u64 = 2;
+pub const ALT_BN128_PAIRING_OPERATION: u64 = 3;
+
+pub const ALT_BN128_FIELD_SIZE: usize = 32;
+pub const ALT_BN128_G1_SIZE: usize = ALT_BN128_FIELD_SIZE * 2;
+pub const ALT_BN128_G2_SIZE: usize = ALT_BN128_FIELD_SIZE * 4;
+
+pub const ALT_BN128_ADDITION_INPUT_SIZE: usize = ALT_BN128_G1_SIZE * 2;
+pub const ALT_BN128_MULTIPLICATION_INPUT_SIZE: usize = ALT_BN128_G1_SIZE + ALT_BN128_FIELD_SIZE;
+pub const ALT_BN128_PAIRING_ELEMENT_SIZE: usize = ALT_BN128_G1_SIZE + ALT_BN128_G2_SIZE;
+pub const ALT_BN128_PAIRING_OUTPUT_SIZE: usize = 32;
+
+/// Number of pairings required for a Groth16 verification
+const GROTH16_PAIRINGS_COUNT: usize = 4;
+
+/// Number of `ComputeVerification` transactions required (at most) for a single verification
+///
+/// # Notes
+///
+/// The first transaction prepares the public inputs, followed by the subgroup check transactions and the pairing check transaction.
+/// If the proof has already been setup at the time of the input preparation, the subgroup check starts in the same transaction.
+pub const ALT_BN128_VERIFICATION_TX_COUNT: usize = G2SubgroupCheck::TX_COUNT + 2;
+
+/// Verifies a proof using the alt_bn128 syscalls
+///
+/// # Notes
+///
+/// Uses the same [`VerificationStep`]s as [`super::verifier::verify_partial`]:
+///     1. `PublicInputPreparation` -> `ProofSubgroupCheck` (with `prepared_inputs` set)
+///     2. `ProofSubgroupCheck` -> `CombinedMillerLoop` (after `G2SubgroupCheck::IX_COUNT` calls)
+///     3. `CombinedMillerLoop` -> `FinalExponentiation` (returning the verification result)
+pub fn verify_alt_bn128(
+    verification_account: &mut VerificationAccount,
+    vkey: &VerifyingKey,
+) -> Result<Option<bool>, ElusivError> {
+    if let VerificationStep::PublicInputPreparation = verification_account.get_step() {
+        let prepared_inputs = prepare_public_inputs_alt_bn128(verification_account, vkey)?;
+
+        verification_account
+            .prepared_inputs
+            .set(G1A(prepared_inputs));
+        verification_account.set_step(&VerificationStep::ProofSubgroupCheck);
+        verification_account.set_round(&0);
+        verification_account.set_instruction(&0);
+
+        if !matches!(
+            verification_account.get_state(),
+            VerificationState::ProofSetup
+        ) {
+            return Ok(None);
+        }
+    }
+
+    match verification_account.get_step() {
+        VerificationStep::ProofSubgroupCheck => {
+            // Proof first has to be setup
+            guard!(
+                matches!(
+                    verification_account.get_state(),
+                    VerificationState::ProofSetup
+                ),
+                InvalidAccountState
+            );
+
+            let instruction = verification_account.get_instruction() as usize;
+            let round = verification_account.get_round() as usize;
+            g2_subgroup_check(verification_account, instruction, round)?;
+            verification_account
+                .serialize_rams()
+                .or(Err(InvalidAccountState))?;
+
+            if instruction + 1 == G2SubgroupCheck::IX_COUNT {
+                verification_account.set_step(&VerificationStep::CombinedMillerLoop);
+                verification_account.set_round(&0);
+                verification_account.set_instruction(&0);
+            }
+
+            Ok(None)
+        }
+        VerificationStep::CombinedMillerLoop => {
+            // Proof first has to be setup
+            guard!(
+                matches!(
+                    verification_account.get_state(),
+                    VerificationState::ProofSetup
+                ),
+                InvalidAccountState
+            );
+
+            let a = verification_account.a.get().0;
+            let b = verification_account.b.get().0;
+            let c = verification_account.c.get().0;
+            let prepared_inputs = verification_account.prepared_inputs.get().0;
+
+            let result = groth16_pairing_check(vkey, &a, &b, &c, &prepared_inputs)?;
+
+            verification_account.set_step(&VerificationStep::FinalExponentiation);
+
+            Ok(Some(result))
+        }
+        _ => Err(ComputationIsAlreadyFinished),
+    }
+}
+
+/// Computes `gamma_abc_g1[0] + sum_i(public_input_i * gamma_abc_g1[i + 1])`
+fn prepare_public_inputs_alt_bn128(
+    verification_account: &VerificationAccount,
+    vkey: &VerifyingKey,
+) -> Result<G1Affine, ElusivError> {
+    let mut acc = g1_to_bytes(&vkey.gamma_abc_base().into());
+
+    for i in 0..vkey.public_inputs_count {
+        let public_input = verification_account.get_public_input(i).skip_mr();
+        if public_input == [0; 32] {
+            continue;
+        }
+
+        let mut input = [0; ALT_BN128_MULTIPLICATION_INPUT_SIZE];
+        input[..ALT_BN128_G1_SIZE].copy_from_slice(&g1_to_bytes(&vkey.gamma_abc(i, 0, 1)));
+        input[ALT_BN128_G1_SIZE..].copy_from_slice(&scalar_to_bytes(&public_input));
+        let product = alt_bn128_multiplication(&input)?;
+
+        let mut input = [0; ALT_BN128_ADDITION_INPUT_SIZE];
+        input[..ALT_BN128_G1_SIZE].copy_from_slice(&acc);
+        input[ALT_BN128_G1_SIZE..].copy_from_slice(&product);
+        acc = alt_bn128_addition(&input)?;
+    }
+
+    g1_from_bytes(&acc)
+}
+
+/// Checks `e(-a, b) * e(alpha, beta) * e(prepared_inputs, gamma) * e(c, delta) == 1`
+fn groth16_pairing_check(
+    vkey: &VerifyingKey,
+    a: &G1Affine,
+    b: &G2Affine,
+    c: &G1Affine,
+    prepared_inputs: &G1Affine,
+) -> Result<bool, ElusivError> {
+    let pairs = [
+        (-*a, *b),
+        (vkey.alpha(), vkey.beta()),
+        (*prepared_inputs, vkey.gamma()),
+        (*c, vkey.delta()),
+    ];
+
+    let mut input = [0; ALT_BN128_PAIRING_ELEMENT_SIZE * GROTH16_PAIRINGS_COUNT];
+    for (i, (g1, g2)) in pairs.iter().enumerate() {
+        let offset = i * ALT_BN128_PAIRING_ELEMENT_SIZE;
+        input[offset..offset + ALT_BN128_G1_SIZE].copy_from_slice(&g1_to_bytes(g1));
+        input[offset + ALT_BN128_G1_SIZE..offset + ALT_BN128_PAIRING_ELEMENT_SIZE]
+            .copy_from_slice(&g2_to_bytes(g2));
+    }
+
+    let result = alt_bn128_pairing(&input)?;
+    Ok(result[ALT_BN128_PAIRING_OUTPUT_SIZE - 1] == 1
+        && result[..ALT_BN128_PAIRING_OUTPUT_SIZE - 1] == [0; ALT_BN128_PAIRING_OUTPUT_SIZE - 1])
+}
+
+pub fn alt_bn128_addition(input: &[u8]) -> Result<[u8; ALT_BN128_G1_SIZE], ElusivError> {
+    guard!(
+        input.len() == ALT_BN128_ADDITION_INPUT_SIZE,
+        CouldNotProcessProof
+    );
+
+    let mut result = [0; ALT_BN128_G1_SIZE];
+    alt_bn128_group_op(ALT_BN128_ADDITION_OPERATION, input, &mut result)?;
+    Ok(result)
+}
+
+pub fn alt_bn128_multiplication(input: &[u8]) -> Result<[u8; ALT_BN128_G1_SIZE], ElusivError> {
+    guard!(
+        input.len() == ALT_BN128_MULTIPLICATION_INPUT_SIZE,
+        CouldNotProcessProof
+    );
+
+    let mut result = [0; ALT_BN128_G1_SIZE];
+    alt_bn128_group_op(ALT_BN128_MULTIPLICATION_OPERATION, input, &mut result)?;
+    Ok(result)
+}
+
+pub fn alt_bn128_pairing(input: &[u8]) -> Result<[u8; ALT_BN128_PAIRING_OUTPUT_SIZE], ElusivError> {
+    guard!(
+        input
+            .chunks_exact(ALT_BN128_PAIRING_ELEMENT_SIZE)
+            .remainder()
+            .is_empty(),
+        CouldNotProcessProof
+    );
+
+    let mut result = [0; ALT_BN128_PAIRING_OUTPUT_SIZE];
+    alt_bn128_group_op(ALT_BN128_PAIRING_OPERATION, input, &mut result)?;
+    Ok(result)
+}
+
+#[cfg(target_arch = "bpf")]
+fn alt_bn128_group_op(operation: u64, input: &[u8], result: &mut [u8]) -> Result<(), ElusivError> {
+    extern "C" {
+        fn sol_alt_bn128_group_op(
+            group_op: u64,
+            input: *const u8,
+            input_size: u64,
+            result: *mut u8,
+        ) -> u64;
+    }
+
+    let status = unsafe {
+        sol_alt_bn128_group_op(
+            operation,
+            input.as_ptr(),
+            input.len() as u64,
+            result.as_mut_ptr(),
+        )
+    };
+
+    match status {
+        0 => Ok(()),
+        _ => Err(CouldNotProcessProof),
+    }
+}
+
+/// Native shim for the `sol_alt_bn128_group_op` syscall
+#[cfg(not(target_arch = "bpf"))]
+fn alt_bn128_group_op(operation: u64, input: &[u8], result: &mut [u8]) -> Result<(), ElusivError> {
+    use ark_bn254::{Bn254, Fq12};
+    use ark_ec::PairingEngine;
+    use ark_ff::One;
+
+    match operation {
+        ALT_BN128_ADDITION_OPERATION => {
+            let p = g1_from_bytes(&input[..ALT_BN128_G1_SIZE])?;
+            let q = g1_from_bytes(&input[ALT_BN128_G1_SIZE..])?;
+            result.copy_from_slice(&g1_to_bytes(&(p + q)));
+        }
+        ALT_BN128_MULTIPLICATION_OPERATION => {
+            let p = g1_from_bytes(&input[..ALT_BN128_G1_SIZE])?;
+            let mut scalar = [0; 32];
+            scalar.copy_from_slice(&input[ALT_BN128_G1_SIZE..]);
+            scalar.reverse();
+            let scalar = BigInteger256([
+                u64_limb(&scalar, 0),
+                u64_limb(&scalar, 8),
+                u64_limb(&scalar, 16),
+                u64_limb(&scalar, 24),
+            ]);
+            result.copy_from_slice(&g1_to_bytes(&p.mul(scalar).into()));
+        }
+        ALT_BN128_PAIRING_OPERATION => {
+            let mut pairs = Vec::with_capacity(input.len() / ALT_BN128_PAIRING_ELEMENT_SIZE);
+            for element in input.chunks(ALT_BN128_PAIRING_ELEMENT_SIZE) {
+                let g1 = g1_from_bytes(&element[..ALT_BN128_G1_SIZE])?;
+                let g2 = g2_from_bytes(&element[ALT_BN128_G1_SIZE..])?;
+                pairs.push((
+                    <Bn254 as PairingEngine>::G1Prepared::from(g1),
+                    <Bn254 as PairingEngine>::G2Prepared::from(g2),
+                ));
+            }
+
+            let f = Bn254::product_of_pairings(pairs.iter());
+            result.fill(0);
+            if f == Fq12::one() {
+                result[ALT_BN128_PAIRING_OUTPUT_SIZE - 1] = 1;
+            }
+        }
+        _ => return Err(CouldNotProcessProof),
+    }
+
+    Ok(())
+}
+
+/// Big-endian non-montgomery field element
+fn fq_to_bytes(f: &Fq) -> [u8; ALT_BN128_FIELD_SIZE] {
+    let mut bytes = [0; ALT_BN128_FIELD_SIZE];
+    bytes.copy_from_slice(&f.into_repr().to_bytes_be());
+    bytes
+}
+
+fn fq_from_bytes(bytes: &[u8]) -> Result<Fq, ElusivError> {
+    let mut le = [0; ALT_BN128_FIELD_SIZE];
+    le.copy_from_slice(bytes);
+    le.reverse();
+
+    Fq::from_repr(BigInteger256([
+        u64_limb(&le, 0),
+        u64_limb(&le, 8),
+        u64_limb(&le, 16),
+        u64_limb(&le, 24),
+    ]))
+    .ok_or(CouldNotProcessProof)
+}
+
+/// Big-endian non-montgomery scalar (from a little-endian non-montgomery scalar)
+fn scalar_to_bytes(scalar: &U256) -> U256 {
+    let mut bytes = *scalar;
+    bytes.reverse();
+    bytes
+}
+
+/// Encodes a G1 point as `x || y` (with the point at infinity being represented by zero-bytes)
+pub fn g1_to_bytes(p: &G1Affine) -> [u8; ALT_BN128_G1_SIZE] {
+    let mut bytes = [0; ALT_BN128_G1_SIZE];
+    if p.is_zero() {
+        return bytes;
+    }
+
+    bytes[..ALT_BN128_FIELD_SIZE].copy_from_slice(&fq_to_bytes(&p.x));
+    bytes[ALT_BN128_FIELD_SIZE..].copy_from_slice(&fq_to_bytes(&p.y));
+    bytes
+}
+
+pub fn g1_from_bytes(bytes: &[u8]) -> Result<G1Affine, ElusivError> {
+    guard!(bytes.len() == ALT_BN128_G1_SIZE, CouldNotProcessProof);
+
+    if bytes.iter().all(|&b| b == 0) {
+        return Ok(G1Affine::zero());
+    }
+
+    let p = G1Affine::new(
+        fq_from_bytes(&bytes[..ALT_BN128_FIELD_SIZE])?,
+        fq_from_bytes(&bytes[ALT_BN128_FIELD_SIZE..])?,
+        false,
+    );
+    guard!(p.is_on_curve(), CouldNotProcessProof);
+
+    Ok(p)
+}
+
+/// Encodes a G2 point as `x.c1 || x.c0 || y.c1 || y.c0` (with the point at infinity being represented by zero-bytes)
+pub fn g2_to_bytes(p: &G2Affine) -> [u8; ALT_BN128_G2_SIZE] {
+    let mut bytes = [0; ALT_BN128_G2_SIZE];
+    if p.is_zero() {
+        return bytes;
+    }
+
+    bytes[..32].copy_from_slice(&fq_to_bytes(&p.x.c1));
+    bytes[32..64].copy_from_slice(&fq_to_bytes(&p.x.c0));
+    bytes[64..96].copy_from_slice(&fq_to_bytes(&p.y.c1));
+    bytes[96..].copy_from_slice(&fq_to_bytes(&p.y.c0));
+    bytes
+}
+
+pub fn g2_from_bytes(bytes: &[u8]) -> Result<G2Affine, ElusivError> {
+    guard!(bytes.len() == ALT_BN128_G2_SIZE, CouldNotProcessProof);
+
+    if bytes.iter().all(|&b| b == 0) {
+        return Ok(G2Affine::zero());
+    }
+
+    let p = G2Affine::new(
+        Fq2::new(fq_from_bytes(&bytes[32..64])?, fq_from_bytes(&bytes[..32])?),
+        Fq2::new(fq_from_bytes(&bytes[96..])?, fq_from_bytes(&bytes[64..96])?),
+        false,
+    );
+    guard!(p.is_on_curve(), CouldNotProcessProof);
+    guard!(
+        p.is_in_correct_subgroup_assuming_on_curve(),
+        CouldNotProcessProof
+    );
+
+    Ok(p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ElusivError::InvalidProofPoint;
+    use crate::macros::zero_program_account;
+    use crate::proof::test_proofs::{invalid_proofs, valid_proofs};
+    use crate::proof::verifier::{
+        precomputed_input_preparation, prepare_public_inputs_instructions,
+    };
+    use crate::proof::vkey::{TestVKey, VerifyingKeyInfo};
+    use crate::types::{Proof, RawU256};
+    use ark_bn254::{Fr, G1Projective};
+    use ark_ec::ProjectiveCurve;
+    use assert_matches::assert_matches;
+
+    fn setup_verification_account(
+        verification_account: &mut VerificationAccount,
+        proof: Proof,
+        public_inputs: &[U256],
+    ) {
+        verification_account.a.set(proof.a);
+        verification_account.b.set(proof.b);
+        verification_account.c.set(proof.c);
+        verification_account.set_state(&VerificationState::ProofSetup);
+
+        for (i, &public_input) in public_inputs.iter().enumerate() {
+            verification_account.set_public_input(i, &RawU256::new(public_input));
+        }
+
+        let instructions =
+            prepare_public_inputs_instructions(public_inputs, TestVKey::public_inputs_count());
+        verification_account
+            .setup_public_inputs_instructions(&instructions)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_g1_bytes() {
+        let p = G1Projective::prime_subgroup_generator()
+            .mul(Fr::from(123u64).into_repr())
+            .into_affine();
+        assert_eq!(g1_from_bytes(&g1_to_bytes(&p)).unwrap(), p);
+
+        assert_eq!(g1_to_bytes(&G1Affine::zero()), [0; ALT_BN128_G1_SIZE]);
+        assert!(g1_from_bytes(&[0; ALT_BN128_G1_SIZE]).unwrap().is_zero());
+
+        // Not on curve
+        let mut bytes = g1_to_bytes(&p);
+        bytes[ALT_BN128_G1_SIZE - 1] ^= 1;
+        assert_matches!(g1_from_bytes(&bytes), Err(_));
+
+        // Invalid field element
+        assert_matches!(g1_from_bytes(&[u8::MAX; ALT_BN128_G1_SIZE]), Err(_));
+    }
+
+    #[test]
+    fn test_g2_bytes() {
+        let p = ark_bn254::G2Projective::prime_subgroup_generator()
+            .mul(Fr::from(456u64).into_repr())
+            .into_affine();
+        assert_eq!(g2_from_bytes(&g2_to_bytes(&p)).unwrap(), p);
+        assert!(g2_from_bytes(&[0; ALT_BN128_G2_SIZE]).unwrap().is_zero());
+
+        let mut bytes = g2_to_bytes(&p);
+        bytes[ALT_BN128_G2_SIZE - 1] ^= 1;
+        assert_matches!(g2_from_bytes(&bytes), Err(_));
+    }
+
+    #[test]
+    fn test_alt_bn128_addition_multiplication() {
+        let g = G1Projective::prime_subgroup_generator().into_affine();
+        let two_g = g + g;
+
+        let mut input = [0; ALT_BN128_ADDITION_INPUT_SIZE];
+        input[..ALT_BN128_G1_SIZE].copy_from_slice(&g1_to_bytes(&g));
+        input[ALT_BN128_G1_SIZE..].copy_from_slice(&g1_to_bytes(&g));
+        assert_eq!(alt_bn128_addition(&input).unwrap(), g1_to_bytes(&two_g));
+
+        let mut input = [0; ALT_BN128_MULTIPLICATION_INPUT_SIZE];
+        input[..ALT_BN128_G1_SIZE].copy_from_slice(&g1_to_bytes(&g));
+        input[ALT_BN128_MULTIPLICATION_INPUT_SIZE - 1] = 2;
+        assert_eq!(
+            alt_bn128_multiplication(&input).unwrap(),
+            g1_to_bytes(&two_g)
+        );
+
+        // Invalid input sizes
+        assert_matches!(alt_bn128_addition(&input), Err(_));
+        assert_matches!(alt_bn128_pairing(&input), Err(_));
+    }
+
+    #[test]
+    fn test_prepare_public_inputs_alt_bn128() {
+        let source = TestVKey::verifying_key_source();
+        let vkey = VerifyingKey::new(&source, TestVKey::public_inputs_count()).unwrap();
+
+        for proof in valid_proofs() {
+            zero_program_account!(mut verification_account, VerificationAccount);
+            setup_verification_account(
+                &mut verification_account,
+                proof.proof,
+                &proof.public_inputs,
+            );
+
+            assert_eq!(
+                prepare_public_inputs_alt_bn128(&verification_account, &vkey).unwrap(),
+                precomputed_input_preparation(&vkey, &proof.public_inputs).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_alt_bn128() {
+        let source = TestVKey::verifying_key_source();
+        let vkey = VerifyingKey::new(&source, TestVKey::public_inputs_count()).unwrap();
+
+        for proof in valid_proofs() {
+            zero_program_account!(mut verification_account, VerificationAccount);
+            setup_verification_account(
+                &mut verification_account,
+                proof.proof,
+                &proof.public_inputs,
+            );
+
+            // Input preparation and subgroup check
+            for _ in 0..G2SubgroupCheck::IX_COUNT {
+                assert_matches!(verify_alt_bn128(&mut verification_account, &vkey), Ok(None));
+            }
+            assert_matches!(
+                verification_account.get_step(),
+                VerificationStep::CombinedMillerLoop
+            );
+
+            assert_matches!(
+                verify_alt_bn128(&mut verification_account, &vkey),
+                Ok(Some(true))
+            );
+            assert_matches!(
+                verification_account.get_step(),
+                VerificationStep::FinalExponentiation
+            );
+
+            // Computation is finished
+            assert_matches!(
+                verify_alt_bn128(&mut verification_account, &vkey),
+                Err(ComputationIsAlreadyFinished)
+            );
+        }
+
+        for proof in invalid_proofs() {
+            zero_program_account!(mut verification_account, VerificationAccount);
+            setup_verification_account(
+                &mut verification_account,
+                proof.proof,
+                &proof.public_inputs,
+            );
+
+            // Invalid points (not on the curve) are rejected by the syscalls
+            let mut result = Ok(None);
+            while let Ok(None) = result {
+                result = verify_alt_bn128(&mut verification_account, &vkey);
+            }
+            assert_matches!(
+                result,
+                Ok(Some(false)) | Err(CouldNotProcessProof) | Err(InvalidProofPoint)
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_alt_bn128_before_proof_setup() {
+        let source = TestVKey::verifying_key_source();
+        let vkey = VerifyingKey::new(&source, TestVKey::public_inputs_count()).unwrap();
+        let proof = &valid_proofs()[0];
+
+        zero_program_account!(mut verification_account, VerificationAccount);
+        setup_verification_account(&mut verification_account, proof.proof, &proof.public_inputs);
+        verification_account.set_state(&VerificationState::FeeTransferred);
+
+        // Input preparation
+        assert_matches!(verify_alt_bn128(&mut verification_account, &vkey), Ok(None));
+        assert_matches!(
+            verification_account.get_step(),
+            VerificationStep::ProofSubgroupCheck
+        );
+
+        // Proof not setup yet
+        assert_matches!(
+            verify_alt_bn128(&mut verification_account, &vkey),
+            Err(InvalidAccountState)
+        );
+
+        verification_account.set_state(&VerificationState::ProofSetup);
+        for _ in 0..G2SubgroupCheck::IX_COUNT {
+            assert_matches!(verify_alt_bn128(&mut verification_account, &vkey), Ok(None));
+        }
+        assert_matches!(
+            verify_alt_bn128(&mut verification_account, &vkey),
+            Ok(Some(true))
+        );
+    }
+}
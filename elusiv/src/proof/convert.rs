@@ -0,0 +1,947 @@
+//! Client-side converters from common Groth16 prover outputs into [`Proof`] and public signals
+//!
+//! # Conventions
+//!
+//! - [`Proof`] stores affine points ([`G1A`], [`G2A`]) whose coordinates are kept in montgomery form, so the program can deserialize them without a reduction.
+//! - Public signals are [`RawU256`] values in canonical little-endian (non-montgomery) form, so they can be checked against the scalar field modulus before [`RawU256::reduce`] is called.
+//!   All converters use [`RawU256::skip_mr`] to access these raw bytes.
+
+use crate::fields::{
+    fr_to_u256_le_repr, is_element_scalar_field, is_in_g2_subgroup, is_valid_g1_point,
+    is_valid_g2_point, u256_to_big_uint, G1A, G2A,
+};
+use crate::types::{Proof, RawU256, U256};
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ff::{BigInteger, BigInteger256, PrimeField, Zero};
+use serde_json::Value;
+use std::ops::Neg;
+
+#[derive(Debug, PartialEq)]
+pub enum ProofConversionError {
+    InvalidJson,
+    InvalidFieldElement,
+    InvalidPoint,
+    InvalidLength,
+    UnsupportedCommitments,
+}
+
+// snarkjs
+
+/// Parses a snarkjs `proof.json` (only the `pi_a`, `pi_b` and `pi_c` fields are required)
+pub fn proof_from_snarkjs_json(json: &str) -> Result<Proof, ProofConversionError> {
+    let value: Value = serde_json::from_str(json).map_err(|_| ProofConversionError::InvalidJson)?;
+
+    let a = snarkjs_g1(&value["pi_a"])?;
+    let b = snarkjs_g2(&value["pi_b"])?;
+    let c = snarkjs_g1(&value["pi_c"])?;
+
+    Ok(Proof {
+        a: G1A(a),
+        b: G2A(b),
+        c: G1A(c),
+    })
+}
+
+/// Serializes a [`Proof`] into the snarkjs `proof.json` format
+pub fn proof_to_snarkjs_json(proof: &Proof) -> String {
+    let g1 = |p: &G1Affine| -> Vec<String> {
+        let (x, y, z) = projective_g1_coordinates(p);
+        vec![fq_to_decimal(&x), fq_to_decimal(&y), fq_to_decimal(&z)]
+    };
+    let fq2 = |e: &Fq2| -> Vec<String> { vec![fq_to_decimal(&e.c0), fq_to_decimal(&e.c1)] };
+    let (bx, by, bz) = projective_g2_coordinates(&proof.b.0);
+
+    serde_json::json!({
+        "pi_a": g1(&proof.a.0),
+        "pi_b": [fq2(&bx), fq2(&by), fq2(&bz)],
+        "pi_c": g1(&proof.c.0),
+        "protocol": "groth16",
+        "curve": "bn128",
+    })
+    .to_string()
+}
+
+/// Parses a snarkjs `public.json` (an array of decimal strings)
+pub fn public_signals_from_snarkjs_json(json: &str) -> Result<Vec<RawU256>, ProofConversionError> {
+    let value: Value = serde_json::from_str(json).map_err(|_| ProofConversionError::InvalidJson)?;
+    let signals = value.as_array().ok_or(ProofConversionError::InvalidJson)?;
+
+    signals
+        .iter()
+        .map(|signal| {
+            let s = signal.as_str().ok_or(ProofConversionError::InvalidJson)?;
+            let fr = Fr::from_repr(parse_decimal(s)?)
+                .ok_or(ProofConversionError::InvalidFieldElement)?;
+            Ok(RawU256::new(fr_to_u256_le_repr(&fr)))
+        })
+        .collect()
+}
+
+/// Serializes public signals into the snarkjs `public.json` format
+pub fn public_signals_to_snarkjs_json(
+    public_signals: &[RawU256],
+) -> Result<String, ProofConversionError> {
+    let signals = public_signals
+        .iter()
+        .map(|signal| Ok(big_uint_to_decimal(raw_to_big_uint(signal)?)))
+        .collect::<Result<Vec<String>, ProofConversionError>>()?;
+
+    Ok(Value::from(signals).to_string())
+}
+
+/// Parses a snarkjs Groth16 `verification_key.json`
+pub fn verifying_key_from_snarkjs_json(
+    json: &str,
+) -> Result<ark_groth16::VerifyingKey<Bn254>, ProofConversionError> {
+    let value: Value = serde_json::from_str(json).map_err(|_| ProofConversionError::InvalidJson)?;
+
+    if value["protocol"] != "groth16" {
+        return Err(ProofConversionError::InvalidJson);
+    }
+
+    let public_inputs_count = value["nPublic"]
+        .as_u64()
+        .ok_or(ProofConversionError::InvalidJson)? as usize;
+    let ic = value["IC"]
+        .as_array()
+        .ok_or(ProofConversionError::InvalidJson)?;
+    if ic.len() != public_inputs_count + 1 {
+        return Err(ProofConversionError::InvalidLength);
+    }
+
+    Ok(ark_groth16::VerifyingKey {
+        alpha_g1: snarkjs_g1(&value["vk_alpha_1"])?,
+        beta_g2: snarkjs_g2(&value["vk_beta_2"])?,
+        gamma_g2: snarkjs_g2(&value["vk_gamma_2"])?,
+        delta_g2: snarkjs_g2(&value["vk_delta_2"])?,
+        gamma_abc_g1: ic.iter().map(snarkjs_g1).collect::<Result<_, _>>()?,
+    })
+}
+
+fn snarkjs_fq(value: &Value) -> Result<Fq, ProofConversionError> {
+    let s = value.as_str().ok_or(ProofConversionError::InvalidJson)?;
+    Fq::from_repr(parse_decimal(s)?).ok_or(ProofConversionError::InvalidFieldElement)
+}
+
+fn snarkjs_fq2(value: &Value) -> Result<Fq2, ProofConversionError> {
+    match value.as_array().map(|v| v.as_slice()) {
+        Some([c0, c1]) => Ok(Fq2::new(snarkjs_fq(c0)?, snarkjs_fq(c1)?)),
+        _ => Err(ProofConversionError::InvalidJson),
+    }
+}
+
+fn snarkjs_g1(value: &Value) -> Result<G1Affine, ProofConversionError> {
+    let p: G1Affine = match value.as_array().map(|v| v.as_slice()) {
+        Some([x, y, z]) => G1Projective::new(snarkjs_fq(x)?, snarkjs_fq(y)?, snarkjs_fq(z)?).into(),
+        _ => return Err(ProofConversionError::InvalidJson),
+    };
+
+    if !is_valid_g1_point(&p) {
+        return Err(ProofConversionError::InvalidPoint);
+    }
+    Ok(p)
+}
+
+fn snarkjs_g2(value: &Value) -> Result<G2Affine, ProofConversionError> {
+    let p: G2Affine = match value.as_array().map(|v| v.as_slice()) {
+        Some([x, y, z]) => {
+            G2Projective::new(snarkjs_fq2(x)?, snarkjs_fq2(y)?, snarkjs_fq2(z)?).into()
+        }
+        _ => return Err(ProofConversionError::InvalidJson),
+    };
+
+    if !is_valid_g2_point(&p) || !is_in_g2_subgroup(&p) {
+        return Err(ProofConversionError::InvalidPoint);
+    }
+    Ok(p)
+}
+
+/// snarkjs represents affine points with `z = 1` and the point at infinity as `(0, 1, 0)`
+fn projective_g1_coordinates(p: &G1Affine) -> (Fq, Fq, Fq) {
+    if p.infinity {
+        (Fq::zero(), Fq::from(1u64), Fq::zero())
+    } else {
+        (p.x, p.y, Fq::from(1u64))
+    }
+}
+
+fn projective_g2_coordinates(p: &G2Affine) -> (Fq2, Fq2, Fq2) {
+    if p.infinity {
+        (Fq2::zero(), Fq2::from(1u64), Fq2::zero())
+    } else {
+        (p.x, p.y, Fq2::from(1u64))
+    }
+}
+
+// arkworks
+
+impl From<ark_groth16::Proof<Bn254>> for Proof {
+    fn from(proof: ark_groth16::Proof<Bn254>) -> Self {
+        Proof {
+            a: G1A(proof.a),
+            b: G2A(proof.b),
+            c: G1A(proof.c),
+        }
+    }
+}
+
+impl From<Proof> for ark_groth16::Proof<Bn254> {
+    fn from(proof: Proof) -> Self {
+        ark_groth16::Proof {
+            a: proof.a.0,
+            b: proof.b.0,
+            c: proof.c.0,
+        }
+    }
+}
+
+/// Converts arkworks public inputs into public signals
+pub fn public_signals_from_fr(public_inputs: &[Fr]) -> Vec<RawU256> {
+    public_inputs
+        .iter()
+        .map(|fr| RawU256::new(fr_to_u256_le_repr(fr)))
+        .collect()
+}
+
+/// Converts public signals into arkworks public inputs
+pub fn public_signals_to_fr(public_signals: &[RawU256]) -> Result<Vec<Fr>, ProofConversionError> {
+    public_signals
+        .iter()
+        .map(|signal| {
+            Fr::from_repr(raw_to_big_uint(signal)?).ok_or(ProofConversionError::InvalidFieldElement)
+        })
+        .collect()
+}
+
+// gnark
+
+const GNARK_MASK: u8 = 0b11 << 6;
+const GNARK_UNCOMPRESSED: u8 = 0b00 << 6;
+const GNARK_UNCOMPRESSED_INFINITY: u8 = 0b01 << 6;
+const GNARK_COMPRESSED_SMALLEST: u8 = 0b10 << 6;
+const GNARK_COMPRESSED_LARGEST: u8 = 0b11 << 6;
+const GNARK_COMPRESSED_INFINITY: u8 = 0b01 << 6;
+
+const GNARK_FQ_SIZE: usize = 32;
+pub const GNARK_PROOF_SIZE: usize = 8 * GNARK_FQ_SIZE;
+pub const GNARK_COMPRESSED_PROOF_SIZE: usize = 4 * GNARK_FQ_SIZE;
+
+/// Size of the commitment data appended by gnark v0.9+ (`u32` count and an infinity proof-of-knowledge) if the circuit has no commitments
+const GNARK_EMPTY_COMMITMENTS_SIZE: usize = 4 + 2 * GNARK_FQ_SIZE;
+
+/// Parses a gnark Groth16 proof (`WriteRawTo` or compressed `WriteTo` encoding)
+///
+/// # Notes
+///
+/// gnark encodes base field elements in big-endian, with `Fq2` elements as `A1 | A0`.
+/// The two most significant bits of each point encode compression and infinity flags.
+pub fn proof_from_gnark_bytes(bytes: &[u8]) -> Result<Proof, ProofConversionError> {
+    let (compressed, points, trailing) = if bytes.len() >= GNARK_PROOF_SIZE
+        && bytes[0] & GNARK_MASK != GNARK_COMPRESSED_SMALLEST
+        && bytes[0] & GNARK_MASK != GNARK_COMPRESSED_LARGEST
+    {
+        (
+            false,
+            &bytes[..GNARK_PROOF_SIZE],
+            &bytes[GNARK_PROOF_SIZE..],
+        )
+    } else if bytes.len() >= GNARK_COMPRESSED_PROOF_SIZE {
+        (
+            true,
+            &bytes[..GNARK_COMPRESSED_PROOF_SIZE],
+            &bytes[GNARK_COMPRESSED_PROOF_SIZE..],
+        )
+    } else {
+        return Err(ProofConversionError::InvalidLength);
+    };
+
+    check_gnark_commitments(trailing, compressed)?;
+
+    let g1_size = if compressed { 1 } else { 2 } * GNARK_FQ_SIZE;
+    let (a, points) = points.split_at(g1_size);
+    let (b, c) = points.split_at(2 * g1_size);
+
+    let a = gnark_g1(a)?;
+    let b = gnark_g2(b)?;
+    let c = gnark_g1(c)?;
+
+    if !is_valid_g1_point(&a)
+        || !is_valid_g2_point(&b)
+        || !is_in_g2_subgroup(&b)
+        || !is_valid_g1_point(&c)
+    {
+        return Err(ProofConversionError::InvalidPoint);
+    }
+
+    Ok(Proof {
+        a: G1A(a),
+        b: G2A(b),
+        c: G1A(c),
+    })
+}
+
+/// Serializes a [`Proof`] into gnark's raw (or compressed) Groth16 encoding
+pub fn proof_to_gnark_bytes(proof: &Proof, compressed: bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(GNARK_PROOF_SIZE);
+    if compressed {
+        bytes.extend(gnark_g1_compressed_bytes(&proof.a.0));
+        bytes.extend(gnark_g2_compressed_bytes(&proof.b.0));
+        bytes.extend(gnark_g1_compressed_bytes(&proof.c.0));
+    } else {
+        bytes.extend(gnark_g1_bytes(&proof.a.0));
+        bytes.extend(gnark_g2_bytes(&proof.b.0));
+        bytes.extend(gnark_g1_bytes(&proof.c.0));
+    }
+    bytes
+}
+
+/// Parses a gnark public witness (`nbPublic: u32 | nbSecret: u32 | len: u32 | elements`, all big-endian)
+pub fn public_signals_from_gnark_witness(
+    bytes: &[u8],
+) -> Result<Vec<RawU256>, ProofConversionError> {
+    if bytes.len() < 12 {
+        return Err(ProofConversionError::InvalidLength);
+    }
+
+    let public_count = u32::from_be_bytes(bytes[0..4].try_into().unwrap()) as usize;
+    let secret_count = u32::from_be_bytes(bytes[4..8].try_into().unwrap());
+    let len = u32::from_be_bytes(bytes[8..12].try_into().unwrap()) as usize;
+    let elements = &bytes[12..];
+
+    if secret_count != 0 || public_count != len || elements.len() != len * GNARK_FQ_SIZE {
+        return Err(ProofConversionError::InvalidLength);
+    }
+
+    elements
+        .chunks(GNARK_FQ_SIZE)
+        .map(|chunk| {
+            let u = be_to_u256(chunk);
+            if !is_element_scalar_field(u256_to_big_uint(&u)) {
+                return Err(ProofConversionError::InvalidFieldElement);
+            }
+            Ok(RawU256::new(u))
+        })
+        .collect()
+}
+
+/// Serializes public signals into a gnark public witness
+pub fn public_signals_to_gnark_witness(
+    public_signals: &[RawU256],
+) -> Result<Vec<u8>, ProofConversionError> {
+    let len = public_signals.len() as u32;
+    let mut bytes = Vec::with_capacity(12 + public_signals.len() * GNARK_FQ_SIZE);
+    bytes.extend(len.to_be_bytes());
+    bytes.extend(0u32.to_be_bytes());
+    bytes.extend(len.to_be_bytes());
+
+    for signal in public_signals {
+        raw_to_big_uint(signal)?;
+
+        let mut be = signal.skip_mr();
+        be.reverse();
+        bytes.extend(be);
+    }
+
+    Ok(bytes)
+}
+
+/// Accepts the (empty) commitment data gnark v0.9+ appends to proofs
+fn check_gnark_commitments(trailing: &[u8], compressed: bool) -> Result<(), ProofConversionError> {
+    if trailing.is_empty() {
+        return Ok(());
+    }
+
+    if trailing.len() < 4 {
+        return Err(ProofConversionError::InvalidLength);
+    }
+    if u32::from_be_bytes(trailing[..4].try_into().unwrap()) != 0 {
+        return Err(ProofConversionError::UnsupportedCommitments);
+    }
+
+    // The commitment proof-of-knowledge has to be the point at infinity
+    let pok = &trailing[4..];
+    let pok_size = if compressed {
+        GNARK_FQ_SIZE
+    } else {
+        GNARK_EMPTY_COMMITMENTS_SIZE - 4
+    };
+    if pok.len() != pok_size || !gnark_g1(pok)?.infinity {
+        return Err(ProofConversionError::UnsupportedCommitments);
+    }
+
+    Ok(())
+}
+
+fn gnark_fq(bytes: &[u8]) -> Result<Fq, ProofConversionError> {
+    Fq::from_repr(u256_to_big_uint(&be_to_u256(bytes)))
+        .ok_or(ProofConversionError::InvalidFieldElement)
+}
+
+/// Reads a big-endian field element with the flag bits cleared
+fn gnark_fq_unmasked(bytes: &[u8]) -> Result<Fq, ProofConversionError> {
+    let mut b = be_to_u256(bytes);
+    b[31] &= !GNARK_MASK;
+    Fq::from_repr(u256_to_big_uint(&b)).ok_or(ProofConversionError::InvalidFieldElement)
+}
+
+fn gnark_g1(bytes: &[u8]) -> Result<G1Affine, ProofConversionError> {
+    let flag = bytes[0] & GNARK_MASK;
+    let x = gnark_fq_unmasked(&bytes[..GNARK_FQ_SIZE])?;
+
+    match flag {
+        GNARK_UNCOMPRESSED | GNARK_UNCOMPRESSED_INFINITY if bytes.len() == 2 * GNARK_FQ_SIZE => {
+            let y = gnark_fq(&bytes[GNARK_FQ_SIZE..])?;
+            if flag == GNARK_UNCOMPRESSED_INFINITY || (x.is_zero() && y.is_zero()) {
+                if !x.is_zero() || !y.is_zero() {
+                    return Err(ProofConversionError::InvalidPoint);
+                }
+                return Ok(G1Affine::zero());
+            }
+            Ok(G1Affine::new(x, y, false))
+        }
+        GNARK_COMPRESSED_INFINITY if bytes.len() == GNARK_FQ_SIZE => {
+            if !x.is_zero() {
+                return Err(ProofConversionError::InvalidPoint);
+            }
+            Ok(G1Affine::zero())
+        }
+        GNARK_COMPRESSED_SMALLEST | GNARK_COMPRESSED_LARGEST if bytes.len() == GNARK_FQ_SIZE => {
+            G1Affine::get_point_from_x(x, flag == GNARK_COMPRESSED_LARGEST)
+                .ok_or(ProofConversionError::InvalidPoint)
+        }
+        _ => Err(ProofConversionError::InvalidPoint),
+    }
+}
+
+fn gnark_g2(bytes: &[u8]) -> Result<G2Affine, ProofConversionError> {
+    let flag = bytes[0] & GNARK_MASK;
+    let x = Fq2::new(
+        gnark_fq(&bytes[GNARK_FQ_SIZE..2 * GNARK_FQ_SIZE])?,
+        gnark_fq_unmasked(&bytes[..GNARK_FQ_SIZE])?,
+    );
+
+    match flag {
+        GNARK_UNCOMPRESSED | GNARK_UNCOMPRESSED_INFINITY if bytes.len() == 4 * GNARK_FQ_SIZE => {
+            let y = Fq2::new(
+                gnark_fq(&bytes[3 * GNARK_FQ_SIZE..])?,
+                gnark_fq(&bytes[2 * GNARK_FQ_SIZE..3 * GNARK_FQ_SIZE])?,
+            );
+            if flag == GNARK_UNCOMPRESSED_INFINITY || (x.is_zero() && y.is_zero()) {
+                if !x.is_zero() || !y.is_zero() {
+                    return Err(ProofConversionError::InvalidPoint);
+                }
+                return Ok(G2Affine::zero());
+            }
+            Ok(G2Affine::new(x, y, false))
+        }
+        GNARK_COMPRESSED_INFINITY if bytes.len() == 2 * GNARK_FQ_SIZE => {
+            if !x.is_zero() {
+                return Err(ProofConversionError::InvalidPoint);
+            }
+            Ok(G2Affine::zero())
+        }
+        // gnark's lexicographic order on `Fq2` (comparing `A1` first) matches the ordering used by arkworks
+        GNARK_COMPRESSED_SMALLEST | GNARK_COMPRESSED_LARGEST
+            if bytes.len() == 2 * GNARK_FQ_SIZE =>
+        {
+            G2Affine::get_point_from_x(x, flag == GNARK_COMPRESSED_LARGEST)
+                .ok_or(ProofConversionError::InvalidPoint)
+        }
+        _ => Err(ProofConversionError::InvalidPoint),
+    }
+}
+
+fn fq_to_be(e: &Fq) -> Vec<u8> {
+    e.into_repr().to_bytes_be()
+}
+
+fn gnark_g1_bytes(p: &G1Affine) -> Vec<u8> {
+    if p.infinity {
+        let mut bytes = vec![0; 2 * GNARK_FQ_SIZE];
+        bytes[0] = GNARK_UNCOMPRESSED_INFINITY;
+        return bytes;
+    }
+
+    let mut bytes = fq_to_be(&p.x);
+    bytes.extend(fq_to_be(&p.y));
+    bytes
+}
+
+fn gnark_g2_bytes(p: &G2Affine) -> Vec<u8> {
+    if p.infinity {
+        let mut bytes = vec![0; 4 * GNARK_FQ_SIZE];
+        bytes[0] = GNARK_UNCOMPRESSED_INFINITY;
+        return bytes;
+    }
+
+    let mut bytes = fq_to_be(&p.x.c1);
+    bytes.extend(fq_to_be(&p.x.c0));
+    bytes.extend(fq_to_be(&p.y.c1));
+    bytes.extend(fq_to_be(&p.y.c0));
+    bytes
+}
+
+fn gnark_g1_compressed_bytes(p: &G1Affine) -> Vec<u8> {
+    if p.infinity {
+        let mut bytes = vec![0; GNARK_FQ_SIZE];
+        bytes[0] = GNARK_COMPRESSED_INFINITY;
+        return bytes;
+    }
+
+    let mut bytes = fq_to_be(&p.x);
+    bytes[0] |= if p.y > p.y.neg() {
+        GNARK_COMPRESSED_LARGEST
+    } else {
+        GNARK_COMPRESSED_SMALLEST
+    };
+    bytes
+}
+
+fn gnark_g2_compressed_bytes(p: &G2Affine) -> Vec<u8> {
+    if p.infinity {
+        let mut bytes = vec![0; 2 * GNARK_FQ_SIZE];
+        bytes[0] = GNARK_COMPRESSED_INFINITY;
+        return bytes;
+    }
+
+    let mut bytes = fq_to_be(&p.x.c1);
+    bytes.extend(fq_to_be(&p.x.c0));
+    bytes[0] |= if p.y > p.y.neg() {
+        GNARK_COMPRESSED_LARGEST
+    } else {
+        GNARK_COMPRESSED_SMALLEST
+    };
+    bytes
+}
+
+// Helpers
+
+fn be_to_u256(bytes: &[u8]) -> U256 {
+    let mut u: U256 = bytes.try_into().unwrap();
+    u.reverse();
+    u
+}
+
+/// Reads the canonical (non-montgomery) value of a public signal
+fn raw_to_big_uint(raw: &RawU256) -> Result<BigInteger256, ProofConversionError> {
+    let big = u256_to_big_uint(&raw.skip_mr());
+    if !is_element_scalar_field(big) {
+        return Err(ProofConversionError::InvalidFieldElement);
+    }
+    Ok(big)
+}
+
+/// Parses a decimal string into a [`BigInteger256`] (without any modular reduction)
+fn parse_decimal(s: &str) -> Result<BigInteger256, ProofConversionError> {
+    if s.is_empty() || !s.bytes().all(|d| d.is_ascii_digit()) {
+        return Err(ProofConversionError::InvalidFieldElement);
+    }
+
+    let mut limbs = [0u64; 4];
+    for d in s.bytes() {
+        let mut carry = (d - b'0') as u128;
+        for limb in limbs.iter_mut() {
+            let v = (*limb as u128) * 10 + carry;
+            *limb = v as u64;
+            carry = v >> 64;
+        }
+        if carry != 0 {
+            return Err(ProofConversionError::InvalidFieldElement);
+        }
+    }
+
+    Ok(BigInteger256(limbs))
+}
+
+fn big_uint_to_decimal(big: BigInteger256) -> String {
+    let mut limbs = big.0;
+    let mut digits = Vec::new();
+
+    loop {
+        let mut rem = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let v = (rem << 64) | *limb as u128;
+            *limb = (v / 10) as u64;
+            rem = v % 10;
+        }
+        digits.push(b'0' + rem as u8);
+
+        if limbs.iter().all(|l| *l == 0) {
+            break;
+        }
+    }
+
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+fn fq_to_decimal(e: &Fq) -> String {
+    big_uint_to_decimal(e.into_repr())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fields::u256_from_str_skip_mr;
+    use crate::proof::test_proofs::valid_proofs;
+    use ark_ec::AffineCurve;
+
+    fn test_signals() -> Vec<RawU256> {
+        valid_proofs()[0]
+            .public_inputs
+            .iter()
+            .map(|p| RawU256::new(*p))
+            .collect()
+    }
+
+    #[test]
+    fn test_decimal() {
+        for s in [
+            "0",
+            "1",
+            "18446744073709551616",
+            "21888242871839275222246405745257275088548364400416034343698204186575808495616",
+        ] {
+            assert_eq!(big_uint_to_decimal(parse_decimal(s).unwrap()), s);
+        }
+
+        // 2^256
+        assert_eq!(
+            parse_decimal(
+                "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+            ),
+            Err(ProofConversionError::InvalidFieldElement)
+        );
+        assert_eq!(
+            parse_decimal(""),
+            Err(ProofConversionError::InvalidFieldElement)
+        );
+        assert_eq!(
+            parse_decimal("-1"),
+            Err(ProofConversionError::InvalidFieldElement)
+        );
+        assert_eq!(
+            parse_decimal("0x1"),
+            Err(ProofConversionError::InvalidFieldElement)
+        );
+    }
+
+    #[test]
+    fn test_snarkjs_proof() {
+        let json = r#"{
+            "pi_a": [
+                "14690239631763315837453664042432597412358242015145136618358222387278279116195",
+                "3643780132787394650252740182203975834437718299044985767317449850565317488166",
+                "1"
+            ],
+            "pi_b": [
+                [
+                    "12318858301116136039901780880140636659938620239898996708075490787377990627021",
+                    "2655335215981242007154487245887430969280221036621749020134517693786655613279"
+                ],
+                [
+                    "13665401110313137408934496500722861939604143361381592485089904000626841203657",
+                    "16886134483886522029016161222749430345330639128944557054644673266184517343819"
+                ],
+                ["1", "0"]
+            ],
+            "pi_c": [
+                "20648835712776577082472214104799321681109444262412204126993043827327940209500",
+                "18221482463531702349023663967222567126976044483242847353303931705097934869008",
+                "1"
+            ],
+            "protocol": "groth16",
+            "curve": "bn128"
+        }"#;
+
+        let proof = proof_from_snarkjs_json(json).unwrap();
+        assert_eq!(proof, valid_proofs()[0].proof);
+
+        // Round trip
+        for test_proof in valid_proofs() {
+            let json = proof_to_snarkjs_json(&test_proof.proof);
+            assert_eq!(proof_from_snarkjs_json(&json).unwrap(), test_proof.proof);
+        }
+    }
+
+    #[test]
+    fn test_snarkjs_proof_invalid() {
+        let json = proof_to_snarkjs_json(&valid_proofs()[0].proof);
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+
+        // Missing field
+        let mut v = value.clone();
+        v.as_object_mut().unwrap().remove("pi_c");
+        assert_eq!(
+            proof_from_snarkjs_json(&v.to_string()),
+            Err(ProofConversionError::InvalidJson)
+        );
+
+        // Coordinate exceeding the base field modulus
+        let mut v = value.clone();
+        v["pi_a"][0] = Value::from(
+            "21888242871839275222246405745257275088696311157297823662689037894645226208583",
+        );
+        assert_eq!(
+            proof_from_snarkjs_json(&v.to_string()),
+            Err(ProofConversionError::InvalidFieldElement)
+        );
+
+        // Point not on the curve
+        value["pi_a"][1] = Value::from("1");
+        assert_eq!(
+            proof_from_snarkjs_json(&value.to_string()),
+            Err(ProofConversionError::InvalidPoint)
+        );
+
+        assert_eq!(
+            proof_from_snarkjs_json("{"),
+            Err(ProofConversionError::InvalidJson)
+        );
+    }
+
+    #[test]
+    fn test_snarkjs_public_signals() {
+        let json = r#"["7889586699914970744657798935358222218486353295005298675075639741334684257960", "0", "120000"]"#;
+        let signals = public_signals_from_snarkjs_json(json).unwrap();
+
+        assert_eq!(
+            signals,
+            vec![
+                RawU256::new(u256_from_str_skip_mr(
+                    "7889586699914970744657798935358222218486353295005298675075639741334684257960"
+                )),
+                RawU256::ZERO,
+                RawU256::new(u256_from_str_skip_mr("120000")),
+            ]
+        );
+
+        // Public signals are stored in canonical form, so `reduce` yields the montgomery form
+        assert_eq!(
+            signals[2].reduce(),
+            crate::fields::fr_to_u256_le(&Fr::from(120000u64))
+        );
+
+        // Round trip
+        let signals = test_signals();
+        let json = public_signals_to_snarkjs_json(&signals).unwrap();
+        assert_eq!(public_signals_from_snarkjs_json(&json).unwrap(), signals);
+
+        // Scalar field modulus
+        assert_eq!(
+            public_signals_from_snarkjs_json(
+                r#"["21888242871839275222246405745257275088548364400416034343698204186575808495617"]"#
+            ),
+            Err(ProofConversionError::InvalidFieldElement)
+        );
+        assert_eq!(
+            public_signals_to_snarkjs_json(&[RawU256::new([u8::MAX; 32])]),
+            Err(ProofConversionError::InvalidFieldElement)
+        );
+        assert_eq!(
+            public_signals_from_snarkjs_json(r#"[1]"#),
+            Err(ProofConversionError::InvalidJson)
+        );
+    }
+
+    #[test]
+    fn test_arkworks() {
+        for test_proof in valid_proofs() {
+            let ark_proof: ark_groth16::Proof<Bn254> = test_proof.proof.into();
+            assert_eq!(ark_proof.a, test_proof.proof.a.0);
+            assert_eq!(ark_proof.b, test_proof.proof.b.0);
+            assert_eq!(ark_proof.c, test_proof.proof.c.0);
+            assert_eq!(Proof::from(ark_proof), test_proof.proof);
+        }
+
+        let signals = test_signals();
+        let inputs = public_signals_to_fr(&signals).unwrap();
+        assert_eq!(inputs[8], Fr::from(120000u64));
+        assert_eq!(public_signals_from_fr(&inputs), signals);
+
+        assert_eq!(
+            public_signals_to_fr(&[RawU256::new([u8::MAX; 32])]),
+            Err(ProofConversionError::InvalidFieldElement)
+        );
+    }
+
+    #[test]
+    fn test_gnark_encoding() {
+        let g1 = G1Affine::prime_subgroup_generator();
+        let g2 = G2Affine::prime_subgroup_generator();
+        let proof = Proof {
+            a: G1A(g1),
+            b: G2A(g2),
+            c: G1A(g1.neg()),
+        };
+
+        let bytes = proof_to_gnark_bytes(&proof, false);
+        assert_eq!(bytes.len(), GNARK_PROOF_SIZE);
+
+        // G1 generator (1, 2) in big-endian
+        assert_eq!(bytes[31], 1);
+        assert_eq!(bytes[63], 2);
+        assert!(bytes[..31].iter().all(|b| *b == 0));
+
+        // G2 coordinates as `A1 | A0`
+        assert_eq!(bytes[64..96], fq_to_be(&g2.x.c1));
+        assert_eq!(bytes[96..128], fq_to_be(&g2.x.c0));
+        assert_eq!(bytes[128..160], fq_to_be(&g2.y.c1));
+        assert_eq!(bytes[160..192], fq_to_be(&g2.y.c0));
+
+        // Compressed: y = 2 is the smallest root, -2 the largest
+        let bytes = proof_to_gnark_bytes(&proof, true);
+        assert_eq!(bytes.len(), GNARK_COMPRESSED_PROOF_SIZE);
+        assert_eq!(bytes[0], GNARK_COMPRESSED_SMALLEST);
+        assert_eq!(bytes[96], GNARK_COMPRESSED_LARGEST);
+        assert_eq!(bytes[127], 1);
+    }
+
+    #[test]
+    fn test_gnark_proof() {
+        for test_proof in valid_proofs() {
+            for compressed in [false, true] {
+                let bytes = proof_to_gnark_bytes(&test_proof.proof, compressed);
+                assert_eq!(proof_from_gnark_bytes(&bytes).unwrap(), test_proof.proof);
+            }
+        }
+
+        // Infinity points
+        let proof = Proof {
+            a: G1A(G1Affine::zero()),
+            b: G2A(G2Affine::zero()),
+            c: G1A(G1Affine::zero()),
+        };
+        for compressed in [false, true] {
+            let bytes = proof_to_gnark_bytes(&proof, compressed);
+            assert_eq!(proof_from_gnark_bytes(&bytes).unwrap(), proof);
+        }
+    }
+
+    #[test]
+    fn test_gnark_proof_commitments() {
+        let proof = valid_proofs()[0].proof;
+
+        for compressed in [false, true] {
+            let mut bytes = proof_to_gnark_bytes(&proof, compressed);
+            bytes.extend(0u32.to_be_bytes());
+            if compressed {
+                bytes.extend(gnark_g1_compressed_bytes(&G1Affine::zero()));
+            } else {
+                bytes.extend(gnark_g1_bytes(&G1Affine::zero()));
+            }
+            assert_eq!(proof_from_gnark_bytes(&bytes).unwrap(), proof);
+        }
+
+        // Non-empty commitments
+        let mut bytes = proof_to_gnark_bytes(&proof, false);
+        bytes.extend(1u32.to_be_bytes());
+        bytes.extend(gnark_g1_bytes(&G1Affine::prime_subgroup_generator()));
+        bytes.extend(gnark_g1_bytes(&G1Affine::prime_subgroup_generator()));
+        assert_eq!(
+            proof_from_gnark_bytes(&bytes),
+            Err(ProofConversionError::UnsupportedCommitments)
+        );
+
+        // Non-infinity proof-of-knowledge
+        let mut bytes = proof_to_gnark_bytes(&proof, false);
+        bytes.extend(0u32.to_be_bytes());
+        bytes.extend(gnark_g1_bytes(&G1Affine::prime_subgroup_generator()));
+        assert_eq!(
+            proof_from_gnark_bytes(&bytes),
+            Err(ProofConversionError::UnsupportedCommitments)
+        );
+    }
+
+    #[test]
+    fn test_gnark_proof_invalid() {
+        let proof = valid_proofs()[0].proof;
+
+        assert_eq!(
+            proof_from_gnark_bytes(&[0; GNARK_COMPRESSED_PROOF_SIZE - 1]),
+            Err(ProofConversionError::InvalidLength)
+        );
+
+        // Point not on the curve
+        let mut bytes = proof_to_gnark_bytes(&proof, false);
+        bytes[63] ^= 1;
+        assert_eq!(
+            proof_from_gnark_bytes(&bytes),
+            Err(ProofConversionError::InvalidPoint)
+        );
+
+        // Coordinate exceeding the base field modulus
+        let mut bytes = proof_to_gnark_bytes(&proof, false);
+        bytes[32..64].copy_from_slice(&[u8::MAX; 32]);
+        assert_eq!(
+            proof_from_gnark_bytes(&bytes),
+            Err(ProofConversionError::InvalidFieldElement)
+        );
+
+        // Infinity flag with non-zero coordinates
+        let mut bytes = proof_to_gnark_bytes(&proof, false);
+        bytes[0] |= GNARK_UNCOMPRESSED_INFINITY;
+        assert_eq!(
+            proof_from_gnark_bytes(&bytes),
+            Err(ProofConversionError::InvalidPoint)
+        );
+
+        // G2 point not in the subgroup
+        let mut x = Fq2::from(1u64);
+        let b = loop {
+            if let Some(p) = G2Affine::get_point_from_x(x, false) {
+                if !p.is_in_correct_subgroup_assuming_on_curve() {
+                    break p;
+                }
+            }
+            x += Fq2::from(1u64);
+        };
+        let invalid_proof = Proof {
+            a: proof.a,
+            b: G2A(b),
+            c: proof.c,
+        };
+        for compressed in [false, true] {
+            let bytes = proof_to_gnark_bytes(&invalid_proof, compressed);
+            assert_eq!(
+                proof_from_gnark_bytes(&bytes),
+                Err(ProofConversionError::InvalidPoint)
+            );
+        }
+    }
+
+    #[test]
+    fn test_gnark_public_witness() {
+        let signals = test_signals();
+        let bytes = public_signals_to_gnark_witness(&signals).unwrap();
+        assert_eq!(bytes.len(), 12 + signals.len() * 32);
+        assert_eq!(bytes[..4], (signals.len() as u32).to_be_bytes());
+
+        // 120000 in big-endian
+        let element = &bytes[12 + 8 * 32..12 + 9 * 32];
+        assert_eq!(element[29..], [0x01, 0xd4, 0xc0]);
+
+        assert_eq!(public_signals_from_gnark_witness(&bytes).unwrap(), signals);
+
+        // Secret values
+        let mut invalid = bytes.clone();
+        invalid[7] = 1;
+        assert_eq!(
+            public_signals_from_gnark_witness(&invalid),
+            Err(ProofConversionError::InvalidLength)
+        );
+
+        // Scalar field modulus
+        let mut invalid = bytes.clone();
+        invalid[12..44].copy_from_slice(&[u8::MAX; 32]);
+        assert_eq!(
+            public_signals_from_gnark_witness(&invalid),
+            Err(ProofConversionError::InvalidFieldElement)
+        );
+
+        assert_eq!(
+            public_signals_from_gnark_witness(&bytes[..bytes.len() - 1]),
+            Err(ProofConversionError::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,686 @@
+//! Client-side deployment of verifying keys
+//!
+//! A deployment is driven by repeatedly fetching the on-chain accounts and executing the next [`VKeyDeploymentStep`].
+//! Since each step is derived from the on-chain state only, a deployment can be resumed at any point after a partial failure.
+
+use crate::bytes::div_ceiling_usize;
+use crate::instruction::{
+    ElusivInstruction, SignerAccount, WritableSignerAccount, WritableUserAccount,
+};
+use crate::processor::{VKeyAccountDataPacket, VKEY_ACCOUNT_DATA_PACKET_SIZE};
+use crate::state::vkey::{ProofSystem, VKeyAccountEager, VKeyAction};
+use elusiv_types::{BorshSerDeSized, ChildAccountConfig, EagerAccountRepr, EagerParentAccountRepr};
+use solana_program::{
+    hash::{hash, Hash},
+    instruction::Instruction,
+    pubkey::Pubkey,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum VKeyDeploymentError {
+    /// The [`crate::state::vkey::VKeyAccount`] does not match the deployment
+    InvalidVKeyAccount,
+
+    /// The deployment authority is not allowed to modify the [`crate::state::vkey::VKeyAccount`]
+    InvalidAuthority,
+
+    /// The [`crate::state::vkey::VKeyAccount`] is frozen with a different verifying key
+    Frozen,
+
+    /// The binary data account of the new version is missing or has an invalid size
+    InvalidBinaryDataAccount,
+
+    /// The uploaded data does not match the verifying key source
+    DataHashMismatch,
+
+    /// The previous version is still used by open verifications
+    PreviousVersionInUse,
+}
+
+/// The next step of a [`VKeyDeployment`]
+#[derive(Debug)]
+pub enum VKeyDeploymentStep {
+    CreateVkeyAccount(Instruction),
+
+    /// A program-owned binary data account of `size` bytes has to be created and registered using [`VKeyDeployment::create_new_vkey_version_instruction`]
+    CreateNewVkeyVersion {
+        size: usize,
+    },
+
+    /// Uploads all packets not yet present on-chain (the instructions are independent of each other)
+    SetVkeyData(Vec<Instruction>),
+
+    /// The uploaded version awaits the approval of the registered signers
+    AwaitApprovals(PendingVKeyVersion),
+
+    /// Activates the uploaded version (if signers are registered, only after the `unlock_timestamp` of the [`PendingVKeyVersion`])
+    UpdateVkeyVersion(Instruction),
+
+    /// Closes the previous version, which is not used by any verification anymore
+    ClosePreviousVkeyVersion(Instruction),
+
+    /// Freezes the [`crate::state::vkey::VKeyAccount`] (if signers are registered, only after they approved [`VKeyAction::Freeze`])
+    FreezeVkey(Instruction),
+    Done,
+}
+
+/// A pending version of a [`crate::state::vkey::VKeyAccount`]
+#[derive(Debug, PartialEq)]
+pub struct PendingVKeyVersion {
+    pub binary_data_account: Pubkey,
+
+    /// The registered signers that approved the version
+    pub approvals: Vec<Pubkey>,
+    pub threshold: u8,
+    pub unlock_timestamp: Option<u64>,
+}
+
+impl PendingVKeyVersion {
+    /// Returns the pending version of a [`crate::state::vkey::VKeyAccount`] (if existing)
+    pub fn new(vkey_account: &[u8]) -> Result<Option<Self>, VKeyDeploymentError> {
+        let vkey_account = VKeyAccountEager::new(vkey_account.to_vec())
+            .or(Err(VKeyDeploymentError::InvalidVKeyAccount))?;
+        Ok(Self::from_eager(&vkey_account))
+    }
+
+    fn from_eager(vkey_account: &VKeyAccountEager) -> Option<Self> {
+        let binary_data_account = vkey_account.pubkeys[1].option()?;
+
+        // Approvals of a different action do not apply to the version
+        let approvals = if vkey_account.pending_action == VKeyAction::ActivateVersion {
+            vkey_account
+                .signers
+                .iter()
+                .zip(vkey_account.approvals)
+                .filter_map(|(signer, approved)| signer.option().filter(|_| approved))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Some(Self {
+            binary_data_account,
+            approvals,
+            threshold: vkey_account.threshold,
+            unlock_timestamp: vkey_account.unlock_timestamp.option(),
+        })
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.approvals.len() >= self.threshold as usize
+    }
+}
+
+/// The on-chain account data relevant to a [`VKeyDeployment`]
+#[derive(Default)]
+pub struct VKeyDeploymentAccounts<'a> {
+    pub vkey_account: Option<&'a [u8]>,
+
+    /// The binary data account of the active version (child-account `0`)
+    pub active_binary_data_account: Option<&'a [u8]>,
+
+    /// The binary data account of the new version (child-account `1`)
+    pub new_binary_data_account: Option<&'a [u8]>,
+}
+
+/// Deploys a verifying key source to a [`crate::state::vkey::VKeyAccount`]
+pub struct VKeyDeployment {
+    pub vkey_id: u32,
+    pub public_inputs_count: u32,
+    pub proof_system: ProofSystem,
+
+    /// The signer and deploy authority
+    pub authority: Pubkey,
+
+    /// Freezes the [`crate::state::vkey::VKeyAccount`] after the deployment
+    pub freeze: bool,
+
+    source: Vec<u8>,
+    source_hash: Hash,
+}
+
+impl VKeyDeployment {
+    pub fn new(
+        vkey_id: u32,
+        public_inputs_count: u32,
+        proof_system: ProofSystem,
+        authority: Pubkey,
+        freeze: bool,
+        source: Vec<u8>,
+    ) -> Self {
+        let source_hash = hash(&source);
+
+        Self {
+            vkey_id,
+            public_inputs_count,
+            proof_system,
+            authority,
+            freeze,
+            source,
+            source_hash,
+        }
+    }
+
+    pub fn source(&self) -> &[u8] {
+        &self.source
+    }
+
+    /// The size of the binary data account (including the [`ChildAccountConfig`])
+    pub fn binary_data_account_size(&self) -> usize {
+        self.source.len() + ChildAccountConfig::SIZE
+    }
+
+    pub fn packet_count(&self) -> usize {
+        div_ceiling_usize(self.source.len(), VKEY_ACCOUNT_DATA_PACKET_SIZE)
+    }
+
+    /// The active and new binary data accounts of a [`crate::state::vkey::VKeyAccount`]
+    pub fn binary_data_accounts(
+        vkey_account: &[u8],
+    ) -> Result<(Option<Pubkey>, Option<Pubkey>), VKeyDeploymentError> {
+        let vkey_account = VKeyAccountEager::new(vkey_account.to_vec())
+            .or(Err(VKeyDeploymentError::InvalidVKeyAccount))?;
+        let pubkeys = vkey_account.child_pubkeys();
+
+        Ok((pubkeys[0], pubkeys[1]))
+    }
+
+    /// Checks whether a binary data account contains the verifying key source
+    pub fn is_uploaded(&self, binary_data_account: &[u8]) -> bool {
+        binary_data_account.len() == self.binary_data_account_size()
+            && hash(&binary_data_account[ChildAccountConfig::SIZE..]) == self.source_hash
+    }
+
+    /// Computes the next step based on the current on-chain state
+    pub fn next_step(
+        &self,
+        accounts: &VKeyDeploymentAccounts,
+    ) -> Result<VKeyDeploymentStep, VKeyDeploymentError> {
+        let vkey_account = match accounts.vkey_account {
+            Some(data) => VKeyAccountEager::new(data.to_vec())
+                .or(Err(VKeyDeploymentError::InvalidVKeyAccount))?,
+            None => {
+                return Ok(VKeyDeploymentStep::CreateVkeyAccount(
+                    ElusivInstruction::create_vkey_account_instruction(
+                        self.vkey_id,
+                        self.public_inputs_count,
+                        self.proof_system,
+                        Some(self.authority).into(),
+                        WritableSignerAccount(self.authority),
+                    ),
+                ))
+            }
+        };
+
+        if vkey_account.public_inputs_count != self.public_inputs_count
+            || vkey_account.proof_system != self.proof_system
+        {
+            return Err(VKeyDeploymentError::InvalidVKeyAccount);
+        }
+
+        // The source has already been deployed
+        let pubkeys = vkey_account.child_pubkeys();
+        if pubkeys[0].is_some()
+            && accounts
+                .active_binary_data_account
+                .map_or(false, |data| self.is_uploaded(data))
+        {
+            if let Some(previous) = pubkeys[2] {
+                if vkey_account.previous_verifications == 0 {
+                    self.check_authority(&vkey_account)?;
+                    return Ok(VKeyDeploymentStep::ClosePreviousVkeyVersion(
+                        ElusivInstruction::close_previous_vkey_version_instruction(
+                            self.vkey_id,
+                            WritableSignerAccount(self.authority),
+                            WritableUserAccount(previous),
+                        ),
+                    ));
+                }
+            }
+
+            if self.freeze && !vkey_account.is_frozen {
+                self.check_authority(&vkey_account)?;
+                return Ok(VKeyDeploymentStep::FreezeVkey(
+                    ElusivInstruction::freeze_vkey_instruction(
+                        self.vkey_id,
+                        SignerAccount(self.authority),
+                    ),
+                ));
+            }
+
+            return Ok(VKeyDeploymentStep::Done);
+        }
+
+        if vkey_account.is_frozen {
+            return Err(VKeyDeploymentError::Frozen);
+        }
+        self.check_authority(&vkey_account)?;
+
+        let new_binary_data_account = match pubkeys[1] {
+            Some(pubkey) => pubkey,
+            None => {
+                return Ok(VKeyDeploymentStep::CreateNewVkeyVersion {
+                    size: self.binary_data_account_size(),
+                })
+            }
+        };
+
+        let data = accounts
+            .new_binary_data_account
+            .ok_or(VKeyDeploymentError::InvalidBinaryDataAccount)?;
+        if data.len() != self.binary_data_account_size() {
+            return Err(VKeyDeploymentError::InvalidBinaryDataAccount);
+        }
+
+        // The version is only switched after the hash of the uploaded data has been verified
+        if self.is_uploaded(data) {
+            if let Some(pending) = PendingVKeyVersion::from_eager(&vkey_account) {
+                if !pending.is_approved() {
+                    return Ok(VKeyDeploymentStep::AwaitApprovals(pending));
+                }
+            }
+
+            // The previous version is closed when activating the new version
+            if pubkeys[2].is_some() && vkey_account.previous_verifications > 0 {
+                return Err(VKeyDeploymentError::PreviousVersionInUse);
+            }
+
+            return Ok(VKeyDeploymentStep::UpdateVkeyVersion(
+                ElusivInstruction::update_vkey_version_instruction(
+                    self.vkey_id,
+                    WritableSignerAccount(self.authority),
+                    WritableUserAccount(pubkeys[2].unwrap_or(self.authority)),
+                ),
+            ));
+        }
+
+        let missing_packets: Vec<usize> = (0..self.packet_count())
+            .filter(|&i| self.packet(i) != Self::packet_range(&data[ChildAccountConfig::SIZE..], i))
+            .collect();
+
+        if missing_packets.is_empty() {
+            return Err(VKeyDeploymentError::DataHashMismatch);
+        }
+
+        Ok(VKeyDeploymentStep::SetVkeyData(
+            missing_packets
+                .into_iter()
+                .map(|i| self.set_vkey_data_instruction(i, new_binary_data_account))
+                .collect(),
+        ))
+    }
+
+    /// Registers `binary_data_account` as the binary data account of a new version
+    ///
+    /// # Notes
+    ///
+    /// The account needs to be owned by the program and be of size [`Self::binary_data_account_size`].
+    pub fn create_new_vkey_version_instruction(&self, binary_data_account: Pubkey) -> Instruction {
+        ElusivInstruction::create_new_vkey_version_instruction(
+            self.vkey_id,
+            SignerAccount(self.authority),
+            WritableUserAccount(binary_data_account),
+        )
+    }
+
+    pub fn set_vkey_data_instruction(
+        &self,
+        data_position: usize,
+        binary_data_account: Pubkey,
+    ) -> Instruction {
+        ElusivInstruction::set_vkey_data_instruction(
+            self.vkey_id,
+            data_position as u32,
+            VKeyAccountDataPacket(self.packet(data_position).to_vec()),
+            SignerAccount(self.authority),
+            &[WritableUserAccount(binary_data_account)],
+        )
+    }
+
+    fn packet(&self, data_position: usize) -> &[u8] {
+        Self::packet_range(&self.source, data_position)
+    }
+
+    fn packet_range(data: &[u8], data_position: usize) -> &[u8] {
+        let start = data_position * VKEY_ACCOUNT_DATA_PACKET_SIZE;
+        let end = std::cmp::min(start + VKEY_ACCOUNT_DATA_PACKET_SIZE, data.len());
+        &data[start..end]
+    }
+
+    fn check_authority(&self, vkey_account: &VKeyAccountEager) -> Result<(), VKeyDeploymentError> {
+        match vkey_account.authority.option() {
+            Some(authority) if authority != self.authority => {
+                Err(VKeyDeploymentError::InvalidAuthority)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{
+        program_account::PDAAccountData,
+        vkey::{VKeyAccount, MAX_VKEY_SIGNERS},
+    };
+    use assert_matches::assert_matches;
+    use borsh::BorshSerialize;
+    use elusiv_types::{ElusivOption, SizedAccount};
+
+    fn deployment(authority: Pubkey, freeze: bool) -> VKeyDeployment {
+        let source = (0..VKEY_ACCOUNT_DATA_PACKET_SIZE * 3 + 100)
+            .map(|i| i as u8)
+            .collect();
+        VKeyDeployment::new(3, 14, ProofSystem::Plonk, authority, freeze, source)
+    }
+
+    fn vkey_account(
+        pubkeys: [Option<Pubkey>; 3],
+        authority: Option<Pubkey>,
+        is_frozen: bool,
+    ) -> Vec<u8> {
+        let data = VKeyAccountEager {
+            pda_data: PDAAccountData {
+                bump_seed: 0,
+                version: 0,
+            },
+            pubkeys: pubkeys.map(ElusivOption::from),
+            public_inputs_count: 14,
+            authority: authority.into(),
+            is_frozen,
+            version: 0,
+            proof_system: ProofSystem::Plonk,
+            active_verifications: 0,
+            previous_verifications: 0,
+            signers: [ElusivOption::None; MAX_VKEY_SIGNERS],
+            threshold: 0,
+            pending_action: VKeyAction::ActivateVersion,
+            approvals: [false; MAX_VKEY_SIGNERS],
+            unlock_timestamp: ElusivOption::None,
+        }
+        .try_to_vec()
+        .unwrap();
+        assert_eq!(data.len(), VKeyAccount::SIZE);
+        data
+    }
+
+    fn binary_data_account(source: &[u8]) -> Vec<u8> {
+        let mut data = vec![1];
+        data.extend(source);
+        data
+    }
+
+    #[test]
+    fn test_create_steps() {
+        let authority = Pubkey::new_unique();
+        let deployment = deployment(authority, false);
+        assert_eq!(deployment.packet_count(), 4);
+
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts::default()),
+            Ok(VKeyDeploymentStep::CreateVkeyAccount(_))
+        );
+
+        let vkey = vkey_account([None, None, None], Some(authority), false);
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                ..Default::default()
+            }),
+            Ok(VKeyDeploymentStep::CreateNewVkeyVersion { size }) if size == deployment.binary_data_account_size()
+        );
+
+        // Invalid authority
+        let vkey = vkey_account([None, None, None], Some(Pubkey::new_unique()), false);
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                ..Default::default()
+            }),
+            Err(VKeyDeploymentError::InvalidAuthority)
+        );
+
+        // Frozen
+        let vkey = vkey_account([None, None, None], Some(authority), true);
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                ..Default::default()
+            }),
+            Err(VKeyDeploymentError::Frozen)
+        );
+
+        // Mismatching proof system
+        let deployment = VKeyDeployment::new(
+            3,
+            14,
+            ProofSystem::Groth16,
+            authority,
+            false,
+            deployment.source,
+        );
+        let vkey = vkey_account([None, None, None], Some(authority), false);
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                ..Default::default()
+            }),
+            Err(VKeyDeploymentError::InvalidVKeyAccount)
+        );
+    }
+
+    #[test]
+    fn test_upload_steps() {
+        let authority = Pubkey::new_unique();
+        let new_account = Pubkey::new_unique();
+        let deployment = deployment(authority, true);
+        let vkey = vkey_account([None, Some(new_account), None], None, false);
+
+        // Missing binary data account
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                ..Default::default()
+            }),
+            Err(VKeyDeploymentError::InvalidBinaryDataAccount)
+        );
+
+        // Partially uploaded data
+        let mut data = binary_data_account(&vec![0; deployment.source().len()]);
+        let offset = ChildAccountConfig::SIZE + VKEY_ACCOUNT_DATA_PACKET_SIZE;
+        data[offset..offset + VKEY_ACCOUNT_DATA_PACKET_SIZE].copy_from_slice(deployment.packet(1));
+        let step = deployment
+            .next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                new_binary_data_account: Some(&data),
+                ..Default::default()
+            })
+            .unwrap();
+
+        match step {
+            VKeyDeploymentStep::SetVkeyData(ixs) => {
+                let expected: Vec<Instruction> = [0, 2, 3]
+                    .iter()
+                    .map(|&i| deployment.set_vkey_data_instruction(i, new_account))
+                    .collect();
+                assert_eq!(ixs, expected);
+            }
+            _ => panic!(),
+        }
+
+        // Uploaded data
+        let data = binary_data_account(deployment.source());
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                new_binary_data_account: Some(&data),
+                ..Default::default()
+            }),
+            Ok(VKeyDeploymentStep::UpdateVkeyVersion(_))
+        );
+
+        // Registered signers
+        let signers = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let mut eager = VKeyAccountEager::new(vkey.clone()).unwrap();
+        for (i, signer) in signers.iter().enumerate() {
+            eager.signers[i] = Some(*signer).into();
+        }
+        eager.threshold = 2;
+        eager.approvals[1] = true;
+        let multisig_vkey = eager.try_to_vec().unwrap();
+
+        assert_eq!(
+            PendingVKeyVersion::new(&multisig_vkey).unwrap(),
+            Some(PendingVKeyVersion {
+                binary_data_account: new_account,
+                approvals: vec![signers[1]],
+                threshold: 2,
+                unlock_timestamp: None,
+            })
+        );
+
+        // Approvals of a different action
+        let mut freeze_eager = VKeyAccountEager::new(multisig_vkey.clone()).unwrap();
+        freeze_eager.pending_action = VKeyAction::Freeze;
+        assert_matches!(
+            PendingVKeyVersion::new(&freeze_eager.try_to_vec().unwrap()),
+            Ok(Some(pending)) if pending.approvals.is_empty()
+        );
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&multisig_vkey),
+                new_binary_data_account: Some(&data),
+                ..Default::default()
+            }),
+            Ok(VKeyDeploymentStep::AwaitApprovals(pending)) if !pending.is_approved()
+        );
+
+        eager.approvals[2] = true;
+        eager.unlock_timestamp = Some(100).into();
+        let multisig_vkey = eager.try_to_vec().unwrap();
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&multisig_vkey),
+                new_binary_data_account: Some(&data),
+                ..Default::default()
+            }),
+            Ok(VKeyDeploymentStep::UpdateVkeyVersion(_))
+        );
+
+        // Invalid binary data account size
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                new_binary_data_account: Some(&data[1..]),
+                ..Default::default()
+            }),
+            Err(VKeyDeploymentError::InvalidBinaryDataAccount)
+        );
+
+        // Active version
+        let vkey = vkey_account([Some(new_account), None, None], None, false);
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                active_binary_data_account: Some(&data),
+                ..Default::default()
+            }),
+            Ok(VKeyDeploymentStep::FreezeVkey(_))
+        );
+
+        let vkey = vkey_account([Some(new_account), None, None], None, true);
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                active_binary_data_account: Some(&data),
+                ..Default::default()
+            }),
+            Ok(VKeyDeploymentStep::Done)
+        );
+
+        // Outdated active version
+        let outdated = binary_data_account(&vec![0; deployment.source().len()]);
+        let vkey = vkey_account([Some(new_account), None, None], None, false);
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&vkey),
+                active_binary_data_account: Some(&outdated),
+                ..Default::default()
+            }),
+            Ok(VKeyDeploymentStep::CreateNewVkeyVersion { .. })
+        );
+    }
+
+    #[test]
+    fn test_previous_version_steps() {
+        let authority = Pubkey::new_unique();
+        let active_account = Pubkey::new_unique();
+        let new_account = Pubkey::new_unique();
+        let previous_account = Pubkey::new_unique();
+        let deployment = deployment(authority, false);
+        let data = binary_data_account(deployment.source());
+
+        // Previous version still in use
+        let vkey = vkey_account(
+            [
+                Some(active_account),
+                Some(new_account),
+                Some(previous_account),
+            ],
+            None,
+            false,
+        );
+        let mut eager = VKeyAccountEager::new(vkey.clone()).unwrap();
+        eager.previous_verifications = 1;
+        let used_vkey = eager.try_to_vec().unwrap();
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&used_vkey),
+                new_binary_data_account: Some(&data),
+                ..Default::default()
+            }),
+            Err(VKeyDeploymentError::PreviousVersionInUse)
+        );
+
+        // The previous version is closed on activation
+        match deployment.next_step(&VKeyDeploymentAccounts {
+            vkey_account: Some(&vkey),
+            new_binary_data_account: Some(&data),
+            ..Default::default()
+        }) {
+            Ok(VKeyDeploymentStep::UpdateVkeyVersion(ix)) => {
+                assert_eq!(ix.accounts[2].pubkey, previous_account)
+            }
+            _ => panic!(),
+        }
+
+        // Close the unused previous version after activation
+        let vkey = vkey_account([Some(new_account), None, Some(active_account)], None, true);
+        match deployment.next_step(&VKeyDeploymentAccounts {
+            vkey_account: Some(&vkey),
+            active_binary_data_account: Some(&data),
+            ..Default::default()
+        }) {
+            Ok(VKeyDeploymentStep::ClosePreviousVkeyVersion(ix)) => {
+                assert_eq!(ix.accounts[2].pubkey, active_account)
+            }
+            _ => panic!(),
+        }
+
+        let mut eager = VKeyAccountEager::new(vkey).unwrap();
+        eager.previous_verifications = 1;
+        assert_matches!(
+            deployment.next_step(&VKeyDeploymentAccounts {
+                vkey_account: Some(&eager.try_to_vec().unwrap()),
+                active_binary_data_account: Some(&data),
+                ..Default::default()
+            }),
+            Ok(VKeyDeploymentStep::Done)
+        );
+    }
+}
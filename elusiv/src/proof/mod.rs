@@ -1,3 +1,9 @@
+pub mod alt_bn128;
+#[cfg(feature = "elusiv-client")]
+pub mod convert;
+#[cfg(feature = "elusiv-client")]
+pub mod deploy;
+pub mod plonk;
 pub mod verifier;
 pub mod vkey;
 
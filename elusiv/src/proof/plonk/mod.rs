@@ -0,0 +1,5 @@
+pub mod verifier;
+pub mod vkey;
+
+#[cfg(test)]
+mod test_prover;
@@ -0,0 +1,346 @@
+//! Minimal PLONK prover (without blinding factors) used to generate test proofs for the verifier
+
+use super::verifier::{plonk_challenges, PlonkChallenges};
+use super::vkey::{plonk_verifying_key_source, PlonkVerifyingKey};
+use crate::fields::{fr_to_u256_le_repr, G1A};
+use crate::types::{PlonkProof, RawU256};
+use ark_bn254::{Fr, G1Affine, G2Affine};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{FftField, Field, One, PrimeField, UniformRand, Zero};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+pub const POWER: u32 = 5;
+const N: usize = 1 << POWER;
+
+pub struct PlonkTestProof {
+    pub vkey_source: Vec<u8>,
+    pub proof: PlonkProof,
+    pub public_inputs: Vec<Fr>,
+    pub x_2: G2Affine,
+}
+
+/// Proves the knowledge of `x` and `t` with `x * x * t = public_inputs[0]`
+/// - all other public inputs are unconstrained
+/// - the circuit additionally contains a constant gate
+pub fn plonk_test_proof(public_inputs: &[Fr], seed: u64) -> PlonkTestProof {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let tau = Fr::rand(&mut rng);
+    let omega = Fr::get_root_of_unity(N).unwrap();
+    let k = [Fr::one(), Fr::from(2u64), Fr::from(3u64)];
+    let domain: Vec<Fr> = (0..N).map(|i| omega.pow([i as u64])).collect();
+
+    // Witness and selectors
+    let m = public_inputs.len();
+    let mut wires = [
+        vec![Fr::zero(); N],
+        vec![Fr::zero(); N],
+        vec![Fr::zero(); N],
+    ];
+    let mut selectors = [
+        vec![Fr::zero(); N],
+        vec![Fr::zero(); N],
+        vec![Fr::zero(); N],
+        vec![Fr::zero(); N],
+        vec![Fr::zero(); N],
+    ];
+    let (q_m, q_l, q_o, q_c) = (0, 1, 3, 4);
+
+    for (i, w) in public_inputs.iter().enumerate() {
+        wires[0][i] = *w;
+        selectors[q_l][i] = Fr::one();
+    }
+
+    let x = Fr::rand(&mut rng);
+    let x_sq = x.square();
+    let t = public_inputs[0] / x_sq;
+    for (row, (a, b, c)) in [(x, x, x_sq), (x_sq, t, public_inputs[0])]
+        .iter()
+        .enumerate()
+    {
+        wires[0][m + row] = *a;
+        wires[1][m + row] = *b;
+        wires[2][m + row] = *c;
+        selectors[q_m][m + row] = Fr::one();
+        selectors[q_o][m + row] = -Fr::one();
+    }
+
+    let constant = Fr::from(5u64);
+    wires[0][m + 2] = constant;
+    selectors[q_l][m + 2] = Fr::one();
+    selectors[q_c][m + 2] = -constant;
+
+    // Copy constraints as cycles of `(wire, row)`
+    let cycles = [
+        vec![(0, m), (1, m)],
+        vec![(2, m), (0, m + 1)],
+        vec![(2, m + 1), (0, 0)],
+    ];
+    let mut sigma: Vec<Vec<Fr>> = (0..3)
+        .map(|j| domain.iter().map(|w| k[j] * w).collect())
+        .collect();
+    for cycle in cycles {
+        for (i, &(wire, row)) in cycle.iter().enumerate() {
+            let (next_wire, next_row) = cycle[(i + 1) % cycle.len()];
+            sigma[wire][row] = k[next_wire] * domain[next_row];
+        }
+    }
+
+    // Preprocessed polynomials and the verifying key
+    let q: Vec<Vec<Fr>> = selectors.iter().map(|s| interpolate(s, omega)).collect();
+    let s: Vec<Vec<Fr>> = sigma.iter().map(|s| interpolate(s, omega)).collect();
+    let commitments =
+        [&q[0], &q[1], &q[2], &q[3], &q[4], &s[0], &s[1], &s[2]].map(|p| commit(p, tau).0);
+    let x_2 = G2Affine::prime_subgroup_generator()
+        .mul(tau.into_repr())
+        .into_affine();
+    let vkey_source = plonk_verifying_key_source(POWER, k[1], k[2], omega, &commitments, x_2);
+    let vkey = PlonkVerifyingKey::new(&vkey_source, m).unwrap();
+
+    // Round 1: wire commitments
+    let a = interpolate(&wires[0], omega);
+    let b = interpolate(&wires[1], omega);
+    let c = interpolate(&wires[2], omega);
+
+    let mut proof = PlonkProof {
+        a: commit(&a, tau),
+        b: commit(&b, tau),
+        c: commit(&c, tau),
+        z: G1A(G1Affine::zero()),
+        t1: G1A(G1Affine::zero()),
+        t2: G1A(G1Affine::zero()),
+        t3: G1A(G1Affine::zero()),
+        w_xi: G1A(G1Affine::zero()),
+        w_xiw: G1A(G1Affine::zero()),
+        eval_a: RawU256::new([0; 32]),
+        eval_b: RawU256::new([0; 32]),
+        eval_c: RawU256::new([0; 32]),
+        eval_s1: RawU256::new([0; 32]),
+        eval_s2: RawU256::new([0; 32]),
+        eval_zw: RawU256::new([0; 32]),
+    };
+
+    // Challenges only depend on the previous rounds
+    let challenges = |proof: &PlonkProof| plonk_challenges(&vkey, proof, public_inputs).unwrap();
+
+    // Round 2: permutation grand product
+    let PlonkChallenges { beta, gamma, .. } = challenges(&proof);
+    let mut z_evals = vec![Fr::one(); N + 1];
+    for i in 0..N {
+        let mut numerator = Fr::one();
+        let mut denominator = Fr::one();
+        for j in 0..3 {
+            numerator *= wires[j][i] + beta * k[j] * domain[i] + gamma;
+            denominator *= wires[j][i] + beta * sigma[j][i] + gamma;
+        }
+        z_evals[i + 1] = z_evals[i] * numerator / denominator;
+    }
+    assert_eq!(z_evals[N], Fr::one());
+    let z = interpolate(&z_evals[..N], omega);
+    proof.z = commit(&z, tau);
+
+    // Round 3: quotient polynomial
+    let PlonkChallenges { alpha, .. } = challenges(&proof);
+    let mut pi_evals = vec![Fr::zero(); N];
+    for (i, w) in public_inputs.iter().enumerate() {
+        pi_evals[i] = -*w;
+    }
+    let pi = interpolate(&pi_evals, omega);
+    let mut l1_evals = vec![Fr::zero(); N];
+    l1_evals[0] = Fr::one();
+    let l1 = interpolate(&l1_evals, omega);
+
+    let gate = add(
+        &add(
+            &add(&mul(&mul(&a, &b), &q[0]), &mul(&a, &q[1])),
+            &add(&mul(&b, &q[2]), &mul(&c, &q[3])),
+        ),
+        &add(&q[4], &pi),
+    );
+    let id_term = |p: &[Fr], k: Fr| add(p, &[gamma, beta * k]);
+    let sigma_term = |p: &[Fr], s: &[Fr]| add(&add(p, &scale(s, beta)), &[gamma]);
+    let permutation_0 = mul(
+        &mul(
+            &mul(&id_term(&a, k[0]), &id_term(&b, k[1])),
+            &id_term(&c, k[2]),
+        ),
+        &z,
+    );
+    let permutation_1 = mul(
+        &mul(
+            &mul(&sigma_term(&a, &s[0]), &sigma_term(&b, &s[1])),
+            &sigma_term(&c, &s[2]),
+        ),
+        &shift(&z, omega),
+    );
+    let permutation_start = mul(&sub(&z, &[Fr::one()]), &l1);
+    let numerator = add(
+        &add(&gate, &scale(&sub(&permutation_0, &permutation_1), alpha)),
+        &scale(&permutation_start, alpha.square()),
+    );
+    let t = div_by_vanishing_polynomial(&numerator);
+    assert!(t.len() <= 3 * N);
+    let t_parts: Vec<Vec<Fr>> = (0..3)
+        .map(|i| t.iter().skip(i * N).take(N).cloned().collect())
+        .collect();
+    proof.t1 = commit(&t_parts[0], tau);
+    proof.t2 = commit(&t_parts[1], tau);
+    proof.t3 = commit(&t_parts[2], tau);
+
+    // Round 4: evaluations
+    let PlonkChallenges { xi, .. } = challenges(&proof);
+    let eval_a = evaluate(&a, xi);
+    let eval_b = evaluate(&b, xi);
+    let eval_c = evaluate(&c, xi);
+    let eval_s1 = evaluate(&s[0], xi);
+    let eval_s2 = evaluate(&s[1], xi);
+    let eval_zw = evaluate(&z, xi * omega);
+    proof.eval_a = RawU256::new(fr_to_u256_le_repr(&eval_a));
+    proof.eval_b = RawU256::new(fr_to_u256_le_repr(&eval_b));
+    proof.eval_c = RawU256::new(fr_to_u256_le_repr(&eval_c));
+    proof.eval_s1 = RawU256::new(fr_to_u256_le_repr(&eval_s1));
+    proof.eval_s2 = RawU256::new(fr_to_u256_le_repr(&eval_s2));
+    proof.eval_zw = RawU256::new(fr_to_u256_le_repr(&eval_zw));
+
+    // Round 5: linearisation and opening proofs
+    let PlonkChallenges { v, .. } = challenges(&proof);
+    let xi_n = xi.pow([N as u64]);
+    let zh = xi_n - Fr::one();
+    let l1_xi = evaluate(&l1, xi);
+    let e1 = eval_a + beta * eval_s1 + gamma;
+    let e2 = eval_b + beta * eval_s2 + gamma;
+    let r0 =
+        evaluate(&pi, xi) - l1_xi * alpha.square() - alpha * e1 * e2 * (eval_c + gamma) * eval_zw;
+
+    let r = [
+        scale(&q[0], eval_a * eval_b),
+        scale(&q[1], eval_a),
+        scale(&q[2], eval_b),
+        scale(&q[3], eval_c),
+        q[4].clone(),
+        scale(
+            &z,
+            alpha
+                * (eval_a + beta * xi + gamma)
+                * (eval_b + beta * k[1] * xi + gamma)
+                * (eval_c + beta * k[2] * xi + gamma)
+                + l1_xi * alpha.square(),
+        ),
+        scale(&s[2], -(alpha * beta * eval_zw * e1 * e2)),
+        scale(&t_parts[0], -zh),
+        scale(&t_parts[1], -zh * xi_n),
+        scale(&t_parts[2], -zh * xi_n * xi_n),
+    ]
+    .iter()
+    .fold(Vec::new(), |acc, p| add(&acc, p));
+    assert_eq!(evaluate(&r, xi), -r0);
+
+    let mut opening = add(&r, &[r0]);
+    for (i, (p, eval)) in [
+        (&a, eval_a),
+        (&b, eval_b),
+        (&c, eval_c),
+        (&s[0], eval_s1),
+        (&s[1], eval_s2),
+    ]
+    .iter()
+    .enumerate()
+    {
+        opening = add(&opening, &scale(&sub(p, &[*eval]), v[i]));
+    }
+    proof.w_xi = commit(&div_by_linear(&opening, xi), tau);
+    proof.w_xiw = commit(&div_by_linear(&sub(&z, &[eval_zw]), xi * omega), tau);
+
+    PlonkTestProof {
+        vkey_source,
+        proof,
+        public_inputs: public_inputs.to_vec(),
+        x_2,
+    }
+}
+
+fn commit(p: &[Fr], tau: Fr) -> G1A {
+    G1A(G1Affine::prime_subgroup_generator()
+        .mul(evaluate(p, tau).into_repr())
+        .into_affine())
+}
+
+fn evaluate(p: &[Fr], x: Fr) -> Fr {
+    p.iter().rev().fold(Fr::zero(), |acc, c| acc * x + c)
+}
+
+/// Inverse DFT over the domain generated by `omega`
+fn interpolate(evals: &[Fr], omega: Fr) -> Vec<Fr> {
+    let n_inv = Fr::from(evals.len() as u64).inverse().unwrap();
+    let omega_inv = omega.inverse().unwrap();
+
+    (0..evals.len())
+        .map(|j| {
+            let w = omega_inv.pow([j as u64]);
+            evaluate(evals, w) * n_inv
+        })
+        .collect()
+}
+
+fn add(p: &[Fr], q: &[Fr]) -> Vec<Fr> {
+    let mut r = vec![Fr::zero(); std::cmp::max(p.len(), q.len())];
+    for (i, c) in p.iter().enumerate() {
+        r[i] += c;
+    }
+    for (i, c) in q.iter().enumerate() {
+        r[i] += c;
+    }
+    r
+}
+
+fn sub(p: &[Fr], q: &[Fr]) -> Vec<Fr> {
+    add(p, &scale(q, -Fr::one()))
+}
+
+fn scale(p: &[Fr], s: Fr) -> Vec<Fr> {
+    p.iter().map(|c| *c * s).collect()
+}
+
+fn mul(p: &[Fr], q: &[Fr]) -> Vec<Fr> {
+    let mut r = vec![Fr::zero(); p.len() + q.len() - 1];
+    for (i, a) in p.iter().enumerate() {
+        for (j, b) in q.iter().enumerate() {
+            r[i + j] += *a * b;
+        }
+    }
+    r
+}
+
+/// `p(ωX)`
+fn shift(p: &[Fr], omega: Fr) -> Vec<Fr> {
+    p.iter()
+        .enumerate()
+        .map(|(i, c)| *c * omega.pow([i as u64]))
+        .collect()
+}
+
+/// Divides `p` by `X^N - 1` (requires a zero remainder)
+fn div_by_vanishing_polynomial(p: &[Fr]) -> Vec<Fr> {
+    let mut r = p.to_vec();
+    let mut q = vec![Fr::zero(); r.len().saturating_sub(N)];
+    for i in (N..r.len()).rev() {
+        let c = r[i];
+        q[i - N] += c;
+        r[i - N] += c;
+        r[i] = Fr::zero();
+    }
+    assert!(r.iter().all(Fr::is_zero));
+    q
+}
+
+/// Divides `p` by `X - z` (requires a zero remainder)
+fn div_by_linear(p: &[Fr], z: Fr) -> Vec<Fr> {
+    let mut q = vec![Fr::zero(); p.len() - 1];
+    let mut carry = Fr::zero();
+    for i in (1..p.len()).rev() {
+        carry = carry * z + p[i];
+        q[i - 1] = carry;
+    }
+    assert!((carry * z + p[0]).is_zero());
+    q
+}
@@ -0,0 +1,774 @@
+//! PLONK proof verification (https://eprint.iacr.org/2019/953.pdf)
+//! - challenges and the verifier equation follow the snarkjs PLONK verifier (Keccak256 transcript, linearisation using `r0`)
+//! - the challenge computation, the multi-scalar-multiplication computing the two pairing points and the pairing check use `elusiv_computations` partial computations
+//! - the final exponentiation is shared with the Groth16 verifier
+
+use super::vkey::{PlonkCommitment, PlonkVerifyingKey};
+use crate::bytes::{usize_as_u32_safe, usize_as_u8_safe};
+use crate::error::ElusivError::{
+    self, ComputationIsAlreadyFinished, CouldNotProcessProof, InvalidAccountState,
+    PartialComputationError,
+};
+use crate::error::ElusivResult;
+use crate::fields::{fr_to_u256_le_repr, u256_to_big_uint, Wrap, G1A};
+use crate::processor::COMPUTE_VERIFICATION_IX_COUNT;
+use crate::proof::alt_bn128::g1_to_bytes;
+use crate::proof::verifier::{
+    final_exponentiation_step, mul_base_field_by_nonresidue, mul_by_fp, mul_fq6_by_c0_c1_0,
+    VerificationStep,
+};
+use crate::state::proof::{VerificationAccount, VerificationState};
+use crate::types::{PlonkProof, RawU256, U256};
+use ark_bn254::{Fq12, Fq2, Fq6, Fr, G1Affine, G1Projective};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{batch_inversion, Field, One, PrimeField, Zero};
+use elusiv_computation::{PartialComputation, RAM};
+use elusiv_interpreter::elusiv_computations;
+use elusiv_utils::guard;
+use solana_program::keccak;
+use std::ops::AddAssign;
+
+/// Requires `verification_account.prepare_inputs_instructions_count + PlonkMillerLoop::IX_COUNT + FINAL_EXPONENTIATION_IXS` calls to verify a valid proof
+pub fn verify_plonk_partial(
+    verification_account: &mut VerificationAccount,
+    vkey: &PlonkVerifyingKey,
+    instruction_index: u16,
+) -> Result<Option<bool>, ElusivError> {
+    let instruction = verification_account.get_instruction() as usize;
+    let round = verification_account.get_round() as usize;
+    let step = verification_account.get_step();
+
+    // In contrast to Groth16, all steps depend on the proof
+    guard!(
+        matches!(
+            verification_account.get_state(),
+            VerificationState::ProofSetup
+        ),
+        InvalidAccountState
+    );
+
+    match step {
+        VerificationStep::PublicInputPreparation => {
+            // This enables us to use a uniform number of ixs per tx (by only allowing the last ix to perform the computation)
+            if instruction_index != COMPUTE_VERIFICATION_IX_COUNT - 1 {
+                return Ok(None);
+            }
+
+            prepare_plonk(verification_account, vkey, instruction, round)?;
+            verification_account
+                .serialize_rams()
+                .or(Err(InvalidAccountState))?;
+        }
+        VerificationStep::CombinedMillerLoop => {
+            plonk_miller_loop(verification_account, vkey, instruction, round)?;
+            verification_account
+                .serialize_rams()
+                .or(Err(InvalidAccountState))?;
+        }
+        VerificationStep::FinalExponentiation => {
+            // This enables us to use a uniform number of ixs per tx (by only allowing the last ix to perform the computation)
+            if instruction_index != COMPUTE_VERIFICATION_IX_COUNT - 1 {
+                return Ok(None);
+            }
+
+            let v = final_exponentiation_step(verification_account, instruction, round)?;
+            verification_account
+                .serialize_rams()
+                .or(Err(InvalidAccountState))?;
+
+            // Final verification, we check: `e(-A1, [x]_2) * e(B1, [1]_2) == 1`
+            return Ok(v.map(|v| v == Fq12::one()));
+        }
+        // PLONK proofs only consist of G1 points
+        VerificationStep::ProofSubgroupCheck => return Err(InvalidAccountState),
+    }
+
+    Ok(None)
+}
+
+/// The number of terms of the multi-scalar-multiplication computing the pairing points
+/// - `B1 = ξ W_ξ + uξω W_ξω + D + v A + v² B + v³ C + v⁴ S1 + v⁵ S2 - e G1` (with the linearisation commitment `D`)
+/// - `A1 = W_ξ + u W_ξω`
+pub const PLONK_MSM_TERMS_COUNT: usize = 18;
+
+/// The only term accumulated into `A1`
+const PLONK_MSM_A1_TERM: usize = PLONK_MSM_TERMS_COUNT - 1;
+
+/// Returns the instructions (and their rounds) required for the PLONK challenge computation and multi-scalar-multiplication
+pub fn plonk_preparation_instructions() -> Vec<u32> {
+    PlonkPreparation::INSTRUCTION_ROUNDS
+        .iter()
+        .map(|&rounds| rounds as u32)
+        .collect()
+}
+
+fn prepare_plonk(
+    verification_account: &mut VerificationAccount,
+    vkey: &PlonkVerifyingKey,
+    instruction: usize,
+    round: usize,
+) -> ElusivResult {
+    guard!(
+        instruction < PlonkPreparation::IX_COUNT,
+        ComputationIsAlreadyFinished
+    );
+
+    let rounds = PlonkPreparation::INSTRUCTION_ROUNDS[instruction] as usize;
+    let proof = verification_account.plonk_proof.get();
+
+    let mut result = None;
+    for round in round..round + rounds {
+        result = plonk_preparation_partial(round, verification_account, vkey, &proof)?;
+    }
+
+    if round + rounds == PlonkPreparation::TOTAL_ROUNDS as usize {
+        let (a1, b1) = result.ok_or(CouldNotProcessProof)?;
+
+        verification_account.plonk_a1.set(G1A(a1));
+        verification_account.plonk_b1.set(G1A(b1));
+
+        verification_account.set_step(&VerificationStep::CombinedMillerLoop);
+        verification_account.set_round(&0);
+        verification_account.set_instruction(&0);
+    } else {
+        verification_account.set_round(&usize_as_u32_safe(round + rounds));
+        verification_account.set_instruction(&(instruction as u32 + 1));
+    }
+
+    Ok(())
+}
+
+/// The pairing points `(A1, B1)`
+type PlonkPairingPoints = (G1Affine, G1Affine);
+
+/// Computes the challenges and stores the [`PLONK_MSM_TERMS_COUNT`] scalars (in non-montgomery form)
+fn store_plonk_scalars(
+    storage: &mut VerificationAccount,
+    vkey: &PlonkVerifyingKey,
+    proof: &PlonkProof,
+) -> Option<()> {
+    let public_inputs = (0..vkey.public_inputs_count)
+        .map(|i| raw_to_fr(&storage.get_public_input(i)).ok())
+        .collect::<Option<Vec<Fr>>>()?;
+
+    let scalars = plonk_scalars(vkey, proof, &public_inputs).ok()?;
+    for (i, scalar) in scalars.iter().enumerate() {
+        storage.set_plonk_scalars(i, &fr_to_u256_le_repr(scalar));
+    }
+
+    Some(())
+}
+
+/// Returns the bit `8 * byte + bit` of the scalar of the multi-scalar-multiplication term `term`
+fn plonk_scalar_bit(storage: &VerificationAccount, term: usize, byte: usize, bit: usize) -> bool {
+    (storage.get_plonk_scalars(term)[byte] >> bit) & 1 == 1
+}
+
+/// Adds the terms with the scalar one and returns the affine pairing points
+fn plonk_pairing_points(
+    vkey: &PlonkVerifyingKey,
+    proof: &PlonkProof,
+    a1: G1Projective,
+    b1: G1Projective,
+) -> PlonkPairingPoints {
+    (
+        a1.add_mixed(&proof.w_xi.0).into_affine(),
+        b1.add_mixed(&vkey.commitment(PlonkCommitment::Qc))
+            .into_affine(),
+    )
+}
+
+/// The point of a multi-scalar-multiplication term (ordered as the scalars returned by [`plonk_scalars`])
+fn plonk_msm_point(term: usize, vkey: &PlonkVerifyingKey, proof: &PlonkProof) -> G1Affine {
+    match term {
+        0 => vkey.commitment(PlonkCommitment::Qm),
+        1 => vkey.commitment(PlonkCommitment::Ql),
+        2 => vkey.commitment(PlonkCommitment::Qr),
+        3 => vkey.commitment(PlonkCommitment::Qo),
+        4 => proof.z.0,
+        5 => vkey.commitment(PlonkCommitment::S3),
+        6 => proof.t1.0,
+        7 => proof.t2.0,
+        8 => proof.t3.0,
+        9 => proof.a.0,
+        10 => proof.b.0,
+        11 => proof.c.0,
+        12 => vkey.commitment(PlonkCommitment::S1),
+        13 => vkey.commitment(PlonkCommitment::S2),
+        14 => G1Affine::prime_subgroup_generator(),
+        15 => proof.w_xi.0,
+        _ => proof.w_xiw.0,
+    }
+}
+
+elusiv_computations!(
+    plonk_preparation, PlonkPreparation, 1_400_000,
+
+    // Challenge computation and double-and-add multi-scalar-multiplication (from the most significant bit)
+    // - the first round computes the challenges and scalars (using a whole instruction)
+    // - since the scalars are not known in advance, the worst case (all bits set) is assumed
+    plonk_preparation(
+        storage: &mut VerificationAccount,
+        vkey: &PlonkVerifyingKey,
+        proof: &PlonkProof,
+    ) -> PlonkPairingPoints {
+        {   /// 1_390_000
+            _ = unwrap store_plonk_scalars(storage, vkey, proof);
+
+            let mut a1: G1Projective = G1Projective::zero();
+            let mut b1: G1Projective = G1Projective::zero();
+        }
+        {
+            for _i, byte in [31,30,29,28,27,26,25,24,23,22,21,20,19,18,17,16,15,14,13,12,11,10,9,8,7,6,5,4,3,2,1,0] {
+                for _j, bit in [7,6,5,4,3,2,1,0] {
+                    {   /// 32_000
+                        a1 = a1.double();
+                        b1 = b1.double();
+                    }
+                    {
+                        for _k, term in [0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16] {
+                            {   /// 22_000
+                                if (plonk_scalar_bit(storage, term, byte, bit)) {
+                                    b1 = b1.add_mixed(&(plonk_msm_point(term, vkey, proof)));
+                                }
+                            }
+                        }
+                    }
+                    {   /// 22_000
+                        if (plonk_scalar_bit(storage, PLONK_MSM_A1_TERM, byte, bit)) {
+                            a1 = a1.add_mixed(&(plonk_msm_point(PLONK_MSM_A1_TERM, vkey, proof)));
+                        }
+                    }
+                }
+            }
+        }
+        {   /// 10_000
+            return plonk_pairing_points(vkey, proof, a1, b1);
+        }
+    }
+);
+
+pub(crate) struct PlonkChallenges {
+    pub beta: Fr,
+    pub gamma: Fr,
+    pub alpha: Fr,
+    pub xi: Fr,
+    pub v: [Fr; 5],
+    pub u: Fr,
+}
+
+/// Computes the Fiat-Shamir challenges
+/// - https://github.com/iden3/snarkjs/blob/master/src/plonk_verify.js
+pub(crate) fn plonk_challenges(
+    vkey: &PlonkVerifyingKey,
+    proof: &PlonkProof,
+    public_inputs: &[Fr],
+) -> Result<PlonkChallenges, ElusivError> {
+    let commitments = [
+        PlonkCommitment::Qm,
+        PlonkCommitment::Ql,
+        PlonkCommitment::Qr,
+        PlonkCommitment::Qo,
+        PlonkCommitment::Qc,
+        PlonkCommitment::S1,
+        PlonkCommitment::S2,
+        PlonkCommitment::S3,
+    ]
+    .map(|c| g1_to_bytes(&vkey.commitment(c)));
+    let public_inputs: Vec<U256> = public_inputs.iter().map(scalar_to_bytes).collect();
+    let witness_commitments = [proof.a, proof.b, proof.c].map(|p| g1_to_bytes(&p.0));
+
+    let mut inputs: Vec<&[u8]> = Vec::new();
+    inputs.extend(commitments.iter().map(|c| &c[..]));
+    inputs.extend(public_inputs.iter().map(|p| &p[..]));
+    inputs.extend(witness_commitments.iter().map(|c| &c[..]));
+    let beta = challenge(&inputs);
+
+    let gamma = challenge(&[&scalar_to_bytes(&beta)]);
+
+    let alpha = challenge(&[
+        &scalar_to_bytes(&beta),
+        &scalar_to_bytes(&gamma),
+        &g1_to_bytes(&proof.z.0),
+    ]);
+
+    let xi = challenge(&[
+        &scalar_to_bytes(&alpha),
+        &g1_to_bytes(&proof.t1.0),
+        &g1_to_bytes(&proof.t2.0),
+        &g1_to_bytes(&proof.t3.0),
+    ]);
+
+    let v1 = challenge(&[
+        &scalar_to_bytes(&xi),
+        &scalar_to_bytes(&raw_to_fr(&proof.eval_a)?),
+        &scalar_to_bytes(&raw_to_fr(&proof.eval_b)?),
+        &scalar_to_bytes(&raw_to_fr(&proof.eval_c)?),
+        &scalar_to_bytes(&raw_to_fr(&proof.eval_s1)?),
+        &scalar_to_bytes(&raw_to_fr(&proof.eval_s2)?),
+        &scalar_to_bytes(&raw_to_fr(&proof.eval_zw)?),
+    ]);
+    let mut v = [v1; 5];
+    for i in 1..5 {
+        v[i] = v[i - 1] * v1;
+    }
+
+    let u = challenge(&[&g1_to_bytes(&proof.w_xi.0), &g1_to_bytes(&proof.w_xiw.0)]);
+
+    Ok(PlonkChallenges {
+        beta,
+        gamma,
+        alpha,
+        xi,
+        v,
+        u,
+    })
+}
+
+/// Computes the scalars of all [`PLONK_MSM_TERMS_COUNT`] multi-scalar-multiplication terms (see [`plonk_msm_point`])
+fn plonk_scalars(
+    vkey: &PlonkVerifyingKey,
+    proof: &PlonkProof,
+    public_inputs: &[Fr],
+) -> Result<[Fr; PLONK_MSM_TERMS_COUNT], ElusivError> {
+    let eval_a = raw_to_fr(&proof.eval_a)?;
+    let eval_b = raw_to_fr(&proof.eval_b)?;
+    let eval_c = raw_to_fr(&proof.eval_c)?;
+    let eval_s1 = raw_to_fr(&proof.eval_s1)?;
+    let eval_s2 = raw_to_fr(&proof.eval_s2)?;
+    let eval_zw = raw_to_fr(&proof.eval_zw)?;
+
+    let PlonkChallenges {
+        beta,
+        gamma,
+        alpha,
+        xi,
+        v,
+        u,
+    } = plonk_challenges(vkey, proof, public_inputs)?;
+
+    // Vanishing polynomial `Z_H(ξ) = ξ^n - 1`
+    let mut xi_n = xi;
+    for _ in 0..vkey.power() {
+        xi_n.square_in_place();
+    }
+    let zh = xi_n - Fr::one();
+
+    let lagrange = lagrange_evaluations(vkey, xi, zh)?;
+    let l1 = lagrange[0];
+    let pi = public_inputs
+        .iter()
+        .zip(lagrange.iter())
+        .fold(Fr::zero(), |acc, (w, l)| acc - *w * l);
+
+    let alpha_2 = alpha.square();
+    let e1 = eval_a + beta * eval_s1 + gamma;
+    let e2 = eval_b + beta * eval_s2 + gamma;
+    let r0 = pi - l1 * alpha_2 - alpha * e1 * e2 * (eval_c + gamma) * eval_zw;
+
+    let z = (eval_a + beta * xi + gamma)
+        * (eval_b + beta * vkey.k1() * xi + gamma)
+        * (eval_c + beta * vkey.k2() * xi + gamma)
+        * alpha
+        + l1 * alpha_2
+        + u;
+    let s3 = e1 * e2 * alpha * beta * eval_zw;
+    let e = -r0
+        + v[0] * eval_a
+        + v[1] * eval_b
+        + v[2] * eval_c
+        + v[3] * eval_s1
+        + v[4] * eval_s2
+        + u * eval_zw;
+
+    Ok([
+        eval_a * eval_b,
+        eval_a,
+        eval_b,
+        eval_c,
+        z,
+        -s3,
+        -zh,
+        -zh * xi_n,
+        -zh * xi_n * xi_n,
+        v[0],
+        v[1],
+        v[2],
+        v[3],
+        v[4],
+        -e,
+        xi,
+        u * xi * vkey.omega(),
+        u,
+    ])
+}
+
+/// Computes `L_i(ξ) = ω^i Z_H(ξ) / (n (ξ - ω^i))` for all public inputs (at least `L_1`)
+fn lagrange_evaluations(vkey: &PlonkVerifyingKey, xi: Fr, zh: Fr) -> Result<Vec<Fr>, ElusivError> {
+    let count = std::cmp::max(vkey.public_inputs_count, 1);
+    let n = Fr::from(vkey.domain_size());
+    let omega = vkey.omega();
+
+    let mut numerators = Vec::with_capacity(count);
+    let mut denominators = Vec::with_capacity(count);
+    let mut w = Fr::one();
+    for _ in 0..count {
+        numerators.push(w * zh);
+        denominators.push(n * (xi - w));
+        w *= omega;
+    }
+
+    guard!(!denominators.iter().any(Fr::is_zero), CouldNotProcessProof);
+    batch_inversion(&mut denominators);
+
+    Ok(numerators
+        .iter()
+        .zip(denominators.iter())
+        .map(|(n, d)| *n * d)
+        .collect())
+}
+
+fn challenge(inputs: &[&[u8]]) -> Fr {
+    Fr::from_be_bytes_mod_order(&keccak::hashv(inputs).to_bytes())
+}
+
+/// Big-endian representation of a scalar
+fn scalar_to_bytes(scalar: &Fr) -> U256 {
+    let mut bytes = fr_to_u256_le_repr(scalar);
+    bytes.reverse();
+    bytes
+}
+
+fn raw_to_fr(raw: &RawU256) -> Result<Fr, ElusivError> {
+    Fr::from_repr(u256_to_big_uint(&raw.skip_mr())).ok_or(CouldNotProcessProof)
+}
+
+fn plonk_miller_loop(
+    verification_account: &mut VerificationAccount,
+    vkey: &PlonkVerifyingKey,
+    instruction: usize,
+    round: usize,
+) -> ElusivResult {
+    guard!(
+        instruction < PlonkMillerLoop::IX_COUNT,
+        ComputationIsAlreadyFinished
+    );
+
+    let rounds = PlonkMillerLoop::INSTRUCTION_ROUNDS[instruction] as usize;
+    let mut coeff_index = verification_account.get_coeff_index() as usize;
+
+    let a1 = verification_account.plonk_a1.get().0;
+    let b1 = verification_account.plonk_b1.get().0;
+
+    let mut result = None;
+    for round in round..round + rounds {
+        result = plonk_miller_loop_partial(
+            round,
+            verification_account,
+            vkey,
+            &a1,
+            &b1,
+            &mut coeff_index,
+        )?;
+    }
+
+    verification_account.set_coeff_index(&usize_as_u8_safe(coeff_index));
+
+    if round + rounds == PlonkMillerLoop::TOTAL_ROUNDS as usize {
+        let f = result.ok_or(CouldNotProcessProof)?;
+
+        // Add `f` for the final exponentiation
+        verification_account.f.set(Wrap(f));
+
+        verification_account.set_step(&VerificationStep::FinalExponentiation);
+        verification_account.set_round(&0);
+        verification_account.set_instruction(&0);
+    } else {
+        verification_account.set_round(&usize_as_u32_safe(round + rounds));
+        verification_account.set_instruction(&(instruction as u32 + 1));
+    }
+
+    Ok(())
+}
+
+elusiv_computations!(
+    plonk_miller_loop, PlonkMillerLoop, 250_000,
+
+    // f.mul_by_034(c0, c1, coeffs.2); (with: self -> f; c0 -> c0; d0 -> c1; d1 -> coeffs.2)
+    // https://github.com/arkworks-rs/r1cs-std/blob/b7874406ec614748608b1739b1578092a8c97fb8/src/fields/fp12.rs#L43
+    mul_by_034(
+        storage: &mut VerificationAccount,
+        c0: &Fq2, d0: &Fq2, d1: &Fq2, f: Fq12
+    ) -> Fq12 {
+        {   /// 20_500
+            let a: Fq6 = Fq6::new(f.c0.c0 * c0, f.c0.c1 * c0, f.c0.c2 * c0);
+        }
+
+        {   /// 55_500
+            let b: Fq6 = mul_fq6_by_c0_c1_0(f.c1, d0, d1);
+        }
+
+        {   /// 44_500
+            let e: Fq6 = mul_fq6_by_c0_c1_0(f.c0 + f.c1, &(*c0 + d0), d1);
+            return Fq12::new(mul_base_field_by_nonresidue(b) + a, e - (a + b));
+        }
+    },
+
+    // We evaluate the line functions of both pairings (with precomputed coefficients for `-[x]_2` and `[1]_2`)
+    // - normal ell implementation: https://github.com/arkworks-rs/algebra/blob/6ea310ef09f8b7510ce947490919ea6229bbecd6/ec/src/models/bn/mod.rs#L59
+    plonk_ell(
+        storage: &mut VerificationAccount,
+        vkey: &PlonkVerifyingKey,
+        a1: &G1Affine, b1: &G1Affine,
+        coeff_index: usize, f: Fq12,
+    ) -> Fq12 {
+        // ell on A1 with x_2_neg_pc
+        {   /// 9_500
+            let r: Fq12 = f;
+
+            let a0: Fq2 = mul_by_fp(&(vkey.x_2_neg_pc(coeff_index, 0)), a1.y);
+            let a1_x: Fq2 = mul_by_fp(&(vkey.x_2_neg_pc(coeff_index, 1)), a1.x);
+        }
+        {   /// mul_by_034
+            if (!(a1.is_zero())) {
+                partial v = mul_by_034(storage, &a0, &a1_x, &(vkey.x_2_neg_pc(coeff_index, 2)), r) { r = v }
+            }
+        }
+
+        // ell on B1 with g2_pc
+        {   /// 9_200
+            let b0: Fq2 = mul_by_fp(&(vkey.g2_pc(coeff_index, 0)), b1.y);
+            let b1_x: Fq2 = mul_by_fp(&(vkey.g2_pc(coeff_index, 1)), b1.x);
+        }
+        {   /// mul_by_034
+            if (!(b1.is_zero())) {
+                partial v = mul_by_034(storage, &b0, &b1_x, &(vkey.g2_pc(coeff_index, 2)), r) { r = v }
+            }
+        }
+
+        {   /// 1000
+            return r;
+        }
+    },
+
+    // Miller loop over `(A1, -[x]_2)` and `(B1, [1]_2)`
+    // - since both G2 elements are part of the vkey, all coefficients are precomputed
+    // - miller loop ref: https://github.com/arkworks-rs/algebra/blob/6ea310ef09f8b7510ce947490919ea6229bbecd6/ec/src/models/bn/mod.rs#L99
+    plonk_miller_loop(
+        storage: &mut VerificationAccount,
+        vkey: &PlonkVerifyingKey,
+        a1: &G1Affine, b1: &G1Affine,
+        j: &mut usize,
+    ) -> Fq12 {
+        {   /// 1000
+            let f: Fq12 = Fq12::one();
+        }
+
+        // Reversed ATE_LOOP_COUNT with the the last element removed (so the first in the reversed order)
+        // https://github.com/arkworks-rs/curves/blob/1551d6d76ce5abf6e7925e53b0ea1af7dbc421c3/bn254/src/curves/mod.rs#L21
+        {
+            for i, ate_loop_count in [1,0,1,0,0,2,0,1,1,0,0,0,2,0,0,1,1,0,0,2,0,0,0,0,0,1,0,0,2,0,0,1,1,1,0,0,0,0,2,0,1,0,0,2,0,1,1,0,0,1,0,0,2,1,0,0,2,0,1,0,1,0,0,0] {
+                {   /// i in { 0 : 0 , _ : 88_000 }
+                    if (i > 0) {
+                        _ = f.square_in_place();
+                    }
+                }
+
+                partial v = plonk_ell(storage, vkey, a1, b1, *j, f) {
+                    f = v;
+                    _ = j.add_assign(1);
+                };
+
+                {   /// ate_loop_count in { 0 : plonk_ell_zero , _ : plonk_ell }
+                    if (ate_loop_count > 0) {
+                        partial v = plonk_ell(storage, vkey, a1, b1, *j, f) {
+                            f = v;
+                            _ = j.add_assign(1);
+                        };
+                    }
+                }
+            }
+        }
+        // The final two coefficient triples
+        {
+            partial v = plonk_ell(storage, vkey, a1, b1, *j, f) {
+                f = v;
+                _ = j.add_assign(1);
+            };
+            partial v = plonk_ell(storage, vkey, a1, b1, *j, f) {
+                f = v;
+                _ = j.add_assign(1);
+            };
+        }
+        {   /// 500
+            return f;
+        }
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_prover::{plonk_test_proof, PlonkTestProof};
+    use super::*;
+    use crate::macros::zero_program_account;
+    use crate::proof::verifier::FINAL_EXPONENTIATION_IXS;
+    use ark_bn254::{Bn254, G2Affine};
+    use ark_ec::PairingEngine;
+    use std::ops::Neg;
+
+    fn test_proof() -> PlonkTestProof {
+        plonk_test_proof(&[Fr::from(1234u64), Fr::from(99u64)], 0)
+    }
+
+    fn setup_storage_account(
+        storage: &mut VerificationAccount,
+        proof: PlonkProof,
+        public_inputs: &[Fr],
+    ) {
+        storage.plonk_proof.set(proof);
+        storage.set_state(&VerificationState::ProofSetup);
+
+        for (i, public_input) in public_inputs.iter().enumerate() {
+            storage.set_public_input(i, &RawU256::new(fr_to_u256_le_repr(public_input)));
+        }
+
+        storage
+            .setup_public_inputs_instructions(&plonk_preparation_instructions())
+            .unwrap();
+    }
+
+    fn full_verification(
+        proof: PlonkProof,
+        public_inputs: &[Fr],
+        vkey: &PlonkVerifyingKey,
+    ) -> bool {
+        zero_program_account!(mut storage, VerificationAccount);
+        setup_storage_account(&mut storage, proof, public_inputs);
+        let instruction_count = storage.get_prepare_inputs_instructions_count() as usize
+            + PlonkMillerLoop::IX_COUNT
+            + FINAL_EXPONENTIATION_IXS;
+
+        let mut result = None;
+        for _ in 0..instruction_count {
+            match verify_plonk_partial(&mut storage, vkey, COMPUTE_VERIFICATION_IX_COUNT - 1) {
+                Ok(r) => result = r,
+                Err(_) => return false,
+            }
+        }
+
+        result.unwrap()
+    }
+
+    #[test]
+    fn test_plonk_preparation_instructions() {
+        let instructions = plonk_preparation_instructions();
+
+        assert_eq!(instructions[0], 1);
+        assert_eq!(
+            instructions.iter().sum::<u32>(),
+            PlonkPreparation::TOTAL_ROUNDS
+        );
+    }
+
+    #[test]
+    fn test_plonk_pairing_points() {
+        let p = test_proof();
+        let vkey = PlonkVerifyingKey::new(&p.vkey_source, p.public_inputs.len()).unwrap();
+
+        // Native computation
+        let scalars = plonk_scalars(&vkey, &p.proof, &p.public_inputs).unwrap();
+        let mut b1 = vkey.commitment(PlonkCommitment::Qc).into_projective();
+        for (term, scalar) in scalars.iter().enumerate().take(PLONK_MSM_A1_TERM) {
+            b1 += plonk_msm_point(term, &vkey, &p.proof).mul(scalar.into_repr());
+        }
+        let a1 = p.proof.w_xi.0.into_projective()
+            + p.proof.w_xiw.0.mul(scalars[PLONK_MSM_A1_TERM].into_repr());
+        let (a1, b1) = (a1.into_affine(), b1.into_affine());
+
+        assert_eq!(
+            Bn254::product_of_pairings(&[
+                (a1.into(), p.x_2.neg().into()),
+                (b1.into(), G2Affine::prime_subgroup_generator().into()),
+            ]),
+            Fq12::one()
+        );
+
+        // Partial computation
+        zero_program_account!(mut storage, VerificationAccount);
+        setup_storage_account(&mut storage, p.proof, &p.public_inputs);
+        for _ in 0..storage.get_prepare_inputs_instructions_count() {
+            verify_plonk_partial(&mut storage, &vkey, COMPUTE_VERIFICATION_IX_COUNT - 1).unwrap();
+        }
+
+        assert_eq!(storage.plonk_a1.get().0, a1);
+        assert_eq!(storage.plonk_b1.get().0, b1);
+        assert_matches::assert_matches!(storage.get_step(), VerificationStep::CombinedMillerLoop);
+    }
+
+    #[test]
+    fn test_plonk_miller_loop() {
+        let p = test_proof();
+        let vkey = PlonkVerifyingKey::new(&p.vkey_source, p.public_inputs.len()).unwrap();
+        let a1 = p.proof.a.0;
+        let b1 = p.proof.b.0;
+
+        zero_program_account!(mut storage, VerificationAccount);
+        storage.plonk_a1.set(G1A(a1));
+        storage.plonk_b1.set(G1A(b1));
+
+        for instruction in 0..PlonkMillerLoop::IX_COUNT {
+            let round = storage.get_round() as usize;
+            plonk_miller_loop(&mut storage, &vkey, instruction, round).unwrap();
+        }
+
+        let expected = Bn254::miller_loop(&[
+            (a1.into(), p.x_2.neg().into()),
+            (b1.into(), G2Affine::prime_subgroup_generator().into()),
+        ]);
+        assert_eq!(storage.f.get().0, expected);
+    }
+
+    #[test]
+    fn test_verify_plonk_proofs() {
+        let p = test_proof();
+        let vkey = PlonkVerifyingKey::new(&p.vkey_source, p.public_inputs.len()).unwrap();
+
+        assert!(full_verification(p.proof, &p.public_inputs, &vkey));
+
+        // Invalid public input
+        let mut public_inputs = p.public_inputs.clone();
+        public_inputs[1] += Fr::one();
+        assert!(!full_verification(p.proof, &public_inputs, &vkey));
+
+        // Invalid evaluation
+        let mut proof = p.proof;
+        let eval = raw_to_fr(&proof.eval_zw).unwrap() + Fr::one();
+        proof.eval_zw = RawU256::new(fr_to_u256_le_repr(&eval));
+        assert!(!full_verification(proof, &p.public_inputs, &vkey));
+
+        // Invalid commitment
+        let mut proof = p.proof;
+        proof.w_xiw = proof.w_xi;
+        assert!(!full_verification(proof, &p.public_inputs, &vkey));
+
+        // Non-canonical evaluation
+        let mut proof = p.proof;
+        proof.eval_a = RawU256::new([u8::MAX; 32]);
+        assert!(!full_verification(proof, &p.public_inputs, &vkey));
+    }
+
+    #[test]
+    fn test_verify_plonk_partial_invalid_state() {
+        let p = test_proof();
+        let vkey = PlonkVerifyingKey::new(&p.vkey_source, p.public_inputs.len()).unwrap();
+
+        zero_program_account!(mut storage, VerificationAccount);
+        setup_storage_account(&mut storage, p.proof, &p.public_inputs);
+        storage.set_state(&VerificationState::FeeTransferred);
+
+        assert_matches::assert_matches!(
+            verify_plonk_partial(&mut storage, &vkey, COMPUTE_VERIFICATION_IX_COUNT - 1),
+            Err(InvalidAccountState)
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use crate::fields::{Wrap, G1A, G2A};
+use ark_bn254::{Fq2, Fr, G1Affine, G2Affine};
+use borsh::BorshDeserialize;
+use elusiv_types::BorshSerDeSized;
+
+/// The max. power of the evaluation domain (two-adicity of [`Fr`])
+pub const MAX_PLONK_DOMAIN_POWER: u32 = 28;
+
+/// The selector and permutation commitments of a [`PlonkVerifyingKey`] (in source order)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PlonkCommitment {
+    Qm,
+    Ql,
+    Qr,
+    Qo,
+    Qc,
+    S1,
+    S2,
+    S3,
+}
+
+const PLONK_COMMITMENTS_COUNT: usize = 8;
+
+/// A PLONK verifying key with precomputed values
+pub struct PlonkVerifyingKey<'a> {
+    source: &'a [u8],
+    pub public_inputs_count: usize,
+}
+
+impl<'a> PlonkVerifyingKey<'a> {
+    /// Creates a new [`PlonkVerifyingKey`]
+    ///
+    /// # `source`
+    ///
+    /// ```
+    /// power: u32,
+    /// k1: Fr,
+    /// k2: Fr,
+    /// omega: Fr,
+    /// commitments: [G1Affine; 8], // Qm, Ql, Qr, Qo, Qc, S1, S2, S3
+    /// x_2: G2Affine,
+    /// x_2_neg: [(Fq2, Fq2, Fq2); 91],
+    /// g2: [(Fq2, Fq2, Fq2); 91],
+    /// ```
+    pub fn new(source: &'a [u8], public_inputs_count: usize) -> Option<Self> {
+        if source.len() != Self::source_size(public_inputs_count) {
+            return None;
+        }
+
+        let vkey = Self {
+            source,
+            public_inputs_count,
+        };
+
+        if vkey.power() > MAX_PLONK_DOMAIN_POWER {
+            return None;
+        }
+
+        Some(vkey)
+    }
+
+    const COEFFS_ARRAY_SIZE: usize = 91 * 3 * Wrap::<Fq2>::SIZE;
+    const COMMITMENTS_OFFSET: usize = u32::SIZE + 3 * Wrap::<Fr>::SIZE;
+    const X_2_OFFSET: usize = Self::COMMITMENTS_OFFSET + PLONK_COMMITMENTS_COUNT * G1A::SIZE;
+    const COEFFS_OFFSET: usize = Self::X_2_OFFSET + G2A::SIZE;
+
+    /// The source size is independent of the public inputs count
+    pub const fn source_size(_public_inputs_count: usize) -> usize {
+        Self::COEFFS_OFFSET + 2 * Self::COEFFS_ARRAY_SIZE
+    }
+
+    /// The domain size is `2^power`
+    pub fn power(&self) -> u32 {
+        u32::try_from_slice(&self.source[..u32::SIZE]).unwrap()
+    }
+
+    pub fn domain_size(&self) -> u64 {
+        1 << self.power()
+    }
+
+    fn scalar(&self, index: usize) -> Fr {
+        let offset = u32::SIZE + index * Wrap::<Fr>::SIZE;
+        let slice = &self.source[offset..offset + Wrap::<Fr>::SIZE];
+        Wrap::try_from_slice(slice).unwrap().0
+    }
+
+    pub fn k1(&self) -> Fr {
+        self.scalar(0)
+    }
+
+    pub fn k2(&self) -> Fr {
+        self.scalar(1)
+    }
+
+    /// Generator of the evaluation domain
+    pub fn omega(&self) -> Fr {
+        self.scalar(2)
+    }
+
+    pub fn commitment(&self, commitment: PlonkCommitment) -> G1Affine {
+        let offset = Self::COMMITMENTS_OFFSET + commitment as usize * G1A::SIZE;
+        let slice = &self.source[offset..offset + G1A::SIZE];
+        G1A::try_from_slice(slice).unwrap().0
+    }
+
+    pub fn x_2(&self) -> G2Affine {
+        let slice = &self.source[Self::X_2_OFFSET..Self::X_2_OFFSET + G2A::SIZE];
+        G2A::try_from_slice(slice).unwrap().0
+    }
+
+    pub fn x_2_neg_pc(&self, index: usize, inner_index: usize) -> Fq2 {
+        let offset = Self::COEFFS_OFFSET + (index * 3 + inner_index) * Wrap::<Fq2>::SIZE;
+        let slice = &self.source[offset..offset + Wrap::<Fq2>::SIZE];
+        Wrap::try_from_slice(slice).unwrap().0
+    }
+
+    pub fn g2_pc(&self, index: usize, inner_index: usize) -> Fq2 {
+        let offset = Self::COEFFS_OFFSET
+            + Self::COEFFS_ARRAY_SIZE
+            + (index * 3 + inner_index) * Wrap::<Fq2>::SIZE;
+        let slice = &self.source[offset..offset + Wrap::<Fq2>::SIZE];
+        Wrap::try_from_slice(slice).unwrap().0
+    }
+}
+
+/// Generates the [`PlonkVerifyingKey`] source from the preprocessed circuit data
+///
+/// # Notes
+///
+/// `commitments` are ordered as the variants of [`PlonkCommitment`].
+#[cfg(feature = "elusiv-client")]
+pub fn plonk_verifying_key_source(
+    power: u32,
+    k1: Fr,
+    k2: Fr,
+    omega: Fr,
+    commitments: &[G1Affine; PLONK_COMMITMENTS_COUNT],
+    x_2: G2Affine,
+) -> Vec<u8> {
+    use ark_bn254::Parameters;
+    use ark_ec::bn::G2Prepared;
+    use ark_ec::AffineCurve;
+    use borsh::BorshSerialize;
+    use std::ops::Neg;
+
+    assert!(power <= MAX_PLONK_DOMAIN_POWER);
+
+    let mut source = power.try_to_vec().unwrap();
+    for scalar in [k1, k2, omega] {
+        source.extend(Wrap(scalar).try_to_vec().unwrap());
+    }
+    for commitment in commitments {
+        source.extend(G1A(*commitment).try_to_vec().unwrap());
+    }
+    source.extend(G2A(x_2).try_to_vec().unwrap());
+
+    let x_2_neg: G2Prepared<Parameters> = x_2.neg().into();
+    let g2: G2Prepared<Parameters> = G2Affine::prime_subgroup_generator().into();
+    for prepared in [x_2_neg, g2] {
+        for (c0, c1, c2) in prepared.ell_coeffs {
+            for c in [c0, c1, c2] {
+                source.extend(Wrap(c).try_to_vec().unwrap());
+            }
+        }
+    }
+
+    assert_eq!(source.len(), PlonkVerifyingKey::source_size(0));
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Parameters;
+    use ark_ec::bn::G2Prepared;
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{FftField, PrimeField};
+    use std::ops::Neg;
+
+    #[test]
+    fn test_plonk_verifying_key_source() {
+        let omega = Fr::get_root_of_unity(1 << 10).unwrap();
+        let commitments = [0u64, 1, 2, 3, 4, 5, 6, 7].map(|i| {
+            G1Affine::prime_subgroup_generator()
+                .mul(Fr::from(i + 11).into_repr())
+                .into_affine()
+        });
+        let x_2 = G2Affine::prime_subgroup_generator()
+            .mul(Fr::from(123u64).into_repr())
+            .into_affine();
+
+        let source = plonk_verifying_key_source(
+            10,
+            Fr::from(2u64),
+            Fr::from(3u64),
+            omega,
+            &commitments,
+            x_2,
+        );
+        let vkey = PlonkVerifyingKey::new(&source, 5).unwrap();
+
+        assert_eq!(vkey.power(), 10);
+        assert_eq!(vkey.domain_size(), 1024);
+        assert_eq!(vkey.k1(), Fr::from(2u64));
+        assert_eq!(vkey.k2(), Fr::from(3u64));
+        assert_eq!(vkey.omega(), omega);
+        assert_eq!(vkey.commitment(PlonkCommitment::Qm), commitments[0]);
+        assert_eq!(vkey.commitment(PlonkCommitment::Qc), commitments[4]);
+        assert_eq!(vkey.commitment(PlonkCommitment::S3), commitments[7]);
+        assert_eq!(vkey.x_2(), x_2);
+
+        let x_2_neg: G2Prepared<Parameters> = x_2.neg().into();
+        let g2: G2Prepared<Parameters> = G2Affine::prime_subgroup_generator().into();
+        for (i, (a, b)) in x_2_neg.ell_coeffs.iter().zip(g2.ell_coeffs).enumerate() {
+            assert_eq!(vkey.x_2_neg_pc(i, 0), a.0);
+            assert_eq!(vkey.x_2_neg_pc(i, 2), a.2);
+            assert_eq!(vkey.g2_pc(i, 1), b.1);
+        }
+
+        // Invalid size
+        assert!(PlonkVerifyingKey::new(&source[1..], 5).is_none());
+
+        // Invalid domain power
+        let mut invalid_source = source.clone();
+        invalid_source[..4].copy_from_slice(&(MAX_PLONK_DOMAIN_POWER + 1).to_le_bytes());
+        assert!(PlonkVerifyingKey::new(&invalid_source, 5).is_none());
+    }
+}
@@ -11,15 +11,16 @@ use super::vkey::VerifyingKey;
 use crate::bytes::{usize_as_u32_safe, usize_as_u8_safe};
 use crate::error::ElusivError::{
     self, ComputationIsAlreadyFinished, CouldNotProcessProof, InvalidAccountState,
-    PartialComputationError,
+    InvalidProofPoint, PartialComputationError,
 };
 use crate::error::ElusivResult;
-use crate::fields::{G2HomProjective, Wrap, G1A, G2A};
+use crate::fields::{psi, G2HomProjective, Wrap, G1A, G2A};
 use crate::processor::COMPUTE_VERIFICATION_IX_COUNT;
 use crate::state::proof::{RAMFq, VerificationAccount, VerificationState};
 use crate::types::U256;
 use ark_bn254::{
-    Fq, Fq12, Fq12Parameters, Fq2, Fq6, Fq6Parameters, G1Affine, G1Projective, G2Affine, Parameters,
+    Fq, Fq12, Fq12Parameters, Fq2, Fq6, Fq6Parameters, G1Affine, G1Projective, G2Affine,
+    G2Projective, Parameters,
 };
 use ark_ec::models::bn::BnParameters;
 use ark_ec::ProjectiveCurve;
@@ -40,9 +41,12 @@ pub enum VerificationStep {
     PublicInputPreparation,
     CombinedMillerLoop,
     FinalExponentiation,
+
+    /// Performed between the `PublicInputPreparation` and the `CombinedMillerLoop` (appended to keep the serialization of the other steps)
+    ProofSubgroupCheck,
 }
 
-/// Requires `verification_account.prepare_inputs_instructions_count + COMBINED_MILLER_LOOP_IXS + FINAL_EXPONENTIATION_IXS` calls to verify a valid proof
+/// Requires `verification_account.prepare_inputs_instructions_count + G2_SUBGROUP_CHECK_IXS + COMBINED_MILLER_LOOP_IXS + FINAL_EXPONENTIATION_IXS` calls to verify a valid proof
 pub fn verify_partial(
     verification_account: &mut VerificationAccount,
     vkey: &VerifyingKey,
@@ -62,6 +66,30 @@ pub fn verify_partial(
             prepare_public_inputs(verification_account, vkey, instruction, round)?;
             verification_account.serialize_rams().unwrap();
         }
+        VerificationStep::ProofSubgroupCheck => {
+            // Proof first has to be setup
+            guard!(
+                matches!(
+                    verification_account.get_state(),
+                    VerificationState::ProofSetup
+                ),
+                InvalidAccountState
+            );
+
+            if instruction < G2SubgroupCheck::IX_COUNT {
+                g2_subgroup_check(verification_account, instruction, round)?;
+                verification_account.serialize_rams().unwrap();
+            }
+
+            // The remaining ixs of the tx are skipped, which allows the miller loop to start with a new tx
+            if verification_account.get_instruction() as usize == G2SubgroupCheck::IX_COUNT
+                && instruction_index == COMPUTE_VERIFICATION_IX_COUNT - 1
+            {
+                verification_account.set_step(&VerificationStep::CombinedMillerLoop);
+                verification_account.set_round(&0);
+                verification_account.set_instruction(&0);
+            }
+        }
         VerificationStep::CombinedMillerLoop => {
             // Proof first has to be setup
             guard!(
@@ -107,7 +135,7 @@ pub fn prepare_public_inputs(
             .prepared_inputs
             .set(G1A(prepared_inputs));
 
-        verification_account.set_step(&VerificationStep::CombinedMillerLoop);
+        verification_account.set_step(&VerificationStep::ProofSubgroupCheck);
         verification_account.set_round(&0);
         verification_account.set_instruction(&0);
     } else {
@@ -118,6 +146,46 @@ pub fn prepare_public_inputs(
     Ok(())
 }
 
+/// Checks that the proof's `b` is part of the prime-order subgroup of G2
+///
+/// # Notes
+///
+/// [`crate::processor::init_verification_proof`] only checks that `b` is a canonical point on the twist,
+/// since the subgroup check exceeds the compute budget of a single instruction.
+/// An invalid `b` results in [`ElusivError::InvalidProofPoint`] (and thereby in a failed verification).
+/// After the last instruction the caller advances to the `CombinedMillerLoop`.
+pub fn g2_subgroup_check(
+    verification_account: &mut VerificationAccount,
+    instruction: usize,
+    round: usize,
+) -> ElusivResult {
+    guard!(
+        instruction < G2SubgroupCheck::IX_COUNT,
+        ComputationIsAlreadyFinished
+    );
+
+    let rounds = G2SubgroupCheck::INSTRUCTION_ROUNDS[instruction] as usize;
+
+    let mut r = verification_account.r.get();
+    let b = verification_account.b.get().0;
+
+    let mut result = None;
+    for round in round..round + rounds {
+        result = g2_subgroup_check_partial(round, verification_account, &b, &mut r)?;
+    }
+
+    if round + rounds == G2SubgroupCheck::TOTAL_ROUNDS as usize {
+        guard!(result.ok_or(CouldNotProcessProof)?, InvalidProofPoint);
+    } else {
+        verification_account.r.set(r);
+    }
+
+    verification_account.set_round(&usize_as_u32_safe(round + rounds));
+    verification_account.set_instruction(&(instruction as u32 + 1));
+
+    Ok(())
+}
+
 pub fn combined_miller_loop(
     verification_account: &mut VerificationAccount,
     vkey: &VerifyingKey,
@@ -179,6 +247,21 @@ pub fn final_exponentiation(
     instruction: usize,
     round: usize,
 ) -> Result<Option<bool>, ElusivError> {
+    let v = final_exponentiation_step(verification_account, instruction, round)?;
+
+    // Final verification, we check:
+    // https://github.com/zkcrypto/bellman/blob/9bb30a7bd261f2aa62840b80ed6750c622bebec3/src/groth16/verifier.rs#L43
+    // https://github.com/arkworks-rs/groth16/blob/765817f77a6e14964c6f264d565b18676b11bd59/src/verifier.rs#L60
+    Ok(v.map(|v| vkey.alpha_beta() == v))
+}
+
+/// Performs the final exponentiation rounds of a single instruction on `verification_account.f`
+/// - returns the final exponentiation result after the last instruction
+pub(crate) fn final_exponentiation_step(
+    verification_account: &mut VerificationAccount,
+    instruction: usize,
+    round: usize,
+) -> Result<Option<Fq12>, ElusivError> {
     guard!(
         instruction < FinalExponentiation::IX_COUNT,
         ComputationIsAlreadyFinished
@@ -200,10 +283,7 @@ pub fn final_exponentiation(
         let v = result.ok_or(CouldNotProcessProof)?;
         verification_account.f.set(Wrap(v));
 
-        // Final verification, we check:
-        // https://github.com/zkcrypto/bellman/blob/9bb30a7bd261f2aa62840b80ed6750c622bebec3/src/groth16/verifier.rs#L43
-        // https://github.com/arkworks-rs/groth16/blob/765817f77a6e14964c6f264d565b18676b11bd59/src/verifier.rs#L60
-        return Ok(Some(vkey.alpha_beta() == v));
+        return Ok(Some(v));
     }
 
     Ok(None)
@@ -315,6 +395,13 @@ pub fn prepare_public_inputs_instructions(
 ) -> Vec<u32> {
     assert!(public_inputs.len() == public_inputs_count);
 
+    // The syscall backend prepares all public inputs in a single instruction
+    if cfg!(feature = "alt-bn128") {
+        return vec![usize_as_u32_safe(prepare_public_inputs_rounds(
+            public_inputs_count,
+        ))];
+    }
+
     let mut instructions = Vec::new();
 
     let mut total_rounds = 0;
@@ -373,9 +460,16 @@ const_assert_eq!(MUL_BY_034_ROUNDS_COUNT, 3);
 #[cfg(test)]
 const_assert_eq!(COMBINED_ELL_ROUNDS_COUNT, 13);
 
+pub const G2_SUBGROUP_CHECK_IXS: usize = 33;
 pub const COMBINED_MILLER_LOOP_IXS: usize = 215;
 pub const FINAL_EXPONENTIATION_IXS: usize = 17;
 
+#[cfg(test)]
+const_assert_eq!(G2SubgroupCheck::IX_COUNT, G2_SUBGROUP_CHECK_IXS);
+
+#[cfg(test)]
+const_assert_eq!(G2SubgroupCheck::TX_COUNT, 7);
+
 #[cfg(test)]
 const_assert_eq!(CombinedMillerLoop::IX_COUNT, COMBINED_MILLER_LOOP_IXS);
 
@@ -628,6 +722,59 @@ elusiv_computations!(
     }
 );
 
+elusiv_computations!(
+    g2_subgroup_check, G2SubgroupCheck, 250_000,
+
+    // Subgroup check `[x + 1]P + ψ([x]P) + ψ²([x]P) - ψ³([2x]P) == 0` (https://eprint.iacr.org/2022/348.pdf)
+    // - `r` is used as the accumulator in jacobian coordinates
+    // - after the scalar multiplication `T = [x]P`, the ψ-terms are evaluated as `T + ψ(T + ψ(T - 2ψ(T)))`
+    // - reference implementation: `crate::fields::is_in_g2_subgroup`
+    g2_subgroup_check(
+        storage: &mut VerificationAccount,
+        b: &G2Affine, r: &mut G2HomProjective,
+    ) -> bool {
+        {   /// 1000
+            r.x = b.x;
+            r.y = b.y;
+            r.z = Fq2::one();
+        }
+
+        // Bits of `x` = 4965661367192848881 with the leading one removed (most significant first)
+        {
+            for i, bit in [0,0,0,1,0,0,1,1,1,0,1,0,0,1,1,0,0,1,0,0,1,0,1,0,1,1,0,1,0,0,0,1,0,0,1,0,1,0,0,1,1,0,1,0,0,1,0,0,0,0,1,0,0,1,1,1,1,1,0,0,0,1] {
+                {   /// 60_000
+                    _ = g2_double_in_place(r);
+                }
+                {   /// bit in { 0 : 0 , _ : 90_000 }
+                    if (bit > 0) {
+                        _ = g2_add_mixed_in_place(r, b);
+                    }
+                }
+            }
+        }
+
+        {   /// 85_000
+            let tx: Fq2 = r.x;
+            let ty: Fq2 = r.y;
+            let tz: Fq2 = r.z;
+            _ = g2_psi_double_neg_in_place(r);
+        }
+        {   /// 120_000
+            _ = g2_add_in_place(r, &tx, &ty, &tz);
+        }
+        {   /// 140_000
+            _ = g2_psi_add_in_place(r, &tx, &ty, &tz);
+        }
+        {   /// 140_000
+            _ = g2_psi_add_in_place(r, &tx, &ty, &tz);
+        }
+        {   /// 95_000
+            _ = g2_add_mixed_in_place(r, b);
+            return r.z.is_zero();
+        }
+    }
+);
+
 #[cfg(test)]
 const_assert_eq!(INVERSE_FQ12_ROUNDS_COUNT, 4);
 #[cfg(test)]
@@ -830,6 +977,46 @@ const COEFF_B: Fq2 = field_new!(
     ),
 );
 
+/// Jacobian G2 arithmetic on a [`G2HomProjective`] (used by the `G2SubgroupCheck`)
+fn g2_jacobian(r: &G2HomProjective) -> G2Projective {
+    G2Projective::new(r.x, r.y, r.z)
+}
+
+fn set_g2_jacobian(r: &mut G2HomProjective, p: &G2Projective) {
+    r.x = p.x;
+    r.y = p.y;
+    r.z = p.z;
+}
+
+fn g2_double_in_place(r: &mut G2HomProjective) {
+    let mut p = g2_jacobian(r);
+    p.double_in_place();
+    set_g2_jacobian(r, &p);
+}
+
+fn g2_add_mixed_in_place(r: &mut G2HomProjective, q: &G2Affine) {
+    let mut p = g2_jacobian(r);
+    p.add_assign_mixed(q);
+    set_g2_jacobian(r, &p);
+}
+
+fn g2_add_in_place(r: &mut G2HomProjective, x: &Fq2, y: &Fq2, z: &Fq2) {
+    let p = g2_jacobian(r) + G2Projective::new(*x, *y, *z);
+    set_g2_jacobian(r, &p);
+}
+
+/// `r = -2ψ(r)`
+fn g2_psi_double_neg_in_place(r: &mut G2HomProjective) {
+    let p = psi(&g2_jacobian(r)).double().neg();
+    set_g2_jacobian(r, &p);
+}
+
+/// `r = ψ(r) + (x, y, z)`
+fn g2_psi_add_in_place(r: &mut G2HomProjective, x: &Fq2, y: &Fq2, z: &Fq2) {
+    let p = psi(&g2_jacobian(r)) + G2Projective::new(*x, *y, *z);
+    set_g2_jacobian(r, &p);
+}
+
 type Coefficients = (Fq2, Fq2, Fq2);
 fn new_coeffs(c0: Fq2, c1: Fq2, c2: Fq2) -> Coefficients {
     (c0, c1, c2)
@@ -854,12 +1041,12 @@ fn sub_and_mul_base_field_by_nonresidue(x: Fq6, y: Fq6) -> Fq6 {
     x + mul_base_field_by_nonresidue(y)
 }*/
 
-fn mul_base_field_by_nonresidue(v: Fq6) -> Fq6 {
+pub(crate) fn mul_base_field_by_nonresidue(v: Fq6) -> Fq6 {
     Fp12ParamsWrapper::<Fq12Parameters>::mul_base_field_by_nonresidue(&v)
 }
 
 // https://github.com/arkworks-rs/r1cs-std/blob/b7874406ec614748608b1739b1578092a8c97fb8/src/fields/fp6_3over2.rs#L53
-fn mul_fq6_by_c0_c1_0(f: Fq6, c0: &Fq2, c1: &Fq2) -> Fq6 {
+pub(crate) fn mul_fq6_by_c0_c1_0(f: Fq6, c0: &Fq2, c1: &Fq2) -> Fq6 {
     let v0: Fq2 = f.c0 * c0;
     let v1: Fq2 = f.c1 * c1;
 
@@ -878,7 +1065,7 @@ fn mul_fq6_by_c0_c1_0(f: Fq6, c0: &Fq2, c1: &Fq2) -> Fq6 {
     )
 }
 
-fn mul_by_fp(v: &Fq2, fp: Fq) -> Fq2 {
+pub(crate) fn mul_by_fp(v: &Fq2, fp: Fq) -> Fq2 {
     let mut v: Fq2 = *v;
     v.mul_assign_by_fp(&fp);
     v
@@ -970,7 +1157,9 @@ mod tests {
     use ark_bn254::{Bn254, Fr};
     use ark_ec::bn::G2Prepared;
     use ark_ec::models::bn::BnParameters;
+    use ark_ec::AffineCurve;
     use ark_ec::PairingEngine;
+    use ark_ff::PrimeField;
     use ark_groth16::prepare_inputs;
     use assert_matches::assert_matches;
     use solana_program::native_token::LAMPORTS_PER_SOL;
@@ -1292,6 +1481,106 @@ mod tests {
         assert_eq!(storage.f.get().0, expected);
     }
 
+    fn g2_subgroup_check_result(b: G2Affine) -> ElusivResult {
+        zero_program_account!(mut storage, VerificationAccount);
+        storage.b.set(G2A(b));
+        storage.set_step(&VerificationStep::ProofSubgroupCheck);
+
+        for i in 0..G2_SUBGROUP_CHECK_IXS {
+            let round = storage.get_round();
+            g2_subgroup_check(&mut storage, i, round as usize)?;
+        }
+        assert_eq!(
+            storage.get_instruction() as usize,
+            G2SubgroupCheck::IX_COUNT
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_g2_subgroup_check() {
+        for p in valid_proofs() {
+            assert_matches!(g2_subgroup_check_result(p.proof.b.0), Ok(()));
+        }
+
+        let g = G2Affine::prime_subgroup_generator();
+        let p = g.mul(Fr::from(123456789u64).into_repr()).into_affine();
+        assert_matches!(g2_subgroup_check_result(g), Ok(()));
+        assert_matches!(g2_subgroup_check_result(p), Ok(()));
+
+        // Points on the twist (mostly) outside of the subgroup
+        let mut outside_count = 0;
+        for i in 0..20u64 {
+            let x = Fq2::new(Fq::from(i), Fq::one());
+            if let Some(p) = G2Affine::get_point_from_x(x, false) {
+                if p.is_in_correct_subgroup_assuming_on_curve() {
+                    assert_matches!(g2_subgroup_check_result(p), Ok(()));
+                } else {
+                    assert_matches!(g2_subgroup_check_result(p), Err(InvalidProofPoint));
+                    outside_count += 1;
+                }
+            }
+        }
+        assert!(outside_count > 0);
+    }
+
+    #[test]
+    fn test_verify_partial_g2_subgroup_check_tx_alignment() {
+        vkey!(vkey, TestVKey);
+        let p = &valid_proofs()[0];
+
+        zero_program_account!(mut storage, VerificationAccount);
+        setup_storage_account::<TestVKey>(&mut storage, p.proof, &p.public_inputs);
+        storage.set_step(&VerificationStep::ProofSubgroupCheck);
+
+        let compute_ixs = COMPUTE_VERIFICATION_IX_COUNT as usize - 2;
+        for i in 0..G2SubgroupCheck::TX_COUNT * compute_ixs {
+            assert_matches!(storage.get_step(), VerificationStep::ProofSubgroupCheck);
+
+            let instruction_index = (i % compute_ixs + 2) as u16;
+            assert_matches!(
+                verify_partial(&mut storage, &vkey, instruction_index),
+                Ok(None)
+            );
+        }
+
+        // The miller loop starts with a new tx
+        assert_matches!(storage.get_step(), VerificationStep::CombinedMillerLoop);
+        assert_eq!(storage.get_instruction(), 0);
+    }
+
+    #[test]
+    fn test_verify_partial_g2_subgroup_check() {
+        vkey!(vkey, TestVKey);
+        let p = &valid_proofs()[0];
+
+        let b = (0..20u64)
+            .filter_map(|i| G2Affine::get_point_from_x(Fq2::new(Fq::from(i), Fq::one()), false))
+            .find(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap();
+        let mut proof = p.proof;
+        proof.b = G2A(b);
+
+        zero_program_account!(mut storage, VerificationAccount);
+        setup_storage_account::<TestVKey>(&mut storage, proof, &p.public_inputs);
+
+        for _ in 0..storage.get_prepare_inputs_instructions_count() {
+            verify_partial(&mut storage, &vkey, COMPUTE_VERIFICATION_IX_COUNT - 1).unwrap();
+        }
+        assert_matches!(storage.get_step(), VerificationStep::ProofSubgroupCheck);
+
+        let mut result = Ok(None);
+        for _ in 0..G2_SUBGROUP_CHECK_IXS {
+            result = verify_partial(&mut storage, &vkey, 0);
+            if result.is_err() {
+                break;
+            }
+        }
+        assert_matches!(result, Err(InvalidProofPoint));
+        assert_matches!(storage.get_step(), VerificationStep::ProofSubgroupCheck);
+    }
+
     #[test]
     fn test_addition_step() {
         zero_program_account!(mut storage, VerificationAccount);
@@ -1453,6 +1742,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "alt-bn128"))]
     fn test_public_inputs_preparation_costs() {
         let abc = SendPublicInputs {
             join_split: JoinSplitPublicInputs {
@@ -1503,12 +1793,18 @@ mod tests {
         zero_program_account!(mut storage, VerificationAccount);
         setup_storage_account::<VKey>(&mut storage, proof, public_inputs);
         let instruction_count = storage.get_prepare_inputs_instructions_count() as usize
+            + G2_SUBGROUP_CHECK_IXS
             + COMBINED_MILLER_LOOP_IXS
             + FINAL_EXPONENTIATION_IXS;
 
         let mut result = None;
         for _ in 0..instruction_count {
-            result = verify_partial(&mut storage, vkey, COMPUTE_VERIFICATION_IX_COUNT - 1).unwrap();
+            match verify_partial(&mut storage, vkey, COMPUTE_VERIFICATION_IX_COUNT - 1) {
+                Ok(v) => result = v,
+                // Points outside of the G2 subgroup
+                Err(InvalidProofPoint) => return false,
+                Err(e) => panic!("{:?}", e),
+            }
         }
 
         result.unwrap()
@@ -1542,6 +1838,7 @@ mod tests {
         zero_program_account!(mut storage, VerificationAccount);
         setup_storage_account::<TestVKey>(&mut storage, proof, &public_inputs);
         let instruction_count = storage.get_prepare_inputs_instructions_count() as usize
+            + G2_SUBGROUP_CHECK_IXS
             + COMBINED_MILLER_LOOP_IXS
             + FINAL_EXPONENTIATION_IXS;
 
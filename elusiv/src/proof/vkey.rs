@@ -166,7 +166,6 @@ impl<'a> VerifyingKey<'a> {
         Wrap::try_from_slice(slice).unwrap().0
     }
 
-    #[cfg(feature = "elusiv-client")]
     pub fn alpha(&self) -> G1Affine {
         let offset =
             Wrap::<Fq12>::SIZE + G1A::SIZE + self.gamma_abc_size + 2 * Self::COEFFS_ARRAY_SIZE;
@@ -174,7 +173,6 @@ impl<'a> VerifyingKey<'a> {
         G1A::try_from_slice(slice).unwrap().0
     }
 
-    #[cfg(feature = "elusiv-client")]
     pub fn beta(&self) -> ark_bn254::G2Affine {
         let offset = Wrap::<Fq12>::SIZE
             + G1A::SIZE
@@ -185,7 +183,6 @@ impl<'a> VerifyingKey<'a> {
         G2A::try_from_slice(slice).unwrap().0
     }
 
-    #[cfg(feature = "elusiv-client")]
     pub fn gamma(&self) -> ark_bn254::G2Affine {
         let offset = Wrap::<Fq12>::SIZE
             + G1A::SIZE
@@ -197,7 +194,6 @@ impl<'a> VerifyingKey<'a> {
         G2A::try_from_slice(slice).unwrap().0
     }
 
-    #[cfg(feature = "elusiv-client")]
     pub fn delta(&self) -> ark_bn254::G2Affine {
         let offset = Wrap::<Fq12>::SIZE
             + G1A::SIZE
@@ -210,6 +206,66 @@ impl<'a> VerifyingKey<'a> {
     }
 }
 
+/// Generates the [`VerifyingKey`] source from a Groth16 verifying key
+///
+/// # Notes
+///
+/// The public inputs count is `vk.gamma_abc_g1.len() - 1`.
+#[cfg(feature = "elusiv-client")]
+pub fn verifying_key_source(vk: &ark_groth16::VerifyingKey<ark_bn254::Bn254>) -> Vec<u8> {
+    use ark_bn254::{Bn254, Parameters};
+    use ark_ec::bn::G2Prepared;
+    use ark_ec::{PairingEngine, ProjectiveCurve};
+    use borsh::BorshSerialize;
+    use std::ops::Neg;
+
+    assert!(!vk.gamma_abc_g1.is_empty());
+    let public_inputs_count = vk.gamma_abc_g1.len() - 1;
+
+    let mut source = Wrap(Bn254::pairing(vk.alpha_g1, vk.beta_g2))
+        .try_to_vec()
+        .unwrap();
+    source.extend(G1A(vk.gamma_abc_g1[0]).try_to_vec().unwrap());
+
+    // gamma_abc[i][j][k - 1] = (k * 256^j) * gamma_abc_g1[i + 1]
+    for gamma_abc in &vk.gamma_abc_g1[1..] {
+        let mut base = gamma_abc.into_projective();
+        for _ in 0..32 {
+            let mut windows = Vec::with_capacity(255);
+            let mut acc = base;
+            for _ in 0..255 {
+                windows.push(acc);
+                acc += &base;
+            }
+            G1Projective::batch_normalization(&mut windows);
+            for window in windows {
+                source.extend(G1A(window.into_affine()).try_to_vec().unwrap());
+            }
+
+            // acc = 256 * base
+            base = acc;
+        }
+    }
+
+    let gamma_neg: G2Prepared<Parameters> = vk.gamma_g2.neg().into();
+    let delta_neg: G2Prepared<Parameters> = vk.delta_g2.neg().into();
+    for prepared in [gamma_neg, delta_neg] {
+        for (c0, c1, c2) in prepared.ell_coeffs {
+            for c in [c0, c1, c2] {
+                source.extend(Wrap(c).try_to_vec().unwrap());
+            }
+        }
+    }
+
+    source.extend(G1A(vk.alpha_g1).try_to_vec().unwrap());
+    for g2 in [vk.beta_g2, vk.gamma_g2, vk.delta_g2] {
+        source.extend(G2A(g2).try_to_vec().unwrap());
+    }
+
+    assert_eq!(source.len(), VerifyingKey::source_size(public_inputs_count));
+    source
+}
+
 /// Groth16 verifying key used for testing purposes
 /// Reference: https://github.com/elusiv-privacy/elusiv-verifying-key/blob/main/src/lib.rs#L13
 #[cfg(feature = "test-elusiv")]
@@ -326,11 +382,35 @@ mod test {
         }
     }
 
+    fn test_vkey_source<VKey: VerifyingKeyInfo>() {
+        let vk =
+            crate::proof::convert::verifying_key_from_snarkjs_json(VKey::verification_key_json())
+                .unwrap();
+        assert_eq!(vk, VKey::arkworks_vk());
+
+        let source = verifying_key_source(&vk);
+        assert_eq!(
+            source.len(),
+            VerifyingKey::source_size(VKey::public_inputs_count())
+        );
+        assert!(source == VKey::verifying_key_source());
+    }
+
     #[test]
     fn test_send_quadra_vkey() {
         test_vkey::<SendQuadraVKey>()
     }
 
+    #[test]
+    fn test_send_quadra_vkey_source() {
+        test_vkey_source::<SendQuadraVKey>()
+    }
+
+    #[test]
+    fn test_migrate_unary_vkey_source() {
+        test_vkey_source::<MigrateUnaryVKey>()
+    }
+
     #[test]
     fn test_migrate_unary_vkey() {
         test_vkey::<MigrateUnaryVKey>()
@@ -0,0 +1,394 @@
+//! Read-only views of the APA-accounts of the elusiv-warden-network program
+
+use super::program_account::PDAAccountData;
+use crate::error::ElusivError;
+use crate::macros::{guard, program_id, BorshSerDeSized};
+use crate::token::TokenID;
+use borsh::{BorshDeserialize, BorshSerialize};
+use elusiv_types::{BorshSerDeSized, ElusivOption};
+use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+pub const WARDEN_NETWORK_PROGRAM_ID: Pubkey = program_id!(elusiv - warden - network);
+
+/// PDA-seed of `elusiv_warden_network::apa::ApaTargetMapAccount`
+const APA_TARGET_MAP_SEED: &[u8] = b"ApaTargetMap";
+
+/// PDA-seed of `elusiv_warden_network::apa::ApaProposalAccount`
+const APA_PROPOSAL_SEED: &[u8] = b"ApaProposal";
+
+/// Mirrors `elusiv_warden_network::apa::ApaLevel`
+#[repr(u8)]
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Clone, Copy)]
+#[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
+pub enum ApaLevel {
+    Flag1,
+    Flag2,
+    Outcast,
+}
+
+/// The leading fields of `elusiv_warden_network::apa::ApaProposal` (the trailing reason is omitted)
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, Clone)]
+#[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
+pub struct ApaProposalHeader {
+    pub proponent: Pubkey,
+    pub proponent_role: u8,
+    pub timestamp: u64,
+    pub target: Pubkey,
+    pub level: ApaLevel,
+    pub token_constraint: ElusivOption<TokenID>,
+}
+
+/// Mirrors `elusiv_warden_network::apa::ApaTargetMapAccount`
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized)]
+struct ApaTargetMap {
+    pda_data: PDAAccountData,
+    proposal_id: ElusivOption<u32>,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+#[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
+pub enum ApaAction {
+    Deposit,
+    Withdrawal,
+}
+
+impl ApaLevel {
+    /// Whether the policy of the level permits `action` for `token_id`
+    ///
+    /// # Notes
+    ///
+    /// - [`ApaLevel::Flag1`]: deposits are restricted to the `token_constraint`
+    /// - [`ApaLevel::Flag2`]: deposits and withdrawals are restricted to the `token_constraint`
+    /// - [`ApaLevel::Outcast`]: neither deposits nor withdrawals are permitted
+    pub fn permits(
+        &self,
+        action: ApaAction,
+        token_id: TokenID,
+        token_constraint: Option<TokenID>,
+    ) -> bool {
+        let is_token_permitted = token_constraint.is_none() || token_constraint == Some(token_id);
+
+        match self {
+            ApaLevel::Flag1 => action == ApaAction::Withdrawal || is_token_permitted,
+            ApaLevel::Flag2 => is_token_permitted,
+            ApaLevel::Outcast => false,
+        }
+    }
+}
+
+/// Enforces the policy of the [`ApaProposalHeader`] (if any) associated with `target`
+pub fn enforce_apa_policy(
+    target: &Pubkey,
+    target_map_account: &AccountInfo,
+    proposal_account: &AccountInfo,
+    action: ApaAction,
+    token_id: TokenID,
+) -> Result<(), ProgramError> {
+    if let Some(proposal) = load_apa_proposal(target, target_map_account, proposal_account)? {
+        guard!(
+            proposal
+                .level
+                .permits(action, token_id, proposal.token_constraint.option()),
+            ElusivError::ApaRestricted
+        );
+    }
+
+    Ok(())
+}
+
+/// Loads the [`ApaProposalHeader`] referenced by the `ApaTargetMapAccount` of `target`
+///
+/// # Notes
+///
+/// - `target_map_account` is required to be the `ApaTargetMapAccount` of `target` (an unopened account means that there is no proposal).
+/// - `proposal_account` is only verified if the map references a proposal (otherwise any account can be supplied).
+pub fn load_apa_proposal(
+    target: &Pubkey,
+    target_map_account: &AccountInfo,
+    proposal_account: &AccountInfo,
+) -> Result<Option<ApaProposalHeader>, ProgramError> {
+    if *target_map_account.owner != WARDEN_NETWORK_PROGRAM_ID {
+        let (pubkey, _) = Pubkey::find_program_address(
+            &[APA_TARGET_MAP_SEED, &target.to_bytes()],
+            &WARDEN_NETWORK_PROGRAM_ID,
+        );
+        guard!(
+            *target_map_account.key == pubkey,
+            ElusivError::InvalidAccount
+        );
+
+        return Ok(None);
+    }
+
+    let data = &target_map_account.data.borrow()[..];
+    guard!(
+        data.len() >= ApaTargetMap::SIZE,
+        ElusivError::InvalidAccount
+    );
+    let map = ApaTargetMap::try_from_slice(&data[..ApaTargetMap::SIZE])?;
+    let pubkey = Pubkey::create_program_address(
+        &[
+            APA_TARGET_MAP_SEED,
+            &target.to_bytes(),
+            &[map.pda_data.bump_seed],
+        ],
+        &WARDEN_NETWORK_PROGRAM_ID,
+    )
+    .or(Err(ElusivError::InvalidAccount))?;
+    guard!(
+        *target_map_account.key == pubkey,
+        ElusivError::InvalidAccount
+    );
+
+    let proposal_id = match map.proposal_id.option() {
+        Some(proposal_id) => proposal_id,
+        None => return Ok(None),
+    };
+
+    guard!(
+        *proposal_account.owner == WARDEN_NETWORK_PROGRAM_ID,
+        ElusivError::InvalidAccount
+    );
+
+    let data = &proposal_account.data.borrow()[..];
+    guard!(
+        data.len() >= PDAAccountData::SIZE + ApaProposalHeader::SIZE,
+        ElusivError::InvalidAccount
+    );
+    let pubkey = Pubkey::create_program_address(
+        &[APA_PROPOSAL_SEED, &proposal_id.to_le_bytes(), &[data[0]]],
+        &WARDEN_NETWORK_PROGRAM_ID,
+    )
+    .or(Err(ElusivError::InvalidAccount))?;
+    guard!(*proposal_account.key == pubkey, ElusivError::InvalidAccount);
+
+    let proposal = ApaProposalHeader::try_from_slice(
+        &data[PDAAccountData::SIZE..PDAAccountData::SIZE + ApaProposalHeader::SIZE],
+    )?;
+    guard!(proposal.target == *target, ElusivError::InvalidAccount);
+
+    Ok(Some(proposal))
+}
+
+/// The `ApaTargetMapAccount` of `target`
+pub fn apa_target_map_account(target: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[APA_TARGET_MAP_SEED, &target.to_bytes()],
+        &WARDEN_NETWORK_PROGRAM_ID,
+    )
+    .0
+}
+
+/// The `ApaProposalAccount` with `proposal_id`
+pub fn apa_proposal_account(proposal_id: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[APA_PROPOSAL_SEED, &proposal_id.to_le_bytes()],
+        &WARDEN_NETWORK_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Creates an `ApaTargetMapAccount` for `target`, referencing an `ApaProposalAccount` with `proposal_id` (returned as `(pubkey, data)` pairs)
+#[cfg(any(test, feature = "test-elusiv"))]
+pub fn apa_accounts_data(
+    target: &Pubkey,
+    proposal_id: u32,
+    level: ApaLevel,
+    token_constraint: Option<TokenID>,
+) -> ((Pubkey, Vec<u8>), (Pubkey, Vec<u8>)) {
+    let (map_pubkey, map_bump) = Pubkey::find_program_address(
+        &[APA_TARGET_MAP_SEED, &target.to_bytes()],
+        &WARDEN_NETWORK_PROGRAM_ID,
+    );
+    let map = ApaTargetMap {
+        pda_data: PDAAccountData {
+            bump_seed: map_bump,
+            version: 0,
+        },
+        proposal_id: ElusivOption::Some(proposal_id),
+    };
+
+    let (proposal_pubkey, proposal_bump) = Pubkey::find_program_address(
+        &[APA_PROPOSAL_SEED, &proposal_id.to_le_bytes()],
+        &WARDEN_NETWORK_PROGRAM_ID,
+    );
+    let mut proposal_data = PDAAccountData {
+        bump_seed: proposal_bump,
+        version: 0,
+    }
+    .try_to_vec()
+    .unwrap();
+    proposal_data.extend(
+        ApaProposalHeader {
+            proponent: Pubkey::new_unique(),
+            proponent_role: 0,
+            timestamp: 0,
+            target: *target,
+            level,
+            token_constraint: token_constraint.into(),
+        }
+        .try_to_vec()
+        .unwrap(),
+    );
+
+    // Reason
+    proposal_data.extend(vec![0; 4 + 512]);
+
+    (
+        (map_pubkey, map.try_to_vec().unwrap()),
+        (proposal_pubkey, proposal_data),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::macros::{account_info, test_account_info};
+    use crate::token::{LAMPORTS_TOKEN_ID, USDC_TOKEN_ID};
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn test_apa_level_permits() {
+        use ApaAction::{Deposit, Withdrawal};
+
+        for action in [Deposit, Withdrawal] {
+            for token_id in [LAMPORTS_TOKEN_ID, USDC_TOKEN_ID] {
+                assert!(ApaLevel::Flag1.permits(action, token_id, None));
+                assert!(ApaLevel::Flag2.permits(action, token_id, None));
+                assert!(!ApaLevel::Outcast.permits(action, token_id, None));
+                assert!(!ApaLevel::Outcast.permits(action, token_id, Some(token_id)));
+            }
+        }
+
+        assert!(ApaLevel::Flag1.permits(Deposit, USDC_TOKEN_ID, Some(USDC_TOKEN_ID)));
+        assert!(!ApaLevel::Flag1.permits(Deposit, LAMPORTS_TOKEN_ID, Some(USDC_TOKEN_ID)));
+        assert!(ApaLevel::Flag1.permits(Withdrawal, LAMPORTS_TOKEN_ID, Some(USDC_TOKEN_ID)));
+
+        assert!(ApaLevel::Flag2.permits(Deposit, USDC_TOKEN_ID, Some(USDC_TOKEN_ID)));
+        assert!(!ApaLevel::Flag2.permits(Deposit, LAMPORTS_TOKEN_ID, Some(USDC_TOKEN_ID)));
+        assert!(!ApaLevel::Flag2.permits(Withdrawal, LAMPORTS_TOKEN_ID, Some(USDC_TOKEN_ID)));
+    }
+
+    #[test]
+    fn test_load_apa_proposal_unopened_map() {
+        let target = Pubkey::new_unique();
+        account_info!(map, apa_target_map_account(&target));
+        test_account_info!(any);
+
+        assert_matches!(load_apa_proposal(&target, &map, &any), Ok(None));
+
+        // Invalid map account
+        assert_matches!(load_apa_proposal(&target, &any, &any), Err(_));
+        assert_matches!(load_apa_proposal(&Pubkey::new_unique(), &map, &any), Err(_));
+    }
+
+    #[test]
+    fn test_load_apa_proposal() {
+        let target = Pubkey::new_unique();
+        let ((map_pubkey, map_data), (proposal_pubkey, proposal_data)) =
+            apa_accounts_data(&target, 3, ApaLevel::Flag2, Some(USDC_TOKEN_ID));
+        account_info!(
+            map,
+            map_pubkey,
+            map_data.clone(),
+            WARDEN_NETWORK_PROGRAM_ID,
+            false
+        );
+        account_info!(
+            proposal,
+            proposal_pubkey,
+            proposal_data.clone(),
+            WARDEN_NETWORK_PROGRAM_ID,
+            false
+        );
+
+        let header = load_apa_proposal(&target, &map, &proposal)
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.target, target);
+        assert_eq!(header.level, ApaLevel::Flag2);
+        assert_eq!(header.token_constraint.option(), Some(USDC_TOKEN_ID));
+
+        // Invalid proposal account
+        test_account_info!(any);
+        assert_matches!(load_apa_proposal(&target, &map, &any), Err(_));
+
+        account_info!(
+            invalid_owner_proposal,
+            proposal_pubkey,
+            proposal_data,
+            crate::id(),
+            false
+        );
+        assert_matches!(
+            load_apa_proposal(&target, &map, &invalid_owner_proposal),
+            Err(_)
+        );
+
+        // Map of a different target
+        assert_matches!(
+            load_apa_proposal(&Pubkey::new_unique(), &map, &proposal),
+            Err(_)
+        );
+
+        // Proposal of a different target
+        let ((other_map_pubkey, other_map_data), _) =
+            apa_accounts_data(&Pubkey::new_unique(), 3, ApaLevel::Flag2, None);
+        account_info!(
+            other_map,
+            other_map_pubkey,
+            other_map_data,
+            WARDEN_NETWORK_PROGRAM_ID,
+            false
+        );
+        assert_matches!(load_apa_proposal(&target, &other_map, &proposal), Err(_));
+
+        // Map without a proposal
+        let mut map_data = map_data;
+        map_data[PDAAccountData::SIZE..].fill(0);
+        account_info!(
+            empty_map,
+            map_pubkey,
+            map_data,
+            WARDEN_NETWORK_PROGRAM_ID,
+            false
+        );
+        assert_matches!(load_apa_proposal(&target, &empty_map, &any), Ok(None));
+    }
+
+    #[test]
+    fn test_enforce_apa_policy() {
+        let target = Pubkey::new_unique();
+        let ((map_pubkey, map_data), (proposal_pubkey, proposal_data)) =
+            apa_accounts_data(&target, 0, ApaLevel::Outcast, None);
+        account_info!(map, map_pubkey, map_data, WARDEN_NETWORK_PROGRAM_ID, false);
+        account_info!(
+            proposal,
+            proposal_pubkey,
+            proposal_data,
+            WARDEN_NETWORK_PROGRAM_ID,
+            false
+        );
+
+        for action in [ApaAction::Deposit, ApaAction::Withdrawal] {
+            assert_matches!(
+                enforce_apa_policy(&target, &map, &proposal, action, LAMPORTS_TOKEN_ID),
+                Err(_)
+            );
+        }
+
+        // Targets without proposals are unrestricted
+        let target = Pubkey::new_unique();
+        account_info!(map, apa_target_map_account(&target));
+        assert_matches!(
+            enforce_apa_policy(
+                &target,
+                &map,
+                &proposal,
+                ApaAction::Deposit,
+                LAMPORTS_TOKEN_ID
+            ),
+            Ok(())
+        );
+    }
+}
@@ -1,18 +1,31 @@
 use crate::bytes::{contains, usize_as_u32_safe};
-use crate::commitment::poseidon_hash::BinarySpongeHashingState;
-use crate::commitment::{commitments_per_batch, MAX_HT_SIZE, MT_HEIGHT};
+use crate::commitment::poseidon_hash::{BinarySpongeHashingState, ROUNDS_PER_HASH};
+use crate::commitment::{
+    base_commitment_hash_computation_instructions, commitment_hash_computation_instructions,
+    commitments_per_batch, MAX_HT_SIZE, MT_HEIGHT,
+};
 use crate::error::ElusivError;
 use crate::fields::{fr_to_u256_le, u256_to_fr_skip_mr};
 use crate::macros::{elusiv_account, guard, two_pow};
 use crate::processor::BaseCommitmentHashRequest;
-use crate::state::program_account::PDAAccountData;
-use crate::state::storage::{StorageAccount, HISTORY_ARRAY_SIZE};
+use crate::state::program_account::{PDAAccountData, PDAOffset};
+use crate::state::storage::{
+    shard_pda_offset, StorageAccount, EMPTY_TREE, MAX_COMMITMENT_SHARDS_COUNT, MT_COMMITMENT_COUNT,
+};
 use crate::types::U256;
 use ark_bn254::Fr;
 use ark_ff::{BigInteger256, PrimeField};
+use solana_program::entrypoint::ProgramResult;
 use solana_program::program_error::ProgramError;
 
+/// The max. number of base commitments hashed in a single [`BaseCommitmentHashingAccount`]
+pub const MAX_BASE_COMMITMENT_BATCH_SIZE: usize = 8;
+
 /// Account used for computing `commitment = h(base_commitment, amount)`
+///
+/// # Note
+///
+/// A batch of base commitments (of the same token) is hashed sequentially, the account is opened with one `batch` entry per base commitment.
 #[elusiv_account(partial_computation: true, eager_type: true)]
 pub struct BaseCommitmentHashingAccount {
     #[no_getter]
@@ -29,6 +42,13 @@ pub struct BaseCommitmentHashingAccount {
     token_id: u16,
     pub state: BinarySpongeHashingState,
     pub min_batching_rate: u32,
+
+    /// Bitmask of the commitments that have already been enqueued (by a finalization per shard)
+    pub finalized_mask: u32,
+
+    /// The initial hashing states of the base commitments (replaced by the final states once hashed)
+    #[growable]
+    pub batch: [BinarySpongeHashingState; 1],
 }
 
 impl<'a> BaseCommitmentHashingAccount<'a> {
@@ -37,30 +57,91 @@ impl<'a> BaseCommitmentHashingAccount<'a> {
         request: BaseCommitmentHashRequest,
         fee_payer: U256,
     ) -> Result<(), ProgramError> {
+        self.setup_batch(&[request], fee_payer)
+    }
+
+    /// Sets up the sequential hashing of a batch of base commitments with the same token, fee version and `min_batching_rate`
+    pub fn setup_batch(
+        &mut self,
+        requests: &[BaseCommitmentHashRequest],
+        fee_payer: U256,
+    ) -> Result<(), ProgramError> {
+        guard!(
+            !requests.is_empty()
+                && requests.len() <= MAX_BASE_COMMITMENT_BATCH_SIZE
+                && requests.len() == self.batch_len(),
+            ElusivError::InvalidInstructionData
+        );
+        let first = &requests[0];
+        guard!(
+            requests
+                .iter()
+                .all(|request| request.token_id == first.token_id
+                    && request.fee_version == first.fee_version
+                    && request.min_batching_rate == first.min_batching_rate),
+            ElusivError::InvalidInstructionData
+        );
+
         self.set_is_active(&true);
         self.set_instruction(&0);
         self.set_round(&0);
         self.set_fee_payer(&fee_payer);
-        self.set_fee_version(&request.fee_version);
+        self.set_fee_version(&first.fee_version);
+
+        self.set_min_batching_rate(&first.min_batching_rate);
+        self.set_token_id(&first.token_id);
+        self.set_finalized_mask(&0);
 
-        self.set_min_batching_rate(&request.min_batching_rate);
-        self.set_token_id(&request.token_id);
+        for (i, request) in requests.iter().enumerate() {
+            self.set_batch(i, &base_commitment_hashing_state(request))?;
+        }
 
         // Reset hashing state
-        self.set_state(&BinarySpongeHashingState::new(
-            u256_to_fr_skip_mr(&request.base_commitment.reduce()),
-            Fr::from_repr(BigInteger256([
-                request.amount,
-                request.token_id as u64 + ((request.commitment_index as u64) << 16),
-                0,
-                0,
-            ]))
-            .unwrap(),
-            false,
-        ));
+        self.set_state(&base_commitment_hashing_state(first));
 
         Ok(())
     }
+
+    /// Whether all base commitments of the batch have been hashed
+    pub fn is_batch_hashed(&self) -> bool {
+        self.get_instruction() as usize
+            == base_commitment_hash_computation_instructions(self.batch_len()).len()
+    }
+
+    /// Returns the commitment of the hashed `index`-th base commitment of the batch
+    pub fn get_commitment(&self, index: usize) -> Option<U256> {
+        self.get_batch(index)
+            .map(|state| fr_to_u256_le(&state.result()))
+    }
+}
+
+fn base_commitment_hashing_state(request: &BaseCommitmentHashRequest) -> BinarySpongeHashingState {
+    BinarySpongeHashingState::new(
+        u256_to_fr_skip_mr(&request.base_commitment.reduce()),
+        Fr::from_repr(BigInteger256([
+            request.amount,
+            request.token_id as u64 + ((request.commitment_index as u64) << 16),
+            0,
+            0,
+        ]))
+        .unwrap(),
+        false,
+    )
+}
+
+/// The max. number of [`CommitmentHashingAccount`]s per shard (hashing consecutive batches concurrently)
+pub const MAX_COMMITMENT_HASHING_ACCOUNTS_COUNT: u32 = 4;
+
+/// The [`PDAOffset`] of the `hashing_account_index`-th [`CommitmentHashingAccount`] of a shard
+///
+/// # Note
+///
+/// The first hashing account of a shard uses the shard's offset.
+pub fn hashing_account_pda_offset(shard: u32, hashing_account_index: u32) -> PDAOffset {
+    match hashing_account_index {
+        0 => shard_pda_offset(shard),
+        _ => Some(shard + hashing_account_index * MAX_COMMITMENT_SHARDS_COUNT),
+    }
 }
 
 /// Account used for computing the hashes of a MT
@@ -89,6 +170,9 @@ pub struct CommitmentHashingAccount {
 
     // commitments and hashes in the HT
     pub hash_tree: [U256; MAX_HT_SIZE],
+
+    /// Bitmask of the MT-levels whose siblings are still being computed for the previous batch
+    pub pending_siblings: u32,
 }
 
 impl<'a> CommitmentHashingAccount<'a> {
@@ -100,6 +184,7 @@ impl<'a> CommitmentHashingAccount<'a> {
         self.set_round(&0);
         self.set_ordering(&ordering);
         self.set_finalization_ix(&0);
+        self.set_pending_siblings(&0);
 
         for (i, sibling) in siblings.iter().enumerate() {
             self.set_siblings(i, sibling);
@@ -108,6 +193,87 @@ impl<'a> CommitmentHashingAccount<'a> {
         Ok(())
     }
 
+    /// Called before reset, sets the siblings of the batch following the (not yet finalized) batch of `previous`
+    ///
+    /// # Notes
+    ///
+    /// - Siblings left of the new batch that contain commitments of `previous` are taken from the hashes computed by `previous`.
+    /// - Siblings that have not yet been computed by `previous` are marked as pending and resolved when `previous` is finalized (see [`Self::resolve_pending_siblings`]).
+    /// - The HT and all hashes below the first pending sibling can be computed before `previous` is finalized.
+    pub fn setup_pipelined(&mut self, previous: &CommitmentHashingAccount) -> ProgramResult {
+        guard!(
+            previous.get_is_active(),
+            ElusivError::ComputationIsNotYetStarted
+        );
+
+        let previous_batching_rate = previous.get_batching_rate();
+        let ordering = previous.get_ordering()
+            + usize_as_u32_safe(commitments_per_batch(previous_batching_rate));
+        guard!(
+            (ordering as usize) < MT_COMMITMENT_COUNT,
+            ElusivError::NoRoomForCommitment
+        );
+
+        let mut siblings = [[0; 32]; MT_HEIGHT];
+        let mut pending_siblings = 0;
+        for (i, sibling) in siblings.iter_mut().enumerate() {
+            let is_pending = if (ordering >> i) & 1 == 0 {
+                // Right siblings are empty
+                *sibling = EMPTY_TREE[i];
+                false
+            } else if ordering & (two_pow!(i as u32) as u32 - 1) != 0 {
+                // Both batches share the node on this level
+                *sibling = previous.get_siblings(i);
+                (previous.get_pending_siblings() >> i) & 1 == 1
+            } else if previous.is_batch_node_computed(i) {
+                // The sibling is the node containing the batch of `previous`
+                *sibling = previous.batch_node(i)?;
+                false
+            } else {
+                true
+            };
+
+            if is_pending {
+                pending_siblings |= 1 << i;
+            }
+        }
+
+        self.setup(ordering, &siblings)?;
+        self.set_pending_siblings(&pending_siblings);
+
+        Ok(())
+    }
+
+    /// Sets the pending siblings, once the previous batch has been finalized
+    pub fn resolve_pending_siblings(&mut self, storage_account: &StorageAccount) -> ProgramResult {
+        let ordering = self.get_ordering();
+        guard!(
+            ordering == storage_account.get_next_commitment_ptr(),
+            ElusivError::ComputationIsNotYetFinished
+        );
+
+        let opening = storage_account.get_mt_opening(ordering as usize)?;
+        let pending_siblings = self.get_pending_siblings();
+        for (i, sibling) in opening.iter().enumerate() {
+            if (pending_siblings >> i) & 1 == 1 {
+                self.set_siblings(i, sibling);
+            }
+        }
+        self.set_pending_siblings(&0);
+
+        Ok(())
+    }
+
+    /// Returns true if the hash with `hash_index` requires a sibling that is still pending
+    pub fn is_sibling_pending(&self, hash_index: usize) -> bool {
+        let batching_rate = self.get_batching_rate() as usize;
+        let sub_tree_size = two_pow!(batching_rate as u32) - 1;
+
+        hash_index >= sub_tree_size
+            && (self.get_pending_siblings() >> (batching_rate + hash_index - sub_tree_size)) & 1
+                == 1
+    }
+
     /// Called after setup, sets the commitments and batching rate
     pub fn reset(
         &mut self,
@@ -132,6 +298,36 @@ impl<'a> CommitmentHashingAccount<'a> {
         Ok(())
     }
 
+    /// Returns true if all hashes of the batch have been computed
+    pub fn is_hashing_finished(&self) -> bool {
+        self.get_instruction() as usize
+            >= commitment_hash_computation_instructions(self.get_batching_rate()).len()
+    }
+
+    /// Returns true if the node on the MT-level `level` (counted from the leaves) containing the batch has been computed
+    fn is_batch_node_computed(&self, level: usize) -> bool {
+        let batching_rate = self.get_batching_rate() as usize;
+        if level < batching_rate {
+            return false;
+        }
+
+        // The HT-root is the last HT hash, all above hashes are computed consecutively
+        let hashes_count = two_pow!(batching_rate as u32) - 1 + level - batching_rate;
+        self.get_round() as usize >= hashes_count * ROUNDS_PER_HASH as usize
+    }
+
+    /// Returns the computed node on the MT-level `level` (counted from the leaves) containing the batch
+    fn batch_node(&self, level: usize) -> Result<U256, ProgramError> {
+        let batching_rate = self.get_batching_rate() as usize;
+        guard!(level >= batching_rate, ElusivError::InvalidBatchingRate);
+
+        if level == batching_rate {
+            Ok(self.get_hash_tree(two_pow!(batching_rate as u32 + 1) - 2))
+        } else {
+            Ok(self.get_above_hashes(level - batching_rate - 1))
+        }
+    }
+
     /// Returns the initial state for the next hash
     /// - hashing order:
     ///     1. commitment sibling hashes on MT-layer `n`: h(c0, c1), h(c2, c3), ..
@@ -250,12 +446,10 @@ impl<'a> CommitmentHashingAccount<'a> {
                 &(ordering + usize_as_u32_safe(commitments_per_batch(batching_rate))),
             );
 
-            // This inserts the new root into the `active_mt_root_history`
-            storage_account.set_active_mt_root_history(
-                ordering as usize % HISTORY_ARRAY_SIZE,
-                &storage_account.get_root().unwrap(),
-            );
-            storage_account.set_mt_roots_count(&(storage_account.get_mt_roots_count() + 1));
+            // This inserts the new root into the `root_history`
+            storage_account
+                .push_root(&storage_account.get_root().unwrap())
+                .unwrap();
         }
     }
 }
@@ -346,10 +540,12 @@ pub fn base_commitment_request(
 mod tests {
     use super::*;
     use crate::commitment::{
-        hash_count_per_batch, MAX_COMMITMENT_BATCHING_RATE, MAX_HT_COMMITMENTS,
+        compute_commitment_hash_partial, hash_count_per_batch, MAX_COMMITMENT_BATCHING_RATE,
+        MAX_HT_COMMITMENTS,
     };
     use crate::fields::{u64_to_scalar, u64_to_scalar_skip_mr, u64_to_u256_skip_mr};
     use crate::macros::{parent_account, zero_program_account};
+    use crate::state::program_account::{ProgramAccount, ReallocAccount};
     use crate::types::RawU256;
     use ark_bn254::Fr;
     use ark_ff::Zero;
@@ -569,6 +765,86 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_setup_pipelined() {
+        parent_account!(mut storage_account, StorageAccount);
+        zero_program_account!(mut account_a, CommitmentHashingAccount);
+        zero_program_account!(mut account_b, CommitmentHashingAccount);
+
+        fn hash_batch(account: &mut CommitmentHashingAccount) -> ProgramResult {
+            while !account.is_hashing_finished() {
+                compute_commitment_hash_partial(account)?;
+            }
+            Ok(())
+        }
+
+        fn batch(batching_rate: u32, offset: usize) -> Vec<U256> {
+            (0..commitments_per_batch(batching_rate))
+                .map(|i| u64_to_u256((offset + i) as u64))
+                .collect()
+        }
+
+        // Previous account is inactive
+        assert_matches!(account_b.setup_pipelined(&account_a), Err(_));
+
+        let batching_rates = [0, 0, 1, 2, 3, 2, 1, 0];
+        let siblings = storage_account.get_mt_opening(0).unwrap();
+        account_a.setup(0, &siblings).unwrap();
+        account_a.reset(batching_rates[0], 0, &batch(0, 0)).unwrap();
+
+        let mut offset = 1;
+        for (i, &batching_rate) in batching_rates.iter().enumerate().skip(1) {
+            let (previous, next) = if i % 2 == 1 {
+                (&mut account_a, &mut account_b)
+            } else {
+                (&mut account_b, &mut account_a)
+            };
+
+            // The next batch is setup and hashed before the previous batch is hashed and finalized
+            next.setup_pipelined(previous).unwrap();
+            next.reset(batching_rate, 0, &batch(batching_rate, offset))
+                .unwrap();
+            offset += commitments_per_batch(batching_rate);
+
+            let pending_siblings = next.get_pending_siblings();
+            if pending_siblings != 0 {
+                assert_matches!(hash_batch(next), Err(_));
+                assert!(!next.is_hashing_finished());
+            }
+
+            hash_batch(previous).unwrap();
+            for finalization_ix in 0..=previous.get_batching_rate() {
+                previous.update_mt(&mut storage_account, finalization_ix);
+            }
+            previous.set_is_active(&false);
+            previous.set_setup(&false);
+
+            // Pending siblings can only be resolved after the previous batch has been finalized
+            next.resolve_pending_siblings(&storage_account).unwrap();
+
+            // The siblings match the MT-opening after finalization
+            let ordering = storage_account.get_next_commitment_ptr();
+            assert_eq!(next.get_ordering(), ordering);
+            let opening = storage_account.get_mt_opening(ordering as usize).unwrap();
+            for (level, sibling) in opening.iter().enumerate() {
+                assert_eq!(next.get_siblings(level), *sibling);
+            }
+
+            hash_batch(next).unwrap();
+        }
+
+        // All batches are inserted in order
+        let (last, _) = if batching_rates.len() % 2 == 1 {
+            (&mut account_a, &mut account_b)
+        } else {
+            (&mut account_b, &mut account_a)
+        };
+        for finalization_ix in 0..=last.get_batching_rate() {
+            last.update_mt(&mut storage_account, finalization_ix);
+        }
+        assert_eq!(storage_account.get_next_commitment_ptr() as usize, offset);
+    }
+
     #[test]
     fn test_base_commitment_account_setup() {
         zero_program_account!(mut account, BaseCommitmentHashingAccount);
@@ -601,6 +877,56 @@ mod tests {
         assert!(account.get_is_active());
     }
 
+    #[test]
+    fn test_base_commitment_account_setup_batch() {
+        let mut data = vec![0; BaseCommitmentHashingAccount::size_with_len(3)];
+        let mut account = BaseCommitmentHashingAccount::new(&mut data).unwrap();
+
+        let request = |base_commitment: u8, token_id: u16| BaseCommitmentHashRequest {
+            base_commitment: RawU256::new([base_commitment; 32]),
+            commitment_index: 0,
+            amount: 1,
+            token_id,
+            commitment: RawU256::new([0; 32]),
+            fee_version: 0,
+            min_batching_rate: 0,
+        };
+
+        // Empty batch
+        assert_matches!(account.setup_batch(&[], [0; 32]), Err(_));
+
+        // Batch too large
+        let requests = vec![request(1, 0); MAX_BASE_COMMITMENT_BATCH_SIZE + 1];
+        assert_matches!(account.setup_batch(&requests, [0; 32]), Err(_));
+
+        // Batch does not match the account size
+        assert_matches!(
+            account.setup_batch(&[request(1, 0), request(2, 0)], [0; 32]),
+            Err(_)
+        );
+
+        // Mismatched token
+        assert_matches!(
+            account.setup_batch(&[request(1, 0), request(2, 1), request(3, 0)], [0; 32]),
+            Err(_)
+        );
+        assert!(!account.get_is_active());
+
+        let requests = [request(1, 0), request(2, 0), request(3, 0)];
+        account.setup_batch(&requests, [0; 32]).unwrap();
+
+        assert_eq!(account.batch_len(), 3);
+        assert_eq!(account.get_finalized_mask(), 0);
+        assert_eq!(account.get_state(), account.get_batch(0).unwrap());
+        for (i, request) in requests.iter().enumerate() {
+            assert_eq!(
+                account.get_batch(i).unwrap().0[1],
+                u256_to_fr_skip_mr(&request.base_commitment.reduce())
+            );
+        }
+        assert!(!account.is_batch_hashed());
+    }
+
     #[test]
     #[allow(clippy::needless_range_loop)]
     fn test_commitment_account_reset() {
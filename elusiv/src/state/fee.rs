@@ -1,17 +1,19 @@
 use super::program_account::PDAAccountData;
 use crate::bytes::{div_ceiling_u64, u64_as_usize_safe};
 use crate::commitment::{
-    commitment_hash_computation_instructions, commitments_per_batch, BaseCommitmentHashComputation,
-    MAX_COMMITMENT_BATCHING_RATE,
+    base_commitment_hash_computation_instructions, commitment_hash_computation_instructions,
+    commitments_per_batch, MAX_COMMITMENT_BATCHING_RATE,
 };
 use crate::macros::elusiv_account;
-use crate::proof::verifier::{CombinedMillerLoop, FinalExponentiation};
+use crate::proof::alt_bn128::ALT_BN128_VERIFICATION_TX_COUNT;
+use crate::proof::plonk::verifier::PlonkMillerLoop;
+use crate::proof::verifier::{CombinedMillerLoop, FinalExponentiation, G2SubgroupCheck};
 use crate::token::{Lamports, Token, TokenError, TokenPrice};
 use borsh::{BorshDeserialize, BorshSerialize};
 use elusiv_computation::PartialComputation;
 use elusiv_derive::BorshSerDeSized;
 
-#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Clone)]
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Clone, Copy)]
 #[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
 pub struct BasisPointFee(pub u64);
 
@@ -21,7 +23,7 @@ impl BasisPointFee {
     }
 }
 
-#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Clone)]
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Clone, Copy)]
 #[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
 pub struct ProgramFee {
     /// Consists of `lamports_per_signature` and possible additional compute units costs
@@ -97,7 +99,20 @@ impl ProgramFee {
     }
 
     pub fn proof_base_tx_count() -> u64 {
-        (CombinedMillerLoop::TX_COUNT + FinalExponentiation::TX_COUNT + 2) as u64
+        if cfg!(feature = "alt-bn128") {
+            // The input preparation tx is already included in the dynamic input preparation tx count
+            return (ALT_BN128_VERIFICATION_TX_COUNT - 1 + 2) as u64;
+        }
+
+        (G2SubgroupCheck::TX_COUNT
+            + CombinedMillerLoop::TX_COUNT
+            + FinalExponentiation::TX_COUNT
+            + 2) as u64
+    }
+
+    /// The PLONK equivalent of [`Self::proof_base_tx_count`] (the PLONK pairing check always uses the partial computations)
+    pub fn plonk_proof_base_tx_count() -> u64 {
+        (PlonkMillerLoop::TX_COUNT + FinalExponentiation::TX_COUNT + 2) as u64
     }
 }
 
@@ -117,12 +132,20 @@ impl ProgramFee {
     }
 
     pub fn base_commitment_hash_computation_fee(&self) -> Lamports {
-        // extra `lamports_per_tx` for the second signature, paid for by the fee-payer
+        self.batched_base_commitment_hash_computation_fee(1)
+    }
+
+    /// The [`Self::base_commitment_hash_computation_fee`] of a single base commitment stored in a batch of `batch_len` base commitments
+    pub fn batched_base_commitment_hash_computation_fee(&self, batch_len: usize) -> Lamports {
+        let batch_len = batch_len.max(1);
+        let tx_count = base_commitment_hash_computation_instructions(batch_len).len() as u64;
 
-        Lamports(
-            BaseCommitmentHashComputation::TX_COUNT as u64 * self.hash_tx_compensation().0
-                + self.lamports_per_tx.0,
-        )
+        // extra `lamports_per_tx` for the second signature, paid for by the fee-payer
+        // (the hash txs and the signature are shared by all base commitments of the batch)
+        Lamports(div_ceiling_u64(
+            tx_count * self.hash_tx_compensation().0 + self.lamports_per_tx.0,
+            batch_len as u64,
+        ))
     }
 
     pub fn commitment_hash_computation_fee(&self, min_batching_rate: u32) -> Lamports {
@@ -134,6 +157,17 @@ impl ProgramFee {
         ))
     }
 
+    /// Quotes the commitment hash fee for every batching rate a user can opt into (starting at `min_batching_rate`)
+    ///
+    /// # Notes
+    ///
+    /// A higher rate results in a lower fee, but the commitment might wait longer in the queue until a batch is complete.
+    pub fn commitment_hash_fee_quote(&self, min_batching_rate: u32) -> Vec<(u32, Lamports)> {
+        (min_batching_rate..=MAX_COMMITMENT_BATCHING_RATE as u32)
+            .map(|rate| (rate, self.commitment_hash_computation_fee(rate)))
+            .collect()
+    }
+
     pub fn proof_verification_computation_fee(
         &self,
         input_preparation_tx_count: usize,
@@ -145,6 +179,17 @@ impl ProgramFee {
         Lamports(amount)
     }
 
+    /// The PLONK equivalent of [`Self::proof_verification_computation_fee`]
+    pub fn plonk_proof_verification_computation_fee(
+        &self,
+        preparation_tx_count: usize,
+    ) -> Lamports {
+        let amount = (preparation_tx_count as u64 + Self::plonk_proof_base_tx_count())
+            * self.lamports_per_tx.0
+            + self.warden_proof_reward.0;
+        Lamports(amount)
+    }
+
     pub fn proof_verification_fee(
         &self,
         input_preparation_tx_count: usize,
@@ -165,3 +210,88 @@ impl ProgramFee {
         ((proof_verification_fee + commitment_hash_fee)? + network_fee)? - subvention
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::commitment::MAX_BASE_COMMITMENT_BATCH_SIZE;
+
+    #[test]
+    fn test_commitment_hash_computation_fee() {
+        let fee = ProgramFee::new(5000, 0, 0, 0, 0, 100, 0).unwrap();
+
+        let tx_count = if cfg!(feature = "poseidon-syscall") {
+            1
+        } else {
+            2
+        };
+        assert_eq!(
+            fee.base_commitment_hash_computation_fee().0,
+            tx_count * 5100 + 5000
+        );
+
+        let tx_count = if cfg!(feature = "poseidon-syscall") {
+            1
+        } else {
+            24
+        };
+        assert_eq!(fee.commitment_hash_computation_fee(0).0, tx_count * 5100);
+        assert_eq!(
+            fee.commitment_hash_computation_fee(2).0,
+            div_ceiling_u64(
+                commitment_hash_computation_instructions(2).len() as u64 * 5100,
+                4
+            )
+        );
+    }
+
+    #[test]
+    fn test_batched_base_commitment_hash_computation_fee() {
+        let fee = ProgramFee::new(5000, 0, 0, 0, 0, 100, 0).unwrap();
+        let hash_fee = |batch_len: usize| {
+            base_commitment_hash_computation_instructions(batch_len).len() as u64 * 5100
+        };
+
+        assert_eq!(
+            fee.batched_base_commitment_hash_computation_fee(1),
+            fee.base_commitment_hash_computation_fee()
+        );
+        assert_eq!(
+            fee.batched_base_commitment_hash_computation_fee(0),
+            fee.base_commitment_hash_computation_fee()
+        );
+        assert_eq!(
+            fee.batched_base_commitment_hash_computation_fee(4).0,
+            div_ceiling_u64(hash_fee(4) + 5000, 4)
+        );
+        assert_eq!(
+            fee.batched_base_commitment_hash_computation_fee(3).0,
+            div_ceiling_u64(hash_fee(3) + 5000, 3)
+        );
+
+        // A batch never costs more per base commitment than a single hash
+        for batch_len in 2..=MAX_BASE_COMMITMENT_BATCH_SIZE {
+            assert!(
+                fee.batched_base_commitment_hash_computation_fee(batch_len)
+                    .0
+                    <= fee.base_commitment_hash_computation_fee().0
+            );
+        }
+    }
+
+    #[test]
+    fn test_commitment_hash_fee_quote() {
+        let fee = ProgramFee::new(5000, 0, 0, 0, 0, 100, 0).unwrap();
+
+        let quote = fee.commitment_hash_fee_quote(1);
+        assert_eq!(quote.len(), MAX_COMMITMENT_BATCHING_RATE);
+        for (i, &(rate, fee_amount)) in quote.iter().enumerate() {
+            assert_eq!(rate, i as u32 + 1);
+            assert_eq!(fee_amount, fee.commitment_hash_computation_fee(rate));
+        }
+
+        assert!(fee
+            .commitment_hash_fee_quote(MAX_COMMITMENT_BATCHING_RATE as u32 + 1)
+            .is_empty());
+    }
+}
@@ -1,5 +1,30 @@
-use super::{fee::ProgramFee, program_account::PDAAccountData};
-use crate::macros::elusiv_account;
+use super::{
+    fee::ProgramFee,
+    program_account::{PDAAccount, PDAAccountData, ProgramAccount, ProgramVersionAccount},
+};
+use crate::error::ElusivError;
+use crate::macros::{elusiv_account, guard};
+use crate::token::{elusiv_token, TokenID, SPL_TOKEN_COUNT};
+use borsh::{BorshDeserialize, BorshSerialize};
+use elusiv_derive::BorshSerDeSized;
+use elusiv_types::{BorshSerDeSized, BorshSerDeSizedEnum, ElusivOption};
+use elusiv_utils::MATH_ERR;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// The max. number of signers registered for approving [`GovernanceProposal`]s
+pub const MAX_GOVERNANCE_SIGNERS: usize = 5;
+
+/// The max. number of simultaneously pending [`GovernanceProposal`]s
+pub const MAX_PENDING_GOVERNANCE_PROPOSALS: usize = 8;
+
+/// The delay (in seconds) between approving and executing a [`GovernanceProposal`]
+pub const GOVERNANCE_TIMELOCK: u64 = 2 * 24 * 60 * 60;
+
+/// The length (in seconds) of the windows in which the [`TokenVolumeAccount`] tracks volumes
+pub const TOKEN_VOLUME_WINDOW: u64 = 24 * 60 * 60;
 
 #[elusiv_account(eager_type: true)]
 pub struct GovernorAccount {
@@ -13,10 +38,133 @@ pub struct GovernorAccount {
     /// The `ProgramFee` for the `FeeAccount` with the offset `fee_version`
     pub program_fee: ProgramFee,
 
-    /// The number of commitments in a MT-root hashing batch
+    /// The lowest `min_batching_rate` users can opt into (the actual batching rate is selected adaptively)
     pub commitment_batching_rate: u32,
 
-    program_version: u32,
+    /// The [`crate::PROGRAM_VERSION`] the program accounts are migrated to
+    pub program_version: u32,
+
+    /// The governance authority (required for the program setup and for proposing changes)
+    pub authority: ElusivOption<Pubkey>,
+
+    /// Signers approving proposals (an M-of-N scheme is active for `threshold > 0`)
+    pub signers: [ElusivOption<Pubkey>; MAX_GOVERNANCE_SIGNERS],
+    pub threshold: u8,
+
+    /// The number of concurrently active MTs (see [`crate::state::storage::commitment_shard`])
+    pub commitment_shards_count: u32,
+}
+
+/// All instructions (except for the setup and the migration) require the [`GovernorAccount`]
+///
+/// # Notes
+///
+/// The [`GovernorAccount::program_version`] is set once the [`GovernorAccount`] is migrated, so it is migrated last.
+impl<'a> ProgramVersionAccount for GovernorAccount<'a> {
+    fn verify_program_version(account: &AccountInfo) -> ProgramResult {
+        Self::verify_account(account, None)?;
+        Self::verify_account_version(account)?;
+
+        let data = &mut account.data.borrow_mut()[..];
+        let governor = GovernorAccount::new(data)?;
+        guard!(
+            governor.get_program_version() == u32::from(crate::PROGRAM_VERSION),
+            ElusivError::InvalidAccountVersion
+        );
+
+        Ok(())
+    }
+}
+
+/// A timelocked change of the program parameters
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Clone, Copy)]
+#[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
+pub enum GovernanceProposal {
+    /// Executed by [`crate::instruction::ElusivInstruction::InitNewFeeVersion`]
+    NewFeeVersion {
+        fee_version: u32,
+        program_fee: ProgramFee,
+    },
+
+    /// Executed by [`crate::instruction::ElusivInstruction::UpgradeGovernorState`]
+    GovernorState {
+        fee_version: u32,
+        batching_rate: u32,
+    },
+
+    /// Executed by [`crate::instruction::ElusivInstruction::ChangeGovernanceAuthority`]
+    Authority {
+        authority: ElusivOption<Pubkey>,
+        signers: [ElusivOption<Pubkey>; MAX_GOVERNANCE_SIGNERS],
+        threshold: u8,
+    },
+
+    /// Executed by [`crate::instruction::ElusivInstruction::SetTokenVolumeLimits`]
+    TokenVolumeLimits {
+        token_id: TokenID,
+        deposit_limit: ElusivOption<u64>,
+        withdrawal_limit: ElusivOption<u64>,
+    },
+
+    /// Executed by [`crate::instruction::ElusivInstruction::RelistToken`]
+    RelistToken { token_id: TokenID },
+
+    /// Executed by [`crate::instruction::ElusivInstruction::SetPoolOpeningBalance`]
+    PoolOpeningBalance {
+        token_id: TokenID,
+        opening_balance: u64,
+    },
+}
+
+#[derive(BorshSerDeSized, PartialEq, Clone, Copy)]
+#[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
+pub struct PendingGovernanceProposal {
+    pub proposal: GovernanceProposal,
+
+    /// Approvals of the registered [`GovernorAccount`] signers
+    pub approvals: [bool; MAX_GOVERNANCE_SIGNERS],
+
+    /// The timestamp after which the proposal can be executed (set once the proposal is approved)
+    pub unlock_timestamp: ElusivOption<u64>,
+}
+
+// The proposal is padded to `GovernanceProposal::SIZE`, so that all variants have the same serialization length
+impl BorshSerialize for PendingGovernanceProposal {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let proposal = self.proposal.try_to_vec()?;
+        writer.write_all(&proposal)?;
+        writer.write_all(&vec![0; GovernanceProposal::SIZE - proposal.len()])?;
+
+        self.approvals.serialize(writer)?;
+        self.unlock_timestamp.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for PendingGovernanceProposal {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        if buf.len() < GovernanceProposal::SIZE {
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidData));
+        }
+
+        let proposal = GovernanceProposal::deserialize_enum(&mut &buf[..GovernanceProposal::SIZE])?;
+        *buf = &buf[GovernanceProposal::SIZE..];
+
+        Ok(PendingGovernanceProposal {
+            proposal,
+            approvals: <[bool; MAX_GOVERNANCE_SIGNERS]>::deserialize(buf)?,
+            unlock_timestamp: ElusivOption::deserialize(buf)?,
+        })
+    }
+}
+
+/// Account storing all pending [`GovernanceProposal`]s
+#[elusiv_account(eager_type: true)]
+pub struct GovernanceProposalsAccount {
+    #[no_getter]
+    #[no_setter]
+    pda_data: PDAAccountData,
+
+    pub proposals: [ElusivOption<PendingGovernanceProposal>; MAX_PENDING_GOVERNANCE_PROPOSALS],
 }
 
 #[elusiv_account(eager_type: true)]
@@ -32,3 +180,467 @@ pub struct FeeCollectorAccount {
     #[no_setter]
     pda_data: PDAAccountData,
 }
+
+/// Deposit and withdrawal volumes of a single token
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
+pub struct TokenVolume {
+    /// The max. deposit volume per [`TOKEN_VOLUME_WINDOW`] (unlimited if `None`)
+    pub deposit_limit: ElusivOption<u64>,
+
+    /// The max. withdrawal volume per [`TOKEN_VOLUME_WINDOW`] (unlimited if `None`)
+    pub withdrawal_limit: ElusivOption<u64>,
+
+    /// The start of the current window (a multiple of [`TOKEN_VOLUME_WINDOW`])
+    pub window_start: u64,
+
+    pub deposit_volume: u64,
+    pub withdrawal_volume: u64,
+}
+
+/// The start of the [`TOKEN_VOLUME_WINDOW`] containing `timestamp`
+pub fn token_volume_window_start(timestamp: u64) -> u64 {
+    timestamp - timestamp % TOKEN_VOLUME_WINDOW
+}
+
+impl TokenVolume {
+    fn track(&mut self, amount: u64, is_deposit: bool, timestamp: u64) -> Result<(), ElusivError> {
+        let window_start = token_volume_window_start(timestamp);
+        if window_start != self.window_start {
+            self.window_start = window_start;
+            self.deposit_volume = 0;
+            self.withdrawal_volume = 0;
+        }
+
+        let (volume, limit) = if is_deposit {
+            (&mut self.deposit_volume, self.deposit_limit)
+        } else {
+            (&mut self.withdrawal_volume, self.withdrawal_limit)
+        };
+
+        let new_volume = volume
+            .checked_add(amount)
+            .ok_or(ElusivError::VolumeLimitExceeded)?;
+
+        if let Some(limit) = limit.option() {
+            guard!(new_volume <= limit, ElusivError::VolumeLimitExceeded);
+        }
+
+        *volume = new_volume;
+
+        Ok(())
+    }
+}
+
+/// Account tracking the deposit and withdrawal volumes of all tokens in fixed windows of [`TOKEN_VOLUME_WINDOW`]
+#[elusiv_account(eager_type: true)]
+pub struct TokenVolumeAccount {
+    #[no_getter]
+    #[no_setter]
+    pda_data: PDAAccountData,
+
+    pub volumes: [TokenVolume; SPL_TOKEN_COUNT + 1],
+
+    /// Runtime overrides of [`crate::token::ElusivToken::active`] (see [`crate::instruction::ElusivInstruction::DelistToken`])
+    pub delisted: [bool; SPL_TOKEN_COUNT + 1],
+}
+
+impl<'a> TokenVolumeAccount<'a> {
+    /// Whether new deposits of `token_id` are accepted
+    pub fn accepts_deposits(&self, token_id: TokenID) -> Result<bool, ProgramError> {
+        let token = elusiv_token(token_id)?;
+        Ok(token.active && !self.get_delisted(token_id as usize))
+    }
+
+    /// Adds a deposit of `amount` to the volume of `token_id`, failing if the deposit limit is exceeded
+    pub fn track_deposit(
+        &mut self,
+        token_id: TokenID,
+        amount: u64,
+        timestamp: u64,
+    ) -> ProgramResult {
+        self.track(token_id, amount, true, timestamp)
+    }
+
+    /// Adds a withdrawal of `amount` to the volume of `token_id`, failing if the withdrawal limit is exceeded
+    ///
+    /// # Notes
+    ///
+    /// Withdrawals are reserved once the verification fee has been transferred, before the proof verification (see [`Self::release_withdrawal`]).
+    pub fn track_withdrawal(
+        &mut self,
+        token_id: TokenID,
+        amount: u64,
+        timestamp: u64,
+    ) -> ProgramResult {
+        self.track(token_id, amount, false, timestamp)
+    }
+
+    /// Releases a withdrawal of `amount`, that has been reserved in the window starting at `window_start` (e.g. for an invalid proof)
+    ///
+    /// # Notes
+    ///
+    /// The volumes of past windows are already reset, so nothing is released for them.
+    pub fn release_withdrawal(
+        &mut self,
+        token_id: TokenID,
+        amount: u64,
+        window_start: u64,
+    ) -> ProgramResult {
+        let index = token_id as usize;
+        guard!(index <= SPL_TOKEN_COUNT, ElusivError::UnsupportedToken);
+
+        let mut volume = self.get_volumes(index);
+        if volume.window_start == window_start {
+            volume.withdrawal_volume = volume.withdrawal_volume.saturating_sub(amount);
+            self.set_volumes(index, &volume);
+        }
+
+        Ok(())
+    }
+
+    fn track(
+        &mut self,
+        token_id: TokenID,
+        amount: u64,
+        is_deposit: bool,
+        timestamp: u64,
+    ) -> ProgramResult {
+        let index = token_id as usize;
+        guard!(index <= SPL_TOKEN_COUNT, ElusivError::UnsupportedToken);
+
+        let mut volume = self.get_volumes(index);
+        volume.track(amount, is_deposit, timestamp)?;
+        self.set_volumes(index, &volume);
+
+        Ok(())
+    }
+}
+
+/// Cumulative flows of a single token into and out of the [`PoolAccount`]
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
+pub struct PoolBalance {
+    /// The liabilities of the pool before the ledger has been opened (set by governance)
+    pub opening_balance: u64,
+
+    /// Amounts deposited into notes
+    pub deposited: u64,
+
+    /// Amounts of notes transferred to recipients
+    pub withdrawn: u64,
+
+    /// Fees and subventions transferred into the pool (to compensate computations)
+    pub fees_reserved: u64,
+
+    /// Fees and subventions paid out of the pool
+    pub fees_paid: u64,
+}
+
+impl PoolBalance {
+    /// The net amount owed by the pool to notes and to pending computations
+    ///
+    /// # Notes
+    ///
+    /// A negative net means that more has flowed out of the pool than into it (which is never the case for a consistent ledger).
+    pub fn liabilities(&self) -> i128 {
+        i128::from(self.opening_balance)
+            + i128::from(self.deposited)
+            + i128::from(self.fees_reserved)
+            - i128::from(self.withdrawn)
+            - i128::from(self.fees_paid)
+    }
+}
+
+/// The [`PoolBalance`] counter a transfer into or out of the [`PoolAccount`] is tracked with
+#[derive(Clone, Copy)]
+pub enum PoolFlow {
+    Deposit,
+    Withdrawal,
+    FeeReservation,
+    FeePayment,
+}
+
+/// Account tracking all flows into and out of the [`PoolAccount`] per token (see [`crate::instruction::ElusivInstruction::AuditPool`])
+///
+/// # Notes
+///
+/// Lamports fees are tracked with [`crate::token::LAMPORTS_TOKEN_ID`], independent of the token of the note.
+#[elusiv_account(eager_type: true)]
+pub struct PoolLedgerAccount {
+    #[no_getter]
+    #[no_setter]
+    pda_data: PDAAccountData,
+
+    pub balances: [PoolBalance; SPL_TOKEN_COUNT + 1],
+}
+
+impl<'a> PoolLedgerAccount<'a> {
+    /// Adds `amount` to the `flow` counter of `token_id`
+    pub fn track(&mut self, token_id: TokenID, flow: PoolFlow, amount: u64) -> ProgramResult {
+        let index = token_id as usize;
+        guard!(index <= SPL_TOKEN_COUNT, ElusivError::UnsupportedToken);
+
+        let mut balance = self.get_balances(index);
+        let counter = match flow {
+            PoolFlow::Deposit => &mut balance.deposited,
+            PoolFlow::Withdrawal => &mut balance.withdrawn,
+            PoolFlow::FeeReservation => &mut balance.fees_reserved,
+            PoolFlow::FeePayment => &mut balance.fees_paid,
+        };
+        *counter = counter.checked_add(amount).ok_or(MATH_ERR)?;
+        self.set_balances(index, &balance);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::macros::{account_info, zero_program_account};
+    use crate::token::USDC_TOKEN_ID;
+    use assert_matches::assert_matches;
+    use elusiv_types::SizedAccount;
+
+    #[test]
+    fn test_token_volume_track() {
+        let mut volume = TokenVolume {
+            deposit_limit: ElusivOption::Some(100),
+            ..Default::default()
+        };
+
+        volume.track(60, true, 10).unwrap();
+        volume.track(40, true, 20).unwrap();
+        assert_matches!(volume.track(1, true, 30), Err(_));
+        assert_eq!(volume.deposit_volume, 100);
+
+        // Withdrawals are unlimited
+        volume.track(u64::MAX, false, 30).unwrap();
+        assert_matches!(volume.track(1, false, 30), Err(_));
+
+        // The volumes are reset in the next window
+        volume.track(100, true, TOKEN_VOLUME_WINDOW).unwrap();
+        assert_eq!(volume.window_start, TOKEN_VOLUME_WINDOW);
+        assert_eq!(volume.deposit_volume, 100);
+        assert_eq!(volume.withdrawal_volume, 0);
+
+        volume.track(1, false, TOKEN_VOLUME_WINDOW * 2 + 1).unwrap();
+        assert_eq!(volume.window_start, TOKEN_VOLUME_WINDOW * 2);
+        assert_eq!(volume.deposit_volume, 0);
+        assert_eq!(volume.withdrawal_volume, 1);
+    }
+
+    #[test]
+    fn test_token_volume_account() {
+        zero_program_account!(mut account, TokenVolumeAccount);
+
+        // No limits by default
+        account.track_deposit(0, u64::MAX, 0).unwrap();
+        account.track_withdrawal(0, u64::MAX, 0).unwrap();
+
+        account.set_volumes(
+            USDC_TOKEN_ID as usize,
+            &TokenVolume {
+                withdrawal_limit: ElusivOption::Some(10),
+                ..Default::default()
+            },
+        );
+        account.track_withdrawal(USDC_TOKEN_ID, 10, 0).unwrap();
+        assert_matches!(account.track_withdrawal(USDC_TOKEN_ID, 1, 0), Err(_));
+        account.track_deposit(USDC_TOKEN_ID, 1_000, 0).unwrap();
+
+        // The last token-id
+        let previous = account.get_volumes(SPL_TOKEN_COUNT - 1);
+        account.set_volumes(
+            SPL_TOKEN_COUNT,
+            &TokenVolume {
+                deposit_limit: ElusivOption::Some(5),
+                ..Default::default()
+            },
+        );
+        account.track_deposit(SPL_TOKEN_COUNT as u16, 5, 0).unwrap();
+        assert_matches!(account.track_deposit(SPL_TOKEN_COUNT as u16, 1, 0), Err(_));
+        assert_eq!(account.get_volumes(SPL_TOKEN_COUNT).deposit_volume, 5);
+        assert_eq!(account.get_volumes(SPL_TOKEN_COUNT - 1), previous);
+
+        // Invalid token-id
+        assert_matches!(
+            account.track_deposit(SPL_TOKEN_COUNT as u16 + 1, 1, 0),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_release_withdrawal() {
+        zero_program_account!(mut account, TokenVolumeAccount);
+        account.set_volumes(
+            USDC_TOKEN_ID as usize,
+            &TokenVolume {
+                withdrawal_limit: ElusivOption::Some(10),
+                ..Default::default()
+            },
+        );
+
+        account.track_withdrawal(USDC_TOKEN_ID, 10, 1).unwrap();
+        assert_matches!(account.track_withdrawal(USDC_TOKEN_ID, 1, 2), Err(_));
+
+        account
+            .release_withdrawal(USDC_TOKEN_ID, 4, token_volume_window_start(1))
+            .unwrap();
+        assert_eq!(
+            account
+                .get_volumes(USDC_TOKEN_ID as usize)
+                .withdrawal_volume,
+            6
+        );
+        account.track_withdrawal(USDC_TOKEN_ID, 4, 3).unwrap();
+
+        // Withdrawals reserved in a past window are not released
+        account
+            .track_withdrawal(USDC_TOKEN_ID, 3, TOKEN_VOLUME_WINDOW)
+            .unwrap();
+        account
+            .release_withdrawal(USDC_TOKEN_ID, 3, token_volume_window_start(1))
+            .unwrap();
+        assert_eq!(
+            account
+                .get_volumes(USDC_TOKEN_ID as usize)
+                .withdrawal_volume,
+            3
+        );
+
+        assert_matches!(
+            account.release_withdrawal(SPL_TOKEN_COUNT as u16 + 1, 1, 0),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_pool_ledger_account() {
+        zero_program_account!(mut account, PoolLedgerAccount);
+
+        account.track(0, PoolFlow::Deposit, 100).unwrap();
+        account.track(0, PoolFlow::FeeReservation, 20).unwrap();
+        account.track(0, PoolFlow::Withdrawal, 30).unwrap();
+        account.track(0, PoolFlow::FeePayment, 5).unwrap();
+        account
+            .track(USDC_TOKEN_ID, PoolFlow::Deposit, 1_000)
+            .unwrap();
+
+        assert_eq!(
+            account.get_balances(0),
+            PoolBalance {
+                opening_balance: 0,
+                deposited: 100,
+                withdrawn: 30,
+                fees_reserved: 20,
+                fees_paid: 5,
+            }
+        );
+        assert_eq!(account.get_balances(0).liabilities(), 85);
+        assert_eq!(
+            account.get_balances(USDC_TOKEN_ID as usize).liabilities(),
+            1_000
+        );
+
+        // Overflow
+        assert_matches!(account.track(0, PoolFlow::Deposit, u64::MAX), Err(_));
+        assert_eq!(account.get_balances(0).deposited, 100);
+
+        // Invalid token-id
+        assert_matches!(
+            account.track(SPL_TOKEN_COUNT as u16 + 1, PoolFlow::Deposit, 1),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_pool_balance_liabilities() {
+        // Fees paid out of notes can exceed the reserved fees
+        let balance = PoolBalance {
+            opening_balance: 0,
+            deposited: 100,
+            withdrawn: 50,
+            fees_reserved: 0,
+            fees_paid: 10,
+        };
+        assert_eq!(balance.liabilities(), 40);
+
+        // Outflows exceeding the inflows
+        let balance = PoolBalance {
+            fees_paid: 1,
+            ..Default::default()
+        };
+        assert_eq!(balance.liabilities(), -1);
+
+        // Opening balance
+        let balance = PoolBalance {
+            opening_balance: 10,
+            withdrawn: 10,
+            ..Default::default()
+        };
+        assert_eq!(balance.liabilities(), 0);
+
+        // No overflow
+        let balance = PoolBalance {
+            opening_balance: u64::MAX,
+            deposited: u64::MAX,
+            fees_reserved: u64::MAX,
+            ..Default::default()
+        };
+        assert_eq!(balance.liabilities(), i128::from(u64::MAX) * 3);
+    }
+
+    #[test]
+    fn test_accepts_deposits() {
+        zero_program_account!(mut account, TokenVolumeAccount);
+
+        for token_id in 0..=SPL_TOKEN_COUNT as u16 {
+            assert_eq!(
+                account.accepts_deposits(token_id).unwrap(),
+                elusiv_token(token_id).unwrap().active
+            );
+        }
+
+        account.set_delisted(USDC_TOKEN_ID as usize, &true);
+        assert!(!account.accepts_deposits(USDC_TOKEN_ID).unwrap());
+        assert_eq!(
+            account.get_volumes(USDC_TOKEN_ID as usize),
+            TokenVolume::default()
+        );
+
+        assert_matches!(account.accepts_deposits(SPL_TOKEN_COUNT as u16 + 1), Err(_));
+    }
+
+    #[test]
+    fn test_verify_program_version() {
+        let (pk, bump) = GovernorAccount::find(None);
+        let mut data = vec![0; GovernorAccount::SIZE];
+        data[0] = bump;
+        data[1] = crate::PROGRAM_VERSION;
+        account_info!(account, pk, data);
+
+        // Accounts are not yet migrated
+        assert_matches!(GovernorAccount::verify_program_version(&account), Err(_));
+
+        {
+            let data = &mut account.data.borrow_mut()[..];
+            let mut governor = GovernorAccount::new(data).unwrap();
+            governor.set_program_version(&u32::from(crate::PROGRAM_VERSION));
+        }
+        assert_matches!(GovernorAccount::verify_program_version(&account), Ok(()));
+
+        // Governor is not yet migrated
+        account.data.borrow_mut()[1] = crate::PROGRAM_VERSION - 1;
+        assert_matches!(GovernorAccount::verify_program_version(&account), Err(_));
+
+        // Invalid PDA
+        account_info!(
+            account,
+            Pubkey::new_unique(),
+            vec![0; GovernorAccount::SIZE]
+        );
+        assert_matches!(GovernorAccount::verify_program_version(&account), Err(_));
+    }
+}
@@ -7,6 +7,8 @@ mod tests {
     use assert_matches::assert_matches;
     use borsh::BorshDeserialize;
     use elusiv_types::{split_child_account_data, BorshSerDeSized, ElusivOption};
+    use elusiv_utils::realloc_size;
+    use solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
     use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
 
     struct TestPDAAccount;
@@ -32,6 +34,20 @@ mod tests {
         //assert_eq!(TestPDAAccount::find(None).0, Pubkey::find_program_address(&[TestPDAAccount::SEED], &crate::PROGRAM_ID).0);
     }
 
+    #[test]
+    fn test_verify_account_version() {
+        let pk = Pubkey::new_unique();
+        account_info!(account, pk, vec![0, 0]);
+        assert_matches!(TestPDAAccount::verify_account_version(&account), Ok(()));
+
+        account_info!(account, pk, vec![0, 1]);
+        assert_eq!(TestPDAAccount::get_version(&account), 1);
+        assert_matches!(
+            TestPDAAccount::verify_account_version(&account),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
     struct TestChildAccount;
 
     impl ChildAccount for TestChildAccount {
@@ -111,6 +127,10 @@ mod tests {
             parent.accounts.copy_from_slice(&child_accounts)
         }
 
+        fn set_child_account(&mut self, index: usize, child_account: &'b AccountInfo<'t>) {
+            self.accounts[index] = Some(child_account)
+        }
+
         fn get_child_pubkey(&self, index: usize) -> Option<Pubkey> {
             self.pubkeys[index].option()
         }
@@ -260,4 +280,80 @@ mod tests {
 
         assert_matches!(unverified_account_info.get_safe(), Ok(_));
     }
+
+    #[crate::macros::elusiv_account(eager_type: true)]
+    struct TestReallocAccount {
+        #[no_getter]
+        #[no_setter]
+        pda_data: PDAAccountData,
+
+        pub value: u64,
+
+        #[growable]
+        pub elements: [ElusivOption<u32>; 2],
+    }
+
+    #[test]
+    fn test_realloc_account() {
+        assert_eq!(TestReallocAccount::ELEMENT_SIZE, 5);
+        assert_eq!(TestReallocAccount::INITIAL_LEN, 2);
+        assert_eq!(TestReallocAccount::SIZE, 2 + 8 + 2 * 5);
+        assert_eq!(TestReallocAccount::size_with_len(0), 10);
+        assert_eq!(TestReallocAccount::size_with_len(5), 35);
+
+        assert_eq!(TestReallocAccount::len_with_size(20), Some(2));
+        assert_eq!(TestReallocAccount::len_with_size(35), Some(5));
+        assert_eq!(TestReallocAccount::len_with_size(19), None);
+        assert_eq!(TestReallocAccount::len_with_size(21), None);
+        assert_eq!(TestReallocAccount::len_with_size(10), None);
+
+        // Intermediate sizes only contain whole elements
+        let size = TestReallocAccount::size_with_len(2);
+        let step = realloc_size(size, TestReallocAccount::size_with_len(100_000), 5).unwrap();
+        assert_eq!(step, size + MAX_PERMITTED_DATA_INCREASE / 5 * 5);
+        assert!(TestReallocAccount::len_with_size(step).is_some());
+        assert_eq!(
+            realloc_size(size, TestReallocAccount::size_with_len(5), 5),
+            Ok(TestReallocAccount::size_with_len(5))
+        );
+        assert_eq!(realloc_size(size, size, 5), Ok(size));
+        assert_matches!(realloc_size(size, size + 7, 5), Err(_));
+        assert_matches!(realloc_size(size, size - 5, 5), Err(_));
+
+        let mut data = vec![0; 21];
+        assert!(TestReallocAccount::new(&mut data).is_err());
+
+        let mut data = vec![0; TestReallocAccount::size_with_len(3)];
+        let mut account = TestReallocAccount::new(&mut data).unwrap();
+        assert_eq!(account.elements_len(), 3);
+
+        // Zero-initialized elements
+        for i in 0..3 {
+            assert_eq!(account.get_elements(i), Some(ElusivOption::None));
+        }
+
+        // Bounds-checked against the live length
+        assert_eq!(account.get_elements(3), None);
+        assert_matches!(account.set_elements(3, &Some(1).into()), Err(_));
+
+        account.set_elements(2, &Some(123).into()).unwrap();
+        account.set_value(&u64::MAX);
+        assert_eq!(account.get_elements(2), Some(ElusivOption::Some(123)));
+        assert_eq!(account.get_elements(1), Some(ElusivOption::None));
+        assert_eq!(account.get_value(), u64::MAX);
+
+        #[cfg(feature = "elusiv-client")]
+        {
+            let eager = TestReallocAccount::new_eager(data).unwrap();
+            assert_eq!(eager.value, u64::MAX);
+            assert_eq!(
+                eager.elements,
+                vec![
+                    ElusivOption::None,
+                    ElusivOption::None,
+                    ElusivOption::Some(123)
+                ]
+            );
+        }
+    }
 }
@@ -3,11 +3,12 @@ use crate::bytes::{
 };
 use crate::fields::{G2HomProjective, Wrap, G1A, G2A};
 use crate::processor::{ProofRequest, MAX_MT_COUNT};
+use crate::proof::plonk::verifier::PLONK_MSM_TERMS_COUNT;
 use crate::proof::verifier::VerificationStep;
 use crate::state::program_account::PDAAccountData;
 use crate::token::Lamports;
-use crate::types::{Lazy, LazyField, RawU256, U256};
-use ark_bn254::{Fq, Fq12, Fq2, Fq6};
+use crate::types::{Lazy, LazyField, PlonkProof, ProgramCall, RawU256, U256};
+use ark_bn254::{Fq, Fq12, Fq2, Fq6, G1Projective};
 use borsh::{BorshDeserialize, BorshSerialize};
 use elusiv_computation::RAM;
 use elusiv_derive::{BorshSerDeSized, EnumVariantIndex};
@@ -20,9 +21,10 @@ pub type RAMFq2<'a> = LazyRAM<'a, Fq2, 10>;
 pub type RAMFq6<'a> = LazyRAM<'a, Fq6, 3>;
 pub type RAMFq12<'a> = LazyRAM<'a, Fq12, 7>;
 pub type RAMG2A<'a> = LazyRAM<'a, G2A, 1>;
+pub type RAMG1Projective<'a> = LazyRAM<'a, G1Projective, 2>;
 
-const MAX_PUBLIC_INPUTS_COUNT: usize = 14;
-const MAX_PREPARE_INPUTS_INSTRUCTIONS: usize = MAX_PUBLIC_INPUTS_COUNT * 10;
+pub(crate) const MAX_PUBLIC_INPUTS_COUNT: usize = 14;
+pub(crate) const MAX_PREPARE_INPUTS_INSTRUCTIONS: usize = MAX_PUBLIC_INPUTS_COUNT * 10;
 
 /// Describes the state of the proof-verification initialization and finalization
 #[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, EnumVariantIndex, Debug, Clone)]
@@ -56,6 +58,10 @@ pub struct VerificationAccount {
     pub prepare_inputs_instructions: [u16; MAX_PREPARE_INPUTS_INSTRUCTIONS],
 
     pub vkey_id: u32,
+
+    /// The version of the [`crate::state::vkey::VKeyAccount`] used for the whole verification
+    pub vkey_version: u32,
+
     pub step: VerificationStep,
     pub state: VerificationState,
 
@@ -69,6 +75,8 @@ pub struct VerificationAccount {
     pub b: Lazy<'a, G2A>,
     #[lazy]
     pub c: Lazy<'a, G1A>,
+    #[lazy]
+    pub plonk_proof: Lazy<'a, PlonkProof>,
 
     // Computation values
     #[lazy]
@@ -81,6 +89,13 @@ pub struct VerificationAccount {
     pub(crate) alt_b: Lazy<'a, G2A>,
     pub(crate) coeff_index: u8,
 
+    // PLONK computation values
+    pub(crate) plonk_scalars: [U256; PLONK_MSM_TERMS_COUNT],
+    #[lazy]
+    pub(crate) plonk_a1: Lazy<'a, G1A>,
+    #[lazy]
+    pub(crate) plonk_b1: Lazy<'a, G1A>,
+
     // RAMs for storing computation values
     #[lazy]
     pub(crate) ram_fq: RAMFq<'a>,
@@ -90,6 +105,8 @@ pub struct VerificationAccount {
     pub(crate) ram_fq6: RAMFq6<'a>,
     #[lazy]
     pub(crate) ram_fq12: RAMFq12<'a>,
+    #[lazy]
+    pub(crate) ram_g1projective: RAMG1Projective<'a>,
 
     // If true, the proof request can be finalized
     pub is_verified: ElusivOption<bool>,
@@ -131,6 +148,14 @@ pub struct VerificationAccountData {
 
     /// The expected associated-token-account-rent in `token_id`-Token
     pub associated_token_account_rent: u64,
+
+    /// The commitment shard the next-commitment is inserted into
+    pub commitment_shard: u32,
+    /// The instruction required to follow the transfer to the recipient
+    pub program_call: ElusivOption<ProgramCall>,
+
+    /// The start of the token volume window, in which the withdrawal has been reserved (see [`crate::state::governor::TokenVolumeAccount::track_withdrawal`])
+    pub withdrawal_window_start: u64,
 }
 
 impl<'a> VerificationAccount<'a> {
@@ -142,10 +167,12 @@ impl<'a> VerificationAccount<'a> {
         public_inputs: &[RawU256],
         instructions: &Vec<u32>,
         vkey_id: u32,
+        vkey_version: u32,
         request: ProofRequest,
         tree_indices: [u32; MAX_MT_COUNT],
     ) -> ProgramResult {
         self.set_vkey_id(&vkey_id);
+        self.set_vkey_version(&vkey_version);
         self.set_request(&request);
         for (i, tree_index) in tree_indices.iter().enumerate() {
             self.set_tree_indices(i, tree_index);
@@ -196,6 +223,7 @@ impl<'a> VerificationAccount<'a> {
         self.ram_fq2.serialize()?;
         self.ram_fq6.serialize()?;
         self.ram_fq12.serialize()?;
+        self.ram_g1projective.serialize()?;
 
         Ok(())
     }
@@ -374,6 +402,7 @@ mod tests {
                 &public_inputs,
                 &instructions,
                 vkey_id,
+                2,
                 request,
                 [123, 456],
             )
@@ -381,6 +410,7 @@ mod tests {
 
         assert_matches!(verification_account.get_state(), VerificationState::None);
         assert_eq!(verification_account.get_vkey_id(), vkey_id);
+        assert_eq!(verification_account.get_vkey_version(), 2);
 
         assert_eq!(
             verification_account.get_prepare_inputs_instructions_count() as usize,
@@ -1,51 +1,85 @@
 #![allow(dead_code)]
 
 use crate::bytes::*;
-use crate::commitment::commitments_per_batch;
-use crate::error::ElusivError::{InvalidFeeVersion, InvalidQueueAccess, QueueIsEmpty, QueueIsFull};
+use crate::commitment::{commitments_per_batch, MAX_COMMITMENT_BATCHING_RATE};
+use crate::error::ElusivError::{InvalidQueueAccess, QueueIsEmpty, QueueIsFull};
 use crate::macros::{elusiv_account, guard};
 use crate::processor::CommitmentHashRequest;
-use elusiv_types::{PDAAccountData, ProgramAccount};
+use crate::types::U256;
+use borsh::{BorshDeserialize, BorshSerialize};
+use elusiv_types::{ChildAccount, PDAAccountData, ParentAccount, ProgramAccount};
+use solana_program::entrypoint::ProgramResult;
 use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
 
 /// Generates a [`QueueAccount`] and a [`Queue`] that implements the [`RingQueue`] trait
+///
+/// # Notes
+///
+/// The ring can be extended by up to `$child_account_count` overflow child-accounts storing `$child_len` elements each.
 macro_rules! queue_account {
-    ($id: ident, $id_account: ident, $seed: literal, $size: literal, $ty_element: ty) => {
-        #[elusiv_account(eager_type: true)]
+    (
+        $id: ident,
+        $id_account: ident,
+        $size: literal,
+        $ty_element: ty,
+        $child_account: ident,
+        $child_account_count: ident,
+        $child_len: ident
+    ) => {
+        pub struct $child_account;
+
+        impl ChildAccount for $child_account {
+            const INNER_SIZE: usize = $child_len * <$ty_element>::SIZE;
+        }
+
+        #[elusiv_account(parent_account: { child_account_count: $child_account_count, child_account: $child_account }, eager_type: true)]
         pub struct $id_account {
             #[no_getter]
             #[no_setter]
             pda_data: PDAAccountData,
+            pubkeys: [ElusivOption<Pubkey>; $child_account_count],
 
             head: u32,
             tail: u32,
+
+            /// The number of enabled overflow child-accounts (the ring is extended by each child-account)
+            pub overflow_accounts_count: u32,
+
             raw_data: [$ty_element; $size],
         }
 
         #[cfg(test)]
         const_assert_eq!(
             <$id_account as elusiv_types::SizedAccount>::SIZE,
-            PDAAccountData::SIZE + (4 + 4) + <$ty_element>::SIZE * ($size)
+            PDAAccountData::SIZE
+                + <ElusivOption<Pubkey>>::SIZE * $child_account_count
+                + (4 + 4 + 4)
+                + <$ty_element>::SIZE * ($size)
         );
 
         #[cfg(test)]
         const_assert_eq!(<$id>::SIZE, $size);
 
-        pub struct $id<'a, 'b> {
-            account: &'b mut $id_account<'a>,
+        pub struct $id<'a, 'b, 't, 'c> {
+            account: &'c mut $id_account<'a, 'b, 't>,
         }
 
-        impl<'a, 'b> Queue<'a, 'b, $id_account<'a>> for $id<'a, 'b> {
-            type T = $id<'a, 'b>;
-            fn new(account: &'b mut $id_account<'a>) -> Self::T {
+        impl<'a, 'b, 't, 'c> Queue<'a, 'c, $id_account<'a, 'b, 't>> for $id<'a, 'b, 't, 'c> {
+            type T = $id<'a, 'b, 't, 'c>;
+            fn new(account: &'c mut $id_account<'a, 'b, 't>) -> Self::T {
                 $id { account }
             }
         }
 
-        impl<'a, 'b> RingQueue for $id<'a, 'b> {
+        impl<'a, 'b, 't, 'c> RingQueue for $id<'a, 'b, 't, 'c> {
             type N = $ty_element;
             const CAPACITY: u32 = $size - 1;
 
+            fn size(&self) -> u32 {
+                Self::SIZE + self.account.get_overflow_accounts_count() * $child_len as u32
+            }
+
             fn get_head(&self) -> u32 {
                 self.account.get_head()
             }
@@ -58,11 +92,34 @@ macro_rules! queue_account {
             fn set_tail(&mut self, value: &u32) {
                 self.account.set_tail(value)
             }
-            fn get_data(&self, index: usize) -> Self::N {
-                self.account.get_raw_data(index)
+            fn get_data(&self, index: usize) -> Result<Self::N, ProgramError> {
+                if index < $size {
+                    return Ok(self.account.get_raw_data(index));
+                }
+
+                let (child_index, local_index) = ((index - $size) / $child_len, (index - $size) % $child_len);
+                let value = self.account.execute_on_child_account(child_index, |data| {
+                    <$ty_element>::try_from_slice(
+                        &data[local_index * <$ty_element>::SIZE..(local_index + 1) * <$ty_element>::SIZE],
+                    )
+                })??;
+
+                Ok(value)
             }
-            fn set_data(&mut self, index: usize, value: &Self::N) {
-                self.account.set_raw_data(index, value)
+            fn set_data(&mut self, index: usize, value: &Self::N) -> ProgramResult {
+                if index < $size {
+                    self.account.set_raw_data(index, value);
+                    return Ok(());
+                }
+
+                let (child_index, local_index) = ((index - $size) / $child_len, (index - $size) % $child_len);
+                self.account.execute_on_child_account_mut(child_index, |data| {
+                    let mut slice = &mut data
+                        [local_index * <$ty_element>::SIZE..(local_index + 1) * <$ty_element>::SIZE];
+                    BorshSerialize::serialize(value, &mut slice)
+                })??;
+
+                Ok(())
             }
         }
     };
@@ -73,46 +130,126 @@ pub trait Queue<'a, 'b, Account: ProgramAccount<'a>> {
     fn new(account: &'b mut Account) -> Self::T;
 }
 
+/// The max. number of overflow child-accounts of a [`CommitmentQueueAccount`]
+pub const COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT: usize = 4;
+
+/// The number of [`CommitmentHashRequest`]s stored in a single [`CommitmentQueueChildAccount`]
+pub const COMMITMENTS_PER_QUEUE_OVERFLOW_ACCOUNT: usize = 4096;
+
 // Queue used for storing commitments that should sequentially inserted into the active MT
 queue_account!(
     CommitmentQueue,
     CommitmentQueueAccount,
-    b"commitment_queue",
     240,
-    CommitmentHashRequest
+    CommitmentHashRequest,
+    CommitmentQueueChildAccount,
+    COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT,
+    COMMITMENTS_PER_QUEUE_OVERFLOW_ACCOUNT
 );
 
-impl<'a, 'b> CommitmentQueue<'a, 'b> {
-    /// Returns the next batch of commitments to be hashed together
-    pub fn next_batch(&self) -> Result<(Vec<CommitmentHashRequest>, u32), ProgramError> {
-        let mut requests = Vec::new();
-        let mut highest_batching_rate = 0;
-        let mut commitment_count: usize = u32::MAX as usize;
-        let mut fee_version = None;
-
-        while requests.len() < commitment_count {
-            let request = self.view(requests.len())?;
+impl<'a, 'b, 't> CommitmentQueueAccount<'a, 'b, 't> {
+    /// Extends the ring by the next overflow child-account
+    ///
+    /// # Notes
+    ///
+    /// - The new slots are appended after the last slot of the ring.
+    /// - If the queue wraps around, the elements in front of `tail` are moved behind the previous last slot (preserving the FIFO order).
+    /// - All child-accounts (including the new one) are required for moving the elements.
+    pub fn enable_overflow_account(&mut self) -> ProgramResult {
+        let head = self.get_head();
+        let tail = self.get_tail();
+        let previous_size = CommitmentQueue::new(self).size();
+        self.set_overflow_accounts_count(&(self.get_overflow_accounts_count() + 1));
 
-            highest_batching_rate = std::cmp::max(highest_batching_rate, request.min_batching_rate);
-            commitment_count = commitments_per_batch(highest_batching_rate);
+        if head > tail {
+            let mut queue = CommitmentQueue::new(self);
+            let size = queue.size();
 
-            // Just a (hopefully always) redundant fee-check (depends on the fee upgrade logic)
-            if let Some(f) = fee_version {
-                guard!(f == request.fee_version, InvalidFeeVersion);
+            // Each element is moved to a slot that has either been freed by the ring extension or has already been moved
+            for index in 0..tail {
+                let value = queue.get_data(index as usize)?;
+                queue.set_data(((previous_size + index) % size) as usize, &value)?;
             }
-            fee_version = Some(request.fee_version);
+            queue.set_tail(&((previous_size + tail) % size));
+        }
 
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 't, 'c> CommitmentQueue<'a, 'b, 't, 'c> {
+    /// Returns the number of commitments ahead of `commitment` in the queue (or [`None`] if it's not enqueued)
+    pub fn commitment_position(&self, commitment: &U256) -> Result<Option<u32>, ProgramError> {
+        self.position_where(|request| request.commitment == *commitment)
+    }
+
+    /// Returns the next batch of commitments to be hashed together at the MT-position `ordering`
+    ///
+    /// # Notes
+    ///
+    /// - The batching rate adapts to the queue depth: the largest batch satisfying the `min_batching_rate` of all its commitments is selected.
+    /// - A commitment is never batched below its `min_batching_rate` (which is the batching rate its fee has been paid for), unless it's the first commitment at an unaligned `ordering`.
+    /// - Batches are aligned in the MT, so the batching rate is limited by `ordering` (see [`max_aligned_batching_rate`]).
+    /// - The queue is never reordered: a commitment with a `min_batching_rate` above the aligned batching rate stops the batch in front of it.
+    /// - If the first commitment exceeds the aligned batching rate, the largest available aligned batch is used instead (which aligns the next MT-position).
+    /// - A batch can contain commitments of consecutive fee versions, the batch is computed with the fee version of the first commitment.
+    pub fn next_batch(
+        &self,
+        ordering: u32,
+    ) -> Result<(Vec<CommitmentHashRequest>, u32), ProgramError> {
+        let first = self.view_first()?;
+        let max_batching_rate = max_aligned_batching_rate(ordering);
+        let max_count = std::cmp::min(
+            commitments_per_batch(max_batching_rate),
+            self.len() as usize,
+        );
+
+        let mut requests = vec![first];
+        for offset in 1..max_count {
+            let request = self.view(offset)?;
+            if request.min_batching_rate > max_batching_rate {
+                break;
+            }
             requests.push(request);
         }
 
-        if requests.is_empty() {
-            return Err(QueueIsEmpty.into());
+        let batching_rate = if first.min_batching_rate > max_batching_rate {
+            (0..=max_batching_rate)
+                .rev()
+                .find(|&batching_rate| requests.len() >= commitments_per_batch(batching_rate))
+        } else {
+            adaptive_batching_rate(&requests, max_batching_rate)
         }
-        Ok((requests, highest_batching_rate))
+        .ok_or(InvalidQueueAccess)?;
+
+        requests.truncate(commitments_per_batch(batching_rate));
+        Ok((requests, batching_rate))
     }
 }
 
-/// Ring-queue with a capacity of [`RingQueue::CAPACITY`] elements
+/// The largest batching rate of a batch starting at the MT-position `ordering`
+pub fn max_aligned_batching_rate(ordering: u32) -> u32 {
+    std::cmp::min(
+        ordering.trailing_zeros(),
+        MAX_COMMITMENT_BATCHING_RATE as u32,
+    )
+}
+
+/// Selects the largest batching rate (up to `max_batching_rate`), for which enough `requests` are available and that satisfies the `min_batching_rate` of all requests in the batch
+pub fn adaptive_batching_rate(
+    requests: &[CommitmentHashRequest],
+    max_batching_rate: u32,
+) -> Option<u32> {
+    (0..=max_batching_rate).rev().find(|&batching_rate| {
+        let count = commitments_per_batch(batching_rate);
+        requests.len() >= count
+            && requests[..count]
+                .iter()
+                .all(|request| request.min_batching_rate <= batching_rate)
+    })
+}
+
+/// Ring-queue with a capacity of [`RingQueue::CAPACITY`] elements (or [`RingQueue::capacity`] for extendable queues)
 /// - works by having two pointers, `head` and `tail` and a some data storage with getter, setter
 /// - `head` points to the first element (first according to the FIFO definition)
 /// - `tail` points to the location to insert the next element
@@ -123,24 +260,33 @@ pub trait RingQueue {
     const CAPACITY: u32;
     const SIZE: u32 = Self::CAPACITY + 1;
 
+    /// The number of slots of the ring
+    fn size(&self) -> u32 {
+        Self::SIZE
+    }
+
+    fn capacity(&self) -> u32 {
+        self.size() - 1
+    }
+
     fn get_head(&self) -> u32;
     fn set_head(&mut self, value: &u32);
 
     fn get_tail(&self) -> u32;
     fn set_tail(&mut self, value: &u32);
 
-    fn get_data(&self, index: usize) -> Self::N;
-    fn set_data(&mut self, index: usize, value: &Self::N);
+    fn get_data(&self, index: usize) -> Result<Self::N, ProgramError>;
+    fn set_data(&mut self, index: usize, value: &Self::N) -> ProgramResult;
 
     /// Try to enqueue a new element in the queue
     fn enqueue(&mut self, value: Self::N) -> Result<(), ProgramError> {
         let head = self.get_head();
         let tail = self.get_tail();
 
-        let next_tail = (tail + 1) % Self::SIZE;
+        let next_tail = (tail + 1) % self.size();
         guard!(next_tail != head, QueueIsFull);
 
-        self.set_data(tail as usize, &value);
+        self.set_data(tail as usize, &value)?;
         self.set_tail(&next_tail);
 
         Ok(())
@@ -157,7 +303,7 @@ pub trait RingQueue {
         guard!(head != tail, QueueIsEmpty);
         guard!(usize_as_u32_safe(offset) < self.len(), InvalidQueueAccess);
 
-        Ok(self.get_data((head as usize + offset) % Self::SIZE as usize))
+        self.get_data((head as usize + offset) % self.size() as usize)
     }
 
     /// Try to remove the first element from the queue
@@ -166,8 +312,8 @@ pub trait RingQueue {
         let tail = self.get_tail();
         guard!(head != tail, QueueIsEmpty);
 
-        let value = self.get_data(head as usize);
-        self.set_head(&((head + 1) % Self::SIZE));
+        let value = self.get_data(head as usize)?;
+        self.set_head(&((head + 1) % self.size()));
 
         Ok(value)
     }
@@ -175,22 +321,31 @@ pub trait RingQueue {
     fn remove(&mut self, count: u32) -> Result<(), ProgramError> {
         let head = self.get_head();
         guard!(self.len() >= count, InvalidQueueAccess);
-        self.set_head(&((head + count) % Self::SIZE));
+        self.set_head(&((head + count) % self.size()));
         Ok(())
     }
 
-    fn contains(&self, value: &Self::N) -> bool {
-        let mut ptr = self.get_head();
-        let tail = self.get_tail();
-
-        while ptr != tail {
-            if self.get_data(ptr as usize) == *value {
-                return true;
+    /// Returns the offset of the first element (starting at the head) matching `predicate`
+    fn position_where<P>(&self, predicate: P) -> Result<Option<u32>, ProgramError>
+    where
+        P: Fn(&Self::N) -> bool,
+    {
+        for offset in 0..self.len() {
+            if predicate(&self.view(offset as usize)?) {
+                return Ok(Some(offset));
             }
-            ptr = (ptr + 1) % Self::SIZE;
         }
 
-        false
+        Ok(None)
+    }
+
+    /// Returns the offset of the first occurrence of `value` (starting at the head)
+    fn position(&self, value: &Self::N) -> Result<Option<u32>, ProgramError> {
+        self.position_where(|v| v == value)
+    }
+
+    fn contains(&self, value: &Self::N) -> Result<bool, ProgramError> {
+        Ok(self.position(value)?.is_some())
     }
 
     fn len(&self) -> u32 {
@@ -200,7 +355,7 @@ pub trait RingQueue {
         if tail >= head {
             tail - head
         } else {
-            Self::SIZE - head + tail
+            self.size() - head + tail
         }
     }
 
@@ -209,7 +364,7 @@ pub trait RingQueue {
     }
 
     fn empty_slots(&self) -> u32 {
-        Self::CAPACITY - self.len()
+        self.capacity() - self.len()
     }
 
     #[cfg(test)]
@@ -222,11 +377,12 @@ pub trait RingQueue {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{
-        commitment::MAX_COMMITMENT_BATCHING_RATE,
-        fields::{fr_to_u256_le, u64_to_scalar},
-    };
+    use crate::commitment::commitment_hash_computation_instructions;
+    use crate::fields::{fr_to_u256_le, u64_to_scalar};
+    use crate::macros::account_info;
+    use crate::state::fee::ProgramFee;
     use assert_matches::assert_matches;
+    use std::collections::VecDeque;
 
     struct TestQueue<const S: usize> {
         head: u32,
@@ -252,17 +408,12 @@ mod tests {
             self.tail = *value;
         }
 
-        fn get_data(&self, index: usize) -> u32 {
-            self.data[index]
+        fn get_data(&self, index: usize) -> Result<u32, ProgramError> {
+            Ok(self.data[index])
         }
-        fn set_data(&mut self, index: usize, value: &u32) {
+        fn set_data(&mut self, index: usize, value: &u32) -> ProgramResult {
             self.data[index] = *value;
-        }
-    }
-
-    impl<const S: usize> TestQueue<S> {
-        pub fn capacity(&self) -> u32 {
-            Self::CAPACITY
+            Ok(())
         }
     }
 
@@ -438,63 +589,382 @@ mod tests {
         assert!(queue.is_empty());
     }
 
+    #[test]
+    fn test_position() {
+        test_queue!(queue, 5, 3, 3);
+        assert_eq!(queue.position(&0).unwrap(), None);
+
+        for i in 0..4 {
+            queue.enqueue(i).unwrap();
+        }
+        for i in 0..4 {
+            assert_eq!(queue.position(&i).unwrap(), Some(i));
+        }
+        assert_eq!(queue.position(&4).unwrap(), None);
+        assert!(queue.contains(&3).unwrap());
+
+        queue.dequeue_first().unwrap();
+        assert_eq!(queue.position(&0).unwrap(), None);
+        assert_eq!(queue.position(&3).unwrap(), Some(2));
+    }
+
+    fn indexed_request(index: u32) -> CommitmentHashRequest {
+        let mut commitment = [0; 32];
+        commitment[..4].copy_from_slice(&index.to_le_bytes());
+        CommitmentHashRequest {
+            commitment,
+            fee_version: 0,
+            min_batching_rate: 0,
+        }
+    }
+
+    #[test]
+    fn test_overflow_accounts() {
+        let mut data = vec![0; <CommitmentQueueAccount as elusiv_types::SizedAccount>::SIZE];
+        account_info!(
+            child_account,
+            Pubkey::new_unique(),
+            vec![0; <CommitmentQueueChildAccount as elusiv_types::SizedAccount>::SIZE]
+        );
+        let mut child_accounts = vec![None; COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT];
+        child_accounts[0] = Some(&child_account);
+        let mut queue =
+            CommitmentQueueAccount::new_with_child_accounts(&mut data, child_accounts).unwrap();
+
+        queue.enable_overflow_account().unwrap();
+        let mut q = CommitmentQueue::new(&mut queue);
+        let capacity = CommitmentQueue::CAPACITY + COMMITMENTS_PER_QUEUE_OVERFLOW_ACCOUNT as u32;
+        assert_eq!(q.capacity(), capacity);
+
+        // Elements are spilled into the overflow account
+        for i in 0..capacity {
+            q.enqueue(indexed_request(i)).unwrap();
+        }
+        assert_matches!(q.enqueue(indexed_request(capacity)), Err(_));
+        assert_eq!(q.len(), capacity);
+        assert_eq!(q.empty_slots(), 0);
+
+        // FIFO is preserved across the main account and the overflow account
+        for i in 0..CommitmentQueue::CAPACITY + 10 {
+            assert_eq!(q.dequeue_first().unwrap(), indexed_request(i));
+        }
+
+        // Wrap around into the main account
+        for i in capacity..capacity + 10 {
+            q.enqueue(indexed_request(i)).unwrap();
+        }
+        assert_eq!(
+            q.commitment_position(&indexed_request(capacity + 9).commitment)
+                .unwrap(),
+            Some(q.len() - 1)
+        );
+        assert_eq!(
+            q.commitment_position(&indexed_request(0).commitment)
+                .unwrap(),
+            None
+        );
+
+        for i in CommitmentQueue::CAPACITY + 10..capacity + 10 {
+            assert_eq!(q.dequeue_first().unwrap(), indexed_request(i));
+        }
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn test_enable_overflow_account_wrapped() {
+        let mut data = vec![0; <CommitmentQueueAccount as elusiv_types::SizedAccount>::SIZE];
+        account_info!(
+            child_account0,
+            Pubkey::new_unique(),
+            vec![0; <CommitmentQueueChildAccount as elusiv_types::SizedAccount>::SIZE]
+        );
+        account_info!(
+            child_account1,
+            Pubkey::new_unique(),
+            vec![0; <CommitmentQueueChildAccount as elusiv_types::SizedAccount>::SIZE]
+        );
+        let mut child_accounts = vec![None; COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT];
+        child_accounts[0] = Some(&child_account0);
+        child_accounts[1] = Some(&child_account1);
+        let mut queue =
+            CommitmentQueueAccount::new_with_child_accounts(&mut data, child_accounts).unwrap();
+
+        // Full queue wrapping around its last slot (the elements in front of `tail` are moved into the new child-account)
+        let mut q = CommitmentQueue::new(&mut queue);
+        q.set_head(&100);
+        q.set_tail(&100);
+        for i in 0..CommitmentQueue::CAPACITY {
+            q.enqueue(indexed_request(i)).unwrap();
+        }
+        assert_eq!(q.get_tail(), 99);
+        assert_matches!(queue.enable_overflow_account(), Ok(()));
+
+        let mut q = CommitmentQueue::new(&mut queue);
+        let len = CommitmentQueue::CAPACITY;
+        assert_eq!(q.len(), len);
+        for i in 0..len {
+            assert_eq!(q.view(i as usize).unwrap(), indexed_request(i));
+        }
+
+        // The new slots are usable
+        let capacity = q.capacity();
+        for i in len..capacity {
+            q.enqueue(indexed_request(i)).unwrap();
+        }
+        assert_matches!(q.enqueue(indexed_request(capacity)), Err(_));
+
+        // Full queue wrapping around with more elements in front of `tail` than the child-account stores
+        q.clear();
+        q.set_head(&(q.size() - 1));
+        q.set_tail(&(q.size() - 1));
+        for i in 0..capacity {
+            q.enqueue(indexed_request(i)).unwrap();
+        }
+        assert!(q.get_tail() as usize > COMMITMENTS_PER_QUEUE_OVERFLOW_ACCOUNT);
+        assert_matches!(queue.enable_overflow_account(), Ok(()));
+
+        let mut q = CommitmentQueue::new(&mut queue);
+        assert_eq!(q.len(), capacity);
+        for i in capacity..q.capacity() {
+            q.enqueue(indexed_request(i)).unwrap();
+        }
+        assert_matches!(q.enqueue(indexed_request(0)), Err(_));
+        for i in 0..q.capacity() {
+            assert_eq!(q.dequeue_first().unwrap(), indexed_request(i));
+        }
+        assert!(q.is_empty());
+    }
+
+    fn request(min_batching_rate: u32, fee_version: u32) -> CommitmentHashRequest {
+        CommitmentHashRequest {
+            commitment: [0; 32],
+            fee_version,
+            min_batching_rate,
+        }
+    }
+
+    fn indexed(min_batching_rate: u32, index: u32) -> CommitmentHashRequest {
+        CommitmentHashRequest {
+            min_batching_rate,
+            ..indexed_request(index)
+        }
+    }
+
     #[test]
     fn test_next_batch() {
         let mut data = vec![0; <CommitmentQueueAccount as elusiv_types::SizedAccount>::SIZE];
         let mut q = CommitmentQueueAccount::new(&mut data).unwrap();
         let mut q = CommitmentQueue::new(&mut q);
 
+        // Empty queue
+        assert_matches!(q.next_batch(0), Err(_));
+
         // Incomplete batch
         for _ in 0..3 {
-            q.enqueue(CommitmentHashRequest {
-                commitment: [0; 32],
-                fee_version: 0,
-                min_batching_rate: 2,
-            })
-            .unwrap();
+            q.enqueue(request(2, 0)).unwrap();
         }
-        assert_matches!(q.next_batch(), Err(_));
+        assert_matches!(q.next_batch(0), Err(_));
 
-        // Complete batches (with variing batching rates)
-        q.clear();
-        for b in 0..=MAX_COMMITMENT_BATCHING_RATE {
-            let c = commitments_per_batch(b as u32);
-            for i in 0..c {
+        // The batching rate adapts to the queue depth
+        for len in 1..=commitments_per_batch(MAX_COMMITMENT_BATCHING_RATE as u32) * 2 {
+            q.clear();
+            for i in 0..len {
                 q.enqueue(CommitmentHashRequest {
                     commitment: fr_to_u256_le(&u64_to_scalar(i as u64)),
                     fee_version: 0,
-                    min_batching_rate: if i == 0 { b as u32 } else { 0 },
+                    min_batching_rate: 0,
                 })
                 .unwrap();
             }
-        }
-
-        for b in 0..=MAX_COMMITMENT_BATCHING_RATE {
-            let (batch, batching_rate) = q.next_batch().unwrap();
-            for _ in 0..commitments_per_batch(batching_rate) {
-                q.dequeue_first().unwrap();
-            }
 
-            assert_eq!(batching_rate as usize, b);
+            let (batch, batching_rate) = q.next_batch(0).unwrap();
+            let expected = std::cmp::min(
+                usize::BITS - 1 - len.leading_zeros(),
+                MAX_COMMITMENT_BATCHING_RATE as u32,
+            );
+            assert_eq!(batching_rate, expected);
+            assert_eq!(batch.len(), commitments_per_batch(expected));
             for (i, c) in batch.iter().enumerate() {
                 assert_eq!(c.commitment, fr_to_u256_le(&u64_to_scalar(i as u64)));
             }
         }
 
-        // Mismatching fee
+        // The min_batching_rate of all commitments in the batch is satisfied
         q.clear();
-        q.enqueue(CommitmentHashRequest {
-            commitment: [0; 32],
-            fee_version: 0,
-            min_batching_rate: 1,
-        })
-        .unwrap();
-        q.enqueue(CommitmentHashRequest {
-            commitment: [0; 32],
-            fee_version: 1,
-            min_batching_rate: 1,
-        })
-        .unwrap();
-        assert_matches!(q.next_batch(), Err(_));
+        q.enqueue(request(0, 0)).unwrap();
+        q.enqueue(request(0, 0)).unwrap();
+        q.enqueue(request(2, 0)).unwrap();
+        assert_eq!(q.next_batch(0).unwrap().1, 1);
+        q.enqueue(request(0, 0)).unwrap();
+        assert_eq!(q.next_batch(0).unwrap().1, 2);
+
+        // Batches are aligned in the MT
+        q.clear();
+        for _ in 0..16 {
+            q.enqueue(request(0, 0)).unwrap();
+        }
+        assert_eq!(q.next_batch(1).unwrap().1, 0);
+        assert_eq!(q.next_batch(2).unwrap().1, 1);
+        assert_eq!(q.next_batch(12).unwrap().1, 2);
+        assert_eq!(q.next_batch(24).unwrap().1, 3);
+
+        // A first commitment exceeding the aligned batching rate is batched in the largest available aligned batch
+        q.clear();
+        for _ in 0..4 {
+            q.enqueue(request(2, 0)).unwrap();
+        }
+        assert_eq!(q.next_batch(4).unwrap().1, 2);
+        assert_eq!(q.next_batch(2).unwrap(), (vec![request(2, 0)], 0));
+        assert_eq!(q.next_batch(1).unwrap(), (vec![request(2, 0)], 0));
+
+        // Commitments exceeding the aligned batching rate stop the batch (the queue is never reordered)
+        q.clear();
+        q.enqueue(indexed(0, 0)).unwrap();
+        q.enqueue(indexed(0, 1)).unwrap();
+        q.enqueue(indexed(3, 2)).unwrap();
+        q.enqueue(indexed(0, 3)).unwrap();
+        let (batch, batching_rate) = q.next_batch(4).unwrap();
+        assert_eq!(batching_rate, 1);
+        assert_eq!(batch, vec![indexed(0, 0), indexed(0, 1)]);
+        q.remove(2).unwrap();
+        assert_eq!(
+            q.commitment_position(&indexed(3, 2).commitment).unwrap(),
+            Some(0)
+        );
+
+        // At an aligned MT-position the commitment is batched at its min_batching_rate
+        for i in 4..10 {
+            q.enqueue(indexed(0, i)).unwrap();
+        }
+        let (batch, batching_rate) = q.next_batch(8).unwrap();
+        assert_eq!(batching_rate, 3);
+        assert_eq!(batch[0], indexed(3, 2));
+
+        // Otherwise the batch aligns the next MT-position
+        let (batch, batching_rate) = q.next_batch(6).unwrap();
+        assert_eq!(batching_rate, 1);
+        assert_eq!(batch, vec![indexed(3, 2), indexed(0, 3)]);
+
+        // A fee version change does not result in a batch below the min_batching_rate
+        q.clear();
+        q.enqueue(request(0, 0)).unwrap();
+        q.enqueue(request(0, 1)).unwrap();
+        let (batch, batching_rate) = q.next_batch(0).unwrap();
+        assert_eq!(batching_rate, 1);
+        assert_eq!(batch, vec![request(0, 0), request(0, 1)]);
+
+        q.clear();
+        q.enqueue(request(1, 0)).unwrap();
+        q.enqueue(request(1, 1)).unwrap();
+        let (batch, batching_rate) = q.next_batch(0).unwrap();
+        assert_eq!(batching_rate, 1);
+        assert_eq!(batch.len(), 2);
+
+        q.clear();
+        q.enqueue(request(2, 0)).unwrap();
+        q.enqueue(request(2, 1)).unwrap();
+        assert_matches!(q.next_batch(0), Err(_));
+
+        q.clear();
+        q.enqueue(request(1, 0)).unwrap();
+        assert_matches!(q.next_batch(0), Err(_));
+    }
+
+    struct SimulationResult {
+        /// Average number of steps a commitment waits in the queue
+        latency: f64,
+
+        /// Average number of hash txs per commitment
+        tx_count: f64,
+    }
+
+    /// Enqueues `arrivals_per_step` commitments per step and hashes all available batches after each step
+    fn simulate(
+        arrivals_per_step: usize,
+        min_batching_rate: u32,
+        steps: usize,
+    ) -> SimulationResult {
+        let mut data = vec![0; <CommitmentQueueAccount as elusiv_types::SizedAccount>::SIZE];
+        let mut q = CommitmentQueueAccount::new(&mut data).unwrap();
+        let mut q = CommitmentQueue::new(&mut q);
+
+        let mut arrivals = VecDeque::new();
+        let mut ordering = 0;
+        let mut latency = 0;
+        let mut tx_count = 0;
+        let mut hashed = 0;
+
+        for step in 0..steps {
+            for _ in 0..arrivals_per_step {
+                q.enqueue(request(min_batching_rate, 0)).unwrap();
+                arrivals.push_back(step);
+            }
+
+            while let Ok((batch, batching_rate)) = q.next_batch(ordering) {
+                q.remove(batch.len() as u32).unwrap();
+                for _ in 0..batch.len() {
+                    latency += step - arrivals.pop_front().unwrap();
+                }
+
+                ordering += batch.len() as u32;
+                tx_count += commitment_hash_computation_instructions(batching_rate).len();
+                hashed += batch.len();
+            }
+        }
+
+        SimulationResult {
+            latency: latency as f64 / hashed as f64,
+            tx_count: tx_count as f64 / hashed as f64,
+        }
+    }
+
+    #[test]
+    fn test_batching_rate_fee_latency_trade_off() {
+        let fee = ProgramFee::new(5000, 0, 0, 0, 0, 100, 0).unwrap();
+        let steps = 64;
+
+        // A higher min_batching_rate results in a lower fee but a higher latency
+        for min_batching_rate in 1..=MAX_COMMITMENT_BATCHING_RATE as u32 {
+            let lower = simulate(1, min_batching_rate - 1, steps);
+            let higher = simulate(1, min_batching_rate, steps);
+
+            assert!(higher.latency > lower.latency);
+            assert!(higher.tx_count <= lower.tx_count);
+            assert!(
+                fee.commitment_hash_computation_fee(min_batching_rate).0
+                    <= fee.commitment_hash_computation_fee(min_batching_rate - 1).0
+            );
+        }
+
+        // A user's fee covers the hash txs of the batches its commitment is part of
+        for min_batching_rate in 0..=MAX_COMMITMENT_BATCHING_RATE as u32 {
+            let result = simulate(1, min_batching_rate, steps);
+            assert!(
+                result.tx_count * fee.hash_tx_compensation().0 as f64
+                    <= fee.commitment_hash_computation_fee(min_batching_rate).0 as f64
+            );
+        }
+    }
+
+    #[test]
+    fn test_batching_rate_adapts_to_load() {
+        let steps = 64;
+
+        // Without any waiting, commitments are hashed individually under low load
+        let low_load = simulate(1, 0, steps);
+        assert_eq!(low_load.latency, 0.0);
+        assert_eq!(
+            low_load.tx_count,
+            commitment_hash_computation_instructions(0).len() as f64
+        );
+
+        // Under high load, larger batches are used without increasing the latency
+        let high_load = simulate(
+            commitments_per_batch(MAX_COMMITMENT_BATCHING_RATE as u32),
+            0,
+            steps,
+        );
+        assert_eq!(high_load.latency, 0.0);
+        assert!(high_load.tx_count < low_load.tx_count);
     }
 }
@@ -1,6 +1,6 @@
 use super::program_account::*;
 use crate::bytes::*;
-use crate::macros::{elusiv_account, two_pow};
+use crate::macros::{elusiv_account, guard, two_pow};
 use crate::types::U256;
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::entrypoint::ProgramResult;
@@ -25,8 +25,61 @@ pub const fn mt_size(height: u32) -> usize {
 pub const MT_COMMITMENT_COUNT: usize = two_pow!(MT_HEIGHT);
 
 /// Since before submitting a proof request the current root can change, we store the [`HISTORY_ARRAY_SIZE`] previous ones
+///
+/// # Note
+///
+/// This is the initial size of the root history, which can be grown up to [`MAX_HISTORY_ARRAY_SIZE`] per deployment.
 pub const HISTORY_ARRAY_SIZE: usize = 100;
 
+/// The max. number of roots stored in the root history
+pub const MAX_HISTORY_ARRAY_SIZE: usize = 1024;
+
+/// The max. number of concurrently active MTs (commitment shards)
+pub const MAX_COMMITMENT_SHARDS_COUNT: u32 = 8;
+
+/// The MT-index bits above this offset store the shard of the MT
+///
+/// # Note
+///
+/// The MTs of the first shard are indexed sequentially, so the MT-indices of trees created before sharding remain unchanged.
+pub const MT_INDEX_SHARD_OFFSET: u32 = 28;
+
+/// The [`PDAOffset`] of the [`StorageAccount`], [`crate::state::queue::CommitmentQueueAccount`] and [`crate::state::commitment::CommitmentHashingAccount`] of a shard
+///
+/// # Note
+///
+/// The first shard uses the single-instance accounts (without an offset).
+pub fn shard_pda_offset(shard: u32) -> PDAOffset {
+    match shard {
+        0 => None,
+        _ => Some(shard),
+    }
+}
+
+/// The shard a commitment is inserted into
+///
+/// # Note
+///
+/// A `shards_count` of zero is treated as a single shard.
+pub fn commitment_shard(commitment: &U256, shards_count: u32) -> u32 {
+    let shards_count = shards_count.clamp(1, MAX_COMMITMENT_SHARDS_COUNT);
+    u32::from_le_bytes(commitment[..4].try_into().unwrap()) % shards_count
+}
+
+/// The shard containing the MT with the index `mt_index`
+pub fn mt_shard(mt_index: u32) -> u32 {
+    mt_index >> MT_INDEX_SHARD_OFFSET
+}
+
+/// The (global) MT-index of the `tree_index`-th MT of a shard
+pub fn shard_mt_index(shard: u32, tree_index: u32) -> Option<u32> {
+    if shard >= MAX_COMMITMENT_SHARDS_COUNT || tree_index >> MT_INDEX_SHARD_OFFSET != 0 {
+        return None;
+    }
+
+    Some((shard << MT_INDEX_SHARD_OFFSET) | tree_index)
+}
+
 pub const VALUES_PER_STORAGE_SUB_ACCOUNT: usize = 83_887;
 const ACCOUNTS_COUNT: usize = div_ceiling_usize(MT_SIZE, VALUES_PER_STORAGE_SUB_ACCOUNT);
 
@@ -54,25 +107,47 @@ pub struct StorageAccount {
     /// Points to the next commitment in the active MT
     pub next_commitment_ptr: u32,
 
-    /// The amount of already finished (closed) MTs
+    /// The amount of already finished (closed) MTs of this shard
     pub trees_count: u32,
 
     /// The amount of archived MTs
     archived_count: u32,
 
-    /// Stores the last [`HISTORY_ARRAY_SIZE`] roots of the active tree (including the current root)
-    pub active_mt_root_history: [U256; HISTORY_ARRAY_SIZE],
+    /// The number of roots of the active MT
     pub mt_roots_count: u32, // required since we batch insert commitments
+
+    /// The number of roots of the most recently closed MT
+    pub closed_mt_roots_count: u32,
+
+    /// The number of all roots inserted into the `root_history` (of all MTs)
+    pub root_history_count: u32,
+
+    /// The commitment shard this account belongs to
+    pub shard: u32,
+
+    /// The number of [`crate::state::commitment::CommitmentHashingAccount`]s of this shard (zero is treated as one)
+    pub hashing_accounts_count: u32,
+
+    /// The index of the [`crate::state::commitment::CommitmentHashingAccount`] hashing the next batch
+    pub next_hashing_account_index: u32,
+
+    /// Ring buffer storing the last roots of the active and the most recently closed MT (including the current root)
+    #[growable]
+    pub root_history: [U256; HISTORY_ARRAY_SIZE],
 }
 
 impl<'a, 'b, 't> StorageAccount<'a, 'b, 't> {
+    /// Resets the active MT, whose roots remain in the root history as the roots of the closed MT
     pub fn reset(&mut self) {
         self.set_next_commitment_ptr(&0);
+        self.set_closed_mt_roots_count(&self.get_mt_roots_count());
         self.set_mt_roots_count(&0);
+    }
 
-        for i in 0..self.active_mt_root_history.len() {
-            self.active_mt_root_history[i] = 0;
-        }
+    /// The (global) MT-index of the active MT
+    pub fn active_mt_index(&self) -> Result<u32, ProgramError> {
+        shard_mt_index(self.get_shard(), self.get_trees_count())
+            .ok_or(ProgramError::InvalidArgument)
     }
 
     pub fn is_full(&self) -> bool {
@@ -124,18 +199,76 @@ impl<'a, 'b, 't> StorageAccount<'a, 'b, 't> {
         self.get_node(0, 0)
     }
 
-    /// A root is valid if it's the current root or inside of the active_mt_root_history array
-    pub fn is_root_valid(&self, root: &U256) -> bool {
-        let max_history_roots =
-            std::cmp::min(self.get_mt_roots_count() as usize, HISTORY_ARRAY_SIZE);
+    /// Inserts a new root of the active MT into the root history
+    pub fn push_root(&mut self, root: &U256) -> ProgramResult {
+        let count = self.get_root_history_count();
+        self.set_root_history(count as usize % self.root_history_len(), root)?;
 
-        // TODO: remove this, has become redundant
+        self.set_root_history_count(&count.checked_add(1).ok_or(ProgramError::InvalidArgument)?);
+        self.set_mt_roots_count(&(self.get_mt_roots_count() + 1));
+
+        Ok(())
+    }
+
+    /// A root is valid if it's the current root or one of the active MT roots inside of the root history
+    pub fn is_root_valid(&self, root: &U256) -> bool {
         if let Ok(current_root) = self.get_root() {
-            return *root == current_root;
+            if *root == current_root {
+                return true;
+            }
         }
 
-        max_history_roots > 0
-            && contains(root, &self.active_mt_root_history[..max_history_roots * 32])
+        self.is_history_root(root, 0, self.get_mt_roots_count())
+    }
+
+    /// A root is valid for the most recently closed MT if it's one of its roots inside of the root history
+    ///
+    /// # Note
+    ///
+    /// The final root of the closed MT is stored in its [`crate::state::nullifier::NullifierAccount`].
+    pub fn is_closed_root_valid(&self, root: &U256) -> bool {
+        self.is_history_root(
+            root,
+            self.get_mt_roots_count(),
+            self.get_closed_mt_roots_count(),
+        )
+    }
+
+    /// Searches the `roots_count` roots preceding the `skip` last roots of the root history
+    fn is_history_root(&self, root: &U256, skip: u32, roots_count: u32) -> bool {
+        let count = self.get_root_history_count() as usize;
+        let len = self.root_history_len();
+
+        // Roots older than the last `len` roots have been overwritten
+        let end = count.saturating_sub(skip as usize);
+        let start = std::cmp::max(
+            end.saturating_sub(roots_count as usize),
+            count.saturating_sub(len),
+        );
+
+        (start..end).any(|i| self.get_root_history(i % len).as_ref() == Some(root))
+    }
+
+    /// Moves the stored roots to their ring buffer positions after the root history has grown from `previous_len`
+    pub fn reorder_root_history(&mut self, previous_len: usize) -> ProgramResult {
+        let count = self.get_root_history_count() as usize;
+        let len = self.root_history_len();
+        guard!(
+            previous_len > 0 && previous_len <= len,
+            ProgramError::InvalidArgument
+        );
+
+        let start = count.saturating_sub(previous_len);
+        let roots = (start..count)
+            .map(|i| self.get_root_history(i % previous_len))
+            .collect::<Option<Vec<U256>>>()
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        for (i, root) in (start..count).zip(roots) {
+            self.set_root_history(i % len, &root)?;
+        }
+
+        Ok(())
     }
 
     #[allow(clippy::needless_range_loop)]
@@ -288,6 +421,44 @@ mod tests {
         assert_eq!(6, mt_array_index(3, 2));
     }
 
+    #[test]
+    fn test_commitment_shard() {
+        assert_eq!(shard_pda_offset(0), None);
+        assert_eq!(shard_pda_offset(3), Some(3));
+
+        let mut commitment = [0xff; 32];
+        commitment[..4].copy_from_slice(&13u32.to_le_bytes());
+        assert_eq!(commitment_shard(&commitment, 0), 0);
+        assert_eq!(commitment_shard(&commitment, 1), 0);
+        assert_eq!(commitment_shard(&commitment, 4), 1);
+        assert_eq!(
+            commitment_shard(&commitment, u32::MAX),
+            13 % MAX_COMMITMENT_SHARDS_COUNT
+        );
+
+        for shard in 0..MAX_COMMITMENT_SHARDS_COUNT {
+            for tree_index in 0..3 {
+                let mt_index = shard_mt_index(shard, tree_index).unwrap();
+                assert_eq!(mt_shard(mt_index), shard);
+                assert_eq!(mt_index & ((1 << MT_INDEX_SHARD_OFFSET) - 1), tree_index);
+            }
+        }
+        assert_eq!(shard_mt_index(0, u32::MAX), None);
+        assert_eq!(shard_mt_index(MAX_COMMITMENT_SHARDS_COUNT, 0), None);
+
+        // The MTs of the first shard are indexed sequentially
+        assert_eq!(shard_mt_index(0, 5), Some(5));
+        assert_eq!(mt_shard(5), 0);
+
+        parent_account!(mut storage_account, StorageAccount);
+        storage_account.set_shard(&3);
+        storage_account.set_trees_count(&2);
+        assert_eq!(
+            storage_account.active_mt_index().unwrap(),
+            (3 << MT_INDEX_SHARD_OFFSET) + 2
+        );
+    }
+
     #[test]
     fn test_empty_root_raw() {
         assert_eq!(empty_root_raw().reduce(), EMPTY_TREE[MT_HEIGHT as usize]);
@@ -438,4 +609,111 @@ mod tests {
         assert!(storage_account.is_root_valid(&EMPTY_TREE[MT_HEIGHT as usize]));
         assert!(!storage_account.is_root_valid(&[0; 32]));
     }
+
+    #[test]
+    fn test_is_root_valid_history() {
+        parent_account!(mut storage_account, StorageAccount);
+
+        for i in 0..3 {
+            storage_account.push_root(&[i + 1; 32]).unwrap();
+        }
+        assert_eq!(storage_account.get_mt_roots_count(), 3);
+        assert_eq!(storage_account.get_root_history_count(), 3);
+
+        // Stale but recent roots
+        for i in 0..3 {
+            assert!(storage_account.is_root_valid(&[i + 1; 32]));
+        }
+        assert!(!storage_account.is_root_valid(&[4; 32]));
+        assert!(!storage_account.is_closed_root_valid(&[1; 32]));
+    }
+
+    #[test]
+    fn test_root_history_overwrite() {
+        parent_account!(mut storage_account, StorageAccount);
+        let len = storage_account.root_history_len();
+        assert_eq!(len, HISTORY_ARRAY_SIZE);
+
+        for i in 0..len + 2 {
+            let mut root = [0; 32];
+            root[..8].copy_from_slice(&(i as u64 + 1).to_le_bytes());
+            storage_account.push_root(&root).unwrap();
+        }
+
+        let root = |i: usize| {
+            let mut root = [0; 32];
+            root[..8].copy_from_slice(&(i as u64 + 1).to_le_bytes());
+            root
+        };
+
+        // The two oldest roots have been overwritten
+        assert!(!storage_account.is_root_valid(&root(0)));
+        assert!(!storage_account.is_root_valid(&root(1)));
+        for i in 2..len + 2 {
+            assert!(storage_account.is_root_valid(&root(i)));
+        }
+    }
+
+    #[test]
+    fn test_is_closed_root_valid() {
+        parent_account!(mut storage_account, StorageAccount);
+
+        storage_account.push_root(&[1; 32]).unwrap();
+        storage_account.push_root(&[2; 32]).unwrap();
+        storage_account.reset();
+
+        assert_eq!(storage_account.get_mt_roots_count(), 0);
+        assert_eq!(storage_account.get_closed_mt_roots_count(), 2);
+
+        // Roots of the closed MT are not valid for the active MT
+        assert!(!storage_account.is_root_valid(&[1; 32]));
+        assert!(storage_account.is_closed_root_valid(&[1; 32]));
+        assert!(storage_account.is_closed_root_valid(&[2; 32]));
+
+        storage_account.push_root(&[3; 32]).unwrap();
+        assert!(storage_account.is_root_valid(&[3; 32]));
+        assert!(!storage_account.is_closed_root_valid(&[3; 32]));
+        assert!(storage_account.is_closed_root_valid(&[2; 32]));
+
+        // Only the most recently closed MT is considered
+        storage_account.reset();
+        assert!(!storage_account.is_closed_root_valid(&[1; 32]));
+        assert!(storage_account.is_closed_root_valid(&[3; 32]));
+    }
+
+    #[test]
+    fn test_reorder_root_history() {
+        let previous_len = HISTORY_ARRAY_SIZE;
+        let mut data = vec![0; StorageAccount::size_with_len(previous_len)];
+        {
+            let mut storage_account = StorageAccount::new(&mut data).unwrap();
+            for i in 0..previous_len + 3 {
+                storage_account.push_root(&[i as u8; 32]).unwrap();
+            }
+        }
+
+        data.extend(vec![0; 5 * 32]);
+        let mut storage_account = StorageAccount::new(&mut data).unwrap();
+        assert_eq!(storage_account.root_history_len(), previous_len + 5);
+
+        assert!(storage_account.reorder_root_history(0).is_err());
+        storage_account.reorder_root_history(previous_len).unwrap();
+
+        let count = previous_len + 3;
+        for i in 3..count {
+            assert_eq!(
+                storage_account.get_root_history(i % (previous_len + 5)),
+                Some([i as u8; 32])
+            );
+            assert!(storage_account.is_history_root(&[i as u8; 32], 0, count as u32));
+        }
+
+        // New roots are inserted after the retained ones
+        storage_account.push_root(&[0xff; 32]).unwrap();
+        assert_eq!(
+            storage_account.get_root_history(count % (previous_len + 5)),
+            Some([0xff; 32])
+        );
+        assert!(storage_account.is_history_root(&[3; 32], 0, u32::MAX));
+    }
 }
@@ -1,5 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use elusiv_derive::BorshSerDeSized;
 use elusiv_proc_macros::elusiv_account;
-use elusiv_types::{ChildAccount, ElusivOption, PDAAccountData};
+use elusiv_types::{BorshSerDeSized, ChildAccount, ElusivOption, PDAAccountData};
 use solana_program::pubkey::Pubkey;
 
 pub struct VKeyChildAccount;
@@ -8,16 +10,104 @@ impl ChildAccount for VKeyChildAccount {
     const INNER_SIZE: usize = 0;
 }
 
+/// The max. number of signers registered for approving [`VKeyAction`]s
+pub const MAX_VKEY_SIGNERS: usize = 5;
+
+/// The delay (in seconds) between reaching the approval threshold and executing a [`VKeyAction`]
+pub const VKEY_UPDATE_TIMELOCK: u64 = 2 * 24 * 60 * 60;
+
+/// The proof system of the verifying key stored in a [`VKeyAccount`]
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum ProofSystem {
+    /// [`crate::proof::vkey::VerifyingKey`]
+    Groth16,
+
+    /// [`crate::proof::plonk::vkey::PlonkVerifyingKey`]
+    Plonk,
+}
+
+/// An action on a [`VKeyAccount`] that requires the approval of the signers (for `threshold > 0`)
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum VKeyAction {
+    /// Executed by [`crate::instruction::ElusivInstruction::UpdateVkeyVersion`]
+    ActivateVersion,
+
+    /// Executed by [`crate::instruction::ElusivInstruction::FreezeVkey`]
+    Freeze,
+
+    /// Executed by [`crate::instruction::ElusivInstruction::ChangeVkeyAuthority`]
+    ChangeAuthority { authority: Pubkey },
+}
+
+impl BorshSerDeSized for VKeyAction {
+    const SIZE: usize = 1 + Pubkey::SIZE;
+}
+
+// All variants are padded to `VKeyAction::SIZE`, so that the serialization length is fixed
+impl BorshSerialize for VKeyAction {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let (variant, authority) = match self {
+            VKeyAction::ActivateVersion => (0u8, Pubkey::default()),
+            VKeyAction::Freeze => (1, Pubkey::default()),
+            VKeyAction::ChangeAuthority { authority } => (2, *authority),
+        };
+
+        variant.serialize(writer)?;
+        authority.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for VKeyAction {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let variant = u8::deserialize(buf)?;
+        let authority = Pubkey::deserialize(buf)?;
+
+        match variant {
+            0 => Ok(VKeyAction::ActivateVersion),
+            1 => Ok(VKeyAction::Freeze),
+            2 => Ok(VKeyAction::ChangeAuthority { authority }),
+            _ => Err(std::io::Error::from(std::io::ErrorKind::InvalidData)),
+        }
+    }
+}
+
 /// Account used for storing a single immutable [`VerifyingKey`]
-#[elusiv_account(parent_account: { child_account_count: 2, child_account: VKeyChildAccount }, eager_type: true)]
+///
+/// # Notes
+///
+/// The child-accounts store the binary data of the active (`0`), the pending (`1`) and the previous (`2`) version.
+/// The previous version remains readable until all [`crate::state::proof::VerificationAccount`]s referencing it are closed.
+#[elusiv_account(parent_account: { child_account_count: 3, child_account: VKeyChildAccount }, eager_type: true)]
 pub struct VKeyAccount {
     #[no_getter]
     #[no_setter]
     pda_data: PDAAccountData,
-    pubkeys: [ElusivOption<Pubkey>; 2],
+    pubkeys: [ElusivOption<Pubkey>; 3],
 
     pub public_inputs_count: u32,
     pub authority: ElusivOption<Pubkey>,
     pub is_frozen: bool,
     pub version: u32,
+    pub proof_system: ProofSystem,
+
+    /// The number of open verifications using the active version
+    pub active_verifications: u32,
+
+    /// The number of open verifications using the previous version
+    pub previous_verifications: u32,
+
+    /// Signers approving all [`VKeyAction`]s (an M-of-N scheme is active for `threshold > 0`)
+    pub signers: [ElusivOption<Pubkey>; MAX_VKEY_SIGNERS],
+    pub threshold: u8,
+
+    /// The action the `approvals` refer to
+    pub pending_action: VKeyAction,
+
+    /// Approvals of the `pending_action`
+    pub approvals: [bool; MAX_VKEY_SIGNERS],
+
+    /// The timestamp after which the `pending_action` can be executed
+    pub unlock_timestamp: ElusivOption<u64>,
 }
@@ -233,6 +233,28 @@ pub struct Proof {
     pub c: G1A,
 }
 
+/// A PLONK proof consisting of the affine commitments and the (non-montgomery) evaluations
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Clone, Copy)]
+#[cfg_attr(any(test, feature = "elusiv-client"), derive(Debug))]
+pub struct PlonkProof {
+    pub a: G1A,
+    pub b: G1A,
+    pub c: G1A,
+    pub z: G1A,
+    pub t1: G1A,
+    pub t2: G1A,
+    pub t3: G1A,
+    pub w_xi: G1A,
+    pub w_xiw: G1A,
+
+    pub eval_a: RawU256,
+    pub eval_b: RawU256,
+    pub eval_c: RawU256,
+    pub eval_s1: RawU256,
+    pub eval_s2: RawU256,
+    pub eval_zw: RawU256,
+}
+
 /// A Groth16 proof in affine form in binary representation (this construct is required for serde-json parsing in the Warden)
 #[cfg(feature = "elusiv-client")]
 #[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Clone, Copy, Debug)]
@@ -379,6 +401,44 @@ pub struct SendPublicInputs {
     pub hashed_inputs: U256,
 }
 
+/// Seed of the temporary account receiving the funds of a send-proof with a [`ProgramCall`] (see [`ProgramCall::temporary_account`])
+pub const PROGRAM_CALL_ACCOUNT_SEED: &[u8] = b"elusiv_program_call";
+
+/// An instruction that has to directly follow the transfer of a send-proof's funds to the recipient
+/// - used to send funds into another program (e.g. a swap with a minimum output) without an intermediate public wallet
+/// - the funds are transferred into a temporary account, that only the invoked program can spend from
+#[derive(BorshDeserialize, BorshSerialize, BorshSerDeSized, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ProgramCall {
+    pub program_id: U256,
+
+    /// SHA-256 hash of the instruction data
+    pub instruction_data_hash: U256,
+}
+
+impl ProgramCall {
+    pub fn new(program_id: &Pubkey, instruction_data: &[u8]) -> Self {
+        Self {
+            program_id: program_id.to_bytes(),
+            instruction_data_hash: solana_program::hash::hash(instruction_data).to_bytes(),
+        }
+    }
+
+    /// The temporary account receiving the funds, a PDA of the invoked program (unique for each `output_commitment`)
+    ///
+    /// # Notes
+    ///
+    /// The invoked program consumes the funds by signing with the seeds `[PROGRAM_CALL_ACCOUNT_SEED, output_commitment, bump]`.
+    pub fn temporary_account(&self, output_commitment: &U256) -> Pubkey {
+        Pubkey::find_program_address(
+            &[PROGRAM_CALL_ACCOUNT_SEED, output_commitment],
+            &Pubkey::new_from_array(self.program_id),
+        )
+        .0
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn generate_hashed_inputs(
     recipient: U256,
     identifier: U256,
@@ -387,6 +447,7 @@ pub fn generate_hashed_inputs(
     transaction_reference: U256,
     is_associated_token_account: bool,
     memo: &Option<Vec<u8>>,
+    program_call: &Option<ProgramCall>,
 ) -> U256 {
     let mut data = recipient.to_vec();
     data.extend(identifier);
@@ -399,6 +460,11 @@ pub fn generate_hashed_inputs(
         data.extend(memo);
     }
 
+    if let Some(program_call) = program_call {
+        data.extend(program_call.program_id);
+        data.extend(program_call.instruction_data_hash);
+    }
+
     let mut hash = solana_program::hash::hash(&data).to_bytes();
 
     // mask the lower 253 bits
@@ -973,9 +1039,37 @@ mod test {
                 encrypted_owner,
                 solana_pay_id,
                 is_associated_token_account,
+                &None,
                 &None
             ),
             expected
         );
     }
+
+    #[test]
+    fn test_generate_hashed_inputs_program_call() {
+        let program_call = ProgramCall::new(&Pubkey::new_unique(), &[1, 2, 3]);
+        let hashed_inputs = |program_call| {
+            generate_hashed_inputs(
+                [1; 32],
+                [2; 32],
+                [3; 32],
+                [4; 32],
+                [0; 32],
+                false,
+                &None,
+                &program_call,
+            )
+        };
+
+        assert_ne!(hashed_inputs(Some(program_call)), hashed_inputs(None));
+        assert_ne!(
+            hashed_inputs(Some(program_call)),
+            hashed_inputs(Some(ProgramCall {
+                instruction_data_hash: [0; 32],
+                ..program_call
+            }))
+        );
+        assert_eq!(hashed_inputs(Some(program_call))[31] & !0b11111, 0);
+    }
 }
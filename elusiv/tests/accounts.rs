@@ -5,20 +5,32 @@ mod common;
 use borsh::BorshSerialize;
 use common::*;
 use elusiv::instruction::*;
-use elusiv::processor::CommitmentHashRequest;
-use elusiv::state::commitment::{BaseCommitmentBufferAccount, CommitmentHashingAccount};
+use elusiv::processor::{CommitmentHashRequest, MigratableAccount};
+use elusiv::state::commitment::{
+    hashing_account_pda_offset, BaseCommitmentBufferAccount, CommitmentHashingAccount,
+};
 use elusiv::state::program_account::PDAOffset;
-use elusiv::state::queue::{CommitmentQueue, Queue, RingQueue};
+use elusiv::state::queue::{
+    CommitmentQueue, Queue, RingQueue, COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT,
+};
 use elusiv::state::{
-    fee::FeeAccount,
-    governor::{FeeCollectorAccount, GovernorAccount, PoolAccount},
+    fee::{FeeAccount, ProgramFee},
+    governor::{
+        FeeCollectorAccount, GovernanceProposal, GovernanceProposalsAccount, GovernorAccount,
+        PoolAccount, PoolBalance, PoolLedgerAccount, TokenVolume, TokenVolumeAccount,
+    },
     nullifier::{NullifierAccount, NullifierChildAccount},
-    program_account::{PDAAccount, PDAAccountData, ProgramAccount, SizedAccount},
+    program_account::{
+        PDAAccount, PDAAccountData, ParentAccount, ProgramAccount, ReallocAccount, SizedAccount,
+    },
     queue::CommitmentQueueAccount,
-    storage::{StorageAccount, MT_COMMITMENT_COUNT},
+    storage::{
+        shard_mt_index, StorageAccount, HISTORY_ARRAY_SIZE, MAX_HISTORY_ARRAY_SIZE,
+        MT_COMMITMENT_COUNT,
+    },
 };
-use elusiv::token::SPL_TOKEN_COUNT;
-use elusiv_types::split_child_account_data_mut;
+use elusiv::token::{SPL_TOKEN_COUNT, USDC_TOKEN_ID};
+use elusiv_types::{split_child_account_data_mut, BorshSerDeSized, ElusivOption, Lamports};
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program_test::*;
 use solana_sdk::signer::Signer;
@@ -43,10 +55,12 @@ async fn test_setup_initial_accounts() {
         // Check pda account fields
         let data = PDAAccountData::new(&data).unwrap();
         assert_eq!(data.bump_seed, T::find(pda_offset).1);
-        assert_eq!(data.version, 0);
+        assert_eq!(data.version, elusiv::PROGRAM_VERSION);
     }
 
     assert_account::<GovernorAccount>(&mut test, None).await;
+    assert_account::<TokenVolumeAccount>(&mut test, None).await;
+    assert_account::<PoolLedgerAccount>(&mut test, None).await;
     assert_account::<PoolAccount>(&mut test, None).await;
     assert_account::<FeeCollectorAccount>(&mut test, None).await;
 
@@ -72,6 +86,96 @@ async fn test_setup_initial_accounts_duplicate() {
     test.tx_should_fail_simple(&ixs).await;
 }
 
+#[tokio::test]
+async fn test_setup_governor_account_upgrade_authority() {
+    let mut test = start_test().await;
+
+    // Only the upgrade authority can setup the governor
+    let actor = test.new_actor().await;
+    test.ix_should_fail(
+        ElusivInstruction::setup_governor_account_instruction(WritableSignerAccount(actor.pubkey)),
+        &[&actor.keypair],
+    )
+    .await;
+
+    // Immutable program
+    set_upgrade_authority(&mut test, None).await;
+    test.ix_should_fail_simple(ElusivInstruction::setup_governor_account_instruction(
+        WritableSignerAccount(test.payer()),
+    ))
+    .await;
+
+    set_upgrade_authority(&mut test, Some(actor.pubkey)).await;
+    test.ix_should_succeed(
+        ElusivInstruction::setup_governor_account_instruction(WritableSignerAccount(actor.pubkey)),
+        &[&actor.keypair],
+    )
+    .await;
+
+    pda_account!(governor, GovernorAccount, None, None, test);
+    assert_eq!(governor.get_authority().option(), Some(actor.pubkey));
+}
+
+#[tokio::test]
+async fn test_open_token_volume_account() {
+    let mut test = start_test().await;
+    let payer = test.payer();
+    test.tx_should_succeed_simple(&[
+        ElusivInstruction::setup_governor_account_instruction(WritableSignerAccount(payer)),
+        ElusivInstruction::open_single_instance_accounts_instruction(WritableSignerAccount(payer)),
+    ])
+    .await;
+
+    // Only the governance authority can open the account
+    let actor = test.new_actor().await;
+    test.ix_should_fail(
+        ElusivInstruction::open_token_volume_account_instruction(WritableSignerAccount(
+            actor.pubkey,
+        )),
+        &[&actor.keypair],
+    )
+    .await;
+
+    let ix = ElusivInstruction::open_token_volume_account_instruction(WritableSignerAccount(payer));
+    test.ix_should_succeed_simple(ix.clone()).await;
+
+    pda_account!(token_volume, TokenVolumeAccount, None, None, test);
+    assert_eq!(token_volume.get_volumes(0), TokenVolume::default());
+
+    // Opened only once
+    test.ix_should_fail_simple(ix).await;
+}
+
+#[tokio::test]
+async fn test_open_pool_ledger_account() {
+    let mut test = start_test().await;
+    let payer = test.payer();
+    test.tx_should_succeed_simple(&[
+        ElusivInstruction::setup_governor_account_instruction(WritableSignerAccount(payer)),
+        ElusivInstruction::open_single_instance_accounts_instruction(WritableSignerAccount(payer)),
+    ])
+    .await;
+
+    // Only the governance authority can open the account
+    let actor = test.new_actor().await;
+    test.ix_should_fail(
+        ElusivInstruction::open_pool_ledger_account_instruction(WritableSignerAccount(
+            actor.pubkey,
+        )),
+        &[&actor.keypair],
+    )
+    .await;
+
+    let ix = ElusivInstruction::open_pool_ledger_account_instruction(WritableSignerAccount(payer));
+    test.ix_should_succeed_simple(ix.clone()).await;
+
+    pda_account!(pool_ledger, PoolLedgerAccount, None, None, test);
+    assert_eq!(pool_ledger.get_balances(0), PoolBalance::default());
+
+    // Opened only once
+    test.ix_should_fail_simple(ix).await;
+}
+
 #[tokio::test]
 async fn test_enable_token_account() {
     let mut test = start_test().await;
@@ -95,12 +199,12 @@ async fn test_setup_fee_account() {
     .await;
 
     let genesis_fee = genesis_fee(&mut test).await;
-    setup_fee(&mut test, 0, genesis_fee.clone()).await;
+    setup_fee(&mut test, 0, genesis_fee).await;
 
     // Second time will fail
     test.ix_should_fail_simple(ElusivInstruction::init_new_fee_version_instruction(
         0,
-        genesis_fee.clone(),
+        genesis_fee,
         WritableSignerAccount(payer),
     ))
     .await;
@@ -114,7 +218,7 @@ async fn test_setup_fee_account() {
     // Attempting to set a version higher than genesis (0) will fail
     test.ix_should_fail_simple(ElusivInstruction::init_new_fee_version_instruction(
         1,
-        genesis_fee.clone(),
+        genesis_fee,
         WritableSignerAccount(payer),
     ))
     .await;
@@ -134,6 +238,464 @@ async fn test_setup_fee_account() {
     .await;
 }
 
+#[tokio::test]
+async fn test_governance_proposal() {
+    let mut test = start_test_with_setup().await;
+    let genesis_fee = genesis_fee(&mut test).await;
+    let proposal = GovernanceProposal::NewFeeVersion {
+        fee_version: 1,
+        program_fee: genesis_fee,
+    };
+
+    // Only the governance authority can propose changes
+    let actor = test.new_actor().await;
+    test.ix_should_fail(
+        ElusivInstruction::propose_governance_change_instruction(
+            proposal,
+            SignerAccount(actor.pubkey),
+        ),
+        &[&actor.keypair],
+    )
+    .await;
+
+    test.ix_should_succeed_simple(ElusivInstruction::propose_governance_change_instruction(
+        proposal,
+        SignerAccount(test.payer()),
+    ))
+    .await;
+
+    // Timelocked
+    test.ix_should_fail_simple(ElusivInstruction::init_new_fee_version_instruction(
+        1,
+        genesis_fee,
+        WritableSignerAccount(test.payer()),
+    ))
+    .await;
+
+    test.set_pda_account::<GovernanceProposalsAccount, _>(&elusiv::id(), None, None, |data| {
+        let mut account = GovernanceProposalsAccount::new(data).unwrap();
+        let mut pending = account.get_proposals(0).option().unwrap();
+        pending.unlock_timestamp = ElusivOption::Some(0);
+        account.set_proposals(0, &ElusivOption::Some(pending));
+    })
+    .await;
+
+    // Mismatching fee
+    let mut fee = genesis_fee;
+    fee.proof_subvention = Lamports(0);
+    test.ix_should_fail_simple(ElusivInstruction::init_new_fee_version_instruction(
+        1,
+        fee,
+        WritableSignerAccount(test.payer()),
+    ))
+    .await;
+
+    test.ix_should_succeed_simple(ElusivInstruction::init_new_fee_version_instruction(
+        1,
+        genesis_fee,
+        WritableSignerAccount(test.payer()),
+    ))
+    .await;
+
+    pda_account!(governor, GovernorAccount, None, None, test);
+    assert_eq!(governor.get_fee_version(), 1);
+
+    pda_account!(proposals, GovernanceProposalsAccount, None, None, test);
+    assert!(proposals.get_proposals(0).option().is_none());
+}
+
+#[tokio::test]
+async fn test_set_token_volume_limits() {
+    let mut test = start_test_with_setup().await;
+    let proposal = GovernanceProposal::TokenVolumeLimits {
+        token_id: USDC_TOKEN_ID,
+        deposit_limit: ElusivOption::Some(1_000_000),
+        withdrawal_limit: ElusivOption::None,
+    };
+
+    test.ix_should_succeed_simple(ElusivInstruction::propose_governance_change_instruction(
+        proposal,
+        SignerAccount(test.payer()),
+    ))
+    .await;
+
+    let ix = ElusivInstruction::set_token_volume_limits_instruction(
+        USDC_TOKEN_ID,
+        ElusivOption::Some(1_000_000),
+        ElusivOption::None,
+    );
+
+    // Timelocked
+    test.ix_should_fail_simple(ix.clone()).await;
+
+    test.set_pda_account::<GovernanceProposalsAccount, _>(&elusiv::id(), None, None, |data| {
+        let mut account = GovernanceProposalsAccount::new(data).unwrap();
+        let mut pending = account.get_proposals(0).option().unwrap();
+        pending.unlock_timestamp = ElusivOption::Some(0);
+        account.set_proposals(0, &ElusivOption::Some(pending));
+    })
+    .await;
+
+    // Mismatching limits
+    test.ix_should_fail_simple(ElusivInstruction::set_token_volume_limits_instruction(
+        USDC_TOKEN_ID,
+        ElusivOption::Some(1_000_001),
+        ElusivOption::None,
+    ))
+    .await;
+
+    test.ix_should_succeed_simple(ix.clone()).await;
+
+    pda_account!(token_volume, TokenVolumeAccount, None, None, test);
+    let volume = token_volume.get_volumes(USDC_TOKEN_ID as usize);
+    assert_eq!(volume.deposit_limit.option(), Some(1_000_000));
+    assert_eq!(volume.withdrawal_limit.option(), None);
+
+    // The proposal can only be executed once
+    test.ix_should_fail_simple(ix).await;
+}
+
+#[tokio::test]
+async fn test_delist_token() {
+    let mut test = start_test_with_setup().await;
+    let payer = test.payer();
+    let invalid_signer = test.new_actor().await;
+
+    // Only the authority or a registered signer can delist
+    test.ix_should_fail(
+        ElusivInstruction::delist_token_instruction(
+            USDC_TOKEN_ID,
+            SignerAccount(invalid_signer.pubkey),
+        ),
+        &[&invalid_signer.keypair],
+    )
+    .await;
+
+    test.ix_should_succeed_simple(ElusivInstruction::delist_token_instruction(
+        USDC_TOKEN_ID,
+        SignerAccount(payer),
+    ))
+    .await;
+
+    pda_account!(token_volume, TokenVolumeAccount, None, None, test);
+    assert!(token_volume.get_delisted(USDC_TOKEN_ID as usize));
+
+    // Relisting is timelocked
+    test.ix_should_succeed_simple(ElusivInstruction::propose_governance_change_instruction(
+        GovernanceProposal::RelistToken {
+            token_id: USDC_TOKEN_ID,
+        },
+        SignerAccount(payer),
+    ))
+    .await;
+
+    let ix = ElusivInstruction::relist_token_instruction(USDC_TOKEN_ID);
+    test.ix_should_fail_simple(ix.clone()).await;
+
+    test.set_pda_account::<GovernanceProposalsAccount, _>(&elusiv::id(), None, None, |data| {
+        let mut account = GovernanceProposalsAccount::new(data).unwrap();
+        let mut pending = account.get_proposals(0).option().unwrap();
+        pending.unlock_timestamp = ElusivOption::Some(0);
+        account.set_proposals(0, &ElusivOption::Some(pending));
+    })
+    .await;
+
+    test.ix_should_succeed_simple(ix).await;
+
+    pda_account!(token_volume, TokenVolumeAccount, None, None, test);
+    assert!(!token_volume.get_delisted(USDC_TOKEN_ID as usize));
+}
+
+#[tokio::test]
+async fn test_migrate_account() {
+    let mut test = start_test_with_setup().await;
+    let payer = test.payer();
+    let governor_address = GovernorAccount::find(None).0;
+
+    // Accounts are created with the current version
+    test.ix_should_fail_simple(ElusivInstruction::migrate_account_instruction(
+        MigratableAccount::Governor,
+        WritableSignerAccount(payer),
+        WritableUserAccount(governor_address),
+    ))
+    .await;
+
+    pda_account!(governor, GovernorAccount, None, None, test);
+    assert_eq!(
+        governor.get_program_version(),
+        u32::from(elusiv::PROGRAM_VERSION)
+    );
+
+    // Instructions are rejected until all accounts are migrated
+    let proposal = GovernanceProposal::GovernorState {
+        fee_version: 0,
+        batching_rate: 0,
+    };
+    test.set_pda_account::<GovernorAccount, _>(&elusiv::id(), None, None, |data| {
+        let mut governor = GovernorAccount::new(data).unwrap();
+        governor.set_program_version(&0);
+    })
+    .await;
+
+    test.ix_should_fail_simple(ElusivInstruction::propose_governance_change_instruction(
+        proposal,
+        SignerAccount(payer),
+    ))
+    .await;
+
+    // Accounts of the previous version are grown and migrated
+    let hashing_account = CommitmentHashingAccount::find(hashing_account_pda_offset(0, 0)).0;
+    let mut data = test.data(&hashing_account).await;
+    data.truncate(CommitmentHashingAccount::SIZE - 4);
+    data[1] = 0;
+    test.set_program_account_rent_exempt(&elusiv::id(), &hashing_account, &data)
+        .await;
+
+    test.ix_should_succeed_simple(ElusivInstruction::migrate_account_instruction(
+        MigratableAccount::CommitmentHashing {
+            shard: 0,
+            hashing_account_index: 0,
+        },
+        WritableSignerAccount(payer),
+        WritableUserAccount(hashing_account),
+    ))
+    .await;
+
+    let data = test.data(&hashing_account).await;
+    assert_eq!(data.len(), CommitmentHashingAccount::SIZE);
+    assert_eq!(data[1], elusiv::PROGRAM_VERSION);
+    assert!(test.rent(data.len()).await.0 <= test.lamports(&hashing_account).await.0);
+
+    // The governor is migrated last
+    let governor_v0_size = PDAAccountData::SIZE + 4 + ProgramFee::SIZE + 4 + 4;
+    let mut data = test.data(&governor_address).await;
+    data.truncate(governor_v0_size);
+    data[1] = 0;
+    test.set_program_account_rent_exempt(&elusiv::id(), &governor_address, &data)
+        .await;
+
+    test.ix_should_succeed_simple(ElusivInstruction::migrate_account_instruction(
+        MigratableAccount::Governor,
+        WritableSignerAccount(payer),
+        WritableUserAccount(governor_address),
+    ))
+    .await;
+
+    assert_eq!(
+        test.data(&governor_address).await.len(),
+        GovernorAccount::SIZE
+    );
+    pda_account!(governor, GovernorAccount, None, None, test);
+    assert_eq!(governor.get_authority().option(), Some(payer));
+    assert_eq!(governor.get_commitment_shards_count(), 1);
+    assert_eq!(
+        governor.get_program_version(),
+        u32::from(elusiv::PROGRAM_VERSION)
+    );
+
+    // Accounts that are left behind are rejected
+    test.set_pda_account::<GovernanceProposalsAccount, _>(&elusiv::id(), None, None, |data| {
+        data[1] = elusiv::PROGRAM_VERSION - 1;
+    })
+    .await;
+
+    test.ix_should_fail_simple(ElusivInstruction::propose_governance_change_instruction(
+        proposal,
+        SignerAccount(payer),
+    ))
+    .await;
+
+    test.ix_should_succeed_simple(ElusivInstruction::migrate_account_instruction(
+        MigratableAccount::GovernanceProposals,
+        WritableSignerAccount(payer),
+        WritableUserAccount(GovernanceProposalsAccount::find(None).0),
+    ))
+    .await;
+
+    test.ix_should_succeed_simple(ElusivInstruction::propose_governance_change_instruction(
+        proposal,
+        SignerAccount(payer),
+    ))
+    .await;
+}
+
+#[tokio::test]
+async fn test_grow_root_history() {
+    let mut test = start_test_with_setup().await;
+    let payer = test.payer();
+    let len = HISTORY_ARRAY_SIZE as u32 + 100;
+
+    // Invalid authority
+    let actor = test.new_actor().await;
+    test.ix_should_fail(
+        ElusivInstruction::grow_root_history_instruction(len, WritableSignerAccount(actor.pubkey)),
+        &[&actor.keypair],
+    )
+    .await;
+
+    // Invalid len
+    for len in [HISTORY_ARRAY_SIZE as u32, MAX_HISTORY_ARRAY_SIZE as u32 + 1] {
+        test.ix_should_fail_simple(ElusivInstruction::grow_root_history_instruction(
+            len,
+            WritableSignerAccount(payer),
+        ))
+        .await;
+    }
+
+    test.ix_should_succeed_simple(ElusivInstruction::grow_root_history_instruction(
+        len,
+        WritableSignerAccount(payer),
+    ))
+    .await;
+
+    let data = test.data(&StorageAccount::find(None).0).await;
+    assert_eq!(data.len(), StorageAccount::size_with_len(len as usize));
+}
+
+#[tokio::test]
+async fn test_open_commitment_shard_accounts() {
+    let mut test = start_test_with_setup().await;
+    let payer = test.payer();
+
+    // Invalid authority
+    let actor = test.new_actor().await;
+    test.ix_should_fail(
+        ElusivInstruction::open_commitment_shard_accounts_instruction(
+            1,
+            WritableSignerAccount(actor.pubkey),
+        ),
+        &[&actor.keypair],
+    )
+    .await;
+
+    // Shards have to be opened in order
+    test.ix_should_fail_simple(
+        ElusivInstruction::open_commitment_shard_accounts_instruction(
+            2,
+            WritableSignerAccount(payer),
+        ),
+    )
+    .await;
+
+    test.ix_should_succeed_simple(
+        ElusivInstruction::open_commitment_shard_accounts_instruction(
+            1,
+            WritableSignerAccount(payer),
+        ),
+    )
+    .await;
+
+    pda_account!(governor, GovernorAccount, None, None, test);
+    assert_eq!(governor.get_commitment_shards_count(), 2);
+
+    let mut data = test.data(&StorageAccount::find(Some(1)).0).await;
+    let storage_account = <StorageAccount as ProgramAccount>::new(&mut data).unwrap();
+    assert_eq!(storage_account.get_shard(), 1);
+    assert_eq!(
+        storage_account.active_mt_index().unwrap(),
+        shard_mt_index(1, 0).unwrap()
+    );
+
+    // Reopening a shard fails
+    test.ix_should_fail_simple(
+        ElusivInstruction::open_commitment_shard_accounts_instruction(
+            1,
+            WritableSignerAccount(payer),
+        ),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_open_commitment_hashing_account() {
+    let mut test = start_test_with_setup().await;
+    let payer = test.payer();
+
+    // Invalid authority
+    let actor = test.new_actor().await;
+    test.ix_should_fail(
+        ElusivInstruction::open_commitment_hashing_account_instruction(
+            0,
+            1,
+            WritableSignerAccount(actor.pubkey),
+        ),
+        &[&actor.keypair],
+    )
+    .await;
+
+    // Hashing accounts have to be opened in order
+    test.ix_should_fail_simple(
+        ElusivInstruction::open_commitment_hashing_account_instruction(
+            0,
+            2,
+            WritableSignerAccount(payer),
+        ),
+    )
+    .await;
+
+    test.ix_should_succeed_simple(
+        ElusivInstruction::open_commitment_hashing_account_instruction(
+            0,
+            1,
+            WritableSignerAccount(payer),
+        ),
+    )
+    .await;
+
+    assert_eq!(
+        test.data(&CommitmentHashingAccount::find(hashing_account_pda_offset(0, 1)).0)
+            .await
+            .len(),
+        CommitmentHashingAccount::SIZE
+    );
+
+    let mut data = test.data(&StorageAccount::find(None).0).await;
+    let storage_account = <StorageAccount as ProgramAccount>::new(&mut data).unwrap();
+    assert_eq!(storage_account.get_hashing_accounts_count(), 2);
+    assert_eq!(storage_account.get_next_hashing_account_index(), 1);
+}
+
+#[tokio::test]
+async fn test_enable_commitment_queue_child_account() {
+    let mut test = start_test_with_setup().await;
+    let child_accounts = test
+        .create_parent_account::<CommitmentQueueAccount>(&elusiv::id())
+        .await;
+
+    // Child-accounts have to be enabled in order
+    test.ix_should_fail_simple(
+        ElusivInstruction::enable_commitment_queue_child_account_instruction(
+            0,
+            1,
+            &[],
+            WritableUserAccount(child_accounts[1]),
+        ),
+    )
+    .await;
+
+    for (i, &child_account) in child_accounts.iter().enumerate() {
+        test.ix_should_succeed_simple(
+            ElusivInstruction::enable_commitment_queue_child_account_instruction(
+                0,
+                i as u32,
+                &writable_user_accounts(&child_accounts[..i]),
+                WritableUserAccount(child_account),
+            ),
+        )
+        .await;
+    }
+
+    let mut data = test.data(&CommitmentQueueAccount::find(None).0).await;
+    let queue = <CommitmentQueueAccount as ProgramAccount>::new(&mut data).unwrap();
+    assert_eq!(
+        queue.get_overflow_accounts_count(),
+        COMMITMENT_QUEUE_OVERFLOW_ACCOUNTS_COUNT as u32
+    );
+    for (i, &child_account) in child_accounts.iter().enumerate() {
+        assert_eq!(queue.get_child_pubkey(i), Some(child_account));
+    }
+}
+
 #[tokio::test]
 async fn test_setup_pda_accounts_invalid_pda() {
     let mut test = start_test().await;
@@ -149,6 +711,7 @@ async fn test_setup_pda_accounts_invalid_pda() {
 #[tokio::test]
 async fn test_open_new_merkle_tree() {
     let mut test = start_test().await;
+    setup_initial_pdas(&mut test).await;
 
     // Multiple MTs can be opened
     for mt_index in 0..3 {
@@ -160,6 +723,7 @@ async fn test_open_new_merkle_tree() {
 #[tokio::test]
 async fn test_open_new_merkle_tree_duplicate() {
     let mut test = start_test().await;
+    setup_initial_pdas(&mut test).await;
     create_merkle_tree(&mut test, 0).await;
 
     // Cannot init MT twice
@@ -200,6 +764,7 @@ async fn test_reset_active_mt() {
     test.ix_should_fail_simple(ElusivInstruction::reset_active_merkle_tree_instruction(
         0,
         &storage_accounts,
+        &[],
     ))
     .await;
 
@@ -230,6 +795,7 @@ async fn test_reset_active_mt() {
             AccountMeta::new(StorageAccount::find(None).0, false),
             AccountMeta::new(CommitmentQueueAccount::find(None).0, false),
             AccountMeta::new(NullifierAccount::find(Some(1)).0, false),
+            AccountMeta::new_readonly(GovernorAccount::find(None).0, false),
         ],
     ))
     .await;
@@ -245,6 +811,7 @@ async fn test_reset_active_mt() {
             AccountMeta::new(root_storage_account, false),
             AccountMeta::new(CommitmentQueueAccount::find(None).0, false),
             AccountMeta::new(NullifierAccount::find(Some(0)).0, false),
+            AccountMeta::new_readonly(GovernorAccount::find(None).0, false),
         ],
     ))
     .await;
@@ -293,12 +860,14 @@ async fn test_reset_active_mt() {
     test.ix_should_fail_simple(ElusivInstruction::reset_active_merkle_tree_instruction(
         1,
         &[],
+        &[],
     ))
     .await;
 
     test.ix_should_succeed_simple(ElusivInstruction::reset_active_merkle_tree_instruction(
         1,
         &storage_accounts,
+        &[],
     ))
     .await;
 }
@@ -367,6 +936,7 @@ async fn test_global_child_account_duplicates() {
 
     // Setting in storage-account
     test.ix_should_fail_simple(ElusivInstruction::enable_storage_child_account_instruction(
+        0,
         0,
         WritableUserAccount(account.pubkey()),
     ))
@@ -16,8 +16,12 @@ use elusiv::{
     },
     processor::{program_token_account_address, BaseCommitmentHashRequest, CommitmentHashRequest},
     state::{
+        apa::{apa_accounts_data, apa_target_map_account, ApaLevel, WARDEN_NETWORK_PROGRAM_ID},
         commitment::{BaseCommitmentHashingAccount, CommitmentHashingAccount},
-        governor::{FeeCollectorAccount, GovernorAccount, PoolAccount},
+        governor::{
+            FeeCollectorAccount, GovernorAccount, PoolAccount, PoolLedgerAccount, TokenVolume,
+            TokenVolumeAccount,
+        },
         program_account::{PDAAccount, ProgramAccount, SizedAccount},
         queue::{CommitmentQueue, CommitmentQueueAccount, Queue, RingQueue},
         storage::{StorageAccount, EMPTY_TREE, MT_HEIGHT},
@@ -26,9 +30,14 @@ use elusiv::{
     types::{RawU256, U256},
 };
 use elusiv_computation::PartialComputation;
-use elusiv_types::tokens::Price;
-use solana_program::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, system_program};
+use elusiv_types::{tokens::Price, ElusivOption};
+use solana_program::{
+    native_token::LAMPORTS_PER_SOL, program_pack::Pack, pubkey::Pubkey, system_program,
+};
 use solana_program_test::*;
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
 
 #[tokio::test]
 async fn test_store_base_commitment_lamports_transfer() {
@@ -92,6 +101,8 @@ async fn test_store_base_commitment_lamports_transfer() {
             UserAccount(sol_price_account),
             WritableUserAccount(hashing_account_pubkey),
             UserAccount(system_program::id()),
+            UserAccount(apa_target_map_account(&client.pubkey)),
+            UserAccount(system_program::id()),
         ),
         &[&client.keypair, &warden.keypair],
     )
@@ -118,6 +129,321 @@ async fn test_store_base_commitment_lamports_transfer() {
     assert_eq!(computation_fee, warden.lamports(&mut test).await);
 }
 
+#[tokio::test]
+async fn test_store_base_commitment_wrapped_lamports_transfer() {
+    let mut test = start_test_with_setup().await;
+    let client = test.new_actor().await;
+    let warden = test.new_actor().await;
+
+    let pool = PoolAccount::find(None).0;
+    let fee_collector = FeeCollectorAccount::find(None).0;
+
+    let request = base_commitment_request(
+        "8337064132573119120838379738103457054645361649757131991036638108422638197362",
+        "139214303935475888711984321184227760578793579443975701453971046059378311483",
+        123,
+        1_000_000_000,
+        LAMPORTS_TOKEN_ID,
+        0,
+        0,
+    );
+
+    let fee = genesis_fee(&mut test).await;
+    let subvention = fee.base_commitment_subvention.0;
+    let computation_fee = (fee.base_commitment_hash_computation_fee()
+        + fee.commitment_hash_computation_fee(request.min_batching_rate))
+    .unwrap()
+    .0;
+    let network_fee = fee.base_commitment_network_fee.calc(request.amount);
+    let hashing_account_rent = test.rent(BaseCommitmentHashingAccount::SIZE).await;
+    let token_account_rent = test.rent(spl_token::state::Account::LEN).await;
+
+    // `client` wraps the lamports in its associated token account
+    let wrapped_sol_account =
+        get_associated_token_address(&client.pubkey, &spl_token::native_mint::ID);
+    let payer = test.payer();
+    test.ix_should_succeed_simple(create_associated_token_account(
+        &payer,
+        &client.pubkey,
+        &spl_token::native_mint::ID,
+        &spl_token::id(),
+    ))
+    .await;
+    test.airdrop_lamports(
+        &wrapped_sol_account,
+        request.amount + computation_fee + network_fee - subvention,
+    )
+    .await;
+    test.ix_should_succeed_simple(
+        spl_token::instruction::sync_native(&spl_token::id(), &wrapped_sol_account).unwrap(),
+    )
+    .await;
+
+    warden
+        .airdrop(0, computation_fee + hashing_account_rent.0, &mut test)
+        .await;
+    test.airdrop(
+        &FeeCollectorAccount::find(None).0,
+        Lamports(subvention).into_token_strict(),
+    )
+    .await;
+
+    test.ix_should_succeed(
+        ElusivInstruction::store_base_commitment_wrapped_sol_instruction(
+            0,
+            request.clone(),
+            client.pubkey,
+            warden.pubkey,
+        ),
+        &[&client.keypair, &warden.keypair],
+    )
+    .await;
+
+    // The wrapped-SOL account is closed and only its rent remains with the client
+    assert!(test.account_does_not_exist(&wrapped_sol_account).await);
+    assert_eq!(token_account_rent.0, client.lamports(&mut test).await);
+
+    assert_eq!(
+        network_fee,
+        test.pda_lamports(&fee_collector, FeeCollectorAccount::SIZE)
+            .await
+            .0
+    );
+    assert_eq!(
+        request.amount + computation_fee,
+        test.pda_lamports(&pool, PoolAccount::SIZE).await.0
+    );
+    assert_eq!(computation_fee, warden.lamports(&mut test).await);
+}
+
+#[tokio::test]
+async fn test_store_base_commitment_deposit_limit() {
+    let mut test = start_test_with_setup().await;
+    let client = test.new_actor().await;
+    let warden = test.new_actor().await;
+
+    let request = base_commitment_request(
+        "8337064132573119120838379738103457054645361649757131991036638108422638197362",
+        "139214303935475888711984321184227760578793579443975701453971046059378311483",
+        123,
+        1_000_000_000,
+        LAMPORTS_TOKEN_ID,
+        0,
+        0,
+    );
+
+    client.airdrop(0, LAMPORTS_PER_SOL * 2, &mut test).await;
+    warden.airdrop(0, LAMPORTS_PER_SOL, &mut test).await;
+    test.airdrop_lamports(&FeeCollectorAccount::find(None).0, LAMPORTS_PER_SOL)
+        .await;
+
+    let set_deposit_limit = |limit: u64| {
+        move |data: &mut [u8]| {
+            let mut account = TokenVolumeAccount::new(data).unwrap();
+            account.set_volumes(
+                LAMPORTS_TOKEN_ID as usize,
+                &TokenVolume {
+                    deposit_limit: ElusivOption::Some(limit),
+                    ..Default::default()
+                },
+            );
+        }
+    };
+
+    let ix = ElusivInstruction::store_base_commitment_sol_instruction(
+        0,
+        request.clone(),
+        client.pubkey,
+        warden.pubkey,
+    );
+
+    test.set_pda_account::<TokenVolumeAccount, _>(
+        &elusiv::id(),
+        None,
+        None,
+        set_deposit_limit(request.amount - 1),
+    )
+    .await;
+    test.ix_should_fail(ix.clone(), &[&client.keypair, &warden.keypair])
+        .await;
+
+    test.set_pda_account::<TokenVolumeAccount, _>(
+        &elusiv::id(),
+        None,
+        None,
+        set_deposit_limit(request.amount),
+    )
+    .await;
+    test.ix_should_succeed(ix, &[&client.keypair, &warden.keypair])
+        .await;
+
+    pda_account!(token_volume, TokenVolumeAccount, None, None, test);
+    assert_eq!(
+        token_volume
+            .get_volumes(LAMPORTS_TOKEN_ID as usize)
+            .deposit_volume,
+        request.amount
+    );
+}
+
+#[tokio::test]
+async fn test_store_base_commitment_apa_restrictions() {
+    let mut test = start_test_with_setup().await;
+    let client = test.new_actor().await;
+    let warden = test.new_actor().await;
+
+    let request = base_commitment_request(
+        "8337064132573119120838379738103457054645361649757131991036638108422638197362",
+        "139214303935475888711984321184227760578793579443975701453971046059378311483",
+        123,
+        1_000_000_000,
+        LAMPORTS_TOKEN_ID,
+        0,
+        0,
+    );
+
+    client.airdrop(0, LAMPORTS_PER_SOL * 2, &mut test).await;
+    warden.airdrop(0, LAMPORTS_PER_SOL, &mut test).await;
+    test.airdrop_lamports(&FeeCollectorAccount::find(None).0, LAMPORTS_PER_SOL)
+        .await;
+
+    let (hashing_account_pubkey, hashing_account_bump) =
+        BaseCommitmentHashingAccount::find(Some(0));
+    let sol_price_account = test.token_to_usd_price_pyth_account(0);
+    let ix = |proposal: Pubkey| {
+        ElusivInstruction::store_base_commitment_instruction(
+            0,
+            hashing_account_bump,
+            request.clone(),
+            SignerAccount(client.pubkey),
+            WritableUserAccount(client.pubkey),
+            WritableSignerAccount(warden.pubkey),
+            WritableUserAccount(warden.pubkey),
+            WritableUserAccount(PoolAccount::find(None).0),
+            WritableUserAccount(FeeCollectorAccount::find(None).0),
+            UserAccount(sol_price_account),
+            UserAccount(sol_price_account),
+            WritableUserAccount(hashing_account_pubkey),
+            UserAccount(system_program::id()),
+            UserAccount(apa_target_map_account(&client.pubkey)),
+            UserAccount(proposal),
+        )
+    };
+
+    // Client is flagged for USDC
+    let ((map_pubkey, map_data), (proposal_pubkey, proposal_data)) =
+        apa_accounts_data(&client.pubkey, 0, ApaLevel::Flag1, Some(USDC_TOKEN_ID));
+    test.set_account_rent_exempt(&map_pubkey, &map_data, &WARDEN_NETWORK_PROGRAM_ID)
+        .await;
+    test.set_account_rent_exempt(&proposal_pubkey, &proposal_data, &WARDEN_NETWORK_PROGRAM_ID)
+        .await;
+
+    // Missing proposal account
+    test.ix_should_fail(
+        ix(system_program::id()),
+        &[&client.keypair, &warden.keypair],
+    )
+    .await;
+
+    test.ix_should_fail(ix(proposal_pubkey), &[&client.keypair, &warden.keypair])
+        .await;
+
+    // Client is flagged for lamports
+    let (_, (_, proposal_data)) =
+        apa_accounts_data(&client.pubkey, 0, ApaLevel::Flag1, Some(LAMPORTS_TOKEN_ID));
+    test.set_account_rent_exempt(&proposal_pubkey, &proposal_data, &WARDEN_NETWORK_PROGRAM_ID)
+        .await;
+
+    test.ix_should_succeed(ix(proposal_pubkey), &[&client.keypair, &warden.keypair])
+        .await;
+}
+
+#[tokio::test]
+async fn test_store_base_commitment_lamports_transfer_cpi() {
+    let mut test = start_test_with_setup().await;
+    let warden = test.new_actor().await;
+
+    // The sender is a PDA of the invoking program
+    let seed = b"payroll";
+    let sender = mock_program::cpi_signer(&MOCK_PROGRAM_ID, seed).0;
+    let pool = PoolAccount::find(None).0;
+    let fee_collector = FeeCollectorAccount::find(None).0;
+
+    let request = base_commitment_request(
+        "8337064132573119120838379738103457054645361649757131991036638108422638197362",
+        "139214303935475888711984321184227760578793579443975701453971046059378311483",
+        123,
+        1_000_000_000,
+        LAMPORTS_TOKEN_ID,
+        0,
+        0,
+    );
+
+    let fee = genesis_fee(&mut test).await;
+    let subvention = fee.base_commitment_subvention.0;
+    let computation_fee = (fee.base_commitment_hash_computation_fee()
+        + fee.commitment_hash_computation_fee(request.min_batching_rate))
+    .unwrap()
+    .0;
+    let network_fee = fee.base_commitment_network_fee.calc(request.amount);
+    let hashing_account_rent = test.rent(BaseCommitmentHashingAccount::SIZE).await;
+
+    test.airdrop_lamports(
+        &sender,
+        request.amount + computation_fee + network_fee - subvention,
+    )
+    .await;
+    warden
+        .airdrop(0, computation_fee + hashing_account_rent.0, &mut test)
+        .await;
+    test.airdrop(&fee_collector, Lamports(subvention).into_token_strict())
+        .await;
+
+    let (hashing_account_pubkey, hashing_account_bump) =
+        BaseCommitmentHashingAccount::find(Some(0));
+    let sol_price_account = test.token_to_usd_price_pyth_account(0);
+    let ix = ElusivInstruction::store_base_commitment_instruction(
+        0,
+        hashing_account_bump,
+        request.clone(),
+        SignerAccount(sender),
+        WritableUserAccount(sender),
+        WritableSignerAccount(warden.pubkey),
+        WritableUserAccount(warden.pubkey),
+        WritableUserAccount(pool),
+        WritableUserAccount(fee_collector),
+        UserAccount(sol_price_account),
+        UserAccount(sol_price_account),
+        WritableUserAccount(hashing_account_pubkey),
+        UserAccount(system_program::id()),
+        UserAccount(apa_target_map_account(&sender)),
+        UserAccount(system_program::id()),
+    );
+
+    // Without the invoking program, the PDA cannot sign
+    let mut unsigned_ix = ix.clone();
+    unsigned_ix.accounts[0].is_signer = false;
+    test.ix_should_fail(unsigned_ix, &[&warden.keypair]).await;
+
+    test.ix_should_succeed(
+        mock_program::cpi_instruction(MOCK_PROGRAM_ID, seed, ix),
+        &[&warden.keypair],
+    )
+    .await;
+
+    // Sender has zero lamports
+    assert_eq!(0, test.lamports(&sender).await.0);
+
+    // Pool has request.amount + computation_fee as lamports
+    assert_eq!(
+        request.amount + computation_fee,
+        test.pda_lamports(&pool, PoolAccount::SIZE).await.0
+    );
+
+    // Warden has computation_fee lamports
+    assert_eq!(computation_fee, warden.lamports(&mut test).await);
+}
+
 #[tokio::test]
 async fn test_store_base_commitment_token_transfer() {
     let mut test = start_test_with_setup().await;
@@ -208,6 +534,8 @@ async fn test_store_base_commitment_token_transfer() {
             UserAccount(token_price_account),
             WritableUserAccount(hashing_account_pubkey),
             UserAccount(spl_token::id()),
+            UserAccount(apa_target_map_account(&client.pubkey)),
+            UserAccount(system_program::id()),
         ),
         &[&client.keypair, &warden.keypair],
     )
@@ -241,6 +569,47 @@ async fn test_store_base_commitment_token_transfer() {
         computation_fee_token.amount(),
         warden.balance(USDC_TOKEN_ID, &mut test).await
     );
+
+    // The pool ledger tracks the deposit (token) and the reserved computation_fee (lamports)
+    pda_account!(pool_ledger, PoolLedgerAccount, None, None, test);
+    assert_eq!(
+        request.amount,
+        pool_ledger.get_balances(USDC_TOKEN_ID as usize).deposited
+    );
+    assert_eq!(computation_fee.0, pool_ledger.get_balances(0).fees_reserved);
+
+    let pool = PoolAccount::find(None).0;
+    test.ix_should_succeed_simple(ElusivInstruction::audit_pool_instruction(
+        USDC_TOKEN_ID,
+        UserAccount(pool_account),
+    ))
+    .await;
+    test.ix_should_succeed_simple(ElusivInstruction::audit_pool_instruction(
+        LAMPORTS_TOKEN_ID,
+        UserAccount(pool),
+    ))
+    .await;
+
+    // Invalid pool_account
+    test.ix_should_fail_simple(ElusivInstruction::audit_pool_instruction(
+        USDC_TOKEN_ID,
+        UserAccount(pool),
+    ))
+    .await;
+
+    // Liabilities exceeding the pool balance
+    test.set_pda_account::<PoolLedgerAccount, _>(&elusiv::id(), None, None, |data| {
+        let mut account = PoolLedgerAccount::new(data).unwrap();
+        let mut balance = account.get_balances(USDC_TOKEN_ID as usize);
+        balance.deposited += 1;
+        account.set_balances(USDC_TOKEN_ID as usize, &balance);
+    })
+    .await;
+    test.ix_should_fail_simple(ElusivInstruction::audit_pool_instruction(
+        USDC_TOKEN_ID,
+        UserAccount(pool_account),
+    ))
+    .await;
 }
 
 #[tokio::test]
@@ -336,6 +705,8 @@ async fn test_base_commitment_lamports() {
             UserAccount(system_program::id()),
             WritableUserAccount(hashing_account_pubkey),
             UserAccount(system_program::id()),
+            UserAccount(apa_target_map_account(&client.pubkey)),
+            UserAccount(system_program::id()),
         ),
         &[&client.keypair, &warden_a.keypair],
     )
@@ -357,6 +728,8 @@ async fn test_base_commitment_lamports() {
             UserAccount(system_program::id()),
             WritableUserAccount(hashing_account_pubkey),
             UserAccount(system_program::id()),
+            UserAccount(apa_target_map_account(&client.pubkey)),
+            UserAccount(system_program::id()),
         ),
         &[&client.keypair, &warden_a.keypair],
     )
@@ -470,9 +843,11 @@ async fn test_base_commitment_lamports() {
 
     let compute_ix = ElusivInstruction::compute_base_commitment_hash_instruction(0);
     let finalize_ix = ElusivInstruction::finalize_base_commitment_hash_instruction(
+        0,
         0,
         0,
         WritableUserAccount(warden_a.pubkey),
+        &[],
     );
 
     // Compute each base_commitment_hash
@@ -505,17 +880,21 @@ async fn test_base_commitment_lamports() {
     // Finalize fails: B attempts to submit the wrong original_fee_payer
     test.ix_should_fail_simple(
         ElusivInstruction::finalize_base_commitment_hash_instruction(
+            0,
             0,
             0,
             WritableUserAccount(warden_b.pubkey),
+            &[],
         ),
     )
     .await;
 
     let finalize_ix = ElusivInstruction::finalize_base_commitment_hash_instruction(
+        0,
         0,
         0,
         WritableUserAccount(warden_a.pubkey),
+        &[],
     );
 
     // Finalize fails: two finalize ix in a single tx
@@ -644,6 +1023,8 @@ async fn test_base_commitment_token() {
             UserAccount(token_price_account),
             WritableUserAccount(hashing_account_pubkey),
             UserAccount(spl_token::id()),
+            UserAccount(apa_target_map_account(&client.pubkey)),
+            UserAccount(system_program::id()),
         ),
         &[&client.keypair, &warden.keypair],
     )
@@ -659,9 +1040,11 @@ async fn test_base_commitment_token() {
 
     test.ix_should_succeed_simple(
         ElusivInstruction::finalize_base_commitment_hash_instruction(
+            0,
             0,
             0,
             WritableUserAccount(warden.pubkey),
+            &[],
         ),
     )
     .await;
@@ -768,8 +1151,8 @@ async fn test_single_commitment() {
 
     // Init succeeds
     test.tx_should_succeed_simple(&[
-        ElusivInstruction::init_commitment_hash_setup_instruction(false, &[]),
-        ElusivInstruction::init_commitment_hash_instruction(false),
+        ElusivInstruction::init_commitment_hash_setup_instruction(false, 0, 0, 0, &[]),
+        ElusivInstruction::init_commitment_hash_instruction(false, 0, 0, &[]),
     ])
     .await;
 
@@ -794,16 +1177,21 @@ async fn test_single_commitment() {
 
     // Second init fails, since a hashing is already active
     test.tx_should_fail_simple(&[
-        ElusivInstruction::init_commitment_hash_setup_instruction(false, &[]),
-        ElusivInstruction::init_commitment_hash_instruction(false),
+        ElusivInstruction::init_commitment_hash_setup_instruction(false, 0, 0, 0, &[]),
+        ElusivInstruction::init_commitment_hash_instruction(false, 0, 0, &[]),
     ])
     .await;
 
     let finalize_ix = ElusivInstruction::finalize_commitment_hash_instruction(
+        0,
+        0,
+        0,
         &writable_user_accounts(&storage_accounts),
     );
 
     let compute_ix = ElusivInstruction::compute_commitment_hash_instruction(
+        0,
+        0,
         0,
         0,
         WritableSignerAccount(warden.pubkey),
@@ -881,7 +1269,7 @@ async fn test_single_commitment() {
         assert_eq!(fr_to_u256_le(&hash), s.get_root().unwrap());
 
         // Root should be equal to first mt_root_history value
-        assert_eq!(s.get_root().unwrap(), s.get_active_mt_root_history(0));
+        assert_eq!(Some(s.get_root().unwrap()), s.get_root_history(0));
     })
     .await;
 }
@@ -942,9 +1330,11 @@ async fn test_commitment_full_queue() {
     // Finalization should now fail due to full queue
     test.ix_should_fail_simple(
         ElusivInstruction::finalize_base_commitment_hash_instruction(
+            0,
             0,
             0,
             WritableUserAccount(warden.pubkey),
+            &[],
         ),
     )
     .await;
@@ -972,6 +1362,9 @@ async fn test_commitment_correct_storage_account_insertion() {
         .await;
 
         test.ix_should_succeed_simple(ElusivInstruction::finalize_commitment_hash_instruction(
+            0,
+            0,
+            0,
             &writable_user_accounts(&storage_accounts),
         ))
         .await;
@@ -1071,9 +1464,12 @@ async fn test_commitment_hash_multiple_commitments_zero_batch() {
         test.tx_should_succeed_simple(&[
             ElusivInstruction::init_commitment_hash_setup_instruction(
                 false,
-                &user_accounts(&storage_accounts),
+                0,
+                0,
+                0,
+                &writable_user_accounts(&storage_accounts),
             ),
-            ElusivInstruction::init_commitment_hash_instruction(false),
+            ElusivInstruction::init_commitment_hash_instruction(false, 0, 0, &[]),
         ])
         .await;
 
@@ -1082,6 +1478,8 @@ async fn test_commitment_hash_multiple_commitments_zero_batch() {
                 &[
                     request_compute_units(COMMITMENT_HASH_COMPUTE_BUDGET),
                     ElusivInstruction::compute_commitment_hash_instruction(
+                        0,
+                        0,
                         0,
                         0,
                         WritableSignerAccount(warden.pubkey),
@@ -1093,6 +1491,9 @@ async fn test_commitment_hash_multiple_commitments_zero_batch() {
         }
 
         test.ix_should_succeed_simple(ElusivInstruction::finalize_commitment_hash_instruction(
+            0,
+            0,
+            0,
             &writable_user_accounts(&storage_accounts),
         ))
         .await;
@@ -1156,9 +1557,12 @@ async fn test_commitment_hash_with_batching_rate(
     test.tx_should_succeed_simple(&[
         ElusivInstruction::init_commitment_hash_setup_instruction(
             false,
-            &user_accounts(&storage_accounts),
+            0,
+            0,
+            0,
+            &writable_user_accounts(&storage_accounts),
         ),
-        ElusivInstruction::init_commitment_hash_instruction(false),
+        ElusivInstruction::init_commitment_hash_instruction(false, 0, 0, &[]),
     ])
     .await;
 
@@ -1167,6 +1571,8 @@ async fn test_commitment_hash_with_batching_rate(
             &[
                 request_compute_units(COMMITMENT_HASH_COMPUTE_BUDGET),
                 ElusivInstruction::compute_commitment_hash_instruction(
+                    0,
+                    0,
                     0,
                     0,
                     WritableSignerAccount(warden.pubkey),
@@ -1179,6 +1585,9 @@ async fn test_commitment_hash_with_batching_rate(
 
     for _ in 0..=batching_rate {
         test.ix_should_succeed_simple(ElusivInstruction::finalize_commitment_hash_instruction(
+            0,
+            0,
+            0,
             &writable_user_accounts(&storage_accounts),
         ))
         .await;
@@ -1,10 +1,11 @@
 #![allow(unused_macros)]
 #![allow(dead_code)]
 
+use borsh::BorshSerialize;
 use elusiv::{
     fields::fr_to_u256_le,
     instruction::ElusivInstruction,
-    proof::verifier::{CombinedMillerLoop, FinalExponentiation},
+    processor::program_data_address,
     state::{
         fee::{BasisPointFee, ProgramFee},
         nullifier::NullifierAccount,
@@ -12,20 +13,51 @@ use elusiv::{
     },
     types::U256,
 };
-use elusiv_computation::PartialComputation;
 pub use elusiv_test::*;
 use elusiv_types::{
     elusiv_token, Lamports, PDAAccount, PDAOffset, WritableSignerAccount, WritableUserAccount,
 };
 use std::str::FromStr;
 
+/// Program depositing into Elusiv through CPI, signing for its own PDAs (see `mock_program::process_store_base_commitment`)
+pub const MOCK_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26,
+    27, 28, 29, 30, 31, 32,
+]);
+
 pub async fn start_test() -> ElusivProgramTest {
-    ElusivProgramTest::start(&[(
-        String::from("elusiv"),
-        elusiv::id(),
-        processor!(elusiv::process_instruction),
-    )])
-    .await
+    let mut test = ElusivProgramTest::start(&[
+        (
+            String::from("elusiv"),
+            elusiv::id(),
+            processor!(elusiv::process_instruction),
+        ),
+        (
+            String::from("mock_program"),
+            MOCK_PROGRAM_ID,
+            processor!(mock_program::process_store_base_commitment),
+        ),
+    ])
+    .await;
+
+    let payer = test.payer();
+    set_upgrade_authority(&mut test, Some(payer)).await;
+
+    test
+}
+
+/// Sets the upgrade authority in the `ProgramData` account of Elusiv (required for [`ElusivInstruction::SetupGovernorAccount`])
+pub async fn set_upgrade_authority(test: &mut ElusivProgramTest, authority: Option<Pubkey>) {
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend(0u64.to_le_bytes());
+    data.extend(authority.try_to_vec().unwrap());
+
+    test.set_account_rent_exempt(
+        &program_data_address(),
+        &data,
+        &solana_program::bpf_loader_upgradeable::id(),
+    )
+    .await;
 }
 
 pub async fn start_test_with_setup() -> ElusivProgramTest {
@@ -47,8 +79,7 @@ pub async fn genesis_fee(test: &mut ElusivProgramTest) -> ProgramFee {
         proof_subvention: Lamports(44),
         warden_hash_tx_reward: Lamports(300),
         warden_proof_reward: Lamports(555),
-        proof_base_tx_count: (CombinedMillerLoop::TX_COUNT + FinalExponentiation::TX_COUNT + 2)
-            as u64,
+        proof_base_tx_count: ProgramFee::proof_base_tx_count(),
     }
 }
 
@@ -61,6 +92,8 @@ pub fn initial_single_instance_pdas(payer: Pubkey) -> Vec<Instruction> {
     vec![
         ElusivInstruction::setup_governor_account_instruction(WritableSignerAccount(payer)),
         ElusivInstruction::open_single_instance_accounts_instruction(WritableSignerAccount(payer)),
+        ElusivInstruction::open_token_volume_account_instruction(WritableSignerAccount(payer)),
+        ElusivInstruction::open_pool_ledger_account_instruction(WritableSignerAccount(payer)),
     ]
 }
 
@@ -80,6 +113,7 @@ pub async fn setup_storage_account(test: &mut ElusivProgramTest) -> Vec<Pubkey>
         .await;
     for (i, p) in pubkeys.iter().enumerate() {
         instructions.push(ElusivInstruction::enable_storage_child_account_instruction(
+            0,
             i as u32,
             WritableUserAccount(*p),
         ));
@@ -2,47 +2,49 @@
 
 mod common;
 
+use ark_bn254::{G1Affine, G2Affine};
+use ark_ec::AffineCurve;
 use borsh::{BorshDeserialize, BorshSerialize};
 use common::*;
 use elusiv::bytes::{BorshSerDeSized, ElusivOption};
-use elusiv::fields::{u64_to_u256, u64_to_u256_skip_mr};
+use elusiv::fields::{u64_to_u256, u64_to_u256_skip_mr, G1A, G2A};
 use elusiv::instruction::{
     ElusivInstruction, SignerAccount, UserAccount, WritableSignerAccount, WritableUserAccount,
 };
 use elusiv::processor::{program_token_account_address, FinalizeSendData, ProofRequest};
-use elusiv::proof::verifier::{
-    prepare_public_inputs_instructions, proof_from_str, CombinedMillerLoop, FinalExponentiation,
-    VerificationStep,
-};
+use elusiv::proof::verifier::{prepare_public_inputs_instructions, VerificationStep};
 use elusiv::proof::vkey::{SendQuadraVKey, VerifyingKeyInfo};
+use elusiv::state::apa::apa_target_map_account;
 use elusiv::state::fee::ProgramFee;
 use elusiv::state::governor::{FeeCollectorAccount, PoolAccount};
 use elusiv::state::nullifier::{NullifierAccount, NullifierMap, NULLIFIERS_PER_ACCOUNT};
 use elusiv::state::program_account::{PDAAccount, PDAAccountData, ProgramAccount, SizedAccount};
 use elusiv::state::proof::{VerificationAccount, VerificationState};
 use elusiv::state::storage::{empty_root_raw, StorageAccount, MT_HEIGHT};
-use elusiv::state::vkey::{VKeyAccount, VKeyAccountEager};
+use elusiv::state::vkey::{
+    ProofSystem, VKeyAccount, VKeyAccountEager, VKeyAction, MAX_VKEY_SIGNERS,
+};
 use elusiv::token::{
     spl_token_account_data, Lamports, Token, TokenPrice, LAMPORTS_TOKEN_ID, TOKENS, USDC_TOKEN_ID,
     USDT_TOKEN_ID,
 };
 use elusiv::types::{
     compute_fee_rec, compute_fee_rec_lamports, generate_hashed_inputs, InputCommitment,
-    JoinSplitPublicInputs, OrdU256, Proof, PublicInputs, RawProof, RawU256, SendPublicInputs,
-    JOIN_SPLIT_MAX_N_ARITY, U256,
+    JoinSplitPublicInputs, OrdU256, ProgramCall, Proof, PublicInputs, RawProof, RawU256,
+    SendPublicInputs, JOIN_SPLIT_MAX_N_ARITY, U256,
 };
-use elusiv_computation::PartialComputation;
 use elusiv_types::tokens::Price;
 use elusiv_types::ParentAccount;
 use elusiv_utils::two_pow;
-use solana_program::instruction::Instruction;
+use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::native_token::LAMPORTS_PER_SOL;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::{system_instruction, system_program};
 use solana_program_test::*;
-use solana_sdk::compute_budget::ComputeBudgetInstruction;
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
 
 async fn start_verification_test() -> ElusivProgramTest {
     let mut test = start_test_with_setup().await;
@@ -71,29 +73,11 @@ impl FullSendRequest {
 }
 
 fn send_request(index: usize) -> FullSendRequest {
-    let proof = proof_from_str(
-        (
-            "10026859857882131638516328056627849627085232677511724829502598764489185541935",
-            "19685960310506634721912121951341598678325833230508240750559904196809564625591",
-            false,
-        ),
-        (
-            (
-                "857882131638516328056627849627085232677511724829502598764489185541935",
-                "685960310506634721912121951341598678325833230508240750559904196809564625591",
-            ),
-            (
-                "837064132573119120838379738103457054645361649757131991036638108422638197362",
-                "86803555845400161937398579081414146527572885637089779856221229551142844794",
-            ),
-            false,
-        ),
-        (
-            "21186803555845400161937398579081414146527572885637089779856221229551142844794",
-            "85960310506634721912121951341598678325833230508240750559904196809564625591",
-            false,
-        ),
-    );
+    let proof = Proof {
+        a: G1A(G1Affine::prime_subgroup_generator()),
+        b: G2A(G2Affine::prime_subgroup_generator()),
+        c: G1A(G1Affine::prime_subgroup_generator()),
+    };
 
     let default_hashed_inputs = ExtraData::default().hash();
 
@@ -214,6 +198,7 @@ struct ExtraData {
     reference: U256,
     is_associated_token_account: bool,
     memo: Option<Vec<u8>>,
+    program_call: Option<ProgramCall>,
 }
 
 impl Default for ExtraData {
@@ -228,6 +213,7 @@ impl Default for ExtraData {
             reference: [0; 32],
             is_associated_token_account: false,
             memo: None,
+            program_call: None,
         }
     }
 }
@@ -242,6 +228,7 @@ impl ExtraData {
             self.reference,
             self.is_associated_token_account,
             &self.memo,
+            &self.program_call,
         )
     }
 
@@ -261,7 +248,7 @@ impl ExtraData {
 async fn init_verification_simple(
     proof: &Proof,
     public_inputs: &SendPublicInputs,
-    identifier: U256,
+    extra_data: &ExtraData,
     test: &mut ElusivProgramTest,
 ) {
     let nullifier_accounts = nullifier_accounts(test, 0).await;
@@ -275,11 +262,15 @@ async fn init_verification_simple(
             false,
             WritableSignerAccount(test.payer()),
             WritableUserAccount(public_inputs.join_split.nullifier_duplicate_pda().0),
-            UserAccount(Pubkey::new_from_array(identifier)),
+            UserAccount(extra_data.identifier()),
             &user_accounts(&[nullifier_accounts[0]]),
             &[],
         ),
-        ElusivInstruction::init_verification_transfer_fee_sol_instruction(0, test.payer()),
+        ElusivInstruction::init_verification_transfer_fee_sol_instruction(
+            0,
+            test.payer(),
+            extra_data.recipient(),
+        ),
         ElusivInstruction::init_verification_proof_instruction(
             0,
             *proof,
@@ -340,11 +331,19 @@ async fn setup_vkey_account<VKey: VerifyingKeyInfo>(
             bump_seed: bump,
             version: 0,
         },
-        pubkeys: [Some(sub_account_pubkey).into(), None.into()],
+        pubkeys: [Some(sub_account_pubkey).into(), None.into(), None.into()],
         public_inputs_count: VKey::PUBLIC_INPUTS_COUNT,
         is_frozen: true,
         authority: ElusivOption::None,
         version: 1,
+        proof_system: ProofSystem::Groth16,
+        active_verifications: 0,
+        previous_verifications: 0,
+        signers: [ElusivOption::None; MAX_VKEY_SIGNERS],
+        threshold: 0,
+        pending_action: VKeyAction::ActivateVersion,
+        approvals: [false; MAX_VKEY_SIGNERS],
+        unlock_timestamp: ElusivOption::None,
     }
     .try_to_vec()
     .unwrap();
@@ -479,6 +478,9 @@ async fn test_init_proof_signers() {
             UserAccount(system_program::id()),
             UserAccount(system_program::id()),
             UserAccount(system_program::id()),
+            UserAccount(warden2.pubkey),
+            UserAccount(apa_target_map_account(&warden2.pubkey)),
+            UserAccount(system_program::id()),
         ),
         &[&warden2.keypair],
     )
@@ -494,6 +496,9 @@ async fn test_init_proof_signers() {
             UserAccount(system_program::id()),
             UserAccount(system_program::id()),
             UserAccount(system_program::id()),
+            UserAccount(warden.pubkey),
+            UserAccount(apa_target_map_account(&warden.pubkey)),
+            UserAccount(system_program::id()),
         ),
         &[&warden.keypair],
     )
@@ -624,6 +629,9 @@ async fn test_init_proof_lamports() {
         UserAccount(system_program::id()),
         UserAccount(system_program::id()),
         UserAccount(system_program::id()),
+        UserAccount(warden.pubkey),
+        UserAccount(apa_target_map_account(&warden.pubkey)),
+        UserAccount(system_program::id()),
     );
 
     test.ix_should_fail(transfer_fee_instruction.clone(), &[&warden.keypair])
@@ -759,6 +767,9 @@ async fn test_init_proof_token() {
             UserAccount(sol_price_account),
             UserAccount(token_price_account),
             UserAccount(spl_token::id()),
+            UserAccount(warden.get_token_account(USDC_TOKEN_ID)),
+            UserAccount(apa_target_map_account(&warden.pubkey)),
+            UserAccount(system_program::id()),
         ),
         &[&warden.keypair],
     )
@@ -841,7 +852,11 @@ async fn test_finalize_proof_lamports() {
                 &user_accounts(&[nullifier_accounts[0]]),
                 &[],
             ),
-            ElusivInstruction::init_verification_transfer_fee_sol_instruction(0, warden.pubkey),
+            ElusivInstruction::init_verification_transfer_fee_sol_instruction(
+                0,
+                warden.pubkey,
+                extra_data.recipient(),
+            ),
             ElusivInstruction::init_verification_proof_instruction(
                 0,
                 request.proof,
@@ -902,9 +917,13 @@ async fn test_finalize_proof_lamports() {
         );
     let finalize_verification_transfer_lamports_instruction =
         ElusivInstruction::finalize_verification_transfer_lamports_instruction(
+            0,
             0,
             WritableSignerAccount(warden.pubkey),
             WritableUserAccount(recipient),
+            UserAccount(recipient),
+            &[],
+            WritableUserAccount(VKeyAccount::find(Some(SendQuadraVKey::VKEY_ID)).0),
             WritableUserAccount(nullifier_duplicate_account),
         );
 
@@ -1089,6 +1108,9 @@ async fn test_finalize_proof_token() {
                 UserAccount(sol_price_account),
                 UserAccount(token_price_account),
                 UserAccount(spl_token::id()),
+                UserAccount(recipient_token_account),
+                UserAccount(apa_target_map_account(&recipient.pubkey)),
+                UserAccount(system_program::id()),
             ),
             ElusivInstruction::init_verification_proof_instruction(
                 0,
@@ -1141,6 +1163,7 @@ async fn test_finalize_proof_token() {
         );
     let finalize_verification_transfer_token_instruction =
         ElusivInstruction::finalize_verification_transfer_token_instruction(
+            0,
             0,
             WritableSignerAccount(warden.pubkey),
             WritableUserAccount(warden.get_token_account(USDC_TOKEN_ID)),
@@ -1148,6 +1171,8 @@ async fn test_finalize_proof_token() {
             UserAccount(recipient_token_account),
             WritableUserAccount(pool_account),
             WritableUserAccount(fee_collector_account),
+            &[],
+            WritableUserAccount(VKeyAccount::find(Some(SendQuadraVKey::VKEY_ID)).0),
             WritableUserAccount(nullifier_duplicate_account),
             UserAccount(spl_token::id()),
         );
@@ -1278,6 +1303,7 @@ async fn test_finalize_proof_skip_nullifier_pda() {
             ElusivInstruction::init_verification_transfer_fee_sol_instruction(
                 v_index,
                 warden.pubkey,
+                recipient.pubkey,
             ),
             ElusivInstruction::init_verification_proof_instruction(
                 v_index,
@@ -1324,8 +1350,12 @@ async fn test_finalize_proof_skip_nullifier_pda() {
             ),
             ElusivInstruction::finalize_verification_transfer_lamports_instruction(
                 v_index,
+                0,
                 WritableSignerAccount(warden.pubkey),
                 WritableUserAccount(recipient.pubkey),
+                UserAccount(recipient.pubkey),
+                &[],
+                WritableUserAccount(VKeyAccount::find(Some(SendQuadraVKey::VKEY_ID)).0),
                 WritableUserAccount(nullifier_duplicate_account),
             ),
         ];
@@ -1410,7 +1440,11 @@ async fn test_finalize_proof_commitment_index() {
             &user_accounts(&[nullifier_accounts[0]]),
             &[],
         ),
-        ElusivInstruction::init_verification_transfer_fee_sol_instruction(0, warden.pubkey),
+        ElusivInstruction::init_verification_transfer_fee_sol_instruction(
+            0,
+            warden.pubkey,
+            recipient.pubkey,
+        ),
         ElusivInstruction::init_verification_proof_instruction(
             0,
             request.proof,
@@ -1433,6 +1467,7 @@ async fn test_finalize_proof_commitment_index() {
                     commitment_index,
                     encrypted_owner: extra_data.encrypted_owner,
                     iv: extra_data.iv,
+                    program_call: ElusivOption::None,
                 },
                 false,
                 UserAccount(recipient.pubkey),
@@ -1447,9 +1482,13 @@ async fn test_finalize_proof_commitment_index() {
                 &writable_user_accounts(&[nullifier_accounts[0]]),
             ),
             ElusivInstruction::finalize_verification_transfer_lamports_instruction(
+                0,
                 0,
                 WritableSignerAccount(warden.pubkey),
                 WritableUserAccount(recipient.pubkey),
+                UserAccount(recipient.pubkey),
+                &[],
+                WritableUserAccount(VKeyAccount::find(Some(SendQuadraVKey::VKEY_ID)).0),
                 WritableUserAccount(nullifier_duplicate_account),
             ),
         ]
@@ -1575,6 +1614,8 @@ async fn test_associated_token_account() {
         warden.get_token_account(USDC_TOKEN_ID),
         pool_account,
         fee_collector_account,
+        recipient.pubkey,
+        recipient.pubkey,
     );
     test.ix_should_succeed(transfer_ix.clone(), &[&warden.keypair])
         .await;
@@ -1624,6 +1665,7 @@ async fn test_associated_token_account() {
                 &writable_user_accounts(&[nullifier_accounts[0]]),
             ),
             ElusivInstruction::finalize_verification_transfer_token_instruction(
+                0,
                 0,
                 WritableSignerAccount(warden.pubkey),
                 WritableUserAccount(warden.get_token_account(USDC_TOKEN_ID)),
@@ -1631,6 +1673,8 @@ async fn test_associated_token_account() {
                 UserAccount(recipient_wallet),
                 WritableUserAccount(pool_account),
                 WritableUserAccount(fee_collector_account),
+                &[],
+                WritableUserAccount(VKeyAccount::find(Some(SendQuadraVKey::VKEY_ID)).0),
                 WritableUserAccount(nullifier_duplicate_account),
                 UserAccount(mint),
             ),
@@ -1744,7 +1788,12 @@ async fn test_finalize_proof_failure_token() {
 }
 
 #[tokio::test]
+#[cfg(not(feature = "alt-bn128"))]
 async fn test_compute_proof_verifcation_invalid_proof() {
+    use elusiv::proof::verifier::{CombinedMillerLoop, FinalExponentiation, G2SubgroupCheck};
+    use elusiv_computation::PartialComputation;
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+
     let mut test = start_verification_test().await;
     let (_, vkey_sub_account) = setup_vkey_account::<SendQuadraVKey>(&mut test).await;
     let warden = test.new_actor().await;
@@ -1790,7 +1839,11 @@ async fn test_compute_proof_verifcation_invalid_proof() {
                 &user_accounts(&[nullifier_accounts[0]]),
                 &[],
             ),
-            ElusivInstruction::init_verification_transfer_fee_sol_instruction(0, warden.pubkey),
+            ElusivInstruction::init_verification_transfer_fee_sol_instruction(
+                0,
+                warden.pubkey,
+                Pubkey::new_unique(),
+            ),
             ElusivInstruction::init_verification_proof_instruction(
                 0,
                 request.proof,
@@ -1841,6 +1894,21 @@ async fn test_compute_proof_verifcation_invalid_proof() {
         test.tx_should_succeed_simple(&instructions).await;
     }
 
+    pda_account!(
+        v_acc,
+        VerificationAccount,
+        Some(warden.pubkey),
+        Some(0),
+        test
+    );
+    assert_eq!(v_acc.get_is_verified().option(), None);
+    assert_matches::assert_matches!(v_acc.get_step(), VerificationStep::ProofSubgroupCheck);
+
+    // G2 subgroup check
+    for _ in 0..G2SubgroupCheck::TX_COUNT {
+        test.tx_should_succeed_simple(&instructions).await;
+    }
+
     pda_account!(
         v_acc,
         VerificationAccount,
@@ -1882,6 +1950,121 @@ async fn test_compute_proof_verifcation_invalid_proof() {
     assert_matches::assert_matches!(v_acc.get_step(), VerificationStep::FinalExponentiation);
 }
 
+#[tokio::test]
+#[cfg(feature = "alt-bn128")]
+async fn test_compute_proof_verifcation_invalid_proof_alt_bn128() {
+    use elusiv::proof::verifier::G2SubgroupCheck;
+    use elusiv_computation::PartialComputation;
+
+    let mut test = start_verification_test().await;
+    let (_, vkey_sub_account) = setup_vkey_account::<SendQuadraVKey>(&mut test).await;
+    let warden = test.new_actor().await;
+    let nullifier_accounts = nullifier_accounts(&mut test, 0).await;
+    let fee = genesis_fee(&mut test).await;
+    let mut request = send_request(0);
+    request.update_fee_token(&fee, &TokenPrice::new_lamports());
+
+    let fee_collector = FeeCollectorAccount::find(None).0;
+    let nullifier_duplicate_account = request.public_inputs.join_split.nullifier_duplicate_pda().0;
+
+    let subvention = fee.proof_subvention;
+    let commitment_hash_fee = fee.commitment_hash_computation_fee(0);
+    let verification_account_rent = test.rent(VerificationAccount::SIZE).await;
+    let nullifier_duplicate_account_rent = test.rent(PDAAccountData::SIZE).await;
+
+    warden
+        .airdrop(
+            LAMPORTS_TOKEN_ID,
+            verification_account_rent.0
+                + nullifier_duplicate_account_rent.0
+                + commitment_hash_fee.0,
+            &mut test,
+        )
+        .await;
+    test.airdrop_lamports(&fee_collector, subvention.0).await;
+
+    test.tx_should_succeed(
+        &[ElusivInstruction::init_verification_instruction(
+            0,
+            SendQuadraVKey::VKEY_ID,
+            [0, 1],
+            ProofRequest::Send(request.public_inputs.clone()),
+            false,
+            WritableSignerAccount(warden.pubkey),
+            WritableUserAccount(nullifier_duplicate_account),
+            UserAccount(Pubkey::new_unique()),
+            &user_accounts(&[nullifier_accounts[0]]),
+            &[],
+        )],
+        &[&warden.keypair],
+    )
+    .await;
+
+    let instructions = [
+        request_compute_units(1_400_000),
+        ElusivInstruction::compute_verification_instruction(
+            0,
+            SendQuadraVKey::VKEY_ID,
+            UserAccount(warden.pubkey),
+            &[UserAccount(vkey_sub_account)],
+        ),
+    ];
+
+    // Input preparation (before the proof is setup)
+    test.tx_should_succeed_simple(&instructions).await;
+
+    pda_account!(
+        v_acc,
+        VerificationAccount,
+        Some(warden.pubkey),
+        Some(0),
+        test
+    );
+    assert_eq!(v_acc.get_is_verified().option(), None);
+    assert_matches::assert_matches!(v_acc.get_step(), VerificationStep::ProofSubgroupCheck);
+
+    // Subgroup check requires the proof
+    test.tx_should_fail_simple(&instructions).await;
+
+    test.tx_should_succeed(
+        &[
+            ElusivInstruction::init_verification_transfer_fee_sol_instruction(
+                0,
+                warden.pubkey,
+                Pubkey::new_unique(),
+            ),
+            ElusivInstruction::init_verification_proof_instruction(
+                0,
+                request.proof,
+                SignerAccount(warden.pubkey),
+            ),
+        ],
+        &[&warden.keypair],
+    )
+    .await;
+
+    // G2 subgroup check
+    for _ in 0..G2SubgroupCheck::IX_COUNT {
+        test.tx_should_succeed_simple(&instructions).await;
+    }
+
+    // Pairing check
+    test.tx_should_succeed_simple(&instructions).await;
+
+    pda_account!(
+        v_acc,
+        VerificationAccount,
+        Some(warden.pubkey),
+        Some(0),
+        test
+    );
+    assert_eq!(v_acc.get_is_verified().option(), Some(false));
+    assert_matches::assert_matches!(v_acc.get_step(), VerificationStep::FinalExponentiation);
+
+    // Computation is finished
+    test.tx_should_fail_simple(&instructions).await;
+}
+
 #[tokio::test]
 async fn test_enforced_finalization_order() {
     let mut test = start_verification_test().await;
@@ -1903,7 +2086,7 @@ async fn test_enforced_finalization_order() {
     init_verification_simple(
         &request.proof,
         &request.public_inputs,
-        extra_data.identifier,
+        &extra_data,
         &mut test,
     )
     .await;
@@ -1933,9 +2116,13 @@ async fn test_enforced_finalization_order() {
         );
     let finalize_verification_transfer_lamports_instruction =
         ElusivInstruction::finalize_verification_transfer_lamports_instruction(
+            0,
             0,
             WritableSignerAccount(test.payer()),
             WritableUserAccount(extra_data.recipient()),
+            UserAccount(extra_data.recipient()),
+            &[],
+            WritableUserAccount(VKeyAccount::find(Some(SendQuadraVKey::VKEY_ID)).0),
             WritableUserAccount(nullifier_duplicate_account),
         );
 
@@ -2013,7 +2200,7 @@ async fn nullifier_finalization_test(number_of_start_nullifiers: u64, input_comm
         .await;
     test.airdrop_lamports(&pool, LAMPORTS_PER_SOL * 1000).await;
 
-    init_verification_simple(&proof, &public_inputs, extra_data.identifier, &mut test).await;
+    init_verification_simple(&proof, &public_inputs, &extra_data, &mut test).await;
     skip_computation(test.payer(), 0, true, &mut test).await;
     set_verification_state(test.payer(), 0, VerificationState::ProofSetup, &mut test).await;
 
@@ -2076,9 +2263,13 @@ async fn nullifier_finalization_test(number_of_start_nullifiers: u64, input_comm
 
     instructions.push(
         ElusivInstruction::finalize_verification_transfer_lamports_instruction(
+            0,
             0,
             WritableSignerAccount(test.payer()),
             WritableUserAccount(recipient),
+            UserAccount(recipient),
+            &[],
+            WritableUserAccount(VKeyAccount::find(Some(SendQuadraVKey::VKEY_ID)).0),
             WritableUserAccount(nullifier_duplicate_account),
         ),
     );
@@ -2106,6 +2297,11 @@ async fn finalize_instructions(
     memo: Option<Vec<u8>>,
 ) -> Vec<Instruction> {
     let nullifier_accounts = nullifier_accounts(test, 0).await;
+    let recipient = if extra_data.is_associated_token_account {
+        get_associated_token_address(&extra_data.recipient(), &spl_token::native_mint::ID)
+    } else {
+        extra_data.recipient()
+    };
 
     vec![
         ElusivInstruction::finalize_verification_send_instruction(
@@ -2114,6 +2310,7 @@ async fn finalize_instructions(
                 total_amount: request.public_inputs.join_split.total_amount(),
                 encrypted_owner: extra_data.encrypted_owner,
                 iv: extra_data.iv,
+                program_call: extra_data.program_call.into(),
                 ..Default::default()
             },
             memo.is_some(),
@@ -2129,9 +2326,13 @@ async fn finalize_instructions(
             &writable_user_accounts(&[nullifier_accounts[0]]),
         ),
         ElusivInstruction::finalize_verification_transfer_lamports_instruction(
+            0,
             0,
             WritableSignerAccount(*signer),
-            WritableUserAccount(extra_data.recipient()),
+            WritableUserAccount(recipient),
+            UserAccount(extra_data.recipient()),
+            &[],
+            WritableUserAccount(VKeyAccount::find(Some(SendQuadraVKey::VKEY_ID)).0),
             WritableUserAccount(request.public_inputs.join_split.nullifier_duplicate_pda().0),
         ),
     ]
@@ -2160,7 +2361,7 @@ async fn test_isolated_memo() {
     init_verification_simple(
         &request.proof,
         &request.public_inputs,
-        extra_data.identifier,
+        &extra_data,
         &mut test,
     )
     .await;
@@ -2240,7 +2441,7 @@ async fn test_solana_pay_lamports() {
     init_verification_simple(
         &request.proof,
         &request.public_inputs,
-        extra_data.identifier,
+        &extra_data,
         &mut test,
     )
     .await;
@@ -2292,6 +2493,174 @@ async fn test_solana_pay_lamports() {
     .await;
 }
 
+#[tokio::test]
+async fn test_program_call_lamports() {
+    let mut test = start_verification_test().await;
+    setup_vkey_account::<SendQuadraVKey>(&mut test).await;
+
+    // Any instruction using the recipient (e.g. a swap out of the recipient account)
+    let recipient = ExtraData::default().recipient();
+    let program_call_ix = Instruction::new_with_bytes(
+        elusiv::id(),
+        &ElusivInstruction::nop_instruction().data,
+        vec![AccountMeta::new(recipient, false)],
+    );
+
+    let mut request = send_request(0);
+    let extra_data = ExtraData {
+        program_call: Some(ProgramCall::new(
+            &program_call_ix.program_id,
+            &program_call_ix.data,
+        )),
+        ..Default::default()
+    };
+    request.public_inputs.hashed_inputs = extra_data.hash();
+    request.update_fee_lamports(&genesis_fee(&mut test).await);
+
+    test.airdrop_lamports(&FeeCollectorAccount::find(None).0, LAMPORTS_PER_SOL)
+        .await;
+    test.airdrop_lamports(&PoolAccount::find(None).0, LAMPORTS_PER_SOL * 1000)
+        .await;
+
+    init_verification_simple(
+        &request.proof,
+        &request.public_inputs,
+        &extra_data,
+        &mut test,
+    )
+    .await;
+    skip_computation(test.payer(), 0, true, &mut test).await;
+    set_verification_state(test.payer(), 0, VerificationState::ProofSetup, &mut test).await;
+
+    let payer = test.payer();
+    let valid_finalize_ixs = finalize_instructions(
+        &mut test,
+        &request,
+        &extra_data,
+        &extra_data.reference(),
+        &payer,
+        None,
+    )
+    .await;
+
+    // Program call missing
+    test.tx_should_fail_simple(&valid_finalize_ixs).await;
+
+    // Program call at wrong location
+    test.tx_should_fail_simple(&merge(
+        &valid_finalize_ixs,
+        &[&ElusivInstruction::nop_instruction(), &program_call_ix],
+    ))
+    .await;
+
+    // Program call not using the recipient
+    test.tx_should_fail_simple(&merge(
+        &valid_finalize_ixs,
+        &[&ElusivInstruction::nop_instruction()],
+    ))
+    .await;
+
+    // Program call not committed to by the proof
+    let invalid_ixs = finalize_instructions(
+        &mut test,
+        &request,
+        &ExtraData::default(),
+        &extra_data.reference(),
+        &payer,
+        None,
+    )
+    .await;
+    test.tx_should_fail_simple(&merge(&invalid_ixs, &[&program_call_ix]))
+        .await;
+
+    test.tx_should_succeed_simple(&merge(&valid_finalize_ixs, &[&program_call_ix]))
+        .await;
+
+    assert_eq!(
+        request.public_inputs.join_split.amount,
+        test.lamports(&recipient).await.0
+    );
+}
+
+#[tokio::test]
+async fn test_wrapped_lamports_associated_token_account() {
+    wrapped_lamports_test(false).await;
+    wrapped_lamports_test(true).await;
+}
+
+async fn wrapped_lamports_test(associated_token_account_exists: bool) {
+    let mut test = start_verification_test().await;
+    setup_vkey_account::<SendQuadraVKey>(&mut test).await;
+
+    let mut request = send_request(0);
+    let extra_data = ExtraData {
+        recipient: Pubkey::new_unique().to_bytes(),
+        is_associated_token_account: true,
+        ..Default::default()
+    };
+    request.public_inputs.recipient_is_associated_token_account = true;
+    request.public_inputs.hashed_inputs = extra_data.hash();
+    request.update_fee_lamports(&genesis_fee(&mut test).await);
+
+    let associated_token_account =
+        get_associated_token_address(&extra_data.recipient(), &spl_token::native_mint::ID);
+    let token_account_rent = test.rent(spl_token::state::Account::LEN).await;
+
+    test.airdrop_lamports(&FeeCollectorAccount::find(None).0, LAMPORTS_PER_SOL)
+        .await;
+    test.airdrop_lamports(&PoolAccount::find(None).0, LAMPORTS_PER_SOL * 1000)
+        .await;
+
+    init_verification_simple(
+        &request.proof,
+        &request.public_inputs,
+        &extra_data,
+        &mut test,
+    )
+    .await;
+    skip_computation(test.payer(), 0, true, &mut test).await;
+    set_verification_state(test.payer(), 0, VerificationState::ProofSetup, &mut test).await;
+
+    let payer = test.payer();
+    let valid_finalize_ixs = finalize_instructions(
+        &mut test,
+        &request,
+        &extra_data,
+        &extra_data.reference(),
+        &payer,
+        None,
+    )
+    .await;
+
+    // Failure: lamports can't be sent to the wallet directly
+    let mut invalid_ixs = valid_finalize_ixs.clone();
+    invalid_ixs[2].accounts[1].pubkey = extra_data.recipient();
+    test.tx_should_fail_simple(&invalid_ixs).await;
+
+    if associated_token_account_exists {
+        test.ix_should_succeed_simple(create_associated_token_account(
+            &payer,
+            &extra_data.recipient(),
+            &spl_token::native_mint::ID,
+            &spl_token::ID,
+        ))
+        .await;
+    }
+
+    test.tx_should_succeed_simple(&valid_finalize_ixs).await;
+
+    // If the associated token account is created, its rent is deducted from the amount
+    let rent = if associated_token_account_exists {
+        0
+    } else {
+        token_account_rent.0
+    };
+    assert_eq!(
+        request.public_inputs.join_split.amount - rent,
+        test.spl_balance(&associated_token_account).await
+    );
+}
+
 #[tokio::test]
 async fn test_solana_pay_lamports_with_memo() {
     let mut test = start_verification_test().await;
@@ -2316,7 +2685,7 @@ async fn test_solana_pay_lamports_with_memo() {
     init_verification_simple(
         &request.proof,
         &request.public_inputs,
-        extra_data.identifier,
+        &extra_data,
         &mut test,
     )
     .await;
@@ -2549,6 +2918,9 @@ async fn test_solana_pay_tokens() {
                 UserAccount(sol_price_account),
                 UserAccount(token_price_account),
                 UserAccount(spl_token::id()),
+                UserAccount(recipient_token_account),
+                UserAccount(apa_target_map_account(&recipient.pubkey)),
+                UserAccount(system_program::id()),
             ),
             ElusivInstruction::init_verification_proof_instruction(
                 0,
@@ -2585,6 +2957,7 @@ async fn test_solana_pay_tokens() {
             &writable_user_accounts(&[nullifier_accounts[0]]),
         ),
         ElusivInstruction::finalize_verification_transfer_token_instruction(
+            0,
             0,
             WritableSignerAccount(warden.pubkey),
             WritableUserAccount(warden.get_token_account(USDC_TOKEN_ID)),
@@ -2592,6 +2965,8 @@ async fn test_solana_pay_tokens() {
             UserAccount(recipient_token_account),
             WritableUserAccount(pool_account),
             WritableUserAccount(fee_collector_account),
+            &[],
+            WritableUserAccount(VKeyAccount::find(Some(SendQuadraVKey::VKEY_ID)).0),
             WritableUserAccount(nullifier_duplicate_account),
             UserAccount(spl_token::id()),
         ),
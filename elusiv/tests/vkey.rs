@@ -0,0 +1,160 @@
+//! Tests the verifying key deployment
+
+mod common;
+
+use ark_bn254::{Fr, G1Affine, G2Affine};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{FftField, PrimeField};
+use common::*;
+use elusiv::proof::deploy::{VKeyDeployment, VKeyDeploymentAccounts, VKeyDeploymentStep};
+use elusiv::proof::plonk::vkey::plonk_verifying_key_source;
+use elusiv::state::program_account::PDAAccount;
+use elusiv::state::vkey::{ProofSystem, VKeyAccount};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::signer::Signer;
+
+const PLONK_VKEY_ID: u32 = 3;
+
+fn plonk_source(k1: u64) -> Vec<u8> {
+    let commitments = [0u64, 1, 2, 3, 4, 5, 6, 7].map(|i| {
+        G1Affine::prime_subgroup_generator()
+            .mul(Fr::from(i + 11).into_repr())
+            .into_affine()
+    });
+    let x_2 = G2Affine::prime_subgroup_generator()
+        .mul(Fr::from(123u64).into_repr())
+        .into_affine();
+
+    plonk_verifying_key_source(
+        10,
+        Fr::from(k1),
+        Fr::from(3u64),
+        Fr::get_root_of_unity(1 << 10).unwrap(),
+        &commitments,
+        x_2,
+    )
+}
+
+async fn account_data(test: &mut ElusivProgramTest, pubkey: Option<Pubkey>) -> Option<Vec<u8>> {
+    match pubkey {
+        Some(pubkey) if test.account_does_exist(&pubkey).await => Some(test.data(&pubkey).await),
+        _ => None,
+    }
+}
+
+/// Fetches the on-chain state and computes the next step
+async fn next_step(
+    test: &mut ElusivProgramTest,
+    deployment: &VKeyDeployment,
+) -> VKeyDeploymentStep {
+    let vkey_account =
+        account_data(test, Some(VKeyAccount::find(Some(deployment.vkey_id)).0)).await;
+    let (active, new) = match &vkey_account {
+        Some(data) => VKeyDeployment::binary_data_accounts(data).unwrap(),
+        None => (None, None),
+    };
+    let active = account_data(test, active).await;
+    let new = account_data(test, new).await;
+
+    deployment
+        .next_step(&VKeyDeploymentAccounts {
+            vkey_account: vkey_account.as_deref(),
+            active_binary_data_account: active.as_deref(),
+            new_binary_data_account: new.as_deref(),
+        })
+        .unwrap()
+}
+
+/// Executes steps until the deployment is done
+///
+/// # Notes
+///
+/// At most `packet_limit` packets are uploaded, to simulate a partial failure.
+async fn deploy(
+    test: &mut ElusivProgramTest,
+    deployment: &VKeyDeployment,
+    packet_limit: Option<usize>,
+) -> bool {
+    loop {
+        match next_step(test, deployment).await {
+            VKeyDeploymentStep::CreateVkeyAccount(ix)
+            | VKeyDeploymentStep::UpdateVkeyVersion(ix)
+            | VKeyDeploymentStep::ClosePreviousVkeyVersion(ix)
+            | VKeyDeploymentStep::FreezeVkey(ix) => test.ix_should_succeed_simple(ix).await,
+            VKeyDeploymentStep::CreateNewVkeyVersion { size } => {
+                let binary_data_account = test
+                    .create_program_account_rent_exempt(&elusiv::id(), size)
+                    .await;
+                test.ix_should_succeed_simple(
+                    deployment.create_new_vkey_version_instruction(binary_data_account.pubkey()),
+                )
+                .await;
+            }
+            VKeyDeploymentStep::SetVkeyData(ixs) => {
+                let count = packet_limit.unwrap_or(ixs.len()).min(ixs.len());
+                for ix in ixs.into_iter().take(count) {
+                    test.ix_should_succeed_simple(ix).await;
+                }
+
+                if packet_limit.is_some() {
+                    return false;
+                }
+            }
+            VKeyDeploymentStep::AwaitApprovals(_) => panic!("No signers registered"),
+            VKeyDeploymentStep::Done => return true,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_vkey_deployment() {
+    let mut test = start_test_with_setup().await;
+    let authority = test.payer();
+    let deployment = VKeyDeployment::new(
+        PLONK_VKEY_ID,
+        14,
+        ProofSystem::Plonk,
+        authority,
+        false,
+        plonk_source(2),
+    );
+
+    // Partial upload
+    assert!(!deploy(&mut test, &deployment, Some(10)).await);
+    match next_step(&mut test, &deployment).await {
+        VKeyDeploymentStep::SetVkeyData(ixs) => {
+            assert_eq!(ixs.len(), deployment.packet_count() - 10)
+        }
+        _ => panic!(),
+    }
+
+    // Resume
+    assert!(deploy(&mut test, &deployment, None).await);
+
+    let vkey_account = test.data(&VKeyAccount::find(Some(PLONK_VKEY_ID)).0).await;
+    let (active, new) = VKeyDeployment::binary_data_accounts(&vkey_account).unwrap();
+    assert!(new.is_none());
+    let active = active.unwrap();
+    assert!(deployment.is_uploaded(&test.data(&active).await));
+
+    // Update to a new version and freeze
+    let deployment = VKeyDeployment::new(
+        PLONK_VKEY_ID,
+        14,
+        ProofSystem::Plonk,
+        authority,
+        true,
+        plonk_source(5),
+    );
+    assert!(deploy(&mut test, &deployment, None).await);
+    assert!(test.account_does_not_exist(&active).await);
+
+    let vkey_account = test.data(&VKeyAccount::find(Some(PLONK_VKEY_ID)).0).await;
+    let (active, _) = VKeyDeployment::binary_data_accounts(&vkey_account).unwrap();
+    assert!(deployment.is_uploaded(&test.data(&active.unwrap()).await));
+    assert!(matches!(
+        next_step(&mut test, &deployment).await,
+        VKeyDeploymentStep::Done
+    ));
+}
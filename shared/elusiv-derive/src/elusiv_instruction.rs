@@ -8,6 +8,9 @@ const ACC_ATTR: &str = "acc";
 const SYS_ATTR: &str = "sys";
 const PDA_ATTR: &str = "pda";
 const MAP_ATTR: &str = "map";
+const IGNORE_FIELD_ATTR: &str = "ignore_field";
+const PROGRAM_VERSION_ACCOUNT_ATTR: &str = "program_version_account";
+const SKIP_PROGRAM_VERSION_ATTR: &str = "skip_program_version";
 
 const RESERVED_ATTR_IDENTS: [&str; 4] = [ACC_ATTR, SYS_ATTR, PDA_ATTR, MAP_ATTR];
 
@@ -23,8 +26,19 @@ pub fn impl_elusiv_instruction(ast: &syn::DeriveInput) -> proc_macro2::TokenStre
     let mut matches = quote!();
     let mut functions = quote!();
     let mut abi_functions = quote!();
+    let mut cpi_functions = quote!();
     let mut variant_indices = quote!();
 
+    // The `ProgramVersionAccount` required by all instructions (as the last account, if not already declared as a PDA)
+    let program_version_account: Option<TokenStream> = ast
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident(PROGRAM_VERSION_ACCOUNT_ATTR))
+        .map(|attr| {
+            let ty = attr.tokens.to_string();
+            ty[1..ty.len() - 1].parse().unwrap()
+        });
+
     if let syn::Data::Enum(e) = &ast.data {
         for (var_index, var) in e.variants.clone().iter().enumerate() {
             let ident = &var.ident;
@@ -42,6 +56,7 @@ pub fn impl_elusiv_instruction(ast: &syn::DeriveInput) -> proc_macro2::TokenStre
             // Processor calls
             let mut accounts = quote!();
             let mut fields = quote!();
+            let mut processor_fields = quote!();
             let mut signature = quote!();
 
             // Instruction creation
@@ -49,21 +64,44 @@ pub fn impl_elusiv_instruction(ast: &syn::DeriveInput) -> proc_macro2::TokenStre
             let mut user_accounts = quote!();
             let mut instruction_accounts = quote!();
 
+            // CPI instruction creation
+            let mut cpi_accounts = quote!();
+            let mut cpi_instruction_accounts = quote!();
+
             let mut docs = quote!();
             let mut other_attrs = quote!();
             let mut current_attr_type = AttrType::Docs;
 
+            let program_version_account = program_version_account.clone().filter(|_| {
+                !var.attrs
+                    .iter()
+                    .any(|attr| attr.path.is_ident(SKIP_PROGRAM_VERSION_ATTR))
+            });
+            let mut declares_program_version_account = false;
+
             for field in &var.fields {
                 let field_name = field.ident.clone().unwrap();
                 let ty = field.ty.clone();
 
                 fields.extend(quote! { #field_name, });
                 fields_with_type.extend(quote! { #field_name: #ty, });
+
+                // Ignored fields are only used for the account verification (e.g. as PDA offsets)
+                if !field
+                    .attrs
+                    .iter()
+                    .any(|attr| attr.path.is_ident(IGNORE_FIELD_ATTR))
+                {
+                    processor_fields.extend(quote! { #field_name, });
+                }
             }
 
             // Account attributes
             for (_, attr) in var.attrs.iter().enumerate() {
                 let attr_name = attr.path.get_ident().unwrap().to_string();
+                if attr_name == SKIP_PROGRAM_VERSION_ATTR {
+                    continue;
+                }
 
                 // No `ElusivInstruction` specific attribute
                 if !RESERVED_ATTR_IDENTS.contains(&attr_name.as_str()) {
@@ -123,6 +161,7 @@ pub fn impl_elusiv_instruction(ast: &syn::DeriveInput) -> proc_macro2::TokenStre
                     .collect();
 
                 let mut account: TokenStream = sub_attrs[0].0.parse().unwrap();
+                let account_ident = account.clone();
                 let mut account_init = Vec::new(); // used for creating the instruction objects with the abi-feature
 
                 accounts.extend(quote! {
@@ -311,6 +350,27 @@ pub fn impl_elusiv_instruction(ast: &syn::DeriveInput) -> proc_macro2::TokenStre
                                 }
                             };
                             accounts.extend(check_pda);
+
+                            // Accounts need to be migrated to the current layout version (new PDAs are created with it)
+                            if !find_pda {
+                                accounts.extend(quote! {
+                                    <#ty as elusiv_types::accounts::PDAAccount>::verify_account_version(&#account)?;
+                                });
+                            }
+
+                            // A declared program version account is reused for the program version verification (before its data is borrowed mutably)
+                            if let Some(version_ty) = &program_version_account {
+                                if version_ty.to_string() == ty.to_string()
+                                    && value::<String>(&sub_attrs, "pda_offset").is_none()
+                                    && !find_pda
+                                    && !include_child_accounts
+                                {
+                                    accounts.extend(quote! {
+                                        <#ty as elusiv_types::accounts::ProgramVersionAccount>::verify_program_version(&#account)?;
+                                    });
+                                    declares_program_version_account = true;
+                                }
+                            }
                         }
 
                         if include_child_accounts {
@@ -383,6 +443,25 @@ pub fn impl_elusiv_instruction(ast: &syn::DeriveInput) -> proc_macro2::TokenStre
                     v => panic!("Invalid attribute name {}", v),
                 }
 
+                // CPI accounts are passed as `AccountInfo`s (child-accounts as a slice)
+                if contains_key(&sub_attrs, "include_child_accounts") {
+                    cpi_accounts.extend(quote! { #account_ident: &[solana_program::account_info::AccountInfo<'a>], });
+                    cpi_instruction_accounts.extend(quote!{
+                        for account in #account_ident {
+                            accounts.push(solana_program::instruction::AccountMeta::#account_init_fn(*account.key, #is_signer));
+                            account_infos.push(account.clone());
+                        }
+                    });
+                } else {
+                    cpi_accounts.extend(
+                        quote! { #account_ident: &solana_program::account_info::AccountInfo<'a>, },
+                    );
+                    cpi_instruction_accounts.extend(quote!{
+                        accounts.push(solana_program::instruction::AccountMeta::#account_init_fn(*#account_ident.key, #is_signer));
+                        account_infos.push(#account_ident.clone());
+                    });
+                }
+
                 // Add account to processor call signature
                 if !ignore {
                     signature.extend(quote! { #account, });
@@ -396,6 +475,35 @@ pub fn impl_elusiv_instruction(ast: &syn::DeriveInput) -> proc_macro2::TokenStre
                 );
             }
 
+            // Program version verification with a trailing account (before any account data is borrowed mutably)
+            let mut program_version_check = quote!();
+            if let Some(ty) = program_version_account
+                .as_ref()
+                .filter(|_| !declares_program_version_account)
+            {
+                program_version_check.extend(quote!{
+                    let (program_version_account, accounts) = accounts
+                        .split_last()
+                        .ok_or(solana_program::program_error::ProgramError::NotEnoughAccountKeys)?;
+                    <#ty as elusiv_types::accounts::ProgramVersionAccount>::verify_program_version(program_version_account)?;
+                });
+
+                instruction_accounts.extend(quote! {
+                    accounts.push(
+                        solana_program::instruction::AccountMeta::new_readonly(
+                            <#ty as elusiv_types::accounts::PDAAccount>::find(None).0,
+                            false
+                        )
+                    );
+                });
+
+                cpi_accounts.extend(quote! { program_version_account: &solana_program::account_info::AccountInfo<'a>, });
+                cpi_instruction_accounts.extend(quote!{
+                    accounts.push(solana_program::instruction::AccountMeta::new_readonly(*program_version_account.key, false));
+                    account_infos.push(program_version_account.clone());
+                });
+            }
+
             matches.extend(quote! {
                 #other_attrs
                 #ast_ident::#ident { #fields } => {
@@ -407,9 +515,10 @@ pub fn impl_elusiv_instruction(ast: &syn::DeriveInput) -> proc_macro2::TokenStre
                 #docs
                 #other_attrs
                 fn #fn_name(accounts: &[solana_program::account_info::AccountInfo], #fields_with_type) -> solana_program::entrypoint::ProgramResult {
+                    #program_version_check
                     let mut account_info_iter = &mut accounts.iter();
                     #accounts
-                    processor::#fn_name(#signature #fields)
+                    processor::#fn_name(#signature #processor_fields)
                 }
             });
 
@@ -430,6 +539,30 @@ pub fn impl_elusiv_instruction(ast: &syn::DeriveInput) -> proc_macro2::TokenStre
                     )
                 }
             });
+
+            cpi_functions.extend(quote!{
+                #docs
+                #other_attrs
+                pub fn #fn_name<'a>(#fields_with_type #cpi_accounts signers_seeds: &[&[&[u8]]]) -> solana_program::entrypoint::ProgramResult {
+                    let mut accounts = Vec::new();
+                    let mut account_infos = Vec::new();
+
+                    #cpi_instruction_accounts
+                    let data = #ast_ident::#ident { #fields };
+                    let data = #ast_ident::try_to_vec(&data)
+                        .or(Err(solana_program::program_error::ProgramError::InvalidInstructionData))?;
+
+                    solana_program::program::invoke_signed(
+                        &solana_program::instruction::Instruction::new_with_bytes(
+                            crate::id(),
+                            &data,
+                            accounts,
+                        ),
+                        &account_infos,
+                        signers_seeds,
+                    )
+                }
+            });
         }
 
         quote! {
@@ -451,6 +584,14 @@ pub fn impl_elusiv_instruction(ast: &syn::DeriveInput) -> proc_macro2::TokenStre
                 #abi_functions
             }
 
+            /// Cross-program invocations of all instructions (`signers_seeds` are used to sign for PDAs of the invoking program)
+            #[cfg(feature = "cpi")]
+            pub mod cpi {
+                use super::*;
+
+                #cpi_functions
+            }
+
         }
     } else {
         panic!("Only enums can be instructions")
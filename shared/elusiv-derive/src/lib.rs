@@ -35,11 +35,25 @@ use pda_account::*;
 ///     - extra_attributes:
 ///         - `signer`
 ///         - `writable`
-///         - `find_pda`: does a PDA verification with a pda_offset but with unknown runtime, since no bump is supplied (used for renting new PDAs)
-///         - `skip_pda_verification`: skips the PDA verification, resulting in an [`UnverifiedAccountInfo`] (requires the `account_info` attribute)
+///         - `find_pda`: does a PDA verification with a pda_offset but with unknown runtime, since no bump is supplied (used for renting new PDAs, so the account version is not verified)
+///         - `skip_pda_verification`: skips the PDA and account version verification, resulting in an [`UnverifiedAccountInfo`] (requires the `account_info` attribute)
 ///         - `account_info`: returns an `AccountInfo` object (only relevant for PDAs)
 ///         - `include_child_accounts`: the `Type` has to implement the `crate::state::program_account::ParentAccount` trait and up to `Type::COUNT + 1` accounts can be matched (but at least 1)
 ///         - `skip_abi`: can be used to add manual pda_offsets in the abi
+/// - Verified PDAs are also required to match the `PDAAccount::VERSION` of their type
+///
+/// # Program version
+/// - `#[program_version_account(Type)]` on the enum: each instruction requires the `Type` account (implementing `ProgramVersionAccount`) as its last account
+/// - the instruction is rejected before any other account is processed, if the program accounts have not been migrated to the current version
+/// - instructions that already declare a verified `Type` PDA (without a `pda_offset`) reuse it instead of requiring the last account
+/// - `#[skip_program_version]` on a variant: the instruction does not require the account (used for the account setup and migration)
+///
+/// # Field attributes
+/// - `ignore_field`: the field is not passed to the processor function (used for fields that are only required for the account verification, e.g. PDA offsets)
+///
+/// # CPI
+/// - With the `cpi` feature, a `cpi` module is generated next to the enum, containing a function per instruction that invokes the program (using `invoke_signed`)
+/// - All accounts are passed as `AccountInfo`s in the order of the account attributes (followed by the `program_version_account` of gated instructions without a declared one)
 ///
 /// # Other attributes
 /// - Each variant can also be equipped with any other kind of attributes (cfg or do documentation).
@@ -56,7 +70,18 @@ use pda_account::*;
 ///     }
 /// }
 /// ```
-#[proc_macro_derive(ElusivInstruction, attributes(acc, sys, pda, map))]
+#[proc_macro_derive(
+    ElusivInstruction,
+    attributes(
+        acc,
+        sys,
+        pda,
+        map,
+        ignore_field,
+        program_version_account,
+        skip_program_version
+    )
+)]
 pub fn elusiv_instruction(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     impl_elusiv_instruction(&ast).into()
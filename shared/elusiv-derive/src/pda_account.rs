@@ -32,6 +32,7 @@ pub fn impl_pda_account(ast: &syn::DeriveInput) -> TokenStream {
                 const PROGRAM_ID: solana_program::pubkey::Pubkey = crate::PROGRAM_ID;
                 const SEED: &'static [u8] = &#pda_seed_tokens;
                 const FIRST_PDA: (solana_program::pubkey::Pubkey, u8) = (solana_program::pubkey::Pubkey::new_from_array(#first_pubkey), #first_bump);
+                const VERSION: u8 = crate::PROGRAM_VERSION;
 
                 #[cfg(feature = "elusiv-client")]
                 const IDENT: &'static str = #ident_str;
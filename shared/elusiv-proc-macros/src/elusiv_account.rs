@@ -128,6 +128,7 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
     let mut eager_defs = quote!();
     let mut eager_init = quote!();
     let mut use_eager_type = false;
+    let mut growable_field = None;
 
     // 'a lifetime for the `ProgramAccount` impl
     let program_account_lifetime = quote!('a);
@@ -164,6 +165,10 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
                             parent.child_accounts = child_accounts
                         }
 
+                        fn set_child_account(&mut self, index: usize, child_account: &'b solana_program::account_info::AccountInfo<'t>) {
+                            self.child_accounts[index] = Some(child_account)
+                        }
+
                         fn set_child_pubkey(&mut self, index: usize, pubkey: ElusivOption<solana_program::pubkey::Pubkey>) {
                             self.set_pubkeys(index, &pubkey)
                         }
@@ -245,13 +250,16 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
     // Since all ElusivAccounts are PDAAccounts, they require leading PDAAccountData
     enforce_field(quote! { pda_data : PDAAccountData }, 0, &s.fields);
 
-    for Field {
-        attrs,
-        vis,
-        ident,
-        ty,
-        ..
-    } in &s.fields
+    for (
+        field_index,
+        Field {
+            attrs,
+            vis,
+            ident,
+            ty,
+            ..
+        },
+    ) in s.fields.iter().enumerate()
     {
         let field_ident = ident.clone().unwrap();
         let vis = vis.to_token_stream();
@@ -260,6 +268,7 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
         let mut custom_field = false;
         let mut use_getter = true;
         let mut use_setter = true;
+        let mut growable = false;
 
         if field_ident == "data" {
             panic!("'data' is a reserved keyword, please pick a different field identifier")
@@ -313,6 +322,17 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
                     todo!("deserialize_by_default")
                 }
 
+                // Trailing array that can grow beyond its initial length (see `elusiv_types::accounts::ReallocAccount`)
+                // - the getter and setter are bounds-checked against the live length
+                "growable" => {
+                    assert_eq!(
+                        field_index,
+                        s.fields.len() - 1,
+                        "'growable' is only allowed for the last field"
+                    );
+                    growable = true;
+                }
+
                 // Skips creation of a getter function
                 "no_getter" => {
                     use_getter = false;
@@ -337,10 +357,12 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
                 #field_ident: &'a mut [u8],
             });
 
-            eager_defs.extend(quote! {
-                #doc
-                pub #field_ident: #ty,
-            });
+            if !growable {
+                eager_defs.extend(quote! {
+                    #doc
+                    pub #field_ident: #ty,
+                });
+            }
         }
 
         match ty {
@@ -393,6 +415,73 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
                     }
                 }
             }
+            Type::Array(array) if growable => {
+                if custom_field {
+                    panic!("Custom fields are not allowed with Array-types");
+                }
+
+                let ty = array.elem.clone().into_token_stream();
+                let len = array.len.clone();
+                let element_size = quote! { <#ty as elusiv_types::bytes::BorshSerDeSized>::SIZE };
+                sizes.push(quote! { #element_size * (#len) });
+                growable_field = Some((element_size.clone(), len));
+
+                let len_ident: TokenStream = format!("{}_len", field_ident).parse().unwrap();
+
+                // The trailing field uses all remaining data
+                fields_split.extend(quote! {
+                    let #field_ident = data;
+                });
+
+                eager_defs.extend(quote! {
+                    #doc
+                    pub #field_ident: Vec<#ty>,
+                });
+
+                eager_init.extend(quote! {
+                    let #field_ident = data
+                        .chunks(#element_size)
+                        .map(<#ty as borsh::BorshDeserialize>::try_from_slice)
+                        .collect::<Result<Vec<#ty>, std::io::Error>>()?;
+                });
+
+                fns.extend(quote! {
+                    #doc
+                    #vis fn #len_ident(&self) -> usize {
+                        self.#field_ident.len() / #element_size
+                    }
+                });
+
+                if use_getter {
+                    fns.extend(quote!{
+                        #doc
+                        #vis fn #getter_ident(&self, index: usize) -> Option<#ty> {
+                            if index >= self.#len_ident() {
+                                return None
+                            }
+
+                            let slice = &self.#field_ident[index * #element_size..(index + 1) * #element_size];
+                            Some(<#ty as borsh::BorshDeserialize>::try_from_slice(slice).unwrap())
+                        }
+                    });
+                }
+
+                if use_setter {
+                    fns.extend(quote! {
+                        #doc
+                        #vis fn #setter_ident(&mut self, index: usize, value: &#ty) -> solana_program::entrypoint::ProgramResult {
+                            if index >= self.#len_ident() {
+                                return Err(solana_program::program_error::ProgramError::InvalidArgument)
+                            }
+
+                            let offset = index * #element_size;
+                            let mut slice = &mut self.#field_ident[offset..offset + #element_size];
+                            borsh::BorshSerialize::serialize(value, &mut slice).unwrap();
+                            Ok(())
+                        }
+                    });
+                }
+            }
             Type::Array(array) => {
                 if custom_field {
                     panic!("Custom fields are not allowed with Array-types");
@@ -400,7 +489,7 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
 
                 let ty = array.elem.clone().into_token_stream();
                 let len = array.len.clone();
-                let size = quote! { <#ty as elusiv_types::bytes::BorshSerDeSized>::SIZE * #len };
+                let size = quote! { <#ty as elusiv_types::bytes::BorshSerDeSized>::SIZE * (#len) };
                 sizes.push(size.clone());
 
                 fields_split.extend(quote! {
@@ -433,6 +522,7 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
                     });
                 }
             }
+            _ if growable => panic!("'growable' is only allowed for Array-types"),
             _ => panic!("Invalid field type '{:?}' for '{:?}'", ty, field_ident),
         }
     }
@@ -450,6 +540,34 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
     });
     let anonymous_lifetimes = lifetimes.as_anonymous_lifetimes();
 
+    // Growable accounts accept any size with a whole number of trailing elements
+    let (size_check, eager_size_check) = if let Some((element_size, len)) = &growable_field {
+        impls.extend(quote! {
+            impl < #lifetimes > elusiv_types::accounts::ReallocAccount for #ident < #lifetimes > {
+                const ELEMENT_SIZE: usize = #element_size;
+                const INITIAL_LEN: usize = #len;
+            }
+        });
+
+        (
+            quote! {
+                <Self as elusiv_types::accounts::ReallocAccount>::len_with_size(data.len()).is_none()
+            },
+            quote! {
+                < #ident < #anonymous_lifetimes > as elusiv_types::accounts::ReallocAccount>::len_with_size(data.len()).is_none()
+            },
+        )
+    } else {
+        (
+            quote! {
+                data.len() != <Self as elusiv_types::accounts::SizedAccount>::SIZE
+            },
+            quote! {
+                data.len() != < #ident < #anonymous_lifetimes > as elusiv_types::accounts::SizedAccount>::SIZE
+            },
+        )
+    };
+
     let eager_type = if use_eager_type {
         quote! {
             #[cfg(feature = "elusiv-client")]
@@ -467,7 +585,7 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
             #[cfg(feature = "elusiv-client")]
             impl elusiv_types::accounts::EagerAccountRepr for #eager_ident {
                 fn new(data: Vec<u8>) -> Result<Self, std::io::Error> {
-                    if data.len() != < #ident < #anonymous_lifetimes > as elusiv_types::accounts::SizedAccount>::SIZE {
+                    if #eager_size_check {
                         return Err(std::io::Error::new(std::io::ErrorKind::Other, "Invalid account data len"))
                     }
 
@@ -496,7 +614,7 @@ pub fn impl_elusiv_account(ast: &syn::DeriveInput, attrs: TokenStream) -> TokenS
 
         impl < #lifetimes > elusiv_types::accounts::ProgramAccount < #program_account_lifetime > for #ident < #lifetimes > {
             fn new(data: &'a mut [u8]) -> Result<Self, solana_program::program_error::ProgramError> {
-                if data.len() != <Self as elusiv_types::accounts::SizedAccount>::SIZE {
+                if #size_check {
                     return Err(solana_program::program_error::ProgramError::InvalidAccountData)
                 }
 
@@ -8,6 +8,9 @@ const COMPUTE_UNIT_PADDING: u32 = 20_000;
 const FULL_ROUNDS_CUS: u32 = 15411 + 17740 + 600;
 const PARTIAL_ROUNDS_CUS: u32 = 5200 + 17740 + 600;
 
+/// `sol_poseidon` cost for two inputs (`61 * 2^2 + 542`), the syscall base cost and the field element conversions
+const SYSCALL_HASH_CUS: u32 = 786 + 100 + 5000;
+
 pub fn impl_elusiv_hash_compute_units(attrs: TokenStream) -> TokenStream {
     let attrs = sub_attrs_prepare(attrs.to_string());
     let attrs: Vec<&str> = attrs.split(',').collect();
@@ -40,10 +43,34 @@ pub fn impl_elusiv_hash_compute_units(attrs: TokenStream) -> TokenStream {
         });
     }
 
+    impl_partial_computation(id, rounds, (hashes * 65) as u32, reduction)
+}
+
+pub fn impl_elusiv_hash_syscall_compute_units(attrs: TokenStream) -> TokenStream {
+    let attrs = sub_attrs_prepare(attrs.to_string());
+    let attrs: Vec<&str> = attrs.split(',').collect();
+
+    // Ident
+    let id: TokenStream = attrs[0].parse().unwrap();
+
+    // Number of hashes
+    let hashes: usize = attrs[1].parse().unwrap();
+
+    // A single round computes a full hash
+    let rounds = vec![SYSCALL_HASH_CUS; hashes];
+
+    impl_partial_computation(id, rounds, hashes as u32, None)
+}
+
+fn impl_partial_computation(
+    id: TokenStream,
+    rounds: Vec<u32>,
+    total_rounds: u32,
+    reduction: Option<u32>,
+) -> TokenStream {
     let max_compute_budget = MAX_COMPUTE_UNIT_LIMIT - COMPUTE_UNIT_PADDING - reduction.unwrap_or(0);
     let result = compute_unit_optimization(rounds, max_compute_budget);
 
-    let total_rounds = (hashes * 65) as u32;
     let total_compute_units = result.total_compute_units;
     assert_eq!(result.total_rounds, total_rounds);
 
@@ -8,7 +8,9 @@ mod repeat;
 mod utils;
 
 use elusiv_account::impl_elusiv_account;
-use elusiv_hash_compute_units::impl_elusiv_hash_compute_units;
+use elusiv_hash_compute_units::{
+    impl_elusiv_hash_compute_units, impl_elusiv_hash_syscall_compute_units,
+};
 use parse_tokens::impl_parse_tokens;
 use program_id::{impl_declare_program_id, impl_program_id};
 use repeat::impl_repeat;
@@ -18,7 +20,8 @@ use syn::{parse_macro_input, DeriveInput};
 ///
 /// # Notes
 ///
-/// Automatically also derives [`elusiv_types::PDAAccount`]
+/// - Automatically also derives [`elusiv_types::PDAAccount`]
+/// - A trailing array field with the `#[growable]` attribute can be grown beyond its initial length (see [`elusiv_types::ReallocAccount`])
 #[proc_macro_attribute]
 pub fn elusiv_account(
     args: proc_macro::TokenStream,
@@ -37,6 +40,18 @@ pub fn elusiv_hash_compute_units(input: proc_macro::TokenStream) -> proc_macro::
     impl_elusiv_hash_compute_units(input.into()).into()
 }
 
+/// Creates a struct `Name` that implements `elusiv_computation::PartialComputation` for hashes computed using the `sol_poseidon` syscall
+///
+/// # Usage
+/// - `elusiv_hash_syscall_compute_units!(<name>, <NUMBER_OF_HASHES>)`
+/// - each round computes a full hash
+#[proc_macro]
+pub fn elusiv_hash_syscall_compute_units(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    impl_elusiv_hash_syscall_compute_units(input.into()).into()
+}
+
 /// Repeates an expression count times
 ///
 /// # Usage
@@ -69,7 +84,8 @@ pub fn elusiv_tokens(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// ```
 #[proc_macro]
 pub fn program_id(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    impl_program_id(input.to_string()).into()
+    // Hyphenated names are tokenized with whitespaces (`elusiv - 2`)
+    impl_program_id(input.to_string().replace(' ', "")).into()
 }
 
 /// Parses `Id.toml` and implements [`solana_program::declare_id`]
@@ -87,5 +103,5 @@ pub fn program_id(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// ```
 #[proc_macro]
 pub fn declare_program_id(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    impl_declare_program_id(input.to_string()).into()
+    impl_declare_program_id(input.to_string().replace(' ', "")).into()
 }
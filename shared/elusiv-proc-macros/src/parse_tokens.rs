@@ -56,6 +56,7 @@ pub fn impl_parse_tokens() -> TokenStream {
         let price_base_exp = token.price_base_exp.unwrap_or_default();
         let min = token.min;
         let max = token.max;
+        let active = token.active;
 
         let mint = if cfg!(feature = "devnet") {
             pubkey_bytes(&token.mint_devnet)
@@ -80,6 +81,7 @@ pub fn impl_parse_tokens() -> TokenStream {
                 pyth_usd_price_key: solana_program::pubkey::Pubkey::new_from_array(#pyth_usd_price_key),
                 min: #min,
                 max: #max,
+                active: #active,
             },
         });
     }
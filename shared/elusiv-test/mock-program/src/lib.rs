@@ -1,8 +1,126 @@
-use solana_program::{pubkey::Pubkey, account_info::AccountInfo, entrypoint::ProgramResult};
+#[cfg(feature = "elusiv-cpi")]
+use borsh::BorshDeserialize;
+#[cfg(feature = "elusiv-cpi")]
+use solana_program::{account_info::next_account_info, program_error::ProgramError};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
 
 #[cfg(not(feature = "no-entrypoint"))]
 solana_program::entrypoint!(process_instruction);
 
 pub fn process_instruction(_: &Pubkey, _: &[AccountInfo], _: &[u8]) -> ProgramResult {
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Invokes Elusiv's `StoreBaseCommitment` through [`elusiv::instruction::cpi`], signing for the `sender` PDA with the seed `[seed]`
+///
+/// # Notes
+///
+/// - `data` is `[seed.len() as u8, seed.., store_base_commitment_data..]`.
+/// - The first account is the Elusiv program, followed by the accounts of `StoreBaseCommitment`.
+#[cfg(feature = "elusiv-cpi")]
+pub fn process_store_base_commitment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    use elusiv::instruction::{cpi, ElusivInstruction};
+
+    let (seed_len, data) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if data.len() < *seed_len as usize {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (seed, data) = data.split_at(*seed_len as usize);
+    let (pda, bump) = cpi_signer(program_id, seed);
+
+    let (hash_account_index, hash_account_bump, request) =
+        match ElusivInstruction::try_from_slice(data)? {
+            ElusivInstruction::StoreBaseCommitment {
+                hash_account_index,
+                hash_account_bump,
+                request,
+            } => (hash_account_index, hash_account_bump, request),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+    let account_info_iter = &mut accounts.iter();
+    let _elusiv_program = next_account_info(account_info_iter)?;
+    let sender = next_account_info(account_info_iter)?;
+    if *sender.key != pda {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let sender_account = next_account_info(account_info_iter)?;
+    let fee_payer = next_account_info(account_info_iter)?;
+    let fee_payer_account = next_account_info(account_info_iter)?;
+    let pool = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let fee_collector = next_account_info(account_info_iter)?;
+    let fee_collector_account = next_account_info(account_info_iter)?;
+    let sol_price_account = next_account_info(account_info_iter)?;
+    let token_price_account = next_account_info(account_info_iter)?;
+    let governor = next_account_info(account_info_iter)?;
+    let token_volume = next_account_info(account_info_iter)?;
+    let pool_ledger = next_account_info(account_info_iter)?;
+    let hashing_account = next_account_info(account_info_iter)?;
+    let buffer = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let apa_target_map = next_account_info(account_info_iter)?;
+    let apa_proposal = next_account_info(account_info_iter)?;
+
+    cpi::store_base_commitment(
+        hash_account_index,
+        hash_account_bump,
+        request,
+        sender,
+        sender_account,
+        fee_payer,
+        fee_payer_account,
+        pool,
+        pool_account,
+        fee_collector,
+        fee_collector_account,
+        sol_price_account,
+        token_price_account,
+        governor,
+        token_volume,
+        pool_ledger,
+        hashing_account,
+        buffer,
+        token_program,
+        system_program,
+        apa_target_map,
+        apa_proposal,
+        &[&[seed, &[bump]]],
+    )
+}
+
+/// The PDA of `program_id` signed for by [`process_store_base_commitment`]
+pub fn cpi_signer(program_id: &Pubkey, seed: &[u8]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seed], program_id)
+}
+
+/// Wraps `instruction` into an instruction of the mock program `program_id` (see [`process_store_base_commitment`])
+pub fn cpi_instruction(program_id: Pubkey, seed: &[u8], instruction: Instruction) -> Instruction {
+    let mut data = vec![seed.len() as u8];
+    data.extend(seed);
+    data.extend(instruction.data);
+
+    let mut accounts = vec![AccountMeta::new_readonly(instruction.program_id, false)];
+    accounts.extend(instruction.accounts.into_iter().map(|mut meta| {
+        // The PDA signs in the invocation
+        if meta.pubkey == cpi_signer(&program_id, seed).0 {
+            meta.is_signer = false;
+        }
+        meta
+    }));
+
+    Instruction::new_with_bytes(program_id, &data, accounts)
+}
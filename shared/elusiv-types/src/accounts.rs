@@ -13,6 +13,39 @@ pub trait SizedAccount: Sized {
     const SIZE: usize;
 }
 
+/// A [`SizedAccount`] with a trailing array that can grow beyond its initial length
+///
+/// # Notes
+///
+/// [`SizedAccount::SIZE`] is the size of the account with [`Self::INITIAL_LEN`] trailing elements.
+pub trait ReallocAccount: SizedAccount {
+    /// The size of a single trailing element measured in bytes
+    const ELEMENT_SIZE: usize;
+
+    /// The number of trailing elements of an account with [`SizedAccount::SIZE`]
+    const INITIAL_LEN: usize;
+
+    /// The account size required for `len` trailing elements
+    fn size_with_len(len: usize) -> usize {
+        Self::SIZE - Self::INITIAL_LEN * Self::ELEMENT_SIZE + len * Self::ELEMENT_SIZE
+    }
+
+    /// The number of trailing elements of an account with `data_len` bytes
+    fn len_with_size(data_len: usize) -> Option<usize> {
+        if data_len < Self::SIZE {
+            return None;
+        }
+
+        let fixed_size = Self::SIZE - Self::INITIAL_LEN * Self::ELEMENT_SIZE;
+        let len = (data_len - fixed_size) / Self::ELEMENT_SIZE;
+        if Self::size_with_len(len) != data_len {
+            return None;
+        }
+
+        Some(len)
+    }
+}
+
 /// A [`SizedAccount`] being owned by the program, represented by a mutable byte slice
 pub trait ProgramAccount<'a>: SizedAccount {
     /// Attempts to create a new instance of [`Self`] from a buffer
@@ -105,6 +138,9 @@ pub trait ParentAccount<'a, 'b, 't>: ProgramAccount<'a> {
     /// Sets all child-accounts for this instance
     fn set_child_accounts(parent: &mut Self, child_accounts: Vec<Option<&'b AccountInfo<'t>>>);
 
+    /// Sets the child-account at `index` for this instance (e.g. after enabling it with [`Self::set_child_pubkey`])
+    fn set_child_account(&mut self, index: usize, child_account: &'b AccountInfo<'t>);
+
     /// Sets a specific child-accounts [`Pubkey`] persistently
     fn set_child_pubkey(&mut self, index: usize, pubkey: ElusivOption<Pubkey>);
 
@@ -226,6 +262,9 @@ pub trait PDAAccount {
     /// The PDA associated with no [`Pubkey`] and the [`None`] [`PDAOffset`]
     const FIRST_PDA: (Pubkey, u8);
 
+    /// The layout version of the account (stored in [`PDAAccountData::version`])
+    const VERSION: u8 = 0;
+
     #[cfg(feature = "elusiv-client")]
     const IDENT: &'static str;
 
@@ -306,6 +345,24 @@ pub trait PDAAccount {
         account.data.borrow()[0]
     }
 
+    /// Extracts the layout version from an [`AccountInfo`]
+    ///
+    /// # Note
+    ///
+    /// This requires the account to store [`PDAAccountData`] as the leading data
+    fn get_version(account: &AccountInfo) -> u8 {
+        account.data.borrow()[1]
+    }
+
+    /// Verifies that the account has been migrated to [`Self::VERSION`]
+    fn verify_account_version(account: &AccountInfo) -> ProgramResult {
+        if Self::get_version(account) != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(())
+    }
+
     fn verify_account(account: &AccountInfo, offset: PDAOffset) -> ProgramResult {
         if Self::create(offset, Self::get_bump(account))? != *account.key {
             return Err(ProgramError::InvalidSeeds);
@@ -327,6 +384,16 @@ pub trait PDAAccount {
     }
 }
 
+/// A [`PDAAccount`] storing the layout version, that all program accounts have been migrated to
+///
+/// # Notes
+///
+/// Instructions of an `ElusivInstruction` with the `program_version_account` attribute require this account as the last account.
+pub trait ProgramVersionAccount: PDAAccount {
+    /// Verifies that the migration of all program accounts to [`PDAAccount::VERSION`] has been completed
+    fn verify_program_version(account: &AccountInfo) -> ProgramResult;
+}
+
 pub trait ComputationAccount: PDAAccount {
     fn instruction(&self) -> u32;
     fn round(&self) -> u32;
@@ -337,7 +404,7 @@ pub trait ComputationAccount: PDAAccount {
 pub struct PDAAccountData {
     pub bump_seed: u8,
 
-    /// Used for future account migrations
+    /// The layout version, used for account migrations
     pub version: u8,
 }
 
@@ -30,6 +30,9 @@ pub struct ElusivToken {
 
     /// Inclusive maximum
     pub max: u64,
+
+    /// Whether new deposits are accepted (withdrawals are always possible)
+    pub active: bool,
 }
 
 elusiv_proc_macros::elusiv_tokens!();
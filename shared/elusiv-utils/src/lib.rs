@@ -1,12 +1,12 @@
 pub mod macros;
 
 use elusiv_types::{
-    accounts::{PDAAccount, PDAAccountData, SizedAccount},
+    accounts::{PDAAccount, PDAAccountData, ReallocAccount, SizedAccount},
     PDAOffset,
 };
 use solana_program::{
     account_info::AccountInfo,
-    entrypoint::ProgramResult,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
     program::invoke_signed,
     program_error::ProgramError::{self, InsufficientFunds, InvalidInstructionData},
     pubkey::Pubkey,
@@ -109,6 +109,7 @@ pub fn open_pda_account<'a, T: PDAAccount>(
         pda_account,
         account_size,
         bump,
+        T::VERSION,
         &signers_seeds,
     )
 }
@@ -119,6 +120,7 @@ pub fn create_pda_account<'a>(
     pda_account: &AccountInfo<'a>,
     account_size: usize,
     bump: u8,
+    version: u8,
     signers_seeds: &[&[u8]],
 ) -> ProgramResult {
     // We require the test-unit feature since cfg!(test) does not work in deps
@@ -147,7 +149,7 @@ pub fn create_pda_account<'a>(
     borsh::BorshSerialize::serialize(
         &PDAAccountData {
             bump_seed: bump,
-            version: 0,
+            version,
         },
         &mut data,
     )?;
@@ -155,6 +157,81 @@ pub fn create_pda_account<'a>(
     Ok(())
 }
 
+/// Grows a program owned account towards `account_size` and funds the additional rent
+///
+/// # Notes
+///
+/// - A single call grows the account by at most [`MAX_PERMITTED_DATA_INCREASE`] bytes.
+/// - Intermediate sizes are multiples of `element_size` (relative to the current size).
+/// - Returns the new size of the account.
+pub fn realloc_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    account_size: usize,
+    element_size: usize,
+) -> Result<usize, ProgramError> {
+    let current_size = account.data_len();
+    let new_size = realloc_size(current_size, account_size, element_size)?;
+
+    // We require the test-unit feature since cfg!(test) does not work in deps
+    if cfg!(feature = "test-unit") || new_size == current_size {
+        return Ok(current_size);
+    }
+
+    let lamports_required = Rent::get()?
+        .minimum_balance(new_size)
+        .saturating_sub(account.lamports());
+
+    if lamports_required > 0 {
+        transfer_with_system_program(payer, account, system_program, lamports_required)?;
+    }
+
+    account.realloc(new_size, true)?;
+
+    Ok(new_size)
+}
+
+/// The size of an account of `current_size` bytes after a single [`realloc_account`] call towards `account_size`
+pub fn realloc_size(
+    current_size: usize,
+    account_size: usize,
+    element_size: usize,
+) -> Result<usize, ProgramError> {
+    guard!(account_size >= current_size, InvalidInstructionData);
+    guard!(element_size > 0, InvalidInstructionData);
+
+    let increase = account_size - current_size;
+    guard!(increase % element_size == 0, InvalidInstructionData);
+
+    let max_increase = MAX_PERMITTED_DATA_INCREASE / element_size * element_size;
+    guard!(max_increase > 0, InvalidInstructionData);
+
+    Ok(current_size + std::cmp::min(increase, max_increase))
+}
+
+/// Grows the trailing array of a [`ReallocAccount`] towards `len` elements
+///
+/// # Notes
+///
+/// - New elements are zero-initialized.
+/// - Returns the new number of trailing elements (can be less than `len` due to [`MAX_PERMITTED_DATA_INCREASE`]).
+pub fn realloc_pda_account<'a, T: ReallocAccount>(
+    payer: &AccountInfo<'a>,
+    pda_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    len: usize,
+) -> Result<usize, ProgramError> {
+    let size = realloc_account(
+        payer,
+        pda_account,
+        system_program,
+        T::size_with_len(len),
+        T::ELEMENT_SIZE,
+    )?;
+    T::len_with_size(size).ok_or(InvalidInstructionData)
+}
+
 pub fn transfer_with_system_program<'a>(
     source: &AccountInfo<'a>,
     destination: &AccountInfo<'a>,
@@ -1,22 +1,26 @@
 #!/bin/bash
 
-# Usage: circuit_name out_dir?
+# Usage: circuits_dir circuit_name out_dir?
 
 DEFAULT_DIR=elusiv/src/proof/vkeys
 
-CIRCUIT_NAME=$1
+CIRCUITS_DIR=$1
+CIRCUIT_NAME=$2
 
-if [ -z "$2" ]; then
+if [ -z "$CIRCUITS_DIR" ] || [ -z "$CIRCUIT_NAME" ]; then
+  echo "Usage: $0 circuits_dir circuit_name out_dir?"
+  exit 1
+fi
+
+if [ -z "$3" ]; then
   DIR=$DEFAULT_DIR
 else
-  DIR=$2
+  DIR=$3
 fi
 
-rm -rf temp
-
-git clone --depth=1 --branch=master --single-branch https://github.com/elusiv-privacy/circuits.git ./temp/circuits &&
+# `build.sh` runs in the `build` directory, so all paths are passed as absolute paths
 mkdir -p $DIR/$CIRCUIT_NAME &&
-cp ./temp/circuits/bin/$CIRCUIT_NAME/$CIRCUIT_NAME.vkey $DIR/$CIRCUIT_NAME/elusiv_vkey.bin &&
-cp ./temp/circuits/bin/$CIRCUIT_NAME/verification_key.json $DIR/$CIRCUIT_NAME/verification_key.json
-
-rm -rf temp
\ No newline at end of file
+DIR=$(cd $DIR && pwd) &&
+CIRCUITS_DIR=$(cd $CIRCUITS_DIR && pwd) &&
+cp $CIRCUITS_DIR/bin/$CIRCUIT_NAME/verification_key.json $DIR/$CIRCUIT_NAME/verification_key.json &&
+sh build.sh vkey --input $DIR/$CIRCUIT_NAME/verification_key.json --output $DIR/$CIRCUIT_NAME/elusiv_vkey.bin